@@ -0,0 +1,154 @@
+use gpu::prelude::*;
+use math::prelude::{Matrix, Vector};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Projection {
+    Perspective {
+        fov: f32,
+        aspect: f32,
+        near: f32,
+        far: f32,
+    },
+    Orthographic {
+        width: f32,
+        height: f32,
+        near: f32,
+        far: f32,
+    },
+}
+
+impl Projection {
+    pub fn near(&self) -> f32 {
+        match *self {
+            Self::Perspective { near, .. } => near,
+            Self::Orthographic { near, .. } => near,
+        }
+    }
+
+    pub fn far(&self) -> f32 {
+        match *self {
+            Self::Perspective { far, .. } => far,
+            Self::Orthographic { far, .. } => far,
+        }
+    }
+}
+
+//std140 compatible: every member is a mat4 or vec4
+#[derive(Default, Clone, Copy, Debug)]
+#[repr(C)]
+pub struct CameraUniforms {
+    pub view: Matrix<f32, 4, 4>,
+    pub proj: Matrix<f32, 4, 4>,
+    pub view_proj: Matrix<f32, 4, 4>,
+    pub inverse_view: Matrix<f32, 4, 4>,
+    pub inverse_proj: Matrix<f32, 4, 4>,
+    pub inverse_view_proj: Matrix<f32, 4, 4>,
+    pub position: Vector<f32, 4>,
+    //near, far, 1.0 when reversed-z, unused
+    pub depth: Vector<f32, 4>,
+}
+
+unsafe impl Pod for CameraUniforms {}
+
+//Build pipelines, depth attachments and clears from depth_config()
+//so they agree with the projection.
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    pub transform: Matrix<f32, 4, 4>,
+    pub projection: Projection,
+    pub reversed_z: bool,
+}
+
+impl Camera {
+    pub fn view(&self) -> Matrix<f32, 4, 4> {
+        self.transform.inverse()
+    }
+
+    pub fn proj(&self) -> Matrix<f32, 4, 4> {
+        let mut proj = Matrix::<f32, 4, 4>::identity();
+
+        match self.projection {
+            Projection::Perspective {
+                fov,
+                aspect,
+                near,
+                far,
+            } => {
+                let focal_length = 1.0 / (fov / 2.0).tan();
+
+                proj[0][0] = focal_length / aspect;
+                proj[1][1] = -focal_length;
+                proj[2][3] = -1.0;
+                proj[3][3] = 0.0;
+
+                if self.reversed_z {
+                    proj[2][2] = near / (far - near);
+                    proj[3][2] = (near * far) / (far - near);
+                } else {
+                    proj[2][2] = far / (near - far);
+                    proj[3][2] = (near * far) / (near - far);
+                }
+            }
+            Projection::Orthographic {
+                width,
+                height,
+                near,
+                far,
+            } => {
+                proj[0][0] = 2.0 / width;
+                proj[1][1] = -2.0 / height;
+
+                if self.reversed_z {
+                    proj[2][2] = 1.0 / (far - near);
+                    proj[3][2] = far / (far - near);
+                } else {
+                    proj[2][2] = -1.0 / (far - near);
+                    proj[3][2] = -near / (far - near);
+                }
+            }
+        }
+
+        proj
+    }
+
+    pub fn depth_config(&self) -> DepthConfig {
+        if self.reversed_z {
+            DepthConfig::ReversedZ
+        } else {
+            DepthConfig::Standard
+        }
+    }
+
+    pub fn uniforms(&self) -> CameraUniforms {
+        let view = self.view();
+        let proj = self.proj();
+
+        //Matrix multiplies in the opposite order to glsl, view * proj here is proj * view there
+        let view_proj = view * proj;
+
+        CameraUniforms {
+            view,
+            proj,
+            view_proj,
+            inverse_view: self.transform,
+            inverse_proj: proj.inverse(),
+            inverse_view_proj: view_proj.inverse(),
+            position: Vector::new([
+                self.transform[3][0],
+                self.transform[3][1],
+                self.transform[3][2],
+                1.0,
+            ]),
+            depth: Vector::new([
+                self.projection.near(),
+                self.projection.far(),
+                if self.reversed_z { 1.0 } else { 0.0 },
+                0.0,
+            ]),
+        }
+    }
+
+    pub fn write(&self, uniforms: &mut FrameUniforms<CameraUniforms>) {
+        uniforms.write_current(&self.uniforms());
+    }
+}
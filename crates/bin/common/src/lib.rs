@@ -1,10 +1,14 @@
 #![feature(derive_default_enum)]
 
 pub mod bucket;
+pub mod camera;
 pub mod input;
 pub mod mesh;
+pub mod meshing;
 pub mod octree;
 pub mod render;
+pub mod shadow;
+pub mod stream;
 pub mod bitfield;
 pub mod voxel;
 
@@ -1,3 +1,5 @@
+use gpu::prelude::Pod;
+
 use std::alloc;
 use std::fs;
 use std::io::{self, BufRead};
@@ -6,12 +8,15 @@ use std::ptr;
 use std::slice;
 
 #[derive(Clone, Copy, Debug)]
+#[repr(C)]
 pub struct Vertex {
     position: [f32; 3],
     normal: [f32; 3],
     uvw: [f32; 3],
 }
 
+unsafe impl Pod for Vertex {}
+
 type Index = u16;
 
 pub struct Mesh {
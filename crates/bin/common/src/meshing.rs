@@ -0,0 +1,195 @@
+use crate::mesh::Vertex;
+
+use gpu::prelude::*;
+
+use std::mem;
+
+//must match CHUNK_SIZE in shaders/greedy.comp.glsl
+pub const CHUNK_SIZE: usize = 8;
+//worst case is a checkerboard, half the voxels exposing all six faces
+pub const MAX_QUADS: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE * 3;
+
+const INDIRECT_SIZE: usize = 5 * mem::size_of::<u32>();
+
+pub struct MeshPoolInfo<'a> {
+    pub device: &'a Device,
+    pub slots: u32,
+}
+
+//Fixed size slot per chunk so the mesher can write without allocating.
+//Each slot owns one VkDrawIndexedIndirectCommand, so the whole pool draws
+//with a single indirect call.
+pub struct MeshPool {
+    vertices: Buffer,
+    indices: Buffer,
+    indirect: Buffer,
+    slots: u32,
+}
+
+impl MeshPool {
+    pub fn new(info: MeshPoolInfo<'_>) -> Self {
+        let slots = info.slots as usize;
+
+        let vertices = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::VERTEX | BufferUsage::STORAGE,
+            properties: MemoryProperties::DEVICE_LOCAL,
+            size: slots * MAX_QUADS * 4 * mem::size_of::<Vertex>(),
+            priority: MemoryPriority::Default,
+        });
+
+        let indices = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::INDEX | BufferUsage::STORAGE,
+            properties: MemoryProperties::DEVICE_LOCAL,
+            size: slots * MAX_QUADS * 6 * mem::size_of::<u16>(),
+            priority: MemoryPriority::Default,
+        });
+
+        let mut indirect = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::INDIRECT | BufferUsage::STORAGE,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: slots * INDIRECT_SIZE,
+            priority: MemoryPriority::Default,
+        });
+
+        indirect.write(BufferWrite {
+            offset: 0,
+            data: &vec![0u32; slots * INDIRECT_SIZE / mem::size_of::<u32>()],
+        });
+
+        Self {
+            vertices,
+            indices,
+            indirect,
+            slots: info.slots,
+        }
+    }
+
+    pub fn clear(&mut self, slot: u32) {
+        self.indirect.write(BufferWrite {
+            offset: (slot as usize * INDIRECT_SIZE) as _,
+            data: &[0u32; INDIRECT_SIZE / mem::size_of::<u32>()],
+        });
+    }
+
+    pub fn draw(&self, commands: &mut Commands<'_>) {
+        commands.bind_vertex_buffers(0, &[&self.vertices], &[0]);
+        commands.bind_index_buffer(&self.indices, 0);
+        commands.draw_indexed_indirect(&self.indirect, 0, self.slots, INDIRECT_SIZE as _);
+    }
+
+    pub fn vertices(&self) -> &Buffer {
+        &self.vertices
+    }
+
+    pub fn indices(&self) -> &Buffer {
+        &self.indices
+    }
+
+    pub fn indirect(&self) -> &Buffer {
+        &self.indirect
+    }
+
+    pub fn slots(&self) -> u32 {
+        self.slots
+    }
+}
+
+pub struct MesherInfo<'a> {
+    pub device: &'a Device,
+    pub pool: &'a MeshPool,
+}
+
+pub struct Mesher {
+    pipeline: Pipeline,
+}
+
+impl Mesher {
+    pub fn new(info: MesherInfo<'_>) -> Self {
+        let shader = Shader::new(ShaderInfo {
+            device: info.device,
+            entry: "main",
+            input: ShaderInput::Embedded {
+                name: "greedy.comp",
+                source: include_str!("shaders/greedy.comp.glsl"),
+            },
+        });
+
+        let layout = (0..4)
+            .map(|binding| Descriptor {
+                binding,
+                ty: DescriptorType::StorageBuffer,
+                count: 1,
+                stage: ShaderStage::COMPUTE,
+            })
+            .collect::<Vec<_>>();
+
+        let pipeline = Pipeline::new_compute_pipeline(ComputePipelineInfo {
+            device: info.device,
+            descriptor_set_count: info.pool.slots(),
+            shader: &shader,
+            layout: &layout,
+            push_constants: &[PushConstantRange {
+                stage: ShaderStage::COMPUTE,
+                offset: 0,
+                size: mem::size_of::<u32>() as _,
+            }],
+            material_table: None,
+            immutable_samplers: &[],
+            cache: None,
+        });
+
+        Self { pipeline }
+    }
+
+    //occupancy holds one u32 voxel id per voxel (0 is empty),
+    //indexed x + y * CHUNK_SIZE + z * CHUNK_SIZE * CHUNK_SIZE
+    pub fn bind(&mut self, slot: u32, occupancy: &Buffer, pool: &MeshPool) {
+        let slots = pool.slots() as usize;
+
+        let storage = |binding, buffer, range| Binding::Buffer {
+            binding,
+            ty: DescriptorType::StorageBuffer,
+            offset: 0,
+            range,
+            buffer,
+        };
+
+        self.pipeline.bind(
+            slot,
+            &[
+                storage(
+                    0,
+                    occupancy,
+                    CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE * mem::size_of::<u32>(),
+                ),
+                storage(
+                    1,
+                    pool.vertices(),
+                    slots * MAX_QUADS * 4 * mem::size_of::<Vertex>(),
+                ),
+                storage(2, pool.indices(), slots * MAX_QUADS * 6 * mem::size_of::<u16>()),
+                storage(3, pool.indirect(), slots * INDIRECT_SIZE),
+            ],
+        );
+    }
+
+    pub fn record(&self, commands: &mut Commands<'_>, slot: u32) {
+        commands.bind_pipeline(slot, &self.pipeline);
+        commands.push_constants(&self.pipeline, ShaderStage::COMPUTE, 0, &[slot]);
+        commands.dispatch(1, 1, 1);
+
+        commands.pipeline_barrier(
+            PipelineStage::COMPUTE_SHADER,
+            PipelineStage::DRAW_INDIRECT | PipelineStage::VERTEX_INPUT,
+            &[Barrier::Memory {
+                src_access: Access::SHADER_WRITE,
+                dst_access: Access::INDIRECT_COMMAND_READ
+                    | Access::INDEX_READ
+                    | Access::VERTEX_ATTRIBUTE_READ,
+            }],
+        );
+    }
+}
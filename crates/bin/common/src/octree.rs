@@ -1,5 +1,6 @@
 use crate::voxel::Voxel;
 
+use gpu::prelude::Pod;
 use math::prelude::Vector;
 
 use std::marker;
@@ -289,6 +290,32 @@ impl Node {
     }
 }
 
+//Node as laid out in the octree buffer. Node itself holds an enum, which may not be
+//read back from arbitrary bytes, so uploads go through this instead.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct GpuNode {
+    child: u32,
+    valid: u32,
+    morton: u64,
+    voxel: u32,
+    padding: u32,
+}
+
+unsafe impl Pod for GpuNode {}
+
+impl From<Node> for GpuNode {
+    fn from(node: Node) -> Self {
+        Self {
+            child: node.child,
+            valid: node.valid,
+            morton: node.morton,
+            voxel: node.voxel.id as u32,
+            padding: 0,
+        }
+    }
+}
+
 impl Default for Node {
     fn default() -> Self {
         Node {
@@ -1,6 +1,6 @@
 use crate::bucket::Bucket;
 use crate::mesh::{Mesh, Vertex};
-use crate::octree::{Node, Octree, SparseOctree};
+use crate::octree::{GpuNode, Node, Octree, SparseOctree};
 use crate::voxel::Id::*;
 use crate::voxel::Voxel;
 
@@ -25,23 +25,32 @@ use raw_window_handle::HasRawWindowHandle;
 pub const CHUNK_SIZE: usize = 8;
 //temporary for here for now.
 #[derive(Default, Clone, Copy, Debug)]
+#[repr(C)]
 pub struct Camera {
     pub view: Matrix<f32, 4, 4>,
     pub proj: Matrix<f32, 4, 4>,
     pub model: Matrix<f32, 4, 4>,
 }
 
+unsafe impl Pod for Camera {}
+
 #[derive(Default, Clone, Copy)]
+#[repr(C)]
 pub struct ObjectData {
     pub model: Matrix<f32, 4, 4>,
 }
 
+unsafe impl Pod for ObjectData {}
+
 #[derive(Default, Clone, Copy)]
+#[repr(C)]
 pub struct RenderSettings {
     pub resolution: Vector<u32, 4>,
     pub render_distance: u32,
 }
 
+unsafe impl Pod for RenderSettings {}
+
 pub struct RendererInfo<'a> {
     pub window: &'a dyn HasRawWindowHandle,
     pub render_distance: u32,
@@ -151,7 +160,7 @@ impl Vulkan {
 
         let mut device = Device::choose_best(DeviceInfo {
             context: &context,
-            surface: &surface,
+            surface: Some(&surface),
         });
 
         let mut staging_buffer = Buffer::new(BufferInfo {
@@ -159,6 +168,7 @@ impl Vulkan {
             usage: BufferUsage::TRANSFER_SRC,
             properties: MemoryProperties::HOST_VISIBLE,
             size: BIG_BUFFER,
+            priority: MemoryPriority::Default,
         });
 
         let mut data_buffer = Buffer::new(BufferInfo {
@@ -169,6 +179,7 @@ impl Vulkan {
                 | BufferUsage::UNIFORM,
             properties: MemoryProperties::DEVICE_LOCAL,
             size: SMALL_BUFFER,
+            priority: MemoryPriority::Default,
         });
 
         let instance_buffer = Buffer::new(BufferInfo {
@@ -176,6 +187,7 @@ impl Vulkan {
             usage: BufferUsage::TRANSFER_DST | BufferUsage::VERTEX,
             properties: MemoryProperties::DEVICE_LOCAL,
             size: BIG_BUFFER,
+            priority: MemoryPriority::Default,
         });
 
         let octree_buffer = Buffer::new(BufferInfo {
@@ -183,6 +195,7 @@ impl Vulkan {
             usage: BufferUsage::TRANSFER_DST | BufferUsage::STORAGE,
             properties: MemoryProperties::DEVICE_LOCAL,
             size: BIG_BUFFER,
+            priority: MemoryPriority::Default,
         });
 
         let mut look_up_table = Image::new(ImageInfo {
@@ -191,6 +204,8 @@ impl Vulkan {
             usage: ImageUsage::TRANSFER_DST | ImageUsage::SAMPLED,
             format: Format::Rgba8Srgb,
             extent: (256, 256, 1),
+            samples: SampleCount::One,
+            priority: MemoryPriority::Default,
         });
 
         use image::io::Reader as ImageReader;
@@ -392,6 +407,8 @@ impl Renderer for Vulkan {
                     b_dst.partial_cmp(&a_dst).unwrap()
                 });
 
+                let instance_data = instance_data.iter().map(|v| **v).collect::<Vec<_>>();
+
                 self.staging_buffer.write(BufferWrite {
                     offset: 0,
                     data: &instance_data[..],
@@ -461,11 +478,18 @@ impl Renderer for Vulkan {
 
 
 
-            let octree_bytes = 2 * mem::size_of::<u32>()
-                + batch.objects[0].data.nodes().len() * mem::size_of::<crate::octree::Node>();
+            let nodes = batch.objects[0]
+                .data
+                .nodes()
+                .iter()
+                .copied()
+                .map(GpuNode::from)
+                .collect::<Vec<_>>();
+
+            let octree_bytes = 2 * mem::size_of::<u32>() + nodes.len() * mem::size_of::<GpuNode>();
             self.staging_buffer.write(BufferWrite {
                 offset: (2 * mem::size_of::<u32>()) as u64,
-                data: batch.objects[0].data.nodes(),
+                data: &nodes[..],
             });
 
             self.device.copy_buffer_to_buffer(BufferCopy {
@@ -685,7 +709,9 @@ impl Renderer for Vulkan {
                 depth_stencil_clear_value: Some((1.0, 0)),
             };
 
-            commands.begin_render_pass(render_pass_begin_info);
+            commands
+                .begin_render_pass(render_pass_begin_info)
+                .expect("failed to begin render pass");
 
             commands.bind_pipeline(image_index, &render_data.graphics_prepass_pipeline);
 
@@ -750,7 +776,9 @@ impl Renderer for Vulkan {
                     depth_stencil_clear_value: None,
                 };
 
-                commands.begin_render_pass(render_pass_begin_info);
+                commands
+                    .begin_render_pass(render_pass_begin_info)
+                    .expect("failed to begin render pass");
 
                 commands.bind_pipeline(image_index, &render_data.postfx_pipeline);
 
@@ -777,7 +805,9 @@ impl Renderer for Vulkan {
                     depth_stencil_clear_value: Some((1.0, 0)),
                 };
 
-                commands.begin_render_pass(render_pass_begin_info);
+                commands
+                    .begin_render_pass(render_pass_begin_info)
+                    .expect("failed to begin render pass");
 
                 commands.bind_pipeline(image_index, &render_data.present_pipeline);
 
@@ -816,6 +846,13 @@ impl VulkanRenderData {
             device: &vk.device,
             surface: &vk.surface,
             old: old.map(|old| old.swapchain),
+            usage: ImageUsage::COLOR,
+            //the present pass below is Bgra8Srgb
+            encoding: OutputEncoding::Hardware,
+            vsync: VsyncMode::Off,
+            extent: Some((vk.settings.resolution[0], vk.settings.resolution[1])),
+            hdr_metadata: None,
+            dedicated_sync: false,
         });
 
         let swapchain_images = swapchain.images();
@@ -831,6 +868,8 @@ impl VulkanRenderData {
             usage: ImageUsage::DEPTH_STENCIL | ImageUsage::SAMPLED,
             format: Format::D32Sfloat,
             extent: graphics_extent,
+            samples: SampleCount::One,
+            priority: MemoryPriority::Default,
         });
 
         let graphics_color = (0..swapchain_images.len())
@@ -841,6 +880,8 @@ impl VulkanRenderData {
                     usage: ImageUsage::COLOR | ImageUsage::STORAGE,
                     format: Format::Rgba32Sfloat,
                     extent: graphics_extent,
+                    samples: SampleCount::One,
+                    priority: MemoryPriority::Default,
                 })
             })
         .collect::<Vec<_>>();
@@ -853,6 +894,8 @@ impl VulkanRenderData {
                     usage: ImageUsage::COLOR | ImageUsage::STORAGE,
                     format: Format::Rgba32Sfloat,
                     extent: graphics_extent,
+                    samples: SampleCount::One,
+                    priority: MemoryPriority::Default,
                 })
             })
         .collect::<Vec<_>>();
@@ -865,6 +908,8 @@ impl VulkanRenderData {
                     usage: ImageUsage::COLOR | ImageUsage::STORAGE,
                     format: Format::Rgba32Sfloat,
                     extent: graphics_extent,
+                    samples: SampleCount::One,
+                    priority: MemoryPriority::Default,
                 })
             })
         .collect::<Vec<_>>();
@@ -875,6 +920,7 @@ impl VulkanRenderData {
             attachments: &[
                 Attachment {
                     format: Format::Rgba32Sfloat,
+                    samples: SampleCount::One,
                     load_op: AttachmentLoadOp::Clear,
                     store_op: AttachmentStoreOp::Store,
                     initial_layout: ImageLayout::Undefined,
@@ -884,6 +930,7 @@ impl VulkanRenderData {
                 },
                 Attachment {
                     format: Format::Rgba32Sfloat,
+                    samples: SampleCount::One,
                     load_op: AttachmentLoadOp::Clear,
                     store_op: AttachmentStoreOp::Store,
                     initial_layout: ImageLayout::Undefined,
@@ -893,6 +940,7 @@ impl VulkanRenderData {
                 },
                 Attachment {
                     format: Format::D32Sfloat,
+                    samples: SampleCount::One,
                     load_op: AttachmentLoadOp::Clear,
                     store_op: AttachmentStoreOp::Store,
                     initial_layout: ImageLayout::Undefined,
@@ -930,6 +978,7 @@ impl VulkanRenderData {
             device: &vk.device,
             attachments: &[Attachment {
                 format: Format::Rgba32Sfloat,
+                samples: SampleCount::One,
                 load_op: AttachmentLoadOp::Clear,
                 store_op: AttachmentStoreOp::Store,
                 initial_layout: ImageLayout::Undefined,
@@ -952,6 +1001,7 @@ impl VulkanRenderData {
             device: &vk.device,
             attachments: &[Attachment {
                 format: Format::Bgra8Srgb,
+                samples: SampleCount::One,
                 load_op: AttachmentLoadOp::Clear,
                 store_op: AttachmentStoreOp::Store,
                 initial_layout: ImageLayout::Undefined,
@@ -978,6 +1028,7 @@ impl VulkanRenderData {
                     render_pass: &graphics_render_pass,
                     extent: graphics_extent,
                     attachments: &[&graphics_color[i], &graphics_occlusion[i], &depth],
+                    generation: Some(swapchain.generation()),
                 })
             })
         .collect::<Vec<_>>();
@@ -989,6 +1040,7 @@ impl VulkanRenderData {
                     render_pass: &postfx_render_pass,
                     extent: graphics_extent,
                     attachments: &[&postfx_color[i]],
+                    generation: Some(swapchain.generation()),
                 })
             })
         .collect::<Vec<_>>();
@@ -1000,6 +1052,7 @@ impl VulkanRenderData {
                     render_pass: &present_render_pass,
                     extent: present_extent,
                     attachments: &[&swapchain_images[i]],
+                    generation: Some(swapchain.generation()),
                 })
             })
         .collect::<Vec<_>>();
@@ -1007,7 +1060,8 @@ impl VulkanRenderData {
         //PIPELINES
         let graphics_prepass_pipeline = Pipeline::new_graphics_pipeline(GraphicsPipelineInfo {
             device: &vk.device,
-            render_pass: &graphics_render_pass,
+            render_pass: Some(&graphics_render_pass),
+            rendering: None,
             descriptor_set_count: swapchain_images.len() as _,
             color_count: 0,
             subpass: 0,
@@ -1019,7 +1073,15 @@ impl VulkanRenderData {
                 test: true,
                 write: true,
                 compare_op: CompareOp::Less,
+                stencil: None,
             },
+            blend_constants: BlendConstants::Static([0.0, 0.0, 0.0, 0.0]),
+            color_blend: ColorBlend::Alpha,
+            color_write_mask: ColorComponents::default(),
+            samples: SampleCount::One,
+            scissor: Scissor::Static,
+            primitive: Primitive::Triangles,
+            depth_range: DepthRange::default(),
             vertex_input: &[
                 VertexInput {
                     binding: 0,
@@ -1072,11 +1134,16 @@ impl VulkanRenderData {
                         stage: ShaderStage::FRAGMENT,
                     },
                     ],
+            push_constants: &[],
+            material_table: None,
+            immutable_samplers: &[],
+            cache: None,
         });
 
         let graphics_raycast_pipeline = Pipeline::new_graphics_pipeline(GraphicsPipelineInfo {
             device: &vk.device,
-            render_pass: &graphics_render_pass,
+            render_pass: Some(&graphics_render_pass),
+            rendering: None,
             descriptor_set_count: swapchain_images.len() as _,
             color_count: 2,
             subpass: 1,
@@ -1088,7 +1155,15 @@ impl VulkanRenderData {
                 test: true,
                 write: false,
                 compare_op: CompareOp::LessOrEqual,
+                stencil: None,
             },
+            blend_constants: BlendConstants::Static([0.0, 0.0, 0.0, 0.0]),
+            color_blend: ColorBlend::Alpha,
+            color_write_mask: ColorComponents::default(),
+            samples: SampleCount::One,
+            scissor: Scissor::Static,
+            primitive: Primitive::Triangles,
+            depth_range: DepthRange::default(),
             vertex_input: &[
                 VertexInput {
                     binding: 0,
@@ -1141,11 +1216,16 @@ impl VulkanRenderData {
                         stage: ShaderStage::FRAGMENT,
                     },
                     ],
+            push_constants: &[],
+            material_table: None,
+            immutable_samplers: &[],
+            cache: None,
         });
 
         let postfx_pipeline = Pipeline::new_graphics_pipeline(GraphicsPipelineInfo {
             device: &vk.device,
-            render_pass: &postfx_render_pass,
+            render_pass: Some(&postfx_render_pass),
+            rendering: None,
             descriptor_set_count: swapchain_images.len() as _,
             color_count: 1,
             subpass: 0,
@@ -1157,7 +1237,15 @@ impl VulkanRenderData {
                 test: false,
                 write: false,
                 compare_op: CompareOp::Always,
+                stencil: None,
             },
+            blend_constants: BlendConstants::Static([0.0, 0.0, 0.0, 0.0]),
+            color_blend: ColorBlend::Alpha,
+            color_write_mask: ColorComponents::default(),
+            samples: SampleCount::One,
+            scissor: Scissor::Static,
+            primitive: Primitive::Triangles,
+            depth_range: DepthRange::default(),
             vertex_input: &[],
             layout: &[
                 Descriptor {
@@ -1185,11 +1273,16 @@ impl VulkanRenderData {
                     stage: ShaderStage::FRAGMENT,
                 },
                 ],
+            push_constants: &[],
+            material_table: None,
+            immutable_samplers: &[],
+            cache: None,
         });
 
         let present_pipeline = Pipeline::new_graphics_pipeline(GraphicsPipelineInfo {
             device: &vk.device,
-            render_pass: &present_render_pass,
+            render_pass: Some(&present_render_pass),
+            rendering: None,
             descriptor_set_count: swapchain_images.len() as _,
             color_count: 1,
             subpass: 0,
@@ -1201,7 +1294,15 @@ impl VulkanRenderData {
                 test: false,
                 write: false,
                 compare_op: CompareOp::Always,
+                stencil: None,
             },
+            blend_constants: BlendConstants::Static([0.0, 0.0, 0.0, 0.0]),
+            color_blend: ColorBlend::Alpha,
+            color_write_mask: ColorComponents::default(),
+            samples: SampleCount::One,
+            scissor: Scissor::Static,
+            primitive: Primitive::Triangles,
+            depth_range: DepthRange::default(),
             vertex_input: &[],
             layout: &[
                 Descriptor {
@@ -1223,6 +1324,10 @@ impl VulkanRenderData {
                     stage: ShaderStage::FRAGMENT,
                 },
             ],
+            push_constants: &[],
+            material_table: None,
+            immutable_samplers: &[],
+            cache: None,
         });
 
         Self {
@@ -1326,12 +1431,14 @@ let jfai_buffer_binding = vk::DescriptorSetLayoutBinding {
 };
 
 let jfa_descriptor_set_layout_create_info = vk::DescriptorSetLayoutCreateInfo {
+    flags: 0,
     bindings: &[
         uniform_buffer_binding,
         octree_buffer_binding,
         //      cubelet_sdf_result_binding,
         jfai_buffer_binding,
     ],
+    binding_flags: &[],
 };
 
     let jfa_descriptor_set_layout =
@@ -1359,6 +1466,7 @@ let jfai_buffer_pool_size = vk::DescriptorPoolSize {
 };
 
 let jfa_descriptor_pool_create_info = vk::DescriptorPoolCreateInfo {
+    flags: 0,
     max_sets: 1,
     pool_sizes: &[
         uniform_buffer_pool_size,
@@ -1375,6 +1483,7 @@ vk::DescriptorPool::new(device.clone(), jfa_descriptor_pool_create_info)
     let descriptor_set_allocate_info = vk::DescriptorSetAllocateInfo {
         descriptor_pool: &jfa_descriptor_pool,
         set_layouts: &[&jfa_descriptor_set_layout],
+        variable_descriptor_counts: &[],
     };
 
     let jfa_descriptor_sets =
@@ -1460,6 +1569,7 @@ impl VulkanRenderData {
     ) -> Self {
         //DEPTH
         let depth_create_info = vk::ImageCreateInfo {
+            flags: 0,
             image_type: vk::ImageType::TwoDim,
             format: vk::Format::D32Sfloat,
             extent: (render_info.extent.0, render_info.extent.1, 1),
@@ -1476,6 +1586,8 @@ impl VulkanRenderData {
 
         let depth_memory_allocate_info = vk::MemoryAllocateInfo {
             property_flags: vk::MEMORY_PROPERTY_DEVICE_LOCAL,
+            flags: 0,
+            priority: None,
         };
 
         let depth_memory = vk::Memory::allocate(
@@ -1552,7 +1664,7 @@ impl VulkanRenderData {
             composite_alpha: vk::CompositeAlpha::Opaque,
             present_mode: render_info.present_mode,
             clipped: true,
-            old_swapchain,
+            old_swapchain: old_swapchain.as_ref(),
         };
 
         let mut swapchain = vk::Swapchain::new(device.clone(), swapchain_create_info)
@@ -1591,6 +1703,7 @@ impl VulkanRenderData {
         let mut distance = (0..swapchain_images.len())
             .map(|_| {
                 let distance_create_info = vk::ImageCreateInfo {
+                    flags: 0,
                     image_type: vk::ImageType::TwoDim,
                     format: vk::Format::Rgba32Sfloat,
                     extent: (
@@ -1616,6 +1729,8 @@ impl VulkanRenderData {
             .map(|distance| {
                 let distance_memory_allocate_info = vk::MemoryAllocateInfo {
                     property_flags: vk::MEMORY_PROPERTY_DEVICE_LOCAL,
+                    flags: 0,
+                    priority: None,
                 };
 
                 let distance_memory = vk::Memory::allocate(
@@ -1691,6 +1806,7 @@ impl VulkanRenderData {
         let mut graphics_color = (0..swapchain_images.len())
             .map(|_| {
                 let graphics_color_create_info = vk::ImageCreateInfo {
+                    flags: 0,
                     image_type: vk::ImageType::TwoDim,
                     format: vk::Format::Rgba32Sfloat,
                     extent: (
@@ -1716,6 +1832,8 @@ impl VulkanRenderData {
             .map(|graphics_color| {
                 let graphics_color_memory_allocate_info = vk::MemoryAllocateInfo {
                     property_flags: vk::MEMORY_PROPERTY_DEVICE_LOCAL,
+                    flags: 0,
+                    priority: None,
                 };
 
                 let graphics_color_memory = vk::Memory::allocate(
@@ -1790,6 +1908,7 @@ impl VulkanRenderData {
         let mut graphics_occlusion = (0..swapchain_images.len())
             .map(|_| {
                 let graphics_occlusion_create_info = vk::ImageCreateInfo {
+                    flags: 0,
                     image_type: vk::ImageType::TwoDim,
                     format: vk::Format::Rgba32Sfloat,
                     extent: (
@@ -1815,6 +1934,8 @@ impl VulkanRenderData {
             .map(|graphics_occlusion| {
                 let graphics_occlusion_memory_allocate_info = vk::MemoryAllocateInfo {
                     property_flags: vk::MEMORY_PROPERTY_DEVICE_LOCAL,
+                    flags: 0,
+                    priority: None,
                 };
 
                 let graphics_occlusion_memory = vk::Memory::allocate(
@@ -1908,11 +2029,13 @@ impl VulkanRenderData {
         };
 
         let descriptor_set_layout_create_info = vk::DescriptorSetLayoutCreateInfo {
+            flags: 0,
             bindings: &[
                 camera_buffer_binding,
                 settings_buffer_binding,
                 octree_buffer_binding,
             ],
+            binding_flags: &[],
         };
 
         let graphics_descriptor_set_layout =
@@ -1935,6 +2058,7 @@ impl VulkanRenderData {
         };
 
         let descriptor_pool_create_info = vk::DescriptorPoolCreateInfo {
+            flags: 0,
             max_sets: swapchain_images.len() as _,
             pool_sizes: &[
                 camera_buffer_pool_size,
@@ -1954,6 +2078,7 @@ impl VulkanRenderData {
         let descriptor_set_allocate_info = vk::DescriptorSetAllocateInfo {
             descriptor_pool: &graphics_descriptor_pool,
             set_layouts: &set_layouts,
+            variable_descriptor_counts: &[],
         };
 
         let graphics_descriptor_sets =
@@ -2119,6 +2244,7 @@ impl VulkanRenderData {
                 uv_attribute,
                 chunk_position_attribute,
             ],
+            binding_divisors: &[],
         };
 
         let graphics_pipeline = create_graphics_pipeline(
@@ -2161,6 +2287,7 @@ impl VulkanRenderData {
         let mut postfx_color = (0..swapchain_images.len())
             .map(|_| {
                 let postfx_color_create_info = vk::ImageCreateInfo {
+                    flags: 0,
                     image_type: vk::ImageType::TwoDim,
                     format: vk::Format::Rgba32Sfloat,
                     extent: (
@@ -2186,6 +2313,8 @@ impl VulkanRenderData {
             .map(|postfx_color| {
                 let postfx_color_memory_allocate_info = vk::MemoryAllocateInfo {
                     property_flags: vk::MEMORY_PROPERTY_DEVICE_LOCAL,
+                    flags: 0,
+                    priority: None,
                 };
 
                 let postfx_color_memory = vk::Memory::allocate(
@@ -2286,12 +2415,14 @@ impl VulkanRenderData {
         };
 
         let descriptor_set_layout_create_info = vk::DescriptorSetLayoutCreateInfo {
+            flags: 0,
             bindings: &[
                 settings_buffer_binding,
                 graphics_color_binding,
                 graphics_occlusion_binding,
                 distance_binding,
             ],
+            binding_flags: &[],
         };
 
         let postfx_descriptor_set_layout =
@@ -2319,6 +2450,7 @@ impl VulkanRenderData {
         };
 
         let descriptor_pool_create_info = vk::DescriptorPoolCreateInfo {
+            flags: 0,
             max_sets: swapchain_images.len() as _,
             pool_sizes: &[
                 settings_buffer_pool_size,
@@ -2339,6 +2471,7 @@ impl VulkanRenderData {
         let descriptor_set_allocate_info = vk::DescriptorSetAllocateInfo {
             descriptor_pool: &postfx_descriptor_pool,
             set_layouts: &set_layouts,
+            variable_descriptor_counts: &[],
         };
 
         let postfx_descriptor_sets =
@@ -2402,6 +2535,7 @@ impl VulkanRenderData {
         let postfx_vertex_input_info = vk::PipelineVertexInputStateCreateInfo {
             bindings: &[],
             attributes: &[],
+            binding_divisors: &[],
         };
 
         let postfx_pipeline = create_graphics_pipeline(
@@ -2461,12 +2595,14 @@ impl VulkanRenderData {
         };
 
         let descriptor_set_layout_create_info = vk::DescriptorSetLayoutCreateInfo {
+            flags: 0,
             bindings: &[
                 settings_buffer_binding,
                 postfx_color_binding,
                 look_up_table_binding,
                 distance_binding,
             ],
+            binding_flags: &[],
         };
 
         let present_descriptor_set_layout =
@@ -2494,6 +2630,7 @@ impl VulkanRenderData {
         };
 
         let descriptor_pool_create_info = vk::DescriptorPoolCreateInfo {
+            flags: 0,
             max_sets: swapchain_images.len() as _,
             pool_sizes: &[
                 settings_buffer_pool_size,
@@ -2514,6 +2651,7 @@ impl VulkanRenderData {
         let descriptor_set_allocate_info = vk::DescriptorSetAllocateInfo {
             descriptor_pool: &present_descriptor_pool,
             set_layouts: &set_layouts,
+            variable_descriptor_counts: &[],
         };
 
         let present_descriptor_sets =
@@ -2590,6 +2728,7 @@ impl VulkanRenderData {
         let present_vertex_input_info = vk::PipelineVertexInputStateCreateInfo {
             bindings: &[],
             attributes: &[],
+            binding_divisors: &[],
         };
 
         let present_pipeline = create_graphics_pipeline(
@@ -0,0 +1,174 @@
+#version 450
+
+#define CHUNK_SIZE 8
+#define MAX_QUADS (CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE * 3)
+#define VERTEX_FLOATS 9
+
+//one invocation per face direction and slice
+layout(local_size_x = 6 * CHUNK_SIZE) in;
+
+layout(binding = 0) readonly buffer Occupancy {
+	uint voxels[];
+};
+
+layout(binding = 1) writeonly buffer Vertices {
+	float vertices[];
+};
+
+//u16 indices packed in pairs
+layout(binding = 2) writeonly buffer Indices {
+	uint indices[];
+};
+
+layout(binding = 3) writeonly buffer Indirect {
+	uint commands[];
+};
+
+layout(push_constant) uniform Chunk {
+	uint slot;
+} chunk;
+
+shared uint quad_count;
+
+uint voxel(ivec3 p) {
+	if (any(lessThan(p, ivec3(0))) || any(greaterThanEqual(p, ivec3(CHUNK_SIZE)))) {
+		return 0;
+	}
+	return voxels[p.x + p.y * CHUNK_SIZE + p.z * CHUNK_SIZE * CHUNK_SIZE];
+}
+
+void emit(uint d, bool positive, uint slice, uint u, uint v, uint w, uint h, uint id) {
+	uint quad = atomicAdd(quad_count, 1);
+
+	uint ud = (d + 1) % 3;
+	uint vd = (d + 2) % 3;
+
+	vec3 normal = vec3(0);
+	normal[d] = positive ? 1.0 : -1.0;
+
+	vec2 corners[4] = vec2[](vec2(0, 0), vec2(w, 0), vec2(w, h), vec2(0, h));
+
+	uint base_vertex = chunk.slot * MAX_QUADS * 4 + quad * 4;
+
+	for (uint c = 0; c < 4; c++) {
+		vec3 position;
+		position[d] = float(slice + (positive ? 1 : 0));
+		position[ud] = float(u) + corners[c].x;
+		position[vd] = float(v) + corners[c].y;
+
+		uint o = (base_vertex + c) * VERTEX_FLOATS;
+
+		vertices[o + 0] = position.x;
+		vertices[o + 1] = position.y;
+		vertices[o + 2] = position.z;
+		vertices[o + 3] = normal.x;
+		vertices[o + 4] = normal.y;
+		vertices[o + 5] = normal.z;
+		vertices[o + 6] = corners[c].x;
+		vertices[o + 7] = corners[c].y;
+		vertices[o + 8] = float(id);
+	}
+
+	uint local = quad * 4;
+	uint base_index = (chunk.slot * MAX_QUADS * 6 + quad * 6) / 2;
+
+	if (positive) {
+		indices[base_index + 0] = local | ((local + 1) << 16);
+		indices[base_index + 1] = (local + 2) | ((local + 2) << 16);
+		indices[base_index + 2] = (local + 3) | (local << 16);
+	} else {
+		indices[base_index + 0] = local | ((local + 3) << 16);
+		indices[base_index + 1] = (local + 2) | ((local + 2) << 16);
+		indices[base_index + 2] = (local + 1) | (local << 16);
+	}
+}
+
+void main() {
+	uint invocation = gl_LocalInvocationID.x;
+
+	if (invocation == 0) {
+		quad_count = 0;
+	}
+
+	barrier();
+
+	uint face = invocation / CHUNK_SIZE;
+	uint slice = invocation % CHUNK_SIZE;
+
+	uint d = face / 2;
+	bool positive = face % 2 == 1;
+
+	uint ud = (d + 1) % 3;
+	uint vd = (d + 2) % 3;
+
+	uint mask[CHUNK_SIZE * CHUNK_SIZE];
+
+	for (uint v = 0; v < CHUNK_SIZE; v++) {
+		for (uint u = 0; u < CHUNK_SIZE; u++) {
+			ivec3 p;
+			p[d] = int(slice);
+			p[ud] = int(u);
+			p[vd] = int(v);
+
+			ivec3 n = p;
+			n[d] += positive ? 1 : -1;
+
+			uint here = voxel(p);
+
+			mask[u + v * CHUNK_SIZE] = here != 0 && voxel(n) == 0 ? here : 0;
+		}
+	}
+
+	for (uint v = 0; v < CHUNK_SIZE; v++) {
+		for (uint u = 0; u < CHUNK_SIZE; u++) {
+			uint id = mask[u + v * CHUNK_SIZE];
+
+			if (id == 0) {
+				continue;
+			}
+
+			uint w = 1;
+
+			while (u + w < CHUNK_SIZE && mask[u + w + v * CHUNK_SIZE] == id) {
+				w++;
+			}
+
+			uint h = 1;
+			bool grow = true;
+
+			while (v + h < CHUNK_SIZE && grow) {
+				for (uint k = 0; k < w; k++) {
+					if (mask[u + k + (v + h) * CHUNK_SIZE] != id) {
+						grow = false;
+						break;
+					}
+				}
+
+				if (grow) {
+					h++;
+				}
+			}
+
+			for (uint y = 0; y < h; y++) {
+				for (uint x = 0; x < w; x++) {
+					mask[u + x + (v + y) * CHUNK_SIZE] = 0;
+				}
+			}
+
+			emit(d, positive, slice, u, v, w, h, id);
+		}
+	}
+
+	barrier();
+
+	//VkDrawIndexedIndirectCommand for this chunk's slot
+	if (invocation == 0) {
+		uint o = chunk.slot * 5;
+
+		commands[o + 0] = quad_count * 6;
+		commands[o + 1] = 1;
+		commands[o + 2] = chunk.slot * MAX_QUADS * 6;
+		commands[o + 3] = chunk.slot * MAX_QUADS * 4;
+		commands[o + 4] = chunk.slot;
+	}
+}
@@ -0,0 +1,265 @@
+use crate::camera::Camera;
+
+use gpu::prelude::*;
+use math::prelude::{Matrix, Vector};
+
+use std::mem;
+
+pub const MAX_CASCADES: usize = 4;
+
+#[derive(Default, Clone, Copy)]
+#[repr(C)]
+pub struct Cascades {
+    pub matrices: [Matrix<f32, 4, 4>; MAX_CASCADES],
+    pub splits: Vector<f32, 4>,
+}
+
+unsafe impl Pod for Cascades {}
+
+pub struct ShadowMapInfo<'a> {
+    pub device: &'a Device,
+    pub resolution: u32,
+    pub cascade_count: u32,
+    pub lambda: f32,
+    pub near: f32,
+    pub far: f32,
+    //how far toward the light past a cascade's bounds casters are still drawn into it
+    pub caster_distance: f32,
+}
+
+//Binding layout expected by resources/shadow.vert.glsl (depth pass)
+//and resources/cascade.glsl (lighting):
+//Cascades uniform buffer followed by the depth array sampled as sampler2DArray.
+//The depth pass pushes the cascade index and writes gl_Layer,
+//so all cascades render into the one layered framebuffer.
+//Writing gl_Layer needs Device::shader_output_layer_supported.
+pub struct ShadowMap {
+    target: LayeredRenderTarget,
+    buffer: Buffer,
+    cascades: Cascades,
+    resolution: u32,
+    cascade_count: u32,
+    lambda: f32,
+    near: f32,
+    far: f32,
+    caster_distance: f32,
+}
+
+impl ShadowMap {
+    pub fn new(info: ShadowMapInfo<'_>) -> Self {
+        if info.cascade_count == 0 || info.cascade_count as usize > MAX_CASCADES {
+            panic!("cascade count must be between 1 and {}", MAX_CASCADES);
+        }
+
+        if !info.device.shader_output_layer_supported() {
+            panic!("shadow cascades need gl_Layer in vertex shaders");
+        }
+
+        let target = LayeredRenderTarget::new(LayeredRenderTargetInfo {
+            device: info.device,
+            format: DepthConfig::Standard.format(),
+            usage: ImageUsage::SAMPLED,
+            extent: (info.resolution, info.resolution),
+            layers: info.cascade_count,
+        });
+
+        let buffer = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::UNIFORM,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: mem::size_of::<Cascades>(),
+            priority: MemoryPriority::Default,
+        });
+
+        Self {
+            target,
+            buffer,
+            cascades: Cascades::default(),
+            resolution: info.resolution,
+            cascade_count: info.cascade_count,
+            lambda: info.lambda,
+            near: info.near,
+            far: info.far,
+            caster_distance: info.caster_distance,
+        }
+    }
+
+    pub fn update(&mut self, camera: &Camera, light: Vector<f32, 3>) {
+        let splits = split_distances(self.near, self.far, self.cascade_count, self.lambda);
+
+        let (camera_near, camera_far) = (camera.projection.near(), camera.projection.far());
+
+        let corners = frustum_corners(camera);
+
+        let light = light.normalize();
+
+        let up = if light[1].abs() > 0.99 {
+            Vector::<f32, 3>::new([0.0, 0.0, 1.0])
+        } else {
+            Vector::<f32, 3>::new([0.0, 1.0, 0.0])
+        };
+
+        let mut start = self.near;
+
+        for (i, &end) in splits.iter().enumerate() {
+            let t_start = (start - camera_near) / (camera_far - camera_near);
+            let t_end = (end - camera_near) / (camera_far - camera_near);
+
+            let mut slice = [Vector::<f32, 3>::default(); 8];
+
+            for j in 0..4 {
+                let edge = corners[j + 4] - corners[j];
+                slice[j] = corners[j] + edge * t_start;
+                slice[j + 4] = corners[j] + edge * t_end;
+            }
+
+            let mut center = Vector::<f32, 3>::default();
+
+            for corner in slice {
+                center += corner;
+            }
+
+            center /= 8.0;
+
+            let mut radius = 0.0_f32;
+
+            for corner in slice {
+                radius = radius.max(corner.distance(&center));
+            }
+
+            radius = (radius * 16.0).ceil() / 16.0;
+
+            //the near plane is pulled toward the light so casters outside the slice still land
+            let eye = center - light * (radius + self.caster_distance);
+
+            let view = look_at(eye, center, up);
+
+            let mut proj = orthographic(radius, 2.0 * radius + self.caster_distance);
+
+            //snap the projection to whole texels so the cascade does not shimmer
+            let view_proj = view * proj;
+
+            let origin = view_proj.transpose() * Vector::<f32, 4>::new([0.0, 0.0, 0.0, 1.0]);
+
+            let texels = self.resolution as f32 / 2.0;
+
+            let x = origin[0] * texels;
+            let y = origin[1] * texels;
+
+            proj[3][0] += (x.round() - x) / texels;
+            proj[3][1] += (y.round() - y) / texels;
+
+            self.cascades.matrices[i] = view * proj;
+            self.cascades.splits[i] = end;
+
+            start = end;
+        }
+
+        self.buffer.write(BufferWrite {
+            offset: 0,
+            data: &[self.cascades],
+        });
+    }
+
+    pub fn cascades(&self) -> &Cascades {
+        &self.cascades
+    }
+
+    pub fn cascade_count(&self) -> u32 {
+        self.cascade_count
+    }
+
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    pub fn image(&self) -> &Image {
+        self.target.image()
+    }
+
+    pub fn render_pass(&self) -> &RenderPass {
+        self.target.render_pass()
+    }
+
+    pub fn framebuffer(&self) -> &Framebuffer {
+        self.target.framebuffer()
+    }
+}
+
+//practical split scheme, lambda blends logarithmic (1.0) and uniform (0.0) splits
+pub fn split_distances(near: f32, far: f32, count: u32, lambda: f32) -> Vec<f32> {
+    (1..=count)
+        .map(|i| {
+            let p = i as f32 / count as f32;
+
+            let log = near * (far / near).powf(p);
+            let uniform = near + (far - near) * p;
+
+            lambda * log + (1.0 - lambda) * uniform
+        })
+        .collect()
+}
+
+//near plane first, in the camera's depth convention
+fn frustum_corners(camera: &Camera) -> [Vector<f32, 3>; 8] {
+    let inverse = (camera.view() * camera.proj()).inverse().transpose();
+
+    let planes = if camera.reversed_z {
+        [1.0, 0.0]
+    } else {
+        [0.0, 1.0]
+    };
+
+    let mut corners = [Vector::<f32, 3>::default(); 8];
+
+    for (i, z) in planes.into_iter().enumerate() {
+        for (j, (x, y)) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .into_iter()
+            .enumerate()
+        {
+            let corner = inverse * Vector::<f32, 4>::new([x, y, z, 1.0]);
+
+            corners[i * 4 + j] = Vector::<f32, 3>::new([
+                corner[0] / corner[3],
+                corner[1] / corner[3],
+                corner[2] / corner[3],
+            ]);
+        }
+    }
+
+    corners
+}
+
+fn look_at(eye: Vector<f32, 3>, center: Vector<f32, 3>, up: Vector<f32, 3>) -> Matrix<f32, 4, 4> {
+    let f = (center - eye).normalize();
+    let s = f.cross(up).normalize();
+    let u = s.cross(f);
+
+    let mut view = Matrix::<f32, 4, 4>::identity();
+
+    for i in 0..3 {
+        view[i][0] = s[i];
+        view[i][1] = u[i];
+        view[i][2] = -f[i];
+    }
+
+    view[3][0] = -s.dot(eye);
+    view[3][1] = -u.dot(eye);
+    view[3][2] = f.dot(eye);
+
+    view
+}
+
+fn orthographic(radius: f32, depth: f32) -> Matrix<f32, 4, 4> {
+    let mut proj = Matrix::<f32, 4, 4>::identity();
+
+    proj[0][0] = 1.0 / radius;
+    proj[1][1] = 1.0 / radius;
+    proj[2][2] = -1.0 / depth;
+
+    proj
+}
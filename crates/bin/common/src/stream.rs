@@ -0,0 +1,167 @@
+use crate::meshing::{MeshPool, Mesher, MesherInfo, CHUNK_SIZE};
+
+use gpu::prelude::*;
+
+use std::collections::VecDeque;
+use std::mem;
+use std::time::{Duration, Instant};
+
+const CHUNK_BYTES: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE * mem::size_of::<u32>();
+
+pub struct ChunkStreamerInfo<'a> {
+    pub device: &'a Device,
+    pub pool: &'a MeshPool,
+    pub frames_in_flight: u32,
+    pub budget_bytes: usize,
+    pub budget_time: Duration,
+}
+
+pub struct ChunkUpload {
+    pub slot: u32,
+    pub voxels: Vec<u32>,
+    pub on_complete: Option<Box<dyn FnOnce(u32)>>,
+}
+
+//Uploads chunk occupancy through a per-frame staging segment and meshes it
+//into the MeshPool slot. Callbacks fire once the frame that carried the
+//upload has been waited on, i.e. when its staging segment comes around again.
+pub struct ChunkStreamer {
+    mesher: Mesher,
+    occupancy: Vec<Buffer>,
+    staging: Buffer,
+    queue: VecDeque<ChunkUpload>,
+    in_flight: Vec<Vec<(u32, Option<Box<dyn FnOnce(u32)>>)>>,
+    frame: usize,
+    budget_bytes: usize,
+    budget_time: Duration,
+}
+
+impl ChunkStreamer {
+    pub fn new(info: ChunkStreamerInfo<'_>) -> Self {
+        if info.budget_bytes < CHUNK_BYTES {
+            panic!("streaming budget is smaller than a single chunk");
+        }
+
+        let mut mesher = Mesher::new(MesherInfo {
+            device: info.device,
+            pool: info.pool,
+        });
+
+        let occupancy = (0..info.pool.slots())
+            .map(|_| {
+                Buffer::new(BufferInfo {
+                    device: info.device,
+                    usage: BufferUsage::STORAGE | BufferUsage::TRANSFER_DST,
+                    properties: MemoryProperties::DEVICE_LOCAL,
+                    size: CHUNK_BYTES,
+                    //voxel data should stay resident over transient targets
+                    priority: MemoryPriority::High,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for (slot, buffer) in occupancy.iter().enumerate() {
+            mesher.bind(slot as _, buffer, info.pool);
+        }
+
+        let staging = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::TRANSFER_SRC,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: info.budget_bytes * info.frames_in_flight as usize,
+            priority: MemoryPriority::Default,
+        });
+
+        Self {
+            mesher,
+            occupancy,
+            staging,
+            queue: VecDeque::new(),
+            in_flight: (0..info.frames_in_flight).map(|_| vec![]).collect(),
+            frame: 0,
+            budget_bytes: info.budget_bytes,
+            budget_time: info.budget_time,
+        }
+    }
+
+    pub fn queue(&mut self, upload: ChunkUpload) {
+        if upload.voxels.len() != CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE {
+            panic!("chunk upload must contain exactly one chunk of voxels");
+        }
+
+        if upload.slot as usize >= self.occupancy.len() {
+            panic!("chunk upload slot is out of range");
+        }
+
+        self.queue.push_back(upload);
+    }
+
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    //Call once per frame, after the frame fence was waited on and before the
+    //pool is drawn.
+    pub fn record(&mut self, commands: &mut Commands<'_>) {
+        for (slot, on_complete) in self.in_flight[self.frame].drain(..) {
+            if let Some(on_complete) = on_complete {
+                on_complete(slot);
+            }
+        }
+
+        let start = Instant::now();
+
+        let base = self.frame * self.budget_bytes;
+
+        let mut offset = 0;
+        let mut slots = vec![];
+
+        while !self.queue.is_empty() {
+            if offset + CHUNK_BYTES > self.budget_bytes || start.elapsed() > self.budget_time {
+                break;
+            }
+
+            let ChunkUpload {
+                slot,
+                voxels,
+                on_complete,
+            } = self.queue.pop_front().unwrap();
+
+            self.staging.write(BufferWrite {
+                offset: (base + offset) as _,
+                data: &voxels,
+            });
+
+            commands.copy_buffer(
+                &self.staging,
+                &mut self.occupancy[slot as usize],
+                base + offset,
+                0,
+                CHUNK_BYTES,
+            );
+
+            self.in_flight[self.frame].push((slot, on_complete));
+
+            slots.push(slot);
+
+            offset += CHUNK_BYTES;
+        }
+
+        if !slots.is_empty() {
+            commands.pipeline_barrier(
+                PipelineStage::TRANSFER,
+                PipelineStage::COMPUTE_SHADER,
+                &[Barrier::Memory {
+                    src_access: Access::TRANSFER_WRITE,
+                    dst_access: Access::SHADER_READ,
+                }],
+            );
+
+            for slot in slots {
+                self.mesher.record(commands, slot);
+            }
+        }
+
+        self.frame = (self.frame + 1) % self.in_flight.len();
+    }
+}
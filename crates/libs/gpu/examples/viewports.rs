@@ -0,0 +1,242 @@
+//Two windows drawn from one Device through Viewports, each cleared to its own color. Either
+//may be resized or minimized while the other keeps drawing, and the second may only be
+//presentable from another queue family than the first. Closing either ends the sample.
+
+#[cfg(target_os = "linux")]
+fn main() {
+    xlib::main();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    eprintln!("the viewports sample opens its windows through Xlib");
+}
+
+#[cfg(target_os = "linux")]
+mod xlib {
+    use gpu::prelude::*;
+
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle, XlibHandle};
+
+    use std::mem;
+
+    enum WindowEvent {
+        Resized((u32, u32)),
+        CloseRequested,
+    }
+
+    //each on its own display connection, so their events stay apart
+    struct Window {
+        display: x11::Display,
+        window: x11::Window,
+        wm_protocols: x11::Atom,
+        wm_delete_window: x11::Atom,
+        extent: (u32, u32),
+    }
+
+    impl Window {
+        fn new(title: &str, extent: (u32, u32)) -> Self {
+            let display = x11::open_display("").expect("failed to open display");
+
+            let screen = x11::default_screen(display);
+
+            let window = x11::create_simple_window(
+                display,
+                x11::root_window(display, screen),
+                0,
+                0,
+                extent.0,
+                extent.1,
+                0,
+                x11::black_pixel(display, screen),
+                x11::black_pixel(display, screen),
+            );
+
+            x11::select_input(display, window, x11::STRUCTURE_NOTIFY_MASK);
+
+            let wm_protocols = x11::intern_atom(display, "WM_PROTOCOLS", false);
+            let wm_delete_window = x11::intern_atom(display, "WM_DELETE_WINDOW", false);
+
+            x11::set_wm_protocols(display, window, &mut [wm_delete_window]);
+
+            x11::store_name(display, window, title);
+
+            x11::map_window(display, window);
+
+            Self {
+                display,
+                window,
+                wm_protocols,
+                wm_delete_window,
+                extent,
+            }
+        }
+
+        fn next_event(&mut self) -> Option<WindowEvent> {
+            while x11::pending(self.display) > 0 {
+                match x11::next_event(self.display) {
+                    Ok(x11::Event::ConfigureNotify { width, height, .. }) => {
+                        let extent = (width as u32, height as u32);
+
+                        if self.extent != extent {
+                            self.extent = extent;
+
+                            return Some(WindowEvent::Resized(extent));
+                        }
+                    }
+                    Ok(x11::Event::ClientMessage {
+                        message_type,
+                        format: 32,
+                        data,
+                        ..
+                    }) if message_type == self.wm_protocols
+                        && data[0] as x11::Atom == self.wm_delete_window =>
+                    {
+                        return Some(WindowEvent::CloseRequested);
+                    }
+                    _ => {}
+                }
+            }
+
+            None
+        }
+    }
+
+    unsafe impl HasRawWindowHandle for Window {
+        fn raw_window_handle(&self) -> RawWindowHandle {
+            let mut xlib_handle = XlibHandle::empty();
+
+            xlib_handle.window = self.window;
+            xlib_handle.display = unsafe { mem::transmute(self.display) };
+
+            RawWindowHandle::Xlib(xlib_handle)
+        }
+    }
+
+    pub fn main() {
+        let context = Context::start();
+
+        let mut windows = [
+            Window::new("left", (640, 480)),
+            Window::new("right", (480, 640)),
+        ];
+
+        let colors = [[0.8, 0.2, 0.2, 1.0], [0.2, 0.2, 0.8, 1.0]];
+
+        let surfaces = windows
+            .iter()
+            .map(|window| {
+                Surface::new(SurfaceInfo {
+                    context: &context,
+                    window,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        //drawn on a family that presents the first window, the second may be presented
+        //from another, see Device::present_queue_family
+        let mut device = Device::choose_best(DeviceInfo {
+            context: &context,
+            surface: Some(&surfaces[0]),
+        });
+
+        if !device.dynamic_rendering_supported() {
+            panic!("the sample clears with dynamic rendering");
+        }
+
+        let mut viewports = Viewports::new();
+
+        let ids = windows
+            .iter()
+            .zip(surfaces)
+            .map(|(window, surface)| {
+                viewports
+                    .add(ViewportInfo {
+                        device: &device,
+                        surface,
+                        usage: ImageUsage::COLOR,
+                        encoding: OutputEncoding::Hardware,
+                        vsync: VsyncMode::On,
+                        extent: Some(window.extent),
+                    })
+                    .expect("failed to add viewport")
+            })
+            .collect::<Vec<_>>();
+
+        'frames: loop {
+            for (window, &id) in windows.iter_mut().zip(&ids) {
+                while let Some(event) = window.next_event() {
+                    match event {
+                        WindowEvent::Resized(extent) => viewports.resize(id, extent),
+                        WindowEvent::CloseRequested => break 'frames,
+                    }
+                }
+            }
+
+            //minimized or out of date windows are left out, the others still draw
+            let frames = viewports.begin_frame(&mut device);
+
+            for &id in &ids {
+                if let Some(error) = viewports.take_error(id) {
+                    eprintln!("viewport {:?} failed to acquire: {:?}", id, error);
+                }
+            }
+
+            viewports.draw(&mut device, |mut commands| {
+                for frame in &frames {
+                    let image = &viewports.images(frame.id)[frame.image_index as usize];
+
+                    let color = colors[ids.iter().position(|&id| id == frame.id).unwrap()];
+
+                    commands.pipeline_barrier(
+                        PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+                        PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+                        &[Barrier::Image {
+                            src_access: Access::empty(),
+                            dst_access: Access::COLOR_ATTACHMENT_WRITE,
+                            old_layout: ImageLayout::Undefined,
+                            new_layout: ImageLayout::ColorAttachment,
+                            image,
+                        }],
+                    );
+
+                    commands.begin_rendering(RenderingInfo {
+                        extent: viewports.swapchain(frame.id).extent(),
+                        color_attachments: &[RenderingAttachment {
+                            image,
+                            layout: ImageLayout::ColorAttachment,
+                            load_op: AttachmentLoadOp::Clear,
+                            store_op: AttachmentStoreOp::Store,
+                            clear_value: ClearValue::Color(color),
+                        }],
+                        depth_attachment: None,
+                    });
+
+                    commands.end_rendering();
+
+                    commands.pipeline_barrier(
+                        PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+                        PipelineStage::BOTTOM_OF_PIPE,
+                        &[Barrier::Image {
+                            src_access: Access::COLOR_ATTACHMENT_WRITE,
+                            dst_access: Access::empty(),
+                            old_layout: ImageLayout::ColorAttachment,
+                            new_layout: ImageLayout::PresentSrc,
+                            image,
+                        }],
+                    );
+                }
+            });
+
+            if let Err(error) = viewports.end_frame(&mut device) {
+                eprintln!("failed to present: {:?}", error);
+            }
+        }
+
+        for id in ids {
+            viewports.remove(&device, id);
+        }
+
+        device.wait_idle();
+    }
+}
@@ -3,11 +3,18 @@ use bitflags::bitflags;
 bitflags! {
     #[repr(transparent)]
     pub struct Access: u32 {
+        const INDIRECT_COMMAND_READ = 0x00000001;
+        const INDEX_READ = 0x00000002;
+        const VERTEX_ATTRIBUTE_READ = 0x00000004;
         const SHADER_READ = 0x00000020;
+        const SHADER_WRITE = 0x00000040;
         const COLOR_ATTACHMENT_READ = 0x00000080;
         const COLOR_ATTACHMENT_WRITE = 0x00000100;
         const DEPTH_STENCIL_ATTACHMENT_READ = 0x00000200;
         const DEPTH_STENCIL_ATTACHMENT_WRITE = 0x00000400;
+        const TRANSFER_READ = 0x00000800;
+        const TRANSFER_WRITE = 0x00001000;
+        const HOST_READ = 0x00002000;
     }
 }
 
@@ -0,0 +1,316 @@
+use crate::prelude::*;
+
+use std::ops::Range;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct AttachmentDesc {
+    pub format: Format,
+    pub extent: (u32, u32),
+    pub usage: ImageUsage,
+    pub samples: SampleCount,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AttachmentId(usize);
+
+#[derive(Clone, PartialEq)]
+struct AttachmentRequest {
+    desc: AttachmentDesc,
+    passes: Range<u32>,
+}
+
+struct AliasSlot {
+    requirements: vk::MemoryRequirements,
+    passes: Vec<Range<u32>>,
+    offset: u64,
+}
+
+enum PoolMemory {
+    Vulkan { memory: vk::Memory },
+}
+
+//Transient render targets handed out by description. Each frame acquire every target with
+//the range of pass indices it is used in, then build. Targets whose pass ranges do not
+//overlap share memory, so a target never keeps its contents from a previous frame or from
+//another target: use an Undefined initial layout with a Clear or DontCare load op, and keep
+//the dependency between the last pass of one target and the first pass of the next.
+pub struct AttachmentPool {
+    requests: Vec<AttachmentRequest>,
+    built: Vec<AttachmentRequest>,
+    images: Vec<Image>,
+    placements: Vec<(u64, u64)>,
+    memory: Option<PoolMemory>,
+    memory_size: u64,
+    unaliased_size: u64,
+}
+
+impl Default for AttachmentPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AttachmentPool {
+    pub fn new() -> Self {
+        Self {
+            requests: vec![],
+            built: vec![],
+            images: vec![],
+            placements: vec![],
+            memory: None,
+            memory_size: 0,
+            unaliased_size: 0,
+        }
+    }
+
+    pub fn begin(&mut self) {
+        self.requests.clear();
+    }
+
+    //passes is the half open range of pass indices reading or writing the target
+    pub fn acquire(&mut self, desc: AttachmentDesc, passes: Range<u32>) -> AttachmentId {
+        self.requests.push(AttachmentRequest { desc, passes });
+
+        AttachmentId(self.requests.len() - 1)
+    }
+
+    //Images are only recreated when the requests differ from the last build, which must
+    //happen after the frame fence wait since the old images are destroyed.
+    pub fn build(&mut self, device: &Device) {
+        if self.requests == self.built && self.memory.is_some() {
+            return;
+        }
+
+        let Device::Vulkan {
+            physical_device,
+            device,
+            ..
+        } = device
+        else {
+            panic!("not a vulkan device");
+        };
+
+        self.images.clear();
+        self.placements.clear();
+        self.memory = None;
+
+        let mut vk_images = self
+            .requests
+            .iter()
+            .map(|request| {
+                Image::new_unbound_vk_image(
+                    device.clone(),
+                    request.desc.format.into(),
+                    request.desc.usage.to_vk(),
+                    vk::ImageType::TwoDim,
+                    (request.desc.extent.0, request.desc.extent.1, 1),
+                    1,
+                    request.desc.samples,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let requirements = vk_images
+            .iter()
+            .map(|image| image.memory_requirements())
+            .collect::<Vec<_>>();
+
+        let mut order = (0..self.requests.len()).collect::<Vec<_>>();
+
+        order.sort_by(|&a, &b| requirements[b].size.cmp(&requirements[a].size));
+
+        let mut slots = Vec::<AliasSlot>::new();
+        let mut assignments = vec![0; self.requests.len()];
+
+        for i in order {
+            let passes = &self.requests[i].passes;
+            let requirement = requirements[i];
+
+            let slot = slots.iter().position(|slot| {
+                slot.requirements.memory_type & requirement.memory_type != 0
+                    && slot
+                        .passes
+                        .iter()
+                        .all(|other| passes.end <= other.start || other.end <= passes.start)
+            });
+
+            assignments[i] = match slot {
+                Some(slot) => {
+                    let slot_requirements = &mut slots[slot].requirements;
+
+                    slot_requirements.size = slot_requirements.size.max(requirement.size);
+                    slot_requirements.alignment =
+                        slot_requirements.alignment.max(requirement.alignment);
+                    slot_requirements.memory_type &= requirement.memory_type;
+
+                    slots[slot].passes.push(passes.clone());
+
+                    slot
+                }
+                None => {
+                    slots.push(AliasSlot {
+                        requirements: requirement,
+                        passes: vec![passes.clone()],
+                        offset: 0,
+                    });
+
+                    slots.len() - 1
+                }
+            };
+        }
+
+        let mut memory_requirements = vk::MemoryRequirements {
+            size: 0,
+            alignment: 1,
+            memory_type: u32::MAX,
+        };
+
+        for slot in &mut slots {
+            let alignment = slot.requirements.alignment.max(1);
+
+            slot.offset = (memory_requirements.size + alignment - 1) / alignment * alignment;
+
+            memory_requirements.size = slot.offset + slot.requirements.size;
+            memory_requirements.alignment = memory_requirements.alignment.max(alignment);
+            memory_requirements.memory_type &= slot.requirements.memory_type;
+        }
+
+        self.placements = (0..self.requests.len())
+            .map(|i| (slots[assignments[i]].offset, requirements[i].size))
+            .collect();
+
+        self.memory_size = memory_requirements.size;
+        self.unaliased_size = requirements.iter().map(|r| r.size).sum();
+        self.built = self.requests.clone();
+
+        if slots.is_empty() {
+            return;
+        }
+
+        if memory_requirements.memory_type == 0 {
+            panic!("attachments do not share a memory type");
+        }
+
+        let mut property_flags = vk::MEMORY_PROPERTY_DEVICE_LOCAL;
+
+        //tilers may never back targets that stay in tile memory
+        if self
+            .requests
+            .iter()
+            .all(|request| request.desc.usage.contains(ImageUsage::TRANSIENT))
+        {
+            property_flags |= vk::MEMORY_PROPERTY_LAZILY_ALLOCATED;
+        }
+
+        //Transient, so the first to be demoted under memory pressure
+        let memory_allocate_info = vk::MemoryAllocateInfo {
+            property_flags,
+            flags: 0,
+            priority: Some(MemoryPriority::Low.to_vk()),
+        };
+
+        let memory = vk::Memory::allocate(
+            device.clone(),
+            memory_allocate_info,
+            memory_requirements,
+            physical_device.memory_properties(),
+            false,
+        )
+        .expect("failed to allocate memory");
+
+        for (i, image) in vk_images.iter_mut().enumerate() {
+            image
+                .bind_memory_at(&memory, slots[assignments[i]].offset)
+                .expect("failed to bind memory to image");
+        }
+
+        self.images = vk_images
+            .into_iter()
+            .zip(self.requests.iter())
+            .map(|(image, request)| {
+                let format = request.desc.format.into();
+
+                let (view, sampler) = Image::new_vk_image_view(
+                    device.clone(),
+                    &image,
+                    format,
+                    vk::ImageViewType::TwoDim,
+                    1,
+                );
+
+                Image::Vulkan {
+                    image,
+                    format,
+                    memory: None,
+                    view,
+                    sampler,
+                }
+            })
+            .collect();
+
+        self.memory = Some(PoolMemory::Vulkan { memory });
+    }
+
+    pub fn image(&self, id: AttachmentId) -> &Image {
+        &self.images[id.0]
+    }
+
+    //Offset and size of the target inside the memory shared by the pool
+    pub fn placement(&self, id: AttachmentId) -> (u64, u64) {
+        self.placements[id.0]
+    }
+
+    #[cfg(feature = "memory-dump")]
+    pub fn dump(&self, name: &str) -> PoolDump {
+        let Some(PoolMemory::Vulkan { memory }) = &self.memory else {
+            return PoolDump {
+                name: name.to_owned(),
+                blocks: vec![],
+            };
+        };
+
+        let allocations = self
+            .built
+            .iter()
+            .zip(self.placements.iter())
+            .map(|(request, &(offset, size))| AllocationDump {
+                name: format!(
+                    "{}x{} passes {}..{}",
+                    request.desc.extent.0,
+                    request.desc.extent.1,
+                    request.passes.start,
+                    request.passes.end
+                ),
+                offset,
+                size,
+            })
+            .collect();
+
+        PoolDump {
+            name: name.to_owned(),
+            blocks: vec![BlockDump {
+                name: "aliased".to_owned(),
+                size: memory.size(),
+                allocations,
+            }],
+        }
+    }
+
+    //bytes backing all targets after aliasing
+    pub fn memory_size(&self) -> u64 {
+        self.memory_size
+    }
+
+    //bytes the same targets would need as separate allocations
+    pub fn unaliased_size(&self) -> u64 {
+        self.unaliased_size
+    }
+
+    //bytes actually committed, below memory_size when lazily allocated targets stay on chip
+    pub fn committed_size(&self) -> u64 {
+        match &self.memory {
+            Some(PoolMemory::Vulkan { memory }) => memory.commitment(),
+            None => 0,
+        }
+    }
+}
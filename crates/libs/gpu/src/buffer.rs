@@ -10,6 +10,10 @@ bitflags! {
         const UNIFORM       = 1 << 3;
         const VERTEX        = 1 << 4;
         const INDEX         = 1 << 5;
+        const INDIRECT      = 1 << 6;
+        const SHADER_BINDING_TABLE = 1 << 7;
+        //Buffer::device_address, with Device::buffer_device_address_supported
+        const SHADER_DEVICE_ADDRESS = 1 << 8;
     }
 }
 
@@ -41,6 +45,18 @@ impl BufferUsage {
             vk |= vk::BUFFER_USAGE_INDEX
         }
 
+        if self.contains(Self::INDIRECT) {
+            vk |= vk::BUFFER_USAGE_INDIRECT
+        }
+
+        if self.contains(Self::SHADER_BINDING_TABLE) {
+            vk |= vk::BUFFER_USAGE_SHADER_BINDING_TABLE
+        }
+
+        if self.contains(Self::SHADER_DEVICE_ADDRESS) {
+            vk |= vk::BUFFER_USAGE_SHADER_DEVICE_ADDRESS
+        }
+
         vk
     }
 }
@@ -61,7 +77,7 @@ pub struct BufferImageCopy<'a> {
     pub dst_offset: (i32, i32, i32),
 }
 
-pub struct BufferWrite<'a, U: ?Sized + Copy> {
+pub struct BufferWrite<'a, U: Pod> {
     pub offset: u64,
     pub data: &'a [U],
 }
@@ -71,6 +87,8 @@ pub struct BufferInfo<'a> {
     pub usage: BufferUsage,
     pub properties: MemoryProperties,
     pub size: usize,
+    //only affects device local memory
+    pub priority: MemoryPriority,
 }
 
 #[non_exhaustive]
@@ -78,11 +96,17 @@ pub enum Buffer {
     Vulkan {
         buffer: vk::Buffer,
         memory: vk::Memory,
+        size: usize,
     },
 }
 
 impl Buffer {
     pub fn new(info: BufferInfo) -> Self {
+        Self::try_new(info).expect("failed to allocate memory")
+    }
+
+    //For callers with a fallback, e.g. a smaller or host visible buffer
+    pub fn try_new(info: BufferInfo) -> Result<Self, Error> {
         match info.device {
             Device::Vulkan {
                 physical_device,
@@ -90,30 +114,86 @@ impl Buffer {
                 ..
             } => {
                 let mut buffer =
-                    vk::Buffer::new(device.clone(), info.size as u64, info.usage.to_vk())
+                    vk::Buffer::new(device.clone(), info.size as u64, info.usage.to_vk(), 0)
                         .expect("failed to create buffer");
 
                 let memory_allocate_info = vk::MemoryAllocateInfo {
                     property_flags: info.properties.to_vk(),
+                    flags: if info.usage.contains(BufferUsage::SHADER_DEVICE_ADDRESS) {
+                        vk::MEMORY_ALLOCATE_DEVICE_ADDRESS
+                    } else {
+                        0
+                    },
+                    priority: info
+                        .properties
+                        .contains(MemoryProperties::DEVICE_LOCAL)
+                        .then(|| info.priority.to_vk()),
                 };
 
-                let memory = vk::Memory::allocate(
+                let memory = match vk::Memory::allocate(
                     device.clone(),
                     memory_allocate_info,
                     buffer.memory_requirements(),
                     physical_device.memory_properties(),
                     info.properties.contains(MemoryProperties::HOST_VISIBLE),
-                )
-                .expect("failed to allocate memory");
+                ) {
+                    Ok(memory) => memory,
+                    Err(vk::Error::OutOfDeviceMemory | vk::Error::OutOfHostMemory) => {
+                        return Err(Error::OutOfMemory)
+                    }
+                    Err(e) => panic!("failed to allocate memory: {:?}", e),
+                };
 
                 buffer.bind_memory(&memory);
 
-                Self::Vulkan { buffer, memory }
+                Ok(Self::Vulkan {
+                    buffer,
+                    memory,
+                    size: info.size,
+                })
+            }
+        }
+    }
+
+    //Bytes requested at creation
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Vulkan { size, .. } => *size,
+        }
+    }
+
+    //For shaders reading the buffer through a pointer, e.g. in push constants. Created with
+    //BufferUsage::SHADER_DEVICE_ADDRESS.
+    pub fn device_address(&self) -> u64 {
+        match self {
+            Self::Vulkan { buffer, .. } => buffer
+                .device_address()
+                .expect("failed to get buffer device address"),
+        }
+    }
+
+    //The buffer as a field of a DescriptorTemplate's data, like a Binding::Buffer
+    pub fn descriptor(&self, offset: usize, range: usize) -> DescriptorData {
+        match self {
+            Self::Vulkan { buffer, .. } => DescriptorData::buffer(vk::DescriptorBufferInfo {
+                buffer,
+                offset,
+                range,
+            }),
+        }
+    }
+
+    //Demotes or promotes a live allocation, does nothing without
+    //Device::pageable_device_local_memory_supported
+    pub fn set_priority(&self, priority: MemoryPriority) {
+        match self {
+            Self::Vulkan { memory, .. } => {
+                let _ = memory.set_priority(priority.to_vk());
             }
         }
     }
 
-    pub fn write<U: ?Sized + Copy>(&mut self, write: BufferWrite<U>) {
+    pub fn write<U: Pod>(&mut self, write: BufferWrite<U>) {
         match self {
             Self::Vulkan { memory, .. } => {
                 memory
@@ -0,0 +1,268 @@
+use crate::compute::{compute_pipeline, storage_binding, storage_descriptor};
+use crate::prelude::*;
+
+use std::mem;
+
+const RING_SIZE: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CaptureFormat {
+    Bgra8,
+    Rgba8,
+    //luma plane of height rows, then interleaved uv plane of height / 2 rows, both at stride
+    Nv12,
+}
+
+pub struct FrameCaptureInfo<'a> {
+    pub device: &'a Device,
+    pub extent: (u32, u32),
+    pub format: Format,
+    pub convert_nv12: bool,
+}
+
+pub struct CapturedFrame<'a> {
+    pub data: &'a [u8],
+    pub width: u32,
+    pub height: u32,
+    pub stride: usize,
+    pub format: CaptureFormat,
+    pub frame: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ConvertConstants {
+    width: u32,
+    height: u32,
+    bgra: u32,
+}
+
+unsafe impl Pod for ConvertConstants {}
+
+struct Convert {
+    pipeline: Pipeline,
+    scratch: Buffer,
+}
+
+//Copies completed frames into a host visible ring without ever waiting on the gpu.
+//A capture recorded in one frame becomes readable once the next frame has begun
+//(begin_frame waits on the previous submission), and stays readable until two more
+//captures have been recorded.
+pub struct FrameCapture {
+    ring: Vec<Buffer>,
+    convert: Option<Convert>,
+    extent: (u32, u32),
+    source: CaptureFormat,
+    frame: u64,
+    next: usize,
+    pending: Option<(usize, u64)>,
+    ready: Option<(usize, u64)>,
+}
+
+impl FrameCapture {
+    pub fn new(info: FrameCaptureInfo<'_>) -> Self {
+        let source = match info.format {
+            Format::Bgra8Srgb => CaptureFormat::Bgra8,
+            Format::Rgba8Srgb | Format::Rgba8Unorm => CaptureFormat::Rgba8,
+            _ => panic!("unsupported capture format"),
+        };
+
+        let (width, height) = (info.extent.0 as usize, info.extent.1 as usize);
+
+        let rgba_size = width * height * 4;
+
+        let (size, mut convert) = if info.convert_nv12 {
+            if width % 4 != 0 || height % 2 != 0 {
+                panic!("nv12 capture needs a width divisible by 4 and an even height");
+            }
+
+            let pipeline = compute_pipeline(
+                info.device,
+                "nv12.comp",
+                include_str!("shaders/nv12.comp.glsl"),
+                RING_SIZE as _,
+                &[storage_descriptor(0), storage_descriptor(1)],
+                mem::size_of::<ConvertConstants>() as _,
+            );
+
+            let scratch = Buffer::new(BufferInfo {
+                device: info.device,
+                usage: BufferUsage::TRANSFER_DST | BufferUsage::STORAGE,
+                properties: MemoryProperties::DEVICE_LOCAL,
+                size: rgba_size,
+                priority: MemoryPriority::Default,
+            });
+
+            (width * height * 3 / 2, Some(Convert { pipeline, scratch }))
+        } else {
+            (rgba_size, None)
+        };
+
+        let ring = (0..RING_SIZE)
+            .map(|_| {
+                Buffer::new(BufferInfo {
+                    device: info.device,
+                    usage: BufferUsage::TRANSFER_DST | BufferUsage::STORAGE,
+                    properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+                    size,
+                    priority: MemoryPriority::Default,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(Convert { pipeline, scratch }) = &mut convert {
+            for (slot, buffer) in ring.iter().enumerate() {
+                pipeline.bind(
+                    slot as _,
+                    &[
+                        storage_binding(0, scratch, rgba_size),
+                        storage_binding(1, buffer, size),
+                    ],
+                );
+            }
+        }
+
+        Self {
+            ring,
+            convert,
+            extent: info.extent,
+            source,
+            frame: 0,
+            next: 0,
+            pending: None,
+            ready: None,
+        }
+    }
+
+    //Records a copy of image, which needs TRANSFER_SRC usage and must be in layout,
+    //it is returned to layout afterwards.
+    //Call once per frame after the image has been rendered.
+    pub fn record(&mut self, commands: &mut Commands<'_>, image: &Image, layout: ImageLayout) {
+        if let Some(pending) = self.pending.take() {
+            self.ready = Some(pending);
+        }
+
+        let slot = self.next;
+
+        self.next = (self.next + 1) % RING_SIZE;
+
+        commands.pipeline_barrier(
+            PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+            PipelineStage::TRANSFER,
+            &[Barrier::Image {
+                src_access: Access::COLOR_ATTACHMENT_WRITE,
+                dst_access: Access::TRANSFER_READ,
+                old_layout: layout,
+                new_layout: ImageLayout::TransferSrc,
+                image,
+            }],
+        );
+
+        match &mut self.convert {
+            Some(Convert { pipeline, scratch }) => {
+                commands.copy_image_to_buffer(image, ImageLayout::TransferSrc, scratch, 0, (0, 0), self.extent);
+
+                commands.pipeline_barrier(
+                    PipelineStage::TRANSFER,
+                    PipelineStage::COMPUTE_SHADER,
+                    &[Barrier::Memory {
+                        src_access: Access::TRANSFER_WRITE,
+                        dst_access: Access::SHADER_READ,
+                    }],
+                );
+
+                let constants = ConvertConstants {
+                    width: self.extent.0,
+                    height: self.extent.1,
+                    bgra: (self.source == CaptureFormat::Bgra8) as u32,
+                };
+
+                commands.bind_pipeline(slot as _, pipeline);
+                commands.push_constants(pipeline, ShaderStage::COMPUTE, 0, &[constants]);
+                commands.dispatch((self.extent.0 / 4 + 7) / 8, (self.extent.1 / 2 + 7) / 8, 1);
+
+                commands.pipeline_barrier(
+                    PipelineStage::COMPUTE_SHADER,
+                    PipelineStage::HOST,
+                    &[Barrier::Memory {
+                        src_access: Access::SHADER_WRITE,
+                        dst_access: Access::HOST_READ,
+                    }],
+                );
+            }
+            None => {
+                commands.copy_image_to_buffer(
+                    image,
+                    ImageLayout::TransferSrc,
+                    &mut self.ring[slot],
+                    0,
+                    (0, 0),
+                    self.extent,
+                );
+
+                commands.pipeline_barrier(
+                    PipelineStage::TRANSFER,
+                    PipelineStage::HOST,
+                    &[Barrier::Memory {
+                        src_access: Access::TRANSFER_WRITE,
+                        dst_access: Access::HOST_READ,
+                    }],
+                );
+            }
+        }
+
+        commands.pipeline_barrier(
+            PipelineStage::TRANSFER,
+            PipelineStage::BOTTOM_OF_PIPE,
+            &[Barrier::Image {
+                src_access: Access::TRANSFER_READ,
+                dst_access: Access::empty(),
+                old_layout: ImageLayout::TransferSrc,
+                new_layout: layout,
+                image,
+            }],
+        );
+
+        self.pending = Some((slot, self.frame));
+        self.frame += 1;
+    }
+
+    //Hands the most recent completed capture to script, returns false if there is none yet.
+    pub fn latest(&self, script: impl FnOnce(CapturedFrame<'_>)) -> bool {
+        let Some((slot, frame)) = self.ready else {
+            return false;
+        };
+
+        let Buffer::Vulkan { memory, .. } = &self.ring[slot] else {
+            panic!("not a vulkan buffer");
+        };
+
+        let (format, stride) = match self.convert {
+            Some(_) => (CaptureFormat::Nv12, self.extent.0 as usize),
+            None => (self.source, self.extent.0 as usize * 4),
+        };
+
+        let mut script = Some(script);
+
+        memory
+            .read(0, |data: &[u8]| {
+                if let Some(script) = script.take() {
+                    script(CapturedFrame {
+                        data,
+                        width: self.extent.0,
+                        height: self.extent.1,
+                        stride,
+                        format,
+                        frame,
+                    });
+                }
+            })
+            .expect("failed to read capture memory");
+
+        true
+    }
+
+    pub fn extent(&self) -> (u32, u32) {
+        self.extent
+    }
+}
@@ -0,0 +1,209 @@
+use crate::prelude::*;
+
+use std::mem;
+
+//How linear color written by shaders ends up sRGB encoded for presentation
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputEncoding {
+    //the target format is sRGB and the hardware encodes on store, shaders write linear color
+    Hardware,
+    //the target format is UNORM, shaders encode themselves or a GammaEncodePass runs last
+    Manual,
+}
+
+impl OutputEncoding {
+    pub fn of(format: Format) -> Self {
+        if format.is_srgb() {
+            Self::Hardware
+        } else {
+            Self::Manual
+        }
+    }
+
+    //Fails when something built for one encoding is about to render into a target of the other,
+    //which shows up as washed out or too dark colors rather than an error
+    pub fn validate(self, target: Format) -> Result<(), Error> {
+        if Self::of(target) == self {
+            Ok(())
+        } else {
+            Err(Error::OutputEncodingMismatch)
+        }
+    }
+
+    //Prefers the formats of this encoding, falling back to whatever the surface offers first
+    pub(crate) fn choose_surface_format(
+        self,
+        surface_formats: &[vk::SurfaceFormat],
+    ) -> vk::SurfaceFormat {
+        let preferred: &[vk::Format] = match self {
+            Self::Hardware => &[vk::Format::Bgra8Srgb, vk::Format::Rgba8Srgb],
+            Self::Manual => &[vk::Format::Rgba8Unorm],
+        };
+
+        preferred
+            .iter()
+            .find_map(|format| {
+                surface_formats.iter().copied().find(|surface_format| {
+                    surface_format.format == *format
+                        && surface_format.color_space == vk::ColorSpace::SrgbNonlinear
+                })
+            })
+            .or_else(|| surface_formats.first().copied())
+            .expect("no supported surface format")
+    }
+}
+
+//sRGB transfer function for a single component in 0..=1, e.g. to turn a color picked in
+//sRGB into the linear clear value of an sRGB target
+pub fn srgb_to_linear(component: f32) -> f32 {
+    if component <= 0.04045 {
+        component / 12.92
+    } else {
+        ((component + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(component: f32) -> f32 {
+    if component <= 0.0031308 {
+        component * 12.92
+    } else {
+        1.055 * component.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+//What the content was mastered for, so HDR displays can tone map it to what they can show.
+//Primaries and white point are CIE 1931 xy coordinates, luminances are in nits.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct HdrMetadata {
+    pub red_primary: (f32, f32),
+    pub green_primary: (f32, f32),
+    pub blue_primary: (f32, f32),
+    pub white_point: (f32, f32),
+    pub max_luminance: f32,
+    pub min_luminance: f32,
+    //MaxCLL, the brightest pixel of the content
+    pub max_content_light_level: f32,
+    //MaxFALL, the brightest frame of the content on average
+    pub max_frame_average_light_level: f32,
+}
+
+impl HdrMetadata {
+    //A mastering display with BT.2020 primaries and a D65 white point, as for HDR10
+    pub fn bt2020(max_luminance: f32, min_luminance: f32) -> Self {
+        Self {
+            red_primary: (0.708, 0.292),
+            green_primary: (0.170, 0.797),
+            blue_primary: (0.131, 0.046),
+            white_point: (0.3127, 0.3290),
+            max_luminance,
+            min_luminance,
+            max_content_light_level: max_luminance,
+            max_frame_average_light_level: max_luminance,
+        }
+    }
+
+    pub(crate) fn to_vk(self) -> vk::HdrMetadata {
+        let xy = |(x, y): (f32, f32)| vk::XyColor { x, y };
+
+        vk::HdrMetadata {
+            display_primary_red: xy(self.red_primary),
+            display_primary_green: xy(self.green_primary),
+            display_primary_blue: xy(self.blue_primary),
+            white_point: xy(self.white_point),
+            max_luminance: self.max_luminance,
+            min_luminance: self.min_luminance,
+            max_content_light_level: self.max_content_light_level,
+            max_frame_average_light_level: self.max_frame_average_light_level,
+        }
+    }
+}
+
+pub struct GammaEncodePassInfo<'a> {
+    pub device: &'a Device,
+    //must be a UNORM format, sRGB targets encode in hardware already
+    pub format: Format,
+    pub extent: (u32, u32),
+    pub descriptor_set_count: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct GammaEncodeConstants {
+    resolution: [f32; 2],
+}
+
+unsafe impl Pod for GammaEncodeConstants {}
+
+//Encodes a linear image into a UNORM target with the sRGB transfer function.
+//Only needed on the OutputEncoding::Manual path when the earlier passes wrote linear color.
+pub struct GammaEncodePass {
+    pass: PostProcessPass,
+    extent: (u32, u32),
+}
+
+impl GammaEncodePass {
+    pub fn new(info: GammaEncodePassInfo<'_>) -> Self {
+        if OutputEncoding::of(info.format) != OutputEncoding::Manual {
+            panic!("gamma encoding into an srgb target would encode twice");
+        }
+
+        let vertex_shader = Shader::new(ShaderInfo {
+            device: info.device,
+            entry: "main",
+            input: ShaderInput::Embedded {
+                name: "fullscreen.vert",
+                source: include_str!("shaders/fullscreen.vert.glsl"),
+            },
+        });
+
+        let fragment_shader = Shader::new(ShaderInfo {
+            device: info.device,
+            entry: "main",
+            input: ShaderInput::Embedded {
+                name: "gamma_encode.frag",
+                source: include_str!("shaders/gamma_encode.frag.glsl"),
+            },
+        });
+
+        let pass = PostProcessPass::new(PostProcessInfo {
+            device: info.device,
+            vertex_shader: &vertex_shader,
+            fragment_shader: &fragment_shader,
+            format: info.format,
+            extent: info.extent,
+            descriptor_set_count: info.descriptor_set_count,
+            push_constant_size: mem::size_of::<GammaEncodeConstants>() as _,
+            layout: &[],
+        });
+
+        Self {
+            pass,
+            extent: info.extent,
+        }
+    }
+
+    pub fn render_pass(&self) -> &RenderPass {
+        self.pass.render_pass()
+    }
+
+    pub fn record(
+        &mut self,
+        commands: &mut Commands<'_>,
+        image_index: u32,
+        linear: &Image,
+        output: &Framebuffer,
+    ) -> Result<(), Error> {
+        let constants = GammaEncodeConstants {
+            resolution: [self.extent.0 as f32, self.extent.1 as f32],
+        };
+
+        self.pass.record(
+            commands,
+            image_index,
+            linear,
+            output,
+            &[],
+            as_bytes(&constants),
+        )
+    }
+}
@@ -0,0 +1,50 @@
+use crate::prelude::*;
+
+pub(crate) fn storage_descriptor(binding: u32) -> Descriptor {
+    Descriptor {
+        binding,
+        ty: DescriptorType::StorageBuffer,
+        count: 1,
+        stage: ShaderStage::COMPUTE,
+    }
+}
+
+pub(crate) fn storage_binding(binding: u32, buffer: &Buffer, range: usize) -> Binding<'_> {
+    Binding::Buffer {
+        binding,
+        ty: DescriptorType::StorageBuffer,
+        offset: 0,
+        range,
+        buffer,
+    }
+}
+
+pub(crate) fn compute_pipeline(
+    device: &Device,
+    name: &'static str,
+    source: &'static str,
+    descriptor_set_count: u32,
+    layout: &'_ [Descriptor],
+    push_constant_size: u32,
+) -> Pipeline {
+    let shader = Shader::new(ShaderInfo {
+        device,
+        entry: "main",
+        input: ShaderInput::Embedded { name, source },
+    });
+
+    Pipeline::new_compute_pipeline(ComputePipelineInfo {
+        device,
+        descriptor_set_count,
+        shader: &shader,
+        layout,
+        push_constants: &[PushConstantRange {
+            stage: ShaderStage::COMPUTE,
+            offset: 0,
+            size: push_constant_size,
+        }],
+        material_table: None,
+        immutable_samplers: &[],
+        cache: None,
+    })
+}
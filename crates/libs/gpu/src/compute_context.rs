@@ -0,0 +1,163 @@
+use crate::prelude::*;
+
+//Headless compute with no surface or swapchain involved. Wires up an instance without
+//surface extensions and a device whose queue handles compute, then runs one-shot
+//dispatches that block on a fence until the gpu is done.
+pub struct ComputeContext {
+    //descriptor sets are never returned to the device's pool, so pipelines are reused
+    //per shader and binding layout rather than created for every dispatch
+    pipelines: PipelineStateCache,
+    command_buffer: vk::CommandBuffer,
+    fence: vk::Fence,
+    //never read, keeps the pool alive for command_buffer which is allocated from it
+    _command_pool: vk::CommandPool,
+    device: Device,
+    context: Context,
+}
+
+impl Default for ComputeContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ComputeContext {
+    pub fn new() -> Self {
+        let context = Context::start_headless();
+
+        let device = Device::choose_best(DeviceInfo {
+            context: &context,
+            #[cfg(feature = "swapchain")]
+            surface: None,
+        });
+
+        let Device::Vulkan {
+            device: vk_device,
+            queue_family_index,
+            ..
+        } = &device
+        else {
+            panic!("not a vulkan device");
+        };
+
+        let command_pool_create_info = vk::CommandPoolCreateInfo {
+            flags: vk::COMMAND_POOL_CREATE_RESET_COMMAND_BUFFER,
+            queue_family_index: *queue_family_index,
+        };
+
+        let command_pool = vk::CommandPool::new(vk_device.clone(), command_pool_create_info)
+            .expect("failed to create command pool");
+
+        let command_buffer_allocate_info = vk::CommandBufferAllocateInfo {
+            command_pool: &command_pool,
+            level: vk::CommandBufferLevel::Primary,
+            count: 1,
+        };
+
+        let command_buffer =
+            vk::CommandBuffer::allocate(vk_device.clone(), command_buffer_allocate_info)
+                .expect("failed to create command buffer")
+                .remove(0);
+
+        let fence = vk::Fence::new(vk_device.clone(), vk::FenceCreateInfo {})
+            .expect("failed to create fence");
+
+        Self {
+            pipelines: PipelineStateCache::new(),
+            command_buffer,
+            fence,
+            _command_pool: command_pool,
+            device,
+            context,
+        }
+    }
+
+    //Runs shader over groups workgroups with bindings in set 0 and returns once the gpu
+    //has finished, with the writes visible to host reads of mapped buffers
+    pub fn dispatch(&mut self, shader: &Shader, bindings: &[Binding<'_>], groups: (u32, u32, u32)) {
+        let layout = bindings
+            .iter()
+            .map(|binding| {
+                let (Binding::Buffer { binding, ty, .. } | Binding::Image { binding, ty, .. }) =
+                    *binding;
+
+                Descriptor {
+                    binding,
+                    ty,
+                    count: 1,
+                    stage: ShaderStage::COMPUTE,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let pipeline = self.pipelines.compute(ComputePipelineInfo {
+            device: &self.device,
+            descriptor_set_count: 1,
+            shader,
+            layout: &layout,
+            push_constants: &[],
+            material_table: None,
+            immutable_samplers: &[],
+            cache: None,
+        });
+
+        //the last dispatch has finished with the set, see the fence wait below
+        pipeline.bind(0, bindings);
+
+        let pipeline = &*pipeline;
+
+        self.command_buffer
+            .record(|commands| {
+                let mut commands = Commands::Vulkan { commands };
+
+                commands.bind_pipeline(0, pipeline);
+                commands.dispatch(groups.0, groups.1, groups.2);
+
+                commands.pipeline_barrier(
+                    PipelineStage::COMPUTE_SHADER,
+                    PipelineStage::HOST,
+                    &[Barrier::Memory {
+                        src_access: Access::SHADER_WRITE,
+                        dst_access: Access::HOST_READ,
+                    }],
+                );
+            })
+            .expect("failed to record compute command buffer");
+
+        #[cfg(feature = "barrier-validation")]
+        crate::device::report_hazards(&self.command_buffer);
+
+        let Device::Vulkan { device, queues, .. } = &mut self.device else {
+            panic!("not a vulkan device");
+        };
+
+        vk::Fence::reset(&[&mut self.fence]).expect("failed to reset fence");
+
+        let submit_info = vk::SubmitInfo {
+            wait_semaphores: &[],
+            wait_stages: &[],
+            command_buffers: &[&self.command_buffer],
+            signal_semaphores: &[],
+            protected: false,
+        };
+
+        queues[0]
+            .submit(&[submit_info], Some(&mut self.fence))
+            .expect("failed to submit compute command buffer");
+
+        vk::Fence::wait(&[&mut self.fence], true, u64::MAX).expect("failed to wait for fence");
+    }
+
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    //For creating buffers, images and shaders
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    pub fn device_mut(&mut self) -> &mut Device {
+        &mut self.device
+    }
+}
@@ -1,5 +1,6 @@
 use std::rc::Rc;
 
+#[cfg(all(debug_assertions, feature = "debug-utils"))]
 use log::{error, info, trace, warn};
 
 #[non_exhaustive]
@@ -8,12 +9,23 @@ pub enum Context {
         instance: Rc<vk::Instance>,
         layers: Vec<&'static str>,
         extensions: Vec<&'static str>,
-        #[cfg(debug_assertions)]
+        #[cfg(all(debug_assertions, feature = "debug-utils"))]
         debug: vk::DebugUtilsMessenger,
     },
 }
 
+#[cfg(all(debug_assertions, feature = "debug-utils"))]
 fn debug_utils_messenger_callback(data: &vk::DebugUtilsMessengerCallbackData) -> bool {
+    //debugPrintfEXT output, tagged with the innermost label, e.g. from Commands::dispatch_labelled
+    #[cfg(feature = "debug-printf")]
+    if data.message_id_name.contains("DEBUG-PRINTF") {
+        let label = data.labels.last().map_or("unlabelled", String::as_str);
+
+        info!(target: "shader_printf", "[{}] {}", label, data.message);
+
+        return false;
+    }
+
     match data.message_severity {
         vk::DEBUG_UTILS_MESSAGE_SEVERITY_VERBOSE => trace!("{}\n", data.message),
         vk::DEBUG_UTILS_MESSAGE_SEVERITY_INFO => info!("{}\n", data.message),
@@ -27,38 +39,121 @@ fn debug_utils_messenger_callback(data: &vk::DebugUtilsMessengerCallbackData) ->
 
 impl Context {
     pub fn start() -> Self {
-        Self::new_vulkan()
+        Self::new_vulkan(true)
+    }
+
+    //Without surface extensions even when the swapchain feature is enabled, for devices
+    //created with no surface
+    pub fn start_headless() -> Self {
+        Self::new_vulkan(false)
     }
 
-    fn new_vulkan() -> Self {
+    //Major, minor and patch of the instance, at most what the loader supports
+    pub fn api_version(&self) -> (u32, u32, u32) {
+        let Self::Vulkan { instance, .. } = self;
+
+        let version = instance.api_version();
+
+        (version.major(), version.minor(), version.patch())
+    }
+
+    #[cfg_attr(not(feature = "swapchain"), allow(unused_variables))]
+    fn new_vulkan(surfaces: bool) -> Self {
         let application_info = vk::ApplicationInfo {
             application_name: "Octane",
             application_version: (0, 1, 0).into(),
             engine_name: "Octane",
             engine_version: (0, 1, 0).into(),
-            api_version: (1, 0, 0).into(),
+            //clamped below, probes and protected memory need 1.1
+            api_version: (1, 1, 0).into(),
         };
 
-        let mut extensions = vec![vk::KHR_SURFACE];
+        //compute only builds need no instance extensions or layers
+        #[allow(unused_mut)]
+        let mut extensions = vec![];
+        #[allow(unused_mut)]
         let mut layers = vec![];
+        #[allow(unused_mut)]
+        let mut flags = 0;
 
-        #[cfg(target_os = "windows")]
+        //1.0 loaders clamp the instance below, the feature probes then go through the extension
+        if vk::Instance::supported_version().map_or(true, |version| version < (1, 1, 0).into())
+            && vk::Instance::available_extensions()
+                .iter()
+                .any(|s| s == vk::KHR_GET_PHYSICAL_DEVICE_PROPERTIES_2)
         {
-            extensions.push(vk::KHR_WIN32_SURFACE);
+            extensions.push(vk::KHR_GET_PHYSICAL_DEVICE_PROPERTIES_2);
         }
-        
-        #[cfg(target_os = "linux")]
+
+        //MoltenVK is a portability driver, only listed when asked for
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        if vk::Instance::available_extensions()
+            .iter()
+            .any(|s| s == vk::KHR_PORTABILITY_ENUMERATION)
         {
-            extensions.push(vk::KHR_XLIB_SURFACE);
+            extensions.push(vk::KHR_PORTABILITY_ENUMERATION);
+            flags |= vk::INSTANCE_CREATE_ENUMERATE_PORTABILITY;
         }
 
+        #[cfg(feature = "swapchain")]
+        if surfaces {
+            extensions.push(vk::KHR_SURFACE);
+
+            #[cfg(target_os = "windows")]
+            extensions.push(vk::KHR_WIN32_SURFACE);
+
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            extensions.push(vk::EXT_METAL_SURFACE);
+
+            //the window may be on any of these, not every driver provides all of them
+            #[cfg(target_os = "linux")]
+            {
+                let available_extensions = vk::Instance::available_extensions();
+
+                for extension in [
+                    vk::KHR_XLIB_SURFACE,
+                    vk::KHR_XCB_SURFACE,
+                    vk::KHR_WAYLAND_SURFACE,
+                ] {
+                    if available_extensions.iter().any(|s| s == extension) {
+                        extensions.push(extension);
+                    }
+                }
+            }
+
+            //present scaling queries, and present fences on devices that support them
+            let available_extensions = vk::Instance::available_extensions();
+
+            let surface_maintenance1 = [
+                vk::KHR_GET_SURFACE_CAPABILITIES_2,
+                vk::EXT_SURFACE_MAINTENANCE_1,
+            ];
+
+            if surface_maintenance1
+                .iter()
+                .all(|extension| available_extensions.iter().any(|s| s == extension))
+            {
+                extensions.extend(surface_maintenance1);
+            }
+        }
+
+        #[allow(unused_mut)]
         let mut debug_utils_messenger_create_info = None;
+        #[allow(unused_mut)]
+        let mut validation_features = vec![];
 
-        #[cfg(debug_assertions)]
+        #[cfg(all(debug_assertions, feature = "debug-utils"))]
         {
             extensions.push(vk::EXT_DEBUG_UTILS);
             layers.push(vk::LAYER_KHRONOS_VALIDATION);
 
+            //provided by the validation layer itself
+            #[cfg(feature = "debug-printf")]
+            {
+                extensions.push(vk::EXT_VALIDATION_FEATURES);
+                validation_features.push(vk::ValidationFeature::DebugPrintf);
+            }
+
             debug_utils_messenger_create_info = Some(vk::DebugUtilsMessengerCreateInfo {
                 message_severity: vk::DEBUG_UTILS_MESSAGE_SEVERITY_VERBOSE
                     | vk::DEBUG_UTILS_MESSAGE_SEVERITY_INFO
@@ -72,15 +167,18 @@ impl Context {
         }
 
         let instance_create_info = vk::InstanceCreateInfo {
+            flags,
             application_info: &application_info,
+            api_version_policy: vk::ApiVersionPolicy::ClampToSupported,
             extensions: &extensions[..],
             layers: &layers[..],
             debug_utils: &debug_utils_messenger_create_info,
+            validation_features: &validation_features,
         };
 
         let instance = vk::Instance::new(instance_create_info).expect("failed to create instance");
 
-        #[cfg(debug_assertions)]
+        #[cfg(all(debug_assertions, feature = "debug-utils"))]
         let debug = vk::DebugUtilsMessenger::new(
             instance.clone(),
             debug_utils_messenger_create_info.unwrap(),
@@ -91,7 +189,7 @@ impl Context {
             instance,
             layers,
             extensions,
-            #[cfg(debug_assertions)]
+            #[cfg(all(debug_assertions, feature = "debug-utils"))]
             debug,
         }
     }
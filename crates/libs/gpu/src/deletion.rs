@@ -0,0 +1,110 @@
+use std::any::Any;
+use std::collections::VecDeque;
+
+//Objects replaced while the gpu may still be using them, e.g. the framebuffers, views and
+//swapchain of an earlier swapchain generation. Each is dropped once a submission made after
+//it was retired has finished, see Device::retire. Retiring usually follows a synchronize,
+//so the submissions before it are done while a present of them may not be.
+#[derive(Default)]
+pub struct DeletionQueue {
+    //submissions made so far and how many of them are known to have finished
+    submitted: u64,
+    completed: u64,
+    //in the order they were retired, so also by the submission they wait for
+    retired: VecDeque<(u64, Box<dyn Any>)>,
+}
+
+impl DeletionQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn retire(&mut self, object: impl Any) {
+        self.retired.push_back((self.submitted + 1, Box::new(object)));
+    }
+
+    //Objects still waiting on a submission
+    pub fn len(&self) -> usize {
+        self.retired.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.retired.is_empty()
+    }
+
+    pub(crate) fn submitted(&mut self) {
+        self.submitted += 1;
+    }
+
+    //Every submission so far has finished
+    pub(crate) fn completed(&mut self) {
+        self.completed = self.submitted;
+
+        while let Some((submission, _)) = self.retired.front() {
+            if *submission > self.completed {
+                break;
+            }
+
+            self.retired.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::rc::Rc;
+
+    #[test]
+    fn retired_object_waits_for_next_submission() {
+        let mut queue = DeletionQueue::new();
+        let object = Rc::new(());
+
+        queue.submitted();
+        queue.retire(object.clone());
+
+        //the submission before retiring finished, one made after it was not yet made
+        queue.completed();
+
+        assert_eq!(queue.len(), 1);
+        assert_eq!(Rc::strong_count(&object), 2);
+    }
+
+    #[test]
+    fn retired_object_is_released_when_its_submission_completes() {
+        let mut queue = DeletionQueue::new();
+        let object = Rc::new(());
+
+        queue.retire(object.clone());
+        queue.submitted();
+
+        assert_eq!(Rc::strong_count(&object), 2);
+
+        queue.completed();
+
+        assert!(queue.is_empty());
+        assert_eq!(Rc::strong_count(&object), 1);
+    }
+
+    #[test]
+    fn objects_are_released_in_submission_order() {
+        let mut queue = DeletionQueue::new();
+        let first = Rc::new(());
+        let second = Rc::new(());
+
+        queue.retire(first.clone());
+        queue.submitted();
+        queue.retire(second.clone());
+        queue.completed();
+
+        assert_eq!(Rc::strong_count(&first), 1);
+        assert_eq!(Rc::strong_count(&second), 2);
+
+        queue.submitted();
+        queue.completed();
+
+        assert!(queue.is_empty());
+        assert_eq!(Rc::strong_count(&second), 1);
+    }
+}
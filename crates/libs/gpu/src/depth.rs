@@ -0,0 +1,55 @@
+use crate::prelude::*;
+
+//Standard maps near to 0.0 and far to 1.0. ReversedZ maps near to 1.0 and
+//far to 0.0, which spreads float precision evenly over distance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DepthConfig {
+    Standard,
+    ReversedZ,
+}
+
+impl DepthConfig {
+    pub fn format(self) -> Format {
+        Format::D32Sfloat
+    }
+
+    pub fn compare_op(self) -> CompareOp {
+        match self {
+            Self::Standard => CompareOp::LessOrEqual,
+            Self::ReversedZ => CompareOp::GreaterOrEqual,
+        }
+    }
+
+    pub fn clear_value(self) -> f32 {
+        match self {
+            Self::Standard => 1.0,
+            Self::ReversedZ => 0.0,
+        }
+    }
+
+    pub fn depth_stencil_clear_value(self) -> Option<(f32, u32)> {
+        Some((self.clear_value(), 0))
+    }
+
+    pub fn depth_stencil(self, write: bool) -> DepthStencil {
+        DepthStencil {
+            test: true,
+            write,
+            compare_op: self.compare_op(),
+            stencil: None,
+        }
+    }
+
+    pub fn attachment(self, load_op: AttachmentLoadOp, store_op: AttachmentStoreOp) -> Attachment {
+        Attachment {
+            format: self.format(),
+            samples: SampleCount::One,
+            load_op,
+            store_op,
+            initial_layout: ImageLayout::Undefined,
+            final_layout: ImageLayout::DepthStencilAttachment,
+            layout: ImageLayout::DepthStencilAttachment,
+            ty: AttachmentType::DepthStencil,
+        }
+    }
+}
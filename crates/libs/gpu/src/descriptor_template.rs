@@ -0,0 +1,75 @@
+use crate::prelude::*;
+
+use std::marker::PhantomData;
+use std::mem;
+
+pub use vk::{DescriptorData, DescriptorTemplateData};
+
+pub struct DescriptorTemplateInfo<'a> {
+    pub device: &'a Device,
+    pub pipeline: &'a Pipeline,
+    //the layout the pipeline was created with
+    pub layout: &'a [Descriptor],
+}
+
+//Writes a whole descriptor set of a pipeline from one T with Device::update_with_template,
+//cheaper than Pipeline::bind for sets rewritten every frame. T is a repr(C) struct or array
+//with one DescriptorData per descriptor of the layout in binding order, made with
+//Buffer::descriptor and Image::descriptor.
+pub enum DescriptorTemplate<T: DescriptorTemplateData> {
+    Vulkan {
+        template: vk::DescriptorUpdateTemplate,
+        marker: PhantomData<T>,
+    },
+}
+
+impl<T: DescriptorTemplateData> DescriptorTemplate<T> {
+    pub fn new(info: DescriptorTemplateInfo<'_>) -> Self {
+        match info.device {
+            Device::Vulkan { device, .. } => {
+                let Pipeline::Vulkan {
+                    descriptor_set_layout,
+                    ..
+                } = info.pipeline
+                else {
+                    panic!("not a vulkan pipeline");
+                };
+
+                let bindings = info
+                    .layout
+                    .iter()
+                    .map(|descriptor| vk::DescriptorSetLayoutBinding {
+                        binding: descriptor.binding,
+                        descriptor_type: descriptor.ty.into(),
+                        descriptor_count: descriptor.count,
+                        stage: descriptor.stage.to_vk(),
+                    })
+                    .collect::<Vec<_>>();
+
+                let entries = vk::DescriptorUpdateTemplateEntry::sequential(&bindings);
+
+                let template = vk::DescriptorUpdateTemplate::new(
+                    device.clone(),
+                    vk::DescriptorUpdateTemplateCreateInfo {
+                        descriptor_set_layout,
+                        entries: &entries,
+                    },
+                )
+                .expect("failed to create descriptor update template");
+
+                if mem::size_of::<T>() != template.data_size() {
+                    panic!(
+                        "descriptor template data is {} bytes, the layout needs {}",
+                        mem::size_of::<T>(),
+                        template.data_size()
+                    );
+                }
+
+                Self::Vulkan {
+                    template,
+                    marker: PhantomData,
+                }
+            }
+        }
+    }
+}
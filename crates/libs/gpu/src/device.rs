@@ -1,7 +1,9 @@
 pub use crate::prelude::*;
 
+use std::any::Any;
 use std::cmp;
 use std::mem;
+use std::ops::Range;
 use std::rc::Rc;
 use std::cell::RefCell;
 
@@ -9,22 +11,40 @@ use log::{error, info, trace, warn};
 
 pub struct DeviceInfo<'a> {
     pub context: &'a Context,
-    pub surface: &'a Surface,
+    //the chosen queue must be able to present to it, None for a headless device
+    #[cfg(feature = "swapchain")]
+    pub surface: Option<&'a Surface>,
 }
 
+//Order independent transparency either resolves in place under fragment shader interlock
+//or falls back to per pixel fragment lists in storage buffers
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TransparencyPath {
+    Interlock,
+    ABuffer,
+}
+
+//Thread affine. Every object created from a Device shares its vk::Device through an Rc,
+//so none of them are Send and recording, submission and present all stay on the
+//thread that created the Device.
 #[non_exhaustive]
 pub enum Device {
     Vulkan {
         instance: Rc<vk::Instance>,
         physical_device: Rc<vk::PhysicalDevice>,
         device: Rc<vk::Device>,
+        queue_family_index: u32,
         queues: Vec<vk::Queue>,
+        //one of every other family when created for a surface, to present the swapchains of
+        //surfaces the family of queues cannot present to
+        present_queues: Vec<(u32, vk::Queue)>,
         command_pool: vk::CommandPool,
         command_buffer: vk::CommandBuffer,
         descriptor_pool: vk::DescriptorPool,
         image_available_semaphore: Rc<RefCell<vk::Semaphore>>,
         render_finished_semaphore: Rc<RefCell<vk::Semaphore>>,
         in_flight_fence: vk::Fence,
+        deletion_queue: RefCell<DeletionQueue>,
     },
 }
 
@@ -34,9 +54,16 @@ impl Device {
             Context::Vulkan {
                 instance, layers, ..
             } => {
+                let required_features = vk::PhysicalDeviceFeatures {
+                    shader_int_64: true,
+                    multi_draw_indirect: true,
+                    ..Default::default()
+                };
+
                 let physical_device = {
                     let mut candidates = vk::PhysicalDevice::enumerate(instance.clone())
                         .into_iter()
+                        .filter(|x| x.supports(&required_features))
                         .map(|x| (0, x.properties(), x)) // suitability of 0, pd properties, pd
                         .collect::<Vec<_>>();
 
@@ -78,15 +105,14 @@ impl Device {
                     if queue_family.queue_flags & vk::QUEUE_COMPUTE == 0 {
                         continue;
                     }
-                    if let Surface::Vulkan { surface, .. } = info.surface {
+                    #[cfg(feature = "swapchain")]
+                    if let Some(Surface::Vulkan { surface, .. }) = info.surface {
                         if !physical_device
                             .surface_supported(&surface, i as _)
                             .expect("failed to query surface support")
                         {
                             continue;
                         }
-                    } else {
-                        panic!("not a vulkan surface");
                     }
                     queue_family_index = Some(i as u32);
                     break;
@@ -95,19 +121,255 @@ impl Device {
                 let queue_family_index = queue_family_index.expect("failed to find suitable queue");
 
                 let queue_create_info = vk::DeviceQueueCreateInfo {
+                    flags: 0,
                     queue_family_index,
                     queue_priorities: &[1.0],
                 };
 
+                #[allow(unused_mut)]
+                let mut present_queue_families = vec![];
+
+                //other windows of a multi-viewport editor may only be presentable from them
+                #[cfg(feature = "swapchain")]
+                if info.surface.is_some() {
+                    present_queue_families.extend(
+                        (0..queue_families.len() as u32).filter(|&i| i != queue_family_index),
+                    );
+                }
+
+                let queue_create_infos = [queue_family_index]
+                    .into_iter()
+                    .chain(present_queue_families.iter().copied())
+                    .map(|queue_family_index| vk::DeviceQueueCreateInfo {
+                        queue_family_index,
+                        ..queue_create_info
+                    })
+                    .collect::<Vec<_>>();
+
+                let supported_features = physical_device.features();
+
                 let physical_device_features = vk::PhysicalDeviceFeatures {
-                    shader_int_64: true,
-                    ..Default::default()
+                    wide_lines: supported_features.wide_lines,
+                    depth_clamp: supported_features.depth_clamp,
+                    sampler_anisotropy: supported_features.sampler_anisotropy,
+                    ..required_features
                 };
 
+                let supported_extensions = physical_device.extensions();
+
+                let mut extensions = vec![];
+
+                #[cfg(feature = "swapchain")]
+                if info.surface.is_some() {
+                    extensions.push(vk::KHR_SWAPCHAIN);
+                }
+
+                //debugPrintfEXT compiles to non semantic instructions
+                #[cfg(feature = "debug-printf")]
+                if supported_extensions
+                    .iter()
+                    .any(|s| s == vk::KHR_SHADER_NON_SEMANTIC_INFO)
+                {
+                    extensions.push(vk::KHR_SHADER_NON_SEMANTIC_INFO);
+                }
+
+                //must be enabled wherever the implementation is not fully conformant, e.g. MoltenVK
+                if supported_extensions
+                    .iter()
+                    .any(|s| s == vk::KHR_PORTABILITY_SUBSET)
+                {
+                    extensions.push(vk::KHR_PORTABILITY_SUBSET);
+                }
+
+                //with their dependencies, the instance side of which is core in 1.1
+                let dma_buf_extensions = [
+                    vk::KHR_EXTERNAL_MEMORY,
+                    vk::KHR_GET_MEMORY_REQUIREMENTS_2,
+                    vk::KHR_DEDICATED_ALLOCATION,
+                    vk::KHR_BIND_MEMORY_2,
+                    vk::KHR_MAINTENANCE_1,
+                    vk::KHR_SAMPLER_YCBCR_CONVERSION,
+                    vk::KHR_IMAGE_FORMAT_LIST,
+                    vk::KHR_EXTERNAL_MEMORY_FD,
+                    vk::EXT_EXTERNAL_MEMORY_DMA_BUF,
+                    vk::EXT_IMAGE_DRM_FORMAT_MODIFIER,
+                ];
+
+                if instance.api_version() >= (1, 1, 0).into()
+                    && dma_buf_extensions
+                        .iter()
+                        .all(|extension| supported_extensions.iter().any(|s| s == extension))
+                {
+                    extensions.extend(dma_buf_extensions);
+                }
+
+                let vertex_attribute_divisor = physical_device.vertex_attribute_divisor_supported();
+
+                if vertex_attribute_divisor {
+                    extensions.push(vk::EXT_VERTEX_ATTRIBUTE_DIVISOR);
+                }
+
+                let line_rasterization = physical_device.line_rasterization_features();
+
+                if line_rasterization.rectangular_lines
+                    || line_rasterization.bresenham_lines
+                    || line_rasterization.smooth_lines
+                {
+                    extensions.push(vk::EXT_LINE_RASTERIZATION);
+                }
+
+                let shader_atomic_float = physical_device.shader_atomic_float_features();
+
+                if shader_atomic_float.any() {
+                    extensions.push(vk::EXT_SHADER_ATOMIC_FLOAT);
+                }
+
+                let depth_clip_enable = physical_device.depth_clip_enable_supported();
+
+                if depth_clip_enable {
+                    extensions.push(vk::EXT_DEPTH_CLIP_ENABLE);
+                }
+
+                if supported_extensions
+                    .iter()
+                    .any(|s| s == vk::EXT_DEPTH_RANGE_UNRESTRICTED)
+                {
+                    extensions.push(vk::EXT_DEPTH_RANGE_UNRESTRICTED);
+                }
+
+                if supported_extensions
+                    .iter()
+                    .any(|s| s == vk::EXT_SHADER_VIEWPORT_INDEX_LAYER)
+                {
+                    extensions.push(vk::EXT_SHADER_VIEWPORT_INDEX_LAYER);
+                }
+
+                let descriptor_indexing = physical_device.descriptor_indexing_features();
+
+                if descriptor_indexing.any() {
+                    extensions.push(vk::KHR_MAINTENANCE_3);
+                    extensions.push(vk::EXT_DESCRIPTOR_INDEXING);
+                }
+
+                let fragment_shader_interlock =
+                    physical_device.fragment_shader_interlock_supported();
+
+                if fragment_shader_interlock {
+                    extensions.push(vk::EXT_FRAGMENT_SHADER_INTERLOCK);
+                }
+
+                let performance_query = physical_device.performance_query_supported();
+
+                if performance_query {
+                    extensions.push(vk::KHR_PERFORMANCE_QUERY);
+                }
+
+                let memory_priority = physical_device.memory_priority_supported();
+
+                if memory_priority {
+                    extensions.push(vk::EXT_MEMORY_PRIORITY);
+                }
+
+                let pageable_device_local_memory =
+                    memory_priority && physical_device.pageable_device_local_memory_supported();
+
+                if pageable_device_local_memory {
+                    extensions.push(vk::EXT_PAGEABLE_DEVICE_LOCAL_MEMORY);
+                }
+
+                let pipeline_executable_info = physical_device.pipeline_executable_info_supported();
+
+                if pipeline_executable_info {
+                    extensions.push(vk::KHR_PIPELINE_EXECUTABLE_PROPERTIES);
+                }
+
+                let synchronization2 = physical_device.synchronization2_supported();
+
+                if synchronization2 {
+                    extensions.push(vk::KHR_SYNCHRONIZATION_2);
+                }
+
+                let dynamic_rendering_dependencies = [
+                    vk::KHR_MULTIVIEW,
+                    vk::KHR_MAINTENANCE_2,
+                    vk::KHR_CREATE_RENDERPASS_2,
+                    vk::KHR_DEPTH_STENCIL_RESOLVE,
+                ];
+
+                let dynamic_rendering = physical_device.dynamic_rendering_supported()
+                    && dynamic_rendering_dependencies
+                        .iter()
+                        .all(|&dependency| supported_extensions.iter().any(|s| s == dependency));
+
+                if dynamic_rendering {
+                    extensions.extend(dynamic_rendering_dependencies);
+                    extensions.push(vk::KHR_DYNAMIC_RENDERING);
+                }
+
+                let inherited_viewport_scissor = physical_device.inherited_viewport_scissor_supported();
+
+                let sampler_ycbcr_conversion = physical_device.sampler_ycbcr_conversion_supported();
+
+                if inherited_viewport_scissor {
+                    extensions.push(vk::NV_INHERITED_VIEWPORT_SCISSOR);
+                }
+
+                if supported_extensions
+                    .iter()
+                    .any(|s| s == vk::KHR_DRAW_INDIRECT_COUNT)
+                {
+                    extensions.push(vk::KHR_DRAW_INDIRECT_COUNT);
+                }
+
+                //depends on KHR_SWAPCHAIN
+                #[cfg(feature = "swapchain")]
+                if info.surface.is_some()
+                    && supported_extensions
+                        .iter()
+                        .any(|s| s == vk::EXT_HDR_METADATA)
+                {
+                    extensions.push(vk::EXT_HDR_METADATA);
+                }
+
+                //present fences, depends on KHR_SWAPCHAIN and EXT_SURFACE_MAINTENANCE_1
+                #[cfg(feature = "swapchain")]
+                let swapchain_maintenance1 = info.surface.is_some()
+                    && instance.is_extension_enabled(vk::EXT_SURFACE_MAINTENANCE_1)
+                    && physical_device.swapchain_maintenance1_supported();
+                #[cfg(not(feature = "swapchain"))]
+                let swapchain_maintenance1 = false;
+
+                if swapchain_maintenance1 {
+                    extensions.push(vk::EXT_SWAPCHAIN_MAINTENANCE_1);
+                }
+
+                let buffer_device_address = physical_device.buffer_device_address_supported();
+
+                if buffer_device_address {
+                    extensions.push(vk::KHR_BUFFER_DEVICE_ADDRESS);
+                }
+
                 let device_create_info = vk::DeviceCreateInfo {
-                    queues: &[queue_create_info],
+                    queues: &queue_create_infos,
                     enabled_features: &physical_device_features,
-                    extensions: &[vk::KHR_SWAPCHAIN],
+                    protected_memory: false,
+                    vertex_attribute_divisor,
+                    line_rasterization,
+                    shader_atomic_float,
+                    depth_clip_enable,
+                    fragment_shader_interlock,
+                    descriptor_indexing,
+                    performance_query,
+                    memory_priority,
+                    pageable_device_local_memory,
+                    pipeline_executable_info,
+                    synchronization2,
+                    dynamic_rendering,
+                    inherited_viewport_scissor,
+                    sampler_ycbcr_conversion,
+                    swapchain_maintenance1,
+                    buffer_device_address,
+                    extensions: &extensions,
                     layers: &layers[..],
                 };
 
@@ -118,7 +380,15 @@ impl Device {
 
                 let queues = vec![queue];
 
-                let command_pool_create_info = vk::CommandPoolCreateInfo { queue_family_index };
+                let present_queues = present_queue_families
+                    .into_iter()
+                    .map(|family| (family, device.queue(family)))
+                    .collect();
+
+                let command_pool_create_info = vk::CommandPoolCreateInfo {
+                    flags: vk::COMMAND_POOL_CREATE_RESET_COMMAND_BUFFER,
+                    queue_family_index,
+                };
 
                 let command_pool = vk::CommandPool::new(device.clone(), command_pool_create_info)
                     .expect("failed to create command pool");
@@ -140,6 +410,11 @@ impl Device {
             descriptor_count: count,
         };
 
+        let dynamic_uniform_buffer_pool_size = vk::DescriptorPoolSize {
+            descriptor_type: vk::DescriptorType::UniformBufferDynamic,
+            descriptor_count: count,
+        };
+
         let storage_buffer_pool_size = vk::DescriptorPoolSize {
             descriptor_type: vk::DescriptorType::StorageBuffer,
             descriptor_count: count,
@@ -156,9 +431,11 @@ impl Device {
         };
 
         let descriptor_pool_create_info = vk::DescriptorPoolCreateInfo {
+            flags: 0,
             max_sets: count,
             pool_sizes: &[
                 uniform_buffer_pool_size,
+                dynamic_uniform_buffer_pool_size,
                 storage_buffer_pool_size,
                 storage_image_pool_size,
                 sampler_pool_size,
@@ -194,13 +471,16 @@ impl Device {
                     instance: instance.clone(),
                     physical_device,
                     device,
+                    queue_family_index,
                     queues,
+                    present_queues,
                     command_pool,
                     command_buffer,
                     descriptor_pool,
                     image_available_semaphore,
                     render_finished_semaphore,
                     in_flight_fence,
+                    deletion_queue: RefCell::new(DeletionQueue::new()),
                 }
             }
         }
@@ -228,6 +508,7 @@ impl Device {
                     wait_stages: &[],
                     command_buffers: &[&command_buffer],
                     signal_semaphores: &[],
+                    protected: false,
                 };
 
                 queues[0]
@@ -326,6 +607,7 @@ impl Device {
                     wait_stages: &[],
                     command_buffers: &[&command_buffer],
                     signal_semaphores: &[],
+                    protected: false,
                 };
 
                 queues[0]
@@ -337,203 +619,937 @@ impl Device {
         }
     }
 
-    pub fn synchronize(&mut self) {
-        match self {
-            Device::Vulkan { in_flight_fence, .. } => {
-                vk::Fence::wait(&[in_flight_fence], true, u64::MAX)
-                    .expect("failed to wait for fence");
+    //Blocks until the device is idle so prior writes to the buffer are visible
+    pub fn read_buffer_to_vec(&mut self, buffer: &Buffer, range: Range<u64>) -> Result<Vec<u8>, Error> {
+        if range.start > range.end || range.end > buffer.size() as u64 {
+            return Err(Error::OutOfBounds);
+        }
 
-                vk::Fence::reset(&[in_flight_fence]).expect("failed to reset fence");
-            }
+        //Vulkan allows neither a zero sized staging buffer nor copy
+        if range.is_empty() {
+            return Ok(vec![]);
         }
-    }
-    
-    pub fn draw_call<'a>(&'a mut self, mut script: impl FnMut(Commands<'_>)) {
-        match self {
-            Device::Vulkan { 
-                queues,
-                command_buffer,
-                image_available_semaphore,
-                render_finished_semaphore,
-                in_flight_fence,
-                .. 
-            } => {
-                command_buffer
-                    .record(|commands| {
-                        let commands = Commands::Vulkan {
-                            commands
-                        };
 
-                        script(commands);
-                    })
-                    .expect("failed to record command buffer");
+        let size = (range.end - range.start) as usize;
 
-                let submit_info = vk::SubmitInfo {
-                    wait_semaphores: &[&image_available_semaphore.borrow()],
-                    wait_stages: &[vk::PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT],
-                    command_buffers: &[command_buffer],
-                    signal_semaphores: &[&mut render_finished_semaphore.borrow_mut()],
-                };
+        let Buffer::Vulkan { memory, .. } = buffer else {
+            panic!("not a vulkan buffer");
+        };
 
-                queues[0]
-                    .submit(&[submit_info], Some(in_flight_fence))
-                    .expect("failed to submit draw command buffer");
-            }
+        self.wait_idle();
+
+        if memory.is_mapped() {
+            let mut bytes = vec![];
+
+            memory.invalidate().expect("failed to invalidate buffer memory");
+
+            memory
+                .read(range.start as _, |slice: &[u8]| {
+                    bytes.extend_from_slice(&slice[..size]);
+                })
+                .expect("failed to read buffer memory");
+
+            return Ok(bytes);
         }
-    }
 
-    pub fn call<'a>(&'a mut self, mut script: impl FnMut(Commands<'_>)) {
-        match self {
-            Device::Vulkan { 
-                queues,
-                command_buffer,
-                .. 
-            } => {
-                command_buffer
-                    .record(|commands| {
-                        let commands = Commands::Vulkan {
-                            commands
-                        };
+        let mut staging = Buffer::new(BufferInfo {
+            device: self,
+            usage: BufferUsage::TRANSFER_DST,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size,
+            priority: MemoryPriority::Default,
+        });
+
+        self.copy_buffer_to_buffer(BufferCopy {
+            from: buffer,
+            to: &mut staging,
+            src: range.start,
+            dst: 0,
+            size: size as _,
+        });
+
+        let Buffer::Vulkan { memory, .. } = &staging else {
+            panic!("not a vulkan buffer");
+        };
 
-                        script(commands);
-                    })
-                    .expect("failed to record command buffer");
+        let mut bytes = vec![];
 
-                let submit_info = vk::SubmitInfo {
-                    wait_semaphores: &[],
-                    wait_stages: &[],
-                    command_buffers: &[command_buffer],
-                    signal_semaphores: &[],
-                };
+        memory
+            .read(0, |slice: &[u8]| {
+                bytes.extend_from_slice(&slice[..size]);
+            })
+            .expect("failed to read staging buffer memory");
 
-                queues[0]
-                    .submit(&[submit_info], None)
-                    .expect("failed to submit draw command buffer");
+        Ok(bytes)
+    }
 
-                queues[0]
-                    .wait_idle()
-                    .expect("failed to wait on queue");
-            }
+    pub fn vertex_attribute_divisor_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.is_extension_enabled(vk::EXT_VERTEX_ATTRIBUTE_DIVISOR),
         }
     }
 
-    pub fn present(&mut self, swapchain: &Swapchain) -> Result<(), Error> {
+    pub fn wide_lines_supported(&self) -> bool {
         match self {
-            Device::Vulkan { 
-                queues,
-                render_finished_semaphore,
-                .. 
-            } => {
-                let (swapchain, &image_index) = if let Swapchain::Vulkan { swapchain, image_index, .. } = swapchain {
-                    (swapchain, image_index)
-                } else {
-                    panic!("not a vulkan swapchain");
-                };
-            
-                let present_info = vk::PresentInfo {
-                wait_semaphores: &[&render_finished_semaphore.borrow()],
-                swapchains: &[&swapchain],
-                image_indices: &[image_index],
-                };
+            Device::Vulkan { device, .. } => device.enabled_features().wide_lines,
+        }
+    }
 
-                queues[0].present(present_info).map_err(|_| Error::Presentation)
+    //Shaders should fall back to a non atomic variant when this is false
+    pub fn image_float_atomics_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => {
+                device
+                    .shader_atomic_float_features()
+                    .shader_image_float_32_atomics
             }
         }
     }
-}
 
-#[non_exhaustive]
-pub enum Commands<'a> {
-    Vulkan {
-        commands: vk::Commands<'a>,
+    pub fn buffer_float_atomics_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => {
+                device
+                    .shader_atomic_float_features()
+                    .shader_buffer_float_32_atomics
+            }
+        }
     }
-}
 
-impl Commands<'_> {
-    pub fn begin_render_pass(&mut self, info: RenderPassBeginInfo<'_>) {
+    //The highest sample count up to requested that color and depth attachments both support
+    pub fn max_sample_count(&self, requested: SampleCount) -> SampleCount {
         match self {
-            Self::Vulkan { commands } => {
-                let RenderPass::Vulkan { render_pass, .. } = info.render_pass else { panic!("not a vulkan render pass") };
-                let Framebuffer::Vulkan { framebuffer, extent, .. } = info.framebuffer else { panic!("not a vulkan framebuffer") };
-
-                let info = vk::RenderPassBeginInfo {
-                    render_pass: &render_pass,
-                    framebuffer: &framebuffer,
-                    render_area: vk::Rect2d {
-                        offset: (0, 0),
-                        extent: (extent.0, extent.1),
-                    },
-                    color_clear_values: &info.color_clear_values,
-                    depth_stencil_clear_value: info.depth_stencil_clear_value,
-                };
+            Device::Vulkan { device, .. } => {
+                let limits = device.limits();
 
-                commands.begin_render_pass(info);
+                requested.clamp_to(
+                    limits.framebuffer_color_sample_counts & limits.framebuffer_depth_sample_counts,
+                )
             }
         }
     }
 
-    pub fn end_render_pass(&mut self) {
+    //Whether optimally tiled images of format support every feature in features
+    pub fn format_supported(&self, format: Format, features: FormatFeatures) -> bool {
         match self {
-            Self::Vulkan { commands } => {
-                commands.end_render_pass();
-            }
+            Device::Vulkan {
+                physical_device, ..
+            } => physical_device
+                .format_properties(format.into())
+                .supports(vk::ImageTiling::Optimal, features.to_vk()),
         }
-        
     }
-    
-    pub fn next_subpass(&mut self) {
+
+    //The first of candidates, in order of preference, with every feature in features
+    pub fn find_supported_format(
+        &self,
+        candidates: &[Format],
+        features: FormatFeatures,
+    ) -> Option<Format> {
         match self {
-            Self::Vulkan { commands } => {
-                commands.next_subpass();
+            Device::Vulkan {
+                physical_device, ..
+            } => {
+                let candidates = candidates
+                    .iter()
+                    .map(|format| (*format).into())
+                    .collect::<Vec<vk::Format>>();
+
+                physical_device
+                    .find_supported_format(
+                        &candidates,
+                        vk::ImageTiling::Optimal,
+                        features.to_vk(),
+                    )
+                    .map(Format::from)
             }
         }
-        
     }
-    
-    pub fn bind_pipeline(&mut self, image_index: u32, pipeline: &Pipeline) {
+
+    //The preferred sampleable depth attachment format
+    pub fn depth_format(&self) -> Format {
+        self.find_supported_format(
+            &[Format::D32Sfloat, Format::X8D24UnormPack32, Format::D16Unorm],
+            FormatFeatures::DEPTH_STENCIL | FormatFeatures::SAMPLED,
+        )
+        .expect("failed to find a supported depth format")
+    }
+
+    //None when anisotropic filtering is unsupported
+    pub fn max_anisotropy(&self) -> Option<f32> {
         match self {
-            Self::Vulkan { commands } => {
-                let Pipeline::Vulkan { descriptor_sets, pipeline, pipeline_layout, bind_point, .. } = pipeline else { panic!("not a vulkan pipeline") };
+            Device::Vulkan { device, .. } => device
+                .enabled_features()
+                .sampler_anisotropy
+                .then(|| device.limits().max_sampler_anisotropy),
+        }
+    }
 
-                commands.bind_pipeline(*bind_point, pipeline);
-                commands.bind_descriptor_sets(*bind_point, pipeline_layout, 0, &[&descriptor_sets[image_index as usize]], &[]);
-            }
+    pub fn depth_clamp_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.enabled_features().depth_clamp,
         }
-        
     }
-    
-    pub fn bind_vertex_buffers(&mut self, 
-        first_binding: u32,
-        buffers: &'_ [&'_ Buffer],
-        offsets: &'_ [usize],
-        ) {
+
+    pub fn depth_clip_enable_supported(&self) -> bool {
         match self {
-            Self::Vulkan { commands } => {
-                 let buffers = buffers.iter().map(|buffer| 
-                     {
-                        let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
+            Device::Vulkan { device, .. } => device.is_extension_enabled(vk::EXT_DEPTH_CLIP_ENABLE),
+        }
+    }
 
-                        buffer
-                     }).collect::<Vec<_>>();
+    pub fn depth_range_unrestricted_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => {
+                device.is_extension_enabled(vk::EXT_DEPTH_RANGE_UNRESTRICTED)
+            }
+        }
+    }
 
-                 commands.bind_vertex_buffers(first_binding, &buffers, offsets);
+    //gl_Layer written from vertex shaders, GL_ARB_shader_viewport_layer_array
+    pub fn shader_output_layer_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => {
+                device.is_extension_enabled(vk::EXT_SHADER_VIEWPORT_INDEX_LAYER)
             }
         }
-        
     }
-    
-    pub fn bind_index_buffer(&mut self, buffer: &'_ Buffer, offset: usize) {
+
+    pub fn fragment_shader_interlock_supported(&self) -> bool {
         match self {
-            Self::Vulkan { commands } => {
-                let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
-                
-                commands.bind_index_buffer(buffer, offset, vk::IndexType::Uint16);
+            Device::Vulkan { device, .. } => {
+                device.is_extension_enabled(vk::EXT_FRAGMENT_SHADER_INTERLOCK)
             }
         }
-        
     }
-    
-    pub fn draw(&mut self,
+
+    pub fn transparency_path(&self) -> TransparencyPath {
+        if self.fragment_shader_interlock_supported() {
+            TransparencyPath::Interlock
+        } else {
+            TransparencyPath::ABuffer
+        }
+    }
+
+    //Everything MaterialTable relies on for a bindless texture array
+    pub fn descriptor_indexing_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => {
+                let features = device.descriptor_indexing_features();
+
+                features.shader_sampled_image_array_non_uniform_indexing
+                    && features.descriptor_binding_sampled_image_update_after_bind
+                    && features.descriptor_binding_update_unused_while_pending
+                    && features.descriptor_binding_partially_bound
+                    && features.descriptor_binding_variable_descriptor_count
+                    && features.runtime_descriptor_array
+            }
+        }
+    }
+
+    pub fn memory_priority_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.memory_priority_enabled(),
+        }
+    }
+
+    //Buffer::set_priority only takes effect when this is supported
+    pub fn pageable_device_local_memory_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.pageable_device_local_memory_enabled(),
+        }
+    }
+
+    //Pipeline::executables returns nothing without it
+    pub fn pipeline_statistics_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.pipeline_executable_info_enabled(),
+        }
+    }
+
+    //Buffers created with BufferUsage::SHADER_DEVICE_ADDRESS have a Buffer::device_address
+    pub fn buffer_device_address_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.buffer_device_address_enabled(),
+        }
+    }
+
+    //Swapchains know when their presents completed, see Swapchain::wait_presented
+    #[cfg(feature = "swapchain")]
+    pub fn present_fences_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.swapchain_maintenance1_enabled(),
+        }
+    }
+
+    //Swapchain::set_hdr_metadata only takes effect when this is supported
+    #[cfg(feature = "swapchain")]
+    pub fn hdr_metadata_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.is_extension_enabled(vk::EXT_HDR_METADATA),
+        }
+    }
+
+    //Events record synchronization2 split barriers, otherwise the legacy event commands
+    pub fn synchronization2_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.synchronization2_enabled(),
+        }
+    }
+
+    //Commands::begin_rendering and graphics pipelines with rendering formats instead of
+    //a render pass
+    pub fn dynamic_rendering_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.dynamic_rendering_enabled(),
+        }
+    }
+
+    //Secondary command buffers inherit the scissor instead of setting it again
+    pub fn inherited_viewport_scissor_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.inherited_viewport_scissor_enabled(),
+        }
+    }
+
+    //YcbcrTexture panics without it
+    pub fn sampler_ycbcr_conversion_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.sampler_ycbcr_conversion_enabled(),
+        }
+    }
+
+    //Allocations for which fail returns true report Error::OutOfMemory from Buffer::try_new
+    //and panic elsewhere, counting from the first allocation after this call
+    #[cfg(feature = "fault-injection")]
+    pub fn inject_allocation_faults(
+        &self,
+        fail: impl FnMut(&vk::AllocationAttempt) -> bool + 'static,
+    ) {
+        match self {
+            Device::Vulkan { device, .. } => device.inject_allocation_faults(fail),
+        }
+    }
+
+    #[cfg(feature = "fault-injection")]
+    pub fn clear_allocation_faults(&self) {
+        match self {
+            Device::Vulkan { device, .. } => device.clear_allocation_faults(),
+        }
+    }
+
+    //Commands::draw_indexed_indirect_count panics without it
+    pub fn draw_indirect_count_supported(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.is_extension_enabled(vk::KHR_DRAW_INDIRECT_COUNT),
+        }
+    }
+
+    pub fn max_storage_buffer_range(&self) -> usize {
+        match self {
+            Device::Vulkan { device, .. } => device.limits().max_storage_buffer_range as _,
+        }
+    }
+
+    //Commands::write_timestamp needs this on the queue draw_call submits to
+    pub fn timestamps_supported(&self) -> bool {
+        match self {
+            Device::Vulkan {
+                physical_device,
+                queue_family_index,
+                ..
+            } => physical_device.queue_families()[*queue_family_index as usize].timestamp_valid_bits > 0,
+        }
+    }
+
+    pub fn performance_query_supported(&self) -> bool {
+        match self {
+            Device::Vulkan {
+                physical_device, ..
+            } => physical_device.performance_query_supported(),
+        }
+    }
+
+    //Counters of the queue draw_call submits to, PerformanceQueryInfo takes indices into this
+    pub fn performance_counters(&self) -> Vec<vk::PerformanceCounter> {
+        match self {
+            Device::Vulkan {
+                physical_device,
+                queue_family_index,
+                ..
+            } => physical_device
+                .performance_counters(*queue_family_index)
+                .expect("failed to enumerate performance counters"),
+        }
+    }
+
+    //Hold the lock from before recording a frame with performance queries until it is submitted
+    pub fn acquire_profiling_lock(&self) -> bool {
+        match self {
+            Device::Vulkan { device, .. } => device.acquire_profiling_lock(u64::MAX).is_ok(),
+        }
+    }
+
+    pub fn release_profiling_lock(&self) {
+        match self {
+            Device::Vulkan { device, .. } => device.release_profiling_lock(),
+        }
+    }
+
+    pub fn wait_idle(&self) {
+        match self {
+            Device::Vulkan { device, deletion_queue, .. } => {
+                device.wait_idle().expect("failed to wait on device");
+
+                deletion_queue.borrow_mut().completed();
+            }
+        }
+    }
+
+    //Drops object once the next submission has finished instead of waiting for the device
+    //to go idle, by then the frames before it were presented
+    pub fn retire(&self, object: impl Any) {
+        match self {
+            Device::Vulkan { deletion_queue, .. } => deletion_queue.borrow_mut().retire(object),
+        }
+    }
+
+    //Rewrites descriptor set set of the pipeline from data in one call, see DescriptorTemplate.
+    //Like Pipeline::bind the set must not be in use by a pending submission.
+    pub fn update_with_template<T: DescriptorTemplateData>(
+        &self,
+        pipeline: &Pipeline,
+        set: u32,
+        template: &DescriptorTemplate<T>,
+        data: &T,
+    ) {
+        match self {
+            Device::Vulkan { device, .. } => {
+                let Pipeline::Vulkan {
+                    descriptor_sets, ..
+                } = pipeline
+                else {
+                    panic!("not a vulkan pipeline");
+                };
+
+                let DescriptorTemplate::Vulkan { template, .. } = template else {
+                    panic!("not a vulkan descriptor template");
+                };
+
+                device
+                    .update_descriptor_set_with_template(
+                        &descriptor_sets[set as usize],
+                        template,
+                        data,
+                    )
+                    .expect("failed to update descriptor set with template");
+            }
+        }
+    }
+
+    //Retired objects still waiting on a submission
+    pub fn retired_count(&self) -> usize {
+        match self {
+            Device::Vulkan { deletion_queue, .. } => deletion_queue.borrow().len(),
+        }
+    }
+
+    pub fn synchronize(&mut self) {
+        match self {
+            Device::Vulkan {
+                device,
+                in_flight_fence,
+                deletion_queue,
+                ..
+            } => {
+                //left signalled until the next submission resets it, so a frame skipped
+                //without submitting doesn't leave the next synchronize waiting forever
+                vk::Fence::wait(&[in_flight_fence], true, u64::MAX)
+                    .expect("failed to wait for fence");
+
+                //the one frame in flight has finished
+                deletion_queue.borrow_mut().completed();
+            }
+        }
+    }
+    
+    pub fn draw_call<'a>(&'a mut self, mut script: impl FnMut(Commands<'_>)) {
+        match self {
+            Device::Vulkan { 
+                queues,
+                command_buffer,
+                image_available_semaphore,
+                render_finished_semaphore,
+                in_flight_fence,
+                deletion_queue,
+                .. 
+            } => {
+                command_buffer
+                    .record(|commands| {
+                        let commands = Commands::Vulkan {
+                            commands
+                        };
+
+                        script(commands);
+                    })
+                    .expect("failed to record command buffer");
+
+                #[cfg(feature = "barrier-validation")]
+                report_hazards(command_buffer);
+
+                let submit_info = vk::SubmitInfo {
+                    wait_semaphores: &[&image_available_semaphore.borrow()],
+                    wait_stages: &[vk::PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT],
+                    command_buffers: &[command_buffer],
+                    signal_semaphores: &[&mut render_finished_semaphore.borrow_mut()],
+                    protected: false,
+                };
+
+                vk::Fence::reset(&[in_flight_fence]).expect("failed to reset fence");
+
+                queues[0]
+                    .submit(&[submit_info], Some(in_flight_fence))
+                    .expect("failed to submit draw command buffer");
+
+                deletion_queue.get_mut().submitted();
+            }
+        }
+    }
+
+    //One submission drawing to every swapchain an image was acquired from this frame, each
+    //presented with Device::present after. Several need SwapchainInfo::dedicated_sync. With
+    //none, e.g. all windows minimized, it still submits the recorded commands.
+    #[cfg(feature = "swapchain")]
+    pub fn draw_call_presenting(
+        &mut self,
+        swapchains: &[&Swapchain],
+        mut script: impl FnMut(Commands<'_>),
+    ) {
+        if swapchains.len() > 1 {
+            assert!(
+                swapchains.iter().all(|swapchain| swapchain.has_dedicated_sync()),
+                "swapchains drawn together need dedicated_sync"
+            );
+        }
+
+        match self {
+            Device::Vulkan {
+                queues,
+                command_buffer,
+                in_flight_fence,
+                deletion_queue,
+                ..
+            } => {
+                command_buffer
+                    .record(|commands| {
+                        let commands = Commands::Vulkan { commands };
+
+                        script(commands);
+                    })
+                    .expect("failed to record command buffer");
+
+                #[cfg(feature = "barrier-validation")]
+                report_hazards(command_buffer);
+
+                let (image_available_semaphores, mut render_finished_semaphores) = swapchains
+                    .iter()
+                    .map(|swapchain| match swapchain {
+                        Swapchain::Vulkan {
+                            image_available_semaphore,
+                            render_finished_semaphore,
+                            ..
+                        } => (
+                            image_available_semaphore.borrow(),
+                            render_finished_semaphore.borrow_mut(),
+                        ),
+                    })
+                    .unzip::<_, _, Vec<_>, Vec<_>>();
+
+                let submit_info = vk::SubmitInfo {
+                    wait_semaphores: &image_available_semaphores
+                        .iter()
+                        .map(|semaphore| &**semaphore)
+                        .collect::<Vec<_>>(),
+                    wait_stages: &vec![vk::PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT; swapchains.len()],
+                    command_buffers: &[command_buffer],
+                    signal_semaphores: &render_finished_semaphores
+                        .iter_mut()
+                        .map(|semaphore| &mut **semaphore)
+                        .collect::<Vec<_>>(),
+                    protected: false,
+                };
+
+                vk::Fence::reset(&[in_flight_fence]).expect("failed to reset fence");
+
+                queues[0]
+                    .submit(&[submit_info], Some(in_flight_fence))
+                    .expect("failed to submit draw command buffer");
+
+                deletion_queue.get_mut().submitted();
+            }
+        }
+    }
+
+    //Whether swapchains of the surface can be presented from one of this device's queues
+    #[cfg(feature = "swapchain")]
+    pub fn supports_present(&self, surface: &Surface) -> bool {
+        self.present_queue_family(surface).is_some()
+    }
+
+    //The family swapchains of the surface are presented from. A surface of another window may
+    //only be presentable from a different family than the one drawn on, see present_queues.
+    #[cfg(feature = "swapchain")]
+    pub fn present_queue_family(&self, surface: &Surface) -> Option<u32> {
+        match self {
+            Device::Vulkan {
+                physical_device,
+                queue_family_index,
+                present_queues,
+                ..
+            } => {
+                let Surface::Vulkan { surface } = surface else { panic!("not a vulkan surface") };
+
+                [*queue_family_index]
+                    .into_iter()
+                    .chain(present_queues.iter().map(|(family, _)| *family))
+                    .find(|&family| {
+                        physical_device
+                            .surface_supported(surface, family)
+                            .expect("failed to query surface support")
+                    })
+            }
+        }
+    }
+
+    pub fn call<'a>(&'a mut self, mut script: impl FnMut(Commands<'_>)) {
+        match self {
+            Device::Vulkan { 
+                queues,
+                command_buffer,
+                deletion_queue,
+                .. 
+            } => {
+                command_buffer
+                    .record(|commands| {
+                        let commands = Commands::Vulkan {
+                            commands
+                        };
+
+                        script(commands);
+                    })
+                    .expect("failed to record command buffer");
+
+                #[cfg(feature = "barrier-validation")]
+                report_hazards(command_buffer);
+
+                let submit_info = vk::SubmitInfo {
+                    wait_semaphores: &[],
+                    wait_stages: &[],
+                    command_buffers: &[command_buffer],
+                    signal_semaphores: &[],
+                    protected: false,
+                };
+
+                queues[0]
+                    .submit(&[submit_info], None)
+                    .expect("failed to submit draw command buffer");
+
+                queues[0]
+                    .wait_idle()
+                    .expect("failed to wait on queue");
+
+                let deletion_queue = deletion_queue.get_mut();
+                deletion_queue.submitted();
+                deletion_queue.completed();
+            }
+        }
+    }
+
+    #[cfg(feature = "swapchain")]
+    pub fn present(&mut self, swapchain: &Swapchain) -> Result<(), Error> {
+        match self {
+            Device::Vulkan { 
+                queues,
+                queue_family_index,
+                present_queues,
+                .. 
+            } => {
+                let (swapchain, &image_index, render_finished_semaphore, present_fence, &present_queue_family) = if let Swapchain::Vulkan { swapchain, image_index, render_finished_semaphore, present_fence, present_queue_family, .. } = swapchain {
+                    (swapchain, image_index, render_finished_semaphore, present_fence, present_queue_family)
+                } else {
+                    panic!("not a vulkan swapchain");
+                };
+
+                let queue = if present_queue_family == *queue_family_index {
+                    &mut queues[0]
+                } else {
+                    present_queues
+                        .iter_mut()
+                        .find(|(family, _)| *family == present_queue_family)
+                        .map(|(_, queue)| queue)
+                        .expect("swapchain is presented from a family the device has no queue of")
+                };
+
+                //waited on by the next acquire, the last present has finished with the semaphore
+                let mut present_fence = present_fence.as_ref().map(|fence| fence.borrow_mut());
+
+                if let Some(fence) = &mut present_fence {
+                    vk::Fence::reset(&[fence]).expect("failed to reset present fence");
+                }
+
+                let present_info = vk::PresentInfo {
+                wait_semaphores: &[&render_finished_semaphore.borrow()],
+                swapchains: &[&swapchain],
+                image_indices: &[image_index],
+                fences: &present_fence.iter_mut().map(|fence| &mut **fence).collect::<Vec<_>>(),
+                };
+
+                queue.present(present_info).map_err(|error| match error {
+                    vk::Error::OutOfDate => Error::OutOfDate,
+                    vk::Error::Suboptimal => Error::Suboptimal,
+                    _ => Error::Presentation,
+                })
+            }
+        }
+    }
+}
+
+//Warns about reads the last recording of command_buffer made after a write to the
+//same resource with no barrier in between
+#[cfg(feature = "barrier-validation")]
+pub(crate) fn report_hazards(command_buffer: &vk::CommandBuffer) {
+    for hazard in command_buffer.hazards() {
+        warn!(target: "barrier_validation", "{}", hazard);
+    }
+}
+
+#[non_exhaustive]
+pub enum Commands<'a> {
+    Vulkan {
+        commands: vk::Commands<'a>,
+    }
+}
+
+impl Commands<'_> {
+    //Fails with StaleFramebuffer for a framebuffer of an older swapchain generation,
+    //nothing is recorded then
+    pub fn begin_render_pass(&mut self, info: RenderPassBeginInfo<'_>) -> Result<(), Error> {
+        self.begin_render_pass_with(info, false)
+    }
+
+    //The first subpass comes from execute_commands instead of inline commands
+    pub fn begin_render_pass_secondary(&mut self, info: RenderPassBeginInfo<'_>) -> Result<(), Error> {
+        self.begin_render_pass_with(info, true)
+    }
+
+    fn begin_render_pass_with(&mut self, info: RenderPassBeginInfo<'_>, secondary: bool) -> Result<(), Error> {
+        if info.framebuffer.is_stale() {
+            return Err(Error::StaleFramebuffer);
+        }
+
+        match self {
+            Self::Vulkan { commands } => {
+                let RenderPass::Vulkan { render_pass, .. } = info.render_pass else { panic!("not a vulkan render pass") };
+                let Framebuffer::Vulkan { framebuffer, extent, .. } = info.framebuffer else { panic!("not a vulkan framebuffer") };
+
+                let info = vk::RenderPassBeginInfo {
+                    render_pass: &render_pass,
+                    framebuffer: &framebuffer,
+                    render_area: vk::Rect2d {
+                        offset: (0, 0),
+                        extent: (extent.0, extent.1),
+                    },
+                    color_clear_values: &info.color_clear_values,
+                    depth_stencil_clear_value: info.depth_stencil_clear_value,
+                };
+
+                if secondary {
+                    commands.begin_render_pass_secondary(info);
+                } else {
+                    commands.begin_render_pass(info);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn end_render_pass(&mut self) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.end_render_pass();
+            }
+        }
+        
+    }
+    
+    //Renders to the attachments without a render pass or framebuffer, the images have to be
+    //in their layouts already. Requires Device::dynamic_rendering_supported.
+    pub fn begin_rendering<'a>(&mut self, info: RenderingInfo<'a>) {
+        match self {
+            Self::Vulkan { commands } => {
+                let attachment = |attachment: &RenderingAttachment<'a>| -> vk::RenderingAttachmentInfo<'a> {
+                    let Image::Vulkan { view, .. } = attachment.image else { panic!("not a vulkan image") };
+
+                    vk::RenderingAttachmentInfo {
+                        image_view: view,
+                        image_layout: attachment.layout.into(),
+                        load_op: attachment.load_op.into(),
+                        store_op: attachment.store_op.into(),
+                        clear_value: attachment.clear_value,
+                    }
+                };
+
+                let color_attachments = info.color_attachments.iter().map(attachment).collect::<Vec<_>>();
+
+                commands
+                    .begin_rendering(vk::RenderingInfo {
+                        render_area: vk::Rect2d {
+                            offset: (0, 0),
+                            extent: info.extent,
+                        },
+                        layer_count: 1,
+                        color_attachments: &color_attachments,
+                        depth_attachment: info.depth_attachment.as_ref().map(attachment),
+                        stencil_attachment: None,
+                    })
+                    .expect("failed to begin rendering");
+            }
+        }
+    }
+
+    pub fn end_rendering(&mut self) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.end_rendering().expect("failed to end rendering");
+            }
+        }
+    }
+
+    pub fn next_subpass(&mut self) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.next_subpass();
+            }
+        }
+        
+    }
+
+    pub fn next_subpass_secondary(&mut self) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.next_subpass_secondary();
+            }
+        }
+    }
+
+    pub fn execute_commands(&mut self, secondaries: &'_ [&'_ SecondaryCommandBuffer]) {
+        match self {
+            Self::Vulkan { commands } => {
+                let command_buffers = secondaries.iter().map(|secondary| {
+                    let SecondaryCommandBuffer::Vulkan { command_buffer, .. } = secondary else { panic!("not a vulkan secondary command buffer") };
+
+                    command_buffer
+                }).collect::<Vec<_>>();
+
+                commands.execute_commands(&command_buffers);
+            }
+        }
+    }
+    
+    //Also binds the pipeline's descriptor set, except for layouts with dynamic uniform buffers
+    //which need their offsets from bind_descriptor_sets
+    pub fn bind_pipeline(&mut self, image_index: u32, pipeline: &Pipeline) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Pipeline::Vulkan { descriptor_sets, pipeline, pipeline_layout, bind_point, dynamic_descriptors, .. } = pipeline else { panic!("not a vulkan pipeline") };
+
+                commands.bind_pipeline(*bind_point, pipeline);
+
+                if !dynamic_descriptors {
+                    commands.bind_descriptor_sets(*bind_point, pipeline_layout, 0, &[&descriptor_sets[image_index as usize]], &[]);
+                }
+            }
+        }
+        
+    }
+    
+    pub fn bind_descriptor_sets(&mut self, image_index: u32, pipeline: &Pipeline, dynamic_offsets: &'_ [u32]) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Pipeline::Vulkan { descriptor_sets, pipeline_layout, bind_point, .. } = pipeline else { panic!("not a vulkan pipeline") };
+
+                commands.bind_descriptor_sets(*bind_point, pipeline_layout, 0, &[&descriptor_sets[image_index as usize]], dynamic_offsets);
+            }
+        }
+    }
+
+    pub fn push_constants<T: Pod>(&mut self, pipeline: &Pipeline, stage: ShaderStage, offset: u32, data: &'_ [T]) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Pipeline::Vulkan { pipeline_layout, .. } = pipeline else { panic!("not a vulkan pipeline") };
+
+                let data = unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, mem::size_of_val(data)) };
+
+                commands
+                    .push_constants(pipeline_layout, stage.to_vk(), offset, data)
+                    .expect("failed to push constants");
+            }
+        }
+    }
+
+    //A single value converted first, e.g. push_constant::<ShaderMat4>(.., view_proj)
+    pub fn push_constant<T: Pod>(&mut self, pipeline: &Pipeline, stage: ShaderStage, offset: u32, value: impl Into<T>) {
+        self.push_constants(pipeline, stage, offset, &[value.into()]);
+    }
+
+    pub fn bind_vertex_buffers(&mut self, 
+        first_binding: u32,
+        buffers: &'_ [&'_ Buffer],
+        offsets: &'_ [usize],
+        ) {
+        match self {
+            Self::Vulkan { commands } => {
+                 let buffers = buffers.iter().map(|buffer| 
+                     {
+                        let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
+
+                        buffer
+                     }).collect::<Vec<_>>();
+
+                 commands.bind_vertex_buffers(first_binding, &buffers, offsets);
+            }
+        }
+        
+    }
+    
+    pub fn bind_index_buffer(&mut self, buffer: &'_ Buffer, offset: usize) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
+                
+                commands.bind_index_buffer(buffer, offset, vk::IndexType::Uint16);
+            }
+        }
+        
+    }
+    
+    pub fn set_blend_constants(&mut self, blend_constants: [f32; 4]) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.set_blend_constants(blend_constants);
+            }
+        }
+    }
+
+    pub fn set_scissor(&mut self, offset: (i32, i32), extent: (u32, u32)) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.set_scissor(0, &[vk::Rect2d { offset, extent }]);
+            }
+        }
+    }
+
+    pub fn draw(&mut self,
         vertex_count: u32,
         instance_count: u32,
         first_vertex: u32,
@@ -563,77 +1579,246 @@ impl Commands<'_> {
         
     }
 
-    pub fn pipeline_barrier(&mut self, src_stage: PipelineStage, dst_stage: PipelineStage, barriers: &'_ [Barrier]) {
+    pub fn draw_indexed_indirect(&mut self, buffer: &'_ Buffer, offset: usize, draw_count: u32, stride: u32) {
         match self {
             Self::Vulkan { commands } => {
-                let mut memory_barriers = vec![];
-                let mut buffer_barriers = vec![];
-                let mut image_barriers = vec![];
-
-                for barrier in barriers {
-                    match barrier {
-                        Barrier::Memory {
-                            src_access,
-                            dst_access,
-                        } => {
-                            let memory_barrier = vk::MemoryBarrier {
-                                src_access_mask: src_access.to_vk(),
-                                dst_access_mask: dst_access.to_vk(),
-                            };
+                let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
 
-                            memory_barriers.push(memory_barrier);
-                        },
-                        Barrier::Buffer {
-                            src_access,
-                            dst_access,
-                            buffer,
-                            offset,
-                            size
-                        } => {
-                            let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
-                            
-                            let buffer_barrier = vk::BufferMemoryBarrier {
-                                src_access_mask: src_access.to_vk(),
-                                dst_access_mask: dst_access.to_vk(),
-                                src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
-                                dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
-                                buffer: &buffer,
-                                offset: *offset as _,
-                                size: *size as _,
-                           };
+                commands.draw_indexed_indirect(buffer, offset as _, draw_count, stride);
+            }
+        }
+    }
 
-                            buffer_barriers.push(buffer_barrier);
-                        },
-                        Barrier::Image {
-                            src_access,
-                            dst_access,
-                            old_layout,
-                            new_layout,
-                            image,
-                        } => {
-                            let Image::Vulkan { image, format, .. } = image else { panic!("not a vulkan image") };
-
-                            let image_barrier = vk::ImageMemoryBarrier {
-old_layout: old_layout.clone().into(),
-                    new_layout: new_layout.clone().into(),
-                    src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
-                    dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
-                    image: &image,
-                    src_access_mask: src_access.to_vk(),
-                    dst_access_mask: dst_access.to_vk(),
-                    subresource_range: vk::ImageSubresourceRange {
+    pub fn draw_indexed_indirect_count(&mut self, buffer: &'_ Buffer, offset: usize, count_buffer: &'_ Buffer, count_offset: usize, max_draw_count: u32, stride: u32) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
+                let Buffer::Vulkan { buffer: count_buffer, .. } = count_buffer else { panic!("not a vulkan buffer") };
+
+                commands
+                    .draw_indexed_indirect_count(buffer, offset as _, count_buffer, count_offset as _, max_draw_count, stride)
+                    .expect("failed to draw indexed indirect count");
+            }
+        }
+    }
+
+    //Buffer needs TRANSFER_DST, size is in bytes and a multiple of 4
+    pub fn fill_buffer(&mut self, buffer: &'_ Buffer, offset: usize, size: usize, data: u32) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
+
+                commands.fill_buffer(buffer, offset as _, size as _, data);
+            }
+        }
+    }
+
+    pub fn copy_buffer(&mut self, from: &'_ Buffer, to: &'_ mut Buffer, src: usize, dst: usize, size: usize) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Buffer::Vulkan { buffer: from, .. } = from else { panic!("not a vulkan buffer") };
+                let Buffer::Vulkan { buffer: to, .. } = to else { panic!("not a vulkan buffer") };
+
+                let buffer_copy = vk::BufferCopy {
+                    src_offset: src as _,
+                    dst_offset: dst as _,
+                    size: size as _,
+                };
+
+                commands.copy_buffer(from, to, &[buffer_copy]);
+            }
+        }
+    }
+
+    pub fn copy_image_to_buffer(&mut self, image: &'_ Image, layout: ImageLayout, buffer: &'_ mut Buffer, offset: usize, image_offset: (i32, i32), extent: (u32, u32)) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Image::Vulkan { image, format, .. } = image else { panic!("not a vulkan image") };
+                let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
+
+                let region = vk::BufferImageCopy {
+                    buffer_offset: offset as _,
+                    buffer_row_length: 0,
+                    buffer_image_height: 0,
+                    image_subresource: vk::ImageSubresourceLayers {
                         aspect_mask: format.aspect_mask(),
-                        base_mip_level: 0,
-                        level_count: 1,
+                        mip_level: 0,
                         base_array_layer: 0,
                         layer_count: 1,
                     },
-                            };
+                    image_offset: (image_offset.0, image_offset.1, 0),
+                    image_extent: (extent.0, extent.1, 1),
+                };
 
-                            image_barriers.push(image_barrier);
-                        }
-                    }
+                commands.copy_image_to_buffer(image, layout.into(), buffer, &[region]);
+            }
+        }
+    }
+
+    pub fn copy_image(&mut self, from: &'_ Image, from_layout: ImageLayout, to: &'_ mut Image, to_layout: ImageLayout, regions: &'_ [ImageCopy]) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Image::Vulkan { image: from, format: from_format, .. } = from else { panic!("not a vulkan image") };
+                let Image::Vulkan { image: to, format: to_format, .. } = to else { panic!("not a vulkan image") };
+
+                let regions = regions
+                    .iter()
+                    .map(|copy| vk::ImageCopy {
+                        src_subresource: subresource_layers(*from_format, copy.src_mip_level),
+                        src_offset: copy.src_offset,
+                        dst_subresource: subresource_layers(*to_format, copy.dst_mip_level),
+                        dst_offset: copy.dst_offset,
+                        extent: copy.extent,
+                    })
+                    .collect::<Vec<_>>();
+
+                commands.copy_image(from, from_layout.into(), to, to_layout.into(), &regions);
+            }
+        }
+    }
+
+    //Scales regions between images of blittable formats, e.g. to generate mip levels
+    //with ImageBlit::downsample. Linear needs a filterable source format.
+    pub fn blit_image(&mut self, from: &'_ Image, from_layout: ImageLayout, to: &'_ mut Image, to_layout: ImageLayout, regions: &'_ [ImageBlit], filter: Filter) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Image::Vulkan { image: from, format: from_format, .. } = from else { panic!("not a vulkan image") };
+                let Image::Vulkan { image: to, format: to_format, .. } = to else { panic!("not a vulkan image") };
+
+                let regions = regions
+                    .iter()
+                    .map(|blit| vk::ImageBlit {
+                        src_subresource: subresource_layers(*from_format, blit.src_mip_level),
+                        src_offsets: blit.src_offsets,
+                        dst_subresource: subresource_layers(*to_format, blit.dst_mip_level),
+                        dst_offsets: blit.dst_offsets,
+                    })
+                    .collect::<Vec<_>>();
+
+                let filter = match filter {
+                    Filter::Nearest => vk::Filter::Nearest,
+                    Filter::Linear => vk::Filter::Linear,
+                };
+
+                commands.blit_image(from, from_layout.into(), to, to_layout.into(), &regions, filter);
+            }
+        }
+    }
+
+    //Resolves a multisampled image into a single sampled one of the same format
+    pub fn resolve_image(&mut self, from: &'_ Image, from_layout: ImageLayout, to: &'_ mut Image, to_layout: ImageLayout, regions: &'_ [ImageResolve]) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Image::Vulkan { image: from, format: from_format, .. } = from else { panic!("not a vulkan image") };
+                let Image::Vulkan { image: to, format: to_format, .. } = to else { panic!("not a vulkan image") };
+
+                let regions = regions
+                    .iter()
+                    .map(|resolve| vk::ImageResolve {
+                        src_subresource: subresource_layers(*from_format, resolve.src_mip_level),
+                        src_offset: resolve.src_offset,
+                        dst_subresource: subresource_layers(*to_format, resolve.dst_mip_level),
+                        dst_offset: resolve.dst_offset,
+                        extent: resolve.extent,
+                    })
+                    .collect::<Vec<_>>();
+
+                commands.resolve_image(from, from_layout.into(), to, to_layout.into(), &regions);
+            }
+        }
+    }
+
+    pub fn dispatch(&mut self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.dispatch(group_count_x, group_count_y, group_count_z);
+            }
+        }
+    }
+
+    //Labels the dispatch for captures and for the shader printf output it produces.
+    //Without the debug-utils feature in a debug build this is a plain dispatch.
+    pub fn dispatch_labelled(
+        &mut self,
+        label: &str,
+        group_count_x: u32,
+        group_count_y: u32,
+        group_count_z: u32,
+    ) {
+        match self {
+            Self::Vulkan { commands } => {
+                let labelled = commands.begin_label(label, [0.0; 4]).is_ok();
+
+                commands.dispatch(group_count_x, group_count_y, group_count_z);
+
+                if labelled {
+                    let _ = commands.end_label();
                 }
+            }
+        }
+    }
+
+    //Names the buffer in missing barrier warnings for the rest of the recording
+    #[cfg(feature = "barrier-validation")]
+    pub fn name_buffer(&mut self, buffer: &Buffer, name: &str) {
+        let (Self::Vulkan { commands }, Buffer::Vulkan { buffer, .. }) = (self, buffer) else {
+            panic!("not a vulkan buffer");
+        };
+
+        commands.name_buffer(buffer, name);
+    }
+
+    #[cfg(feature = "barrier-validation")]
+    pub fn name_image(&mut self, image: &Image, name: &str) {
+        let (Self::Vulkan { commands }, Image::Vulkan { image, .. }) = (self, image) else {
+            panic!("not a vulkan image");
+        };
+
+        commands.name_image(image, name);
+    }
+
+    //Must be recorded outside a render pass
+    pub fn reset_query_pool(&mut self, query_pool: &QueryPool, first: u32, count: u32) {
+        let (Self::Vulkan { commands }, QueryPool::Vulkan { query_pool, .. }) = (self, query_pool) else {
+            panic!("not a vulkan query pool");
+        };
+
+        commands.reset_query_pool(query_pool, first, count);
+    }
+
+    //precise counts every passing sample of an occlusion query instead of only telling
+    //zero from non zero, which may be slower
+    pub fn begin_query(&mut self, query_pool: &QueryPool, query: u32, precise: bool) {
+        let (Self::Vulkan { commands }, QueryPool::Vulkan { query_pool, .. }) = (self, query_pool) else {
+            panic!("not a vulkan query pool");
+        };
+
+        let flags = if precise { vk::QUERY_CONTROL_PRECISE } else { 0 };
+
+        commands.begin_query_with(query_pool, query, flags);
+    }
+
+    pub fn end_query(&mut self, query_pool: &QueryPool, query: u32) {
+        let (Self::Vulkan { commands }, QueryPool::Vulkan { query_pool, .. }) = (self, query_pool) else {
+            panic!("not a vulkan query pool");
+        };
+
+        commands.end_query(query_pool, query);
+    }
+
+    //See Device::timestamps_supported
+    pub fn write_timestamp(&mut self, stage: PipelineStage, query_pool: &QueryPool, query: u32) {
+        let (Self::Vulkan { commands }, QueryPool::Vulkan { query_pool, .. }) = (self, query_pool) else {
+            panic!("not a vulkan query pool");
+        };
+
+        commands.write_timestamp(stage.to_vk(), query_pool, query);
+    }
+
+    pub fn pipeline_barrier(&mut self, src_stage: PipelineStage, dst_stage: PipelineStage, barriers: &'_ [Barrier]) {
+        match self {
+            Self::Vulkan { commands } => {
+                let (memory_barriers, buffer_barriers, image_barriers) = vk_barriers(barriers);
 
                 commands.pipeline_barrier(
                     src_stage.to_vk(),
@@ -645,8 +1830,223 @@ old_layout: old_layout.clone().into(),
                 );
             }
         }
-        
     }
+
+    //First half of a split barrier, recorded after the producer. The event must be
+    //unsignaled, pass the same stages and barriers to wait_event.
+    pub fn set_event(&mut self, event: &Event, src_stage: PipelineStage, dst_stage: PipelineStage, barriers: &'_ [Barrier]) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Event::Vulkan { event, synchronization2 } = event else { panic!("not a vulkan event") };
+
+                if *synchronization2 {
+                    let (memory_barriers, buffer_barriers, image_barriers) = vk_barriers2(src_stage, dst_stage, barriers);
+
+                    commands
+                        .set_event2(
+                            event,
+                            &vk::DependencyInfo {
+                                dependency_flags: 0,
+                                memory_barriers: &memory_barriers,
+                                buffer_memory_barriers: &buffer_barriers,
+                                image_memory_barriers: &image_barriers,
+                            },
+                        )
+                        .expect("failed to set event");
+                } else {
+                    commands.set_event(event, src_stage.to_vk());
+                }
+            }
+        }
+    }
+
+    //Second half of a split barrier, recorded before the consumer
+    pub fn wait_event(&mut self, event: &Event, src_stage: PipelineStage, dst_stage: PipelineStage, barriers: &'_ [Barrier]) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Event::Vulkan { event, synchronization2 } = event else { panic!("not a vulkan event") };
+
+                if *synchronization2 {
+                    let (memory_barriers, buffer_barriers, image_barriers) = vk_barriers2(src_stage, dst_stage, barriers);
+
+                    commands
+                        .wait_events2(
+                            &[event],
+                            &[vk::DependencyInfo {
+                                dependency_flags: 0,
+                                memory_barriers: &memory_barriers,
+                                buffer_memory_barriers: &buffer_barriers,
+                                image_memory_barriers: &image_barriers,
+                            }],
+                        )
+                        .expect("failed to wait on event");
+                } else {
+                    let (memory_barriers, buffer_barriers, image_barriers) = vk_barriers(barriers);
+
+                    commands.wait_events(
+                        &[event],
+                        src_stage.to_vk(),
+                        dst_stage.to_vk(),
+                        &memory_barriers,
+                        &buffer_barriers,
+                        &image_barriers,
+                    );
+                }
+            }
+        }
+    }
+
+    //Unsignals the event once the stage completes, so it can be set again
+    pub fn reset_event(&mut self, event: &Event, stage: PipelineStage) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Event::Vulkan { event, .. } = event else { panic!("not a vulkan event") };
+
+                commands.reset_event(event, stage.to_vk());
+            }
+        }
+    }
+}
+
+//The first array layer of a mip level
+fn subresource_layers(format: vk::Format, mip_level: u32) -> vk::ImageSubresourceLayers {
+    vk::ImageSubresourceLayers {
+        aspect_mask: format.aspect_mask(),
+        mip_level,
+        base_array_layer: 0,
+        layer_count: 1,
+    }
+}
+
+fn vk_barriers<'a>(
+    barriers: &'a [Barrier<'a>],
+) -> (
+    Vec<vk::MemoryBarrier>,
+    Vec<vk::BufferMemoryBarrier<'a>>,
+    Vec<vk::ImageMemoryBarrier<'a>>,
+) {
+    let mut memory_barriers = vec![];
+    let mut buffer_barriers = vec![];
+    let mut image_barriers = vec![];
+
+    for barrier in barriers {
+        match barrier {
+            Barrier::Memory {
+                src_access,
+                dst_access,
+            } => {
+                memory_barriers.push(vk::MemoryBarrier {
+                    src_access_mask: src_access.to_vk(),
+                    dst_access_mask: dst_access.to_vk(),
+                });
+            }
+            Barrier::Buffer {
+                src_access,
+                dst_access,
+                buffer,
+                offset,
+                size,
+            } => {
+                let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
+
+                buffer_barriers.push(vk::BufferMemoryBarrier {
+                    src_access_mask: src_access.to_vk(),
+                    dst_access_mask: dst_access.to_vk(),
+                    src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    buffer: &buffer,
+                    offset: *offset as _,
+                    size: *size as _,
+                });
+            }
+            Barrier::Image {
+                src_access,
+                dst_access,
+                old_layout,
+                new_layout,
+                image,
+            } => {
+                let Image::Vulkan { image, format, .. } = image else { panic!("not a vulkan image") };
+
+                image_barriers.push(vk::ImageMemoryBarrier {
+                    old_layout: old_layout.clone().into(),
+                    new_layout: new_layout.clone().into(),
+                    src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    image: &image,
+                    src_access_mask: src_access.to_vk(),
+                    dst_access_mask: dst_access.to_vk(),
+                    subresource_range: vk::ImageSubresourceRange {
+                        aspect_mask: format.aspect_mask(),
+                        base_mip_level: 0,
+                        level_count: 1,
+                        base_array_layer: 0,
+                        layer_count: 1,
+                    },
+                });
+            }
+        }
+    }
+
+    (memory_barriers, buffer_barriers, image_barriers)
+}
+
+//Same barriers with the stages folded in, the legacy stage and access bits carry over
+fn vk_barriers2<'a>(
+    src_stage: PipelineStage,
+    dst_stage: PipelineStage,
+    barriers: &'a [Barrier<'a>],
+) -> (
+    Vec<vk::MemoryBarrier2>,
+    Vec<vk::BufferMemoryBarrier2<'a>>,
+    Vec<vk::ImageMemoryBarrier2<'a>>,
+) {
+    let (src_stage_mask, dst_stage_mask) = (src_stage.to_vk() as u64, dst_stage.to_vk() as u64);
+
+    let (memory_barriers, buffer_barriers, image_barriers) = vk_barriers(barriers);
+
+    let memory_barriers = memory_barriers
+        .into_iter()
+        .map(|barrier| vk::MemoryBarrier2 {
+            src_stage_mask,
+            src_access_mask: barrier.src_access_mask as _,
+            dst_stage_mask,
+            dst_access_mask: barrier.dst_access_mask as _,
+        })
+        .collect();
+
+    let buffer_barriers = buffer_barriers
+        .into_iter()
+        .map(|barrier| vk::BufferMemoryBarrier2 {
+            src_stage_mask,
+            src_access_mask: barrier.src_access_mask as _,
+            dst_stage_mask,
+            dst_access_mask: barrier.dst_access_mask as _,
+            src_queue_family_index: barrier.src_queue_family_index,
+            dst_queue_family_index: barrier.dst_queue_family_index,
+            buffer: barrier.buffer,
+            offset: barrier.offset,
+            size: barrier.size,
+        })
+        .collect();
+
+    let image_barriers = image_barriers
+        .into_iter()
+        .map(|barrier| vk::ImageMemoryBarrier2 {
+            src_stage_mask,
+            src_access_mask: barrier.src_access_mask as _,
+            dst_stage_mask,
+            dst_access_mask: barrier.dst_access_mask as _,
+            old_layout: barrier.old_layout,
+            new_layout: barrier.new_layout,
+            src_queue_family_index: barrier.src_queue_family_index,
+            dst_queue_family_index: barrier.dst_queue_family_index,
+            image: barrier.image,
+            subresource_range: barrier.subresource_range,
+        })
+        .collect();
+
+    (memory_barriers, buffer_barriers, image_barriers)
 }
 
 pub enum Barrier<'a> {
@@ -669,3 +2069,25 @@ pub enum Barrier<'a> {
         image: &'a Image
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //What begin_frame does for a frame skipped while minimized: synchronize, then no
+    //acquire and no submission. Two in a row must not wait on an unsignalled fence.
+    #[test]
+    fn synchronize_returns_after_skipped_frames() {
+        let context = Context::start_headless();
+
+        let mut device = Device::choose_best(DeviceInfo {
+            context: &context,
+            #[cfg(feature = "swapchain")]
+            surface: None,
+        });
+
+        device.synchronize();
+        device.synchronize();
+        device.synchronize();
+    }
+}
@@ -2,4 +2,19 @@
 pub enum Error {
     Acquisition,
     Presentation,
+    //The surface changed and the swapchain no longer matches it, recreate the swapchain
+    OutOfDate,
+    //Presented, but the swapchain no longer matches the surface exactly
+    Suboptimal,
+    //The framebuffer was created for an earlier swapchain or extent, recreate it
+    StaleFramebuffer,
+    OutOfMemory,
+    //The target's format is sRGB where UNORM was expected or the reverse, see OutputEncoding
+    OutputEncodingMismatch,
+    //The handle's resource was removed from the ResourceRegistry, its slot may be reused
+    StaleHandle,
+    //The range ends before it starts or runs past the end of the buffer
+    OutOfBounds,
+    //None of the device's queues can present to the surface, see Device::supports_present
+    SurfaceNotPresentable,
 }
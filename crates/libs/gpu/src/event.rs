@@ -0,0 +1,91 @@
+use crate::prelude::*;
+
+pub struct EventInfo<'a> {
+    pub device: &'a Device,
+}
+
+#[non_exhaustive]
+pub enum Event {
+    Vulkan {
+        event: vk::Event,
+        synchronization2: bool,
+    },
+}
+
+impl Event {
+    pub fn new(info: EventInfo<'_>) -> Self {
+        match info.device {
+            Device::Vulkan { device, .. } => {
+                let event = vk::Event::new(device.clone(), vk::EventCreateInfo {})
+                    .expect("failed to create event");
+
+                Self::Vulkan {
+                    event,
+                    synchronization2: device.synchronization2_enabled(),
+                }
+            }
+        }
+    }
+
+    //Signaled by set or by an executed Commands::set_event
+    pub fn is_set(&self) -> bool {
+        match self {
+            Self::Vulkan { event, .. } => event.status().expect("failed to get event status"),
+        }
+    }
+
+    //From the host, e.g. to release work recorded with Commands::wait_event ahead of time
+    pub fn set(&self) {
+        match self {
+            Self::Vulkan { event, .. } => event.set().expect("failed to set event"),
+        }
+    }
+
+    pub fn reset(&self) {
+        match self {
+            Self::Vulkan { event, .. } => event.reset().expect("failed to reset event"),
+        }
+    }
+}
+
+pub struct SplitBarrierInfo<'a> {
+    pub device: &'a Device,
+    pub src_stage: PipelineStage,
+    pub dst_stage: PipelineStage,
+}
+
+//A barrier split around independent work in the same command buffer, e.g. chunk meshing
+//in compute followed by shadow rendering that only reads the meshes much later. signal
+//goes right after the producer and wait right before the consumer, so whatever is
+//recorded in between overlaps with the producer instead of waiting on it.
+pub struct SplitBarrier {
+    event: Event,
+    src_stage: PipelineStage,
+    dst_stage: PipelineStage,
+}
+
+impl SplitBarrier {
+    pub fn new(info: SplitBarrierInfo<'_>) -> Self {
+        Self {
+            event: Event::new(EventInfo {
+                device: info.device,
+            }),
+            src_stage: info.src_stage,
+            dst_stage: info.dst_stage,
+        }
+    }
+
+    pub fn signal(&self, commands: &mut Commands<'_>, barriers: &'_ [Barrier]) {
+        commands.set_event(&self.event, self.src_stage, self.dst_stage, barriers);
+    }
+
+    //Takes the same barriers as signal and resets the event for the next submission
+    pub fn wait(&self, commands: &mut Commands<'_>, barriers: &'_ [Barrier]) {
+        commands.wait_event(&self.event, self.src_stage, self.dst_stage, barriers);
+        commands.reset_event(&self.event, self.dst_stage);
+    }
+
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+}
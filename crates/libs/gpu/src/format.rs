@@ -1,65 +1,814 @@
-#[derive(Clone, Copy)]
+use bitflags::bitflags;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Format {
+    Rg4UnormPack8,
+    Rgba4UnormPack16,
+    Bgra4UnormPack16,
+    R5G6B5UnormPack16,
+    B5G6R5UnormPack16,
+    R5G5B5A1UnormPack16,
+    B5G5R5A1UnormPack16,
+    A1R5G5B5UnormPack16,
+    R8Unorm,
+    R8Snorm,
+    R8Uscaled,
+    R8Sscaled,
+    R8Uint,
+    R8Sint,
+    R8Srgb,
+    Rg8Unorm,
+    Rg8Snorm,
+    Rg8Uscaled,
+    Rg8Sscaled,
+    Rg8Uint,
+    Rg8Sint,
+    Rg8Srgb,
+    Rgb8Unorm,
+    Rgb8Snorm,
+    Rgb8Uscaled,
+    Rgb8Sscaled,
+    Rgb8Uint,
+    Rgb8Sint,
+    Rgb8Srgb,
+    Bgr8Unorm,
+    Bgr8Snorm,
+    Bgr8Uscaled,
+    Bgr8Sscaled,
+    Bgr8Uint,
+    Bgr8Sint,
+    Bgr8Srgb,
+    Rgba8Unorm,
+    Rgba8Snorm,
+    Rgba8Uscaled,
+    Rgba8Sscaled,
+    Rgba8Uint,
+    Rgba8Sint,
     Rgba8Srgb,
+    Bgra8Unorm,
+    Bgra8Snorm,
+    Bgra8Uscaled,
+    Bgra8Sscaled,
+    Bgra8Uint,
+    Bgra8Sint,
     Bgra8Srgb,
+    Abgr8UnormPack32,
+    Abgr8SnormPack32,
+    Abgr8UscaledPack32,
+    Abgr8SscaledPack32,
+    Abgr8UintPack32,
+    Abgr8SintPack32,
+    Abgr8SrgbPack32,
+    A2R10G10B10UnormPack32,
+    A2R10G10B10SnormPack32,
+    A2R10G10B10UscaledPack32,
+    A2R10G10B10SscaledPack32,
+    A2R10G10B10UintPack32,
+    A2R10G10B10SintPack32,
+    A2B10G10R10UnormPack32,
+    A2B10G10R10SnormPack32,
+    A2B10G10R10UscaledPack32,
+    A2B10G10R10SscaledPack32,
+    A2B10G10R10UintPack32,
+    A2B10G10R10SintPack32,
+    R16Unorm,
+    R16Snorm,
+    R16Uscaled,
+    R16Sscaled,
     R16Uint,
+    R16Sint,
+    R16Sfloat,
+    Rg16Unorm,
+    Rg16Snorm,
+    Rg16Uscaled,
+    Rg16Sscaled,
+    Rg16Uint,
+    Rg16Sint,
+    Rg16Sfloat,
+    Rgb16Unorm,
+    Rgb16Snorm,
+    Rgb16Uscaled,
+    Rgb16Sscaled,
+    Rgb16Uint,
+    Rgb16Sint,
+    Rgb16Sfloat,
+    Rgba16Unorm,
+    Rgba16Snorm,
+    Rgba16Uscaled,
+    Rgba16Sscaled,
+    Rgba16Uint,
+    Rgba16Sint,
+    Rgba16Sfloat,
     R32Uint,
+    R32Sint,
     R32Sfloat,
+    Rg32Uint,
+    Rg32Sint,
     Rg32Sfloat,
     Rgb32Uint,
+    Rgb32Sint,
     Rgb32Sfloat,
+    Rgba32Uint,
+    Rgba32Sint,
     Rgba32Sfloat,
+    R64Uint,
+    R64Sint,
+    R64Sfloat,
+    Rg64Uint,
+    Rg64Sint,
+    Rg64Sfloat,
+    Rgb64Uint,
+    Rgb64Sint,
+    Rgb64Sfloat,
+    Rgba64Uint,
+    Rgba64Sint,
+    Rgba64Sfloat,
+    B10G11R11UfloatPack32,
+    E5B9G9R9UfloatPack32,
+    D16Unorm,
+    X8D24UnormPack32,
     D32Sfloat,
+    S8Uint,
+    D16UnormS8Uint,
+    D24UnormS8Uint,
+    D32SfloatS8Uint,
+    Bc1RgbUnormBlock,
+    Bc1RgbSrgbBlock,
+    Bc1RgbaUnormBlock,
+    Bc1RgbaSrgbBlock,
+    Bc2UnormBlock,
+    Bc2SrgbBlock,
+    Bc3UnormBlock,
+    Bc3SrgbBlock,
+    Bc4UnormBlock,
+    Bc4SnormBlock,
+    Bc5UnormBlock,
+    Bc5SnormBlock,
+    Bc6hUfloatBlock,
+    Bc6hSfloatBlock,
+    Bc7UnormBlock,
+    Bc7SrgbBlock,
+    Etc2Rgb8UnormBlock,
+    Etc2Rgb8SrgbBlock,
+    Etc2R8G8B8A1UnormBlock,
+    Etc2R8G8B8A1SrgbBlock,
+    Etc2Rgba8UnormBlock,
+    Etc2Rgba8SrgbBlock,
+    EacR11UnormBlock,
+    EacR11SnormBlock,
+    EacRg11UnormBlock,
+    EacRg11SnormBlock,
+    Astc4x4UnormBlock,
+    Astc4x4SrgbBlock,
+    Astc5x4UnormBlock,
+    Astc5x4SrgbBlock,
+    Astc5x5UnormBlock,
+    Astc5x5SrgbBlock,
+    Astc6x5UnormBlock,
+    Astc6x5SrgbBlock,
+    Astc6x6UnormBlock,
+    Astc6x6SrgbBlock,
+    Astc8x5UnormBlock,
+    Astc8x5SrgbBlock,
+    Astc8x6UnormBlock,
+    Astc8x6SrgbBlock,
+    Astc8x8UnormBlock,
+    Astc8x8SrgbBlock,
+    Astc10x5UnormBlock,
+    Astc10x5SrgbBlock,
+    Astc10x6UnormBlock,
+    Astc10x6SrgbBlock,
+    Astc10x8UnormBlock,
+    Astc10x8SrgbBlock,
+    Astc10x10UnormBlock,
+    Astc10x10SrgbBlock,
+    Astc12x10UnormBlock,
+    Astc12x10SrgbBlock,
+    Astc12x12UnormBlock,
+    Astc12x12SrgbBlock,
+    //I420, sampled through a YcbcrTexture
+    G8B8R83Plane420Unorm,
+    //NV12, sampled through a YcbcrTexture
+    G8B8R82Plane420Unorm,
+    //P010, sampled through a YcbcrTexture
+    G10X6B10X6R10X62Plane420Unorm3Pack16,
 }
 
 impl Format {
+    //Bytes per texel, block compressed formats only have a size per block, see block_bytes
     pub fn to_bytes(self) -> usize {
         match self {
-            Self::R16Uint => 2,
-            Self::Rgba8Srgb
+            Self::Rg4UnormPack8
+            | Self::R8Unorm
+            | Self::R8Snorm
+            | Self::R8Uscaled
+            | Self::R8Sscaled
+            | Self::R8Uint
+            | Self::R8Sint
+            | Self::R8Srgb
+            | Self::S8Uint => 1,
+            Self::Rgba4UnormPack16
+            | Self::Bgra4UnormPack16
+            | Self::R5G6B5UnormPack16
+            | Self::B5G6R5UnormPack16
+            | Self::R5G5B5A1UnormPack16
+            | Self::B5G5R5A1UnormPack16
+            | Self::A1R5G5B5UnormPack16
+            | Self::Rg8Unorm
+            | Self::Rg8Snorm
+            | Self::Rg8Uscaled
+            | Self::Rg8Sscaled
+            | Self::Rg8Uint
+            | Self::Rg8Sint
+            | Self::Rg8Srgb
+            | Self::R16Unorm
+            | Self::R16Snorm
+            | Self::R16Uscaled
+            | Self::R16Sscaled
+            | Self::R16Uint
+            | Self::R16Sint
+            | Self::R16Sfloat
+            | Self::D16Unorm => 2,
+            Self::Rgb8Unorm
+            | Self::Rgb8Snorm
+            | Self::Rgb8Uscaled
+            | Self::Rgb8Sscaled
+            | Self::Rgb8Uint
+            | Self::Rgb8Sint
+            | Self::Rgb8Srgb
+            | Self::Bgr8Unorm
+            | Self::Bgr8Snorm
+            | Self::Bgr8Uscaled
+            | Self::Bgr8Sscaled
+            | Self::Bgr8Uint
+            | Self::Bgr8Sint
+            | Self::Bgr8Srgb
+            | Self::D16UnormS8Uint => 3,
+            Self::Rgba8Unorm
+            | Self::Rgba8Snorm
+            | Self::Rgba8Uscaled
+            | Self::Rgba8Sscaled
+            | Self::Rgba8Uint
+            | Self::Rgba8Sint
+            | Self::Rgba8Srgb
+            | Self::Bgra8Unorm
+            | Self::Bgra8Snorm
+            | Self::Bgra8Uscaled
+            | Self::Bgra8Sscaled
+            | Self::Bgra8Uint
+            | Self::Bgra8Sint
             | Self::Bgra8Srgb
+            | Self::Abgr8UnormPack32
+            | Self::Abgr8SnormPack32
+            | Self::Abgr8UscaledPack32
+            | Self::Abgr8SscaledPack32
+            | Self::Abgr8UintPack32
+            | Self::Abgr8SintPack32
+            | Self::Abgr8SrgbPack32
+            | Self::A2R10G10B10UnormPack32
+            | Self::A2R10G10B10SnormPack32
+            | Self::A2R10G10B10UscaledPack32
+            | Self::A2R10G10B10SscaledPack32
+            | Self::A2R10G10B10UintPack32
+            | Self::A2R10G10B10SintPack32
+            | Self::A2B10G10R10UnormPack32
+            | Self::A2B10G10R10SnormPack32
+            | Self::A2B10G10R10UscaledPack32
+            | Self::A2B10G10R10SscaledPack32
+            | Self::A2B10G10R10UintPack32
+            | Self::A2B10G10R10SintPack32
+            | Self::Rg16Unorm
+            | Self::Rg16Snorm
+            | Self::Rg16Uscaled
+            | Self::Rg16Sscaled
+            | Self::Rg16Uint
+            | Self::Rg16Sint
+            | Self::Rg16Sfloat
             | Self::R32Uint
+            | Self::R32Sint
             | Self::R32Sfloat
-            | Self::D32Sfloat => 4,
-            Self::Rg32Sfloat => 8,
-            Self::Rgb32Uint | Self::Rgb32Sfloat => 12,
-            Self::Rgba32Sfloat => 16,
+            | Self::B10G11R11UfloatPack32
+            | Self::E5B9G9R9UfloatPack32
+            | Self::X8D24UnormPack32
+            | Self::D32Sfloat
+            | Self::D24UnormS8Uint => 4,
+            Self::D32SfloatS8Uint => 5,
+            Self::Rgb16Unorm
+            | Self::Rgb16Snorm
+            | Self::Rgb16Uscaled
+            | Self::Rgb16Sscaled
+            | Self::Rgb16Uint
+            | Self::Rgb16Sint
+            | Self::Rgb16Sfloat => 6,
+            Self::Rgba16Unorm
+            | Self::Rgba16Snorm
+            | Self::Rgba16Uscaled
+            | Self::Rgba16Sscaled
+            | Self::Rgba16Uint
+            | Self::Rgba16Sint
+            | Self::Rgba16Sfloat
+            | Self::Rg32Uint
+            | Self::Rg32Sint
+            | Self::Rg32Sfloat
+            | Self::R64Uint
+            | Self::R64Sint
+            | Self::R64Sfloat => 8,
+            Self::Rgb32Uint | Self::Rgb32Sint | Self::Rgb32Sfloat => 12,
+            Self::Rgba32Uint
+            | Self::Rgba32Sint
+            | Self::Rgba32Sfloat
+            | Self::Rg64Uint
+            | Self::Rg64Sint
+            | Self::Rg64Sfloat => 16,
+            Self::Rgb64Uint | Self::Rgb64Sint | Self::Rgb64Sfloat => 24,
+            Self::Rgba64Uint | Self::Rgba64Sint | Self::Rgba64Sfloat => 32,
+            Self::G8B8R83Plane420Unorm
+            | Self::G8B8R82Plane420Unorm
+            | Self::G10X6B10X6R10X62Plane420Unorm3Pack16 => {
+                panic!("multi-planar formats have a size per plane, see plane_bytes")
+            }
+            _ => panic!("block compressed formats have a size per block, see block_bytes"),
+        }
+    }
+
+    pub fn is_compressed(self) -> bool {
+        self.block_extent() != (1, 1)
+    }
+
+    //Texels covered by one block, (1, 1) for uncompressed formats
+    pub fn block_extent(self) -> (u32, u32) {
+        match self {
+            Self::Bc1RgbUnormBlock
+            | Self::Bc1RgbSrgbBlock
+            | Self::Bc1RgbaUnormBlock
+            | Self::Bc1RgbaSrgbBlock
+            | Self::Bc2UnormBlock
+            | Self::Bc2SrgbBlock
+            | Self::Bc3UnormBlock
+            | Self::Bc3SrgbBlock
+            | Self::Bc4UnormBlock
+            | Self::Bc4SnormBlock
+            | Self::Bc5UnormBlock
+            | Self::Bc5SnormBlock
+            | Self::Bc6hUfloatBlock
+            | Self::Bc6hSfloatBlock
+            | Self::Bc7UnormBlock
+            | Self::Bc7SrgbBlock
+            | Self::Etc2Rgb8UnormBlock
+            | Self::Etc2Rgb8SrgbBlock
+            | Self::Etc2R8G8B8A1UnormBlock
+            | Self::Etc2R8G8B8A1SrgbBlock
+            | Self::Etc2Rgba8UnormBlock
+            | Self::Etc2Rgba8SrgbBlock
+            | Self::EacR11UnormBlock
+            | Self::EacR11SnormBlock
+            | Self::EacRg11UnormBlock
+            | Self::EacRg11SnormBlock
+            | Self::Astc4x4UnormBlock
+            | Self::Astc4x4SrgbBlock => (4, 4),
+            Self::Astc5x4UnormBlock | Self::Astc5x4SrgbBlock => (5, 4),
+            Self::Astc5x5UnormBlock | Self::Astc5x5SrgbBlock => (5, 5),
+            Self::Astc6x5UnormBlock | Self::Astc6x5SrgbBlock => (6, 5),
+            Self::Astc6x6UnormBlock | Self::Astc6x6SrgbBlock => (6, 6),
+            Self::Astc8x5UnormBlock | Self::Astc8x5SrgbBlock => (8, 5),
+            Self::Astc8x6UnormBlock | Self::Astc8x6SrgbBlock => (8, 6),
+            Self::Astc8x8UnormBlock | Self::Astc8x8SrgbBlock => (8, 8),
+            Self::Astc10x5UnormBlock | Self::Astc10x5SrgbBlock => (10, 5),
+            Self::Astc10x6UnormBlock | Self::Astc10x6SrgbBlock => (10, 6),
+            Self::Astc10x8UnormBlock | Self::Astc10x8SrgbBlock => (10, 8),
+            Self::Astc10x10UnormBlock | Self::Astc10x10SrgbBlock => (10, 10),
+            Self::Astc12x10UnormBlock | Self::Astc12x10SrgbBlock => (12, 10),
+            Self::Astc12x12UnormBlock | Self::Astc12x12SrgbBlock => (12, 12),
+            _ => (1, 1),
+        }
+    }
+
+    //Bytes per block, the same as to_bytes for uncompressed formats
+    pub fn block_bytes(self) -> usize {
+        match self {
+            Self::Bc1RgbUnormBlock
+            | Self::Bc1RgbSrgbBlock
+            | Self::Bc1RgbaUnormBlock
+            | Self::Bc1RgbaSrgbBlock
+            | Self::Bc4UnormBlock
+            | Self::Bc4SnormBlock
+            | Self::Etc2Rgb8UnormBlock
+            | Self::Etc2Rgb8SrgbBlock
+            | Self::Etc2R8G8B8A1UnormBlock
+            | Self::Etc2R8G8B8A1SrgbBlock
+            | Self::EacR11UnormBlock
+            | Self::EacR11SnormBlock => 8,
+            Self::Bc2UnormBlock
+            | Self::Bc2SrgbBlock
+            | Self::Bc3UnormBlock
+            | Self::Bc3SrgbBlock
+            | Self::Bc5UnormBlock
+            | Self::Bc5SnormBlock
+            | Self::Bc6hUfloatBlock
+            | Self::Bc6hSfloatBlock
+            | Self::Bc7UnormBlock
+            | Self::Bc7SrgbBlock
+            | Self::Etc2Rgba8UnormBlock
+            | Self::Etc2Rgba8SrgbBlock
+            | Self::EacRg11UnormBlock
+            | Self::EacRg11SnormBlock
+            | Self::Astc4x4UnormBlock
+            | Self::Astc4x4SrgbBlock
+            | Self::Astc5x4UnormBlock
+            | Self::Astc5x4SrgbBlock
+            | Self::Astc5x5UnormBlock
+            | Self::Astc5x5SrgbBlock
+            | Self::Astc6x5UnormBlock
+            | Self::Astc6x5SrgbBlock
+            | Self::Astc6x6UnormBlock
+            | Self::Astc6x6SrgbBlock
+            | Self::Astc8x5UnormBlock
+            | Self::Astc8x5SrgbBlock
+            | Self::Astc8x6UnormBlock
+            | Self::Astc8x6SrgbBlock
+            | Self::Astc8x8UnormBlock
+            | Self::Astc8x8SrgbBlock
+            | Self::Astc10x5UnormBlock
+            | Self::Astc10x5SrgbBlock
+            | Self::Astc10x6UnormBlock
+            | Self::Astc10x6SrgbBlock
+            | Self::Astc10x8UnormBlock
+            | Self::Astc10x8SrgbBlock
+            | Self::Astc10x10UnormBlock
+            | Self::Astc10x10SrgbBlock
+            | Self::Astc12x10UnormBlock
+            | Self::Astc12x10SrgbBlock
+            | Self::Astc12x12UnormBlock
+            | Self::Astc12x12SrgbBlock => 16,
+            _ => self.to_bytes(),
+        }
+    }
+
+    //Bytes of a tightly packed extent, partial blocks at the edges count in full
+    pub fn size(self, extent: (u32, u32)) -> usize {
+        let (width, height) = self.block_extent();
+
+        let blocks =
+            ((extent.0 + width - 1) / width) as usize * ((extent.1 + height - 1) / height) as usize;
+
+        blocks * self.block_bytes()
+    }
+
+    pub fn is_depth(self) -> bool {
+        matches!(
+            self,
+            Self::D16Unorm
+                | Self::X8D24UnormPack32
+                | Self::D32Sfloat
+                | Self::D16UnormS8Uint
+                | Self::D24UnormS8Uint
+                | Self::D32SfloatS8Uint
+        )
+    }
+
+    pub fn has_stencil(self) -> bool {
+        matches!(
+            self,
+            Self::S8Uint | Self::D16UnormS8Uint | Self::D24UnormS8Uint | Self::D32SfloatS8Uint
+        )
+    }
+
+    //Whether the hardware applies the sRGB transfer function on store and sample
+    pub fn is_srgb(self) -> bool {
+        matches!(
+            self,
+            Self::R8Srgb
+                | Self::Rg8Srgb
+                | Self::Rgb8Srgb
+                | Self::Bgr8Srgb
+                | Self::Rgba8Srgb
+                | Self::Bgra8Srgb
+                | Self::Abgr8SrgbPack32
+                | Self::Bc1RgbSrgbBlock
+                | Self::Bc1RgbaSrgbBlock
+                | Self::Bc2SrgbBlock
+                | Self::Bc3SrgbBlock
+                | Self::Bc7SrgbBlock
+                | Self::Etc2Rgb8SrgbBlock
+                | Self::Etc2R8G8B8A1SrgbBlock
+                | Self::Etc2Rgba8SrgbBlock
+                | Self::Astc4x4SrgbBlock
+                | Self::Astc5x4SrgbBlock
+                | Self::Astc5x5SrgbBlock
+                | Self::Astc6x5SrgbBlock
+                | Self::Astc6x6SrgbBlock
+                | Self::Astc8x5SrgbBlock
+                | Self::Astc8x6SrgbBlock
+                | Self::Astc8x8SrgbBlock
+                | Self::Astc10x5SrgbBlock
+                | Self::Astc10x6SrgbBlock
+                | Self::Astc10x8SrgbBlock
+                | Self::Astc10x10SrgbBlock
+                | Self::Astc12x10SrgbBlock
+                | Self::Astc12x12SrgbBlock
+        )
+    }
+
+    pub fn plane_count(self) -> usize {
+        match self {
+            Self::G8B8R83Plane420Unorm => 3,
+            Self::G8B8R82Plane420Unorm | Self::G10X6B10X6R10X62Plane420Unorm3Pack16 => 2,
+            _ => 1,
         }
     }
-}
 
-impl From<Format> for vk::Format {
-    fn from(format: Format) -> Self {
-        match format {
-            Format::Rgba8Srgb => Self::Rgba8Srgb,
-            Format::Bgra8Srgb => Self::Bgra8Srgb,
-            Format::R16Uint => Self::R16Uint,
-            Format::R32Uint => Self::R32Uint,
-            Format::R32Sfloat => Self::R32Sfloat,
-            Format::Rg32Sfloat => Self::Rg32Sfloat,
-            Format::Rg32Sfloat => Self::Rg32Sfloat,
-            Format::Rgb32Uint => Self::Rgb32Uint,
-            Format::Rgb32Sfloat => Self::Rgb32Sfloat,
-            Format::Rgba32Sfloat => Self::Rgba32Sfloat,
-            Format::D32Sfloat => Self::D32Sfloat,
+    //Bytes per texel of a plane, chroma planes of the 420 formats are half size
+    pub fn plane_bytes(self, plane: usize) -> usize {
+        match (self, plane) {
+            (Self::G8B8R83Plane420Unorm, 0..=2) => 1,
+            (Self::G8B8R82Plane420Unorm, 0) => 1,
+            (Self::G8B8R82Plane420Unorm, 1) => 2,
+            (Self::G10X6B10X6R10X62Plane420Unorm3Pack16, 0) => 2,
+            (Self::G10X6B10X6R10X62Plane420Unorm3Pack16, 1) => 4,
+            (_, 0) => self.to_bytes(),
+            _ => panic!("format has no plane {}", plane),
         }
     }
+
+    pub fn plane_extent(self, plane: usize, extent: (u32, u32)) -> (u32, u32) {
+        match plane {
+            0 => extent,
+            _ if self.plane_count() > plane => ((extent.0 + 1) / 2, (extent.1 + 1) / 2),
+            _ => panic!("format has no plane {}", plane),
+        }
+    }
+}
+
+bitflags! {
+    pub struct FormatFeatures: usize {
+        const SAMPLED               = 1 << 0;
+        const SAMPLED_FILTER_LINEAR = 1 << 1;
+        const STORAGE               = 1 << 2;
+        const STORAGE_ATOMIC        = 1 << 3;
+        const COLOR_ATTACHMENT      = 1 << 4;
+        const COLOR_ATTACHMENT_BLEND = 1 << 5;
+        const DEPTH_STENCIL         = 1 << 6;
+        const BLIT_SRC              = 1 << 7;
+        const BLIT_DST              = 1 << 8;
+        const TRANSFER_SRC          = 1 << 9;
+        const TRANSFER_DST          = 1 << 10;
+    }
 }
 
-impl From<vk::Format> for Format {
-    fn from(format: vk::Format) -> Self {
-        match format {
-            vk::Format::Rgba8Srgb => Self::Rgba8Srgb,
-            vk::Format::Bgra8Srgb => Self::Bgra8Srgb,
-            vk::Format::R16Uint => Self::R16Uint,
-            vk::Format::R32Uint => Self::R32Uint,
-            vk::Format::R32Sfloat => Self::R32Sfloat,
-            vk::Format::Rg32Sfloat => Self::Rg32Sfloat,
-            vk::Format::Rg32Sfloat => Self::Rg32Sfloat,
-            vk::Format::Rgb32Uint => Self::Rgb32Uint,
-            vk::Format::Rgb32Sfloat => Self::Rgb32Sfloat,
-            vk::Format::Rgba32Sfloat => Self::Rgba32Sfloat,
-            vk::Format::D32Sfloat => Self::D32Sfloat,
+impl FormatFeatures {
+    pub(crate) fn to_vk(self) -> u32 {
+        let mut vk = 0;
+
+        if self.contains(Self::SAMPLED) {
+            vk |= vk::FORMAT_FEATURE_SAMPLED_IMAGE;
+        }
+
+        if self.contains(Self::SAMPLED_FILTER_LINEAR) {
+            vk |= vk::FORMAT_FEATURE_SAMPLED_IMAGE_FILTER_LINEAR;
+        }
+
+        if self.contains(Self::STORAGE) {
+            vk |= vk::FORMAT_FEATURE_STORAGE_IMAGE;
+        }
+
+        if self.contains(Self::STORAGE_ATOMIC) {
+            vk |= vk::FORMAT_FEATURE_STORAGE_IMAGE_ATOMIC;
+        }
+
+        if self.contains(Self::COLOR_ATTACHMENT) {
+            vk |= vk::FORMAT_FEATURE_COLOR_ATTACHMENT;
+        }
+
+        if self.contains(Self::COLOR_ATTACHMENT_BLEND) {
+            vk |= vk::FORMAT_FEATURE_COLOR_ATTACHMENT_BLEND;
+        }
+
+        if self.contains(Self::DEPTH_STENCIL) {
+            vk |= vk::FORMAT_FEATURE_DEPTH_STENCIL_ATTACHMENT;
+        }
+
+        if self.contains(Self::BLIT_SRC) {
+            vk |= vk::FORMAT_FEATURE_BLIT_SRC;
+        }
+
+        if self.contains(Self::BLIT_DST) {
+            vk |= vk::FORMAT_FEATURE_BLIT_DST;
+        }
+
+        if self.contains(Self::TRANSFER_SRC) {
+            vk |= vk::FORMAT_FEATURE_TRANSFER_SRC;
         }
+
+        if self.contains(Self::TRANSFER_DST) {
+            vk |= vk::FORMAT_FEATURE_TRANSFER_DST;
+        }
+
+        vk
     }
 }
+
+macro_rules! impl_from_format {
+    ($from:ty => $to:ty, $($case:ident),*) => {
+        impl From<$from> for $to {
+            fn from(format: $from) -> Self {
+                match format {
+                    $(<$from>::$case => Self::$case),*
+                }
+            }
+        }
+    };
+    ($($case:ident),*) => {
+        impl_from_format!(Format => vk::Format, $($case),*);
+        impl_from_format!(vk::Format => Format, $($case),*);
+    };
+}
+
+impl_from_format!(
+    Rg4UnormPack8,
+    Rgba4UnormPack16,
+    Bgra4UnormPack16,
+    R5G6B5UnormPack16,
+    B5G6R5UnormPack16,
+    R5G5B5A1UnormPack16,
+    B5G5R5A1UnormPack16,
+    A1R5G5B5UnormPack16,
+    R8Unorm,
+    R8Snorm,
+    R8Uscaled,
+    R8Sscaled,
+    R8Uint,
+    R8Sint,
+    R8Srgb,
+    Rg8Unorm,
+    Rg8Snorm,
+    Rg8Uscaled,
+    Rg8Sscaled,
+    Rg8Uint,
+    Rg8Sint,
+    Rg8Srgb,
+    Rgb8Unorm,
+    Rgb8Snorm,
+    Rgb8Uscaled,
+    Rgb8Sscaled,
+    Rgb8Uint,
+    Rgb8Sint,
+    Rgb8Srgb,
+    Bgr8Unorm,
+    Bgr8Snorm,
+    Bgr8Uscaled,
+    Bgr8Sscaled,
+    Bgr8Uint,
+    Bgr8Sint,
+    Bgr8Srgb,
+    Rgba8Unorm,
+    Rgba8Snorm,
+    Rgba8Uscaled,
+    Rgba8Sscaled,
+    Rgba8Uint,
+    Rgba8Sint,
+    Rgba8Srgb,
+    Bgra8Unorm,
+    Bgra8Snorm,
+    Bgra8Uscaled,
+    Bgra8Sscaled,
+    Bgra8Uint,
+    Bgra8Sint,
+    Bgra8Srgb,
+    Abgr8UnormPack32,
+    Abgr8SnormPack32,
+    Abgr8UscaledPack32,
+    Abgr8SscaledPack32,
+    Abgr8UintPack32,
+    Abgr8SintPack32,
+    Abgr8SrgbPack32,
+    A2R10G10B10UnormPack32,
+    A2R10G10B10SnormPack32,
+    A2R10G10B10UscaledPack32,
+    A2R10G10B10SscaledPack32,
+    A2R10G10B10UintPack32,
+    A2R10G10B10SintPack32,
+    A2B10G10R10UnormPack32,
+    A2B10G10R10SnormPack32,
+    A2B10G10R10UscaledPack32,
+    A2B10G10R10SscaledPack32,
+    A2B10G10R10UintPack32,
+    A2B10G10R10SintPack32,
+    R16Unorm,
+    R16Snorm,
+    R16Uscaled,
+    R16Sscaled,
+    R16Uint,
+    R16Sint,
+    R16Sfloat,
+    Rg16Unorm,
+    Rg16Snorm,
+    Rg16Uscaled,
+    Rg16Sscaled,
+    Rg16Uint,
+    Rg16Sint,
+    Rg16Sfloat,
+    Rgb16Unorm,
+    Rgb16Snorm,
+    Rgb16Uscaled,
+    Rgb16Sscaled,
+    Rgb16Uint,
+    Rgb16Sint,
+    Rgb16Sfloat,
+    Rgba16Unorm,
+    Rgba16Snorm,
+    Rgba16Uscaled,
+    Rgba16Sscaled,
+    Rgba16Uint,
+    Rgba16Sint,
+    Rgba16Sfloat,
+    R32Uint,
+    R32Sint,
+    R32Sfloat,
+    Rg32Uint,
+    Rg32Sint,
+    Rg32Sfloat,
+    Rgb32Uint,
+    Rgb32Sint,
+    Rgb32Sfloat,
+    Rgba32Uint,
+    Rgba32Sint,
+    Rgba32Sfloat,
+    R64Uint,
+    R64Sint,
+    R64Sfloat,
+    Rg64Uint,
+    Rg64Sint,
+    Rg64Sfloat,
+    Rgb64Uint,
+    Rgb64Sint,
+    Rgb64Sfloat,
+    Rgba64Uint,
+    Rgba64Sint,
+    Rgba64Sfloat,
+    B10G11R11UfloatPack32,
+    E5B9G9R9UfloatPack32,
+    D16Unorm,
+    X8D24UnormPack32,
+    D32Sfloat,
+    S8Uint,
+    D16UnormS8Uint,
+    D24UnormS8Uint,
+    D32SfloatS8Uint,
+    Bc1RgbUnormBlock,
+    Bc1RgbSrgbBlock,
+    Bc1RgbaUnormBlock,
+    Bc1RgbaSrgbBlock,
+    Bc2UnormBlock,
+    Bc2SrgbBlock,
+    Bc3UnormBlock,
+    Bc3SrgbBlock,
+    Bc4UnormBlock,
+    Bc4SnormBlock,
+    Bc5UnormBlock,
+    Bc5SnormBlock,
+    Bc6hUfloatBlock,
+    Bc6hSfloatBlock,
+    Bc7UnormBlock,
+    Bc7SrgbBlock,
+    Etc2Rgb8UnormBlock,
+    Etc2Rgb8SrgbBlock,
+    Etc2R8G8B8A1UnormBlock,
+    Etc2R8G8B8A1SrgbBlock,
+    Etc2Rgba8UnormBlock,
+    Etc2Rgba8SrgbBlock,
+    EacR11UnormBlock,
+    EacR11SnormBlock,
+    EacRg11UnormBlock,
+    EacRg11SnormBlock,
+    Astc4x4UnormBlock,
+    Astc4x4SrgbBlock,
+    Astc5x4UnormBlock,
+    Astc5x4SrgbBlock,
+    Astc5x5UnormBlock,
+    Astc5x5SrgbBlock,
+    Astc6x5UnormBlock,
+    Astc6x5SrgbBlock,
+    Astc6x6UnormBlock,
+    Astc6x6SrgbBlock,
+    Astc8x5UnormBlock,
+    Astc8x5SrgbBlock,
+    Astc8x6UnormBlock,
+    Astc8x6SrgbBlock,
+    Astc8x8UnormBlock,
+    Astc8x8SrgbBlock,
+    Astc10x5UnormBlock,
+    Astc10x5SrgbBlock,
+    Astc10x6UnormBlock,
+    Astc10x6SrgbBlock,
+    Astc10x8UnormBlock,
+    Astc10x8SrgbBlock,
+    Astc10x10UnormBlock,
+    Astc10x10SrgbBlock,
+    Astc12x10UnormBlock,
+    Astc12x10SrgbBlock,
+    Astc12x12UnormBlock,
+    Astc12x12SrgbBlock,
+    G8B8R83Plane420Unorm,
+    G8B8R82Plane420Unorm,
+    G10X6B10X6R10X62Plane420Unorm3Pack16
+);
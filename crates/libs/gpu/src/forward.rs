@@ -0,0 +1,786 @@
+use crate::compute::storage_binding;
+use crate::prelude::*;
+
+use std::mem;
+
+//matrices[4] and splits of the Cascades block in resources/cascade.glsl
+const CASCADES_SIZE: usize = (4 * 16 + 4) * mem::size_of::<f32>();
+
+//first binding of LightCulling::descriptors in the shading pass, after the shadows
+const CLUSTER_BINDING: u32 = 5;
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct ForwardVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+unsafe impl Pod for ForwardVertex {}
+
+//std430 layout of the material storage buffer, indexed by ForwardDraw::material
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct PbrMaterial {
+    pub base_color: [f32; 4],
+    pub emissive: [f32; 3],
+    pub metallic: f32,
+    pub roughness: f32,
+    pub occlusion: f32,
+    pub padding: [f32; 2],
+}
+
+unsafe impl Pod for PbrMaterial {}
+
+impl Default for PbrMaterial {
+    fn default() -> Self {
+        Self {
+            base_color: [1.0, 1.0, 1.0, 1.0],
+            emissive: [0.0, 0.0, 0.0],
+            metallic: 0.0,
+            roughness: 0.5,
+            occlusion: 1.0,
+            padding: [0.0, 0.0],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum LightKind {
+    Directional,
+    Point,
+}
+
+//std430 layout of the light storage buffer
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ForwardLight {
+    pub position: [f32; 3],
+    pub range: f32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub direction: [f32; 3],
+    pub kind: u32,
+}
+
+unsafe impl Pod for ForwardLight {}
+
+impl ForwardLight {
+    //Directional lights are the only ones that receive the cascaded shadows
+    pub fn directional(direction: [f32; 3], color: [f32; 3], intensity: f32) -> Self {
+        Self {
+            position: [0.0; 3],
+            range: 0.0,
+            color,
+            intensity,
+            direction,
+            kind: LightKind::Directional as u32,
+        }
+    }
+
+    pub fn point(position: [f32; 3], range: f32, color: [f32; 3], intensity: f32) -> Self {
+        Self {
+            position,
+            range,
+            color,
+            intensity,
+            direction: [0.0; 3],
+            kind: LightKind::Point as u32,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ForwardCamera {
+    pub view: [[f32; 4]; 4],
+    pub view_proj: [[f32; 4]; 4],
+    pub position: [f32; 3],
+}
+
+impl ForwardCamera {
+    pub fn new(
+        view: impl Into<ShaderMat4>,
+        view_proj: impl Into<ShaderMat4>,
+        position: impl Into<ShaderVec3>,
+    ) -> Self {
+        Self {
+            view: view.into().0,
+            view_proj: view_proj.into().0,
+            position: position.into().0,
+        }
+    }
+}
+
+pub struct ForwardDraw<'a> {
+    pub vertices: &'a Buffer,
+    //u16 indices
+    pub indices: &'a Buffer,
+    pub index_count: u32,
+    pub model: [[f32; 4]; 4],
+    pub material: u32,
+}
+
+//Resources produced by a cascaded shadow pass, laid out as in resources/cascade.glsl:
+//the Cascades uniform buffer and the depth array sampled as sampler2DArray.
+pub struct ForwardShadows<'a> {
+    pub cascades: &'a Buffer,
+    pub shadow_map: &'a Image,
+}
+
+pub struct ForwardRendererInfo<'a> {
+    pub device: &'a Device,
+    pub format: Format,
+    pub extent: (u32, u32),
+    pub depth: DepthConfig,
+    pub swapchain_images: &'a [Image],
+    //the swapchain's, so render fails with StaleFramebuffer until resize
+    pub generation: Option<&'a FramebufferGeneration>,
+    pub max_materials: usize,
+    pub max_lights: usize,
+    pub shadows: Option<ForwardShadows<'a>>,
+    //None loops every light per pixel, Some shades only the lights culled into each cluster
+    pub clusters: Option<&'a LightCulling>,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ForwardFrame {
+    view: [[f32; 4]; 4],
+    view_proj: [[f32; 4]; 4],
+    camera_position: [f32; 4],
+    light_count: u32,
+    linear_output: u32,
+    padding: [u32; 2],
+}
+
+unsafe impl Pod for ForwardFrame {}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ForwardConstants {
+    model: [[f32; 4]; 4],
+    material: u32,
+}
+
+unsafe impl Pod for ForwardConstants {}
+
+//Reference renderer built only on this crate. Subpass 0 is a depth pre-pass, subpass 1
+//shades each pixel once with an equal depth test, looping every light over a
+//Cook-Torrance material, or only the lights LightCulling assigned to the pixel's
+//cluster, and the result is written straight into the swapchain image.
+//One descriptor set per swapchain image, all pointing at the same buffers since a
+//single frame is in flight.
+pub struct ForwardRenderer {
+    prepass: Pipeline,
+    shading: Pipeline,
+    render_pass: RenderPass,
+    vertex_shader: Shader,
+    fragment_shader: Shader,
+    framebuffers: Vec<Framebuffer>,
+    generation: Option<FramebufferGeneration>,
+    depth_image: Image,
+    frame: Buffer,
+    materials: Buffer,
+    lights: Buffer,
+    light_count: u32,
+    max_materials: usize,
+    max_lights: usize,
+    depth: DepthConfig,
+    shadows: bool,
+    clustered: bool,
+    extent: (u32, u32),
+    linear_output: bool,
+}
+
+impl ForwardRenderer {
+    pub fn new(info: ForwardRendererInfo<'_>) -> Self {
+        let render_pass = RenderPass::new(RenderPassInfo {
+            device: info.device,
+            attachments: &[
+                Attachment {
+                    format: info.format,
+                    samples: SampleCount::One,
+                    load_op: AttachmentLoadOp::Clear,
+                    store_op: AttachmentStoreOp::Store,
+                    initial_layout: ImageLayout::Undefined,
+                    final_layout: ImageLayout::PresentSrc,
+                    layout: ImageLayout::ColorAttachment,
+                    ty: AttachmentType::Color,
+                },
+                info.depth
+                    .attachment(AttachmentLoadOp::Clear, AttachmentStoreOp::DontCare),
+            ],
+            subpasses: &[
+                Subpass {
+                    src: None,
+                    src_access: Access::empty(),
+                    src_stage: PipelineStage::COLOR_ATTACHMENT_OUTPUT
+                        | PipelineStage::EARLY_FRAGMENT_TESTS,
+                    dst: Some(0),
+                    dst_access: Access::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                    dst_stage: PipelineStage::EARLY_FRAGMENT_TESTS,
+                    attachments: &[1],
+                },
+                Subpass {
+                    src: Some(0),
+                    src_access: Access::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                    src_stage: PipelineStage::LATE_FRAGMENT_TESTS,
+                    dst: Some(1),
+                    dst_access: Access::DEPTH_STENCIL_ATTACHMENT_READ
+                        | Access::COLOR_ATTACHMENT_WRITE,
+                    dst_stage: PipelineStage::EARLY_FRAGMENT_TESTS
+                        | PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+                    attachments: &[0, 1],
+                },
+            ],
+        });
+
+        let vertex_shader = Shader::new(ShaderInfo {
+            device: info.device,
+            entry: "main",
+            input: ShaderInput::Embedded {
+                name: "forward.vert",
+                source: include_str!("shaders/forward.vert.glsl"),
+            },
+        });
+
+        let fragment_source = match (info.shadows.is_some(), info.clusters.is_some()) {
+            (false, false) => concat!("#version 450\n", include_str!("shaders/forward.frag.glsl")),
+            (true, false) => concat!(
+                "#version 450\n#define SHADOWS\n",
+                include_str!("shaders/forward.frag.glsl")
+            ),
+            (false, true) => concat!(
+                "#version 450\n#define CLUSTERED\n#define CLUSTER_BINDING 5\n",
+                include_str!("shaders/cluster.glsl"),
+                include_str!("shaders/forward.frag.glsl")
+            ),
+            (true, true) => concat!(
+                "#version 450\n#define SHADOWS\n#define CLUSTERED\n#define CLUSTER_BINDING 5\n",
+                include_str!("shaders/cluster.glsl"),
+                include_str!("shaders/forward.frag.glsl")
+            ),
+        };
+
+        let fragment_shader = Shader::new(ShaderInfo {
+            device: info.device,
+            entry: "main",
+            input: ShaderInput::Embedded {
+                name: "forward.frag",
+                source: fragment_source,
+            },
+        });
+
+        let frame = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::UNIFORM,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: mem::size_of::<ForwardFrame>(),
+            priority: MemoryPriority::Default,
+        });
+
+        let mut materials = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::STORAGE,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: info.max_materials.max(1) * mem::size_of::<PbrMaterial>(),
+            priority: MemoryPriority::Default,
+        });
+
+        //material 0 is valid before any are set
+        materials.write(BufferWrite {
+            offset: 0,
+            data: &[PbrMaterial::default()],
+        });
+
+        let lights = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::STORAGE,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: info.max_lights.max(1) * mem::size_of::<ForwardLight>(),
+            priority: MemoryPriority::Default,
+        });
+
+        let depth_image = Self::new_depth_image(info.device, info.depth, info.extent);
+
+        let framebuffers = Self::new_framebuffers(
+            info.device,
+            &render_pass,
+            &depth_image,
+            info.extent,
+            info.swapchain_images,
+            info.generation,
+        );
+
+        let descriptor_set_count = info.swapchain_images.len() as u32;
+
+        let (mut prepass, mut shading) = Self::new_pipelines(
+            info.device,
+            &render_pass,
+            &vertex_shader,
+            &fragment_shader,
+            info.depth,
+            info.shadows.is_some(),
+            info.clusters,
+            info.extent,
+            descriptor_set_count,
+        );
+
+        for image_index in 0..descriptor_set_count {
+            prepass.bind(image_index, &[Self::frame_binding(&frame)]);
+
+            shading.bind(
+                image_index,
+                &[
+                    Self::frame_binding(&frame),
+                    storage_binding(
+                        1,
+                        &materials,
+                        info.max_materials.max(1) * mem::size_of::<PbrMaterial>(),
+                    ),
+                    storage_binding(
+                        2,
+                        &lights,
+                        info.max_lights.max(1) * mem::size_of::<ForwardLight>(),
+                    ),
+                ],
+            );
+
+            if let Some(shadows) = &info.shadows {
+                Self::bind_shadows(&mut shading, image_index, shadows);
+            }
+
+            if let Some(clusters) = info.clusters {
+                shading.bind(image_index, &clusters.bindings(CLUSTER_BINDING));
+            }
+        }
+
+        let linear_output = OutputEncoding::of(info.format) == OutputEncoding::Hardware;
+
+        Self {
+            prepass,
+            shading,
+            render_pass,
+            vertex_shader,
+            fragment_shader,
+            framebuffers,
+            generation: info.generation.cloned(),
+            depth_image,
+            frame,
+            materials,
+            lights,
+            light_count: 0,
+            max_materials: info.max_materials.max(1),
+            max_lights: info.max_lights.max(1),
+            depth: info.depth,
+            shadows: info.shadows.is_some(),
+            clustered: info.clusters.is_some(),
+            extent: info.extent,
+            linear_output,
+        }
+    }
+
+    fn new_depth_image(device: &Device, depth: DepthConfig, extent: (u32, u32)) -> Image {
+        Image::new(ImageInfo {
+            device,
+            format: depth.format(),
+            usage: ImageUsage::DEPTH_STENCIL | ImageUsage::TRANSIENT,
+            ty: ImageType::TwoDim,
+            extent: (extent.0, extent.1, 1),
+            samples: SampleCount::One,
+            priority: MemoryPriority::High,
+        })
+    }
+
+    fn new_framebuffers(
+        device: &Device,
+        render_pass: &RenderPass,
+        depth_image: &Image,
+        extent: (u32, u32),
+        swapchain_images: &[Image],
+        generation: Option<&FramebufferGeneration>,
+    ) -> Vec<Framebuffer> {
+        swapchain_images
+            .iter()
+            .map(|image| {
+                Framebuffer::new(FramebufferInfo {
+                    device,
+                    render_pass,
+                    extent: (extent.0, extent.1, 1),
+                    attachments: &[image, depth_image],
+                    generation,
+                })
+            })
+            .collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new_pipelines(
+        device: &Device,
+        render_pass: &RenderPass,
+        vertex_shader: &Shader,
+        fragment_shader: &Shader,
+        depth: DepthConfig,
+        shadows: bool,
+        clusters: Option<&LightCulling>,
+        extent: (u32, u32),
+        descriptor_set_count: u32,
+    ) -> (Pipeline, Pipeline) {
+        let vertex_input = [
+            VertexInput {
+                binding: 0,
+                location: 0,
+                format: Format::Rgb32Sfloat,
+                rate: InputRate::Vertex,
+            },
+            VertexInput {
+                binding: 0,
+                location: 1,
+                format: Format::Rgb32Sfloat,
+                rate: InputRate::Vertex,
+            },
+            VertexInput {
+                binding: 0,
+                location: 2,
+                format: Format::Rg32Sfloat,
+                rate: InputRate::Vertex,
+            },
+        ];
+
+        let push_constants = [PushConstantRange {
+            stage: ShaderStage::VERTEX | ShaderStage::FRAGMENT,
+            offset: 0,
+            size: mem::size_of::<ForwardConstants>() as _,
+        }];
+
+        let frame = Descriptor {
+            binding: 0,
+            ty: DescriptorType::UniformBuffer,
+            count: 1,
+            stage: ShaderStage::VERTEX | ShaderStage::FRAGMENT,
+        };
+
+        let prepass = Pipeline::new_graphics_pipeline(GraphicsPipelineInfo {
+            device,
+            render_pass: Some(render_pass),
+            rendering: None,
+            descriptor_set_count,
+            color_count: 0,
+            subpass: 0,
+            extent,
+            cull_mode: CullMode::BACK,
+            vertex_shader,
+            fragment_shader: None,
+            depth_stencil: depth.depth_stencil(true),
+            blend_constants: BlendConstants::Static([0.0, 0.0, 0.0, 0.0]),
+            color_blend: ColorBlend::Disabled,
+            color_write_mask: ColorComponents::default(),
+            samples: SampleCount::One,
+            scissor: Scissor::Static,
+            primitive: Primitive::Triangles,
+            depth_range: DepthRange::default(),
+            vertex_input: &vertex_input,
+            layout: &[frame],
+            push_constants: &push_constants,
+            material_table: None,
+            immutable_samplers: &[],
+            cache: None,
+        });
+
+        let mut layout = vec![
+            frame,
+            Descriptor {
+                binding: 1,
+                ty: DescriptorType::StorageBuffer,
+                count: 1,
+                stage: ShaderStage::FRAGMENT,
+            },
+            Descriptor {
+                binding: 2,
+                ty: DescriptorType::StorageBuffer,
+                count: 1,
+                stage: ShaderStage::FRAGMENT,
+            },
+        ];
+
+        if shadows {
+            layout.push(Descriptor {
+                binding: 3,
+                ty: DescriptorType::UniformBuffer,
+                count: 1,
+                stage: ShaderStage::FRAGMENT,
+            });
+
+            layout.push(Descriptor {
+                binding: 4,
+                ty: DescriptorType::CombinedImageSampler,
+                count: 1,
+                stage: ShaderStage::FRAGMENT,
+            });
+        }
+
+        if let Some(clusters) = clusters {
+            layout.extend(clusters.descriptors(CLUSTER_BINDING, ShaderStage::FRAGMENT));
+        }
+
+        //depth was resolved by the pre-pass, so only the closest surface passes
+        let shading = Pipeline::new_graphics_pipeline(GraphicsPipelineInfo {
+            device,
+            render_pass: Some(render_pass),
+            rendering: None,
+            descriptor_set_count,
+            color_count: 1,
+            subpass: 1,
+            extent,
+            cull_mode: CullMode::BACK,
+            vertex_shader,
+            fragment_shader: Some(fragment_shader),
+            depth_stencil: DepthStencil {
+                test: true,
+                write: false,
+                compare_op: CompareOp::Equal,
+                stencil: None,
+            },
+            blend_constants: BlendConstants::Static([0.0, 0.0, 0.0, 0.0]),
+            color_blend: ColorBlend::Disabled,
+            color_write_mask: ColorComponents::default(),
+            samples: SampleCount::One,
+            scissor: Scissor::Static,
+            primitive: Primitive::Triangles,
+            depth_range: DepthRange::default(),
+            vertex_input: &vertex_input,
+            layout: &layout,
+            push_constants: &push_constants,
+            material_table: None,
+            immutable_samplers: &[],
+            cache: None,
+        });
+
+        (prepass, shading)
+    }
+
+    fn frame_binding(frame: &Buffer) -> Binding<'_> {
+        Binding::Buffer {
+            binding: 0,
+            ty: DescriptorType::UniformBuffer,
+            offset: 0,
+            range: mem::size_of::<ForwardFrame>(),
+            buffer: frame,
+        }
+    }
+
+    fn bind_shadows(shading: &mut Pipeline, image_index: u32, shadows: &ForwardShadows<'_>) {
+        shading.bind(
+            image_index,
+            &[
+                Binding::Buffer {
+                    binding: 3,
+                    ty: DescriptorType::UniformBuffer,
+                    offset: 0,
+                    range: CASCADES_SIZE,
+                    buffer: shadows.cascades,
+                },
+                Binding::Image {
+                    binding: 4,
+                    ty: DescriptorType::CombinedImageSampler,
+                    layout: ImageLayout::ShaderReadOnly,
+                    image: shadows.shadow_map,
+                },
+            ],
+        );
+    }
+
+    pub fn render_pass(&self) -> &RenderPass {
+        &self.render_pass
+    }
+
+    pub fn extent(&self) -> (u32, u32) {
+        self.extent
+    }
+
+    //The ForwardLight buffer to bind to LightCulling
+    pub fn lights(&self) -> &Buffer {
+        &self.lights
+    }
+
+    pub fn max_lights(&self) -> usize {
+        self.max_lights
+    }
+
+    pub fn light_count(&self) -> u32 {
+        self.light_count
+    }
+
+    pub fn set_materials(&mut self, offset: usize, materials: &[PbrMaterial]) {
+        if offset + materials.len() > self.max_materials {
+            panic!("out of forward renderer material slots");
+        }
+
+        self.materials.write(BufferWrite {
+            offset: (offset * mem::size_of::<PbrMaterial>()) as _,
+            data: materials,
+        });
+    }
+
+    //Replaces this frame's lights, any beyond max_lights are dropped
+    pub fn set_lights(&mut self, lights: &[ForwardLight]) {
+        let lights = &lights[..lights.len().min(self.max_lights)];
+
+        if !lights.is_empty() {
+            self.lights.write(BufferWrite {
+                offset: 0,
+                data: lights,
+            });
+        }
+
+        self.light_count = lights.len() as u32;
+    }
+
+    //Rebinds the shadow resources, e.g. after the shadow map is recreated. The renderer
+    //must have been created with shadows. Call outside of command recording.
+    pub fn set_shadows(&mut self, shadows: ForwardShadows<'_>) {
+        if !self.shadows {
+            panic!("forward renderer was created without shadows");
+        }
+
+        for image_index in 0..self.framebuffers.len() as u32 {
+            Self::bind_shadows(&mut self.shading, image_index, &shadows);
+        }
+    }
+
+    //Call from a Graphics::on_swapchain_change listener once the device is idle.
+    //Rebuilds the depth buffer, the framebuffers and, for a new extent, the pipelines.
+    pub fn resize(
+        &mut self,
+        device: &Device,
+        extent: (u32, u32),
+        swapchain_images: &[Image],
+        shadows: Option<ForwardShadows<'_>>,
+        clusters: Option<&LightCulling>,
+    ) {
+        if self.clustered != clusters.is_some() {
+            panic!("forward renderer clusters must match creation");
+        }
+
+        self.framebuffers.clear();
+
+        self.depth_image = Self::new_depth_image(device, self.depth, extent);
+        self.framebuffers = Self::new_framebuffers(
+            device,
+            &self.render_pass,
+            &self.depth_image,
+            extent,
+            swapchain_images,
+            self.generation.as_ref(),
+        );
+        self.extent = extent;
+
+        let (mut prepass, mut shading) = Self::new_pipelines(
+            device,
+            &self.render_pass,
+            &self.vertex_shader,
+            &self.fragment_shader,
+            self.depth,
+            self.shadows,
+            clusters,
+            extent,
+            swapchain_images.len() as u32,
+        );
+
+        for image_index in 0..swapchain_images.len() as u32 {
+            prepass.bind(image_index, &[Self::frame_binding(&self.frame)]);
+
+            shading.bind(
+                image_index,
+                &[
+                    Self::frame_binding(&self.frame),
+                    storage_binding(
+                        1,
+                        &self.materials,
+                        self.max_materials * mem::size_of::<PbrMaterial>(),
+                    ),
+                    storage_binding(
+                        2,
+                        &self.lights,
+                        self.max_lights * mem::size_of::<ForwardLight>(),
+                    ),
+                ],
+            );
+
+            if let Some(shadows) = &shadows {
+                Self::bind_shadows(&mut shading, image_index, shadows);
+            }
+
+            if let Some(clusters) = clusters {
+                shading.bind(image_index, &clusters.bindings(CLUSTER_BINDING));
+            }
+        }
+
+        self.prepass = prepass;
+        self.shading = shading;
+    }
+
+    //Records the pre-pass and the shading pass into the swapchain image at image_index,
+    //leaving it in the present layout. Shadow maps must be rendered before this.
+    pub fn render(
+        &mut self,
+        commands: &mut Commands<'_>,
+        image_index: u32,
+        camera: &ForwardCamera,
+        draws: &[ForwardDraw<'_>],
+    ) -> Result<(), Error> {
+        let [x, y, z] = camera.position;
+
+        self.frame.write(BufferWrite {
+            offset: 0,
+            data: &[ForwardFrame {
+                view: camera.view,
+                view_proj: camera.view_proj,
+                camera_position: [x, y, z, 1.0],
+                light_count: self.light_count,
+                linear_output: self.linear_output as u32,
+                padding: [0; 2],
+            }],
+        });
+
+        commands.begin_render_pass(RenderPassBeginInfo {
+            render_pass: &self.render_pass,
+            framebuffer: &self.framebuffers[image_index as usize],
+            color_clear_values: &[[0.0, 0.0, 0.0, 1.0]],
+            depth_stencil_clear_value: self.depth.depth_stencil_clear_value(),
+        })?;
+
+        commands.bind_pipeline(image_index, &self.prepass);
+        Self::record_draws(commands, &self.prepass, draws);
+
+        commands.next_subpass();
+
+        commands.bind_pipeline(image_index, &self.shading);
+        Self::record_draws(commands, &self.shading, draws);
+
+        commands.end_render_pass();
+
+        Ok(())
+    }
+
+    fn record_draws(commands: &mut Commands<'_>, pipeline: &Pipeline, draws: &[ForwardDraw<'_>]) {
+        for draw in draws {
+            let constants = ForwardConstants {
+                model: draw.model,
+                material: draw.material,
+            };
+
+            commands.push_constants(
+                pipeline,
+                ShaderStage::VERTEX | ShaderStage::FRAGMENT,
+                0,
+                &[constants],
+            );
+            commands.bind_vertex_buffers(0, &[draw.vertices], &[0]);
+            commands.bind_index_buffer(draw.indices, 0);
+            commands.draw_indexed(draw.index_count, 1, 0, 0, 0);
+        }
+    }
+}
@@ -1,16 +1,42 @@
 use crate::prelude::*;
 
+use std::cell::Cell;
+use std::rc::Rc;
+
+//Counter shared by everything sized after the swapchain, a Swapchain advances it on
+//every recreation. Framebuffers remember the value they were created at and refuse to
+//begin a render pass once it moved on.
+#[derive(Clone, Default)]
+pub struct FramebufferGeneration(Rc<Cell<u64>>);
+
+impl FramebufferGeneration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.get()
+    }
+
+    pub fn advance(&self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
 pub struct FramebufferInfo<'a> {
     pub device: &'a Device,
     pub render_pass: &'a RenderPass,
     pub extent: (u32, u32, u32),
     pub attachments: &'a [&'a Image],
+    //Some for framebuffers of swapchain images or swapchain sized targets
+    pub generation: Option<&'a FramebufferGeneration>,
 }
 
 pub enum Framebuffer {
     Vulkan {
         framebuffer: vk::Framebuffer,
         extent: (u32, u32, u32),
+        generation: Option<(FramebufferGeneration, u64)>,
     },
 }
 
@@ -47,8 +73,142 @@ impl Framebuffer {
                 Self::Vulkan {
                     framebuffer,
                     extent: info.extent,
+                    generation: info
+                        .generation
+                        .map(|generation| (generation.clone(), generation.get())),
+                }
+            }
+        }
+    }
+
+    pub fn is_stale(&self) -> bool {
+        match self {
+            Self::Vulkan { generation, .. } => generation
+                .as_ref()
+                .map_or(false, |(generation, created)| generation.get() != *created),
+        }
+    }
+}
+
+pub struct LayeredRenderTargetInfo<'a> {
+    pub device: &'a Device,
+    pub format: Format,
+    pub usage: ImageUsage,
+    pub extent: (u32, u32),
+    pub layers: u32,
+}
+
+pub struct LayeredRenderTarget {
+    framebuffer: Framebuffer,
+    render_pass: RenderPass,
+    image: Image,
+}
+
+impl LayeredRenderTarget {
+    pub fn new(info: LayeredRenderTargetInfo<'_>) -> Self {
+        match info.device {
+            Device::Vulkan {
+                physical_device,
+                device,
+                ..
+            } => {
+                let depth = info.format.is_depth() || info.format.has_stencil();
+
+                let (usage, ty, layout, access, stage) = if depth {
+                    (
+                        info.usage | ImageUsage::DEPTH_STENCIL,
+                        AttachmentType::DepthStencil,
+                        ImageLayout::DepthStencilAttachment,
+                        Access::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                        PipelineStage::EARLY_FRAGMENT_TESTS,
+                    )
+                } else {
+                    (
+                        info.usage | ImageUsage::COLOR,
+                        AttachmentType::Color,
+                        ImageLayout::ColorAttachment,
+                        Access::COLOR_ATTACHMENT_WRITE,
+                        PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+                    )
+                };
+
+                let (image, memory) = Image::new_managed_vk_image(
+                    &physical_device,
+                    device.clone(),
+                    info.format.into(),
+                    usage.to_vk(),
+                    vk::ImageType::TwoDim,
+                    (info.extent.0, info.extent.1, 1),
+                    info.layers,
+                    SampleCount::One,
+                    MemoryPriority::Default,
+                );
+
+                let (view, sampler) = Image::new_vk_image_view(
+                    device.clone(),
+                    &image,
+                    info.format.into(),
+                    vk::ImageViewType::TwoDimArray,
+                    info.layers,
+                );
+
+                let image = Image::Vulkan {
+                    image,
+                    format: info.format.into(),
+                    memory: Some(memory),
+                    view,
+                    sampler,
+                };
+
+                let render_pass = RenderPass::new(RenderPassInfo {
+                    device: info.device,
+                    attachments: &[Attachment {
+                        format: info.format,
+                        samples: SampleCount::One,
+                        load_op: AttachmentLoadOp::Clear,
+                        store_op: AttachmentStoreOp::Store,
+                        initial_layout: ImageLayout::Undefined,
+                        final_layout: ImageLayout::ShaderReadOnly,
+                        layout,
+                        ty,
+                    }],
+                    subpasses: &[Subpass {
+                        src: None,
+                        src_access: Access::empty(),
+                        src_stage: stage,
+                        dst: Some(0),
+                        dst_access: access,
+                        dst_stage: stage,
+                        attachments: &[0],
+                    }],
+                });
+
+                let framebuffer = Framebuffer::new(FramebufferInfo {
+                    device: info.device,
+                    render_pass: &render_pass,
+                    extent: (info.extent.0, info.extent.1, info.layers),
+                    attachments: &[&image],
+                    generation: None,
+                });
+
+                Self {
+                    framebuffer,
+                    render_pass,
+                    image,
                 }
             }
         }
     }
+
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    pub fn render_pass(&self) -> &RenderPass {
+        &self.render_pass
+    }
+
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
 }
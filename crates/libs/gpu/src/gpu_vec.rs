@@ -0,0 +1,178 @@
+use crate::prelude::*;
+
+use std::marker::PhantomData;
+use std::mem;
+
+pub struct GrowableGpuVecInfo<'a> {
+    pub device: &'a Device,
+    //names the buffer in the registry
+    pub label: &'a str,
+    //elements allocated up front
+    pub capacity: usize,
+    //on top of STORAGE, TRANSFER_SRC and TRANSFER_DST
+    pub usage: BufferUsage,
+}
+
+//A storage buffer used like a Vec, e.g. a sparse voxel octree's node pool. The buffer
+//lives in a ResourceRegistry and growing swaps a larger one in behind the same handle,
+//copying the elements over with a transfer. Descriptors bound through RegistryBindings
+//follow on their next refresh, the old buffer is retired to the device's deletion queue.
+pub struct GrowableGpuVec<T: Pod> {
+    buffer: BufferHandle,
+    len: usize,
+    capacity: usize,
+    usage: BufferUsage,
+    marker: PhantomData<T>,
+}
+
+impl<T: Pod> GrowableGpuVec<T> {
+    pub fn new(info: GrowableGpuVecInfo<'_>, registry: &mut ResourceRegistry) -> Self {
+        let capacity = info.capacity.max(1);
+
+        let usage = info.usage
+            | BufferUsage::STORAGE
+            | BufferUsage::TRANSFER_SRC
+            | BufferUsage::TRANSFER_DST;
+
+        let buffer = Self::allocate(info.device, usage, capacity)
+            .expect("failed to allocate memory");
+
+        Self {
+            buffer: registry.insert_buffer(info.label, buffer),
+            len: 0,
+            capacity,
+            usage,
+            marker: PhantomData,
+        }
+    }
+
+    //Appends values, growing first when they do not fit, and returns the index of the
+    //first. Growing records on the device's command buffer, so not while a frame is pending.
+    pub fn push(
+        &mut self,
+        device: &mut Device,
+        registry: &mut ResourceRegistry,
+        values: &[T],
+    ) -> Result<usize, Error> {
+        let index = self.len;
+
+        self.reserve(device, registry, values.len())?;
+
+        self.len += values.len();
+
+        self.write(registry, index, values)?;
+
+        Ok(index)
+    }
+
+    //Overwrites values from index on, which must stay within len
+    pub fn write(
+        &mut self,
+        registry: &mut ResourceRegistry,
+        index: usize,
+        values: &[T],
+    ) -> Result<(), Error> {
+        if index + values.len() > self.len {
+            panic!(
+                "write of {} elements at {} is out of bounds of {}",
+                values.len(),
+                index,
+                self.len
+            );
+        }
+
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        registry.buffer_mut(self.buffer)?.write(BufferWrite {
+            offset: (index * mem::size_of::<T>()) as _,
+            data: values,
+        });
+
+        Ok(())
+    }
+
+    //Grows to fit additional more elements, at least doubling the capacity
+    pub fn reserve(
+        &mut self,
+        device: &mut Device,
+        registry: &mut ResourceRegistry,
+        additional: usize,
+    ) -> Result<(), Error> {
+        let required = self.len + additional;
+
+        if required <= self.capacity {
+            return Ok(());
+        }
+
+        let capacity = required.max(self.capacity * 2);
+
+        let mut buffer = Self::allocate(device, self.usage, capacity)?;
+
+        if self.len > 0 {
+            device.copy_buffer_to_buffer(BufferCopy {
+                from: registry.buffer(self.buffer)?,
+                to: &mut buffer,
+                src: 0,
+                dst: 0,
+                size: (self.len * mem::size_of::<T>()) as _,
+            });
+        }
+
+        let old = registry.replace_buffer(self.buffer, buffer)?;
+
+        device.retire(old);
+
+        self.capacity = capacity;
+
+        Ok(())
+    }
+
+    //Keeps the capacity
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    //Stays valid across growth, unlike the buffer behind it
+    pub fn handle(&self) -> BufferHandle {
+        self.buffer
+    }
+
+    //A storage buffer descriptor of the whole buffer, for RegistryBindings::bind
+    pub fn binding(&self, set: u32, binding: u32) -> RegistryBinding {
+        RegistryBinding {
+            set,
+            binding,
+            ty: DescriptorType::StorageBuffer,
+            buffer: self.buffer,
+            offset: 0,
+        }
+    }
+
+    fn allocate(device: &Device, usage: BufferUsage, capacity: usize) -> Result<Buffer, Error> {
+        Buffer::try_new(BufferInfo {
+            device,
+            usage,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: capacity * mem::size_of::<T>(),
+            priority: MemoryPriority::Default,
+        })
+    }
+}
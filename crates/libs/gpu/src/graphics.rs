@@ -0,0 +1,182 @@
+use crate::prelude::*;
+
+use raw_window_handle::HasRawWindowHandle;
+
+pub struct GraphicsInfo<'a> {
+    pub window: &'a dyn HasRawWindowHandle,
+    pub swapchain_usage: ImageUsage,
+    pub swapchain_encoding: OutputEncoding,
+    pub settings: RendererSettings,
+    //the window's size in physical pixels, see SwapchainInfo::extent
+    pub extent: Option<(u32, u32)>,
+}
+
+pub struct Graphics {
+    swapchain_listeners: Vec<Box<dyn FnMut(&SwapchainChange)>>,
+    settings_listeners: Vec<Box<dyn FnMut(&SettingsChange)>>,
+    settings: RendererSettings,
+    pending_settings: Option<RendererSettings>,
+    swapchain: SwapchainManager,
+    device: Device,
+    surface: Surface,
+    context: Context,
+}
+
+impl Graphics {
+    pub fn new(info: GraphicsInfo<'_>) -> Self {
+        let context = Context::start();
+
+        let surface = Surface::new(SurfaceInfo {
+            context: &context,
+            window: info.window,
+        });
+
+        let device = Device::choose_best(DeviceInfo {
+            context: &context,
+            surface: Some(&surface),
+        });
+
+        let swapchain = SwapchainManager::new(SwapchainManagerInfo {
+            device: &device,
+            surface: &surface,
+            usage: info.swapchain_usage,
+            encoding: info.swapchain_encoding,
+            vsync: info.settings.vsync,
+            extent: info.extent,
+            hdr_metadata: None,
+            dedicated_sync: false,
+        });
+
+        Self {
+            swapchain_listeners: vec![],
+            settings_listeners: vec![],
+            settings: info.settings,
+            pending_settings: None,
+            swapchain,
+            device,
+            surface,
+            context,
+        }
+    }
+
+    //Recreates the swapchain when it is out of date, see SwapchainManager::acquire
+    pub fn begin_frame(&mut self) -> Result<u32, Error> {
+        self.device.synchronize();
+
+        self.apply_settings();
+
+        let image_index = self.swapchain.acquire(&self.device, &self.surface);
+
+        self.notify_swapchain_change();
+
+        image_index
+    }
+
+    pub fn end_frame(&mut self) -> Result<(), Error> {
+        self.swapchain.present(&mut self.device)
+    }
+
+    //The window's new size in physical pixels, applied by the next begin_frame
+    pub fn resize(&mut self, extent: (u32, u32)) {
+        self.swapchain.resize(extent);
+    }
+
+    pub fn set_hdr_metadata(&mut self, metadata: HdrMetadata) {
+        self.swapchain.set_hdr_metadata(&self.device, metadata);
+    }
+
+    //Does not wait for the device, the old swapchain and its images are retired to the
+    //device's deletion queue. Listeners should retire what they replace the same way.
+    pub fn recreate_swapchain(&mut self) {
+        self.swapchain.recreate(&self.device, &self.surface);
+
+        self.notify_swapchain_change();
+    }
+
+    fn notify_swapchain_change(&mut self) {
+        let Some(change) = self.swapchain.take_change() else {
+            return;
+        };
+
+        for listener in &mut self.swapchain_listeners {
+            listener(&change);
+        }
+    }
+
+    pub fn on_swapchain_change(&mut self, listener: impl FnMut(&SwapchainChange) + 'static) {
+        self.swapchain_listeners.push(Box::new(listener));
+    }
+
+    //The settings in effect, changes are applied at the start of the next begin_frame
+    pub fn settings(&self) -> &RendererSettings {
+        &self.settings
+    }
+
+    //Replaces any change still pending
+    pub fn set_settings(&mut self, settings: RendererSettings) {
+        self.pending_settings = Some(settings);
+    }
+
+    //Called from begin_frame with the device idle, after the swapchain is recreated
+    //for a vsync change and before the next image is acquired
+    pub fn on_settings_change(&mut self, listener: impl FnMut(&SettingsChange) + 'static) {
+        self.settings_listeners.push(Box::new(listener));
+    }
+
+    fn apply_settings(&mut self) {
+        let Some(settings) = self.pending_settings.take() else {
+            return;
+        };
+
+        if settings == self.settings {
+            return;
+        }
+
+        let change = SettingsChange {
+            old: self.settings,
+            new: settings,
+        };
+
+        self.settings = settings;
+
+        //listeners destroy pipelines, attachments and samplers the last frame used
+        self.device.wait_idle();
+
+        if change.vsync_changed() {
+            self.swapchain.set_vsync(settings.vsync);
+            self.recreate_swapchain();
+        }
+
+        for listener in &mut self.settings_listeners {
+            listener(&change);
+        }
+    }
+
+    pub fn swapchain_generation(&self) -> u64 {
+        self.swapchain.generation()
+    }
+
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    pub fn surface(&self) -> &Surface {
+        &self.surface
+    }
+
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    pub fn device_mut(&mut self) -> &mut Device {
+        &mut self.device
+    }
+
+    pub fn swapchain(&self) -> &Swapchain {
+        self.swapchain.swapchain()
+    }
+
+    pub fn swapchain_images(&self) -> &[Image] {
+        self.swapchain.images()
+    }
+}
@@ -0,0 +1,91 @@
+use crate::prelude::*;
+
+pub struct HistoryResourceInfo<'a> {
+    pub device: &'a Device,
+    pub format: Format,
+    pub usage: ImageUsage,
+    pub extent: (u32, u32),
+}
+
+//Ping-ponged target for temporal passes. Write current() and read history(), then advance
+//once the frame is recorded. It is independent of the swapchain and only recreated when
+//resize is given a different extent, after which history is invalid for one frame.
+pub struct HistoryResource {
+    images: [Image; 2],
+    format: Format,
+    usage: ImageUsage,
+    extent: (u32, u32),
+    current: usize,
+    valid: bool,
+}
+
+impl HistoryResource {
+    pub fn new(info: HistoryResourceInfo<'_>) -> Self {
+        let images = Self::images(info.device, info.format, info.usage, info.extent);
+
+        Self {
+            images,
+            format: info.format,
+            usage: info.usage,
+            extent: info.extent,
+            current: 0,
+            valid: false,
+        }
+    }
+
+    //Must run after the frame fence wait since the old images are destroyed
+    pub fn resize(&mut self, device: &Device, extent: (u32, u32)) {
+        if extent == self.extent {
+            return;
+        }
+
+        self.images = Self::images(device, self.format, self.usage, extent);
+        self.extent = extent;
+        self.current = 0;
+        self.valid = false;
+    }
+
+    pub fn advance(&mut self) {
+        self.current ^= 1;
+        self.valid = true;
+    }
+
+    //Forces a reset, e.g. on camera cuts
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+
+    pub fn current(&self) -> &Image {
+        &self.images[self.current]
+    }
+
+    pub fn history(&self) -> &Image {
+        &self.images[self.current ^ 1]
+    }
+
+    //False on the first frame after creation or resize, temporal passes should reset
+    //and ignore history() until the next advance
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    pub fn extent(&self) -> (u32, u32) {
+        self.extent
+    }
+
+    fn images(device: &Device, format: Format, usage: ImageUsage, extent: (u32, u32)) -> [Image; 2] {
+        let image = || {
+            Image::new(ImageInfo {
+                device,
+                format,
+                usage,
+                ty: ImageType::TwoDim,
+                extent: (extent.0, extent.1, 1),
+                samples: SampleCount::One,
+                priority: MemoryPriority::Default,
+            })
+        };
+
+        [image(), image()]
+    }
+}
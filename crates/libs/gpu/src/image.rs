@@ -4,6 +4,8 @@ use std::rc::Rc;
 
 use bitflags::bitflags;
 
+pub use vk::SampleCount;
+
 bitflags! {
     pub struct ImageUsage: usize {
         const TRANSFER_SRC = 1 << 0;
@@ -55,9 +57,34 @@ impl ImageUsage {
 
         vk
     }
+
+    pub(crate) fn from_vk(vk: u32) -> Self {
+        let mut usage = Self::empty();
+
+        for (flag, vk_flag) in [
+            (Self::TRANSFER_SRC, vk::IMAGE_USAGE_TRANSFER_SRC),
+            (Self::TRANSFER_DST, vk::IMAGE_USAGE_TRANSFER_DST),
+            (Self::SAMPLED, vk::IMAGE_USAGE_SAMPLED),
+            (Self::STORAGE, vk::IMAGE_USAGE_STORAGE),
+            (Self::COLOR, vk::IMAGE_USAGE_COLOR_ATTACHMENT),
+            (
+                Self::DEPTH_STENCIL,
+                vk::IMAGE_USAGE_DEPTH_STENCIL_ATTACHMENT,
+            ),
+            (Self::TRANSIENT, vk::IMAGE_USAGE_TRANSIENT_ATTACHMENT),
+            (Self::INPUT, vk::IMAGE_USAGE_INPUT_ATTACHMENT),
+        ] {
+            if vk & vk_flag != 0 {
+                usage |= flag;
+            }
+        }
+
+        usage
+    }
 }
 
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "replay", derive(serde::Serialize, serde::Deserialize))]
 pub enum ImageLayout {
     Undefined,
     General,
@@ -119,6 +146,76 @@ pub struct ImageInfo<'a> {
     pub usage: ImageUsage,
     pub ty: ImageType,
     pub extent: (u32, u32, u32),
+    //above One for multisampled attachments, resolved with Attachment::Resolve or
+    //Commands::resolve_image before sampling
+    pub samples: SampleCount,
+    pub priority: MemoryPriority,
+}
+
+pub struct ImagePlane {
+    pub offset: u64,
+    pub row_pitch: u64,
+}
+
+pub struct ImageImportInfo<'a> {
+    pub device: &'a Device,
+    pub format: Format,
+    pub usage: ImageUsage,
+    pub extent: (u32, u32),
+    //closed by the image once imported, pass a dup of an fd that is still needed
+    pub fd: i32,
+    pub drm_format_modifier: u64,
+    pub planes: &'a [ImagePlane],
+}
+
+//Regions of Commands::copy_image, over the first array layer of each mip level
+#[derive(Clone, Copy, Debug)]
+pub struct ImageCopy {
+    pub src_mip_level: u32,
+    pub src_offset: (i32, i32, i32),
+    pub dst_mip_level: u32,
+    pub dst_offset: (i32, i32, i32),
+    pub extent: (u32, u32, u32),
+}
+
+//Regions of Commands::blit_image, the offsets are opposite corners and the source
+//is scaled to fit the destination
+#[derive(Clone, Copy, Debug)]
+pub struct ImageBlit {
+    pub src_mip_level: u32,
+    pub src_offsets: [(i32, i32, i32); 2],
+    pub dst_mip_level: u32,
+    pub dst_offsets: [(i32, i32, i32); 2],
+}
+
+impl ImageBlit {
+    //Halves mip level - 1 of an image with the given base extent into mip level
+    pub fn downsample(extent: (u32, u32), mip_level: u32) -> Self {
+        let level = |mip_level: u32| {
+            (
+                (extent.0 >> mip_level).max(1) as i32,
+                (extent.1 >> mip_level).max(1) as i32,
+                1,
+            )
+        };
+
+        Self {
+            src_mip_level: mip_level - 1,
+            src_offsets: [(0, 0, 0), level(mip_level - 1)],
+            dst_mip_level: mip_level,
+            dst_offsets: [(0, 0, 0), level(mip_level)],
+        }
+    }
+}
+
+//Regions of Commands::resolve_image
+#[derive(Clone, Copy, Debug)]
+pub struct ImageResolve {
+    pub src_mip_level: u32,
+    pub src_offset: (i32, i32, i32),
+    pub dst_mip_level: u32,
+    pub dst_offset: (i32, i32, i32),
+    pub extent: (u32, u32, u32),
 }
 
 #[non_exhaustive]
@@ -147,6 +244,9 @@ impl Image {
                     info.usage.to_vk(),
                     info.ty.to_vk_image(),
                     info.extent,
+                    1,
+                    info.samples,
+                    info.priority,
                 );
 
                 let (view, sampler) = Self::new_vk_image_view(
@@ -154,6 +254,72 @@ impl Image {
                     &image,
                     info.format.into(),
                     info.ty.to_vk_image_view(),
+                    1,
+                );
+
+                Self::Vulkan {
+                    image,
+                    format: info.format.into(),
+                    memory: Some(memory),
+                    view,
+                    sampler,
+                }
+            }
+        }
+    }
+
+    pub fn import(info: ImageImportInfo<'_>) -> Self {
+        match info.device {
+            Device::Vulkan {
+                device,
+                physical_device,
+                ..
+            } => {
+                let image_create_info = vk::ImageCreateInfo {
+                    flags: 0,
+                    image_type: vk::ImageType::TwoDim,
+                    format: info.format.into(),
+                    extent: (info.extent.0, info.extent.1, 1),
+                    mip_levels: 1,
+                    array_layers: 1,
+                    samples: SampleCount::One,
+                    tiling: vk::ImageTiling::DrmFormatModifier,
+                    image_usage: info.usage.to_vk(),
+                    initial_layout: vk::ImageLayout::Undefined,
+                };
+
+                let plane_layouts = info
+                    .planes
+                    .iter()
+                    .map(|plane| vk::SubresourceLayout {
+                        offset: plane.offset,
+                        size: 0,
+                        row_pitch: plane.row_pitch,
+                        array_pitch: 0,
+                        depth_pitch: 0,
+                    })
+                    .collect::<Vec<_>>();
+
+                let import_info = vk::DmaBufImportInfo {
+                    fd: info.fd,
+                    drm_format_modifier: info.drm_format_modifier,
+                    plane_layouts: &plane_layouts,
+                };
+
+                let (image, memory) = vk::Image::import_dma_buf(
+                    device.clone(),
+                    physical_device,
+                    image_create_info,
+                    import_info,
+                )
+                .expect("failed to import dma buf image");
+
+                let (view, sampler) = Self::new_vk_image_view(
+                    device.clone(),
+                    &image,
+                    info.format.into(),
+                    vk::ImageViewType::TwoDim,
+                    1,
                 );
 
                 Self::Vulkan {
@@ -167,6 +333,26 @@ impl Image {
         }
     }
 
+    //Bytes committed to the image's own memory, which is less than its size while a
+    //lazily allocated transient attachment stays in tile memory. None for images without
+    //their own memory, such as swapchain images and AttachmentPool targets.
+    pub fn committed_memory(&self) -> Option<u64> {
+        match self {
+            Image::Vulkan { memory, .. } => memory.as_ref().map(|memory| memory.commitment()),
+        }
+    }
+
+    //The image as a field of a DescriptorTemplate's data, like a Binding::Image
+    pub fn descriptor(&self, layout: ImageLayout) -> DescriptorData {
+        match self {
+            Image::Vulkan { view, sampler, .. } => DescriptorData::image(vk::DescriptorImageInfo {
+                sampler,
+                image_view: view,
+                image_layout: layout.into(),
+            }),
+        }
+    }
+
     pub(crate) fn new_managed_vk_image(
         physical_device: &vk::PhysicalDevice,
         device: Rc<vk::Device>,
@@ -174,24 +360,30 @@ impl Image {
         image_usage: u32,
         image_type: vk::ImageType,
         extent: (u32, u32, u32),
+        array_layers: u32,
+        samples: SampleCount,
+        priority: MemoryPriority,
     ) -> (vk::Image, vk::Memory) {
-        let image_create_info = vk::ImageCreateInfo {
-            image_type,
+        let mut image = Self::new_unbound_vk_image(
+            device.clone(),
             format,
-            extent,
-            mip_levels: 1,
-            array_layers: 1,
-            samples: vk::SAMPLE_COUNT_1,
-            tiling: vk::ImageTiling::Optimal,
             image_usage,
-            initial_layout: vk::ImageLayout::Undefined,
-        };
+            image_type,
+            extent,
+            array_layers,
+            samples,
+        );
 
-        let mut image =
-            vk::Image::new(device.clone(), image_create_info).expect("failed to allocate image");
+        let mut property_flags = vk::MEMORY_PROPERTY_DEVICE_LOCAL;
+
+        if image_usage & vk::IMAGE_USAGE_TRANSIENT_ATTACHMENT != 0 {
+            property_flags |= vk::MEMORY_PROPERTY_LAZILY_ALLOCATED;
+        }
 
         let memory_allocate_info = vk::MemoryAllocateInfo {
-            property_flags: vk::MEMORY_PROPERTY_DEVICE_LOCAL,
+            property_flags,
+            flags: 0,
+            priority: Some(priority.to_vk()),
         };
 
         let memory = vk::Memory::allocate(
@@ -210,11 +402,38 @@ impl Image {
         (image, memory)
     }
 
+    //Memory is bound by the caller, see AttachmentPool
+    pub(crate) fn new_unbound_vk_image(
+        device: Rc<vk::Device>,
+        format: vk::Format,
+        image_usage: u32,
+        image_type: vk::ImageType,
+        extent: (u32, u32, u32),
+        array_layers: u32,
+        samples: SampleCount,
+    ) -> vk::Image {
+        let image_create_info = vk::ImageCreateInfo {
+            flags: 0,
+            image_type,
+            format,
+            extent,
+            mip_levels: 1,
+            array_layers,
+            samples,
+            tiling: vk::ImageTiling::Optimal,
+            image_usage,
+            initial_layout: vk::ImageLayout::Undefined,
+        };
+
+        vk::Image::new(device, image_create_info).expect("failed to allocate image")
+    }
+
     pub(crate) fn new_vk_image_view(
         device: Rc<vk::Device>,
         image: &vk::Image,
         format: vk::Format,
         view_type: vk::ImageViewType,
+        layer_count: u32,
     ) -> (vk::ImageView, vk::Sampler) {
         let view_create_info = vk::ImageViewCreateInfo {
             image,
@@ -231,7 +450,7 @@ impl Image {
                 base_mip_level: 0,
                 level_count: 1,
                 base_array_layer: 0,
-                layer_count: 1,
+                layer_count,
             },
         };
 
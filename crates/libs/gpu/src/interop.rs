@@ -0,0 +1,161 @@
+use crate::prelude::*;
+
+//Column major, as shaders read a mat4 from uniforms, storage buffers and push constants.
+//The glam, nalgebra and mint features add From conversions of their types, so matrices go
+//into FrameUniforms::write, Commands::push_constant or a camera without copying by hand.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct ShaderMat4(pub [[f32; 4]; 4]);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct ShaderVec4(pub [f32; 4]);
+
+//12 bytes, pad it when it is followed by a scalar in std140
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct ShaderVec3(pub [f32; 3]);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct ShaderVec2(pub [f32; 2]);
+
+unsafe impl Pod for ShaderMat4 {}
+unsafe impl Pod for ShaderVec4 {}
+unsafe impl Pod for ShaderVec3 {}
+unsafe impl Pod for ShaderVec2 {}
+
+impl ShaderMat4 {
+    pub const IDENTITY: Self = Self([
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+}
+
+macro_rules! impl_from_array {
+    ($ty:ident, $array:ty) => {
+        impl From<$array> for $ty {
+            fn from(array: $array) -> Self {
+                Self(array)
+            }
+        }
+
+        impl From<$ty> for $array {
+            fn from(value: $ty) -> Self {
+                value.0
+            }
+        }
+    };
+}
+
+impl_from_array!(ShaderMat4, [[f32; 4]; 4]);
+impl_from_array!(ShaderVec4, [f32; 4]);
+impl_from_array!(ShaderVec3, [f32; 3]);
+impl_from_array!(ShaderVec2, [f32; 2]);
+
+#[cfg(feature = "glam")]
+mod glam_interop {
+    use super::*;
+
+    impl From<glam::Mat4> for ShaderMat4 {
+        fn from(matrix: glam::Mat4) -> Self {
+            Self(matrix.to_cols_array_2d())
+        }
+    }
+
+    impl From<ShaderMat4> for glam::Mat4 {
+        fn from(matrix: ShaderMat4) -> Self {
+            Self::from_cols_array_2d(&matrix.0)
+        }
+    }
+
+    impl From<glam::Vec4> for ShaderVec4 {
+        fn from(vector: glam::Vec4) -> Self {
+            Self(vector.to_array())
+        }
+    }
+
+    impl From<glam::Vec3> for ShaderVec3 {
+        fn from(vector: glam::Vec3) -> Self {
+            Self(vector.to_array())
+        }
+    }
+
+    impl From<glam::Vec2> for ShaderVec2 {
+        fn from(vector: glam::Vec2) -> Self {
+            Self(vector.to_array())
+        }
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_interop {
+    use super::*;
+
+    impl From<nalgebra::Matrix4<f32>> for ShaderMat4 {
+        fn from(matrix: nalgebra::Matrix4<f32>) -> Self {
+            Self(matrix.into())
+        }
+    }
+
+    impl From<ShaderMat4> for nalgebra::Matrix4<f32> {
+        fn from(matrix: ShaderMat4) -> Self {
+            matrix.0.into()
+        }
+    }
+
+    impl From<nalgebra::Vector4<f32>> for ShaderVec4 {
+        fn from(vector: nalgebra::Vector4<f32>) -> Self {
+            Self(vector.into())
+        }
+    }
+
+    impl From<nalgebra::Vector3<f32>> for ShaderVec3 {
+        fn from(vector: nalgebra::Vector3<f32>) -> Self {
+            Self(vector.into())
+        }
+    }
+
+    impl From<nalgebra::Vector2<f32>> for ShaderVec2 {
+        fn from(vector: nalgebra::Vector2<f32>) -> Self {
+            Self(vector.into())
+        }
+    }
+}
+
+#[cfg(feature = "mint")]
+mod mint_interop {
+    use super::*;
+
+    impl From<mint::ColumnMatrix4<f32>> for ShaderMat4 {
+        fn from(matrix: mint::ColumnMatrix4<f32>) -> Self {
+            Self(matrix.into())
+        }
+    }
+
+    impl From<ShaderMat4> for mint::ColumnMatrix4<f32> {
+        fn from(matrix: ShaderMat4) -> Self {
+            matrix.0.into()
+        }
+    }
+
+    impl From<mint::Vector4<f32>> for ShaderVec4 {
+        fn from(vector: mint::Vector4<f32>) -> Self {
+            Self(vector.into())
+        }
+    }
+
+    impl From<mint::Vector3<f32>> for ShaderVec3 {
+        fn from(vector: mint::Vector3<f32>) -> Self {
+            Self(vector.into())
+        }
+    }
+
+    impl From<mint::Vector2<f32>> for ShaderVec2 {
+        fn from(vector: mint::Vector2<f32>) -> Self {
+            Self(vector.into())
+        }
+    }
+}
@@ -0,0 +1,337 @@
+use crate::compute::{compute_pipeline, storage_binding, storage_descriptor};
+use crate::prelude::*;
+
+use std::mem;
+
+const BLOCK_SIZE: usize = 256;
+const RADIX: usize = 16;
+const RADIX_BITS: u32 = 4;
+
+fn group_count(count: usize) -> usize {
+    (count + BLOCK_SIZE - 1) / BLOCK_SIZE
+}
+
+fn storage_buffer(device: &Device, size: usize) -> Buffer {
+    Buffer::new(BufferInfo {
+        device,
+        usage: BufferUsage::STORAGE,
+        properties: MemoryProperties::DEVICE_LOCAL,
+        size,
+        priority: MemoryPriority::Default,
+    })
+}
+
+fn compute_barrier(commands: &mut Commands<'_>) {
+    commands.pipeline_barrier(
+        PipelineStage::COMPUTE_SHADER,
+        PipelineStage::COMPUTE_SHADER,
+        &[Barrier::Memory {
+            src_access: Access::SHADER_WRITE,
+            dst_access: Access::SHADER_READ | Access::SHADER_WRITE,
+        }],
+    );
+}
+
+pub struct PrefixScanInfo<'a> {
+    pub device: &'a Device,
+    pub max_count: usize,
+}
+
+pub struct PrefixScan {
+    scan: Pipeline,
+    add: Pipeline,
+    sums: Vec<Buffer>,
+    max_count: usize,
+    bound_count: usize,
+}
+
+impl PrefixScan {
+    pub fn new(info: PrefixScanInfo<'_>) -> Self {
+        let mut sums = vec![];
+        let mut counts = vec![];
+
+        let mut count = info.max_count.max(1);
+
+        loop {
+            let groups = group_count(count);
+
+            counts.push(count);
+            sums.push(storage_buffer(info.device, groups * mem::size_of::<u32>()));
+
+            if groups == 1 {
+                break;
+            }
+
+            count = groups;
+        }
+
+        let layout = [storage_descriptor(0), storage_descriptor(1)];
+
+        let mut scan = compute_pipeline(
+            info.device,
+            "scan.comp",
+            include_str!("shaders/scan.comp.glsl"),
+            sums.len() as _,
+            &layout,
+            mem::size_of::<u32>() as _,
+        );
+
+        let mut add = compute_pipeline(
+            info.device,
+            "scan_add.comp",
+            include_str!("shaders/scan_add.comp.glsl"),
+            sums.len() as _,
+            &layout,
+            mem::size_of::<u32>() as _,
+        );
+
+        for level in 1..sums.len() {
+            let bindings = [
+                storage_binding(0, &sums[level - 1], counts[level] * mem::size_of::<u32>()),
+                storage_binding(
+                    1,
+                    &sums[level],
+                    group_count(counts[level]) * mem::size_of::<u32>(),
+                ),
+            ];
+
+            scan.bind(level as _, &bindings);
+            add.bind(level as _, &bindings);
+        }
+
+        Self {
+            scan,
+            add,
+            sums,
+            max_count: info.max_count,
+            bound_count: 0,
+        }
+    }
+
+    //Binds the u32 buffer scanned in place by record. Do not rebind while
+    //a command buffer recorded against the previous binding is pending.
+    pub fn bind(&mut self, data: &Buffer, count: usize) {
+        if count > self.max_count {
+            panic!("prefix scan count exceeds max count");
+        }
+
+        let bindings = [
+            storage_binding(0, data, count * mem::size_of::<u32>()),
+            storage_binding(1, &self.sums[0], group_count(count) * mem::size_of::<u32>()),
+        ];
+
+        self.scan.bind(0, &bindings);
+        self.add.bind(0, &bindings);
+
+        self.bound_count = count;
+    }
+
+    pub fn record(&mut self, commands: &mut Commands<'_>, count: usize) {
+        if count > self.bound_count {
+            panic!("prefix scan count exceeds bound buffer");
+        }
+
+        if count == 0 {
+            return;
+        }
+
+        let mut counts = vec![count];
+
+        while group_count(counts[counts.len() - 1]) > 1 {
+            counts.push(group_count(counts[counts.len() - 1]));
+        }
+
+        for (level, &count) in counts.iter().enumerate() {
+            commands.bind_pipeline(level as _, &self.scan);
+            commands.push_constants(&self.scan, ShaderStage::COMPUTE, 0, &[count as u32]);
+            commands.dispatch(group_count(count) as _, 1, 1);
+
+            compute_barrier(commands);
+        }
+
+        for level in (0..counts.len() - 1).rev() {
+            commands.bind_pipeline(level as _, &self.add);
+            commands.push_constants(&self.add, ShaderStage::COMPUTE, 0, &[counts[level] as u32]);
+            commands.dispatch(group_count(counts[level]) as _, 1, 1);
+
+            compute_barrier(commands);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    U32,
+    U64,
+}
+
+impl SortKey {
+    fn words(self) -> usize {
+        match self {
+            Self::U32 => 1,
+            Self::U64 => 2,
+        }
+    }
+}
+
+pub struct RadixSortInfo<'a> {
+    pub device: &'a Device,
+    pub max_count: usize,
+    pub key: SortKey,
+}
+
+pub struct RadixSort {
+    histogram: Pipeline,
+    scatter: Pipeline,
+    scan: PrefixScan,
+    counts: Buffer,
+    keys: Buffer,
+    values: Buffer,
+    key: SortKey,
+    max_count: usize,
+    bound_count: usize,
+    has_values: bool,
+}
+
+impl RadixSort {
+    pub fn new(info: RadixSortInfo<'_>) -> Self {
+        let max_count = info.max_count.max(1);
+
+        let counts_len = RADIX * group_count(max_count);
+
+        let counts = storage_buffer(info.device, counts_len * mem::size_of::<u32>());
+
+        let keys = storage_buffer(
+            info.device,
+            max_count * info.key.words() * mem::size_of::<u32>(),
+        );
+
+        let values = storage_buffer(info.device, max_count * mem::size_of::<u32>());
+
+        let mut scan = PrefixScan::new(PrefixScanInfo {
+            device: info.device,
+            max_count: counts_len,
+        });
+
+        scan.bind(&counts, counts_len);
+
+        let histogram = compute_pipeline(
+            info.device,
+            "histogram.comp",
+            include_str!("shaders/histogram.comp.glsl"),
+            2,
+            &[storage_descriptor(0), storage_descriptor(1)],
+            mem::size_of::<[u32; 4]>() as _,
+        );
+
+        let scatter = compute_pipeline(
+            info.device,
+            "scatter.comp",
+            include_str!("shaders/scatter.comp.glsl"),
+            2,
+            &[
+                storage_descriptor(0),
+                storage_descriptor(1),
+                storage_descriptor(2),
+                storage_descriptor(3),
+                storage_descriptor(4),
+            ],
+            mem::size_of::<[u32; 4]>() as _,
+        );
+
+        Self {
+            histogram,
+            scatter,
+            scan,
+            counts,
+            keys,
+            values,
+            key: info.key,
+            max_count: info.max_count,
+            bound_count: 0,
+            has_values: false,
+        }
+    }
+
+    //Binds the keys (and optional u32 payload) sorted in place by record.
+    //Do not rebind while a command buffer recorded against the previous
+    //binding is pending.
+    pub fn bind(&mut self, keys: &Buffer, values: Option<&Buffer>, count: usize) {
+        if count > self.max_count {
+            panic!("radix sort count exceeds max count");
+        }
+
+        let keys_range = count * self.key.words() * mem::size_of::<u32>();
+        let values_range = count * mem::size_of::<u32>();
+        let counts_range = RADIX * group_count(count) * mem::size_of::<u32>();
+
+        let values = values.unwrap_or(&self.values);
+
+        for (slot, (keys_in, values_in, keys_out, values_out)) in [
+            (keys, values, &self.keys, &self.values),
+            (&self.keys, &self.values, keys, values),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            self.histogram.bind(
+                slot as _,
+                &[
+                    storage_binding(0, keys_in, keys_range),
+                    storage_binding(1, &self.counts, counts_range),
+                ],
+            );
+
+            self.scatter.bind(
+                slot as _,
+                &[
+                    storage_binding(0, keys_in, keys_range),
+                    storage_binding(1, keys_out, keys_range),
+                    storage_binding(2, values_in, values_range),
+                    storage_binding(3, values_out, values_range),
+                    storage_binding(4, &self.counts, counts_range),
+                ],
+            );
+        }
+
+        self.has_values = !std::ptr::eq(values, &self.values);
+        self.bound_count = count;
+    }
+
+    pub fn record(&mut self, commands: &mut Commands<'_>) {
+        let count = self.bound_count;
+
+        if count == 0 {
+            return;
+        }
+
+        let groups = group_count(count);
+
+        let passes = self.key.words() as u32 * 32 / RADIX_BITS;
+
+        for pass in 0..passes {
+            let slot = pass % 2;
+
+            let push_constants = [
+                count as u32,
+                pass * RADIX_BITS,
+                (self.key == SortKey::U64) as u32,
+                self.has_values as u32,
+            ];
+
+            commands.bind_pipeline(slot, &self.histogram);
+            commands.push_constants(&self.histogram, ShaderStage::COMPUTE, 0, &push_constants);
+            commands.dispatch(groups as _, 1, 1);
+
+            compute_barrier(commands);
+
+            self.scan.record(commands, RADIX * groups);
+
+            commands.bind_pipeline(slot, &self.scatter);
+            commands.push_constants(&self.scatter, ShaderStage::COMPUTE, 0, &push_constants);
+            commands.dispatch(groups as _, 1, 1);
+
+            compute_barrier(commands);
+        }
+    }
+}
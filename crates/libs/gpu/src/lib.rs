@@ -2,33 +2,162 @@
 #![feature(let_else)]
 
 mod access;
+mod attachment_pool;
 mod buffer;
+#[cfg(feature = "capture")]
+mod capture;
+mod color_space;
+mod compute;
+mod compute_context;
 mod context;
+mod deletion;
+mod depth;
+mod descriptor_template;
 mod device;
 mod error;
+mod event;
 mod format;
+#[cfg(feature = "reference")]
+mod forward;
 mod framebuffer;
+mod gpu_vec;
+#[cfg(feature = "swapchain")]
+mod graphics;
+mod history;
 mod image;
+mod interop;
+#[cfg(feature = "compute-kernels")]
+mod kernels;
+#[cfg(feature = "reference")]
+mod light_culling;
+#[cfg(feature = "compute-kernels")]
+mod lod;
+mod material;
 mod memory;
+#[cfg(feature = "memory-dump")]
+mod memory_dump;
+mod mesh_pool;
+#[cfg(feature = "compute-kernels")]
+mod particles;
+mod performance;
+mod pick;
 mod pipeline;
+mod pipeline_cache;
+mod pipeline_warmup;
+mod post_process;
+mod query;
+mod registry;
 mod render_pass;
+mod resolution;
+#[cfg(feature = "resources")]
+mod resources;
+#[cfg(feature = "replay")]
+mod replay;
+#[cfg(feature = "rt")]
+mod sbt;
+mod secondary;
+#[cfg(feature = "swapchain")]
+mod settings;
 mod shader;
+#[cfg(feature = "reference")]
+mod sky;
+mod streaming;
+#[cfg(feature = "swapchain")]
 mod surface;
+#[cfg(feature = "swapchain")]
 mod swapchain;
+#[cfg(feature = "swapchain")]
+mod swapchain_manager;
+mod texture;
+#[cfg(feature = "reference")]
+mod tonemap;
+#[cfg(feature = "ui")]
+mod ui;
+mod uniform;
+mod upload;
+mod upscale;
+#[cfg(feature = "swapchain")]
+mod viewports;
+mod watchdog;
+mod ycbcr;
 
 pub mod prelude {
     pub use crate::access::*;
+    pub use crate::attachment_pool::*;
     pub use crate::buffer::*;
+    #[cfg(feature = "capture")]
+    pub use crate::capture::*;
+    pub use crate::color_space::*;
+    pub use crate::compute_context::*;
     pub use crate::context::*;
+    pub use crate::deletion::*;
+    pub use crate::depth::*;
+    pub use crate::descriptor_template::*;
     pub use crate::device::*;
     pub use crate::error::*;
+    pub use crate::event::*;
     pub use crate::format::*;
+    #[cfg(feature = "reference")]
+    pub use crate::forward::*;
     pub use crate::framebuffer::*;
+    pub use crate::gpu_vec::*;
+    #[cfg(feature = "swapchain")]
+    pub use crate::graphics::*;
+    pub use crate::history::*;
     pub use crate::image::*;
+    pub use crate::interop::*;
+    #[cfg(feature = "compute-kernels")]
+    pub use crate::kernels::*;
+    #[cfg(feature = "reference")]
+    pub use crate::light_culling::*;
+    #[cfg(feature = "compute-kernels")]
+    pub use crate::lod::*;
+    pub use crate::material::*;
     pub use crate::memory::*;
+    #[cfg(feature = "memory-dump")]
+    pub use crate::memory_dump::*;
+    pub use crate::mesh_pool::*;
+    #[cfg(feature = "compute-kernels")]
+    pub use crate::particles::*;
+    pub use crate::performance::*;
+    pub use crate::pick::*;
     pub use crate::pipeline::*;
+    pub use crate::pipeline_cache::*;
+    pub use crate::pipeline_warmup::*;
+    pub use crate::post_process::*;
+    pub use crate::query::*;
+    pub use crate::registry::*;
     pub use crate::render_pass::*;
+    pub use crate::resolution::*;
+    #[cfg(feature = "resources")]
+    pub use crate::resources::*;
+    #[cfg(feature = "replay")]
+    pub use crate::replay::*;
+    #[cfg(feature = "rt")]
+    pub use crate::sbt::*;
+    pub use crate::secondary::*;
+    #[cfg(feature = "swapchain")]
+    pub use crate::settings::*;
     pub use crate::shader::*;
+    #[cfg(feature = "reference")]
+    pub use crate::sky::*;
+    pub use crate::streaming::*;
+    #[cfg(feature = "swapchain")]
     pub use crate::surface::*;
+    #[cfg(feature = "swapchain")]
     pub use crate::swapchain::*;
+    #[cfg(feature = "swapchain")]
+    pub use crate::swapchain_manager::*;
+    pub use crate::texture::*;
+    #[cfg(feature = "reference")]
+    pub use crate::tonemap::*;
+    #[cfg(feature = "ui")]
+    pub use crate::ui::*;
+    pub use crate::uniform::*;
+    pub use crate::upload::*;
+    pub use crate::upscale::*;
+    #[cfg(feature = "swapchain")]
+    pub use crate::viewports::*;
+    pub use crate::watchdog::*;
+    pub use crate::ycbcr::*;
 }
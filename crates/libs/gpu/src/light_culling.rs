@@ -0,0 +1,327 @@
+use crate::compute::{compute_pipeline, storage_binding, storage_descriptor};
+use crate::prelude::*;
+
+use std::mem;
+
+const BLOCK_SIZE: u32 = 64;
+
+pub struct LightCullingInfo<'a> {
+    pub device: &'a Device,
+    //tiles across the screen and exponential depth slices, fixed regardless of extent
+    pub grid: (u32, u32, u32),
+    //clamped so the index buffer fits in the device storage buffer range
+    pub max_lights_per_cluster: u32,
+}
+
+//std140 layout of the Clusters block in shaders/cluster.glsl
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ClusterConstants {
+    view: [[f32; 4]; 4],
+    grid: [u32; 4],
+    tile: [f32; 4],
+    depth: [f32; 4],
+}
+
+unsafe impl Pod for ClusterConstants {}
+
+//Clustered light culling over ForwardLight buffers. The grid of view space bounds is
+//rebuilt on the gpu when the projection changes, then every frame one invocation per
+//cluster tests each light against its bounds and writes up to max_lights_per_cluster
+//light indices. Shaders consume the result through shaders/cluster.glsl using
+//descriptors and bindings at any free binding.
+pub struct LightCulling {
+    build: Pipeline,
+    cull: Pipeline,
+    uniform: Buffer,
+    _bounds: Buffer,
+    counts: Buffer,
+    indices: Buffer,
+    constants: ClusterConstants,
+    max_lights_per_cluster: u32,
+    cluster_count: u32,
+    light_count: u32,
+    dirty: bool,
+    bound: bool,
+}
+
+impl LightCulling {
+    pub fn new(info: LightCullingInfo<'_>) -> Self {
+        let (x, y, z) = info.grid;
+
+        let cluster_count = x * y * z;
+
+        if cluster_count == 0 {
+            panic!("light culling grid must not be empty");
+        }
+
+        let max_lights_per_cluster = info
+            .max_lights_per_cluster
+            .min(
+                (info.device.max_storage_buffer_range()
+                    / (cluster_count as usize * mem::size_of::<u32>())) as u32,
+            )
+            .max(1);
+
+        let uniform = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::UNIFORM,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: mem::size_of::<ClusterConstants>(),
+            priority: MemoryPriority::Default,
+        });
+
+        let bounds = Self::storage_buffer(
+            info.device,
+            cluster_count as usize * 2 * mem::size_of::<[f32; 4]>(),
+        );
+        let counts =
+            Self::storage_buffer(info.device, cluster_count as usize * mem::size_of::<u32>());
+        let indices = Self::storage_buffer(
+            info.device,
+            (cluster_count * max_lights_per_cluster) as usize * mem::size_of::<u32>(),
+        );
+
+        let uniform_descriptor = Descriptor {
+            binding: 0,
+            ty: DescriptorType::UniformBuffer,
+            count: 1,
+            stage: ShaderStage::COMPUTE,
+        };
+
+        let mut build = compute_pipeline(
+            info.device,
+            "cluster_build.comp",
+            include_str!("shaders/cluster_build.comp.glsl"),
+            1,
+            &[uniform_descriptor, storage_descriptor(1)],
+            mem::size_of::<u32>() as _,
+        );
+
+        let mut cull = compute_pipeline(
+            info.device,
+            "light_cull.comp",
+            include_str!("shaders/light_cull.comp.glsl"),
+            1,
+            &[
+                uniform_descriptor,
+                storage_descriptor(1),
+                storage_descriptor(2),
+                storage_descriptor(3),
+                storage_descriptor(4),
+            ],
+            mem::size_of::<u32>() as _,
+        );
+
+        let bounds_range = cluster_count as usize * 2 * mem::size_of::<[f32; 4]>();
+
+        build.bind(
+            0,
+            &[
+                Self::uniform_binding(0, &uniform),
+                storage_binding(1, &bounds, bounds_range),
+            ],
+        );
+
+        cull.bind(
+            0,
+            &[
+                Self::uniform_binding(0, &uniform),
+                storage_binding(1, &bounds, bounds_range),
+                storage_binding(3, &counts, cluster_count as usize * mem::size_of::<u32>()),
+                storage_binding(
+                    4,
+                    &indices,
+                    (cluster_count * max_lights_per_cluster) as usize * mem::size_of::<u32>(),
+                ),
+            ],
+        );
+
+        let constants = ClusterConstants {
+            view: [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+            grid: [x, y, z, max_lights_per_cluster],
+            tile: [1.0, 1.0, 1.0, 1.0],
+            depth: [0.1, 1000.0, 1.0, 0.0],
+        };
+
+        Self {
+            build,
+            cull,
+            uniform,
+            _bounds: bounds,
+            counts,
+            indices,
+            constants,
+            max_lights_per_cluster,
+            cluster_count,
+            light_count: 0,
+            dirty: true,
+            bound: false,
+        }
+    }
+
+    fn storage_buffer(device: &Device, size: usize) -> Buffer {
+        Buffer::new(BufferInfo {
+            device,
+            usage: BufferUsage::STORAGE,
+            properties: MemoryProperties::DEVICE_LOCAL,
+            size,
+            priority: MemoryPriority::Default,
+        })
+    }
+
+    fn uniform_binding(binding: u32, uniform: &Buffer) -> Binding<'_> {
+        Binding::Buffer {
+            binding,
+            ty: DescriptorType::UniformBuffer,
+            offset: 0,
+            range: mem::size_of::<ClusterConstants>(),
+            buffer: uniform,
+        }
+    }
+
+    pub fn max_lights_per_cluster(&self) -> u32 {
+        self.max_lights_per_cluster
+    }
+
+    pub fn cluster_count(&self) -> u32 {
+        self.cluster_count
+    }
+
+    //Binds the ForwardLight buffer culled by record, see ForwardRenderer::lights.
+    //Do not rebind while a command buffer recorded against the previous binding is pending.
+    pub fn bind(&mut self, lights: &Buffer, max_lights: usize) {
+        self.cull.bind(
+            0,
+            &[storage_binding(
+                2,
+                lights,
+                max_lights.max(1) * mem::size_of::<ForwardLight>(),
+            )],
+        );
+
+        self.bound = true;
+    }
+
+    //Call when the extent or projection changes. Only the x and y scale of the projection
+    //are used, so any symmetric perspective works, including reversed and infinite z.
+    pub fn set_projection(&mut self, extent: (u32, u32), proj: [[f32; 4]; 4], near: f32, far: f32) {
+        let (x, y, z) = (
+            self.constants.grid[0],
+            self.constants.grid[1],
+            self.constants.grid[2],
+        );
+
+        let log_ratio = (far / near).ln();
+
+        self.constants.tile = [
+            extent.0 as f32 / x as f32,
+            extent.1 as f32 / y as f32,
+            1.0 / proj[0][0],
+            1.0 / proj[1][1],
+        ];
+
+        self.constants.depth = [
+            near,
+            far,
+            z as f32 / log_ratio,
+            z as f32 * near.ln() / log_ratio,
+        ];
+
+        self.dirty = true;
+    }
+
+    pub fn update(&mut self, view: [[f32; 4]; 4], light_count: u32) {
+        self.constants.view = view;
+        self.light_count = light_count;
+
+        self.uniform.write(BufferWrite {
+            offset: 0,
+            data: &[self.constants],
+        });
+    }
+
+    //Must be recorded outside a render pass, before the passes that shade with the clusters
+    pub fn record(&mut self, commands: &mut Commands<'_>) {
+        if !self.bound {
+            panic!("light culling has no light buffer bound");
+        }
+
+        let group_count = (self.cluster_count + BLOCK_SIZE - 1) / BLOCK_SIZE;
+
+        if self.dirty {
+            commands.bind_pipeline(0, &self.build);
+            commands.push_constants(&self.build, ShaderStage::COMPUTE, 0, &[self.cluster_count]);
+            commands.dispatch(group_count, 1, 1);
+
+            commands.pipeline_barrier(
+                PipelineStage::COMPUTE_SHADER,
+                PipelineStage::COMPUTE_SHADER,
+                &[Barrier::Memory {
+                    src_access: Access::SHADER_WRITE,
+                    dst_access: Access::SHADER_READ,
+                }],
+            );
+
+            self.dirty = false;
+        }
+
+        commands.bind_pipeline(0, &self.cull);
+        commands.push_constants(&self.cull, ShaderStage::COMPUTE, 0, &[self.light_count]);
+        commands.dispatch(group_count, 1, 1);
+
+        commands.pipeline_barrier(
+            PipelineStage::COMPUTE_SHADER,
+            PipelineStage::FRAGMENT_SHADER,
+            &[Barrier::Memory {
+                src_access: Access::SHADER_WRITE,
+                dst_access: Access::SHADER_READ,
+            }],
+        );
+    }
+
+    //The Clusters uniform and the per cluster light counts and indices, in that order
+    pub fn descriptors(&self, first_binding: u32, stage: ShaderStage) -> [Descriptor; 3] {
+        [
+            Descriptor {
+                binding: first_binding,
+                ty: DescriptorType::UniformBuffer,
+                count: 1,
+                stage,
+            },
+            Descriptor {
+                binding: first_binding + 1,
+                ty: DescriptorType::StorageBuffer,
+                count: 1,
+                stage,
+            },
+            Descriptor {
+                binding: first_binding + 2,
+                ty: DescriptorType::StorageBuffer,
+                count: 1,
+                stage,
+            },
+        ]
+    }
+
+    pub fn bindings(&self, first_binding: u32) -> [Binding<'_>; 3] {
+        [
+            Self::uniform_binding(first_binding, &self.uniform),
+            storage_binding(
+                first_binding + 1,
+                &self.counts,
+                self.cluster_count as usize * mem::size_of::<u32>(),
+            ),
+            storage_binding(
+                first_binding + 2,
+                &self.indices,
+                (self.cluster_count * self.max_lights_per_cluster) as usize * mem::size_of::<u32>(),
+            ),
+        ]
+    }
+}
@@ -0,0 +1,354 @@
+use crate::compute::{compute_pipeline, storage_binding, storage_descriptor};
+use crate::prelude::*;
+
+use std::mem;
+
+pub const MAX_CHUNK_LODS: usize = 4;
+
+const BLOCK_SIZE: u32 = 64;
+
+//One chunk with its meshes in a MeshPool, finest first
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ChunkLod {
+    pub center: [f32; 3],
+    pub radius: f32,
+    pub lods: [MeshRange; MAX_CHUNK_LODS],
+    pub lod_count: u32,
+    //first_instance of the draw, e.g. the index of the chunk's transform
+    pub instance: u32,
+}
+
+//Layout of VkDrawIndexedIndirectCommand
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct DrawIndexedIndirectCommand {
+    pub index_count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub vertex_offset: i32,
+    pub first_instance: u32,
+}
+
+unsafe impl Pod for DrawIndexedIndirectCommand {}
+
+#[derive(Clone, Copy)]
+pub struct LodCamera {
+    pub view_proj: [[f32; 4]; 4],
+    pub position: [f32; 3],
+    //proj[1][1] of the projection and the viewport height in pixels
+    pub proj_y_scale: f32,
+    pub viewport_height: u32,
+}
+
+impl LodCamera {
+    pub fn new(
+        view_proj: impl Into<ShaderMat4>,
+        position: impl Into<ShaderVec3>,
+        proj_y_scale: f32,
+        viewport_height: u32,
+    ) -> Self {
+        Self {
+            view_proj: view_proj.into().0,
+            position: position.into().0,
+            proj_y_scale,
+            viewport_height,
+        }
+    }
+}
+
+pub struct ChunkLodPassInfo<'a> {
+    pub device: &'a Device,
+    pub max_chunks: usize,
+    //world space error of each lod level, increasing from the finest
+    pub lod_errors: [f32; MAX_CHUNK_LODS],
+    //largest projected error in pixels a coarser lod may introduce
+    pub pixel_error: f32,
+}
+
+//std430 layout of Chunk in shaders/lod_select.comp.glsl
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct GpuChunk {
+    bounds: [f32; 4],
+    lods: [[u32; 4]; MAX_CHUNK_LODS],
+    lod_count: u32,
+    instance: u32,
+    padding: [u32; 2],
+}
+
+unsafe impl Pod for GpuChunk {}
+
+impl From<&ChunkLod> for GpuChunk {
+    fn from(chunk: &ChunkLod) -> Self {
+        let [x, y, z] = chunk.center;
+
+        Self {
+            bounds: [x, y, z, chunk.radius],
+            lods: chunk.lods.map(|lod| {
+                [
+                    lod.first_index,
+                    lod.index_count,
+                    lod.vertex_offset as u32,
+                    0,
+                ]
+            }),
+            lod_count: (chunk.lod_count as usize).min(MAX_CHUNK_LODS) as _,
+            instance: chunk.instance,
+            padding: [0; 2],
+        }
+    }
+}
+
+//std140 layout of the Lod block in shaders/lod_select.comp.glsl
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct LodConstants {
+    planes: [[f32; 4]; 6],
+    camera: [f32; 4],
+    errors: [f32; 4],
+    chunk_count: u32,
+    pixel_error: f32,
+    compact: u32,
+    padding: u32,
+}
+
+unsafe impl Pod for LodConstants {}
+
+//Per chunk lod selection and frustum culling on the gpu. Each frame one invocation per
+//chunk picks the coarsest mesh whose projected error stays under pixel_error and writes
+//its indirect draw, so the cpu only uploads chunks when they stream in or out. With
+//KHR_DRAW_INDIRECT_COUNT visible draws are compacted and drawn by count, otherwise every
+//chunk keeps its slot and culled chunks draw zero instances.
+pub struct ChunkLodPass {
+    pipeline: Pipeline,
+    uniform: Buffer,
+    chunks: Buffer,
+    draws: Buffer,
+    count: Buffer,
+    constants: LodConstants,
+    max_chunks: usize,
+}
+
+impl ChunkLodPass {
+    pub fn new(info: ChunkLodPassInfo<'_>) -> Self {
+        let max_chunks = info.max_chunks.max(1);
+
+        let uniform = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::UNIFORM,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: mem::size_of::<LodConstants>(),
+            priority: MemoryPriority::Default,
+        });
+
+        let chunks = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::STORAGE,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: max_chunks * mem::size_of::<GpuChunk>(),
+            priority: MemoryPriority::Default,
+        });
+
+        let draws = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::STORAGE | BufferUsage::INDIRECT,
+            properties: MemoryProperties::DEVICE_LOCAL,
+            size: max_chunks * mem::size_of::<DrawIndexedIndirectCommand>(),
+            priority: MemoryPriority::High,
+        });
+
+        let count = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::STORAGE | BufferUsage::INDIRECT | BufferUsage::TRANSFER_DST,
+            properties: MemoryProperties::DEVICE_LOCAL,
+            size: mem::size_of::<u32>(),
+            priority: MemoryPriority::High,
+        });
+
+        let mut pipeline = compute_pipeline(
+            info.device,
+            "lod_select.comp",
+            include_str!("shaders/lod_select.comp.glsl"),
+            1,
+            &[
+                Descriptor {
+                    binding: 0,
+                    ty: DescriptorType::UniformBuffer,
+                    count: 1,
+                    stage: ShaderStage::COMPUTE,
+                },
+                storage_descriptor(1),
+                storage_descriptor(2),
+                storage_descriptor(3),
+            ],
+            mem::size_of::<u32>() as _,
+        );
+
+        pipeline.bind(
+            0,
+            &[
+                Binding::Buffer {
+                    binding: 0,
+                    ty: DescriptorType::UniformBuffer,
+                    offset: 0,
+                    range: mem::size_of::<LodConstants>(),
+                    buffer: &uniform,
+                },
+                storage_binding(1, &chunks, max_chunks * mem::size_of::<GpuChunk>()),
+                storage_binding(
+                    2,
+                    &draws,
+                    max_chunks * mem::size_of::<DrawIndexedIndirectCommand>(),
+                ),
+                storage_binding(3, &count, mem::size_of::<u32>()),
+            ],
+        );
+
+        let constants = LodConstants {
+            planes: [[0.0; 4]; 6],
+            camera: [0.0; 4],
+            errors: info.lod_errors,
+            chunk_count: 0,
+            pixel_error: info.pixel_error,
+            compact: info.device.draw_indirect_count_supported() as u32,
+            padding: 0,
+        };
+
+        Self {
+            pipeline,
+            uniform,
+            chunks,
+            draws,
+            count,
+            constants,
+            max_chunks,
+        }
+    }
+
+    //Replaces the chunk list, call when chunks stream in or out rather than every frame
+    pub fn set_chunks(&mut self, chunks: &[ChunkLod]) {
+        if chunks.len() > self.max_chunks {
+            panic!("chunk count exceeds max chunks");
+        }
+
+        let chunks = chunks.iter().map(GpuChunk::from).collect::<Vec<_>>();
+
+        if !chunks.is_empty() {
+            self.chunks.write(BufferWrite {
+                offset: 0,
+                data: &chunks[..],
+            });
+        }
+
+        self.constants.chunk_count = chunks.len() as _;
+    }
+
+    pub fn set_pixel_error(&mut self, pixel_error: f32) {
+        self.constants.pixel_error = pixel_error;
+    }
+
+    pub fn update(&mut self, camera: &LodCamera) {
+        let [x, y, z] = camera.position;
+
+        self.constants.planes = frustum_planes(camera.view_proj);
+        self.constants.camera = [
+            x,
+            y,
+            z,
+            camera.viewport_height as f32 * 0.5 * camera.proj_y_scale.abs(),
+        ];
+
+        self.uniform.write(BufferWrite {
+            offset: 0,
+            data: &[self.constants],
+        });
+    }
+
+    //Must be recorded outside a render pass
+    pub fn record(&mut self, commands: &mut Commands<'_>) {
+        let chunk_count = self.constants.chunk_count;
+
+        if self.constants.compact != 0 {
+            commands.fill_buffer(&self.count, 0, mem::size_of::<u32>(), 0);
+
+            commands.pipeline_barrier(
+                PipelineStage::TRANSFER,
+                PipelineStage::COMPUTE_SHADER,
+                &[Barrier::Memory {
+                    src_access: Access::TRANSFER_WRITE,
+                    dst_access: Access::SHADER_READ | Access::SHADER_WRITE,
+                }],
+            );
+        }
+
+        if chunk_count > 0 {
+            commands.bind_pipeline(0, &self.pipeline);
+            commands.dispatch((chunk_count + BLOCK_SIZE - 1) / BLOCK_SIZE, 1, 1);
+        }
+
+        commands.pipeline_barrier(
+            PipelineStage::COMPUTE_SHADER,
+            PipelineStage::DRAW_INDIRECT,
+            &[Barrier::Memory {
+                src_access: Access::SHADER_WRITE,
+                dst_access: Access::INDIRECT_COMMAND_READ,
+            }],
+        );
+    }
+
+    //Issues the selected draws, with the pipeline and MeshPool bound inside a render pass
+    pub fn draw(&self, commands: &mut Commands<'_>) {
+        let stride = mem::size_of::<DrawIndexedIndirectCommand>() as u32;
+
+        if self.constants.compact != 0 {
+            commands.draw_indexed_indirect_count(
+                &self.draws,
+                0,
+                &self.count,
+                0,
+                self.max_chunks as _,
+                stride,
+            );
+        } else if self.constants.chunk_count > 0 {
+            commands.draw_indexed_indirect(&self.draws, 0, self.constants.chunk_count, stride);
+        }
+    }
+
+    pub fn draws(&self) -> &Buffer {
+        &self.draws
+    }
+
+    //Only written when draws are compacted
+    pub fn draw_count(&self) -> &Buffer {
+        &self.count
+    }
+}
+
+//Clip space planes of view_proj, pointing inwards and normalized
+fn frustum_planes(m: [[f32; 4]; 4]) -> [[f32; 4]; 6] {
+    let row = |i: usize| [m[0][i], m[1][i], m[2][i], m[3][i]];
+
+    let [r0, r1, r2, r3] = [row(0), row(1), row(2), row(3)];
+
+    let add = |a: [f32; 4], b: [f32; 4]| [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]];
+    let sub = |a: [f32; 4], b: [f32; 4]| [a[0] - b[0], a[1] - b[1], a[2] - b[2], a[3] - b[3]];
+
+    //vulkan clip space keeps 0 <= z <= w
+    [
+        add(r3, r0),
+        sub(r3, r0),
+        add(r3, r1),
+        sub(r3, r1),
+        r2,
+        sub(r3, r2),
+    ]
+    .map(|plane| {
+        let length = (plane[0] * plane[0] + plane[1] * plane[1] + plane[2] * plane[2]).sqrt();
+
+        if length > 0.0 {
+            plane.map(|x| x / length)
+        } else {
+            plane
+        }
+    })
+}
@@ -0,0 +1,452 @@
+use crate::prelude::*;
+
+use std::mem;
+use std::ops::Range;
+use std::rc::Rc;
+
+//Set index the table is bound at, pipelines place their own descriptors at set 0
+pub const MATERIAL_TABLE_SET: u32 = 1;
+
+const MATERIAL_BINDING: u32 = 0;
+const TEXTURE_BINDING: u32 = 1;
+
+pub struct MaterialTableInfo<'a> {
+    pub device: &'a Device,
+    pub stage: ShaderStage,
+    //fixed by the descriptor set layout, the texture array grows up to this
+    pub max_textures: u32,
+    pub initial_textures: u32,
+    pub material_size: usize,
+    pub initial_materials: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TextureId(pub u32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct MaterialId(pub u32);
+
+enum MaterialDescriptors {
+    Vulkan {
+        device: Rc<vk::Device>,
+        descriptor_set: vk::DescriptorSet,
+        descriptor_pool: vk::DescriptorPool,
+        descriptor_set_layout: vk::DescriptorSetLayout,
+    },
+}
+
+//Bindless material table. Shaders see
+//  layout(set = 1, binding = 0) buffer Materials { Material materials[]; };
+//  layout(set = 1, binding = 1) uniform sampler2D textures[];
+//and index both with the ids handed out here. Inserts and updates are batched and
+//written by flush, which must run after the frame fence wait and before recording.
+pub struct MaterialTable {
+    descriptors: MaterialDescriptors,
+    textures: Vec<Option<Image>>,
+    free_textures: Vec<u32>,
+    dirty_textures: Vec<u32>,
+    texture_capacity: u32,
+    max_textures: u32,
+    materials: Vec<u8>,
+    free_materials: Vec<u32>,
+    dirty_materials: Option<Range<usize>>,
+    material_buffer: Buffer,
+    material_size: usize,
+    material_capacity: u32,
+}
+
+impl MaterialTable {
+    pub fn new(info: MaterialTableInfo<'_>) -> Self {
+        if !info.device.descriptor_indexing_supported() {
+            panic!("descriptor indexing is not supported");
+        }
+
+        let texture_capacity = info.initial_textures.max(1).min(info.max_textures);
+        let material_capacity = info.initial_materials.max(1);
+
+        let descriptors =
+            MaterialDescriptors::new(info.device, info.stage, info.max_textures, texture_capacity);
+
+        let material_buffer = Self::material_buffer(
+            info.device,
+            info.material_size * material_capacity as usize,
+        );
+
+        let table = Self {
+            descriptors,
+            textures: vec![],
+            free_textures: vec![],
+            dirty_textures: vec![],
+            texture_capacity,
+            max_textures: info.max_textures,
+            materials: vec![],
+            free_materials: vec![],
+            dirty_materials: None,
+            material_buffer,
+            material_size: info.material_size,
+            material_capacity,
+        };
+
+        table.write_material_binding();
+
+        table
+    }
+
+    pub fn insert_texture(&mut self, image: Image) -> TextureId {
+        let index = match self.free_textures.pop() {
+            Some(index) => {
+                self.textures[index as usize] = Some(image);
+                index
+            }
+            None => {
+                if self.textures.len() as u32 == self.max_textures {
+                    panic!("material table is out of texture slots");
+                }
+
+                self.textures.push(Some(image));
+                self.textures.len() as u32 - 1
+            }
+        };
+
+        self.dirty_textures.push(index);
+
+        TextureId(index)
+    }
+
+    //The slot is left partially bound, shaders must not sample removed ids
+    pub fn remove_texture(&mut self, id: TextureId) -> Option<Image> {
+        let image = self.textures.get_mut(id.0 as usize)?.take()?;
+
+        self.free_textures.push(id.0);
+        self.dirty_textures.retain(|&index| index != id.0);
+
+        Some(image)
+    }
+
+    pub fn insert_material<T: Pod>(&mut self, material: &T) -> MaterialId {
+        let index = match self.free_materials.pop() {
+            Some(index) => index,
+            None => {
+                let index = (self.materials.len() / self.material_size) as u32;
+
+                self.materials
+                    .resize(self.materials.len() + self.material_size, 0);
+
+                index
+            }
+        };
+
+        let id = MaterialId(index);
+
+        self.update_material(id, material);
+
+        id
+    }
+
+    pub fn update_material<T: Pod>(&mut self, id: MaterialId, material: &T) {
+        if mem::size_of::<T>() != self.material_size {
+            panic!("material does not match the table material size");
+        }
+
+        let bytes = unsafe {
+            std::slice::from_raw_parts(material as *const T as *const u8, self.material_size)
+        };
+
+        let start = id.0 as usize * self.material_size;
+        let end = start + self.material_size;
+
+        self.materials[start..end].copy_from_slice(bytes);
+
+        self.dirty_materials = Some(match self.dirty_materials.take() {
+            Some(dirty) => dirty.start.min(start)..dirty.end.max(end),
+            None => start..end,
+        });
+    }
+
+    pub fn remove_material(&mut self, id: MaterialId) {
+        self.free_materials.push(id.0);
+    }
+
+    pub fn flush(&mut self, device: &Device) {
+        let texture_count = self.textures.len() as u32;
+
+        if texture_count > self.texture_capacity {
+            self.texture_capacity = (self.texture_capacity * 2)
+                .max(texture_count)
+                .min(self.max_textures);
+
+            self.descriptors.grow(device, self.texture_capacity);
+
+            self.dirty_textures = (0..texture_count)
+                .filter(|&index| self.textures[index as usize].is_some())
+                .collect();
+
+            self.write_material_binding();
+        }
+
+        let material_count = (self.materials.len() / self.material_size) as u32;
+
+        if material_count > self.material_capacity {
+            self.material_capacity = (self.material_capacity * 2).max(material_count);
+
+            self.material_buffer = Self::material_buffer(
+                device,
+                self.material_size * self.material_capacity as usize,
+            );
+
+            self.dirty_materials = Some(0..self.materials.len());
+
+            self.write_material_binding();
+        }
+
+        if let Some(dirty) = self.dirty_materials.take() {
+            self.material_buffer.write(BufferWrite {
+                offset: dirty.start as _,
+                data: &self.materials[dirty],
+            });
+        }
+
+        self.write_textures();
+    }
+
+    pub fn bind(&self, commands: &mut Commands<'_>, pipeline: &Pipeline) {
+        let Commands::Vulkan { commands } = commands else {
+            panic!("not vulkan commands");
+        };
+
+        let Pipeline::Vulkan {
+            pipeline_layout,
+            bind_point,
+            ..
+        } = pipeline else {
+            panic!("not a vulkan pipeline");
+        };
+
+        let MaterialDescriptors::Vulkan { descriptor_set, .. } = &self.descriptors;
+
+        commands.bind_descriptor_sets(
+            *bind_point,
+            pipeline_layout,
+            MATERIAL_TABLE_SET,
+            &[descriptor_set],
+            &[],
+        );
+    }
+
+    pub fn texture_count(&self) -> u32 {
+        self.textures.len() as u32 - self.free_textures.len() as u32
+    }
+
+    pub fn material_count(&self) -> u32 {
+        (self.materials.len() / self.material_size) as u32 - self.free_materials.len() as u32
+    }
+
+    pub(crate) fn vk_descriptor_set_layout(&self) -> &vk::DescriptorSetLayout {
+        let MaterialDescriptors::Vulkan {
+            descriptor_set_layout,
+            ..
+        } = &self.descriptors;
+
+        descriptor_set_layout
+    }
+
+    fn material_buffer(device: &Device, size: usize) -> Buffer {
+        Buffer::new(BufferInfo {
+            device,
+            usage: BufferUsage::STORAGE,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size,
+            priority: MemoryPriority::Default,
+        })
+    }
+
+    fn write_material_binding(&self) {
+        let MaterialDescriptors::Vulkan {
+            device,
+            descriptor_set,
+            ..
+        } = &self.descriptors;
+
+        let Buffer::Vulkan { buffer, .. } = &self.material_buffer else {
+            panic!("not a vulkan buffer");
+        };
+
+        let buffer_info = vk::DescriptorBufferInfo {
+            buffer,
+            offset: 0,
+            range: self.material_size * self.material_capacity as usize,
+        };
+
+        let write_descriptor = vk::WriteDescriptorSet {
+            dst_set: descriptor_set,
+            dst_binding: MATERIAL_BINDING,
+            dst_array_element: 0,
+            descriptor_count: 1,
+            descriptor_type: vk::DescriptorType::StorageBuffer,
+            buffer_infos: &[buffer_info],
+            image_infos: &[],
+        };
+
+        device
+            .update_descriptor_sets(&[write_descriptor], &[])
+            .expect("failed to update descriptor sets");
+    }
+
+    fn write_textures(&mut self) {
+        if self.dirty_textures.is_empty() {
+            return;
+        }
+
+        let MaterialDescriptors::Vulkan {
+            device,
+            descriptor_set,
+            ..
+        } = &self.descriptors;
+
+        let image_infos = self
+            .dirty_textures
+            .iter()
+            .map(|&index| {
+                let Some(Image::Vulkan { view, sampler, .. }) = &self.textures[index as usize] else {
+                    panic!("not a vulkan image");
+                };
+
+                vk::DescriptorImageInfo {
+                    sampler,
+                    image_view: view,
+                    image_layout: ImageLayout::ShaderReadOnly.into(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let write_descriptors = self
+            .dirty_textures
+            .iter()
+            .enumerate()
+            .map(|(i, &index)| vk::WriteDescriptorSet {
+                dst_set: descriptor_set,
+                dst_binding: TEXTURE_BINDING,
+                dst_array_element: index,
+                descriptor_count: 1,
+                descriptor_type: vk::DescriptorType::CombinedImageSampler,
+                buffer_infos: &[],
+                image_infos: &image_infos[i..=i],
+            })
+            .collect::<Vec<_>>();
+
+        device
+            .update_descriptor_sets(&write_descriptors, &[])
+            .expect("failed to update descriptor sets");
+
+        self.dirty_textures.clear();
+    }
+}
+
+impl MaterialDescriptors {
+    fn new(device: &Device, stage: ShaderStage, max_textures: u32, capacity: u32) -> Self {
+        let Device::Vulkan { device: vk_device, .. } = device else {
+            panic!("not a vulkan device");
+        };
+
+        let bindings = [
+            vk::DescriptorSetLayoutBinding {
+                binding: MATERIAL_BINDING,
+                descriptor_type: vk::DescriptorType::StorageBuffer,
+                descriptor_count: 1,
+                stage: stage.to_vk(),
+            },
+            vk::DescriptorSetLayoutBinding {
+                binding: TEXTURE_BINDING,
+                descriptor_type: vk::DescriptorType::CombinedImageSampler,
+                descriptor_count: max_textures,
+                stage: stage.to_vk(),
+            },
+        ];
+
+        //the material buffer is only rewritten by flush, outside of recording
+        let binding_flags = [
+            0,
+            vk::DESCRIPTOR_BINDING_UPDATE_AFTER_BIND
+                | vk::DESCRIPTOR_BINDING_UPDATE_UNUSED_WHILE_PENDING
+                | vk::DESCRIPTOR_BINDING_PARTIALLY_BOUND
+                | vk::DESCRIPTOR_BINDING_VARIABLE_DESCRIPTOR_COUNT,
+        ];
+
+        let descriptor_set_layout_create_info = vk::DescriptorSetLayoutCreateInfo {
+            flags: vk::DESCRIPTOR_SET_LAYOUT_CREATE_UPDATE_AFTER_BIND_POOL,
+            bindings: &bindings,
+            binding_flags: &binding_flags,
+            immutable_samplers: &[],
+        };
+
+        let descriptor_set_layout =
+            vk::DescriptorSetLayout::new(vk_device.clone(), descriptor_set_layout_create_info)
+                .expect("failed to create descriptor set layout");
+
+        let (descriptor_pool, descriptor_set) =
+            Self::allocate(vk_device.clone(), &descriptor_set_layout, capacity);
+
+        Self::Vulkan {
+            device: vk_device.clone(),
+            descriptor_set,
+            descriptor_pool,
+            descriptor_set_layout,
+        }
+    }
+
+    //The layout is kept so existing pipelines stay compatible when the array grows
+    fn grow(&mut self, device: &Device, capacity: u32) {
+        let Device::Vulkan { device, .. } = device else {
+            panic!("not a vulkan device");
+        };
+
+        let Self::Vulkan {
+            descriptor_set,
+            descriptor_pool,
+            descriptor_set_layout,
+            ..
+        } = self;
+
+        let (new_descriptor_pool, new_descriptor_set) =
+            Self::allocate(device.clone(), descriptor_set_layout, capacity);
+
+        *descriptor_set = new_descriptor_set;
+        *descriptor_pool = new_descriptor_pool;
+    }
+
+    fn allocate(
+        device: Rc<vk::Device>,
+        descriptor_set_layout: &vk::DescriptorSetLayout,
+        capacity: u32,
+    ) -> (vk::DescriptorPool, vk::DescriptorSet) {
+        let descriptor_pool_create_info = vk::DescriptorPoolCreateInfo {
+            flags: vk::DESCRIPTOR_POOL_CREATE_UPDATE_AFTER_BIND,
+            max_sets: 1,
+            pool_sizes: &[
+                vk::DescriptorPoolSize {
+                    descriptor_type: vk::DescriptorType::StorageBuffer,
+                    descriptor_count: 1,
+                },
+                vk::DescriptorPoolSize {
+                    descriptor_type: vk::DescriptorType::CombinedImageSampler,
+                    descriptor_count: capacity,
+                },
+            ],
+        };
+
+        let descriptor_pool = vk::DescriptorPool::new(device.clone(), descriptor_pool_create_info)
+            .expect("failed to create descriptor pool");
+
+        let descriptor_set_allocate_info = vk::DescriptorSetAllocateInfo {
+            descriptor_pool: &descriptor_pool,
+            set_layouts: &[descriptor_set_layout],
+            variable_descriptor_counts: &[capacity],
+        };
+
+        let descriptor_set = vk::DescriptorSet::allocate(device, descriptor_set_allocate_info)
+            .expect("failed to allocate descriptor sets")
+            .remove(0);
+
+        (descriptor_pool, descriptor_set)
+    }
+}
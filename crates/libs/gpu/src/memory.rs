@@ -1,10 +1,17 @@
+use std::mem;
+
 use bitflags::bitflags;
 
+//Bound on everything copied into buffers, uniforms and push constants
+pub use vk::Pod;
+
 bitflags! {
     pub struct MemoryProperties: usize {
         const DEVICE_LOCAL = 1 << 0;
         const HOST_VISIBLE = 1 << 1;
         const HOST_COHERENT = 1 << 2;
+        //only backed as tiles spill, for transient attachments on tilers
+        const LAZILY_ALLOCATED = 1 << 3;
     }
 }
 
@@ -12,18 +19,55 @@ impl MemoryProperties {
     pub(crate) fn to_vk(self) -> u32 {
         let mut vk = 0;
 
-        if self == Self::DEVICE_LOCAL {
+        if self.contains(Self::DEVICE_LOCAL) {
             vk |= vk::MEMORY_PROPERTY_DEVICE_LOCAL;
         }
 
-        if self == Self::HOST_VISIBLE {
+        if self.contains(Self::HOST_VISIBLE) {
             vk |= vk::MEMORY_PROPERTY_HOST_VISIBLE;
         }
 
-        if self == Self::HOST_COHERENT {
+        if self.contains(Self::HOST_COHERENT) {
             vk |= vk::MEMORY_PROPERTY_HOST_COHERENT;
         }
 
+        if self.contains(Self::LAZILY_ALLOCATED) {
+            vk |= vk::MEMORY_PROPERTY_LAZILY_ALLOCATED;
+        }
+
         vk
     }
 }
+
+//Which allocations the driver keeps resident when device local memory runs out. Only a
+//hint, and ignored unless Device::memory_priority_supported
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MemoryPriority {
+    //transient targets that are cheap to lose
+    Low,
+    Default,
+    //e.g. the chunk atlas
+    High,
+    Custom(f32),
+}
+
+impl Default for MemoryPriority {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
+impl MemoryPriority {
+    pub(crate) fn to_vk(self) -> f32 {
+        match self {
+            Self::Low => 0.0,
+            Self::Default => 0.5,
+            Self::High => 1.0,
+            Self::Custom(priority) => priority.clamp(0.0, 1.0),
+        }
+    }
+}
+
+pub(crate) fn as_bytes<T: Pod>(value: &T) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(value as *const T as *const u8, mem::size_of::<T>()) }
+}
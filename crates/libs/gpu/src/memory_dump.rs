@@ -0,0 +1,68 @@
+use serde::Serialize;
+
+use std::fs;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+//Byte range handed out of a block. Ranges of an AttachmentPool overlap where targets alias.
+#[derive(Clone, Debug, Serialize)]
+pub struct AllocationDump {
+    pub name: String,
+    pub offset: u64,
+    pub size: u64,
+}
+
+//One vk::Memory allocation
+#[derive(Clone, Debug, Serialize)]
+pub struct BlockDump {
+    pub name: String,
+    pub size: u64,
+    pub allocations: Vec<AllocationDump>,
+}
+
+impl BlockDump {
+    //bytes up to the end of the last allocation
+    pub fn used(&self) -> u64 {
+        self.allocations
+            .iter()
+            .map(|allocation| allocation.offset + allocation.size)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PoolDump {
+    pub name: String,
+    pub blocks: Vec<BlockDump>,
+}
+
+//Snapshot of where device memory went, for a fragmentation view in an editor or to attach
+//to a bug report. Collect one PoolDump from each MeshPool, AttachmentPool and
+//ResourceRegistry of interest through their dump methods.
+#[derive(Clone, Default, Debug, Serialize)]
+pub struct MemoryDump {
+    pub pools: Vec<PoolDump>,
+}
+
+impl MemoryDump {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, pool: PoolDump) {
+        self.pools.push(pool);
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("failed to serialize memory dump")
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = fs::File::create(path)?;
+
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+
+        Ok(())
+    }
+}
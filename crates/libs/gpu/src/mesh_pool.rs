@@ -0,0 +1,184 @@
+use crate::prelude::*;
+
+use std::mem;
+
+pub struct MeshPoolInfo<'a> {
+    pub device: &'a Device,
+    pub vertex_size: usize,
+    pub vertex_capacity: usize,
+    pub index_capacity: usize,
+}
+
+//Location of one mesh inside the pool, the arguments of an indexed draw
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct MeshRange {
+    pub first_index: u32,
+    pub index_count: u32,
+    pub vertex_offset: i32,
+}
+
+//Shared vertex and u16 index buffers for many small meshes, so every chunk draws from
+//the same bindings and the draws can be generated on the gpu. Meshes are appended,
+//space is only reclaimed by clear.
+pub struct MeshPool {
+    vertices: Buffer,
+    indices: Buffer,
+    vertex_size: usize,
+    vertex_capacity: usize,
+    index_capacity: usize,
+    vertex_count: usize,
+    index_count: usize,
+}
+
+impl MeshPool {
+    pub fn new(info: MeshPoolInfo<'_>) -> Self {
+        let vertices = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::VERTEX | BufferUsage::TRANSFER_DST,
+            properties: MemoryProperties::DEVICE_LOCAL,
+            size: info.vertex_capacity * info.vertex_size,
+            priority: MemoryPriority::High,
+        });
+
+        let indices = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::INDEX | BufferUsage::TRANSFER_DST,
+            properties: MemoryProperties::DEVICE_LOCAL,
+            size: info.index_capacity * mem::size_of::<u16>(),
+            priority: MemoryPriority::High,
+        });
+
+        Self {
+            vertices,
+            indices,
+            vertex_size: info.vertex_size,
+            vertex_capacity: info.vertex_capacity,
+            index_capacity: info.index_capacity,
+            vertex_count: 0,
+            index_count: 0,
+        }
+    }
+
+    //Uploads a mesh and waits for the copy, None when the pool is full
+    pub fn insert<V: Pod>(
+        &mut self,
+        device: &mut Device,
+        vertices: &[V],
+        indices: &[u16],
+    ) -> Option<MeshRange> {
+        if mem::size_of::<V>() != self.vertex_size {
+            panic!("vertex size does not match the mesh pool");
+        }
+
+        if self.vertex_count + vertices.len() > self.vertex_capacity
+            || self.index_count + indices.len() > self.index_capacity
+        {
+            return None;
+        }
+
+        let range = MeshRange {
+            first_index: self.index_count as _,
+            index_count: indices.len() as _,
+            vertex_offset: self.vertex_count as _,
+        };
+
+        if !vertices.is_empty() {
+            Self::upload(
+                device,
+                &mut self.vertices,
+                (self.vertex_count * self.vertex_size) as _,
+                vertices,
+            );
+        }
+
+        if !indices.is_empty() {
+            Self::upload(
+                device,
+                &mut self.indices,
+                (self.index_count * mem::size_of::<u16>()) as _,
+                indices,
+            );
+        }
+
+        self.vertex_count += vertices.len();
+        self.index_count += indices.len();
+
+        Some(range)
+    }
+
+    fn upload<T: Pod>(device: &mut Device, to: &mut Buffer, dst: u64, data: &[T]) {
+        let size = data.len() * mem::size_of::<T>();
+
+        let mut staging = Buffer::new(BufferInfo {
+            device,
+            usage: BufferUsage::TRANSFER_SRC,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size,
+            priority: MemoryPriority::Default,
+        });
+
+        staging.write(BufferWrite { offset: 0, data });
+
+        device.copy_buffer_to_buffer(BufferCopy {
+            from: &staging,
+            to,
+            src: 0,
+            dst,
+            size: size as _,
+        });
+    }
+
+    #[cfg(feature = "memory-dump")]
+    pub fn dump(&self, name: &str) -> PoolDump {
+        let block = |name: &str, buffer: &Buffer, used: usize| {
+            let Buffer::Vulkan { memory, .. } = buffer else {
+                panic!("not a vulkan buffer");
+            };
+
+            BlockDump {
+                name: name.to_owned(),
+                size: memory.size(),
+                allocations: vec![AllocationDump {
+                    name: "meshes".to_owned(),
+                    offset: 0,
+                    size: used as _,
+                }],
+            }
+        };
+
+        PoolDump {
+            name: name.to_owned(),
+            blocks: vec![
+                block(
+                    "vertices",
+                    &self.vertices,
+                    self.vertex_count * self.vertex_size,
+                ),
+                block(
+                    "indices",
+                    &self.indices,
+                    self.index_count * mem::size_of::<u16>(),
+                ),
+            ],
+        }
+    }
+
+    //Ranges handed out before are invalid afterwards
+    pub fn clear(&mut self) {
+        self.vertex_count = 0;
+        self.index_count = 0;
+    }
+
+    pub fn vertices(&self) -> &Buffer {
+        &self.vertices
+    }
+
+    pub fn indices(&self) -> &Buffer {
+        &self.indices
+    }
+
+    pub fn bind(&self, commands: &mut Commands<'_>) {
+        commands.bind_vertex_buffers(0, &[&self.vertices], &[0]);
+        commands.bind_index_buffer(&self.indices, 0);
+    }
+}
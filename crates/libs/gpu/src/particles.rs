@@ -0,0 +1,337 @@
+use crate::compute::{compute_pipeline, storage_binding, storage_descriptor};
+use crate::prelude::*;
+
+use std::mem;
+
+const BLOCK_SIZE: usize = 64;
+
+//std430 layout of Particle in shaders/particle_update.comp.glsl. Also read as two
+//Rgba32Sfloat instance attributes, see ParticleSystem::vertex_input.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Particle {
+    pub position: [f32; 3],
+    //seconds left, the particle dies at zero
+    pub life: f32,
+    pub velocity: [f32; 3],
+    pub size: f32,
+}
+
+unsafe impl Pod for Particle {}
+
+pub struct ParticleSystemInfo<'a> {
+    pub device: &'a Device,
+    pub max_particles: usize,
+    //particles emit can queue between two records
+    pub max_spawns: usize,
+    pub gravity: [f32; 3],
+}
+
+//push constants of shaders/particle_update.comp.glsl
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct UpdateConstants {
+    gravity: [f32; 4],
+    delta_time: f32,
+    spawn_count: u32,
+    max_particles: u32,
+    padding: u32,
+}
+
+unsafe impl Pod for UpdateConstants {}
+
+//Particles simulated and compacted on the gpu, the cpu only queues spawns. Each record
+//updates the particles in place and flags the ones still alive, the prefix scan turns
+//the flags into offsets and a compaction pass packs the survivors into the other buffer
+//of the pair while writing the indirect draw of the alive count. The compacted buffer
+//is what draw reads, one instanced quad per particle, and what the next record updates.
+pub struct ParticleSystem {
+    update: Pipeline,
+    compact: Pipeline,
+    scan: PrefixScan,
+    particles: [Buffer; 2],
+    spawns: Buffer,
+    //only referenced by the descriptor sets and the scan
+    _alive: Buffer,
+    draw: Buffer,
+    quad: Buffer,
+    //which of particles holds the live particles
+    current: usize,
+    spawn_count: usize,
+    max_particles: usize,
+    max_spawns: usize,
+    gravity: [f32; 3],
+    cleared: bool,
+}
+
+impl ParticleSystem {
+    pub fn new(info: ParticleSystemInfo<'_>) -> Self {
+        let max_particles = info.max_particles.max(1);
+        let max_spawns = info.max_spawns.max(1);
+
+        let particles_size = max_particles * mem::size_of::<Particle>();
+        let spawns_size = max_spawns * mem::size_of::<Particle>();
+        let alive_size = max_particles * mem::size_of::<u32>();
+        let draw_size = mem::size_of::<DrawIndexedIndirectCommand>();
+
+        let particles = [(); 2].map(|_| {
+            Buffer::new(BufferInfo {
+                device: info.device,
+                usage: BufferUsage::STORAGE | BufferUsage::VERTEX,
+                properties: MemoryProperties::DEVICE_LOCAL,
+                size: particles_size,
+                priority: MemoryPriority::High,
+            })
+        });
+
+        let spawns = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::STORAGE,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: spawns_size,
+            priority: MemoryPriority::Default,
+        });
+
+        let alive = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::STORAGE,
+            properties: MemoryProperties::DEVICE_LOCAL,
+            size: alive_size,
+            priority: MemoryPriority::Default,
+        });
+
+        let draw = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::STORAGE | BufferUsage::INDIRECT | BufferUsage::TRANSFER_DST,
+            properties: MemoryProperties::DEVICE_LOCAL,
+            size: draw_size,
+            priority: MemoryPriority::High,
+        });
+
+        let mut quad = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::INDEX,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: 6 * mem::size_of::<u16>(),
+            priority: MemoryPriority::Default,
+        });
+
+        quad.write(BufferWrite {
+            offset: 0,
+            data: &[0u16, 1, 2, 2, 1, 3],
+        });
+
+        let mut scan = PrefixScan::new(PrefixScanInfo {
+            device: info.device,
+            max_count: max_particles,
+        });
+
+        scan.bind(&alive, max_particles);
+
+        let mut update = compute_pipeline(
+            info.device,
+            "particle_update.comp",
+            include_str!("shaders/particle_update.comp.glsl"),
+            2,
+            &[
+                storage_descriptor(0),
+                storage_descriptor(1),
+                storage_descriptor(2),
+                storage_descriptor(3),
+            ],
+            mem::size_of::<UpdateConstants>() as _,
+        );
+
+        let mut compact = compute_pipeline(
+            info.device,
+            "particle_compact.comp",
+            include_str!("shaders/particle_compact.comp.glsl"),
+            2,
+            &[
+                storage_descriptor(0),
+                storage_descriptor(1),
+                storage_descriptor(2),
+                storage_descriptor(3),
+            ],
+            mem::size_of::<u32>() as _,
+        );
+
+        //slot i updates particles[i] and compacts it into the other buffer
+        for slot in 0..2 {
+            let (from, to) = (&particles[slot], &particles[1 - slot]);
+
+            update.bind(
+                slot as _,
+                &[
+                    storage_binding(0, from, particles_size),
+                    storage_binding(1, &spawns, spawns_size),
+                    storage_binding(2, &alive, alive_size),
+                    storage_binding(3, &draw, draw_size),
+                ],
+            );
+
+            compact.bind(
+                slot as _,
+                &[
+                    storage_binding(0, from, particles_size),
+                    storage_binding(1, to, particles_size),
+                    storage_binding(2, &alive, alive_size),
+                    storage_binding(3, &draw, draw_size),
+                ],
+            );
+        }
+
+        Self {
+            update,
+            compact,
+            scan,
+            particles,
+            spawns,
+            _alive: alive,
+            draw,
+            quad,
+            current: 0,
+            spawn_count: 0,
+            max_particles,
+            max_spawns,
+            gravity: info.gravity,
+            cleared: false,
+        }
+    }
+
+    //Queues particles for the next record, past max_spawns they are dropped. Spawns
+    //only take free slots, so they are also dropped while max_particles are alive.
+    //Must not be called while a recorded update is still pending on the gpu.
+    pub fn emit(&mut self, particles: &[Particle]) {
+        let count = particles.len().min(self.max_spawns - self.spawn_count);
+
+        if count == 0 {
+            return;
+        }
+
+        self.spawns.write(BufferWrite {
+            offset: (self.spawn_count * mem::size_of::<Particle>()) as _,
+            data: &particles[..count],
+        });
+
+        self.spawn_count += count;
+    }
+
+    pub fn set_gravity(&mut self, gravity: [f32; 3]) {
+        self.gravity = gravity;
+    }
+
+    //Must be recorded outside a render pass
+    pub fn record(&mut self, commands: &mut Commands<'_>, delta_time: f32) {
+        let draw_size = mem::size_of::<DrawIndexedIndirectCommand>();
+
+        if !self.cleared {
+            //zero particles alive to begin with
+            commands.fill_buffer(&self.draw, 0, draw_size, 0);
+
+            commands.pipeline_barrier(
+                PipelineStage::TRANSFER,
+                PipelineStage::COMPUTE_SHADER,
+                &[Barrier::Memory {
+                    src_access: Access::TRANSFER_WRITE,
+                    dst_access: Access::SHADER_READ,
+                }],
+            );
+
+            self.cleared = true;
+        }
+
+        //the last draw and compaction are done with the buffers this update writes
+        commands.pipeline_barrier(
+            PipelineStage::COMPUTE_SHADER | PipelineStage::DRAW_INDIRECT | PipelineStage::VERTEX_INPUT,
+            PipelineStage::COMPUTE_SHADER,
+            &[Barrier::Memory {
+                src_access: Access::SHADER_WRITE,
+                dst_access: Access::SHADER_READ | Access::SHADER_WRITE,
+            }],
+        );
+
+        let [x, y, z] = self.gravity;
+
+        let constants = UpdateConstants {
+            gravity: [x, y, z, 0.0],
+            delta_time,
+            spawn_count: self.spawn_count as _,
+            max_particles: self.max_particles as _,
+            padding: 0,
+        };
+
+        let groups = ((self.max_particles + BLOCK_SIZE - 1) / BLOCK_SIZE) as u32;
+
+        commands.bind_pipeline(self.current as _, &self.update);
+        commands.push_constants(&self.update, ShaderStage::COMPUTE, 0, &[constants]);
+        commands.dispatch(groups, 1, 1);
+
+        commands.pipeline_barrier(
+            PipelineStage::COMPUTE_SHADER,
+            PipelineStage::COMPUTE_SHADER,
+            &[Barrier::Memory {
+                src_access: Access::SHADER_WRITE,
+                dst_access: Access::SHADER_READ | Access::SHADER_WRITE,
+            }],
+        );
+
+        //ends with a barrier of its own
+        self.scan.record(commands, self.max_particles);
+
+        commands.bind_pipeline(self.current as _, &self.compact);
+        commands.push_constants(
+            &self.compact,
+            ShaderStage::COMPUTE,
+            0,
+            &[self.max_particles as u32],
+        );
+        commands.dispatch(groups, 1, 1);
+
+        commands.pipeline_barrier(
+            PipelineStage::COMPUTE_SHADER,
+            PipelineStage::DRAW_INDIRECT | PipelineStage::VERTEX_INPUT,
+            &[Barrier::Memory {
+                src_access: Access::SHADER_WRITE,
+                dst_access: Access::INDIRECT_COMMAND_READ | Access::VERTEX_ATTRIBUTE_READ,
+            }],
+        );
+
+        self.current = 1 - self.current;
+        self.spawn_count = 0;
+    }
+
+    //Draws one quad instance per alive particle, with a pipeline using vertex_input
+    //bound inside a render pass. The quad's corner is gl_VertexIndex.
+    pub fn draw(&self, commands: &mut Commands<'_>, binding: u32) {
+        commands.bind_vertex_buffers(binding, &[&self.particles[self.current]], &[0]);
+        commands.bind_index_buffer(&self.quad, 0);
+        commands.draw_indexed_indirect(
+            &self.draw,
+            0,
+            1,
+            mem::size_of::<DrawIndexedIndirectCommand>() as _,
+        );
+    }
+
+    //Per instance attributes for binding: xyz position and life at location, xyz
+    //velocity and size at location + 1
+    pub fn vertex_input(binding: u32, location: u32) -> [VertexInput; 2] {
+        [0, 1].map(|i| VertexInput {
+            binding,
+            location: location + i,
+            format: Format::Rgba32Sfloat,
+            rate: InputRate::Instance,
+        })
+    }
+
+    //The compacted particles draw reads
+    pub fn particles(&self) -> &Buffer {
+        &self.particles[self.current]
+    }
+
+    //DrawIndexedIndirectCommand whose instance count is the alive count
+    pub fn draw_command(&self) -> &Buffer {
+        &self.draw
+    }
+}
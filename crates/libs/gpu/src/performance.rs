@@ -0,0 +1,126 @@
+use crate::prelude::*;
+
+pub use vk::{
+    PerformanceCounter, PerformanceCounterScope, PerformanceCounterStorage, PerformanceCounterUnit,
+    PerformanceCounterValue,
+};
+
+pub struct PerformanceQueryInfo<'a> {
+    pub device: &'a Device,
+    //indices into Device::performance_counters
+    pub counters: &'a [u32],
+}
+
+pub enum PerformanceQuery {
+    Vulkan {
+        query_pool: vk::QueryPool,
+        storages: Vec<vk::PerformanceCounterStorage>,
+        recorded: bool,
+    },
+}
+
+//Hardware counters such as bandwidth and occupancy over a span of one frame's commands.
+//draw_call submits every frame once, so the selected counters have to fit in a single pass,
+//new returns None otherwise. Acquire the device profiling lock before recording the span.
+impl PerformanceQuery {
+    pub fn new(info: PerformanceQueryInfo<'_>) -> Option<Self> {
+        let Device::Vulkan {
+            physical_device,
+            device,
+            queue_family_index,
+            ..
+        } = info.device
+        else {
+            panic!("not a vulkan device");
+        };
+
+        if !physical_device.performance_query_supported() {
+            return None;
+        }
+
+        let passes = physical_device
+            .performance_query_passes(*queue_family_index, info.counters)
+            .expect("failed to get performance query passes");
+
+        if passes != 1 {
+            return None;
+        }
+
+        let counters = physical_device
+            .performance_counters(*queue_family_index)
+            .expect("failed to enumerate performance counters");
+
+        let storages = info
+            .counters
+            .iter()
+            .map(|&index| counters[index as usize].storage)
+            .collect();
+
+        let query_pool = vk::QueryPool::new(
+            device.clone(),
+            vk::QueryPoolCreateInfo {
+                query_type: vk::QueryType::PerformanceQuery,
+                query_count: 1,
+                performance: Some(vk::QueryPoolPerformanceCreateInfo {
+                    queue_family_index: *queue_family_index,
+                    counter_indices: info.counters,
+                }),
+            },
+        )
+        .expect("failed to create query pool");
+
+        Some(Self::Vulkan {
+            query_pool,
+            storages,
+            recorded: false,
+        })
+    }
+
+    //Must be recorded outside a render pass
+    pub fn begin(&mut self, commands: &mut Commands<'_>) {
+        let Self::Vulkan { query_pool, .. } = self;
+
+        let Commands::Vulkan { commands } = commands else {
+            panic!("not vulkan commands");
+        };
+
+        commands.reset_query_pool(query_pool, 0, 1);
+        commands.begin_query(query_pool, 0);
+    }
+
+    pub fn end(&mut self, commands: &mut Commands<'_>) {
+        let Self::Vulkan {
+            query_pool,
+            recorded,
+            ..
+        } = self;
+
+        let Commands::Vulkan { commands } = commands else {
+            panic!("not vulkan commands");
+        };
+
+        commands.end_query(query_pool, 0);
+
+        *recorded = true;
+    }
+
+    //Values in the order the counters were given, None before the first recorded span.
+    //Only call after the frame fence wait, otherwise this blocks until the gpu finishes.
+    pub fn results(&self) -> Option<Vec<PerformanceCounterValue>> {
+        let Self::Vulkan {
+            query_pool,
+            storages,
+            recorded,
+        } = self;
+
+        if !recorded {
+            return None;
+        }
+
+        let values = query_pool
+            .performance_counter_values(0, storages)
+            .expect("failed to get performance query results");
+
+        Some(values)
+    }
+}
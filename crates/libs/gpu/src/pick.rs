@@ -0,0 +1,186 @@
+use crate::prelude::*;
+
+use std::mem;
+
+pub struct PickTargetInfo<'a> {
+    pub device: &'a Device,
+    pub extent: (u32, u32),
+    pub depth: DepthConfig,
+    //for a swapchain sized target, begin fails with StaleFramebuffer after a resize
+    pub generation: Option<&'a FramebufferGeneration>,
+}
+
+//Object id target for editor selection. Draw into render_pass() with pipelines built with
+//color_blend: ColorBlend::Disabled and depth_stencil() writing a uint to location 0.
+//Id 0 is the cleared value and means nothing was hit.
+pub struct PickTarget {
+    render_pass: RenderPass,
+    framebuffer: Framebuffer,
+    ids: Image,
+    _depth: Image,
+    readback: Buffer,
+    extent: (u32, u32),
+    depth: DepthConfig,
+}
+
+impl PickTarget {
+    pub fn new(info: PickTargetInfo<'_>) -> Self {
+        let ids = Image::new(ImageInfo {
+            device: info.device,
+            format: Format::R32Uint,
+            usage: ImageUsage::COLOR | ImageUsage::TRANSFER_SRC,
+            ty: ImageType::TwoDim,
+            extent: (info.extent.0, info.extent.1, 1),
+            samples: SampleCount::One,
+            priority: MemoryPriority::Default,
+        });
+
+        let depth = Image::new(ImageInfo {
+            device: info.device,
+            format: info.depth.format(),
+            usage: ImageUsage::DEPTH_STENCIL,
+            ty: ImageType::TwoDim,
+            extent: (info.extent.0, info.extent.1, 1),
+            samples: SampleCount::One,
+            priority: MemoryPriority::Default,
+        });
+
+        let render_pass = RenderPass::new(RenderPassInfo {
+            device: info.device,
+            attachments: &[
+                Attachment {
+                    format: Format::R32Uint,
+                    samples: SampleCount::One,
+                    load_op: AttachmentLoadOp::Clear,
+                    store_op: AttachmentStoreOp::Store,
+                    initial_layout: ImageLayout::Undefined,
+                    final_layout: ImageLayout::TransferSrc,
+                    layout: ImageLayout::ColorAttachment,
+                    ty: AttachmentType::Color,
+                },
+                info.depth
+                    .attachment(AttachmentLoadOp::Clear, AttachmentStoreOp::DontCare),
+            ],
+            subpasses: &[Subpass {
+                src: None,
+                src_access: Access::empty(),
+                src_stage: PipelineStage::COLOR_ATTACHMENT_OUTPUT
+                    | PipelineStage::EARLY_FRAGMENT_TESTS,
+                dst: Some(0),
+                dst_access: Access::COLOR_ATTACHMENT_WRITE
+                    | Access::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                dst_stage: PipelineStage::COLOR_ATTACHMENT_OUTPUT
+                    | PipelineStage::EARLY_FRAGMENT_TESTS,
+                attachments: &[0, 1],
+            }],
+        });
+
+        let framebuffer = Framebuffer::new(FramebufferInfo {
+            device: info.device,
+            render_pass: &render_pass,
+            extent: (info.extent.0, info.extent.1, 1),
+            attachments: &[&ids, &depth],
+            generation: info.generation,
+        });
+
+        let readback = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::TRANSFER_DST,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: mem::size_of::<u32>(),
+            priority: MemoryPriority::Default,
+        });
+
+        Self {
+            render_pass,
+            framebuffer,
+            ids,
+            _depth: depth,
+            readback,
+            extent: info.extent,
+            depth: info.depth,
+        }
+    }
+
+    pub fn render_pass(&self) -> &RenderPass {
+        &self.render_pass
+    }
+
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    pub fn image(&self) -> &Image {
+        &self.ids
+    }
+
+    pub fn extent(&self) -> (u32, u32) {
+        self.extent
+    }
+
+    pub fn depth_stencil(&self) -> DepthStencil {
+        self.depth.depth_stencil(true)
+    }
+
+    pub fn begin(&self, commands: &mut Commands<'_>) -> Result<(), Error> {
+        commands.begin_render_pass(RenderPassBeginInfo {
+            render_pass: &self.render_pass,
+            framebuffer: &self.framebuffer,
+            //zero bits read back as id 0
+            color_clear_values: &[[0.0, 0.0, 0.0, 0.0]],
+            depth_stencil_clear_value: self.depth.depth_stencil_clear_value(),
+        })
+    }
+
+    //Reads back the id under pixel (x, y). Blocks on a one off submission, so call it
+    //between frames once the pick pass has been rendered.
+    pub fn read_pick(&mut self, device: &mut Device, x: u32, y: u32) -> Option<u32> {
+        if x >= self.extent.0 || y >= self.extent.1 {
+            return None;
+        }
+
+        let ids = &self.ids;
+        let readback = &mut self.readback;
+
+        device.call(|mut commands| {
+            commands.pipeline_barrier(
+                PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+                PipelineStage::TRANSFER,
+                &[Barrier::Image {
+                    src_access: Access::COLOR_ATTACHMENT_WRITE,
+                    dst_access: Access::TRANSFER_READ,
+                    old_layout: ImageLayout::TransferSrc,
+                    new_layout: ImageLayout::TransferSrc,
+                    image: ids,
+                }],
+            );
+
+            commands.copy_image_to_buffer(
+                ids,
+                ImageLayout::TransferSrc,
+                readback,
+                0,
+                (x as i32, y as i32),
+                (1, 1),
+            );
+
+            commands.pipeline_barrier(
+                PipelineStage::TRANSFER,
+                PipelineStage::HOST,
+                &[Barrier::Memory {
+                    src_access: Access::TRANSFER_WRITE,
+                    dst_access: Access::HOST_READ,
+                }],
+            );
+        });
+
+        let bytes = device
+            .read_buffer_to_vec(&self.readback, 0..mem::size_of::<u32>() as u64)
+            .expect("failed to read pick buffer");
+
+        match u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) {
+            0 => None,
+            id => Some(id),
+        }
+    }
+}
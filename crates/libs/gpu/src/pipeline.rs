@@ -1,14 +1,24 @@
 use crate::prelude::*;
 
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::iter;
+use std::rc::Rc;
 
 use bitflags::bitflags;
+use log::warn;
+
+pub use vk::{
+    PipelineExecutableInternalRepresentation, PipelineExecutableStatistic,
+    PipelineExecutableStatisticValue,
+};
 
 bitflags! {
     #[repr(transparent)]
     pub struct PipelineStage: u32 {
         const TOP_OF_PIPE = 0x00000001;
+        const DRAW_INDIRECT = 0x00000002;
+        const VERTEX_INPUT = 0x00000004;
         const FRAGMENT_SHADER = 0x00000080;
         const EARLY_FRAGMENT_TESTS = 0x00000100;
         const LATE_FRAGMENT_TESTS = 0x00000200;
@@ -16,6 +26,7 @@ bitflags! {
         const COMPUTE_SHADER = 0x00000800;
         const TRANSFER = 0x00001000;
         const BOTTOM_OF_PIPE = 0x00002000;
+        const HOST = 0x00004000;
     }
 }
 
@@ -39,7 +50,30 @@ impl CullMode {
     }
 }
 
-#[derive(Clone, Copy)]
+bitflags! {
+    #[repr(transparent)]
+    pub struct ColorComponents: u32 {
+        const R = vk::COLOR_COMPONENT_R;
+        const G = vk::COLOR_COMPONENT_G;
+        const B = vk::COLOR_COMPONENT_B;
+        const A = vk::COLOR_COMPONENT_A;
+    }
+}
+
+//An empty mask writes nothing, which looks like a black attachment rather than an error
+impl Default for ColorComponents {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl ColorComponents {
+    pub fn to_vk(self) -> u32 {
+        self.bits()
+    }
+}
+
+#[derive(Clone, Copy, Hash)]
 pub enum CompareOp {
     Never,
     Less,
@@ -66,29 +100,239 @@ impl From<CompareOp> for vk::CompareOp {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Hash)]
+pub enum StencilOp {
+    Keep,
+    Zero,
+    Replace,
+    IncrementAndClamp,
+    DecrementAndClamp,
+    Invert,
+    IncrementAndWrap,
+    DecrementAndWrap,
+}
+
+impl From<StencilOp> for vk::StencilOp {
+    fn from(op: StencilOp) -> Self {
+        match op {
+            StencilOp::Keep => Self::Keep,
+            StencilOp::Zero => Self::Zero,
+            StencilOp::Replace => Self::Replace,
+            StencilOp::IncrementAndClamp => Self::IncrementAndClamp,
+            StencilOp::DecrementAndClamp => Self::DecrementAndClamp,
+            StencilOp::Invert => Self::Invert,
+            StencilOp::IncrementAndWrap => Self::IncrementAndWrap,
+            StencilOp::DecrementAndWrap => Self::DecrementAndWrap,
+        }
+    }
+}
+
+//What happens to the stencil value of a fragment, by whether it failed the stencil test,
+//passed both tests or only failed the depth test
+#[derive(Clone, Copy, Hash)]
+pub struct StencilOpState {
+    pub fail_op: StencilOp,
+    pub pass_op: StencilOp,
+    pub depth_fail_op: StencilOp,
+    pub compare_op: CompareOp,
+    pub compare_mask: u32,
+    pub write_mask: u32,
+    pub reference: u32,
+}
+
+impl StencilOpState {
+    //Passes where the stencil equals reference, e.g. drawing only inside a portal
+    pub fn equal(reference: u32) -> Self {
+        Self {
+            fail_op: StencilOp::Keep,
+            pass_op: StencilOp::Keep,
+            depth_fail_op: StencilOp::Keep,
+            compare_op: CompareOp::Equal,
+            compare_mask: 0xff,
+            write_mask: 0,
+            reference,
+        }
+    }
+
+    //Writes reference wherever the fragment passes the depth test, e.g. masking an outline
+    pub fn replace(reference: u32) -> Self {
+        Self {
+            fail_op: StencilOp::Keep,
+            pass_op: StencilOp::Replace,
+            depth_fail_op: StencilOp::Keep,
+            compare_op: CompareOp::Always,
+            compare_mask: 0xff,
+            write_mask: 0xff,
+            reference,
+        }
+    }
+}
+
+impl From<StencilOpState> for vk::StencilOpState {
+    fn from(state: StencilOpState) -> Self {
+        Self {
+            fail_op: state.fail_op.into(),
+            pass_op: state.pass_op.into(),
+            depth_fail_op: state.depth_fail_op.into(),
+            compare_op: state.compare_op.into(),
+            compare_mask: state.compare_mask,
+            write_mask: state.write_mask,
+            reference: state.reference,
+        }
+    }
+}
+
+//Requires an attachment format with a stencil aspect, see Format::has_stencil
+#[derive(Clone, Copy, Hash)]
+pub struct Stencil {
+    pub front: StencilOpState,
+    pub back: StencilOpState,
+}
+
+impl Stencil {
+    pub fn both(state: StencilOpState) -> Self {
+        Self {
+            front: state,
+            back: state,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Hash)]
 pub struct DepthStencil {
     pub test: bool,
     pub write: bool,
     pub compare_op: CompareOp,
+    //None disables the stencil test
+    pub stencil: Option<Stencil>,
+}
+
+//Bounds outside of 0..=1 require Device::depth_range_unrestricted_supported
+#[derive(Clone, Copy)]
+pub struct DepthRange {
+    pub min: f32,
+    pub max: f32,
+    //clamp fragment depth to the range, requires Device::depth_clamp_supported
+    pub clamp: bool,
+    //clipping is normally the opposite of clamp, anything else requires Device::depth_clip_enable_supported
+    pub clip: bool,
+}
+
+impl Default for DepthRange {
+    fn default() -> Self {
+        Self {
+            min: 0.0,
+            max: 1.0,
+            clamp: false,
+            clip: true,
+        }
+    }
+}
+
+impl Hash for DepthRange {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.min.to_bits().hash(state);
+        self.max.to_bits().hash(state);
+        self.clamp.hash(state);
+        self.clip.hash(state);
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum BlendConstants {
+    Static([f32; 4]),
+    Dynamic,
+}
+
+impl Hash for BlendConstants {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Self::Static(constants) => {
+                0.hash(state);
+                constants.map(f32::to_bits).hash(state);
+            }
+            Self::Dynamic => 1.hash(state),
+        }
+    }
+}
+
+//Integer attachments such as object ids cannot be blended
+#[derive(Clone, Copy, Hash, Default)]
+pub enum ColorBlend {
+    Alpha,
+    #[default]
+    Disabled,
+}
+
+#[derive(Clone, Copy, Hash)]
+pub enum Scissor {
+    Static,
+    Dynamic,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineTopology {
+    List,
+    Strip,
+    StripWithAdjacency,
+}
+
+//Anything but Default requires the line rasterization extension, unsupported modes fall back to Default
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineMode {
+    Default,
+    Rectangular,
+    Bresenham,
+    Smooth,
+}
+
+#[derive(Clone, Copy)]
+pub enum Primitive {
+    Triangles,
+    //Strips are restarted by the maximum index value
+    Lines {
+        topology: LineTopology,
+        width: f32,
+        mode: LineMode,
+    },
+}
+
+impl Hash for Primitive {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Self::Triangles => 0.hash(state),
+            Self::Lines {
+                topology,
+                width,
+                mode,
+            } => {
+                1.hash(state);
+                topology.hash(state);
+                width.to_bits().hash(state);
+                mode.hash(state);
+            }
+        }
+    }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum InputRate {
     Vertex,
     Instance,
+    //advance once every n instances, see Device::vertex_attribute_divisor_supported
+    InstanceDivisor(u32),
 }
 
 impl From<InputRate> for vk::VertexInputRate {
     fn from(input_rate: InputRate) -> Self {
         match input_rate {
             InputRate::Vertex => Self::Vertex,
-            InputRate::Instance => Self::Instance,
+            InputRate::Instance | InputRate::InstanceDivisor(_) => Self::Instance,
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Hash)]
 pub struct VertexInput {
     pub binding: u32,
     pub location: u32,
@@ -96,12 +340,13 @@ pub struct VertexInput {
     pub rate: InputRate,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Hash)]
 pub enum DescriptorType {
     CombinedImageSampler,
     StorageImage,
     UniformBuffer,
     StorageBuffer,
+    UniformBufferDynamic,
 }
 
 impl From<DescriptorType> for vk::DescriptorType {
@@ -111,10 +356,12 @@ impl From<DescriptorType> for vk::DescriptorType {
             DescriptorType::StorageImage => Self::StorageImage,
             DescriptorType::UniformBuffer => Self::UniformBuffer,
             DescriptorType::StorageBuffer => Self::StorageBuffer,
+            DescriptorType::UniformBufferDynamic => Self::UniformBufferDynamic,
         }
     }
 }
 
+#[derive(Clone, Copy, Hash)]
 pub struct Descriptor {
     pub binding: u32,
     pub ty: DescriptorType,
@@ -122,6 +369,13 @@ pub struct Descriptor {
     pub stage: ShaderStage,
 }
 
+#[derive(Clone, Copy, Hash)]
+pub struct PushConstantRange {
+    pub stage: ShaderStage,
+    pub offset: u32,
+    pub size: u32,
+}
+
 #[derive(Clone, Copy)]
 pub enum Binding<'a> {
     Buffer {
@@ -139,9 +393,18 @@ pub enum Binding<'a> {
     },
 }
 
+//Attachment formats of a pipeline drawn between begin_rendering and end_rendering
+#[derive(Clone, Copy, Hash)]
+pub struct RenderingFormats<'a> {
+    pub color: &'a [Format],
+    pub depth: Option<Format>,
+}
+
 pub struct GraphicsPipelineInfo<'a> {
     pub device: &'a Device,
-    pub render_pass: &'a RenderPass,
+    //None with rendering set, see Device::dynamic_rendering_supported
+    pub render_pass: Option<&'a RenderPass>,
+    pub rendering: Option<RenderingFormats<'a>>,
     pub descriptor_set_count: u32,
     pub color_count: u32,
     pub subpass: u32,
@@ -150,17 +413,51 @@ pub struct GraphicsPipelineInfo<'a> {
     pub vertex_shader: &'a Shader,
     pub fragment_shader: Option<&'a Shader>,
     pub depth_stencil: DepthStencil,
+    pub blend_constants: BlendConstants,
+    pub color_blend: ColorBlend,
+    //components written to every color attachment, ColorComponents::default() for all
+    pub color_write_mask: ColorComponents,
+    //of every attachment drawn to, see Device::max_sample_count
+    pub samples: SampleCount,
+    pub scissor: Scissor,
+    pub primitive: Primitive,
+    pub depth_range: DepthRange,
     pub vertex_input: &'a [VertexInput],
     pub layout: &'a [Descriptor],
+    pub push_constants: &'a [PushConstantRange],
+    //bound at MATERIAL_TABLE_SET
+    pub material_table: Option<&'a MaterialTable>,
+    //bindings whose image sampler is baked into the layout, required for YcbcrTexture
+    pub immutable_samplers: &'a [(u32, &'a Image)],
+    //reuses compiled state from earlier runs or pipelines, see PipelineCache
+    pub cache: Option<&'a PipelineCache>,
+}
+
+pub struct ComputePipelineInfo<'a> {
+    pub device: &'a Device,
+    pub descriptor_set_count: u32,
+    pub shader: &'a Shader,
+    pub layout: &'a [Descriptor],
+    pub push_constants: &'a [PushConstantRange],
+    //bound at MATERIAL_TABLE_SET
+    pub material_table: Option<&'a MaterialTable>,
+    //bindings whose image sampler is baked into the layout, required for YcbcrTexture
+    pub immutable_samplers: &'a [(u32, &'a Image)],
+    //reuses compiled state from earlier runs or pipelines, see PipelineCache
+    pub cache: Option<&'a PipelineCache>,
 }
 
 pub enum Pipeline {
     Vulkan {
+        device: Rc<vk::Device>,
         descriptor_sets: Vec<vk::DescriptorSet>,
         descriptor_set_layout: vk::DescriptorSetLayout,
         pipeline: vk::Pipeline,
         pipeline_layout: vk::PipelineLayout,
         bind_point: vk::PipelineBindPoint,
+        //UniformBufferDynamic in the layout, Commands::bind_pipeline leaves the set to
+        //Commands::bind_descriptor_sets and its offsets
+        dynamic_descriptors: bool,
     },
 }
 
@@ -172,51 +469,42 @@ impl Pipeline {
                 descriptor_pool,
                 ..
             } => {
-                let bindings = info
-                    .layout
-                    .iter()
-                    .map(|descriptor| vk::DescriptorSetLayoutBinding {
-                        binding: descriptor.binding,
-                        descriptor_type: descriptor.ty.into(),
-                        descriptor_count: descriptor.count,
-                        stage: descriptor.stage.to_vk(),
-                    })
-                    .collect::<Vec<_>>();
-
-                let descriptor_set_layout_create_info = vk::DescriptorSetLayoutCreateInfo {
-                    bindings: &bindings,
-                };
-
-                let descriptor_set_layout =
-                    vk::DescriptorSetLayout::new(device.clone(), descriptor_set_layout_create_info)
-                        .expect("failed to create descriptor set layout");
-
-                let set_layouts = iter::repeat(&descriptor_set_layout)
-                    .take(info.descriptor_set_count as _)
-                    .collect::<Vec<_>>();
-
-                let descriptor_set_allocate_info = vk::DescriptorSetAllocateInfo {
-                    descriptor_pool: &descriptor_pool,
-                    set_layouts: &set_layouts,
-                };
+                let (descriptor_set_layout, descriptor_sets, pipeline_layout) =
+                    Self::new_vk_layout(
+                        device.clone(),
+                        descriptor_pool,
+                        info.layout,
+                        info.descriptor_set_count,
+                        info.push_constants,
+                        info.material_table,
+                        info.immutable_samplers,
+                    );
+
+                let render_pass = info.render_pass.map(|render_pass| {
+                    let RenderPass::Vulkan { render_pass } = render_pass else {
+                        panic!("not a vulkan render pass");
+                    };
 
-                let descriptor_sets =
-                    vk::DescriptorSet::allocate(device.clone(), descriptor_set_allocate_info)
-                        .expect("failed to allocate descriptor sets");
+                    render_pass
+                });
 
-                let pipeline_layout_create_info = vk::PipelineLayoutCreateInfo {
-                    set_layouts: &[&descriptor_set_layout],
-                };
+                if render_pass.is_none() && info.rendering.is_none() {
+                    panic!("graphics pipeline needs a render pass or rendering formats");
+                }
 
-                let pipeline_layout =
-                    vk::PipelineLayout::new(device.clone(), pipeline_layout_create_info)
-                        .expect("failed to create pipeline layout");
+                let color_formats = info.rendering.map_or(vec![], |rendering| {
+                    rendering
+                        .color
+                        .iter()
+                        .map(|&format| format.into())
+                        .collect::<Vec<_>>()
+                });
 
-                let render_pass = if let RenderPass::Vulkan { render_pass } = info.render_pass {
-                    render_pass
-                } else {
-                    panic!("not a vulkan surface");
-                };
+                let rendering = info.rendering.map(|rendering| vk::PipelineRenderingCreateInfo {
+                    color_attachment_formats: &color_formats,
+                    depth_attachment_format: rendering.depth.map(Into::into),
+                    stencil_attachment_format: None,
+                });
 
                 let mut stages = vec![];
 
@@ -266,6 +554,7 @@ impl Pipeline {
 
                 let mut bindings = vec![];
                 let mut attributes = vec![];
+                let mut binding_divisors = vec![];
 
                 for (&binding, input) in &mut split {
                     input.sort_by(|a, b| a.location.cmp(&b.location));
@@ -289,6 +578,17 @@ impl Pipeline {
 
                     bindings.push(vertex_binding);
 
+                    if let InputRate::InstanceDivisor(divisor) = input[0].rate {
+                        if !device.is_extension_enabled(vk::EXT_VERTEX_ATTRIBUTE_DIVISOR) {
+                            panic!("vertex attribute divisor is not supported");
+                        }
+
+                        binding_divisors.push(vk::VertexInputBindingDivisorDescription {
+                            binding,
+                            divisor,
+                        });
+                    }
+
                     for (x, i) in input.iter().enumerate() {
                         let location = i.location;
 
@@ -314,22 +614,55 @@ impl Pipeline {
                 let vertex_input_info = vk::PipelineVertexInputStateCreateInfo {
                     bindings: &bindings,
                     attributes: &attributes,
+                    binding_divisors: &binding_divisors,
                 };
 
+                let (topology, primitive_restart_enable, line_width, line_rasterization_mode) =
+                    match info.primitive {
+                        Primitive::Triangles => (
+                            vk::PrimitiveTopology::TriangleList,
+                            false,
+                            1.0,
+                            vk::LineRasterizationMode::Default,
+                        ),
+                        Primitive::Lines {
+                            topology,
+                            width,
+                            mode,
+                        } => {
+                            let (topology, primitive_restart_enable) = match topology {
+                                LineTopology::List => (vk::PrimitiveTopology::LineList, false),
+                                LineTopology::Strip => (vk::PrimitiveTopology::LineStrip, true),
+                                LineTopology::StripWithAdjacency => {
+                                    (vk::PrimitiveTopology::LineStripWithAdjacency, true)
+                                }
+                            };
+
+                            (
+                                topology,
+                                primitive_restart_enable,
+                                Self::vk_line_width(device, width),
+                                Self::vk_line_rasterization_mode(device, mode),
+                            )
+                        }
+                    };
+
                 let input_assembly = vk::PipelineInputAssemblyStateCreateInfo {
-                    topology: vk::PrimitiveTopology::TriangleList,
-                    primitive_restart_enable: false,
+                    topology,
+                    primitive_restart_enable,
                 };
 
                 let tessellation_state = vk::PipelineTessellationStateCreateInfo {};
 
+                let depth_clip_enable = Self::vk_depth_clip_enable(device, info.depth_range);
+
                 let viewport = vk::Viewport {
                     x: 0.0,
                     y: 0.0,
                     width: info.extent.0 as f32,
                     height: info.extent.1 as f32,
-                    min_depth: 0.0,
-                    max_depth: 1.0,
+                    min_depth: info.depth_range.min,
+                    max_depth: info.depth_range.max,
                 };
 
                 let scissor = vk::Rect2d {
@@ -343,7 +676,8 @@ impl Pipeline {
                 };
 
                 let rasterizer = vk::PipelineRasterizationStateCreateInfo {
-                    depth_clamp_enable: false,
+                    depth_clamp_enable: info.depth_range.clamp,
+                    depth_clip_enable,
                     rasterizer_discard_enable: false,
                     polygon_mode: vk::PolygonMode::Fill,
                     cull_mode: info.cull_mode.to_vk(),
@@ -352,27 +686,41 @@ impl Pipeline {
                     depth_bias_constant_factor: 0.0,
                     depth_bias_clamp: 0.0,
                     depth_bias_slope_factor: 0.0,
-                    line_width: 1.0,
+                    line_width,
+                    line_rasterization_mode,
                 };
 
-                let multisampling = vk::PipelineMultisampleStateCreateInfo {};
+                let multisampling = vk::PipelineMultisampleStateCreateInfo {
+                    rasterization_samples: info.samples,
+                    ..Default::default()
+                };
 
                 let depth_stencil = vk::PipelineDepthStencilStateCreateInfo {
                     depth_test_enable: info.depth_stencil.test,
                     depth_write_enable: info.depth_stencil.write,
                     depth_compare_op: info.depth_stencil.compare_op.into(),
                     depth_bounds_test_enable: false,
+                    stencil_test_enable: info.depth_stencil.stencil.is_some(),
+                    front: info
+                        .depth_stencil
+                        .stencil
+                        .map_or(Default::default(), |stencil| stencil.front.into()),
+                    back: info
+                        .depth_stencil
+                        .stencil
+                        .map_or(Default::default(), |stencil| stencil.back.into()),
                     min_depth_bounds: 0.0,
                     max_depth_bounds: 1.0,
                 };
 
+                if info.color_count > 0 && info.color_write_mask.is_empty() {
+                    warn!("color write mask is empty, the color attachments will not be written");
+                }
+
                 let color_blend_attachments = (0..info.color_count)
                     .map(|_| vk::PipelineColorBlendAttachmentState {
-                        color_write_mask: vk::COLOR_COMPONENT_R
-                            | vk::COLOR_COMPONENT_G
-                            | vk::COLOR_COMPONENT_B
-                            | vk::COLOR_COMPONENT_A,
-                        blend_enable: true,
+                        color_write_mask: info.color_write_mask.to_vk(),
+                        blend_enable: matches!(info.color_blend, ColorBlend::Alpha),
                         src_color_blend_factor: vk::BlendFactor::SrcAlpha,
                         dst_color_blend_factor: vk::BlendFactor::OneMinusSrcAlpha,
                         color_blend_op: vk::BlendOp::Add,
@@ -382,15 +730,26 @@ impl Pipeline {
                     })
                     .collect::<Vec<_>>();
 
+                let (blend_constants, mut dynamic_states) = match info.blend_constants {
+                    BlendConstants::Static(blend_constants) => (blend_constants, vec![]),
+                    BlendConstants::Dynamic => {
+                        ([0.0, 0.0, 0.0, 0.0], vec![vk::DynamicState::BlendConstants])
+                    }
+                };
+
+                if let Scissor::Dynamic = info.scissor {
+                    dynamic_states.push(vk::DynamicState::Scissor);
+                }
+
                 let color_blending = vk::PipelineColorBlendStateCreateInfo {
                     logic_op_enable: false,
                     logic_op: vk::LogicOp::Copy,
                     attachments: &color_blend_attachments[..],
-                    blend_constants: &[0.0, 0.0, 0.0, 0.0],
+                    blend_constants: &blend_constants,
                 };
 
                 let dynamic_state = vk::PipelineDynamicStateCreateInfo {
-                    dynamic_states: &[],
+                    dynamic_states: &dynamic_states,
                 };
 
                 let present_pipeline_create_info = vk::GraphicsPipelineCreateInfo {
@@ -405,35 +764,261 @@ impl Pipeline {
                     color_blend_state: &color_blending,
                     dynamic_state: &dynamic_state,
                     layout: &pipeline_layout,
-                    render_pass: &render_pass,
+                    render_pass,
                     subpass: info.subpass,
+                    rendering,
                     base_pipeline: None,
                     base_pipeline_index: -1,
                 };
 
                 let pipeline = vk::Pipeline::new_graphics_pipelines(
                     device.clone(),
-                    None,
+                    info.cache.map(PipelineCache::vk_pipeline_cache),
                     &[present_pipeline_create_info],
                 )
                 .expect("failed to create graphics pipeline")
                 .remove(0);
 
                 Self::Vulkan {
+                    device: device.clone(),
                     descriptor_sets,
                     descriptor_set_layout,
                     pipeline,
                     pipeline_layout,
                     bind_point: vk::PipelineBindPoint::Graphics,
+                    dynamic_descriptors: Self::has_dynamic_descriptors(info.layout),
+                }
+            }
+        }
+    }
+
+    pub fn new_compute_pipeline(info: ComputePipelineInfo<'_>) -> Self {
+        match info.device {
+            Device::Vulkan {
+                device,
+                descriptor_pool,
+                ..
+            } => {
+                let (descriptor_set_layout, descriptor_sets, pipeline_layout) =
+                    Self::new_vk_layout(
+                        device.clone(),
+                        descriptor_pool,
+                        info.layout,
+                        info.descriptor_set_count,
+                        info.push_constants,
+                        info.material_table,
+                        info.immutable_samplers,
+                    );
+
+                let Shader::Vulkan { shader_module, entry, .. } = info.shader else {
+                    panic!("not a vulkan shader");
+                };
+
+                let compute_pipeline_create_info = vk::ComputePipelineCreateInfo {
+                    stage: vk::PipelineShaderStageCreateInfo {
+                        stage: vk::SHADER_STAGE_COMPUTE,
+                        module: &shader_module,
+                        entry_point: &entry,
+                    },
+                    layout: &pipeline_layout,
+                    base_pipeline: None,
+                    base_pipeline_index: -1,
+                };
+
+                let pipeline = vk::Pipeline::new_compute_pipelines(
+                    device.clone(),
+                    info.cache.map(PipelineCache::vk_pipeline_cache),
+                    &[compute_pipeline_create_info],
+                )
+                .expect("failed to create compute pipeline")
+                .remove(0);
+
+                Self::Vulkan {
+                    device: device.clone(),
+                    descriptor_sets,
+                    descriptor_set_layout,
+                    pipeline,
+                    pipeline_layout,
+                    bind_point: vk::PipelineBindPoint::Compute,
+                    dynamic_descriptors: Self::has_dynamic_descriptors(info.layout),
                 }
             }
         }
     }
 
+    fn vk_depth_clip_enable(device: &vk::Device, depth_range: DepthRange) -> Option<bool> {
+        let DepthRange {
+            min,
+            max,
+            clamp,
+            clip,
+        } = depth_range;
+
+        if (!(0.0..=1.0).contains(&min) || !(0.0..=1.0).contains(&max))
+            && !device.is_extension_enabled(vk::EXT_DEPTH_RANGE_UNRESTRICTED)
+        {
+            panic!("unrestricted depth range is not supported");
+        }
+
+        if clamp && !device.enabled_features().depth_clamp {
+            panic!("depth clamp is not supported");
+        }
+
+        if clip != clamp {
+            return None;
+        }
+
+        if !device.is_extension_enabled(vk::EXT_DEPTH_CLIP_ENABLE) {
+            panic!("depth clip enable is not supported");
+        }
+
+        Some(clip)
+    }
+
+    fn vk_line_width(device: &vk::Device, width: f32) -> f32 {
+        if width == 1.0 {
+            return width;
+        }
+
+        if !device.enabled_features().wide_lines {
+            warn!("wide lines are not supported, falling back to a line width of 1.0");
+            return 1.0;
+        }
+
+        let [min, max] = device.limits().line_width_range;
+
+        if !(min..=max).contains(&width) {
+            warn!(
+                "line width {} is outside of the supported range {}..={}, falling back to 1.0",
+                width, min, max
+            );
+            return 1.0;
+        }
+
+        width
+    }
+
+    fn vk_line_rasterization_mode(device: &vk::Device, mode: LineMode) -> vk::LineRasterizationMode {
+        let features = device.line_rasterization_features();
+
+        let (supported, vk_mode) = match mode {
+            LineMode::Default => (true, vk::LineRasterizationMode::Default),
+            LineMode::Rectangular => (
+                features.rectangular_lines,
+                vk::LineRasterizationMode::Rectangular,
+            ),
+            LineMode::Bresenham => (
+                features.bresenham_lines,
+                vk::LineRasterizationMode::Bresenham,
+            ),
+            LineMode::Smooth => (
+                features.smooth_lines,
+                vk::LineRasterizationMode::RectangularSmooth,
+            ),
+        };
+
+        if !supported {
+            warn!("line rasterization mode {:?} is not supported, falling back to default", vk_mode);
+            return vk::LineRasterizationMode::Default;
+        }
+
+        vk_mode
+    }
+
+    fn has_dynamic_descriptors(layout: &'_ [Descriptor]) -> bool {
+        layout
+            .iter()
+            .any(|descriptor| matches!(descriptor.ty, DescriptorType::UniformBufferDynamic))
+    }
+
+    fn new_vk_layout(
+        device: Rc<vk::Device>,
+        descriptor_pool: &vk::DescriptorPool,
+        layout: &'_ [Descriptor],
+        descriptor_set_count: u32,
+        push_constants: &'_ [PushConstantRange],
+        material_table: Option<&'_ MaterialTable>,
+        immutable_samplers: &'_ [(u32, &'_ Image)],
+    ) -> (vk::DescriptorSetLayout, Vec<vk::DescriptorSet>, vk::PipelineLayout) {
+        let bindings = layout
+            .iter()
+            .map(|descriptor| vk::DescriptorSetLayoutBinding {
+                binding: descriptor.binding,
+                descriptor_type: descriptor.ty.into(),
+                descriptor_count: descriptor.count,
+                stage: descriptor.stage.to_vk(),
+            })
+            .collect::<Vec<_>>();
+
+        let immutable_samplers = immutable_samplers
+            .iter()
+            .map(|(binding, image)| {
+                let Image::Vulkan { sampler, .. } = image else {
+                    panic!("not a vulkan image");
+                };
+
+                (*binding, sampler)
+            })
+            .collect::<Vec<_>>();
+
+        let descriptor_set_layout_create_info = vk::DescriptorSetLayoutCreateInfo {
+            flags: 0,
+            bindings: &bindings,
+            binding_flags: &[],
+            immutable_samplers: &immutable_samplers,
+        };
+
+        let descriptor_set_layout =
+            vk::DescriptorSetLayout::new(device.clone(), descriptor_set_layout_create_info)
+                .expect("failed to create descriptor set layout");
+
+        let set_layouts = iter::repeat(&descriptor_set_layout)
+            .take(descriptor_set_count as _)
+            .collect::<Vec<_>>();
+
+        let descriptor_set_allocate_info = vk::DescriptorSetAllocateInfo {
+            descriptor_pool: &descriptor_pool,
+            set_layouts: &set_layouts,
+            variable_descriptor_counts: &[],
+        };
+
+        let descriptor_sets =
+            vk::DescriptorSet::allocate(device.clone(), descriptor_set_allocate_info)
+                .expect("failed to allocate descriptor sets");
+
+        let push_constant_ranges = push_constants
+            .iter()
+            .map(|range| vk::PushConstantRange {
+                stage_flags: range.stage.to_vk(),
+                offset: range.offset,
+                size: range.size,
+            })
+            .collect::<Vec<_>>();
+
+        let mut pipeline_set_layouts = vec![&descriptor_set_layout];
+
+        if let Some(material_table) = material_table {
+            pipeline_set_layouts.push(material_table.vk_descriptor_set_layout());
+        }
+
+        let pipeline_layout_create_info = vk::PipelineLayoutCreateInfo {
+            set_layouts: &pipeline_set_layouts,
+            push_constant_ranges: &push_constant_ranges,
+        };
+
+        let pipeline_layout =
+            vk::PipelineLayout::new(device.clone(), pipeline_layout_create_info)
+                .expect("failed to create pipeline layout");
+
+        (descriptor_set_layout, descriptor_sets, pipeline_layout)
+    }
+
     pub fn bind(&mut self, image_index: u32, bindings: &'_ [Binding]) {
         match self {
             Pipeline::Vulkan {
-                descriptor_sets, ..
+                device,
+                descriptor_sets,
+                ..
             } => {
                 let mut buffer_infos = vec![];
                 let mut image_infos = vec![];
@@ -524,8 +1109,66 @@ impl Pipeline {
                     write_descriptors.push(write_descriptor);
                 }
 
-                vk::DescriptorSet::update(&write_descriptors, &[]);
+                device
+                    .update_descriptor_sets(&write_descriptors, &[])
+                    .expect("failed to update descriptor sets");
             }
         }
     }
+
+    //Empty unless Device::pipeline_statistics_supported when the pipeline was created
+    pub fn executables(&self) -> Vec<PipelineExecutable> {
+        let Pipeline::Vulkan { pipeline, .. } = self;
+
+        let Ok(properties) = pipeline.executable_properties() else {
+            return vec![];
+        };
+
+        properties
+            .into_iter()
+            .enumerate()
+            .map(|(index, properties)| PipelineExecutable {
+                index: index as _,
+                stage: ShaderStage::from_bits_truncate(properties.stages),
+                name: properties.name,
+                description: properties.description,
+                subgroup_size: properties.subgroup_size,
+                statistics: pipeline
+                    .executable_statistics(index as _)
+                    .expect("failed to get pipeline executable statistics"),
+            })
+            .collect()
+    }
+
+    pub fn internal_representations(
+        &self,
+        executable: &PipelineExecutable,
+    ) -> Vec<PipelineExecutableInternalRepresentation> {
+        let Pipeline::Vulkan { pipeline, .. } = self;
+
+        pipeline
+            .executable_internal_representations(executable.index)
+            .unwrap_or_default()
+    }
+}
+
+//One compiled executable of a pipeline, usually one per shader stage
+#[derive(Clone, Debug)]
+pub struct PipelineExecutable {
+    pub index: u32,
+    pub stage: ShaderStage,
+    pub name: String,
+    pub description: String,
+    pub subgroup_size: u32,
+    pub statistics: Vec<PipelineExecutableStatistic>,
+}
+
+impl PipelineExecutable {
+    //Statistic names are driver specific, e.g. "VGPRs" or "Spilled Bytes"
+    pub fn statistic(&self, name: &str) -> Option<PipelineExecutableStatisticValue> {
+        self.statistics
+            .iter()
+            .find(|statistic| statistic.name.eq_ignore_ascii_case(name))
+            .map(|statistic| statistic.value)
+    }
 }
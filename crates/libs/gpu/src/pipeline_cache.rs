@@ -0,0 +1,241 @@
+use crate::prelude::*;
+
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+pub struct PipelineCacheInfo<'a> {
+    pub device: &'a Device,
+    //what data returned in an earlier run, empty to start cold
+    pub initial_data: &'a [u8],
+}
+
+//The driver's cache of compiled pipelines, passed to pipeline creation through the
+//cache field of the pipeline infos. Persist data on exit and hand it back to new so
+//shaders are not compiled again on the next run.
+pub enum PipelineCache {
+    Vulkan { cache: vk::PipelineCache },
+}
+
+impl PipelineCache {
+    pub fn new(info: PipelineCacheInfo<'_>) -> Self {
+        let Device::Vulkan { device, .. } = info.device else {
+            panic!("not a vulkan device");
+        };
+
+        let cache = vk::PipelineCache::new(device.clone(), info.initial_data)
+            .expect("failed to create pipeline cache");
+
+        Self::Vulkan { cache }
+    }
+
+    pub fn data(&self) -> Vec<u8> {
+        let Self::Vulkan { cache } = self;
+
+        cache.get_data().expect("failed to get pipeline cache data")
+    }
+
+    //Adds the contents of sources, e.g. one cache per thread creating pipelines
+    pub fn merge(&mut self, sources: &[&PipelineCache]) {
+        let Self::Vulkan { cache } = self;
+
+        let sources = sources
+            .iter()
+            .map(|source| source.vk_pipeline_cache())
+            .collect::<Vec<_>>();
+
+        cache
+            .merge(&sources)
+            .expect("failed to merge pipeline caches");
+    }
+
+    pub(crate) fn vk_pipeline_cache(&self) -> &vk::PipelineCache {
+        let Self::Vulkan { cache } = self;
+
+        cache
+    }
+}
+
+pub enum PipelineDescription<'a> {
+    Graphics(GraphicsPipelineInfo<'a>),
+    Compute(ComputePipelineInfo<'a>),
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct PipelineCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+//Pipelines requested by description. The key covers the identity of the shader modules,
+//render pass and material table plus all fixed function state, so a reloaded shader or a
+//recreated render pass produces a new pipeline while the old one stays cached until clear.
+pub struct PipelineStateCache {
+    pipelines: HashMap<u64, Pipeline>,
+    stats: PipelineCacheStats,
+}
+
+impl Default for PipelineStateCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PipelineStateCache {
+    pub fn new() -> Self {
+        Self {
+            pipelines: HashMap::new(),
+            stats: PipelineCacheStats::default(),
+        }
+    }
+
+    pub fn graphics(&mut self, info: GraphicsPipelineInfo<'_>) -> &mut Pipeline {
+        let key = Self::graphics_key(&info);
+
+        Self::get_or_insert(&mut self.pipelines, &mut self.stats, key, || {
+            Pipeline::new_graphics_pipeline(info)
+        })
+    }
+
+    pub fn compute(&mut self, info: ComputePipelineInfo<'_>) -> &mut Pipeline {
+        let key = Self::compute_key(&info);
+
+        Self::get_or_insert(&mut self.pipelines, &mut self.stats, key, || {
+            Pipeline::new_compute_pipeline(info)
+        })
+    }
+
+    //Creates the pipeline unless it is cached, without counting towards the stats since
+    //nothing is drawn with it yet. Returns whether it was created, see PipelineWarmup.
+    pub fn warm(&mut self, description: PipelineDescription<'_>) -> bool {
+        let key = match &description {
+            PipelineDescription::Graphics(info) => Self::graphics_key(info),
+            PipelineDescription::Compute(info) => Self::compute_key(info),
+        };
+
+        let Entry::Vacant(entry) = self.pipelines.entry(key) else {
+            return false;
+        };
+
+        entry.insert(match description {
+            PipelineDescription::Graphics(info) => Pipeline::new_graphics_pipeline(info),
+            PipelineDescription::Compute(info) => Pipeline::new_compute_pipeline(info),
+        });
+
+        true
+    }
+
+    pub fn stats(&self) -> PipelineCacheStats {
+        self.stats
+    }
+
+    pub fn len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+
+    //Must run after the frame fence wait since the pipelines are destroyed
+    pub fn clear(&mut self) {
+        self.pipelines.clear();
+    }
+
+    fn get_or_insert<'a>(
+        pipelines: &'a mut HashMap<u64, Pipeline>,
+        stats: &mut PipelineCacheStats,
+        key: u64,
+        create: impl FnOnce() -> Pipeline,
+    ) -> &'a mut Pipeline {
+        match pipelines.entry(key) {
+            Entry::Occupied(entry) => {
+                stats.hits += 1;
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => {
+                stats.misses += 1;
+                entry.insert(create())
+            }
+        }
+    }
+
+    fn graphics_key(info: &GraphicsPipelineInfo<'_>) -> u64 {
+        let mut state = DefaultHasher::new();
+
+        0.hash(&mut state);
+        Self::hash_device(info.device, &mut state);
+
+        info.render_pass.is_some().hash(&mut state);
+        if let Some(RenderPass::Vulkan { render_pass }) = info.render_pass {
+            render_pass.hash(&mut state);
+        }
+        info.rendering.hash(&mut state);
+
+        Self::hash_shader(info.vertex_shader, &mut state);
+        info.fragment_shader.is_some().hash(&mut state);
+        if let Some(shader) = info.fragment_shader {
+            Self::hash_shader(shader, &mut state);
+        }
+
+        info.descriptor_set_count.hash(&mut state);
+        info.color_count.hash(&mut state);
+        info.subpass.hash(&mut state);
+        info.extent.hash(&mut state);
+        info.cull_mode.hash(&mut state);
+        info.depth_stencil.hash(&mut state);
+        info.blend_constants.hash(&mut state);
+        info.color_blend.hash(&mut state);
+        info.color_write_mask.hash(&mut state);
+        info.samples.hash(&mut state);
+        info.scissor.hash(&mut state);
+        info.primitive.hash(&mut state);
+        info.depth_range.hash(&mut state);
+        info.vertex_input.hash(&mut state);
+        info.layout.hash(&mut state);
+        info.push_constants.hash(&mut state);
+        Self::hash_material_table(info.material_table, &mut state);
+
+        state.finish()
+    }
+
+    fn compute_key(info: &ComputePipelineInfo<'_>) -> u64 {
+        let mut state = DefaultHasher::new();
+
+        1.hash(&mut state);
+        Self::hash_device(info.device, &mut state);
+        Self::hash_shader(info.shader, &mut state);
+        info.descriptor_set_count.hash(&mut state);
+        info.layout.hash(&mut state);
+        info.push_constants.hash(&mut state);
+        Self::hash_material_table(info.material_table, &mut state);
+
+        state.finish()
+    }
+
+    fn hash_device(device: &Device, state: &mut DefaultHasher) {
+        let Device::Vulkan { device, .. } = device else {
+            panic!("not a vulkan device");
+        };
+
+        Rc::as_ptr(device).hash(state);
+    }
+
+    fn hash_shader(shader: &Shader, state: &mut DefaultHasher) {
+        let Shader::Vulkan {
+            shader_module,
+            entry,
+            ..
+        } = shader;
+
+        shader_module.hash(state);
+        entry.hash(state);
+    }
+
+    fn hash_material_table(material_table: Option<&MaterialTable>, state: &mut DefaultHasher) {
+        material_table
+            .map(MaterialTable::vk_descriptor_set_layout)
+            .hash(state);
+    }
+}
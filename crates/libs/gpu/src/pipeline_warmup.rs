@@ -0,0 +1,95 @@
+use crate::prelude::*;
+
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct WarmupProgress {
+    //created by the warm-up
+    pub compiled: usize,
+    //already in the PipelineStateCache
+    pub cached: usize,
+    pub total: usize,
+}
+
+impl WarmupProgress {
+    pub fn done(&self) -> usize {
+        self.compiled + self.cached
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+
+        self.done() as f32 / self.total as f32
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.done() >= self.total
+    }
+}
+
+//Compiles a known set of pipelines ahead of their first draw, e.g. the materials of the next
+//level, so gameplay does not hitch on them. The Device is thread affine, so instead of worker
+//threads the work is spread over frames: pump compiles until its budget runs out and the rest
+//waits for the next call. Descriptions are asked for by index every pump rather than stored,
+//which keeps the Device free to be borrowed mutably in between.
+pub struct PipelineWarmup {
+    next: usize,
+    progress: WarmupProgress,
+}
+
+impl PipelineWarmup {
+    pub fn new(count: usize) -> Self {
+        Self {
+            next: 0,
+            progress: WarmupProgress {
+                total: count,
+                ..Default::default()
+            },
+        }
+    }
+
+    //Compiles at least one pipeline, so a budget below the cost of any compile still advances
+    pub fn pump<'a>(
+        &mut self,
+        cache: &mut PipelineStateCache,
+        budget: Duration,
+        mut describe: impl FnMut(usize) -> PipelineDescription<'a>,
+    ) -> WarmupProgress {
+        let start = Instant::now();
+
+        while self.next < self.progress.total {
+            if cache.warm(describe(self.next)) {
+                self.progress.compiled += 1;
+            } else {
+                self.progress.cached += 1;
+            }
+
+            self.next += 1;
+
+            if start.elapsed() >= budget {
+                break;
+            }
+        }
+
+        self.progress
+    }
+
+    //Compiles everything left, e.g. behind a loading screen
+    pub fn finish<'a>(
+        &mut self,
+        cache: &mut PipelineStateCache,
+        describe: impl FnMut(usize) -> PipelineDescription<'a>,
+    ) -> WarmupProgress {
+        self.pump(cache, Duration::MAX, describe)
+    }
+
+    pub fn progress(&self) -> WarmupProgress {
+        self.progress
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.progress.is_complete()
+    }
+}
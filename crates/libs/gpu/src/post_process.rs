@@ -0,0 +1,152 @@
+use crate::prelude::*;
+
+pub struct PostProcessInfo<'a> {
+    pub device: &'a Device,
+    pub vertex_shader: &'a Shader,
+    pub fragment_shader: &'a Shader,
+    pub format: Format,
+    pub extent: (u32, u32),
+    pub descriptor_set_count: u32,
+    pub push_constant_size: u32,
+    pub layout: &'a [Descriptor],
+}
+
+pub struct PostProcessPass {
+    pipeline: Pipeline,
+    render_pass: RenderPass,
+}
+
+impl PostProcessPass {
+    pub fn new(info: PostProcessInfo<'_>) -> Self {
+        let render_pass = RenderPass::new(RenderPassInfo {
+            device: info.device,
+            attachments: &[Attachment {
+                format: info.format,
+                samples: SampleCount::One,
+                load_op: AttachmentLoadOp::DontCare,
+                store_op: AttachmentStoreOp::Store,
+                initial_layout: ImageLayout::Undefined,
+                final_layout: ImageLayout::ShaderReadOnly,
+                layout: ImageLayout::ColorAttachment,
+                ty: AttachmentType::Color,
+            }],
+            subpasses: &[Subpass {
+                src: None,
+                src_access: Access::empty(),
+                src_stage: PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+                dst: Some(0),
+                dst_access: Access::COLOR_ATTACHMENT_WRITE,
+                dst_stage: PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+                attachments: &[0],
+            }],
+        });
+
+        let push_constants = if info.push_constant_size > 0 {
+            vec![PushConstantRange {
+                stage: ShaderStage::FRAGMENT,
+                offset: 0,
+                size: info.push_constant_size,
+            }]
+        } else {
+            vec![]
+        };
+
+        //binding 0 is always the input image, extra descriptors follow it
+        let mut layout = vec![Descriptor {
+            binding: 0,
+            ty: DescriptorType::CombinedImageSampler,
+            count: 1,
+            stage: ShaderStage::FRAGMENT,
+        }];
+
+        layout.extend(info.layout.iter().cloned());
+
+        let pipeline = Pipeline::new_graphics_pipeline(GraphicsPipelineInfo {
+            device: info.device,
+            render_pass: Some(&render_pass),
+            rendering: None,
+            descriptor_set_count: info.descriptor_set_count,
+            color_count: 1,
+            subpass: 0,
+            extent: info.extent,
+            cull_mode: CullMode::empty(),
+            vertex_shader: info.vertex_shader,
+            fragment_shader: Some(info.fragment_shader),
+            depth_stencil: DepthStencil {
+                test: false,
+                write: false,
+                compare_op: CompareOp::Always,
+                stencil: None,
+            },
+            blend_constants: BlendConstants::Static([0.0, 0.0, 0.0, 0.0]),
+            color_blend: ColorBlend::Alpha,
+            color_write_mask: ColorComponents::default(),
+            samples: SampleCount::One,
+            scissor: Scissor::Static,
+            primitive: Primitive::Triangles,
+            depth_range: DepthRange::default(),
+            vertex_input: &[],
+            layout: &layout,
+            push_constants: &push_constants,
+            material_table: None,
+            immutable_samplers: &[],
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            render_pass,
+        }
+    }
+
+    pub fn render_pass(&self) -> &RenderPass {
+        &self.render_pass
+    }
+
+    pub fn pipeline(&self) -> &Pipeline {
+        &self.pipeline
+    }
+
+    pub fn record(
+        &mut self,
+        commands: &mut Commands<'_>,
+        image_index: u32,
+        input: &Image,
+        output: &Framebuffer,
+        bindings: &[Binding<'_>],
+        push_constants: &[u8],
+    ) -> Result<(), Error> {
+        self.pipeline.bind(
+            image_index,
+            &[Binding::Image {
+                binding: 0,
+                ty: DescriptorType::CombinedImageSampler,
+                layout: ImageLayout::ShaderReadOnly,
+                image: input,
+            }],
+        );
+
+        if bindings.len() > 0 {
+            self.pipeline.bind(image_index, bindings);
+        }
+
+        commands.begin_render_pass(RenderPassBeginInfo {
+            render_pass: &self.render_pass,
+            framebuffer: output,
+            color_clear_values: &[[0.0, 0.0, 0.0, 1.0]],
+            depth_stencil_clear_value: None,
+        })?;
+
+        commands.bind_pipeline(image_index, &self.pipeline);
+
+        if push_constants.len() > 0 {
+            commands.push_constants(&self.pipeline, ShaderStage::FRAGMENT, 0, push_constants);
+        }
+
+        commands.draw(3, 1, 0, 0);
+
+        commands.end_render_pass();
+
+        Ok(())
+    }
+}
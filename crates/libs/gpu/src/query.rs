@@ -0,0 +1,96 @@
+use crate::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QueryKind {
+    //samples passing the depth and stencil tests between begin_query and end_query
+    Occlusion,
+    Timestamp,
+}
+
+pub struct QueryPoolInfo<'a> {
+    pub device: &'a Device,
+    pub kind: QueryKind,
+    pub count: u32,
+}
+
+pub enum QueryPool {
+    Vulkan {
+        query_pool: vk::QueryPool,
+        kind: QueryKind,
+        count: u32,
+        timestamp_period: f32,
+    },
+}
+
+//Timestamps for profiling passes and occlusion queries for culling. Queries must be
+//reset outside a render pass before each use, results are read on the cpu once the
+//commands writing them have finished.
+impl QueryPool {
+    pub fn new(info: QueryPoolInfo<'_>) -> Self {
+        let Device::Vulkan { device, .. } = info.device else {
+            panic!("not a vulkan device");
+        };
+
+        let query_type = match info.kind {
+            QueryKind::Occlusion => vk::QueryType::Occlusion,
+            QueryKind::Timestamp => vk::QueryType::Timestamp,
+        };
+
+        let query_pool = vk::QueryPool::new(
+            device.clone(),
+            vk::QueryPoolCreateInfo {
+                query_type,
+                query_count: info.count,
+                performance: None,
+            },
+        )
+        .expect("failed to create query pool");
+
+        Self::Vulkan {
+            query_pool,
+            kind: info.kind,
+            count: info.count,
+            timestamp_period: device.limits().timestamp_period,
+        }
+    }
+
+    pub fn kind(&self) -> QueryKind {
+        let Self::Vulkan { kind, .. } = self;
+
+        *kind
+    }
+
+    pub fn count(&self) -> u32 {
+        let Self::Vulkan { count, .. } = self;
+
+        *count
+    }
+
+    //Results of count queries from first, None while any of them is still pending
+    pub fn results(&self, first: u32, count: u32) -> Option<Vec<u64>> {
+        let Self::Vulkan { query_pool, .. } = self;
+
+        query_pool
+            .get_results::<u64>(first, count, 0)
+            .expect("failed to get query results")
+    }
+
+    //Like results but blocks until the gpu has written them
+    pub fn wait_results(&self, first: u32, count: u32) -> Vec<u64> {
+        let Self::Vulkan { query_pool, .. } = self;
+
+        query_pool
+            .get_results::<u64>(first, count, vk::QUERY_RESULT_WAIT)
+            .expect("failed to get query results")
+            .expect("query results not available after waiting")
+    }
+
+    //Milliseconds between two timestamp results, end written after start
+    pub fn elapsed_ms(&self, start: u64, end: u64) -> f64 {
+        let Self::Vulkan {
+            timestamp_period, ..
+        } = self;
+
+        end.wrapping_sub(start) as f64 * *timestamp_period as f64 / 1_000_000.0
+    }
+}
@@ -0,0 +1,454 @@
+use crate::prelude::*;
+
+use std::marker::PhantomData;
+
+//Index plus the generation of the slot when the resource was inserted. Freeing a
+//resource bumps its slot's generation, so handles to it go stale instead of
+//aliasing whatever reuses the slot.
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> std::hash::Hash for Handle<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> std::fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handle({}v{})", self.index, self.generation)
+    }
+}
+
+pub type BufferHandle = Handle<Buffer>;
+pub type ImageHandle = Handle<Image>;
+
+struct Slot<T> {
+    generation: u32,
+    //bumped when the resource is replaced in place, handles stay valid
+    revision: u32,
+    entry: Option<(String, T)>,
+}
+
+struct Slots<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+}
+
+impl<T> Slots<T> {
+    fn new() -> Self {
+        Self {
+            slots: vec![],
+            free: vec![],
+        }
+    }
+
+    fn insert(&mut self, label: &str, value: T) -> Handle<T> {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    revision: 0,
+                    entry: None,
+                });
+
+                self.slots.len() as u32 - 1
+            }
+        };
+
+        let slot = &mut self.slots[index as usize];
+
+        slot.entry = Some((label.to_owned(), value));
+
+        Handle {
+            index,
+            generation: slot.generation,
+            marker: PhantomData,
+        }
+    }
+
+    fn get(&self, handle: Handle<T>) -> Result<&T, Error> {
+        match self.slots.get(handle.index as usize) {
+            Some(Slot {
+                generation,
+                entry: Some((_, value)),
+                ..
+            }) if *generation == handle.generation => Ok(value),
+            _ => Err(Error::StaleHandle),
+        }
+    }
+
+    fn get_mut(&mut self, handle: Handle<T>) -> Result<&mut T, Error> {
+        match self.slots.get_mut(handle.index as usize) {
+            Some(Slot {
+                generation,
+                entry: Some((_, value)),
+                ..
+            }) if *generation == handle.generation => Ok(value),
+            _ => Err(Error::StaleHandle),
+        }
+    }
+
+    fn replace(&mut self, handle: Handle<T>, value: T) -> Result<T, Error> {
+        let slot = self
+            .slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation && slot.entry.is_some())
+            .ok_or(Error::StaleHandle)?;
+
+        let (_, old) = slot.entry.as_mut().unwrap();
+
+        slot.revision = slot.revision.wrapping_add(1);
+
+        Ok(std::mem::replace(old, value))
+    }
+
+    fn revision(&self, handle: Handle<T>) -> Result<u32, Error> {
+        match self.slots.get(handle.index as usize) {
+            Some(slot) if slot.generation == handle.generation && slot.entry.is_some() => {
+                Ok(slot.revision)
+            }
+            _ => Err(Error::StaleHandle),
+        }
+    }
+
+    fn remove(&mut self, handle: Handle<T>) -> Result<T, Error> {
+        let slot = self
+            .slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation && slot.entry.is_some())
+            .ok_or(Error::StaleHandle)?;
+
+        let (_, value) = slot.entry.take().unwrap();
+
+        slot.generation = slot.generation.wrapping_add(1);
+
+        self.free.push(handle.index);
+
+        Ok(value)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (Handle<T>, &str, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let (label, value) = slot.entry.as_ref()?;
+
+            let handle = Handle {
+                index: index as u32,
+                generation: slot.generation,
+                marker: PhantomData,
+            };
+
+            Some((handle, label.as_str(), value))
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+}
+
+//Owns buffers and images behind handles that can be copied around freely.
+//A handle used after its resource was removed returns Error::StaleHandle.
+pub struct ResourceRegistry {
+    buffers: Slots<Buffer>,
+    images: Slots<Image>,
+}
+
+impl Default for ResourceRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResourceRegistry {
+    pub fn new() -> Self {
+        Self {
+            buffers: Slots::new(),
+            images: Slots::new(),
+        }
+    }
+
+    //label names the buffer when iterating live resources
+    pub fn insert_buffer(&mut self, label: &str, buffer: Buffer) -> BufferHandle {
+        self.buffers.insert(label, buffer)
+    }
+
+    pub fn buffer(&self, handle: BufferHandle) -> Result<&Buffer, Error> {
+        self.buffers.get(handle)
+    }
+
+    pub fn buffer_mut(&mut self, handle: BufferHandle) -> Result<&mut Buffer, Error> {
+        self.buffers.get_mut(handle)
+    }
+
+    //Must run after the frame fence wait if the buffer is dropped, like any other buffer
+    pub fn remove_buffer(&mut self, handle: BufferHandle) -> Result<Buffer, Error> {
+        self.buffers.remove(handle)
+    }
+
+    //Swaps in a new buffer behind the same handle, e.g. a larger one, and returns the old
+    //one to retire. Bindings made through RegistryBindings pick the new one up on refresh.
+    pub fn replace_buffer(&mut self, handle: BufferHandle, buffer: Buffer) -> Result<Buffer, Error> {
+        self.buffers.replace(handle, buffer)
+    }
+
+    //Changes whenever replace_buffer swaps the buffer behind handle
+    pub fn buffer_revision(&self, handle: BufferHandle) -> Result<u32, Error> {
+        self.buffers.revision(handle)
+    }
+
+    pub fn insert_image(&mut self, label: &str, image: Image) -> ImageHandle {
+        self.images.insert(label, image)
+    }
+
+    pub fn image(&self, handle: ImageHandle) -> Result<&Image, Error> {
+        self.images.get(handle)
+    }
+
+    pub fn image_mut(&mut self, handle: ImageHandle) -> Result<&mut Image, Error> {
+        self.images.get_mut(handle)
+    }
+
+    pub fn remove_image(&mut self, handle: ImageHandle) -> Result<Image, Error> {
+        self.images.remove(handle)
+    }
+
+    //Live buffers with their labels, e.g. for a leak tracking overlay
+    pub fn buffers(&self) -> impl Iterator<Item = (BufferHandle, &str, &Buffer)> {
+        self.buffers.iter()
+    }
+
+    pub fn images(&self) -> impl Iterator<Item = (ImageHandle, &str, &Image)> {
+        self.images.iter()
+    }
+
+    //One block per dedicated allocation, images bound to memory owned elsewhere are left out
+    #[cfg(feature = "memory-dump")]
+    pub fn dump(&self, name: &str) -> PoolDump {
+        let buffers = self.buffers().map(|(_, label, buffer)| {
+            let Buffer::Vulkan { memory, size, .. } = buffer else {
+                panic!("not a vulkan buffer");
+            };
+
+            (label, memory.size(), *size as u64)
+        });
+
+        let images = self.images().filter_map(|(_, label, image)| {
+            let Image::Vulkan { memory, .. } = image else {
+                panic!("not a vulkan image");
+            };
+
+            memory
+                .as_ref()
+                .map(|memory| (label, memory.size(), memory.size()))
+        });
+
+        let blocks = buffers
+            .chain(images)
+            .map(|(label, block_size, size)| BlockDump {
+                name: label.to_owned(),
+                size: block_size,
+                allocations: vec![AllocationDump {
+                    name: label.to_owned(),
+                    offset: 0,
+                    size,
+                }],
+            })
+            .collect();
+
+        PoolDump {
+            name: name.to_owned(),
+            blocks,
+        }
+    }
+
+    pub fn buffer_count(&self) -> usize {
+        self.buffers.len()
+    }
+
+    pub fn image_count(&self) -> usize {
+        self.images.len()
+    }
+}
+
+//A buffer descriptor of a registry buffer, covering it from offset to its end
+#[derive(Clone, Copy)]
+pub struct RegistryBinding {
+    //the descriptor set, as passed to Pipeline::bind
+    pub set: u32,
+    pub binding: u32,
+    pub ty: DescriptorType,
+    pub buffer: BufferHandle,
+    pub offset: usize,
+}
+
+//Descriptor writes of one pipeline that name registry buffers. Writes are remembered
+//with the revision they were made at, so refresh rewrites exactly the descriptors whose
+//buffer was replaced since, e.g. by a GrowableGpuVec growing.
+#[derive(Default)]
+pub struct RegistryBindings {
+    bindings: Vec<(RegistryBinding, u32)>,
+}
+
+impl RegistryBindings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //Writes the descriptor now and tracks it, replacing an earlier binding of the same
+    //set and binding
+    pub fn bind(
+        &mut self,
+        pipeline: &mut Pipeline,
+        registry: &ResourceRegistry,
+        binding: RegistryBinding,
+    ) -> Result<(), Error> {
+        let revision = Self::write(pipeline, registry, &binding)?;
+
+        self.bindings
+            .retain(|(bound, _)| (bound.set, bound.binding) != (binding.set, binding.binding));
+
+        self.bindings.push((binding, revision));
+
+        Ok(())
+    }
+
+    //Rewrites the descriptors whose buffer was replaced, returns how many. Like any
+    //descriptor write the sets must not be in use by a pending frame.
+    pub fn refresh(
+        &mut self,
+        pipeline: &mut Pipeline,
+        registry: &ResourceRegistry,
+    ) -> Result<usize, Error> {
+        let mut count = 0;
+
+        for (binding, revision) in &mut self.bindings {
+            if registry.buffer_revision(binding.buffer)? == *revision {
+                continue;
+            }
+
+            *revision = Self::write(pipeline, registry, binding)?;
+
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    fn write(
+        pipeline: &mut Pipeline,
+        registry: &ResourceRegistry,
+        binding: &RegistryBinding,
+    ) -> Result<u32, Error> {
+        let buffer = registry.buffer(binding.buffer)?;
+
+        pipeline.bind(
+            binding.set,
+            &[Binding::Buffer {
+                binding: binding.binding,
+                ty: binding.ty,
+                offset: binding.offset,
+                range: buffer.size() - binding.offset,
+                buffer,
+            }],
+        );
+
+        registry.buffer_revision(binding.buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inserted_value_is_found() {
+        let mut slots = Slots::new();
+
+        let a = slots.insert("a", 1);
+        let b = slots.insert("b", 2);
+
+        assert_eq!(*slots.get(a).unwrap(), 1);
+        assert_eq!(*slots.get(b).unwrap(), 2);
+        assert_eq!(slots.len(), 2);
+    }
+
+    #[test]
+    fn removed_handle_is_stale() {
+        let mut slots = Slots::new();
+
+        let a = slots.insert("a", 1);
+
+        assert_eq!(slots.remove(a).unwrap(), 1);
+        assert!(matches!(slots.get(a), Err(Error::StaleHandle)));
+        assert!(matches!(slots.get_mut(a), Err(Error::StaleHandle)));
+        assert!(matches!(slots.remove(a), Err(Error::StaleHandle)));
+        assert_eq!(slots.len(), 0);
+    }
+
+    #[test]
+    fn reused_slot_does_not_alias_stale_handle() {
+        let mut slots = Slots::new();
+
+        let a = slots.insert("a", 1);
+        slots.remove(a).unwrap();
+        let b = slots.insert("b", 2);
+
+        //same slot, newer generation
+        assert_eq!(a.index, b.index);
+        assert_ne!(a, b);
+        assert!(matches!(slots.get(a), Err(Error::StaleHandle)));
+        assert!(matches!(slots.replace(a, 3), Err(Error::StaleHandle)));
+        assert!(matches!(slots.revision(a), Err(Error::StaleHandle)));
+        assert_eq!(*slots.get(b).unwrap(), 2);
+    }
+
+    #[test]
+    fn replace_keeps_handle_and_bumps_revision() {
+        let mut slots = Slots::new();
+
+        let a = slots.insert("a", 1);
+        let revision = slots.revision(a).unwrap();
+
+        assert_eq!(slots.replace(a, 2).unwrap(), 1);
+        assert_eq!(*slots.get(a).unwrap(), 2);
+        assert_ne!(slots.revision(a).unwrap(), revision);
+    }
+
+    #[test]
+    fn iter_skips_removed() {
+        let mut slots = Slots::new();
+
+        let a = slots.insert("a", 1);
+        let b = slots.insert("b", 2);
+        slots.remove(a).unwrap();
+
+        let live = slots
+            .iter()
+            .map(|(handle, label, value)| (handle, label.to_owned(), *value))
+            .collect::<Vec<_>>();
+
+        assert_eq!(live, vec![(b, "b".to_owned(), 2)]);
+    }
+}
@@ -1,5 +1,7 @@
 use crate::prelude::*;
 
+pub use vk::ClearValue;
+
 pub struct RenderPassInfo<'a> {
     pub device: &'a Device,
     pub attachments: &'a [Attachment],
@@ -13,6 +15,21 @@ pub struct RenderPassBeginInfo<'a> {
     pub depth_stencil_clear_value: Option<(f32, u32)>,
 }
 
+//An attachment of begin_rendering, the image stays in layout while rendering
+pub struct RenderingAttachment<'a> {
+    pub image: &'a Image,
+    pub layout: ImageLayout,
+    pub load_op: AttachmentLoadOp,
+    pub store_op: AttachmentStoreOp,
+    pub clear_value: ClearValue,
+}
+
+pub struct RenderingInfo<'a> {
+    pub extent: (u32, u32),
+    pub color_attachments: &'a [RenderingAttachment<'a>],
+    pub depth_attachment: Option<RenderingAttachment<'a>>,
+}
+
 pub struct Subpass<'a> {
     pub src: Option<u32>,
     pub src_access: Access,
@@ -25,6 +42,8 @@ pub struct Subpass<'a> {
 
 pub struct Attachment {
     pub format: Format,
+    //of the image, Resolve attachments have One
+    pub samples: SampleCount,
     pub load_op: AttachmentLoadOp,
     pub store_op: AttachmentStoreOp,
     pub initial_layout: ImageLayout,
@@ -70,6 +89,9 @@ pub enum AttachmentType {
     Color,
     DepthStencil,
     Input,
+    //The multisampled color attachments of a subpass are resolved into its Resolve
+    //attachments at the end of it, the first into the first and so on
+    Resolve,
 }
 
 pub enum RenderPass {
@@ -85,7 +107,7 @@ impl RenderPass {
                     .iter()
                     .map(|attachment| vk::AttachmentDescription {
                         format: attachment.format.into(),
-                        samples: vk::SAMPLE_COUNT_1,
+                        samples: attachment.samples,
                         load_op: attachment.load_op.into(),
                         store_op: attachment.store_op.into(),
                         stencil_load_op: vk::AttachmentLoadOp::DontCare,
@@ -131,6 +153,38 @@ impl RenderPass {
                     })
                     .collect::<Vec<_>>();
 
+                let resolve_attachments = info
+                    .subpasses
+                    .iter()
+                    .map(|subpass| {
+                        subpass
+                            .attachments
+                            .iter()
+                            .filter(|&attachment| {
+                                info.attachments[*attachment as usize].ty == AttachmentType::Resolve
+                            })
+                            .map(|&attachment| vk::AttachmentReference {
+                                attachment,
+                                layout: info.attachments[attachment as usize].layout.into(),
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+
+                for (color_attachments, resolve_attachments) in
+                    color_attachments.iter().zip(&resolve_attachments)
+                {
+                    if !resolve_attachments.is_empty()
+                        && resolve_attachments.len() != color_attachments.len()
+                    {
+                        panic!(
+                            "subpass has {} resolve attachments for {} color attachments",
+                            resolve_attachments.len(),
+                            color_attachments.len()
+                        );
+                    }
+                }
+
                 let depth_stencil_attachments = info
                     .subpasses
                     .iter()
@@ -156,7 +210,7 @@ impl RenderPass {
                         pipeline_bind_point: vk::PipelineBindPoint::Graphics,
                         input_attachments: &input_attachments[i],
                         color_attachments: &color_attachments[i],
-                        resolve_attachments: &[],
+                        resolve_attachments: &resolve_attachments[i],
                         depth_stencil_attachment: depth_stencil_attachments[i].get(0),
                         preserve_attachments: &[],
                     })
@@ -0,0 +1,623 @@
+use crate::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufReader, BufWriter};
+use std::mem;
+use std::path::Path;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct ResourceId(pub u32);
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum RecordedBarrier {
+    Memory {
+        src_access: u32,
+        dst_access: u32,
+    },
+    Buffer {
+        src_access: u32,
+        dst_access: u32,
+        offset: usize,
+        size: usize,
+        buffer: ResourceId,
+    },
+    Image {
+        src_access: u32,
+        dst_access: u32,
+        old_layout: ImageLayout,
+        new_layout: ImageLayout,
+        image: ResourceId,
+    },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum RecordedCommand {
+    BeginRenderPass {
+        render_pass: ResourceId,
+        framebuffer: ResourceId,
+        color_clear_values: Vec<[f32; 4]>,
+        depth_stencil_clear_value: Option<(f32, u32)>,
+    },
+    EndRenderPass,
+    NextSubpass,
+    BindPipeline {
+        slot: u32,
+        pipeline: ResourceId,
+    },
+    BindDescriptorSets {
+        slot: u32,
+        pipeline: ResourceId,
+        dynamic_offsets: Vec<u32>,
+    },
+    PushConstants {
+        pipeline: ResourceId,
+        stage: u32,
+        offset: u32,
+        data: Vec<u8>,
+    },
+    BindVertexBuffers {
+        first_binding: u32,
+        buffers: Vec<ResourceId>,
+        offsets: Vec<usize>,
+    },
+    BindIndexBuffer {
+        buffer: ResourceId,
+        offset: usize,
+    },
+    SetBlendConstants {
+        blend_constants: [f32; 4],
+    },
+    SetScissor {
+        offset: (i32, i32),
+        extent: (u32, u32),
+    },
+    Draw {
+        vertex_count: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    },
+    DrawIndexed {
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    },
+    DrawIndexedIndirect {
+        buffer: ResourceId,
+        offset: usize,
+        draw_count: u32,
+        stride: u32,
+    },
+    CopyBuffer {
+        from: ResourceId,
+        to: ResourceId,
+        src: usize,
+        dst: usize,
+        size: usize,
+    },
+    CopyImageToBuffer {
+        image: ResourceId,
+        layout: ImageLayout,
+        buffer: ResourceId,
+        offset: usize,
+        image_offset: (i32, i32),
+        extent: (u32, u32),
+    },
+    Dispatch {
+        group_count_x: u32,
+        group_count_y: u32,
+        group_count_z: u32,
+    },
+    PipelineBarrier {
+        src_stage: u32,
+        dst_stage: u32,
+        barriers: Vec<RecordedBarrier>,
+    },
+}
+
+//Resources are identified by address, so register them once they have reached
+//their final place and keep them there while recording.
+#[derive(Default)]
+pub struct Recorder {
+    ids: HashMap<usize, ResourceId>,
+    commands: Vec<RecordedCommand>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<T>(&mut self, id: ResourceId, resource: &T) {
+        self.ids.insert(resource as *const T as usize, id);
+    }
+
+    fn id<T>(&self, resource: &T) -> ResourceId {
+        *self
+            .ids
+            .get(&(resource as *const T as usize))
+            .expect("resource was not registered with the recorder")
+    }
+
+    pub fn record<'a, 'b>(&'a mut self, commands: &'a mut Commands<'b>) -> Recording<'a, 'b> {
+        Recording {
+            recorder: self,
+            commands,
+        }
+    }
+
+    pub fn commands(&self) -> &[RecordedCommand] {
+        &self.commands
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = fs::File::create(path)?;
+
+        serde_json::to_writer(BufWriter::new(file), &self.commands)?;
+
+        Ok(())
+    }
+}
+
+pub fn load_recording(path: impl AsRef<Path>) -> io::Result<Vec<RecordedCommand>> {
+    let file = fs::File::open(path)?;
+
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
+
+//Forwards every call to the wrapped commands and appends it to the recorder.
+//Descriptor set contents are not part of the stream, bind them the same way before replaying.
+pub struct Recording<'a, 'b> {
+    recorder: &'a mut Recorder,
+    commands: &'a mut Commands<'b>,
+}
+
+impl Recording<'_, '_> {
+    fn push(&mut self, command: RecordedCommand) {
+        self.recorder.commands.push(command);
+    }
+
+    //Nothing is recorded for a stale framebuffer
+    pub fn begin_render_pass(&mut self, info: RenderPassBeginInfo<'_>) -> Result<(), Error> {
+        let command = RecordedCommand::BeginRenderPass {
+            render_pass: self.recorder.id(info.render_pass),
+            framebuffer: self.recorder.id(info.framebuffer),
+            color_clear_values: info.color_clear_values.to_vec(),
+            depth_stencil_clear_value: info.depth_stencil_clear_value,
+        };
+
+        self.commands.begin_render_pass(info)?;
+        self.push(command);
+
+        Ok(())
+    }
+
+    pub fn end_render_pass(&mut self) {
+        self.push(RecordedCommand::EndRenderPass);
+        self.commands.end_render_pass();
+    }
+
+    pub fn next_subpass(&mut self) {
+        self.push(RecordedCommand::NextSubpass);
+        self.commands.next_subpass();
+    }
+
+    pub fn bind_pipeline(&mut self, slot: u32, pipeline: &Pipeline) {
+        let pipeline_id = self.recorder.id(pipeline);
+
+        self.push(RecordedCommand::BindPipeline {
+            slot,
+            pipeline: pipeline_id,
+        });
+        self.commands.bind_pipeline(slot, pipeline);
+    }
+
+    pub fn bind_descriptor_sets(&mut self, slot: u32, pipeline: &Pipeline, dynamic_offsets: &'_ [u32]) {
+        let pipeline_id = self.recorder.id(pipeline);
+
+        self.push(RecordedCommand::BindDescriptorSets {
+            slot,
+            pipeline: pipeline_id,
+            dynamic_offsets: dynamic_offsets.to_vec(),
+        });
+        self.commands.bind_descriptor_sets(slot, pipeline, dynamic_offsets);
+    }
+
+    pub fn push_constants<T: Pod>(&mut self, pipeline: &Pipeline, stage: ShaderStage, offset: u32, data: &'_ [T]) {
+        let bytes = unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, mem::size_of_val(data)) };
+
+        let pipeline_id = self.recorder.id(pipeline);
+
+        self.push(RecordedCommand::PushConstants {
+            pipeline: pipeline_id,
+            stage: stage.bits(),
+            offset,
+            data: bytes.to_vec(),
+        });
+        self.commands.push_constants(pipeline, stage, offset, bytes);
+    }
+
+    pub fn bind_vertex_buffers(&mut self, first_binding: u32, buffers: &'_ [&'_ Buffer], offsets: &'_ [usize]) {
+        let buffer_ids = buffers.iter().map(|buffer| self.recorder.id(*buffer)).collect();
+
+        self.push(RecordedCommand::BindVertexBuffers {
+            first_binding,
+            buffers: buffer_ids,
+            offsets: offsets.to_vec(),
+        });
+        self.commands.bind_vertex_buffers(first_binding, buffers, offsets);
+    }
+
+    pub fn bind_index_buffer(&mut self, buffer: &'_ Buffer, offset: usize) {
+        let buffer_id = self.recorder.id(buffer);
+
+        self.push(RecordedCommand::BindIndexBuffer {
+            buffer: buffer_id,
+            offset,
+        });
+        self.commands.bind_index_buffer(buffer, offset);
+    }
+
+    pub fn set_blend_constants(&mut self, blend_constants: [f32; 4]) {
+        self.push(RecordedCommand::SetBlendConstants { blend_constants });
+        self.commands.set_blend_constants(blend_constants);
+    }
+
+    pub fn set_scissor(&mut self, offset: (i32, i32), extent: (u32, u32)) {
+        self.push(RecordedCommand::SetScissor { offset, extent });
+        self.commands.set_scissor(offset, extent);
+    }
+
+    pub fn draw(&mut self, vertex_count: u32, instance_count: u32, first_vertex: u32, first_instance: u32) {
+        self.push(RecordedCommand::Draw {
+            vertex_count,
+            instance_count,
+            first_vertex,
+            first_instance,
+        });
+        self.commands
+            .draw(vertex_count, instance_count, first_vertex, first_instance);
+    }
+
+    pub fn draw_indexed(
+        &mut self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    ) {
+        self.push(RecordedCommand::DrawIndexed {
+            index_count,
+            instance_count,
+            first_index,
+            vertex_offset,
+            first_instance,
+        });
+        self.commands.draw_indexed(
+            index_count,
+            instance_count,
+            first_index,
+            vertex_offset,
+            first_instance,
+        );
+    }
+
+    pub fn draw_indexed_indirect(&mut self, buffer: &'_ Buffer, offset: usize, draw_count: u32, stride: u32) {
+        let buffer_id = self.recorder.id(buffer);
+
+        self.push(RecordedCommand::DrawIndexedIndirect {
+            buffer: buffer_id,
+            offset,
+            draw_count,
+            stride,
+        });
+        self.commands
+            .draw_indexed_indirect(buffer, offset, draw_count, stride);
+    }
+
+    pub fn copy_buffer(&mut self, from: &'_ Buffer, to: &'_ mut Buffer, src: usize, dst: usize, size: usize) {
+        let from_id = self.recorder.id(from);
+        let to_id = self.recorder.id(to);
+
+        self.push(RecordedCommand::CopyBuffer {
+            from: from_id,
+            to: to_id,
+            src,
+            dst,
+            size,
+        });
+        self.commands.copy_buffer(from, to, src, dst, size);
+    }
+
+    pub fn copy_image_to_buffer(
+        &mut self,
+        image: &'_ Image,
+        layout: ImageLayout,
+        buffer: &'_ mut Buffer,
+        offset: usize,
+        image_offset: (i32, i32),
+        extent: (u32, u32),
+    ) {
+        let image_id = self.recorder.id(image);
+        let buffer_id = self.recorder.id(buffer);
+
+        self.push(RecordedCommand::CopyImageToBuffer {
+            image: image_id,
+            layout,
+            buffer: buffer_id,
+            offset,
+            image_offset,
+            extent,
+        });
+        self.commands
+            .copy_image_to_buffer(image, layout, buffer, offset, image_offset, extent);
+    }
+
+    pub fn dispatch(&mut self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        self.push(RecordedCommand::Dispatch {
+            group_count_x,
+            group_count_y,
+            group_count_z,
+        });
+        self.commands
+            .dispatch(group_count_x, group_count_y, group_count_z);
+    }
+
+    pub fn pipeline_barrier(&mut self, src_stage: PipelineStage, dst_stage: PipelineStage, barriers: &'_ [Barrier]) {
+        let recorded = barriers
+            .iter()
+            .map(|barrier| match barrier {
+                Barrier::Memory {
+                    src_access,
+                    dst_access,
+                } => RecordedBarrier::Memory {
+                    src_access: src_access.bits(),
+                    dst_access: dst_access.bits(),
+                },
+                Barrier::Buffer {
+                    src_access,
+                    dst_access,
+                    offset,
+                    size,
+                    buffer,
+                } => RecordedBarrier::Buffer {
+                    src_access: src_access.bits(),
+                    dst_access: dst_access.bits(),
+                    offset: *offset,
+                    size: *size,
+                    buffer: self.recorder.id(*buffer),
+                },
+                Barrier::Image {
+                    src_access,
+                    dst_access,
+                    old_layout,
+                    new_layout,
+                    image,
+                } => RecordedBarrier::Image {
+                    src_access: src_access.bits(),
+                    dst_access: dst_access.bits(),
+                    old_layout: *old_layout,
+                    new_layout: *new_layout,
+                    image: self.recorder.id(*image),
+                },
+            })
+            .collect();
+
+        self.push(RecordedCommand::PipelineBarrier {
+            src_stage: src_stage.bits(),
+            dst_stage: dst_stage.bits(),
+            barriers: recorded,
+        });
+        self.commands.pipeline_barrier(src_stage, dst_stage, barriers);
+    }
+}
+
+//The same resources the recording was made against, looked up by the ids they were registered with.
+#[derive(Default)]
+pub struct ReplayResources<'a> {
+    pub pipelines: HashMap<ResourceId, &'a Pipeline>,
+    pub render_passes: HashMap<ResourceId, &'a RenderPass>,
+    pub framebuffers: HashMap<ResourceId, &'a Framebuffer>,
+    pub images: HashMap<ResourceId, &'a Image>,
+    pub buffers: HashMap<ResourceId, &'a mut Buffer>,
+}
+
+impl<'a> ReplayResources<'a> {
+    fn pipeline(&self, id: ResourceId) -> &'a Pipeline {
+        self.pipelines.get(&id).expect("missing replay pipeline")
+    }
+
+    fn image(&self, id: ResourceId) -> &'a Image {
+        self.images.get(&id).expect("missing replay image")
+    }
+
+    fn buffer(&self, id: ResourceId) -> &Buffer {
+        self.buffers.get(&id).expect("missing replay buffer")
+    }
+}
+
+pub fn replay(commands: &mut Commands<'_>, recorded: &[RecordedCommand], resources: &mut ReplayResources<'_>) -> Result<(), Error> {
+    for command in recorded {
+        match command {
+            RecordedCommand::BeginRenderPass {
+                render_pass,
+                framebuffer,
+                color_clear_values,
+                depth_stencil_clear_value,
+            } => commands.begin_render_pass(RenderPassBeginInfo {
+                render_pass: resources
+                    .render_passes
+                    .get(render_pass)
+                    .expect("missing replay render pass"),
+                framebuffer: resources
+                    .framebuffers
+                    .get(framebuffer)
+                    .expect("missing replay framebuffer"),
+                color_clear_values,
+                depth_stencil_clear_value: *depth_stencil_clear_value,
+            })?,
+            RecordedCommand::EndRenderPass => commands.end_render_pass(),
+            RecordedCommand::NextSubpass => commands.next_subpass(),
+            RecordedCommand::BindPipeline { slot, pipeline } => {
+                commands.bind_pipeline(*slot, resources.pipeline(*pipeline))
+            }
+            RecordedCommand::BindDescriptorSets {
+                slot,
+                pipeline,
+                dynamic_offsets,
+            } => commands.bind_descriptor_sets(*slot, resources.pipeline(*pipeline), dynamic_offsets),
+            RecordedCommand::PushConstants {
+                pipeline,
+                stage,
+                offset,
+                data,
+            } => commands.push_constants(
+                resources.pipeline(*pipeline),
+                ShaderStage::from_bits_truncate(*stage),
+                *offset,
+                data,
+            ),
+            RecordedCommand::BindVertexBuffers {
+                first_binding,
+                buffers,
+                offsets,
+            } => {
+                let buffers = buffers
+                    .iter()
+                    .map(|&id| resources.buffer(id))
+                    .collect::<Vec<_>>();
+
+                commands.bind_vertex_buffers(*first_binding, &buffers, offsets)
+            }
+            RecordedCommand::BindIndexBuffer { buffer, offset } => {
+                commands.bind_index_buffer(resources.buffer(*buffer), *offset)
+            }
+            RecordedCommand::SetBlendConstants { blend_constants } => {
+                commands.set_blend_constants(*blend_constants)
+            }
+            RecordedCommand::SetScissor { offset, extent } => commands.set_scissor(*offset, *extent),
+            RecordedCommand::Draw {
+                vertex_count,
+                instance_count,
+                first_vertex,
+                first_instance,
+            } => commands.draw(*vertex_count, *instance_count, *first_vertex, *first_instance),
+            RecordedCommand::DrawIndexed {
+                index_count,
+                instance_count,
+                first_index,
+                vertex_offset,
+                first_instance,
+            } => commands.draw_indexed(
+                *index_count,
+                *instance_count,
+                *first_index,
+                *vertex_offset,
+                *first_instance,
+            ),
+            RecordedCommand::DrawIndexedIndirect {
+                buffer,
+                offset,
+                draw_count,
+                stride,
+            } => commands.draw_indexed_indirect(resources.buffer(*buffer), *offset, *draw_count, *stride),
+            RecordedCommand::CopyBuffer {
+                from,
+                to,
+                src,
+                dst,
+                size,
+            } => {
+                let to_buffer = resources.buffers.remove(to).expect("missing replay buffer");
+
+                commands.copy_buffer(resources.buffer(*from), to_buffer, *src, *dst, *size);
+
+                resources.buffers.insert(*to, to_buffer);
+            }
+            RecordedCommand::CopyImageToBuffer {
+                image,
+                layout,
+                buffer,
+                offset,
+                image_offset,
+                extent,
+            } => {
+                let image = resources.image(*image);
+
+                let buffer = resources
+                    .buffers
+                    .get_mut(buffer)
+                    .expect("missing replay buffer");
+
+                commands.copy_image_to_buffer(image, *layout, buffer, *offset, *image_offset, *extent);
+            }
+            RecordedCommand::Dispatch {
+                group_count_x,
+                group_count_y,
+                group_count_z,
+            } => commands.dispatch(*group_count_x, *group_count_y, *group_count_z),
+            RecordedCommand::PipelineBarrier {
+                src_stage,
+                dst_stage,
+                barriers,
+            } => {
+                let barriers = barriers
+                    .iter()
+                    .map(|barrier| match barrier {
+                        RecordedBarrier::Memory {
+                            src_access,
+                            dst_access,
+                        } => Barrier::Memory {
+                            src_access: Access::from_bits_truncate(*src_access),
+                            dst_access: Access::from_bits_truncate(*dst_access),
+                        },
+                        RecordedBarrier::Buffer {
+                            src_access,
+                            dst_access,
+                            offset,
+                            size,
+                            buffer,
+                        } => Barrier::Buffer {
+                            src_access: Access::from_bits_truncate(*src_access),
+                            dst_access: Access::from_bits_truncate(*dst_access),
+                            offset: *offset,
+                            size: *size,
+                            buffer: resources.buffer(*buffer),
+                        },
+                        RecordedBarrier::Image {
+                            src_access,
+                            dst_access,
+                            old_layout,
+                            new_layout,
+                            image,
+                        } => Barrier::Image {
+                            src_access: Access::from_bits_truncate(*src_access),
+                            dst_access: Access::from_bits_truncate(*dst_access),
+                            old_layout: *old_layout,
+                            new_layout: *new_layout,
+                            image: resources.image(*image),
+                        },
+                    })
+                    .collect::<Vec<_>>();
+
+                commands.pipeline_barrier(
+                    PipelineStage::from_bits_truncate(*src_stage),
+                    PipelineStage::from_bits_truncate(*dst_stage),
+                    &barriers,
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
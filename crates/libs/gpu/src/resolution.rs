@@ -0,0 +1,67 @@
+use crate::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RenderScale {
+    Native,
+    //fraction of the output extent per axis, e.g. 0.5 renders a quarter of the pixels
+    Factor(f32),
+    Fixed((u32, u32)),
+}
+
+//Extent the scene is rendered at, independent of the swapchain. Size render targets from
+//extent(), then upscale into the output with UpscalePass. Changing the scale only changes
+//the attachments requested from the pool, the swapchain and its framebuffers are kept.
+pub struct RenderResolution {
+    scale: RenderScale,
+    output: (u32, u32),
+}
+
+impl RenderResolution {
+    pub fn new(output: (u32, u32), scale: RenderScale) -> Self {
+        Self { scale, output }
+    }
+
+    pub fn set_scale(&mut self, scale: RenderScale) {
+        self.scale = scale;
+    }
+
+    //Call when the swapchain is recreated
+    pub fn set_output_extent(&mut self, output: (u32, u32)) {
+        self.output = output;
+    }
+
+    pub fn scale(&self) -> RenderScale {
+        self.scale
+    }
+
+    pub fn output_extent(&self) -> (u32, u32) {
+        self.output
+    }
+
+    pub fn extent(&self) -> (u32, u32) {
+        let (width, height) = match self.scale {
+            RenderScale::Native => self.output,
+            RenderScale::Factor(factor) => (
+                (self.output.0 as f32 * factor).round() as u32,
+                (self.output.1 as f32 * factor).round() as u32,
+            ),
+            RenderScale::Fixed(extent) => extent,
+        };
+
+        (width.max(1), height.max(1))
+    }
+
+    //False when the scene has to be upscaled into the output
+    pub fn is_native(&self) -> bool {
+        self.extent() == self.output
+    }
+
+    pub fn attachment(&self, format: Format, usage: ImageUsage) -> AttachmentDesc {
+        AttachmentDesc {
+            format,
+            extent: self.extent(),
+            usage,
+            samples: SampleCount::One,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::prelude::*;
+
+use std::collections::HashMap;
+
+//Small constant textures shared by passes, embedded as KTX2
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Resource {
+    //64x64 Rgba8Unorm, four independent void and cluster channels, tiles with Repeat
+    BlueNoise,
+    //64x64 Rg32Sfloat split sum GGX scale and bias, u is n dot v and v is roughness
+    BrdfLut,
+    //256x16 Rgba8Unorm identity 16^3 grading LUT, blue slices laid out left to right
+    NeutralLut,
+}
+
+impl Resource {
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            Self::BlueNoise => include_bytes!("resources/blue_noise.ktx2"),
+            Self::BrdfLut => include_bytes!("resources/brdf_lut.ktx2"),
+            Self::NeutralLut => include_bytes!("resources/neutral_lut.ktx2"),
+        }
+    }
+
+    fn sampler(self) -> SamplerDesc {
+        match self {
+            Self::BlueNoise => SamplerDesc {
+                filter: Filter::Nearest,
+                address_mode: AddressMode::Repeat,
+                anisotropy: 0,
+            },
+            Self::BrdfLut | Self::NeutralLut => SamplerDesc {
+                filter: Filter::Linear,
+                address_mode: AddressMode::ClampToEdge,
+                anisotropy: 0,
+            },
+        }
+    }
+}
+
+//Creates each resource on first use and keeps it, one pack per Device
+pub struct ResourcePack {
+    textures: HashMap<Resource, Texture>,
+}
+
+impl Default for ResourcePack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResourcePack {
+    pub fn new() -> Self {
+        Self {
+            textures: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, device: &mut Device, resource: Resource) -> &Texture {
+        self.textures.entry(resource).or_insert_with(|| {
+            Texture::from_ktx2(device, resource.bytes(), resource.sampler())
+                .expect("failed to load embedded resource")
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+
+    //Must run after the frame fence wait since the textures are destroyed
+    pub fn clear(&mut self) {
+        self.textures.clear();
+    }
+}
@@ -0,0 +1,264 @@
+use crate::prelude::*;
+
+pub use vk::{RayTracingPipelineProperties, StridedDeviceAddressRegion};
+
+//Where a region lies in the table, relative to its start
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct SbtRegion {
+    pub offset: u64,
+    pub stride: u64,
+    pub size: u64,
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct SbtLayout {
+    pub raygen: SbtRegion,
+    pub miss: SbtRegion,
+    pub hit: SbtRegion,
+    pub callable: SbtRegion,
+    //bytes the whole table takes
+    pub size: u64,
+}
+
+//The four regions trace_rays takes
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct SbtRegions {
+    pub raygen: StridedDeviceAddressRegion,
+    pub miss: StridedDeviceAddressRegion,
+    pub hit: StridedDeviceAddressRegion,
+    pub callable: StridedDeviceAddressRegion,
+}
+
+impl SbtLayout {
+    //base_address is the device address of the table's buffer, see Buffer::device_address,
+    //which has to be a multiple of shader_group_base_alignment
+    pub fn regions(&self, base_address: u64) -> SbtRegions {
+        let region = |region: SbtRegion| {
+            if region.size == 0 {
+                return StridedDeviceAddressRegion::default();
+            }
+
+            StridedDeviceAddressRegion {
+                device_address: base_address + region.offset,
+                stride: region.stride,
+                size: region.size,
+            }
+        };
+
+        SbtRegions {
+            raygen: region(self.raygen),
+            miss: region(self.miss),
+            hit: region(self.hit),
+            callable: region(self.callable),
+        }
+    }
+}
+
+//A group handle followed by its shader record data
+struct SbtRecord {
+    handle: Vec<u8>,
+    data: Vec<u8>,
+}
+
+pub struct SbtBuilderInfo<'a> {
+    pub device: &'a Device,
+}
+
+pub struct Sbt {
+    pub buffer: Buffer,
+    pub layout: SbtLayout,
+}
+
+//Lays group handles and their shader record data out into a shader binding table.
+//Records in a region share the stride of the largest one, rounded up to
+//shader_group_handle_alignment, and regions start at multiples of
+//shader_group_base_alignment.
+pub struct SbtBuilder {
+    properties: RayTracingPipelineProperties,
+    raygen: Option<SbtRecord>,
+    miss: Vec<SbtRecord>,
+    hit: Vec<SbtRecord>,
+    callable: Vec<SbtRecord>,
+}
+
+impl SbtBuilder {
+    //None when the device does not support ray tracing pipelines
+    pub fn new(info: SbtBuilderInfo<'_>) -> Option<Self> {
+        let Device::Vulkan {
+            physical_device, ..
+        } = info.device
+        else {
+            panic!("not a vulkan device");
+        };
+
+        let properties = physical_device.ray_tracing_pipeline_properties()?;
+
+        Some(Self {
+            properties,
+            raygen: None,
+            miss: vec![],
+            hit: vec![],
+            callable: vec![],
+        })
+    }
+
+    pub fn properties(&self) -> &RayTracingPipelineProperties {
+        &self.properties
+    }
+
+    //There is one ray generation record per table, a second call replaces the first
+    pub fn raygen(&mut self, handle: &[u8], data: &[u8]) -> &mut Self {
+        self.raygen = Some(self.record(handle, data));
+        self
+    }
+
+    //Records are indexed in the order they were added
+    pub fn miss(&mut self, handle: &[u8], data: &[u8]) -> &mut Self {
+        let record = self.record(handle, data);
+        self.miss.push(record);
+        self
+    }
+
+    pub fn hit(&mut self, handle: &[u8], data: &[u8]) -> &mut Self {
+        let record = self.record(handle, data);
+        self.hit.push(record);
+        self
+    }
+
+    pub fn callable(&mut self, handle: &[u8], data: &[u8]) -> &mut Self {
+        let record = self.record(handle, data);
+        self.callable.push(record);
+        self
+    }
+
+    pub fn layout(&self) -> SbtLayout {
+        let base_alignment = self.properties.shader_group_base_alignment as u64;
+
+        let mut offset = 0;
+
+        let mut region = |records: &[&SbtRecord]| {
+            if records.is_empty() {
+                return SbtRegion::default();
+            }
+
+            let stride = self.stride(records);
+
+            offset = align(offset, base_alignment);
+
+            let region = SbtRegion {
+                offset,
+                stride,
+                size: stride * records.len() as u64,
+            };
+
+            offset += region.size;
+
+            region
+        };
+
+        let raygen = region(&self.raygen.iter().collect::<Vec<_>>());
+        let miss = region(&self.miss.iter().collect::<Vec<_>>());
+        let hit = region(&self.hit.iter().collect::<Vec<_>>());
+        let callable = region(&self.callable.iter().collect::<Vec<_>>());
+
+        SbtLayout {
+            raygen,
+            miss,
+            hit,
+            callable,
+            size: offset,
+        }
+    }
+
+    //The table as bytes, laid out as layout() describes
+    pub fn bytes(&self) -> Vec<u8> {
+        let layout = self.layout();
+
+        let mut bytes = vec![0u8; layout.size as usize];
+
+        let mut write = |region: SbtRegion, records: &[&SbtRecord]| {
+            for (i, record) in records.iter().enumerate() {
+                let start = (region.offset + region.stride * i as u64) as usize;
+                let data = start + record.handle.len();
+
+                bytes[start..data].copy_from_slice(&record.handle);
+                bytes[data..data + record.data.len()].copy_from_slice(&record.data);
+            }
+        };
+
+        write(layout.raygen, &self.raygen.iter().collect::<Vec<_>>());
+        write(layout.miss, &self.miss.iter().collect::<Vec<_>>());
+        write(layout.hit, &self.hit.iter().collect::<Vec<_>>());
+        write(layout.callable, &self.callable.iter().collect::<Vec<_>>());
+
+        bytes
+    }
+
+    //Writes the table into a host visible buffer. trace_rays needs its device address,
+    //which SbtLayout::regions turns into the four regions.
+    pub fn build(&self, device: &Device) -> Sbt {
+        if self.raygen.is_none() {
+            panic!("shader binding table needs a raygen record");
+        }
+
+        let layout = self.layout();
+
+        let mut buffer = Buffer::new(BufferInfo {
+            device,
+            usage: BufferUsage::SHADER_BINDING_TABLE | BufferUsage::TRANSFER_SRC,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: layout.size as _,
+            priority: MemoryPriority::Default,
+        });
+
+        buffer.write(BufferWrite {
+            offset: 0,
+            data: &self.bytes(),
+        });
+
+        Sbt { buffer, layout }
+    }
+
+    fn record(&self, handle: &[u8], data: &[u8]) -> SbtRecord {
+        if handle.len() != self.properties.shader_group_handle_size as usize {
+            panic!(
+                "shader group handle is {} bytes, expected {}",
+                handle.len(),
+                self.properties.shader_group_handle_size
+            );
+        }
+
+        SbtRecord {
+            handle: handle.to_vec(),
+            data: data.to_vec(),
+        }
+    }
+
+    fn stride(&self, records: &[&SbtRecord]) -> u64 {
+        let largest = records
+            .iter()
+            .map(|record| record.handle.len() + record.data.len())
+            .max()
+            .unwrap_or(0);
+
+        let stride = align(
+            largest as u64,
+            self.properties.shader_group_handle_alignment as u64,
+        );
+
+        if stride > self.properties.max_shader_group_stride as u64 {
+            panic!(
+                "shader record stride {} exceeds max_shader_group_stride {}",
+                stride, self.properties.max_shader_group_stride
+            );
+        }
+
+        stride
+    }
+}
+
+fn align(value: u64, alignment: u64) -> u64 {
+    let alignment = alignment.max(1);
+
+    (value + alignment - 1) / alignment * alignment
+}
@@ -0,0 +1,125 @@
+use crate::prelude::*;
+
+pub struct SecondaryCommandBufferInfo<'a> {
+    pub device: &'a Device,
+}
+
+pub struct SecondaryRecordInfo<'a> {
+    pub render_pass: &'a RenderPass,
+    pub subpass: u32,
+    //optional, but lets the driver optimize for the framebuffer it will run in
+    pub framebuffer: Option<&'a Framebuffer>,
+    //The scissor the primary sets before execute_commands. Inherited where
+    //inherited_viewport_scissor_supported, otherwise set again at the start of the secondary.
+    pub scissor: Option<((i32, i32), (u32, u32))>,
+}
+
+//Commands recorded once for a subpass, e.g. per chunk, and replayed with
+//Commands::execute_commands inside begin_render_pass_secondary. Each one owns its
+//command pool so they can be recorded independently of each other.
+#[non_exhaustive]
+pub enum SecondaryCommandBuffer {
+    Vulkan {
+        command_buffer: vk::CommandBuffer,
+        command_pool: vk::CommandPool,
+        inherited_viewport_scissor: bool,
+    },
+}
+
+impl SecondaryCommandBuffer {
+    pub fn new(info: SecondaryCommandBufferInfo<'_>) -> Self {
+        match info.device {
+            Device::Vulkan {
+                device,
+                queue_family_index,
+                ..
+            } => {
+                let command_pool_create_info = vk::CommandPoolCreateInfo {
+                    flags: vk::COMMAND_POOL_CREATE_RESET_COMMAND_BUFFER,
+                    queue_family_index: *queue_family_index,
+                };
+
+                let command_pool = vk::CommandPool::new(device.clone(), command_pool_create_info)
+                    .expect("failed to create command pool");
+
+                let command_buffer_allocate_info = vk::CommandBufferAllocateInfo {
+                    command_pool: &command_pool,
+                    level: vk::CommandBufferLevel::Secondary,
+                    count: 1,
+                };
+
+                let command_buffer =
+                    vk::CommandBuffer::allocate(device.clone(), command_buffer_allocate_info)
+                        .expect("failed to create command buffer")
+                        .remove(0);
+
+                Self::Vulkan {
+                    command_buffer,
+                    command_pool,
+                    inherited_viewport_scissor: device.inherited_viewport_scissor_enabled(),
+                }
+            }
+        }
+    }
+
+    //Replaces whatever was recorded before, the buffer must not be pending execution
+    pub fn record(&mut self, info: SecondaryRecordInfo<'_>, mut script: impl FnMut(Commands<'_>)) {
+        match self {
+            Self::Vulkan {
+                command_buffer,
+                inherited_viewport_scissor,
+                ..
+            } => {
+                let RenderPass::Vulkan { render_pass } = info.render_pass else {
+                    panic!("not a vulkan render pass")
+                };
+
+                let framebuffer = info.framebuffer.map(|framebuffer| {
+                    let Framebuffer::Vulkan { framebuffer, .. } = framebuffer else {
+                        panic!("not a vulkan framebuffer")
+                    };
+
+                    framebuffer
+                });
+
+                let inherit = *inherited_viewport_scissor && info.scissor.is_some();
+
+                //only the depth range of an inherited viewport is taken from here
+                let viewport_depths = [vk::Viewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 0.0,
+                    height: 0.0,
+                    min_depth: 0.0,
+                    max_depth: 1.0,
+                }];
+
+                let inheritance_info = vk::CommandBufferInheritanceInfo {
+                    render_pass,
+                    subpass: info.subpass,
+                    framebuffer,
+                    viewport_depths: inherit.then(|| &viewport_depths[..]),
+                };
+
+                command_buffer
+                    .record_secondary(
+                        vk::COMMAND_BUFFER_USAGE_RENDER_PASS_CONTINUE,
+                        inheritance_info,
+                        |commands| {
+                            let mut commands = Commands::Vulkan { commands };
+
+                            if let (false, Some((offset, extent))) = (inherit, info.scissor) {
+                                commands.set_scissor(offset, extent);
+                            }
+
+                            script(commands);
+                        },
+                    )
+                    .expect("failed to record secondary command buffer");
+
+                #[cfg(feature = "barrier-validation")]
+                crate::device::report_hazards(command_buffer);
+            }
+        }
+    }
+}
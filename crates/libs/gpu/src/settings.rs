@@ -0,0 +1,61 @@
+use crate::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RendererSettings {
+    pub vsync: VsyncMode,
+    //scene extent relative to the swapchain, see RenderResolution
+    pub render_scale: RenderScale,
+    //samples per pixel of the scene attachments, 1 for no MSAA
+    pub msaa: u32,
+    //width and height of the shadow map
+    pub shadow_resolution: u32,
+    //SamplerDesc::anisotropy of material textures
+    pub anisotropy: u32,
+}
+
+impl Default for RendererSettings {
+    fn default() -> Self {
+        Self {
+            vsync: VsyncMode::Off,
+            render_scale: RenderScale::Native,
+            msaa: 1,
+            shadow_resolution: 2048,
+            anisotropy: 0,
+        }
+    }
+}
+
+//Passed to Graphics::on_settings_change listeners. Each subsystem checks the part it
+//depends on and rebuilds what it has to, the device is idle while they run.
+#[derive(Clone, Copy, Debug)]
+pub struct SettingsChange {
+    pub old: RendererSettings,
+    pub new: RendererSettings,
+}
+
+impl SettingsChange {
+    //Graphics has already recreated the swapchain, SwapchainChange listeners included
+    pub fn vsync_changed(&self) -> bool {
+        self.old.vsync != self.new.vsync
+    }
+
+    //RenderResolution::set_scale, then resize the scene attachments
+    pub fn render_scale_changed(&self) -> bool {
+        self.old.render_scale != self.new.render_scale
+    }
+
+    //Render passes, attachments and the pipelines using them depend on the sample count
+    pub fn needs_pipeline_rebuild(&self) -> bool {
+        self.old.msaa != self.new.msaa
+    }
+
+    //Recreate the shadow map and rebind it, e.g. with ForwardRenderer::set_shadows
+    pub fn needs_shadow_map_recreate(&self) -> bool {
+        self.old.shadow_resolution != self.new.shadow_resolution
+    }
+
+    //Texture::set_sampler with the new anisotropy, then bind the textures again
+    pub fn needs_sampler_refresh(&self) -> bool {
+        self.old.anisotropy != self.new.anisotropy
+    }
+}
@@ -36,6 +36,7 @@ pub enum ShaderError {
 pub enum ShaderInput {
     Spirv { asset: PathBuf },
     Glsl { asset: PathBuf, resource: PathBuf },
+    Embedded { name: &'static str, source: &'static str },
 }
 
 impl ShaderInput {
@@ -50,6 +51,7 @@ impl ShaderInput {
         match self {
             Self::Spirv { asset } => Some(&asset),
             Self::Glsl { asset, .. } => Some(&asset),
+            _ => None,
         }
     }
 }
@@ -109,6 +111,22 @@ impl Shader {
             Device::Vulkan { device, .. } => {
                 let mut last_modified = Default::default();
 
+                if let ShaderInput::Embedded { name, source } = info.input {
+                    let code = Self::compile_embedded(name, source, &info.entry)
+                        .expect("failed to compile shader");
+
+                    let shader_module = Self::new_vk_shader(device.clone(), &code);
+
+                    return Self::Vulkan {
+                        device: device.clone(),
+                        entry: info.entry.to_string(),
+                        input: info.input,
+                        last_modified,
+                        shader_module,
+                        initial: false,
+                    };
+                }
+
                 if let Some(_) = info.input.get_resource() {
                     Self::compile_spirv(&info.input, &last_modified, &info.entry)
                         .expect("failed to compile shader");
@@ -142,6 +160,10 @@ impl Shader {
                 entry,
                 initial,
             } => {
+                if let ShaderInput::Embedded { .. } = input {
+                    return Ok(false);
+                }
+
                 let modified = {
                     let metadata = fs::metadata(input.get_resource().unwrap())
                         .expect("failed to get metadata of shader file");
@@ -293,11 +315,47 @@ impl Shader {
             code.push(u32::from_le_bytes(slice.try_into().unwrap()));
         }
 
-        let shader_module_create_info = vk::ShaderModuleCreateInfo { code: &code[..] };
+        Self::new_vk_shader(device, &code)
+    }
+
+    fn new_vk_shader(device: Rc<vk::Device>, code: &[u32]) -> vk::ShaderModule {
+        let shader_module_create_info = vk::ShaderModuleCreateInfo { code };
 
         let shader_module = vk::ShaderModule::new(device, shader_module_create_info)
             .expect("failed to create shader module");
 
         shader_module
     }
+
+    fn compile_embedded(
+        name: &'_ str,
+        source: &'_ str,
+        entry: &'_ str,
+    ) -> Result<Vec<u32>, ShaderError> {
+        use shaderc::*;
+
+        let kind = match name.rsplit(".").next() {
+            Some("vert") => ShaderKind::Vertex,
+            Some("frag") => ShaderKind::Fragment,
+            Some("comp") => ShaderKind::Compute,
+            _ => return Err(ShaderError::InvalidResource),
+        };
+
+        info!("Compiling shader: {}\n", name);
+
+        let compiler = Compiler::new().unwrap();
+
+        let mut options = CompileOptions::new().unwrap();
+
+        options.add_macro_definition("EP", Some(entry));
+
+        let artifact = compiler
+            .compile_into_spirv(source, kind, name, entry, Some(&options))
+            .map_err(|err| match err {
+                Error::CompilationError(num, details) => ShaderError::Compilation(num, details),
+                _ => panic!("failed to compile shader to spirv"),
+            })?;
+
+        Ok(artifact.as_binary().to_vec())
+    }
 }
@@ -0,0 +1,35 @@
+//Shading helper for LightCulling. Define CLUSTER_BINDING before this and
+//bind LightCulling::bindings at the same binding.
+
+layout(binding = CLUSTER_BINDING) uniform Clusters {
+	mat4 view;
+	uvec4 grid;
+	vec4 tile;
+	vec4 depth;
+} clusters;
+
+layout(binding = CLUSTER_BINDING + 1) readonly buffer ClusterLightCounts {
+	uint cluster_light_counts[];
+};
+
+layout(binding = CLUSTER_BINDING + 2) readonly buffer ClusterLightIndices {
+	uint cluster_light_indices[];
+};
+
+//frag_coord in pixels from the top left, view_depth is the positive distance along the camera forward axis
+uint cluster_index(vec2 frag_coord, float view_depth) {
+	uvec2 tile = min(uvec2(frag_coord / clusters.tile.xy), clusters.grid.xy - 1);
+
+	float slice = log(max(view_depth, clusters.depth.x)) * clusters.depth.z - clusters.depth.w;
+	uint z = min(uint(max(slice, 0.0)), clusters.grid.z - 1);
+
+	return (z * clusters.grid.y + tile.y) * clusters.grid.x + tile.x;
+}
+
+uint cluster_light_count(uint cluster) {
+	return cluster_light_counts[cluster];
+}
+
+uint cluster_light(uint cluster, uint i) {
+	return cluster_light_indices[cluster * clusters.grid.w + i];
+}
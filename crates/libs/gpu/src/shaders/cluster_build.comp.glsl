@@ -0,0 +1,71 @@
+#version 450
+
+#define BLOCK_SIZE 64
+
+layout(local_size_x = BLOCK_SIZE) in;
+
+layout(binding = 0) uniform Clusters {
+	mat4 view;
+	uvec4 grid;
+	vec4 tile;
+	vec4 depth;
+} clusters;
+
+//min and max corner of each cluster in view space
+layout(binding = 1) writeonly buffer Bounds {
+	vec4 bounds[];
+};
+
+layout(push_constant) uniform Build {
+	uint count;
+} build;
+
+//view space point at distance d along the ray through ndc
+vec3 view_point(vec2 ndc, float d) {
+	return vec3(ndc * clusters.tile.zw * d, -d);
+}
+
+void main() {
+	uint index = gl_GlobalInvocationID.x;
+
+	if (index >= build.count) {
+		return;
+	}
+
+	uint x = index % clusters.grid.x;
+	uint y = (index / clusters.grid.x) % clusters.grid.y;
+	uint z = index / (clusters.grid.x * clusters.grid.y);
+
+	vec2 extent = clusters.tile.xy * vec2(clusters.grid.xy);
+
+	vec2 ndc_min = vec2(x, y) * clusters.tile.xy / extent * 2.0 - 1.0;
+	vec2 ndc_max = vec2(x + 1, y + 1) * clusters.tile.xy / extent * 2.0 - 1.0;
+
+	float near = clusters.depth.x;
+	float far = clusters.depth.y;
+
+	float slice_near = near * pow(far / near, float(z) / float(clusters.grid.z));
+	float slice_far = near * pow(far / near, float(z + 1) / float(clusters.grid.z));
+
+	vec3 corners[8] = vec3[](
+		view_point(ndc_min, slice_near),
+		view_point(vec2(ndc_max.x, ndc_min.y), slice_near),
+		view_point(vec2(ndc_min.x, ndc_max.y), slice_near),
+		view_point(ndc_max, slice_near),
+		view_point(ndc_min, slice_far),
+		view_point(vec2(ndc_max.x, ndc_min.y), slice_far),
+		view_point(vec2(ndc_min.x, ndc_max.y), slice_far),
+		view_point(ndc_max, slice_far)
+	);
+
+	vec3 lower = corners[0];
+	vec3 upper = corners[0];
+
+	for (uint i = 1; i < 8; i++) {
+		lower = min(lower, corners[i]);
+		upper = max(upper, corners[i]);
+	}
+
+	bounds[index * 2] = vec4(lower, 0.0);
+	bounds[index * 2 + 1] = vec4(upper, 0.0);
+}
@@ -0,0 +1,57 @@
+#version 450
+
+#define BINS 256
+
+layout(local_size_x = BINS) in;
+
+layout(binding = 0) buffer Histogram {
+	uint bins[BINS];
+};
+
+layout(binding = 1) buffer Exposure {
+	float average;
+	float exposure;
+};
+
+layout(push_constant) uniform Luminance {
+	uint width;
+	uint height;
+	float min_log_luminance;
+	float log_luminance_range;
+	float adaptation;
+} lum;
+
+shared float weights[BINS];
+
+void main() {
+	uint l = gl_LocalInvocationIndex;
+
+	uint count = bins[l];
+
+	weights[l] = float(count * l);
+
+	barrier();
+
+	//the next frame accumulates into a cleared histogram
+	bins[l] = 0;
+
+	for (uint stride = BINS / 2; stride > 0; stride >>= 1) {
+		if (l < stride) {
+			weights[l] += weights[l + stride];
+		}
+
+		barrier();
+	}
+
+	if (l == 0) {
+		float lit = max(float(lum.width * lum.height) - float(count), 1.0);
+
+		float bin = weights[0] / lit - 1.0;
+		float luminance = exp2(bin / 254.0 * lum.log_luminance_range + lum.min_log_luminance);
+
+		float adapted = average > 0.0 ? average + (luminance - average) * lum.adaptation : luminance;
+
+		average = adapted;
+		exposure = 1.0 / (9.6 * adapted);
+	}
+}
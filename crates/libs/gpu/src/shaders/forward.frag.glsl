@@ -0,0 +1,193 @@
+//No version directive, forward.rs prepends it along with SHADOWS when shadows are bound
+//and CLUSTERED, followed by cluster.glsl, when lights are culled by LightCulling
+
+#define PI 3.14159265359
+
+#define DIRECTIONAL_LIGHT 0
+#define POINT_LIGHT 1
+
+layout(binding = 0) uniform Frame {
+	mat4 view;
+	mat4 view_proj;
+	vec4 camera_position;
+	uint light_count;
+	uint linear_output;
+} frame;
+
+struct Material {
+	vec4 base_color;
+	vec3 emissive;
+	float metallic;
+	float roughness;
+	float occlusion;
+};
+
+layout(binding = 1) readonly buffer Materials {
+	Material materials[];
+};
+
+struct Light {
+	vec3 position;
+	float range;
+	vec3 color;
+	float intensity;
+	vec3 direction;
+	uint kind;
+};
+
+layout(binding = 2) readonly buffer Lights {
+	Light lights[];
+};
+
+#ifdef SHADOWS
+#define MAX_CASCADES 4
+#define CASCADE_BIAS 5e-4
+
+layout(binding = 3) uniform Cascades {
+	mat4 matrices[MAX_CASCADES];
+	vec4 splits;
+} cascades;
+
+layout(binding = 4) uniform sampler2DArray shadow_map;
+
+uint cascade_index(float view_depth) {
+	for (uint i = 0; i < MAX_CASCADES - 1; i++) {
+		if (view_depth < cascades.splits[i]) {
+			return i;
+		}
+	}
+	return MAX_CASCADES - 1;
+}
+
+float cascade_shadow(vec3 world_position, float view_depth) {
+	uint index = cascade_index(view_depth);
+
+	vec4 light_clip = cascades.matrices[index] * vec4(world_position, 1.0);
+	vec3 light_ndc = light_clip.xyz / light_clip.w;
+	vec2 uv = light_ndc.xy * 0.5 + 0.5;
+
+	if (any(lessThan(uv, vec2(0))) || any(greaterThan(uv, vec2(1))) || light_ndc.z > 1.0) {
+		return 1.0;
+	}
+
+	vec2 texel = 1.0 / vec2(textureSize(shadow_map, 0).xy);
+
+	float lit = 0.0;
+	for (int x = -1; x <= 1; x++) {
+		for (int y = -1; y <= 1; y++) {
+			float depth = texture(shadow_map, vec3(uv + vec2(x, y) * texel, float(index))).r;
+			lit += light_ndc.z - CASCADE_BIAS > depth ? 0.0 : 1.0;
+		}
+	}
+
+	return lit / 9.0;
+}
+#endif
+
+layout(push_constant) uniform Draw {
+	mat4 model;
+	uint material;
+} draw;
+
+layout(location = 0) in vec3 in_world_position;
+layout(location = 1) in vec3 in_normal;
+layout(location = 2) in vec2 in_uv;
+layout(location = 3) in float in_view_depth;
+
+layout(location = 0) out vec4 out_color;
+
+float distribution_ggx(float n_dot_h, float roughness) {
+	float a = roughness * roughness;
+	float a2 = a * a;
+	float d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+	return a2 / (PI * d * d);
+}
+
+float geometry_smith(float n_dot_v, float n_dot_l, float roughness) {
+	float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+	float v = n_dot_v / (n_dot_v * (1.0 - k) + k);
+	float l = n_dot_l / (n_dot_l * (1.0 - k) + k);
+	return v * l;
+}
+
+vec3 fresnel_schlick(float cos_theta, vec3 f0) {
+	return f0 + (1.0 - f0) * pow(1.0 - cos_theta, 5.0);
+}
+
+void main() {
+	Material material = materials[draw.material];
+
+	vec3 albedo = material.base_color.rgb;
+	float roughness = clamp(material.roughness, 0.045, 1.0);
+
+	vec3 n = normalize(in_normal);
+	vec3 v = normalize(frame.camera_position.xyz - in_world_position);
+	float n_dot_v = max(dot(n, v), 1e-4);
+
+	vec3 f0 = mix(vec3(0.04), albedo, material.metallic);
+
+	vec3 radiance = vec3(0.0);
+
+#ifdef CLUSTERED
+	uint cluster = cluster_index(gl_FragCoord.xy, in_view_depth);
+	uint light_count = cluster_light_count(cluster);
+#else
+	uint light_count = frame.light_count;
+#endif
+
+	for (uint j = 0; j < light_count; j++) {
+#ifdef CLUSTERED
+		Light light = lights[cluster_light(cluster, j)];
+#else
+		Light light = lights[j];
+#endif
+
+		vec3 l;
+		float attenuation;
+
+		if (light.kind == DIRECTIONAL_LIGHT) {
+			l = -normalize(light.direction);
+			attenuation = 1.0;
+#ifdef SHADOWS
+			attenuation *= cascade_shadow(in_world_position, in_view_depth);
+#endif
+		} else {
+			vec3 to_light = light.position - in_world_position;
+			float distance = length(to_light);
+			float window = clamp(1.0 - pow(distance / light.range, 4.0), 0.0, 1.0);
+
+			l = to_light / distance;
+			attenuation = window * window / (distance * distance + 1.0);
+		}
+
+		float n_dot_l = dot(n, l);
+
+		if (n_dot_l <= 0.0 || attenuation <= 0.0) {
+			continue;
+		}
+
+		vec3 h = normalize(v + l);
+
+		vec3 f = fresnel_schlick(max(dot(h, v), 0.0), f0);
+		float d = distribution_ggx(max(dot(n, h), 0.0), roughness);
+		float g = geometry_smith(n_dot_v, n_dot_l, roughness);
+
+		vec3 specular = d * g * f / (4.0 * n_dot_v * n_dot_l);
+		vec3 diffuse = (1.0 - f) * (1.0 - material.metallic) * albedo / PI;
+
+		radiance += (diffuse + specular) * light.color * light.intensity * attenuation * n_dot_l;
+	}
+
+	vec3 ambient = 0.03 * albedo * material.occlusion;
+
+	vec3 color = ambient + radiance + material.emissive;
+
+	//reinhard, the reference renderer presents directly without a separate tonemap pass
+	color = color / (1.0 + color);
+
+	if (frame.linear_output == 0) {
+		color = pow(color, vec3(1.0 / 2.2));
+	}
+
+	out_color = vec4(color, material.base_color.a);
+}
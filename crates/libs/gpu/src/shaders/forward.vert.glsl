@@ -0,0 +1,37 @@
+#version 450
+
+layout(binding = 0) uniform Frame {
+	mat4 view;
+	mat4 view_proj;
+	vec4 camera_position;
+	uint light_count;
+	uint linear_output;
+} frame;
+
+layout(push_constant) uniform Draw {
+	mat4 model;
+	uint material;
+} draw;
+
+layout(location = 0) in vec3 in_position;
+layout(location = 1) in vec3 in_normal;
+layout(location = 2) in vec2 in_uv;
+
+layout(location = 0) out vec3 out_world_position;
+layout(location = 1) out vec3 out_normal;
+layout(location = 2) out vec2 out_uv;
+layout(location = 3) out float out_view_depth;
+
+//the pre-pass and the shading pass share this shader, the equal depth test relies on it
+invariant gl_Position;
+
+void main() {
+	vec4 world_position = draw.model * vec4(in_position, 1.0);
+
+	out_world_position = world_position.xyz;
+	out_normal = mat3(draw.model) * in_normal;
+	out_uv = in_uv;
+	out_view_depth = -(frame.view * world_position).z;
+
+	gl_Position = frame.view_proj * world_position;
+}
@@ -0,0 +1,27 @@
+#version 450
+
+layout(origin_upper_left) in vec4 gl_FragCoord;
+
+layout(binding = 0) uniform sampler2D linear_color;
+
+layout(push_constant) uniform GammaEncode {
+	vec2 resolution;
+} gamma_encode;
+
+layout(location = 0) out vec4 out_color;
+
+//piecewise sRGB transfer function, the inverse of linear() in ui.vert
+vec3 srgb(vec3 linear) {
+	bvec3 cutoff = lessThan(linear, vec3(0.0031308));
+	vec3 lower = linear * 12.92;
+	vec3 higher = 1.055 * pow(linear, vec3(1.0 / 2.4)) - 0.055;
+	return mix(higher, lower, cutoff);
+}
+
+void main() {
+	vec2 uv = gl_FragCoord.xy / gamma_encode.resolution;
+
+	vec4 color = texture(linear_color, uv);
+
+	out_color = vec4(srgb(clamp(color.rgb, 0.0, 1.0)), color.a);
+}
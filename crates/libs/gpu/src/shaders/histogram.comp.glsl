@@ -0,0 +1,50 @@
+#version 450
+
+#define BLOCK_SIZE 256
+#define RADIX 16
+
+layout(local_size_x = BLOCK_SIZE) in;
+
+layout(binding = 0) buffer Keys {
+	uint keys[];
+};
+
+layout(binding = 1) buffer Counts {
+	uint counts[];
+};
+
+layout(push_constant) uniform Sort {
+	uint count;
+	uint shift;
+	uint wide;
+	uint has_values;
+} sort;
+
+shared uint histogram[RADIX];
+
+uint digit(uint i) {
+	uint key = sort.wide != 0 ? keys[2 * i + sort.shift / 32] : keys[i];
+	return (key >> (sort.shift % 32)) & (RADIX - 1);
+}
+
+void main() {
+	uint i = gl_GlobalInvocationID.x;
+	uint l = gl_LocalInvocationID.x;
+
+	if (l < RADIX) {
+		histogram[l] = 0;
+	}
+
+	barrier();
+
+	if (i < sort.count) {
+		atomicAdd(histogram[digit(i)], 1);
+	}
+
+	barrier();
+
+	//digit major so one exclusive scan yields every scatter offset
+	if (l < RADIX) {
+		counts[l * gl_NumWorkGroups.x + gl_WorkGroupID.x] = histogram[l];
+	}
+}
@@ -0,0 +1,78 @@
+#version 450
+
+#define BLOCK_SIZE 64
+
+#define DIRECTIONAL_LIGHT 0
+#define POINT_LIGHT 1
+
+layout(local_size_x = BLOCK_SIZE) in;
+
+layout(binding = 0) uniform Clusters {
+	mat4 view;
+	uvec4 grid;
+	vec4 tile;
+	vec4 depth;
+} clusters;
+
+layout(binding = 1) readonly buffer Bounds {
+	vec4 bounds[];
+};
+
+struct Light {
+	vec3 position;
+	float range;
+	vec3 color;
+	float intensity;
+	vec3 direction;
+	uint kind;
+};
+
+layout(binding = 2) readonly buffer Lights {
+	Light lights[];
+};
+
+layout(binding = 3) writeonly buffer ClusterLightCounts {
+	uint cluster_light_counts[];
+};
+
+layout(binding = 4) writeonly buffer ClusterLightIndices {
+	uint cluster_light_indices[];
+};
+
+layout(push_constant) uniform Cull {
+	uint light_count;
+} cull;
+
+void main() {
+	uint index = gl_GlobalInvocationID.x;
+
+	if (index >= clusters.grid.x * clusters.grid.y * clusters.grid.z) {
+		return;
+	}
+
+	vec3 lower = bounds[index * 2].xyz;
+	vec3 upper = bounds[index * 2 + 1].xyz;
+
+	uint capacity = clusters.grid.w;
+	uint base = index * capacity;
+	uint count = 0;
+
+	for (uint i = 0; i < cull.light_count && count < capacity; i++) {
+		Light light = lights[i];
+
+		//directional lights reach every cluster
+		if (light.kind == POINT_LIGHT) {
+			vec3 center = (clusters.view * vec4(light.position, 1.0)).xyz;
+			vec3 offset = clamp(center, lower, upper) - center;
+
+			if (dot(offset, offset) > light.range * light.range) {
+				continue;
+			}
+		}
+
+		cluster_light_indices[base + count] = i;
+		count++;
+	}
+
+	cluster_light_counts[index] = count;
+}
@@ -0,0 +1,94 @@
+#version 450
+
+#define BLOCK_SIZE 64
+#define MAX_LODS 4
+#define DRAW_SIZE 5
+
+layout(local_size_x = BLOCK_SIZE) in;
+
+layout(binding = 0) uniform Lod {
+	vec4 planes[6];
+	//xyz position, w pixels per unit of error at distance 1
+	vec4 camera;
+	vec4 errors;
+	uint chunk_count;
+	float pixel_error;
+	uint compact;
+} lod;
+
+struct Chunk {
+	vec4 bounds;
+	//first index, index count, vertex offset per lod, finest first
+	uvec4 lods[MAX_LODS];
+	uint lod_count;
+	uint instance;
+};
+
+layout(binding = 1) readonly buffer Chunks {
+	Chunk chunks[];
+};
+
+//VkDrawIndexedIndirectCommand
+layout(binding = 2) writeonly buffer Draws {
+	uint draws[];
+};
+
+layout(binding = 3) buffer Count {
+	uint count;
+};
+
+void write_draw(uint slot, uvec4 mesh, uint instance_count, uint first_instance) {
+	uint base = slot * DRAW_SIZE;
+
+	draws[base] = mesh.y;
+	draws[base + 1] = instance_count;
+	draws[base + 2] = mesh.x;
+	draws[base + 3] = mesh.z;
+	draws[base + 4] = first_instance;
+}
+
+void main() {
+	uint index = gl_GlobalInvocationID.x;
+
+	if (index >= lod.chunk_count) {
+		return;
+	}
+
+	Chunk chunk = chunks[index];
+
+	vec3 center = chunk.bounds.xyz;
+	float radius = chunk.bounds.w;
+
+	bool visible = chunk.lod_count > 0;
+
+	for (uint i = 0; i < 6; i++) {
+		if (dot(lod.planes[i].xyz, center) + lod.planes[i].w < -radius) {
+			visible = false;
+		}
+	}
+
+	uint selected = 0;
+
+	if (visible) {
+		float distance = max(length(center - lod.camera.xyz) - radius, 1e-4);
+
+		//coarsest lod whose projected error stays under the threshold
+		for (int i = int(chunk.lod_count) - 1; i > 0; i--) {
+			if (lod.errors[i] * lod.camera.w / distance <= lod.pixel_error) {
+				selected = uint(i);
+				break;
+			}
+		}
+	}
+
+	uvec4 mesh = chunk.lods[selected];
+
+	visible = visible && mesh.y > 0;
+
+	if (lod.compact == 0) {
+		//one slot per chunk, culled chunks draw zero instances
+		write_draw(index, mesh, visible ? 1 : 0, chunk.instance);
+	} else if (visible) {
+		write_draw(atomicAdd(count, 1), mesh, 1, chunk.instance);
+	}
+}
@@ -0,0 +1,52 @@
+#version 450
+
+#define BINS 256
+
+layout(local_size_x = 16, local_size_y = 16) in;
+
+layout(binding = 0) uniform sampler2D hdr;
+
+layout(binding = 1) buffer Histogram {
+	uint bins[BINS];
+};
+
+layout(push_constant) uniform Luminance {
+	uint width;
+	uint height;
+	float min_log_luminance;
+	float log_luminance_range;
+	float adaptation;
+} lum;
+
+shared uint histogram[BINS];
+
+uint bin(vec3 color) {
+	float l = dot(color, vec3(0.2126, 0.7152, 0.0722));
+
+	//bin 0 is reserved for black so it can be excluded from the average
+	if (l < 1e-5) {
+		return 0;
+	}
+
+	float t = clamp((log2(l) - lum.min_log_luminance) / lum.log_luminance_range, 0.0, 1.0);
+
+	return uint(t * 254.0 + 1.0);
+}
+
+void main() {
+	uint l = gl_LocalInvocationIndex;
+
+	histogram[l] = 0;
+
+	barrier();
+
+	uvec2 pixel = gl_GlobalInvocationID.xy;
+
+	if (pixel.x < lum.width && pixel.y < lum.height) {
+		atomicAdd(histogram[bin(texelFetch(hdr, ivec2(pixel), 0).rgb)], 1);
+	}
+
+	barrier();
+
+	atomicAdd(bins[l], histogram[l]);
+}
@@ -0,0 +1,67 @@
+#version 450
+
+layout(local_size_x = 8, local_size_y = 8) in;
+
+layout(binding = 0) readonly buffer Source {
+	uint pixels[];
+};
+
+layout(binding = 1) writeonly buffer Destination {
+	uint bytes[];
+};
+
+layout(push_constant) uniform Convert {
+	uint width;
+	uint height;
+	uint bgra;
+} convert;
+
+vec3 rgb(uint pixel) {
+	vec3 color = vec3(pixel & 0xff, (pixel >> 8) & 0xff, (pixel >> 16) & 0xff);
+	return convert.bgra != 0 ? color.bgr : color;
+}
+
+//bt.709 limited range
+uint luma(vec3 c) {
+	return uint(clamp(16.0 + 0.1826 * c.r + 0.6142 * c.g + 0.0620 * c.b + 0.5, 0.0, 255.0));
+}
+
+uvec2 chroma(vec3 c) {
+	float u = 128.0 - 0.1006 * c.r - 0.3386 * c.g + 0.4392 * c.b;
+	float v = 128.0 + 0.4392 * c.r - 0.3989 * c.g - 0.0403 * c.b;
+	return uvec2(clamp(vec2(u, v) + 0.5, 0.0, 255.0));
+}
+
+//each invocation converts a 4x2 block, so every write is a whole word
+void main() {
+	uvec2 block = gl_GlobalInvocationID.xy;
+
+	uint x = block.x * 4;
+	uint y = block.y * 2;
+
+	if (x >= convert.width || y >= convert.height) {
+		return;
+	}
+
+	uvec2 uv[2] = uvec2[](uvec2(0), uvec2(0));
+
+	for (uint row = 0; row < 2; row++) {
+		uint word = 0;
+
+		for (uint i = 0; i < 4; i++) {
+			vec3 c = rgb(pixels[(y + row) * convert.width + x + i]);
+
+			word |= luma(c) << (8 * i);
+			uv[i / 2] += chroma(c);
+		}
+
+		bytes[((y + row) * convert.width + x) / 4] = word;
+	}
+
+	uvec2 a = (uv[0] + 2) / 4;
+	uvec2 b = (uv[1] + 2) / 4;
+
+	uint plane = convert.width * convert.height;
+
+	bytes[(plane + block.y * convert.width + x) / 4] = a.x | (a.y << 8) | (b.x << 16) | (b.y << 24);
+}
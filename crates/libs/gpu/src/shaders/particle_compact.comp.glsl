@@ -0,0 +1,54 @@
+#version 450
+
+#define BLOCK_SIZE 64
+
+layout(local_size_x = BLOCK_SIZE) in;
+
+struct Particle {
+	vec4 position;
+	vec4 velocity;
+};
+
+layout(binding = 0) readonly buffer Particles {
+	Particle particles[];
+};
+
+layout(binding = 1) writeonly buffer Compacted {
+	Particle compacted[];
+};
+
+//exclusive prefix sum of the alive flags
+layout(binding = 2) readonly buffer Offsets {
+	uint offsets[];
+};
+
+//VkDrawIndexedIndirectCommand drawing one quad instance per alive particle
+layout(binding = 3) writeonly buffer Draw {
+	uint draw[5];
+};
+
+layout(push_constant) uniform Compact {
+	uint max_particles;
+} compact;
+
+void main() {
+	uint i = gl_GlobalInvocationID.x;
+
+	if (i >= compact.max_particles) {
+		return;
+	}
+
+	bool alive = particles[i].position.w > 0.0;
+
+	if (alive) {
+		compacted[offsets[i]] = particles[i];
+	}
+
+	if (i == compact.max_particles - 1) {
+		draw[0] = 6;
+		draw[1] = offsets[i] + (alive ? 1 : 0);
+		draw[2] = 0;
+		draw[3] = 0;
+		draw[4] = 0;
+	}
+}
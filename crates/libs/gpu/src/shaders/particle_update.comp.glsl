@@ -0,0 +1,66 @@
+#version 450
+
+#define BLOCK_SIZE 64
+
+layout(local_size_x = BLOCK_SIZE) in;
+
+struct Particle {
+	//xyz position, w seconds left to live
+	vec4 position;
+	//xyz velocity, w size
+	vec4 velocity;
+};
+
+layout(binding = 0) buffer Particles {
+	Particle particles[];
+};
+
+layout(binding = 1) readonly buffer Spawns {
+	Particle spawns[];
+};
+
+//1 for particles alive after this update, scanned into compaction offsets
+layout(binding = 2) writeonly buffer Alive {
+	uint alive[];
+};
+
+//VkDrawIndexedIndirectCommand of the last compaction, instance count is the alive count
+layout(binding = 3) readonly buffer Draw {
+	uint draw[5];
+};
+
+layout(push_constant) uniform Update {
+	vec4 gravity;
+	float delta_time;
+	uint spawn_count;
+	uint max_particles;
+} update;
+
+void main() {
+	uint i = gl_GlobalInvocationID.x;
+
+	if (i >= update.max_particles) {
+		return;
+	}
+
+	uint alive_count = draw[1];
+
+	Particle particle;
+
+	if (i < alive_count) {
+		particle = particles[i];
+
+		particle.velocity.xyz += update.gravity.xyz * update.delta_time;
+		particle.position.xyz += particle.velocity.xyz * update.delta_time;
+		particle.position.w -= update.delta_time;
+	} else if (i - alive_count < update.spawn_count) {
+		//spawns fill the slots after the live particles
+		particle = spawns[i - alive_count];
+	} else {
+		particle.position = vec4(0.0);
+		particle.velocity = vec4(0.0);
+	}
+
+	particles[i] = particle;
+	alive[i] = particle.position.w > 0.0 ? 1 : 0;
+}
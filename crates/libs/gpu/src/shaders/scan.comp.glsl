@@ -0,0 +1,45 @@
+#version 450
+
+#define BLOCK_SIZE 256
+
+layout(local_size_x = BLOCK_SIZE) in;
+
+layout(binding = 0) buffer Data {
+	uint data[];
+};
+
+layout(binding = 1) buffer Sums {
+	uint sums[];
+};
+
+layout(push_constant) uniform Scan {
+	uint count;
+} scan;
+
+shared uint block[BLOCK_SIZE];
+
+void main() {
+	uint i = gl_GlobalInvocationID.x;
+	uint l = gl_LocalInvocationID.x;
+
+	uint value = i < scan.count ? data[i] : 0;
+
+	block[l] = value;
+
+	barrier();
+
+	for (uint offset = 1; offset < BLOCK_SIZE; offset <<= 1) {
+		uint x = l >= offset ? block[l - offset] : 0;
+		barrier();
+		block[l] += x;
+		barrier();
+	}
+
+	if (i < scan.count) {
+		data[i] = block[l] - value;
+	}
+
+	if (l == BLOCK_SIZE - 1) {
+		sums[gl_WorkGroupID.x] = block[l];
+	}
+}
@@ -0,0 +1,25 @@
+#version 450
+
+#define BLOCK_SIZE 256
+
+layout(local_size_x = BLOCK_SIZE) in;
+
+layout(binding = 0) buffer Data {
+	uint data[];
+};
+
+layout(binding = 1) buffer Sums {
+	uint sums[];
+};
+
+layout(push_constant) uniform Scan {
+	uint count;
+} scan;
+
+void main() {
+	uint i = gl_GlobalInvocationID.x;
+
+	if (i < scan.count) {
+		data[i] += sums[gl_WorkGroupID.x];
+	}
+}
@@ -0,0 +1,77 @@
+#version 450
+
+#define BLOCK_SIZE 256
+#define RADIX 16
+
+layout(local_size_x = BLOCK_SIZE) in;
+
+layout(binding = 0) buffer KeysIn {
+	uint keys_in[];
+};
+
+layout(binding = 1) buffer KeysOut {
+	uint keys_out[];
+};
+
+layout(binding = 2) buffer ValuesIn {
+	uint values_in[];
+};
+
+layout(binding = 3) buffer ValuesOut {
+	uint values_out[];
+};
+
+layout(binding = 4) buffer Offsets {
+	uint offsets[];
+};
+
+layout(push_constant) uniform Sort {
+	uint count;
+	uint shift;
+	uint wide;
+	uint has_values;
+} sort;
+
+shared uint digits[BLOCK_SIZE];
+
+uint digit(uint i) {
+	uint key = sort.wide != 0 ? keys_in[2 * i + sort.shift / 32] : keys_in[i];
+	return (key >> (sort.shift % 32)) & (RADIX - 1);
+}
+
+void main() {
+	uint i = gl_GlobalInvocationID.x;
+	uint l = gl_LocalInvocationID.x;
+
+	uint d = i < sort.count ? digit(i) : RADIX;
+
+	digits[l] = d;
+
+	barrier();
+
+	if (i >= sort.count) {
+		return;
+	}
+
+	//rank among earlier invocations with the same digit keeps the sort stable
+	uint rank = 0;
+
+	for (uint j = 0; j < l; j++) {
+		if (digits[j] == d) {
+			rank++;
+		}
+	}
+
+	uint dst = offsets[d * gl_NumWorkGroups.x + gl_WorkGroupID.x] + rank;
+
+	if (sort.wide != 0) {
+		keys_out[2 * dst] = keys_in[2 * i];
+		keys_out[2 * dst + 1] = keys_in[2 * i + 1];
+	} else {
+		keys_out[dst] = keys_in[i];
+	}
+
+	if (sort.has_values != 0) {
+		values_out[dst] = values_in[i];
+	}
+}
@@ -0,0 +1,118 @@
+#version 450
+
+#define PI 3.14159265
+#define PLANET_RADIUS 6371e3
+#define ATMOSPHERE_RADIUS 6471e3
+#define RAYLEIGH_HEIGHT 8e3
+#define MIE_HEIGHT 1.2e3
+#define VIEW_STEPS 16
+#define LIGHT_STEPS 4
+
+layout(origin_upper_left) in vec4 gl_FragCoord;
+
+//scene color, alpha is geometry coverage (cleared to 0)
+layout(binding = 0) uniform sampler2D scene;
+
+layout(push_constant) uniform Sky {
+	mat4 inverse_view_proj;
+	vec4 sun;
+	vec4 viewport;
+} sky;
+
+layout(location = 0) out vec4 out_color;
+
+const vec3 rayleigh = vec3(5.5e-6, 13.0e-6, 22.4e-6);
+const float mie = 21e-6;
+const float g = 0.76;
+
+vec2 sphere(vec3 origin, vec3 dir, float radius) {
+	float b = dot(origin, dir);
+	float c = dot(origin, origin) - radius * radius;
+	float d = b * b - c;
+
+	if (d < 0.0) {
+		return vec2(1e9, -1e9);
+	}
+
+	d = sqrt(d);
+
+	return vec2(-b - d, -b + d);
+}
+
+vec3 atmosphere(vec3 dir, vec3 sun) {
+	vec3 origin = vec3(0.0, PLANET_RADIUS + 1.0, 0.0);
+
+	vec2 hit = sphere(origin, dir, ATMOSPHERE_RADIUS);
+	float end = hit.y;
+
+	vec2 ground = sphere(origin, dir, PLANET_RADIUS);
+
+	if (ground.x > 0.0) {
+		end = min(end, ground.x);
+	}
+
+	float step_size = end / float(VIEW_STEPS);
+
+	vec3 total_rayleigh = vec3(0.0);
+	vec3 total_mie = vec3(0.0);
+
+	float depth_rayleigh = 0.0;
+	float depth_mie = 0.0;
+
+	for (int i = 0; i < VIEW_STEPS; i++) {
+		vec3 p = origin + dir * (float(i) + 0.5) * step_size;
+		float height = length(p) - PLANET_RADIUS;
+
+		float h_rayleigh = exp(-height / RAYLEIGH_HEIGHT) * step_size;
+		float h_mie = exp(-height / MIE_HEIGHT) * step_size;
+
+		depth_rayleigh += h_rayleigh;
+		depth_mie += h_mie;
+
+		float light_size = sphere(p, sun, ATMOSPHERE_RADIUS).y / float(LIGHT_STEPS);
+
+		float light_rayleigh = 0.0;
+		float light_mie = 0.0;
+
+		for (int j = 0; j < LIGHT_STEPS; j++) {
+			vec3 q = p + sun * (float(j) + 0.5) * light_size;
+			float light_height = length(q) - PLANET_RADIUS;
+
+			light_rayleigh += exp(-light_height / RAYLEIGH_HEIGHT) * light_size;
+			light_mie += exp(-light_height / MIE_HEIGHT) * light_size;
+		}
+
+		vec3 attenuation = exp(-(rayleigh * (depth_rayleigh + light_rayleigh) + mie * 1.1 * (depth_mie + light_mie)));
+
+		total_rayleigh += h_rayleigh * attenuation;
+		total_mie += h_mie * attenuation;
+	}
+
+	float mu = dot(dir, sun);
+	float phase_rayleigh = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+	float phase_mie = 3.0 / (8.0 * PI) * ((1.0 - g * g) * (1.0 + mu * mu)) / ((2.0 + g * g) * pow(1.0 + g * g - 2.0 * g * mu, 1.5));
+
+	return sky.sun.w * (phase_rayleigh * rayleigh * total_rayleigh + phase_mie * mie * total_mie);
+}
+
+void main() {
+	vec2 uv = gl_FragCoord.xy / sky.viewport.xy;
+	vec4 color = texture(scene, uv);
+
+	vec2 ndc = uv * 2.0 - 1.0;
+
+	//two points along the pixel ray, independent of the depth convention
+	vec4 a = sky.inverse_view_proj * vec4(ndc, 0.25, 1.0);
+	vec4 b = sky.inverse_view_proj * vec4(ndc, 0.75, 1.0);
+
+	vec3 dir = normalize(b.xyz / b.w - a.xyz / a.w);
+
+	//reversed-z puts 0.75 nearer than 0.25
+	if (sky.viewport.z != 0.0) {
+		dir = -dir;
+	}
+
+	vec3 radiance = atmosphere(dir, normalize(sky.sun.xyz));
+
+	out_color = vec4(mix(radiance, color.rgb, color.a), 1.0);
+}
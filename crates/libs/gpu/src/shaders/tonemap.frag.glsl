@@ -0,0 +1,44 @@
+#version 450
+
+#define ACES 0
+#define REINHARD 1
+
+layout(origin_upper_left) in vec4 gl_FragCoord;
+
+layout(binding = 0) uniform sampler2D hdr;
+
+layout(binding = 1) readonly buffer Exposure {
+	float average;
+	float exposure;
+};
+
+layout(push_constant) uniform Tonemap {
+	vec2 resolution;
+	float exposure_bias;
+	uint operator;
+} tonemap;
+
+layout(location = 0) out vec4 out_color;
+
+//Narkowicz fit of the ACES filmic curve
+vec3 aces(vec3 x) {
+	return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), 0.0, 1.0);
+}
+
+vec3 reinhard(vec3 x) {
+	return x / (1.0 + x);
+}
+
+void main() {
+	vec2 uv = gl_FragCoord.xy / tonemap.resolution;
+
+	vec3 color = texture(hdr, uv).rgb * exposure * exp2(tonemap.exposure_bias);
+
+	if (tonemap.operator == ACES) {
+		color = aces(color);
+	} else {
+		color = reinhard(color);
+	}
+
+	out_color = vec4(color, 1.0);
+}
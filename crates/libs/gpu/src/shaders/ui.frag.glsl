@@ -0,0 +1,15 @@
+#version 450
+
+layout(binding = 0) uniform sampler2D atlas;
+
+layout(location = 0) in vec2 in_uv;
+layout(location = 1) in vec4 in_color;
+
+layout(location = 0) out vec4 out_color;
+
+void main() {
+	vec4 color = in_color * texture(atlas, in_uv);
+
+	//ui colors are premultiplied but the pipeline blends with source alpha
+	out_color = vec4(color.rgb / max(color.a, 1e-4), color.a);
+}
@@ -0,0 +1,27 @@
+#version 450
+
+layout(location = 0) in vec2 in_position;
+layout(location = 1) in vec2 in_uv;
+layout(location = 2) in vec4 in_color;
+
+layout(push_constant) uniform Ui {
+	vec2 screen_size;
+	uint linear_output;
+} ui;
+
+layout(location = 0) out vec2 out_uv;
+layout(location = 1) out vec4 out_color;
+
+vec3 linear(vec3 srgb) {
+	bvec3 cutoff = lessThan(srgb, vec3(0.04045));
+	vec3 lower = srgb / 12.92;
+	vec3 higher = pow((srgb + 0.055) / 1.055, vec3(2.4));
+	return mix(higher, lower, cutoff);
+}
+
+void main() {
+	gl_Position = vec4(2.0 * in_position / ui.screen_size - 1.0, 0.0, 1.0);
+
+	out_uv = in_uv;
+	out_color = ui.linear_output != 0 ? vec4(linear(in_color.rgb), in_color.a) : in_color;
+}
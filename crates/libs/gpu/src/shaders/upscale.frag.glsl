@@ -0,0 +1,86 @@
+#version 450
+
+#define BILINEAR 0
+#define LANCZOS 1
+
+#define PI 3.14159265
+
+layout(origin_upper_left) in vec4 gl_FragCoord;
+
+layout(binding = 0) uniform sampler2D scene;
+
+layout(push_constant) uniform Upscale {
+	vec2 input_extent;
+	vec2 output_extent;
+	uint mode;
+} upscale;
+
+layout(location = 0) out vec4 out_color;
+
+vec4 load(ivec2 texel) {
+	return texelFetch(scene, clamp(texel, ivec2(0), ivec2(upscale.input_extent) - 1), 0);
+}
+
+//The scene sampler filters with nearest, so bilinear is done by hand
+vec4 bilinear(vec2 position) {
+	ivec2 texel = ivec2(floor(position));
+	vec2 weight = fract(position);
+
+	vec4 top = mix(load(texel), load(texel + ivec2(1, 0)), weight.x);
+	vec4 bottom = mix(load(texel + ivec2(0, 1)), load(texel + ivec2(1, 1)), weight.x);
+
+	return mix(top, bottom, weight.y);
+}
+
+float lanczos2(float x) {
+	if (abs(x) < 1e-5) {
+		return 1.0;
+	}
+
+	if (abs(x) >= 2.0) {
+		return 0.0;
+	}
+
+	float px = PI * x;
+
+	return 2.0 * sin(px) * sin(px / 2.0) / (px * px);
+}
+
+//4x4 lanczos2, clamped to the nearest 2x2 texels to remove ringing around hard edges
+vec4 lanczos(vec2 position) {
+	ivec2 texel = ivec2(floor(position));
+	vec2 f = fract(position);
+
+	vec4 color = vec4(0.0);
+	float total = 0.0;
+
+	vec4 low = vec4(1e30);
+	vec4 high = vec4(-1e30);
+
+	for (int y = -1; y <= 2; y++) {
+		for (int x = -1; x <= 2; x++) {
+			vec4 sample_color = load(texel + ivec2(x, y));
+			float weight = lanczos2(float(x) - f.x) * lanczos2(float(y) - f.y);
+
+			color += sample_color * weight;
+			total += weight;
+
+			if (x >= 0 && x <= 1 && y >= 0 && y <= 1) {
+				low = min(low, sample_color);
+				high = max(high, sample_color);
+			}
+		}
+	}
+
+	return clamp(color / total, low, high);
+}
+
+void main() {
+	vec2 position = gl_FragCoord.xy / upscale.output_extent * upscale.input_extent - 0.5;
+
+	if (upscale.mode == LANCZOS) {
+		out_color = lanczos(position);
+	} else {
+		out_color = bilinear(position);
+	}
+}
@@ -0,0 +1,109 @@
+use crate::prelude::*;
+
+use std::mem;
+
+pub struct SkyPassInfo<'a> {
+    pub device: &'a Device,
+    pub format: Format,
+    pub extent: (u32, u32),
+    pub descriptor_set_count: u32,
+}
+
+#[derive(Clone, Copy)]
+pub struct SkyParams {
+    pub inverse_view_proj: [[f32; 4]; 4],
+    pub sun_direction: [f32; 3],
+    pub sun_intensity: f32,
+    pub reversed_z: bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SkyConstants {
+    inverse_view_proj: [[f32; 4]; 4],
+    sun: [f32; 4],
+    viewport: [f32; 4],
+}
+
+unsafe impl Pod for SkyConstants {}
+
+//Reference procedural sky. Single scattering (rayleigh and mie) is marched per pixel
+//and composited under the scene, whose color alpha marks geometry coverage,
+//so the scene must clear its color attachment to zero alpha.
+pub struct SkyPass {
+    pass: PostProcessPass,
+    extent: (u32, u32),
+}
+
+impl SkyPass {
+    pub fn new(info: SkyPassInfo<'_>) -> Self {
+        let vertex_shader = Shader::new(ShaderInfo {
+            device: info.device,
+            entry: "main",
+            input: ShaderInput::Embedded {
+                name: "fullscreen.vert",
+                source: include_str!("shaders/fullscreen.vert.glsl"),
+            },
+        });
+
+        let fragment_shader = Shader::new(ShaderInfo {
+            device: info.device,
+            entry: "main",
+            input: ShaderInput::Embedded {
+                name: "sky.frag",
+                source: include_str!("shaders/sky.frag.glsl"),
+            },
+        });
+
+        let pass = PostProcessPass::new(PostProcessInfo {
+            device: info.device,
+            vertex_shader: &vertex_shader,
+            fragment_shader: &fragment_shader,
+            format: info.format,
+            extent: info.extent,
+            descriptor_set_count: info.descriptor_set_count,
+            push_constant_size: mem::size_of::<SkyConstants>() as _,
+            layout: &[],
+        });
+
+        Self {
+            pass,
+            extent: info.extent,
+        }
+    }
+
+    pub fn render_pass(&self) -> &RenderPass {
+        self.pass.render_pass()
+    }
+
+    pub fn record(
+        &mut self,
+        commands: &mut Commands<'_>,
+        image_index: u32,
+        scene: &Image,
+        output: &Framebuffer,
+        params: &SkyParams,
+    ) -> Result<(), Error> {
+        let [x, y, z] = params.sun_direction;
+
+        let constants = SkyConstants {
+            inverse_view_proj: params.inverse_view_proj,
+            sun: [x, y, z, params.sun_intensity],
+            viewport: [
+                self.extent.0 as f32,
+                self.extent.1 as f32,
+                params.reversed_z as u32 as f32,
+                0.0,
+            ],
+        };
+
+        self.pass.record(
+            commands,
+            image_index,
+            scene,
+            output,
+            &[],
+            as_bytes(&constants),
+        )
+    }
+}
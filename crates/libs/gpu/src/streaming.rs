@@ -0,0 +1,76 @@
+use crate::prelude::*;
+
+use std::mem;
+
+pub struct StreamingBufferInfo<'a> {
+    pub device: &'a Device,
+    pub usage: BufferUsage,
+    pub frames_in_flight: u32,
+    pub frame_size: usize,
+}
+
+//Host visible ring with one segment per frame in flight. Data pushed this frame stays
+//valid until the ring wraps back around, so advance once per frame after the fence wait.
+pub struct StreamingBuffer {
+    buffer: Buffer,
+    frame_size: usize,
+    frames_in_flight: u32,
+    frame: u32,
+    cursor: usize,
+}
+
+impl StreamingBuffer {
+    pub fn new(info: StreamingBufferInfo<'_>) -> Self {
+        let buffer = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: info.usage,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: info.frame_size * info.frames_in_flight as usize,
+            priority: MemoryPriority::Default,
+        });
+
+        Self {
+            buffer,
+            frame_size: info.frame_size,
+            frames_in_flight: info.frames_in_flight,
+            frame: 0,
+            cursor: 0,
+        }
+    }
+
+    //Returns the offset of the data within buffer(), or None if this frame's segment is full.
+    pub fn push<T: Pod>(&mut self, data: &[T]) -> Option<usize> {
+        let alignment = mem::align_of::<T>().max(4);
+
+        let start = (self.cursor + alignment - 1) / alignment * alignment;
+        let end = start + mem::size_of_val(data);
+
+        if end > self.frame_size {
+            return None;
+        }
+
+        let offset = self.frame as usize * self.frame_size + start;
+
+        self.buffer.write(BufferWrite {
+            offset: offset as _,
+            data,
+        });
+
+        self.cursor = end;
+
+        Some(offset)
+    }
+
+    pub fn advance(&mut self) {
+        self.frame = (self.frame + 1) % self.frames_in_flight;
+        self.cursor = 0;
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.frame_size - self.cursor
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+}
@@ -18,7 +18,8 @@ impl Surface {
     pub fn new(info: SurfaceInfo) -> Self {
         match info.context {
             Context::Vulkan { instance, .. } => {
-                let surface = vk::Surface::new(instance.clone(), &info.window);
+                let surface = vk::Surface::new(instance.clone(), &info.window)
+                    .expect("failed to create surface");
 
                 Self::Vulkan { surface }
             }
@@ -28,10 +29,9 @@ impl Surface {
     pub(crate) fn get_vk_surface_format(
         &self,
         physical_device: &vk::PhysicalDevice,
-    ) -> vk::SurfaceFormat {
-        vk::SurfaceFormat {
-            format: vk::Format::Bgra8Srgb,
-            color_space: vk::ColorSpace::SrgbNonlinear,
+    ) -> Result<vk::SurfaceFormat, vk::Error> {
+        match self {
+            Self::Vulkan { surface } => physical_device.surface_format(surface),
         }
     }
 }
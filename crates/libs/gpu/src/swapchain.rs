@@ -7,6 +7,172 @@ pub struct SwapchainInfo<'a> {
     pub device: &'a Device,
     pub surface: &'a Surface,
     pub old: Option<Swapchain>,
+    //must be supported by the surface, COLOR at least for rendering into the images.
+    //TRANSFER_SRC is added where supported when the capture feature is enabled.
+    pub usage: ImageUsage,
+    //Hardware picks an sRGB format, Manual a UNORM one. Falls back to the other when
+    //the surface has none, so check encoding() on the result.
+    pub encoding: OutputEncoding,
+    pub vsync: VsyncMode,
+    //the window's size in physical pixels, only used where the surface leaves the extent
+    //to the swapchain, see negotiate_extent
+    pub extent: Option<(u32, u32)>,
+    //applied once created, see Swapchain::set_hdr_metadata
+    pub hdr_metadata: Option<HdrMetadata>,
+    //Acquire and present through semaphores of its own rather than the device's, for
+    //several swapchains drawn by one Device::draw_call_presenting. Kept on recreation.
+    pub dedicated_sync: bool,
+}
+
+//current_extent of a surface whose size follows the swapchain's instead of the reverse
+pub const UNDEFINED_EXTENT: u32 = 0xFFFFFFFF;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExtentDecision {
+    //the surface's current_extent, which the swapchain must match. It may still differ
+    //from the requested extent while a high-DPI window is being resized or rescaled.
+    Surface,
+    //the extent is undefined and the requested one fits the surface as is
+    Requested,
+    //the extent is undefined and the requested one was clamped to the surface's limits
+    Clamped,
+    //the extent is undefined and none was requested, min_image_extent is used
+    Minimum,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ExtentNegotiation {
+    pub requested: Option<(u32, u32)>,
+    pub extent: (u32, u32),
+    pub decision: ExtentDecision,
+}
+
+impl ExtentNegotiation {
+    //A mismatch means the window and surface disagree, recreating again right away
+    //would end up with the same extent and another Suboptimal
+    pub fn matches_request(&self) -> bool {
+        self.requested.is_none_or(|requested| requested == self.extent)
+    }
+
+    //A minimized window, no swapchain can be created until it is restored
+    pub fn is_empty(&self) -> bool {
+        self.extent.0 == 0 || self.extent.1 == 0
+    }
+}
+
+//Picks the swapchain extent for a surface. The surface's current_extent wins whenever it
+//is defined, otherwise the requested extent is clamped to min_image_extent..=max_image_extent.
+pub fn negotiate_extent(
+    capabilities: &vk::SurfaceCapabilities,
+    requested: Option<(u32, u32)>,
+) -> ExtentNegotiation {
+    let current = capabilities.current_extent;
+
+    if current.0 != UNDEFINED_EXTENT && current.1 != UNDEFINED_EXTENT {
+        return ExtentNegotiation {
+            requested,
+            extent: current,
+            decision: ExtentDecision::Surface,
+        };
+    }
+
+    let (min, max) = (capabilities.min_image_extent, capabilities.max_image_extent);
+
+    let Some(requested_extent) = requested else {
+        return ExtentNegotiation {
+            requested,
+            extent: min,
+            decision: ExtentDecision::Minimum,
+        };
+    };
+
+    let extent = (
+        requested_extent.0.clamp(min.0, max.0),
+        requested_extent.1.clamp(min.1, max.1),
+    );
+
+    let decision = if extent == requested_extent {
+        ExtentDecision::Requested
+    } else {
+        ExtentDecision::Clamped
+    };
+
+    ExtentNegotiation {
+        requested,
+        extent,
+        decision,
+    }
+}
+
+impl ExtentNegotiation {
+    //What a swapchain created for surface right now would end up with
+    pub fn query(device: &Device, surface: &Surface, requested: Option<(u32, u32)>) -> Self {
+        let Device::Vulkan {
+            physical_device, ..
+        } = device
+        else {
+            panic!("not a vulkan device");
+        };
+
+        let Surface::Vulkan { surface } = surface else {
+            panic!("not a vulkan surface");
+        };
+
+        negotiate_extent(&physical_device.surface_capabilities(surface), requested)
+    }
+}
+
+//A window's logical size in physical pixels, rounded to the nearest like the windowing
+//systems do, so the request lines up with the surface's current_extent
+pub fn physical_extent(logical: (f64, f64), scale_factor: f64) -> (u32, u32) {
+    (
+        (logical.0 * scale_factor).round().max(0.0) as u32,
+        (logical.1 * scale_factor).round().max(0.0) as u32,
+    )
+}
+
+//Falls back along the list when the surface lacks the present mode, Fifo is the one
+//mode every surface supports
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum VsyncMode {
+    //Immediate, may tear. Falls back to Mailbox then Fifo.
+    #[default]
+    Off,
+    //Mailbox, no tearing and the newest frame replaces queued ones
+    Mailbox,
+    //Fifo, waits for the vertical blank
+    On,
+    //FifoRelaxed, tears only when a frame is late
+    Adaptive,
+}
+
+impl VsyncMode {
+    fn choose_present_mode(self, present_modes: &[vk::PresentMode]) -> vk::PresentMode {
+        let preferred: &[vk::PresentMode] = match self {
+            Self::Off => &[vk::PresentMode::Immediate, vk::PresentMode::Mailbox],
+            Self::Mailbox => &[vk::PresentMode::Mailbox],
+            Self::On => &[],
+            Self::Adaptive => &[vk::PresentMode::FifoRelaxed],
+        };
+
+        preferred
+            .iter()
+            .copied()
+            .find(|mode| present_modes.contains(mode))
+            .unwrap_or(vk::PresentMode::Fifo)
+    }
+}
+
+//Scaling a surface supports for the swapchain's present mode
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PresentScaling {
+    pub one_to_one: bool,
+    pub aspect_ratio_stretch: bool,
+    pub stretch: bool,
+    //placed in the middle on both axes rather than a corner
+    pub centered: bool,
+    pub min_extent: (u32, u32),
+    pub max_extent: (u32, u32),
 }
 
 pub struct SwapchainImageFetch<'a> {
@@ -14,6 +180,7 @@ pub struct SwapchainImageFetch<'a> {
     pub surface: &'a Surface,
 }
 
+//Thread affine like the Device it was created from, present on that thread
 #[non_exhaustive]
 pub enum Swapchain {
     Vulkan {
@@ -22,7 +189,22 @@ pub enum Swapchain {
         surface: Rc<vk::Surface>,
         swapchain: vk::Swapchain,
         image_available_semaphore: Rc<RefCell<vk::Semaphore>>,
+        render_finished_semaphore: Rc<RefCell<vk::Semaphore>>,
+        dedicated_sync: bool,
+        //see Device::present_queue_family
+        present_queue_family: u32,
+        //signaled once the last present completed, with Device::present_fences_supported
+        present_fence: Option<RefCell<vk::Fence>>,
+        present_mode: vk::PresentMode,
         image_index: u32,
+        suboptimal: bool,
+        extent: (u32, u32),
+        extent_negotiation: ExtentNegotiation,
+        generation: FramebufferGeneration,
+        usage: ImageUsage,
+        format: vk::Format,
+        vsync: VsyncMode,
+        hdr_metadata: Option<HdrMetadata>,
     },
 }
 
@@ -32,79 +214,251 @@ impl Swapchain {
             Device::Vulkan {
                 physical_device,
                 device,
+                queue_family_index,
                 image_available_semaphore,
+                render_finished_semaphore,
                 ..
             } => {
+                let present_queue_family = info
+                    .device
+                    .present_queue_family(info.surface)
+                    .expect("surface cannot be presented from any of the device's queues");
+
+                //presented from another family than drawn on, shared by both instead of
+                //transferring ownership for every frame
+                let queue_family_indices = [*queue_family_index, present_queue_family];
+
+                let (image_sharing_mode, queue_family_indices) =
+                    if present_queue_family == *queue_family_index {
+                        (vk::SharingMode::Exclusive, &[][..])
+                    } else {
+                        (vk::SharingMode::Concurrent, &queue_family_indices[..])
+                    };
+
                 let surface = if let Surface::Vulkan { surface } = info.surface {
                     surface
                 } else {
                     panic!("not a vulkan surface");
                 };
 
+                let capabilities = physical_device.surface_capabilities(&surface);
+
+                let extent_negotiation = negotiate_extent(&capabilities, info.extent);
+
                 let vk::SurfaceCapabilities {
                     mut min_image_count,
                     current_transform: pre_transform,
-                    current_extent: image_extent,
+                    supported_usage_flags,
                     ..
-                } = physical_device.surface_capabilities(&surface);
+                } = capabilities;
+
+                let image_extent = extent_negotiation.extent;
+
+                let supported_usage = ImageUsage::from_vk(supported_usage_flags);
+
+                if !supported_usage.contains(info.usage) {
+                    panic!(
+                        "swapchain image usage {:?} is not supported by the surface, which supports {:?}",
+                        info.usage, supported_usage
+                    );
+                }
+
+                #[allow(unused_mut)]
+                let mut usage = info.usage;
+
+                //screenshots copy out of the swapchain images
+                #[cfg(feature = "capture")]
+                if supported_usage.contains(ImageUsage::TRANSFER_SRC) {
+                    usage |= ImageUsage::TRANSFER_SRC;
+                } else {
+                    log::warn!(
+                        "swapchain images do not support TRANSFER_SRC, they cannot be captured"
+                    );
+                }
 
                 min_image_count += 1;
 
+                let surface_formats = physical_device
+                    .surface_formats(&surface)
+                    .expect("failed to get surface formats");
+
                 let vk::SurfaceFormat {
                     format: image_format,
                     color_space: image_color_space,
-                } = physical_device.surface_format(&surface);
+                } = info.encoding.choose_surface_format(&surface_formats);
 
-                let present_mode = vk::PresentMode::Immediate;
+                let present_modes = physical_device
+                    .surface_present_modes(&surface)
+                    .expect("failed to get surface present modes");
 
-                let old_swapchain = info.old.map(|old| match old {
-                    Self::Vulkan { swapchain, .. } => swapchain,
-                    _ => panic!("not a vulkan swapchain"),
-                });
+                let present_mode = info.vsync.choose_present_mode(&present_modes);
+
+                //the old swapchain is only retired once its last present is done. Without
+                //present fences that is only known once the device is idle.
+                if info.old.as_ref().is_some_and(|old| !old.wait_presented()) {
+                    info.device.wait_idle();
+                }
+
+                //framebuffers of the old swapchain become stale. Its semaphores are taken
+                //over, an acquire of the skipped frame may still signal them.
+                let (old_swapchain, generation, old_semaphores) = match info.old {
+                    Some(Self::Vulkan {
+                        swapchain,
+                        generation,
+                        image_available_semaphore,
+                        render_finished_semaphore,
+                        dedicated_sync: true,
+                        ..
+                    }) => {
+                        generation.advance();
+
+                        (
+                            Some(swapchain),
+                            generation,
+                            Some((image_available_semaphore, render_finished_semaphore)),
+                        )
+                    }
+                    Some(Self::Vulkan {
+                        swapchain,
+                        generation,
+                        ..
+                    }) => {
+                        generation.advance();
+
+                        (Some(swapchain), generation, None)
+                    }
+                    None => (None, FramebufferGeneration::new(), None),
+                };
+
+                let (image_available_semaphore, render_finished_semaphore) =
+                    match (old_semaphores, info.dedicated_sync) {
+                        (Some(semaphores), true) => semaphores,
+                        (None, true) => (
+                            Self::new_vk_semaphore(device.clone()),
+                            Self::new_vk_semaphore(device.clone()),
+                        ),
+                        (old_semaphores, false) => {
+                            if let Some(old_semaphores) = old_semaphores {
+                                info.device.retire(old_semaphores);
+                            }
+
+                            (
+                                image_available_semaphore.clone(),
+                                render_finished_semaphore.clone(),
+                            )
+                        }
+                    };
 
                 let swapchain_create_info = vk::SwapchainCreateInfo {
+                    flags: 0,
                     surface,
                     min_image_count,
                     image_format,
                     image_color_space,
                     image_extent,
                     image_array_layers: 1,
-                    image_usage: vk::IMAGE_USAGE_COLOR_ATTACHMENT,
-                    //TODO support concurrent image sharing mode
-                    image_sharing_mode: vk::SharingMode::Exclusive,
-                    queue_family_indices: &[],
+                    image_usage: usage.to_vk(),
+                    image_sharing_mode,
+                    queue_family_indices,
                     pre_transform,
                     composite_alpha: vk::CompositeAlpha::Opaque,
                     present_mode,
                     clipped: true,
-                    old_swapchain,
+                    old_swapchain: old_swapchain.as_ref(),
                 };
 
                 let mut swapchain = vk::Swapchain::new(device.clone(), swapchain_create_info)
                     .expect("failed to create swapchain");
 
+                if let Some(metadata) = info.hdr_metadata {
+                    Self::set_vk_hdr_metadata(info.device, &swapchain, metadata);
+                }
+
+                //the last frame may still be presenting from its images
+                if let Some(old_swapchain) = old_swapchain {
+                    info.device.retire(old_swapchain);
+                }
+
                 Self::Vulkan {
                     physical_device: physical_device.clone(),
                     device: device.clone(),
                     surface: surface.clone(),
-                    image_available_semaphore: image_available_semaphore.clone(),
+                    image_available_semaphore,
+                    render_finished_semaphore,
+                    dedicated_sync: info.dedicated_sync,
+                    present_queue_family,
+                    present_fence: info.device.present_fences_supported().then(|| {
+                        //created signaled, nothing to wait for before the first present
+                        let fence = vk::Fence::new(device.clone(), vk::FenceCreateInfo {})
+                            .expect("failed to create present fence");
+
+                        RefCell::new(fence)
+                    }),
+                    present_mode,
                     swapchain,
                     image_index: 0,
+                    suboptimal: false,
+                    extent: image_extent,
+                    extent_negotiation,
+                    generation,
+                    usage,
+                    format: image_format,
+                    vsync: info.vsync,
+                    hdr_metadata: info.hdr_metadata,
                 }
             }
         }
     }
 
+    //Tells the display how to tone map what is presented from the next present on. Remembered
+    //either way but only sent with Device::hdr_metadata_supported, and only meaningful
+    //for HDR color spaces.
+    pub fn set_hdr_metadata(&mut self, device: &Device, metadata: HdrMetadata) {
+        match self {
+            Self::Vulkan {
+                swapchain,
+                hdr_metadata,
+                ..
+            } => {
+                Self::set_vk_hdr_metadata(device, swapchain, metadata);
+
+                *hdr_metadata = Some(metadata);
+            }
+        }
+    }
+
+    pub fn hdr_metadata(&self) -> Option<HdrMetadata> {
+        match self {
+            Self::Vulkan { hdr_metadata, .. } => *hdr_metadata,
+        }
+    }
+
+    fn set_vk_hdr_metadata(device: &Device, swapchain: &vk::Swapchain, metadata: HdrMetadata) {
+        if !device.hdr_metadata_supported() {
+            return;
+        }
+
+        swapchain
+            .set_hdr_metadata(&metadata.to_vk())
+            .expect("failed to set hdr metadata");
+    }
+
+    fn new_vk_semaphore(device: Rc<vk::Device>) -> Rc<RefCell<vk::Semaphore>> {
+        let semaphore = vk::Semaphore::new(device, vk::SemaphoreCreateInfo {})
+            .expect("failed to create semaphore");
+
+        Rc::new(RefCell::new(semaphore))
+    }
+
     pub fn images(&self) -> Vec<Image> {
         match self {
             Self::Vulkan {
-                physical_device,
                 device,
-                surface,
                 swapchain,
+                format,
                 ..
             } => {
-                let vk::SurfaceFormat { format, .. } = physical_device.surface_format(&surface);
+                let format = *format;
 
                 swapchain
                     .images()
@@ -115,6 +469,7 @@ impl Swapchain {
                             &image,
                             format,
                             vk::ImageViewType::TwoDim,
+                            1,
                         );
 
                         Image::Vulkan {
@@ -130,24 +485,243 @@ impl Swapchain {
         }
     }
 
+    pub fn extent(&self) -> (u32, u32) {
+        match self {
+            Self::Vulkan { extent, .. } => *extent,
+        }
+    }
+
+    //How extent was picked, compare it against the window to tell a resize the surface
+    //has not caught up with from one that needs a recreate
+    pub fn extent_negotiation(&self) -> ExtentNegotiation {
+        match self {
+            Self::Vulkan { extent_negotiation, .. } => *extent_negotiation,
+        }
+    }
+
+    //The usage the images were created with, including any added for capture
+    pub fn usage(&self) -> ImageUsage {
+        match self {
+            Self::Vulkan { usage, .. } => *usage,
+        }
+    }
+
+    pub fn format(&self) -> Format {
+        match self {
+            Self::Vulkan { format, .. } => (*format).into(),
+        }
+    }
+
+    pub fn encoding(&self) -> OutputEncoding {
+        OutputEncoding::of(self.format())
+    }
+
+    //The requested mode, not necessarily the present mode the surface ended up with
+    pub fn vsync(&self) -> VsyncMode {
+        match self {
+            Self::Vulkan { vsync, .. } => *vsync,
+        }
+    }
+
+    //Pass to FramebufferInfo for framebuffers of this swapchain's images
+    pub fn generation(&self) -> &FramebufferGeneration {
+        match self {
+            Self::Vulkan { generation, .. } => generation,
+        }
+    }
+
+    pub fn has_dedicated_sync(&self) -> bool {
+        match self {
+            Self::Vulkan { dedicated_sync, .. } => *dedicated_sync,
+        }
+    }
+
+    //Blocks until the last present completed, e.g. to recycle what only that present used.
+    //False right away without Device::present_fences_supported.
+    pub fn wait_presented(&self) -> bool {
+        match self {
+            Self::Vulkan { present_fence, .. } => {
+                let Some(present_fence) = present_fence else {
+                    return false;
+                };
+
+                vk::Fence::wait(&[&mut present_fence.borrow_mut()], true, u64::MAX)
+                    .expect("failed to wait for present fence");
+
+                true
+            }
+        }
+    }
+
+    //How the surface may scale images that do not match its size, e.g. while a window is
+    //being resized. None without the surface maintenance instance extension.
+    pub fn present_scaling(&self) -> Option<PresentScaling> {
+        match self {
+            Self::Vulkan {
+                physical_device,
+                surface,
+                present_mode,
+                ..
+            } => {
+                let capabilities = physical_device
+                    .surface_present_scaling(surface, *present_mode)
+                    .ok()?;
+
+                Some(PresentScaling {
+                    one_to_one: capabilities.supported_scaling & vk::PRESENT_SCALING_ONE_TO_ONE
+                        != 0,
+                    aspect_ratio_stretch: capabilities.supported_scaling
+                        & vk::PRESENT_SCALING_ASPECT_RATIO_STRETCH
+                        != 0,
+                    stretch: capabilities.supported_scaling & vk::PRESENT_SCALING_STRETCH != 0,
+                    centered: capabilities.supported_gravity_x
+                        & capabilities.supported_gravity_y
+                        & vk::PRESENT_GRAVITY_CENTERED
+                        != 0,
+                    min_extent: capabilities.min_scaled_image_extent,
+                    max_extent: capabilities.max_scaled_image_extent,
+                })
+            }
+        }
+    }
+
+    //A suboptimal swapchain still hands out the image, see is_suboptimal. With present
+    //fences it first waits for the last present to release the render finished semaphore.
     pub fn acquire(&mut self) -> Result<u32, Error> {
+        self.wait_presented();
+
         match self {
             Self::Vulkan {
                 swapchain,
                 image_available_semaphore,
                 image_index,
+                suboptimal,
                 ..
             } => {
-                *image_index = swapchain
+                (*image_index, *suboptimal) = swapchain
                     .acquire_next_image(
                         u64::MAX,
                         Some(&mut image_available_semaphore.borrow_mut()),
                         None,
                     )
-                    .map_err(|_| Error::Acquisition)?;
+                    .map_err(|error| match error {
+                        vk::Error::OutOfDate => Error::OutOfDate,
+                        _ => Error::Acquisition,
+                    })?;
 
                 Ok(*image_index)
             }
         }
     }
+
+    //The last acquire succeeded but the swapchain no longer matches the surface exactly,
+    //recreate it once the image has been presented
+    pub fn is_suboptimal(&self) -> bool {
+        match self {
+            Self::Vulkan { suboptimal, .. } => *suboptimal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capabilities(
+        current: (u32, u32),
+        min: (u32, u32),
+        max: (u32, u32),
+    ) -> vk::SurfaceCapabilities {
+        vk::SurfaceCapabilities {
+            min_image_count: 2,
+            max_image_count: 0,
+            current_extent: current,
+            min_image_extent: min,
+            max_image_extent: max,
+            max_image_array_layers: 1,
+            supported_transforms: 0,
+            current_transform: 0,
+            supported_composite_alpha: 0,
+            supported_usage_flags: 0,
+        }
+    }
+
+    const UNDEFINED: (u32, u32) = (UNDEFINED_EXTENT, UNDEFINED_EXTENT);
+
+    #[test]
+    fn defined_current_extent_wins() {
+        let negotiation = negotiate_extent(
+            &capabilities((800, 600), (1, 1), (4096, 4096)),
+            Some((1600, 1200)),
+        );
+
+        assert_eq!(negotiation.extent, (800, 600));
+        assert_eq!(negotiation.decision, ExtentDecision::Surface);
+        assert!(!negotiation.matches_request());
+    }
+
+    #[test]
+    fn minimized_surface_is_empty() {
+        let negotiation = negotiate_extent(
+            &capabilities((0, 0), (0, 0), (4096, 4096)),
+            Some((800, 600)),
+        );
+
+        assert_eq!(negotiation.decision, ExtentDecision::Surface);
+        assert!(negotiation.is_empty());
+    }
+
+    #[test]
+    fn requested_extent_within_limits_is_used() {
+        let negotiation = negotiate_extent(
+            &capabilities(UNDEFINED, (1, 1), (4096, 4096)),
+            Some((800, 600)),
+        );
+
+        assert_eq!(negotiation.extent, (800, 600));
+        assert_eq!(negotiation.decision, ExtentDecision::Requested);
+        assert!(negotiation.matches_request());
+    }
+
+    #[test]
+    fn requested_extent_is_clamped_per_axis() {
+        let negotiation = negotiate_extent(
+            &capabilities(UNDEFINED, (64, 64), (1024, 1024)),
+            Some((16, 2048)),
+        );
+
+        assert_eq!(negotiation.extent, (64, 1024));
+        assert_eq!(negotiation.decision, ExtentDecision::Clamped);
+        assert!(!negotiation.matches_request());
+    }
+
+    #[test]
+    fn zero_request_is_clamped_to_min() {
+        let negotiation =
+            negotiate_extent(&capabilities(UNDEFINED, (1, 1), (4096, 4096)), Some((0, 0)));
+
+        assert_eq!(negotiation.extent, (1, 1));
+        assert_eq!(negotiation.decision, ExtentDecision::Clamped);
+        assert!(!negotiation.is_empty());
+    }
+
+    #[test]
+    fn zero_request_within_zero_min_is_empty() {
+        let negotiation = negotiate_extent(
+            &capabilities(UNDEFINED, (0, 0), (4096, 4096)),
+            Some((0, 600)),
+        );
+
+        assert_eq!(negotiation.decision, ExtentDecision::Requested);
+        assert!(negotiation.is_empty());
+    }
+
+    #[test]
+    fn no_request_uses_min() {
+        let negotiation = negotiate_extent(&capabilities(UNDEFINED, (32, 16), (4096, 4096)), None);
+
+        assert_eq!(negotiation.extent, (32, 16));
+        assert_eq!(negotiation.decision, ExtentDecision::Minimum);
+        assert!(negotiation.matches_request());
+    }
 }
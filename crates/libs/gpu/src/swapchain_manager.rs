@@ -0,0 +1,202 @@
+use crate::prelude::*;
+
+use std::mem;
+
+//acquires in a row that may find the swapchain out of date before giving up
+const MAX_ACQUIRE_ATTEMPTS: usize = 3;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SwapchainChange {
+    pub generation: u64,
+    pub extent: (u32, u32),
+    pub image_count: usize,
+    pub extent_changed: bool,
+    pub image_count_changed: bool,
+}
+
+pub struct SwapchainManagerInfo<'a> {
+    pub device: &'a Device,
+    pub surface: &'a Surface,
+    pub usage: ImageUsage,
+    pub encoding: OutputEncoding,
+    pub vsync: VsyncMode,
+    //see SwapchainInfo::extent
+    pub extent: Option<(u32, u32)>,
+    pub hdr_metadata: Option<HdrMetadata>,
+    //see SwapchainInfo::dedicated_sync, for one manager per window of a multi-window editor
+    pub dedicated_sync: bool,
+}
+
+//Owns the swapchain and its images and recreates them when the surface changes, on
+//OutOfDate and Suboptimal or after resize. The replaced swapchain and images are retired
+//to the device's deletion queue, framebuffers of them show up as stale.
+pub struct SwapchainManager {
+    images: Vec<Image>,
+    swapchain: Option<Swapchain>,
+    usage: ImageUsage,
+    encoding: OutputEncoding,
+    vsync: VsyncMode,
+    extent: Option<(u32, u32)>,
+    //reapplied to every new swapchain
+    hdr_metadata: Option<HdrMetadata>,
+    dedicated_sync: bool,
+    //recreate before the next acquire
+    outdated: bool,
+    //the last recreation, until taken
+    change: Option<SwapchainChange>,
+}
+
+impl SwapchainManager {
+    pub fn new(info: SwapchainManagerInfo<'_>) -> Self {
+        let swapchain = Swapchain::new(SwapchainInfo {
+            device: info.device,
+            surface: info.surface,
+            old: None,
+            usage: info.usage,
+            encoding: info.encoding,
+            vsync: info.vsync,
+            extent: info.extent,
+            hdr_metadata: info.hdr_metadata,
+            dedicated_sync: info.dedicated_sync,
+        });
+
+        let images = swapchain.images();
+
+        Self {
+            images,
+            swapchain: Some(swapchain),
+            usage: info.usage,
+            encoding: info.encoding,
+            vsync: info.vsync,
+            extent: info.extent,
+            hdr_metadata: info.hdr_metadata,
+            dedicated_sync: info.dedicated_sync,
+            outdated: false,
+            change: None,
+        }
+    }
+
+    //Recreates first when the swapchain is outdated. Fails with OutOfDate while the window
+    //is minimized, skip the frame and try again later.
+    pub fn acquire(&mut self, device: &Device, surface: &Surface) -> Result<u32, Error> {
+        for _ in 0..MAX_ACQUIRE_ATTEMPTS {
+            if self.outdated {
+                if ExtentNegotiation::query(device, surface, self.extent).is_empty() {
+                    return Err(Error::OutOfDate);
+                }
+
+                self.recreate(device, surface);
+            }
+
+            match self.swapchain_mut().acquire() {
+                Ok(image_index) => {
+                    //presented first, the image is acquired already
+                    if self.swapchain().is_suboptimal() {
+                        self.outdated = true;
+                    }
+
+                    return Ok(image_index);
+                }
+                Err(Error::OutOfDate) => self.outdated = true,
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(Error::OutOfDate)
+    }
+
+    //OutOfDate and Suboptimal are handled by recreating on the next acquire
+    pub fn present(&mut self, device: &mut Device) -> Result<(), Error> {
+        match device.present(self.swapchain()) {
+            Ok(()) => Ok(()),
+            Err(Error::OutOfDate | Error::Suboptimal) => {
+                self.outdated = true;
+
+                Ok(())
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    //The window's new size in physical pixels, the swapchain is recreated on the next acquire
+    pub fn resize(&mut self, extent: (u32, u32)) {
+        if self.extent != Some(extent) {
+            self.extent = Some(extent);
+            self.outdated = true;
+        }
+    }
+
+    pub fn set_vsync(&mut self, vsync: VsyncMode) {
+        if self.vsync != vsync {
+            self.vsync = vsync;
+            self.outdated = true;
+        }
+    }
+
+    //Applies to the current swapchain right away and to those it is recreated as
+    pub fn set_hdr_metadata(&mut self, device: &Device, metadata: HdrMetadata) {
+        self.hdr_metadata = Some(metadata);
+
+        self.swapchain_mut().set_hdr_metadata(device, metadata);
+    }
+
+    //Recreates right away, passing the old swapchain on to the new one
+    pub fn recreate(&mut self, device: &Device, surface: &Surface) {
+        let old = self.swapchain.take().expect("swapchain is being recreated");
+
+        let old_extent = old.extent();
+        let old_image_count = self.images.len();
+
+        device.retire(mem::take(&mut self.images));
+
+        let swapchain = Swapchain::new(SwapchainInfo {
+            device,
+            surface,
+            old: Some(old),
+            usage: self.usage,
+            encoding: self.encoding,
+            vsync: self.vsync,
+            extent: self.extent,
+            hdr_metadata: self.hdr_metadata,
+            dedicated_sync: self.dedicated_sync,
+        });
+
+        self.images = swapchain.images();
+
+        self.change = Some(SwapchainChange {
+            generation: swapchain.generation().get(),
+            extent: swapchain.extent(),
+            image_count: self.images.len(),
+            extent_changed: swapchain.extent() != old_extent,
+            image_count_changed: self.images.len() != old_image_count,
+        });
+
+        self.swapchain = Some(swapchain);
+        self.outdated = false;
+    }
+
+    //The last recreation since this was called, for rebuilding what depends on the images
+    pub fn take_change(&mut self) -> Option<SwapchainChange> {
+        self.change.take()
+    }
+
+    pub fn swapchain(&self) -> &Swapchain {
+        self.swapchain.as_ref().expect("swapchain is being recreated")
+    }
+
+    fn swapchain_mut(&mut self) -> &mut Swapchain {
+        self.swapchain.as_mut().expect("swapchain is being recreated")
+    }
+
+    pub fn images(&self) -> &[Image] {
+        &self.images
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.swapchain().generation().get()
+    }
+
+    pub fn vsync(&self) -> VsyncMode {
+        self.vsync
+    }
+}
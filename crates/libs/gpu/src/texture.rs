@@ -0,0 +1,327 @@
+use crate::prelude::*;
+
+use std::rc::Rc;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AddressMode {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    ClampToBorder,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SamplerDesc {
+    pub filter: Filter,
+    pub address_mode: AddressMode,
+    //max samples of anisotropic filtering, 0 or 1 to disable. Clamped to
+    //Device::max_anisotropy and ignored where anisotropy is unsupported.
+    pub anisotropy: u32,
+}
+
+//Same sampling as a plain Image
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        Self {
+            filter: Filter::Nearest,
+            address_mode: AddressMode::ClampToBorder,
+            anisotropy: 0,
+        }
+    }
+}
+
+pub struct TextureInfo<'a> {
+    pub device: &'a mut Device,
+    pub format: Format,
+    pub extent: (u32, u32),
+    //tightly packed rows of format texels
+    pub pixels: &'a [u8],
+    pub sampler: SamplerDesc,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Ktx2Error {
+    InvalidIdentifier,
+    Truncated,
+    //the vkFormat of the file has no matching Format
+    UnsupportedFormat(u32),
+    Supercompressed,
+    //array, cube map or 3d textures
+    UnsupportedShape,
+}
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+//A sampled 2d image with its own sampler and the layout it was last transitioned to, for
+//the common case of uploading pixels once and binding them to a shader
+pub struct Texture {
+    image: Image,
+    format: Format,
+    extent: (u32, u32),
+    layout: ImageLayout,
+}
+
+impl Texture {
+    pub fn new(info: TextureInfo<'_>) -> Self {
+        let mut image = Image::new(ImageInfo {
+            device: info.device,
+            format: info.format,
+            usage: ImageUsage::SAMPLED | ImageUsage::TRANSFER_DST,
+            ty: ImageType::TwoDim,
+            extent: (info.extent.0, info.extent.1, 1),
+            samples: SampleCount::One,
+            priority: MemoryPriority::Default,
+        });
+
+        match info.device {
+            Device::Vulkan { device, .. } => {
+                let Image::Vulkan { sampler, .. } = &mut image else {
+                    panic!("not a vulkan image")
+                };
+
+                *sampler = Self::new_vk_sampler(device.clone(), info.sampler);
+            }
+        }
+
+        let mut texture = Self {
+            image,
+            format: info.format,
+            extent: info.extent,
+            layout: ImageLayout::Undefined,
+        };
+
+        texture.write(info.device, info.pixels);
+
+        texture
+    }
+
+    //Loads the base level of an uncompressed, non supercompressed 2d KTX2 file
+    pub fn from_ktx2(
+        device: &mut Device,
+        bytes: &[u8],
+        sampler: SamplerDesc,
+    ) -> Result<Self, Ktx2Error> {
+        if bytes.len() < 80 + 24 {
+            return Err(if bytes.starts_with(&KTX2_IDENTIFIER) {
+                Ktx2Error::Truncated
+            } else {
+                Ktx2Error::InvalidIdentifier
+            });
+        }
+
+        if bytes[..12] != KTX2_IDENTIFIER {
+            return Err(Ktx2Error::InvalidIdentifier);
+        }
+
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        let u64_at =
+            |offset: usize| u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+
+        let vk_format = u32_at(12);
+        let width = u32_at(20);
+        let height = u32_at(24).max(1);
+
+        if u32_at(28) > 1 || u32_at(32) > 1 || u32_at(36) > 1 {
+            return Err(Ktx2Error::UnsupportedShape);
+        }
+
+        if u32_at(44) != 0 {
+            return Err(Ktx2Error::Supercompressed);
+        }
+
+        let format = match vk_format {
+            37 => Format::Rgba8Unorm,
+            43 => Format::Rgba8Srgb,
+            50 => Format::Bgra8Srgb,
+            74 => Format::R16Uint,
+            98 => Format::R32Uint,
+            100 => Format::R32Sfloat,
+            103 => Format::Rg32Sfloat,
+            104 => Format::Rgb32Uint,
+            106 => Format::Rgb32Sfloat,
+            109 => Format::Rgba32Sfloat,
+            _ => return Err(Ktx2Error::UnsupportedFormat(vk_format)),
+        };
+
+        //the level index starts with the base level
+        let offset = u64_at(80) as usize;
+        let length = format.size((width, height));
+
+        let end = offset.checked_add(length).ok_or(Ktx2Error::Truncated)?;
+
+        if u64_at(88) < length as u64 || bytes.len() < end {
+            return Err(Ktx2Error::Truncated);
+        }
+
+        Ok(Self::new(TextureInfo {
+            device,
+            format,
+            extent: (width, height),
+            pixels: &bytes[offset..end],
+            sampler,
+        }))
+    }
+
+    //Replaces every texel and leaves the texture in ShaderReadOnly
+    pub fn write(&mut self, device: &mut Device, pixels: &[u8]) {
+        let size = self.format.size(self.extent);
+
+        if pixels.len() != size {
+            panic!("pixels do not match the texture extent and format");
+        }
+
+        let mut staging = Buffer::new(BufferInfo {
+            device,
+            usage: BufferUsage::TRANSFER_SRC,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size,
+            priority: MemoryPriority::Default,
+        });
+
+        staging.write(BufferWrite {
+            offset: 0,
+            data: pixels,
+        });
+
+        device.copy_buffer_to_image(BufferImageCopy {
+            from: &staging,
+            to: &mut self.image,
+            src: 0,
+            dst_extent: (self.extent.0, self.extent.1, 1),
+            dst_offset: (0, 0, 0),
+        });
+
+        self.layout = ImageLayout::ShaderReadOnly;
+    }
+
+    //Records the barrier from the tracked layout, e.g. to General for compute writes
+    pub fn transition(
+        &mut self,
+        commands: &mut Commands<'_>,
+        layout: ImageLayout,
+        src_stage: PipelineStage,
+        dst_stage: PipelineStage,
+    ) {
+        commands.pipeline_barrier(
+            src_stage,
+            dst_stage,
+            &[Barrier::Image {
+                src_access: Self::layout_access(self.layout),
+                dst_access: Self::layout_access(layout),
+                old_layout: self.layout,
+                new_layout: layout,
+                image: &self.image,
+            }],
+        );
+
+        self.layout = layout;
+    }
+
+    fn layout_access(layout: ImageLayout) -> Access {
+        match layout {
+            ImageLayout::General => Access::SHADER_READ | Access::SHADER_WRITE,
+            ImageLayout::ShaderReadOnly => Access::SHADER_READ,
+            ImageLayout::TransferSrc => Access::TRANSFER_READ,
+            ImageLayout::TransferDst => Access::TRANSFER_WRITE,
+            ImageLayout::ColorAttachment => {
+                Access::COLOR_ATTACHMENT_READ | Access::COLOR_ATTACHMENT_WRITE
+            }
+            ImageLayout::DepthStencilAttachment => {
+                Access::DEPTH_STENCIL_ATTACHMENT_READ | Access::DEPTH_STENCIL_ATTACHMENT_WRITE
+            }
+            ImageLayout::DepthStencilReadOnly => Access::DEPTH_STENCIL_ATTACHMENT_READ,
+            ImageLayout::Undefined | ImageLayout::Preinitialized | ImageLayout::PresentSrc => {
+                Access::empty()
+            }
+        }
+    }
+
+    //Combined image sampler at the tracked layout, for Pipeline::bind
+    pub fn descriptor_info(&self, binding: u32) -> Binding<'_> {
+        Binding::Image {
+            binding,
+            ty: DescriptorType::CombinedImageSampler,
+            layout: self.layout,
+            image: &self.image,
+        }
+    }
+
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn extent(&self) -> (u32, u32) {
+        self.extent
+    }
+
+    pub fn layout(&self) -> ImageLayout {
+        self.layout
+    }
+
+    //Replaces the sampler, e.g. when the anisotropy setting changes. The old sampler
+    //must no longer be in use, and descriptor sets have to be bound again.
+    pub fn set_sampler(&mut self, device: &Device, desc: SamplerDesc) {
+        match device {
+            Device::Vulkan { device, .. } => {
+                let Image::Vulkan { sampler, .. } = &mut self.image else {
+                    panic!("not a vulkan image")
+                };
+
+                *sampler = Self::new_vk_sampler(device.clone(), desc);
+            }
+        }
+    }
+
+    fn new_vk_sampler(device: Rc<vk::Device>, desc: SamplerDesc) -> vk::Sampler {
+        let filter = match desc.filter {
+            Filter::Nearest => vk::Filter::Nearest,
+            Filter::Linear => vk::Filter::Linear,
+        };
+
+        let address_mode = match desc.address_mode {
+            AddressMode::Repeat => vk::SamplerAddressMode::Repeat,
+            AddressMode::MirroredRepeat => vk::SamplerAddressMode::MirroredRepeat,
+            AddressMode::ClampToEdge => vk::SamplerAddressMode::ClampToEdge,
+            AddressMode::ClampToBorder => vk::SamplerAddressMode::ClampToBorder,
+        };
+
+        let max_anisotropy = if device.enabled_features().sampler_anisotropy {
+            (desc.anisotropy as f32).min(device.limits().max_sampler_anisotropy)
+        } else {
+            0.0
+        };
+
+        let sampler_create_info = vk::SamplerCreateInfo {
+            mag_filter: filter,
+            min_filter: filter,
+            mipmap_mode: vk::SamplerMipmapMode::Nearest,
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            mip_lod_bias: 0.0,
+            anisotropy_enable: max_anisotropy > 1.0,
+            max_anisotropy,
+            compare_enable: false,
+            compare_op: vk::CompareOp::Always,
+            min_lod: 0.0,
+            max_lod: 0.0,
+            border_color: vk::BorderColor::IntTransparentBlack,
+            unnormalized_coordinates: false,
+        };
+
+        vk::Sampler::new(device, sampler_create_info).expect("failed to create sampler")
+    }
+}
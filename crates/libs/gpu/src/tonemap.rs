@@ -0,0 +1,317 @@
+use crate::compute::{compute_pipeline, storage_binding, storage_descriptor};
+use crate::prelude::*;
+
+use std::mem;
+
+const BINS: usize = 256;
+const TILE: u32 = 16;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TonemapOperator {
+    Aces,
+    Reinhard,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct LuminanceConstants {
+    width: u32,
+    height: u32,
+    min_log_luminance: f32,
+    log_luminance_range: f32,
+    adaptation: f32,
+}
+
+unsafe impl Pod for LuminanceConstants {}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct TonemapConstants {
+    resolution: [f32; 2],
+    exposure_bias: f32,
+    operator: u32,
+}
+
+unsafe impl Pod for TonemapConstants {}
+
+pub struct LuminanceHistogramInfo<'a> {
+    pub device: &'a Device,
+    pub descriptor_set_count: u32,
+    pub min_log_luminance: f32,
+    pub max_log_luminance: f32,
+    //seconds for the eye to adapt most of the way to a new brightness
+    pub adaptation_time: f32,
+}
+
+//Builds a log luminance histogram of an hdr image and reduces it
+//to a temporally adapted exposure, kept in exposure() as [average, exposure].
+pub struct LuminanceHistogram {
+    histogram: Pipeline,
+    reduce: Pipeline,
+    bins: Buffer,
+    exposure: Buffer,
+    min_log_luminance: f32,
+    log_luminance_range: f32,
+    adaptation_time: f32,
+}
+
+impl LuminanceHistogram {
+    pub fn new(info: LuminanceHistogramInfo<'_>) -> Self {
+        let push_constant_size = mem::size_of::<LuminanceConstants>() as _;
+
+        let histogram = compute_pipeline(
+            info.device,
+            "luminance.comp",
+            include_str!("shaders/luminance.comp.glsl"),
+            info.descriptor_set_count,
+            &[
+                Descriptor {
+                    binding: 0,
+                    ty: DescriptorType::CombinedImageSampler,
+                    count: 1,
+                    stage: ShaderStage::COMPUTE,
+                },
+                storage_descriptor(1),
+            ],
+            push_constant_size,
+        );
+
+        let mut reduce = compute_pipeline(
+            info.device,
+            "exposure.comp",
+            include_str!("shaders/exposure.comp.glsl"),
+            1,
+            &[storage_descriptor(0), storage_descriptor(1)],
+            push_constant_size,
+        );
+
+        let mut bins = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::STORAGE,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: BINS * mem::size_of::<u32>(),
+            priority: MemoryPriority::Default,
+        });
+
+        bins.write(BufferWrite {
+            offset: 0,
+            data: &[0u32; BINS],
+        });
+
+        let mut exposure = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::STORAGE,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: 2 * mem::size_of::<f32>(),
+            priority: MemoryPriority::Default,
+        });
+
+        exposure.write(BufferWrite {
+            offset: 0,
+            data: &[0.0f32, 1.0],
+        });
+
+        reduce.bind(
+            0,
+            &[
+                storage_binding(0, &bins, BINS * mem::size_of::<u32>()),
+                storage_binding(1, &exposure, 2 * mem::size_of::<f32>()),
+            ],
+        );
+
+        Self {
+            histogram,
+            reduce,
+            bins,
+            exposure,
+            min_log_luminance: info.min_log_luminance,
+            log_luminance_range: info.max_log_luminance - info.min_log_luminance,
+            adaptation_time: info.adaptation_time,
+        }
+    }
+
+    pub fn exposure(&self) -> &Buffer {
+        &self.exposure
+    }
+
+    //The hdr image must be in ShaderReadOnly layout. Leaves the exposure buffer
+    //visible to fragment shaders.
+    pub fn record(
+        &mut self,
+        commands: &mut Commands<'_>,
+        image_index: u32,
+        hdr: &Image,
+        extent: (u32, u32),
+        delta_time: f32,
+    ) {
+        self.histogram.bind(
+            image_index,
+            &[
+                Binding::Image {
+                    binding: 0,
+                    ty: DescriptorType::CombinedImageSampler,
+                    layout: ImageLayout::ShaderReadOnly,
+                    image: hdr,
+                },
+                storage_binding(1, &self.bins, BINS * mem::size_of::<u32>()),
+            ],
+        );
+
+        let adaptation = if self.adaptation_time > 0.0 {
+            1.0 - (-delta_time / self.adaptation_time).exp()
+        } else {
+            1.0
+        };
+
+        let constants = LuminanceConstants {
+            width: extent.0,
+            height: extent.1,
+            min_log_luminance: self.min_log_luminance,
+            log_luminance_range: self.log_luminance_range,
+            adaptation,
+        };
+
+        commands.bind_pipeline(image_index, &self.histogram);
+        commands.push_constants(&self.histogram, ShaderStage::COMPUTE, 0, &[constants]);
+        commands.dispatch((extent.0 + TILE - 1) / TILE, (extent.1 + TILE - 1) / TILE, 1);
+
+        commands.pipeline_barrier(
+            PipelineStage::COMPUTE_SHADER,
+            PipelineStage::COMPUTE_SHADER,
+            &[Barrier::Memory {
+                src_access: Access::SHADER_WRITE,
+                dst_access: Access::SHADER_READ | Access::SHADER_WRITE,
+            }],
+        );
+
+        commands.bind_pipeline(0, &self.reduce);
+        commands.push_constants(&self.reduce, ShaderStage::COMPUTE, 0, &[constants]);
+        commands.dispatch(1, 1, 1);
+
+        commands.pipeline_barrier(
+            PipelineStage::COMPUTE_SHADER,
+            PipelineStage::COMPUTE_SHADER | PipelineStage::FRAGMENT_SHADER,
+            &[Barrier::Memory {
+                src_access: Access::SHADER_WRITE,
+                dst_access: Access::SHADER_READ | Access::SHADER_WRITE,
+            }],
+        );
+    }
+}
+
+pub struct TonemapPassInfo<'a> {
+    pub device: &'a Device,
+    pub format: Format,
+    pub extent: (u32, u32),
+    pub descriptor_set_count: u32,
+    pub operator: TonemapOperator,
+    pub min_log_luminance: f32,
+    pub max_log_luminance: f32,
+    pub adaptation_time: f32,
+}
+
+//Reference tonemapper. Output is linear, so present it through an srgb format.
+pub struct TonemapPass {
+    pass: PostProcessPass,
+    luminance: LuminanceHistogram,
+    extent: (u32, u32),
+    pub operator: TonemapOperator,
+    pub exposure_bias: f32,
+}
+
+impl TonemapPass {
+    pub fn new(info: TonemapPassInfo<'_>) -> Self {
+        let vertex_shader = Shader::new(ShaderInfo {
+            device: info.device,
+            entry: "main",
+            input: ShaderInput::Embedded {
+                name: "fullscreen.vert",
+                source: include_str!("shaders/fullscreen.vert.glsl"),
+            },
+        });
+
+        let fragment_shader = Shader::new(ShaderInfo {
+            device: info.device,
+            entry: "main",
+            input: ShaderInput::Embedded {
+                name: "tonemap.frag",
+                source: include_str!("shaders/tonemap.frag.glsl"),
+            },
+        });
+
+        let pass = PostProcessPass::new(PostProcessInfo {
+            device: info.device,
+            vertex_shader: &vertex_shader,
+            fragment_shader: &fragment_shader,
+            format: info.format,
+            extent: info.extent,
+            descriptor_set_count: info.descriptor_set_count,
+            push_constant_size: mem::size_of::<TonemapConstants>() as _,
+            layout: &[Descriptor {
+                binding: 1,
+                ty: DescriptorType::StorageBuffer,
+                count: 1,
+                stage: ShaderStage::FRAGMENT,
+            }],
+        });
+
+        let luminance = LuminanceHistogram::new(LuminanceHistogramInfo {
+            device: info.device,
+            descriptor_set_count: info.descriptor_set_count,
+            min_log_luminance: info.min_log_luminance,
+            max_log_luminance: info.max_log_luminance,
+            adaptation_time: info.adaptation_time,
+        });
+
+        Self {
+            pass,
+            luminance,
+            extent: info.extent,
+            operator: info.operator,
+            exposure_bias: 0.0,
+        }
+    }
+
+    pub fn render_pass(&self) -> &RenderPass {
+        self.pass.render_pass()
+    }
+
+    pub fn luminance(&self) -> &LuminanceHistogram {
+        &self.luminance
+    }
+
+    //Must be recorded outside a render pass since it dispatches the histogram first.
+    pub fn record(
+        &mut self,
+        commands: &mut Commands<'_>,
+        image_index: u32,
+        hdr: &Image,
+        output: &Framebuffer,
+        delta_time: f32,
+    ) -> Result<(), Error> {
+        self.luminance
+            .record(commands, image_index, hdr, self.extent, delta_time);
+
+        let constants = TonemapConstants {
+            resolution: [self.extent.0 as f32, self.extent.1 as f32],
+            exposure_bias: self.exposure_bias,
+            operator: match self.operator {
+                TonemapOperator::Aces => 0,
+                TonemapOperator::Reinhard => 1,
+            },
+        };
+
+        self.pass.record(
+            commands,
+            image_index,
+            hdr,
+            output,
+            &[storage_binding(
+                1,
+                self.luminance.exposure(),
+                2 * mem::size_of::<f32>(),
+            )],
+            as_bytes(&constants),
+        )
+    }
+}
@@ -0,0 +1,421 @@
+use crate::prelude::*;
+
+use std::collections::HashMap;
+use std::mem;
+
+//Matches the vertex layout of both egui (epaint::Vertex) and Dear ImGui (ImDrawVert),
+//so their buffers can be reinterpreted without a copy.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct UiVertex {
+    pub position: [f32; 2],
+    pub uv: [f32; 2],
+    pub color: [u8; 4],
+}
+
+unsafe impl Pod for UiVertex {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct UiTextureId(pub u64);
+
+pub struct UiTextureUpdate<'a> {
+    pub id: UiTextureId,
+    //None replaces the whole texture, Some patches a region of the existing one
+    pub offset: Option<(u32, u32)>,
+    pub extent: (u32, u32),
+    //premultiplied rgba8 in srgb
+    pub pixels: &'a [u8],
+}
+
+pub struct UiMesh<'a> {
+    //min x, min y, max x, max y in points
+    pub clip_rect: [f32; 4],
+    pub texture: UiTextureId,
+    pub vertices: &'a [UiVertex],
+    pub indices: &'a [u16],
+}
+
+pub struct UiDrawData<'a> {
+    pub pixels_per_point: f32,
+    pub textures: &'a [UiTextureUpdate<'a>],
+    pub freed: &'a [UiTextureId],
+    pub meshes: &'a [UiMesh<'a>],
+}
+
+pub struct UiRendererInfo<'a> {
+    pub device: &'a Device,
+    pub format: Format,
+    pub extent: (u32, u32),
+    pub initial_layout: ImageLayout,
+    pub final_layout: ImageLayout,
+    pub frames_in_flight: u32,
+    pub max_textures: u32,
+    pub vertex_capacity: usize,
+    pub index_capacity: usize,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct UiConstants {
+    screen_size: [f32; 2],
+    linear_output: u32,
+}
+
+unsafe impl Pod for UiConstants {}
+
+struct UiTexture {
+    image: Image,
+    slot: u32,
+    extent: (u32, u32),
+    pixels: Vec<u8>,
+}
+
+//Draws ui meshes over an existing color image with one scissored draw per mesh.
+//Each texture owns a descriptor set slot, bound once when it is uploaded.
+pub struct UiRenderer {
+    pipeline: Pipeline,
+    render_pass: RenderPass,
+    vertices: StreamingBuffer,
+    indices: StreamingBuffer,
+    textures: HashMap<UiTextureId, UiTexture>,
+    free_slots: Vec<u32>,
+    extent: (u32, u32),
+    linear_output: bool,
+}
+
+impl UiRenderer {
+    pub fn new(info: UiRendererInfo<'_>) -> Self {
+        let render_pass = RenderPass::new(RenderPassInfo {
+            device: info.device,
+            attachments: &[Attachment {
+                format: info.format,
+                samples: SampleCount::One,
+                load_op: AttachmentLoadOp::Load,
+                store_op: AttachmentStoreOp::Store,
+                initial_layout: info.initial_layout,
+                final_layout: info.final_layout,
+                layout: ImageLayout::ColorAttachment,
+                ty: AttachmentType::Color,
+            }],
+            subpasses: &[Subpass {
+                src: None,
+                src_access: Access::empty(),
+                src_stage: PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+                dst: Some(0),
+                dst_access: Access::COLOR_ATTACHMENT_READ | Access::COLOR_ATTACHMENT_WRITE,
+                dst_stage: PipelineStage::COLOR_ATTACHMENT_OUTPUT,
+                attachments: &[0],
+            }],
+        });
+
+        let vertex_shader = Shader::new(ShaderInfo {
+            device: info.device,
+            entry: "main",
+            input: ShaderInput::Embedded {
+                name: "ui.vert",
+                source: include_str!("shaders/ui.vert.glsl"),
+            },
+        });
+
+        let fragment_shader = Shader::new(ShaderInfo {
+            device: info.device,
+            entry: "main",
+            input: ShaderInput::Embedded {
+                name: "ui.frag",
+                source: include_str!("shaders/ui.frag.glsl"),
+            },
+        });
+
+        let pipeline = Pipeline::new_graphics_pipeline(GraphicsPipelineInfo {
+            device: info.device,
+            render_pass: Some(&render_pass),
+            rendering: None,
+            descriptor_set_count: info.max_textures,
+            color_count: 1,
+            subpass: 0,
+            extent: info.extent,
+            cull_mode: CullMode::empty(),
+            vertex_shader: &vertex_shader,
+            fragment_shader: Some(&fragment_shader),
+            depth_stencil: DepthStencil {
+                test: false,
+                write: false,
+                compare_op: CompareOp::Always,
+                stencil: None,
+            },
+            blend_constants: BlendConstants::Static([0.0, 0.0, 0.0, 0.0]),
+            color_blend: ColorBlend::Alpha,
+            color_write_mask: ColorComponents::default(),
+            samples: SampleCount::One,
+            scissor: Scissor::Dynamic,
+            primitive: Primitive::Triangles,
+            depth_range: DepthRange::default(),
+            vertex_input: &[
+                VertexInput {
+                    binding: 0,
+                    location: 0,
+                    format: Format::Rg32Sfloat,
+                    rate: InputRate::Vertex,
+                },
+                VertexInput {
+                    binding: 0,
+                    location: 1,
+                    format: Format::Rg32Sfloat,
+                    rate: InputRate::Vertex,
+                },
+                VertexInput {
+                    binding: 0,
+                    location: 2,
+                    format: Format::Rgba8Unorm,
+                    rate: InputRate::Vertex,
+                },
+            ],
+            layout: &[Descriptor {
+                binding: 0,
+                ty: DescriptorType::CombinedImageSampler,
+                count: 1,
+                stage: ShaderStage::FRAGMENT,
+            }],
+            push_constants: &[PushConstantRange {
+                stage: ShaderStage::VERTEX,
+                offset: 0,
+                size: mem::size_of::<UiConstants>() as _,
+            }],
+            material_table: None,
+            immutable_samplers: &[],
+            cache: None,
+        });
+
+        let vertices = StreamingBuffer::new(StreamingBufferInfo {
+            device: info.device,
+            usage: BufferUsage::VERTEX,
+            frames_in_flight: info.frames_in_flight,
+            frame_size: info.vertex_capacity * mem::size_of::<UiVertex>(),
+        });
+
+        let indices = StreamingBuffer::new(StreamingBufferInfo {
+            device: info.device,
+            usage: BufferUsage::INDEX,
+            frames_in_flight: info.frames_in_flight,
+            frame_size: info.index_capacity * mem::size_of::<u16>(),
+        });
+
+        let linear_output = OutputEncoding::of(info.format) == OutputEncoding::Hardware;
+
+        Self {
+            pipeline,
+            render_pass,
+            vertices,
+            indices,
+            textures: HashMap::new(),
+            free_slots: (0..info.max_textures).rev().collect(),
+            extent: info.extent,
+            linear_output,
+        }
+    }
+
+    pub fn render_pass(&self) -> &RenderPass {
+        &self.render_pass
+    }
+
+    //The ui backend hands out gamma space colors, which are linearized only for Hardware
+    pub fn encoding(&self) -> OutputEncoding {
+        if self.linear_output {
+            OutputEncoding::Hardware
+        } else {
+            OutputEncoding::Manual
+        }
+    }
+
+    //Call with the swapchain format after it is created or recreated, the surface
+    //may not offer the encoding this renderer was created for
+    pub fn validate_target(&self, format: Format) -> Result<(), Error> {
+        self.encoding().validate(format)
+    }
+
+    //Applies texture uploads and frees. Call outside of command recording,
+    //this waits for the device when anything changes so in flight frames
+    //never see a descriptor set or image being replaced.
+    pub fn update_textures(&mut self, device: &mut Device, draw_data: &UiDrawData<'_>) {
+        if draw_data.textures.is_empty() && draw_data.freed.is_empty() {
+            return;
+        }
+
+        device.wait_idle();
+
+        for update in draw_data.textures {
+            let texture = match (update.offset, self.textures.remove(&update.id)) {
+                (Some(offset), Some(mut texture)) => {
+                    let row = update.extent.0 as usize * 4;
+                    let stride = texture.extent.0 as usize * 4;
+
+                    for y in 0..update.extent.1 as usize {
+                        let src = y * row;
+                        let dst = (offset.1 as usize + y) * stride + offset.0 as usize * 4;
+
+                        texture.pixels[dst..dst + row]
+                            .copy_from_slice(&update.pixels[src..src + row]);
+                    }
+
+                    texture
+                }
+                (None, existing) => {
+                    let slot = match existing {
+                        Some(texture) => texture.slot,
+                        None => self.free_slots.pop().expect("out of ui texture slots"),
+                    };
+
+                    UiTexture {
+                        image: Self::new_image(device, update.extent),
+                        slot,
+                        extent: update.extent,
+                        pixels: update.pixels.to_vec(),
+                    }
+                }
+                (Some(_), None) => panic!("partial update of an unknown ui texture"),
+            };
+
+            let texture = self.upload(device, texture);
+
+            self.textures.insert(update.id, texture);
+        }
+
+        for id in draw_data.freed {
+            if let Some(texture) = self.textures.remove(id) {
+                self.free_slots.push(texture.slot);
+            }
+        }
+    }
+
+    fn new_image(device: &Device, extent: (u32, u32)) -> Image {
+        Image::new(ImageInfo {
+            device,
+            format: Format::Rgba8Srgb,
+            usage: ImageUsage::SAMPLED | ImageUsage::TRANSFER_DST,
+            ty: ImageType::TwoDim,
+            extent: (extent.0, extent.1, 1),
+            samples: SampleCount::One,
+            priority: MemoryPriority::Default,
+        })
+    }
+
+    //The copy transitions from undefined, so the whole texture is uploaded from
+    //the cpu side copy even when only a region changed.
+    fn upload(&mut self, device: &mut Device, mut texture: UiTexture) -> UiTexture {
+        let mut staging = Buffer::new(BufferInfo {
+            device,
+            usage: BufferUsage::TRANSFER_SRC,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: texture.pixels.len(),
+            priority: MemoryPriority::Default,
+        });
+
+        staging.write(BufferWrite {
+            offset: 0,
+            data: &texture.pixels[..],
+        });
+
+        device.copy_buffer_to_image(BufferImageCopy {
+            from: &staging,
+            to: &mut texture.image,
+            src: 0,
+            dst_extent: (texture.extent.0, texture.extent.1, 1),
+            dst_offset: (0, 0, 0),
+        });
+
+        self.pipeline.bind(
+            texture.slot,
+            &[Binding::Image {
+                binding: 0,
+                ty: DescriptorType::CombinedImageSampler,
+                layout: ImageLayout::ShaderReadOnly,
+                image: &texture.image,
+            }],
+        );
+
+        texture
+    }
+
+    //Streams this frame's meshes and records the draws into output. Meshes that
+    //do not fit in the remaining streaming capacity are skipped.
+    pub fn render(
+        &mut self,
+        commands: &mut Commands<'_>,
+        output: &Framebuffer,
+        draw_data: &UiDrawData<'_>,
+    ) -> Result<(), Error> {
+        self.vertices.advance();
+        self.indices.advance();
+
+        commands.begin_render_pass(RenderPassBeginInfo {
+            render_pass: &self.render_pass,
+            framebuffer: output,
+            color_clear_values: &[[0.0, 0.0, 0.0, 0.0]],
+            depth_stencil_clear_value: None,
+        })?;
+
+        let constants = UiConstants {
+            screen_size: [
+                self.extent.0 as f32 / draw_data.pixels_per_point,
+                self.extent.1 as f32 / draw_data.pixels_per_point,
+            ],
+            linear_output: self.linear_output as u32,
+        };
+
+        let mut bound = None;
+
+        for mesh in draw_data.meshes {
+            let Some(texture) = self.textures.get(&mesh.texture) else {
+                continue;
+            };
+
+            let Some((offset, extent)) = self.scissor(mesh.clip_rect, draw_data.pixels_per_point) else {
+                continue;
+            };
+
+            let Some(vertex_offset) = self.vertices.push(mesh.vertices) else {
+                break;
+            };
+
+            let Some(index_offset) = self.indices.push(mesh.indices) else {
+                break;
+            };
+
+            if bound.is_none() {
+                commands.bind_pipeline(texture.slot, &self.pipeline);
+                commands.push_constants(&self.pipeline, ShaderStage::VERTEX, 0, &[constants]);
+            } else if bound != Some(texture.slot) {
+                commands.bind_descriptor_sets(texture.slot, &self.pipeline, &[]);
+            }
+
+            bound = Some(texture.slot);
+
+            commands.set_scissor(offset, extent);
+            commands.bind_vertex_buffers(0, &[self.vertices.buffer()], &[vertex_offset]);
+            commands.bind_index_buffer(self.indices.buffer(), index_offset);
+            commands.draw_indexed(mesh.indices.len() as _, 1, 0, 0, 0);
+        }
+
+        commands.end_render_pass();
+
+        Ok(())
+    }
+
+    fn scissor(&self, clip_rect: [f32; 4], pixels_per_point: f32) -> Option<((i32, i32), (u32, u32))> {
+        let [min_x, min_y, max_x, max_y] = clip_rect.map(|x| x * pixels_per_point);
+
+        let min_x = (min_x.round() as i64).clamp(0, self.extent.0 as i64);
+        let min_y = (min_y.round() as i64).clamp(0, self.extent.1 as i64);
+        let max_x = (max_x.round() as i64).clamp(min_x, self.extent.0 as i64);
+        let max_y = (max_y.round() as i64).clamp(min_y, self.extent.1 as i64);
+
+        if max_x == min_x || max_y == min_y {
+            return None;
+        }
+
+        Some((
+            (min_x as i32, min_y as i32),
+            ((max_x - min_x) as u32, (max_y - min_y) as u32),
+        ))
+    }
+}
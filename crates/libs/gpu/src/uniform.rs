@@ -0,0 +1,112 @@
+use crate::prelude::*;
+
+use std::cmp;
+use std::marker::PhantomData;
+use std::mem;
+
+pub struct FrameUniformsInfo<'a> {
+    pub device: &'a Device,
+    pub frames_in_flight: u32,
+}
+
+pub struct FrameUniforms<T: Pod> {
+    buffer: Buffer,
+    aligned_size: usize,
+    frames_in_flight: u32,
+    frame: u32,
+    marker: PhantomData<T>,
+}
+
+impl<T: Pod> FrameUniforms<T> {
+    pub fn new(info: FrameUniformsInfo<'_>) -> Self {
+        if info.frames_in_flight == 0 {
+            panic!("frames in flight must be at least 1");
+        }
+
+        let Device::Vulkan { device, .. } = info.device else {
+            panic!("not a vulkan device");
+        };
+
+        let alignment = cmp::max(device.limits().min_uniform_buffer_offset_alignment as usize, 1);
+
+        let aligned_size = (mem::size_of::<T>() + alignment - 1) / alignment * alignment;
+
+        let buffer = Buffer::new(BufferInfo {
+            device: info.device,
+            usage: BufferUsage::UNIFORM,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size: aligned_size * info.frames_in_flight as usize,
+            priority: MemoryPriority::Default,
+        });
+
+        Self {
+            buffer,
+            aligned_size,
+            frames_in_flight: info.frames_in_flight,
+            frame: 0,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn write_current(&mut self, data: &T) {
+        self.buffer.write(BufferWrite {
+            offset: self.dynamic_offset() as _,
+            data: &[*data],
+        });
+    }
+
+    //For T built from math library types, e.g. FrameUniforms<ShaderMat4> and a glam::Mat4
+    pub fn write(&mut self, data: impl Into<T>) {
+        self.write_current(&data.into());
+    }
+
+    //Pass to Commands::bind_descriptor_sets after bind_pipeline, which leaves sets with
+    //dynamic uniform buffers unbound
+    pub fn dynamic_offset(&self) -> u32 {
+        (self.frame as usize * self.aligned_size) as u32
+    }
+
+    pub fn advance(&mut self) {
+        self.frame = (self.frame + 1) % self.frames_in_flight;
+    }
+
+    pub fn binding(&self, binding: u32) -> Binding<'_> {
+        Binding::Buffer {
+            binding,
+            ty: DescriptorType::UniformBufferDynamic,
+            offset: 0,
+            range: mem::size_of::<T>(),
+            buffer: &self.buffer,
+        }
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    pub fn aligned_size(&self) -> usize {
+        self.aligned_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "frames in flight must be at least 1")]
+    fn zero_frames_in_flight_is_rejected() {
+        let context = Context::start_headless();
+
+        let device = Device::choose_best(DeviceInfo {
+            context: &context,
+            #[cfg(feature = "swapchain")]
+            surface: None,
+        });
+
+        FrameUniforms::<[f32; 4]>::new(FrameUniformsInfo {
+            device: &device,
+            frames_in_flight: 0,
+        });
+    }
+}
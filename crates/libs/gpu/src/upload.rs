@@ -0,0 +1,384 @@
+use crate::prelude::*;
+
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::rc::Rc;
+
+pub struct UploaderInfo<'a> {
+    pub device: &'a Device,
+    //bytes of host visible staging memory, larger uploads get a staging buffer of their own
+    pub staging_size: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct UploadId(u64);
+
+struct Upload {
+    id: UploadId,
+    //part of the staging ring in use until the copy retires
+    range: Option<Range<usize>>,
+    //for uploads that did not fit the ring
+    _oversized: Option<Buffer>,
+    command_buffer: vk::CommandBuffer,
+    fence: vk::Fence,
+}
+
+//Copies data into device local buffers and images through a staging ring. Each upload
+//is recorded and submitted right away with its own fence, so the destination must
+//not be used on the gpu before wait(id) returns.
+pub struct Uploader {
+    pending: VecDeque<Upload>,
+    //retired command buffers and fences for reuse
+    free: Vec<(vk::CommandBuffer, vk::Fence)>,
+    command_pool: vk::CommandPool,
+    staging: Buffer,
+    staging_size: usize,
+    //where the next upload is written in the ring
+    head: usize,
+    device: Rc<vk::Device>,
+    next_id: u64,
+}
+
+impl Uploader {
+    pub fn new(info: UploaderInfo<'_>) -> Self {
+        let Device::Vulkan {
+            device,
+            queue_family_index,
+            ..
+        } = info.device
+        else {
+            panic!("not a vulkan device");
+        };
+
+        let command_pool_create_info = vk::CommandPoolCreateInfo {
+            flags: vk::COMMAND_POOL_CREATE_RESET_COMMAND_BUFFER | vk::COMMAND_POOL_CREATE_TRANSIENT,
+            queue_family_index: *queue_family_index,
+        };
+
+        let command_pool = vk::CommandPool::new(device.clone(), command_pool_create_info)
+            .expect("failed to create command pool");
+
+        let staging = Self::staging_buffer(info.device, info.staging_size);
+
+        Self {
+            pending: VecDeque::new(),
+            free: vec![],
+            command_pool,
+            staging,
+            staging_size: info.staging_size,
+            head: 0,
+            device: device.clone(),
+            next_id: 0,
+        }
+    }
+
+    pub fn upload_buffer(
+        &mut self,
+        device: &mut Device,
+        data: &[u8],
+        buffer: &mut Buffer,
+    ) -> UploadId {
+        self.upload(device, data, 4, |commands, staging, offset| {
+            let (Buffer::Vulkan { buffer: from, .. }, Buffer::Vulkan { buffer: to, .. }) =
+                (staging, &mut *buffer)
+            else {
+                panic!("not a vulkan buffer");
+            };
+
+            let region = vk::BufferCopy {
+                src_offset: offset as _,
+                dst_offset: 0,
+                size: data.len() as _,
+            };
+
+            commands.copy_buffer(from, to, &[region]);
+
+            //the fence wait covers execution, this makes the write available to later reads
+            let barrier = vk::BufferMemoryBarrier {
+                src_access_mask: vk::ACCESS_TRANSFER_WRITE,
+                dst_access_mask: vk::ACCESS_MEMORY_READ,
+                src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                buffer: to,
+                offset: 0,
+                size: data.len() as _,
+            };
+
+            commands.pipeline_barrier(
+                vk::PIPELINE_STAGE_TRANSFER,
+                vk::PIPELINE_STAGE_ALL_COMMANDS,
+                0,
+                &[],
+                &[barrier],
+                &[],
+            );
+        })
+    }
+
+    //Replaces the first mip and layer of image, whose previous contents are discarded,
+    //and leaves it in layout
+    pub fn upload_image(
+        &mut self,
+        device: &mut Device,
+        data: &[u8],
+        image: &mut Image,
+        extent: (u32, u32),
+        layout: ImageLayout,
+    ) -> UploadId {
+        let Image::Vulkan { format, .. } = image else {
+            panic!("not a vulkan image");
+        };
+
+        let format = *format;
+
+        //buffer offsets of copies must be a multiple of 4 and of the texel or block size
+        let texel = Format::from(format).block_bytes();
+        let alignment = if texel % 4 == 0 { texel } else { texel * 4 };
+
+        self.upload(device, data, alignment, |commands, staging, offset| {
+            let (Buffer::Vulkan { buffer: from, .. }, Image::Vulkan { image: to, .. }) =
+                (staging, &mut *image)
+            else {
+                panic!("not a vulkan buffer or image");
+            };
+
+            let subresource_range = || vk::ImageSubresourceRange {
+                aspect_mask: format.aspect_mask(),
+                base_mip_level: 0,
+                level_count: 1,
+                base_array_layer: 0,
+                layer_count: 1,
+            };
+
+            let barrier = vk::ImageMemoryBarrier {
+                old_layout: vk::ImageLayout::Undefined,
+                new_layout: vk::ImageLayout::TransferDst,
+                src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                image: to,
+                src_access_mask: 0,
+                dst_access_mask: vk::ACCESS_TRANSFER_WRITE,
+                subresource_range: subresource_range(),
+            };
+
+            commands.pipeline_barrier(
+                vk::PIPELINE_STAGE_TOP_OF_PIPE,
+                vk::PIPELINE_STAGE_TRANSFER,
+                0,
+                &[],
+                &[],
+                &[barrier],
+            );
+
+            let region = vk::BufferImageCopy {
+                buffer_offset: offset as _,
+                buffer_row_length: 0,
+                buffer_image_height: 0,
+                image_subresource: vk::ImageSubresourceLayers {
+                    aspect_mask: format.aspect_mask(),
+                    mip_level: 0,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                },
+                image_offset: (0, 0, 0),
+                image_extent: (extent.0, extent.1, 1),
+            };
+
+            commands.copy_buffer_to_image(from, to, vk::ImageLayout::TransferDst, &[region]);
+
+            let barrier = vk::ImageMemoryBarrier {
+                old_layout: vk::ImageLayout::TransferDst,
+                new_layout: layout.into(),
+                src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                image: to,
+                src_access_mask: vk::ACCESS_TRANSFER_WRITE,
+                dst_access_mask: vk::ACCESS_MEMORY_READ,
+                subresource_range: subresource_range(),
+            };
+
+            commands.pipeline_barrier(
+                vk::PIPELINE_STAGE_TRANSFER,
+                vk::PIPELINE_STAGE_ALL_COMMANDS,
+                0,
+                &[],
+                &[],
+                &[barrier],
+            );
+        })
+    }
+
+    //Waits for id and every upload before it, they retire in order on the one queue
+    pub fn wait(&mut self, id: UploadId) {
+        while self
+            .pending
+            .front()
+            .map_or(false, |upload| upload.id.0 <= id.0)
+        {
+            self.retire_front();
+        }
+    }
+
+    pub fn wait_all(&mut self) {
+        while !self.pending.is_empty() {
+            self.retire_front();
+        }
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn upload(
+        &mut self,
+        device: &mut Device,
+        data: &[u8],
+        alignment: usize,
+        mut record: impl FnMut(&mut vk::Commands<'_>, &Buffer, usize),
+    ) -> UploadId {
+        let (range, oversized) = if data.len() <= self.staging_size {
+            (Some(self.reserve(data.len(), alignment)), None)
+        } else {
+            (None, Some(Self::staging_buffer(device, data.len())))
+        };
+
+        let (staging, offset) = match (&range, &oversized) {
+            (Some(range), _) => (&self.staging, range.start),
+            (None, Some(oversized)) => (oversized, 0),
+            (None, None) => unreachable!(),
+        };
+
+        let Buffer::Vulkan { memory, .. } = staging else {
+            panic!("not a vulkan buffer");
+        };
+
+        memory
+            .write(offset, |slice: &mut [u8]| {
+                slice[..data.len()].copy_from_slice(data);
+            })
+            .expect("failed to write staging memory");
+
+        let Device::Vulkan { queues, .. } = device else {
+            panic!("not a vulkan device");
+        };
+
+        let (mut command_buffer, mut fence) = match self.free.pop() {
+            Some(free) => free,
+            None => {
+                let command_buffer_allocate_info = vk::CommandBufferAllocateInfo {
+                    command_pool: &self.command_pool,
+                    level: vk::CommandBufferLevel::Primary,
+                    count: 1,
+                };
+
+                let command_buffer =
+                    vk::CommandBuffer::allocate(self.device.clone(), command_buffer_allocate_info)
+                        .expect("failed to create command buffer")
+                        .remove(0);
+
+                let fence = vk::Fence::new(self.device.clone(), vk::FenceCreateInfo {})
+                    .expect("failed to create fence");
+
+                (command_buffer, fence)
+            }
+        };
+
+        command_buffer
+            .record(|mut commands| record(&mut commands, staging, offset))
+            .expect("failed to record upload command buffer");
+
+        #[cfg(feature = "barrier-validation")]
+        crate::device::report_hazards(&command_buffer);
+
+        vk::Fence::reset(&[&mut fence]).expect("failed to reset fence");
+
+        let submit_info = vk::SubmitInfo {
+            wait_semaphores: &[],
+            wait_stages: &[],
+            command_buffers: &[&command_buffer],
+            signal_semaphores: &[],
+            protected: false,
+        };
+
+        //the device has a single queue family, which covers transfers
+        queues[0]
+            .submit(&[submit_info], Some(&mut fence))
+            .expect("failed to submit upload command buffer");
+
+        let id = UploadId(self.next_id);
+
+        self.next_id += 1;
+
+        self.pending.push_back(Upload {
+            id,
+            range,
+            _oversized: oversized,
+            command_buffer,
+            fence,
+        });
+
+        id
+    }
+
+    //Finds room for size bytes in the ring, waiting on the oldest uploads until there is
+    fn reserve(&mut self, size: usize, alignment: usize) -> Range<usize> {
+        loop {
+            let tail = self
+                .pending
+                .iter()
+                .find_map(|upload| upload.range.as_ref())
+                .map(|range| range.start);
+
+            let head = (self.head + alignment - 1) / alignment * alignment;
+
+            let start = match tail {
+                None => Some(0),
+                //the free space is [head, end) and [0, tail)
+                Some(tail) if self.head > tail => {
+                    if head + size <= self.staging_size {
+                        Some(head)
+                    } else if size <= tail {
+                        Some(0)
+                    } else {
+                        None
+                    }
+                }
+                //wrapped, the free space is [head, tail)
+                Some(tail) => (head + size <= tail).then(|| head),
+            };
+
+            if let Some(start) = start {
+                self.head = start + size;
+
+                return start..start + size;
+            }
+
+            self.retire_front();
+        }
+    }
+
+    fn retire_front(&mut self) {
+        let mut upload = self.pending.pop_front().unwrap();
+
+        vk::Fence::wait(&[&mut upload.fence], true, u64::MAX).expect("failed to wait for fence");
+
+        self.free.push((upload.command_buffer, upload.fence));
+    }
+
+    fn staging_buffer(device: &Device, size: usize) -> Buffer {
+        Buffer::new(BufferInfo {
+            device,
+            usage: BufferUsage::TRANSFER_SRC,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size,
+            priority: MemoryPriority::Default,
+        })
+    }
+}
+
+//The staging memory must outlive the copies reading it
+impl Drop for Uploader {
+    fn drop(&mut self) {
+        self.wait_all();
+    }
+}
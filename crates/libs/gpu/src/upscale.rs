@@ -0,0 +1,116 @@
+use crate::prelude::*;
+
+use std::mem;
+
+//Lanczos is sharper and costs 16 taps a pixel against 4 for bilinear
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UpscaleFilter {
+    Bilinear,
+    Lanczos,
+}
+
+pub struct UpscalePassInfo<'a> {
+    pub device: &'a Device,
+    pub format: Format,
+    //extent of the output, the input may be any size
+    pub extent: (u32, u32),
+    pub descriptor_set_count: u32,
+    pub filter: UpscaleFilter,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct UpscaleConstants {
+    input_extent: [f32; 2],
+    output_extent: [f32; 2],
+    mode: u32,
+}
+
+unsafe impl Pod for UpscaleConstants {}
+
+//Resamples a scene rendered at RenderResolution::extent() to the output extent. The input
+//extent is passed every record, so rescaling never recreates the pass.
+pub struct UpscalePass {
+    pass: PostProcessPass,
+    extent: (u32, u32),
+    filter: UpscaleFilter,
+}
+
+impl UpscalePass {
+    pub fn new(info: UpscalePassInfo<'_>) -> Self {
+        let vertex_shader = Shader::new(ShaderInfo {
+            device: info.device,
+            entry: "main",
+            input: ShaderInput::Embedded {
+                name: "fullscreen.vert",
+                source: include_str!("shaders/fullscreen.vert.glsl"),
+            },
+        });
+
+        let fragment_shader = Shader::new(ShaderInfo {
+            device: info.device,
+            entry: "main",
+            input: ShaderInput::Embedded {
+                name: "upscale.frag",
+                source: include_str!("shaders/upscale.frag.glsl"),
+            },
+        });
+
+        let pass = PostProcessPass::new(PostProcessInfo {
+            device: info.device,
+            vertex_shader: &vertex_shader,
+            fragment_shader: &fragment_shader,
+            format: info.format,
+            extent: info.extent,
+            descriptor_set_count: info.descriptor_set_count,
+            push_constant_size: mem::size_of::<UpscaleConstants>() as _,
+            layout: &[],
+        });
+
+        Self {
+            pass,
+            extent: info.extent,
+            filter: info.filter,
+        }
+    }
+
+    pub fn set_filter(&mut self, filter: UpscaleFilter) {
+        self.filter = filter;
+    }
+
+    pub fn filter(&self) -> UpscaleFilter {
+        self.filter
+    }
+
+    pub fn render_pass(&self) -> &RenderPass {
+        self.pass.render_pass()
+    }
+
+    //The scene must be in ShaderReadOnly layout
+    pub fn record(
+        &mut self,
+        commands: &mut Commands<'_>,
+        image_index: u32,
+        scene: &Image,
+        scene_extent: (u32, u32),
+        output: &Framebuffer,
+    ) -> Result<(), Error> {
+        let constants = UpscaleConstants {
+            input_extent: [scene_extent.0 as f32, scene_extent.1 as f32],
+            output_extent: [self.extent.0 as f32, self.extent.1 as f32],
+            mode: match self.filter {
+                UpscaleFilter::Bilinear => 0,
+                UpscaleFilter::Lanczos => 1,
+            },
+        };
+
+        self.pass.record(
+            commands,
+            image_index,
+            scene,
+            output,
+            &[],
+            as_bytes(&constants),
+        )
+    }
+}
@@ -0,0 +1,202 @@
+use crate::prelude::*;
+
+use std::mem;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ViewportId(usize);
+
+pub struct ViewportInfo<'a> {
+    pub device: &'a Device,
+    //presented from whichever of the device's queues can, see Device::present_queue_family
+    pub surface: Surface,
+    pub usage: ImageUsage,
+    pub encoding: OutputEncoding,
+    pub vsync: VsyncMode,
+    //the window's size in physical pixels
+    pub extent: Option<(u32, u32)>,
+}
+
+//A viewport whose image was acquired by begin_frame, draw to images()[image_index]
+#[derive(Clone, Copy, Debug)]
+pub struct ViewportFrame {
+    pub id: ViewportId,
+    pub image_index: u32,
+}
+
+struct Viewport {
+    swapchain: SwapchainManager,
+    surface: Surface,
+    //acquired this frame
+    image_index: Option<u32>,
+    //why the last begin_frame left it out, see Viewports::take_error
+    error: Option<Error>,
+}
+
+//The windows of a multi-viewport editor drawn from one Device. Each keeps its own
+//SwapchainManager, so one going out of date, being minimized or resized leaves the others
+//drawing. A frame is begin_frame, one draw recording all acquired viewports, then end_frame.
+#[derive(Default)]
+pub struct Viewports {
+    viewports: Vec<Option<Viewport>>,
+}
+
+impl Viewports {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, info: ViewportInfo<'_>) -> Result<ViewportId, Error> {
+        if !info.device.supports_present(&info.surface) {
+            return Err(Error::SurfaceNotPresentable);
+        }
+
+        let swapchain = SwapchainManager::new(SwapchainManagerInfo {
+            device: info.device,
+            surface: &info.surface,
+            usage: info.usage,
+            encoding: info.encoding,
+            vsync: info.vsync,
+            extent: info.extent,
+            hdr_metadata: None,
+            dedicated_sync: true,
+        });
+
+        let viewport = Viewport {
+            swapchain,
+            surface: info.surface,
+            image_index: None,
+            error: None,
+        };
+
+        let index = match self.viewports.iter().position(Option::is_none) {
+            Some(index) => {
+                self.viewports[index] = Some(viewport);
+
+                index
+            }
+            None => {
+                self.viewports.push(Some(viewport));
+
+                self.viewports.len() - 1
+            }
+        };
+
+        Ok(ViewportId(index))
+    }
+
+    //The swapchain is retired before its surface, the last frame may still present to it
+    pub fn remove(&mut self, device: &Device, id: ViewportId) {
+        let viewport = self.viewports[id.0].take().expect("viewport was removed");
+
+        if !viewport.swapchain.swapchain().wait_presented() {
+            device.wait_idle();
+        }
+
+        device.retire((viewport.swapchain, viewport.surface));
+    }
+
+    //The window's new size in physical pixels, applied by the next begin_frame
+    pub fn resize(&mut self, id: ViewportId, extent: (u32, u32)) {
+        self.viewport_mut(id).swapchain.resize(extent);
+    }
+
+    //Acquires from every viewport, recreating those out of date. Viewports that are
+    //minimized, still out of date or fail to acquire are left out of the frame rather than
+    //failing it, the others' acquires still have to be drawn and presented.
+    pub fn begin_frame(&mut self, device: &mut Device) -> Vec<ViewportFrame> {
+        device.synchronize();
+
+        let mut frames = vec![];
+
+        for (index, viewport) in self.viewports.iter_mut().enumerate() {
+            let Some(viewport) = viewport else {
+                continue;
+            };
+
+            viewport.image_index = match viewport.swapchain.acquire(device, &viewport.surface) {
+                Ok(image_index) => Some(image_index),
+                Err(Error::OutOfDate) => None,
+                Err(error) => {
+                    viewport.error = Some(error);
+
+                    None
+                }
+            };
+
+            if let Some(image_index) = viewport.image_index {
+                frames.push(ViewportFrame {
+                    id: ViewportId(index),
+                    image_index,
+                });
+            }
+        }
+
+        frames
+    }
+
+    //Records the frame once for all acquired viewports
+    pub fn draw(&self, device: &mut Device, script: impl FnMut(Commands<'_>)) {
+        let swapchains = self
+            .viewports
+            .iter()
+            .flatten()
+            .filter(|viewport| viewport.image_index.is_some())
+            .map(|viewport| viewport.swapchain.swapchain())
+            .collect::<Vec<_>>();
+
+        device.draw_call_presenting(&swapchains, script);
+    }
+
+    //Presents every acquired viewport, failing with the first error once all were presented
+    pub fn end_frame(&mut self, device: &mut Device) -> Result<(), Error> {
+        let mut result = Ok(());
+
+        for viewport in self.viewports.iter_mut().flatten() {
+            if mem::take(&mut viewport.image_index).is_none() {
+                continue;
+            }
+
+            let presented = viewport.swapchain.present(device);
+
+            if result.is_ok() {
+                result = presented;
+            }
+        }
+
+        result
+    }
+
+    //The last recreation of the viewport's swapchain, see SwapchainManager::take_change
+    pub fn take_change(&mut self, id: ViewportId) -> Option<SwapchainChange> {
+        self.viewport_mut(id).swapchain.take_change()
+    }
+
+    //The error the viewport failed to acquire with, e.g. to remove it once its surface is lost
+    pub fn take_error(&mut self, id: ViewportId) -> Option<Error> {
+        self.viewport_mut(id).error.take()
+    }
+
+    pub fn swapchain(&self, id: ViewportId) -> &Swapchain {
+        self.viewport(id).swapchain.swapchain()
+    }
+
+    pub fn images(&self, id: ViewportId) -> &[Image] {
+        self.viewport(id).swapchain.images()
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = ViewportId> + '_ {
+        self.viewports
+            .iter()
+            .enumerate()
+            .filter(|(_, viewport)| viewport.is_some())
+            .map(|(index, _)| ViewportId(index))
+    }
+
+    fn viewport(&self, id: ViewportId) -> &Viewport {
+        self.viewports[id.0].as_ref().expect("viewport was removed")
+    }
+
+    fn viewport_mut(&mut self, id: ViewportId) -> &mut Viewport {
+        self.viewports[id.0].as_mut().expect("viewport was removed")
+    }
+}
@@ -0,0 +1,235 @@
+use crate::prelude::*;
+
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use log::error;
+
+pub struct WatchdogInfo<'a> {
+    pub device: &'a Device,
+    //how long a submission may take to retire before it counts as hung
+    pub timeout: Duration,
+    //log the pending submissions and live device objects when one hangs
+    pub dump_on_hang: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SubmissionId(u64);
+
+//The submission that failed to retire within the timeout. The device is most likely
+//lost or stuck in a shader that never terminates.
+#[derive(Clone, Debug)]
+pub struct Hang {
+    pub id: SubmissionId,
+    pub label: String,
+    pub elapsed: Duration,
+}
+
+struct Submission {
+    id: SubmissionId,
+    label: String,
+    submitted: Instant,
+    command_buffer: vk::CommandBuffer,
+    fence: vk::Fence,
+}
+
+//Submits labelled work, e.g. compute dispatches, each with its own fence and waits on
+//them with a timeout instead of u64::MAX, so a misbehaving shader reports which
+//submission hung rather than hanging the app.
+pub struct Watchdog {
+    pending: VecDeque<Submission>,
+    //retired command buffers and fences for reuse
+    free: Vec<(vk::CommandBuffer, vk::Fence)>,
+    command_pool: vk::CommandPool,
+    device: Rc<vk::Device>,
+    timeout: Duration,
+    dump_on_hang: bool,
+    next_id: u64,
+}
+
+impl Watchdog {
+    pub fn new(info: WatchdogInfo<'_>) -> Self {
+        let Device::Vulkan {
+            device,
+            queue_family_index,
+            ..
+        } = info.device
+        else {
+            panic!("not a vulkan device");
+        };
+
+        let command_pool_create_info = vk::CommandPoolCreateInfo {
+            flags: vk::COMMAND_POOL_CREATE_RESET_COMMAND_BUFFER,
+            queue_family_index: *queue_family_index,
+        };
+
+        let command_pool = vk::CommandPool::new(device.clone(), command_pool_create_info)
+            .expect("failed to create command pool");
+
+        Self {
+            pending: VecDeque::new(),
+            free: vec![],
+            command_pool,
+            device: device.clone(),
+            timeout: info.timeout,
+            dump_on_hang: info.dump_on_hang,
+            next_id: 0,
+        }
+    }
+
+    pub fn submit(
+        &mut self,
+        device: &mut Device,
+        label: &str,
+        mut script: impl FnMut(Commands<'_>),
+    ) -> SubmissionId {
+        let Device::Vulkan { queues, .. } = device else {
+            panic!("not a vulkan device");
+        };
+
+        let (mut command_buffer, mut fence) = match self.free.pop() {
+            Some(free) => free,
+            None => {
+                let command_buffer_allocate_info = vk::CommandBufferAllocateInfo {
+                    command_pool: &self.command_pool,
+                    level: vk::CommandBufferLevel::Primary,
+                    count: 1,
+                };
+
+                let command_buffer =
+                    vk::CommandBuffer::allocate(self.device.clone(), command_buffer_allocate_info)
+                        .expect("failed to create command buffer")
+                        .remove(0);
+
+                let fence = vk::Fence::new(self.device.clone(), vk::FenceCreateInfo {})
+                    .expect("failed to create fence");
+
+                (command_buffer, fence)
+            }
+        };
+
+        command_buffer
+            .record(|commands| script(Commands::Vulkan { commands }))
+            .expect("failed to record command buffer");
+
+        #[cfg(feature = "barrier-validation")]
+        crate::device::report_hazards(&command_buffer);
+
+        vk::Fence::reset(&[&mut fence]).expect("failed to reset fence");
+
+        let submit_info = vk::SubmitInfo {
+            wait_semaphores: &[],
+            wait_stages: &[],
+            command_buffers: &[&command_buffer],
+            signal_semaphores: &[],
+            protected: false,
+        };
+
+        queues[0]
+            .submit(&[submit_info], Some(&mut fence))
+            .expect("failed to submit watched command buffer");
+
+        let id = SubmissionId(self.next_id);
+
+        self.next_id += 1;
+
+        self.pending.push_back(Submission {
+            id,
+            label: label.to_owned(),
+            submitted: Instant::now(),
+            command_buffer,
+            fence,
+        });
+
+        id
+    }
+
+    //Waits for id and every submission before it, they retire in order on the one queue
+    pub fn wait(&mut self, id: SubmissionId) -> Result<(), Hang> {
+        while self
+            .pending
+            .front()
+            .map_or(false, |submission| submission.id.0 <= id.0)
+        {
+            self.retire_front()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn wait_all(&mut self) -> Result<(), Hang> {
+        while !self.pending.is_empty() {
+            self.retire_front()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn retire_front(&mut self) -> Result<(), Hang> {
+        let submission = self.pending.front_mut().unwrap();
+
+        let remaining = self.timeout.saturating_sub(submission.submitted.elapsed());
+
+        let result = vk::Fence::wait(
+            &[&mut submission.fence],
+            true,
+            remaining.as_nanos().min(u64::MAX as u128) as u64,
+        );
+
+        match result {
+            Ok(()) => {
+                let submission = self.pending.pop_front().unwrap();
+
+                self.free
+                    .push((submission.command_buffer, submission.fence));
+
+                Ok(())
+            }
+            Err(vk::Error::Timeout | vk::Error::DeviceLost) => {
+                let hang = Hang {
+                    id: submission.id,
+                    label: submission.label.clone(),
+                    elapsed: submission.submitted.elapsed(),
+                };
+
+                if self.dump_on_hang {
+                    self.dump(&hang);
+                }
+
+                Err(hang)
+            }
+            Err(e) => panic!("failed to wait for fence: {:?}", e),
+        }
+    }
+
+    fn dump(&self, hang: &Hang) {
+        error!(
+            "submission {} \"{}\" did not retire after {:?}",
+            hang.id.0, hang.label, hang.elapsed
+        );
+
+        for submission in &self.pending {
+            error!(
+                "  pending {} \"{}\" submitted {:?} ago",
+                submission.id.0,
+                submission.label,
+                submission.submitted.elapsed()
+            );
+        }
+
+        let children = self
+            .device
+            .live_children()
+            .into_iter()
+            .map(|(ty, count)| format!("{} x{}", ty, count))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        error!("  live device objects: {}", children);
+    }
+}
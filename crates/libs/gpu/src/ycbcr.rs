@@ -0,0 +1,308 @@
+use crate::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum YcbcrModel {
+    Bt601,
+    Bt709,
+    Bt2020,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum YcbcrRange {
+    //0..255 luma and chroma, as most webcams produce
+    Full,
+    //16..235 luma and 16..240 chroma, as most video decoders produce
+    Narrow,
+}
+
+pub struct YcbcrTextureInfo<'a> {
+    pub device: &'a Device,
+    //one of the multi-planar formats
+    pub format: Format,
+    pub extent: (u32, u32),
+    pub model: YcbcrModel,
+    pub range: YcbcrRange,
+    pub filter: Filter,
+}
+
+//A multi-planar video frame, e.g. NV12, converted to RGB by the sampler. The sampler must
+//be immutable, so pass image() in the immutable_samplers of the pipeline that samples it.
+pub struct YcbcrTexture {
+    image: Image,
+    _conversion: vk::SamplerYcbcrConversion,
+    format: Format,
+    extent: (u32, u32),
+}
+
+impl YcbcrTexture {
+    pub fn new(info: YcbcrTextureInfo<'_>) -> Self {
+        if info.format.plane_count() < 2 {
+            panic!("not a multi-planar format");
+        }
+
+        match info.device {
+            Device::Vulkan {
+                physical_device,
+                device,
+                ..
+            } => {
+                let format: vk::Format = info.format.into();
+
+                let filter = match info.filter {
+                    Filter::Nearest => vk::Filter::Nearest,
+                    Filter::Linear => vk::Filter::Linear,
+                };
+
+                let conversion_create_info = vk::SamplerYcbcrConversionCreateInfo {
+                    format,
+                    ycbcr_model: match info.model {
+                        YcbcrModel::Bt601 => vk::SamplerYcbcrModelConversion::Ycbcr601,
+                        YcbcrModel::Bt709 => vk::SamplerYcbcrModelConversion::Ycbcr709,
+                        YcbcrModel::Bt2020 => vk::SamplerYcbcrModelConversion::Ycbcr2020,
+                    },
+                    ycbcr_range: match info.range {
+                        YcbcrRange::Full => vk::SamplerYcbcrRange::ItuFull,
+                        YcbcrRange::Narrow => vk::SamplerYcbcrRange::ItuNarrow,
+                    },
+                    components: Self::identity(),
+                    x_chroma_offset: vk::ChromaLocation::Midpoint,
+                    y_chroma_offset: vk::ChromaLocation::Midpoint,
+                    chroma_filter: filter,
+                    force_explicit_reconstruction: false,
+                };
+
+                let conversion =
+                    vk::SamplerYcbcrConversion::new(device.clone(), conversion_create_info)
+                        .expect("failed to create sampler ycbcr conversion");
+
+                let (image, memory) = Image::new_managed_vk_image(
+                    &physical_device,
+                    device.clone(),
+                    format,
+                    (ImageUsage::SAMPLED | ImageUsage::TRANSFER_DST).to_vk(),
+                    vk::ImageType::TwoDim,
+                    (info.extent.0, info.extent.1, 1),
+                    1,
+                    SampleCount::One,
+                    MemoryPriority::Default,
+                );
+
+                let view_create_info = vk::ImageViewCreateInfo {
+                    image: &image,
+                    view_type: vk::ImageViewType::TwoDim,
+                    format,
+                    components: Self::identity(),
+                    subresource_range: vk::ImageSubresourceRange {
+                        aspect_mask: format.aspect_mask(),
+                        base_mip_level: 0,
+                        level_count: 1,
+                        base_array_layer: 0,
+                        layer_count: 1,
+                    },
+                };
+
+                let view = vk::ImageView::new_with_conversion(
+                    device.clone(),
+                    view_create_info,
+                    &conversion,
+                )
+                .expect("failed to create image view");
+
+                //conversion samplers must clamp to edge and have unnormalized coordinates off
+                let sampler_create_info = vk::SamplerCreateInfo {
+                    mag_filter: filter,
+                    min_filter: filter,
+                    mipmap_mode: vk::SamplerMipmapMode::Nearest,
+                    address_mode_u: vk::SamplerAddressMode::ClampToEdge,
+                    address_mode_v: vk::SamplerAddressMode::ClampToEdge,
+                    address_mode_w: vk::SamplerAddressMode::ClampToEdge,
+                    mip_lod_bias: 0.0,
+                    anisotropy_enable: false,
+                    max_anisotropy: 0.0,
+                    compare_enable: false,
+                    compare_op: vk::CompareOp::Always,
+                    min_lod: 0.0,
+                    max_lod: 0.0,
+                    border_color: vk::BorderColor::IntTransparentBlack,
+                    unnormalized_coordinates: false,
+                };
+
+                let sampler = vk::Sampler::new_with_conversion(
+                    device.clone(),
+                    sampler_create_info,
+                    &conversion,
+                )
+                .expect("failed to create sampler");
+
+                Self {
+                    image: Image::Vulkan {
+                        image,
+                        format,
+                        memory: Some(memory),
+                        view,
+                        sampler,
+                    },
+                    _conversion: conversion,
+                    format: info.format,
+                    extent: info.extent,
+                }
+            }
+        }
+    }
+
+    //One tightly packed slice per plane, e.g. luma then interleaved chroma for NV12.
+    //Leaves the texture in ShaderReadOnly.
+    pub fn write(&mut self, device: &mut Device, planes: &[&[u8]]) {
+        if planes.len() != self.format.plane_count() {
+            panic!("expected {} planes", self.format.plane_count());
+        }
+
+        let mut offsets = vec![];
+        let mut size = 0;
+
+        for (plane, pixels) in planes.iter().enumerate() {
+            let (width, height) = self.format.plane_extent(plane, self.extent);
+
+            if pixels.len() != width as usize * height as usize * self.format.plane_bytes(plane) {
+                panic!(
+                    "plane {} does not match the texture extent and format",
+                    plane
+                );
+            }
+
+            offsets.push(size);
+            size += pixels.len();
+        }
+
+        let mut staging = Buffer::new(BufferInfo {
+            device,
+            usage: BufferUsage::TRANSFER_SRC,
+            properties: MemoryProperties::HOST_VISIBLE | MemoryProperties::HOST_COHERENT,
+            size,
+            priority: MemoryPriority::Default,
+        });
+
+        for (pixels, offset) in planes.iter().zip(&offsets) {
+            staging.write(BufferWrite {
+                offset: *offset as _,
+                data: pixels,
+            });
+        }
+
+        let Buffer::Vulkan { buffer, .. } = &staging else {
+            panic!("not a vulkan buffer")
+        };
+
+        let Image::Vulkan { image, format, .. } = &mut self.image else {
+            panic!("not a vulkan image")
+        };
+
+        let regions = offsets
+            .iter()
+            .enumerate()
+            .map(|(plane, offset)| {
+                let (width, height) = self.format.plane_extent(plane, self.extent);
+
+                vk::BufferImageCopy {
+                    buffer_offset: *offset as _,
+                    buffer_row_length: 0,
+                    buffer_image_height: 0,
+                    image_subresource: vk::ImageSubresourceLayers {
+                        aspect_mask: vk::Format::plane_aspect(plane as _)
+                            .expect("multi-planar formats have at most three planes"),
+                        mip_level: 0,
+                        base_array_layer: 0,
+                        layer_count: 1,
+                    },
+                    image_offset: (0, 0, 0),
+                    image_extent: (width, height, 1),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let aspect_mask = format.aspect_mask();
+
+        let subresource_range = || vk::ImageSubresourceRange {
+            aspect_mask,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+
+        device.call(|commands| {
+            let Commands::Vulkan { mut commands } = commands else {
+                panic!("not vulkan commands")
+            };
+
+            commands.pipeline_barrier(
+                vk::PIPELINE_STAGE_TOP_OF_PIPE,
+                vk::PIPELINE_STAGE_TRANSFER,
+                0,
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    old_layout: vk::ImageLayout::Undefined,
+                    new_layout: vk::ImageLayout::TransferDst,
+                    src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    image: &image,
+                    src_access_mask: 0,
+                    dst_access_mask: vk::ACCESS_TRANSFER_WRITE,
+                    subresource_range: subresource_range(),
+                }],
+            );
+
+            commands.copy_buffer_to_image(buffer, image, vk::ImageLayout::TransferDst, &regions);
+
+            commands.pipeline_barrier(
+                vk::PIPELINE_STAGE_TRANSFER,
+                vk::PIPELINE_STAGE_FRAGMENT_SHADER | vk::PIPELINE_STAGE_COMPUTE_SHADER,
+                0,
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    old_layout: vk::ImageLayout::TransferDst,
+                    new_layout: vk::ImageLayout::ShaderReadOnly,
+                    src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                    image: &image,
+                    src_access_mask: vk::ACCESS_TRANSFER_WRITE,
+                    dst_access_mask: vk::ACCESS_SHADER_READ,
+                    subresource_range: subresource_range(),
+                }],
+            );
+        });
+    }
+
+    //The sampler in the descriptor is ignored, the layout's immutable one is used
+    pub fn descriptor_info(&self, binding: u32) -> Binding<'_> {
+        Binding::Image {
+            binding,
+            ty: DescriptorType::CombinedImageSampler,
+            layout: ImageLayout::ShaderReadOnly,
+            image: &self.image,
+        }
+    }
+
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn extent(&self) -> (u32, u32) {
+        self.extent
+    }
+
+    fn identity() -> vk::ComponentMapping {
+        vk::ComponentMapping {
+            r: vk::ComponentSwizzle::Identity,
+            g: vk::ComponentSwizzle::Identity,
+            b: vk::ComponentSwizzle::Identity,
+            a: vk::ComponentSwizzle::Identity,
+        }
+    }
+}
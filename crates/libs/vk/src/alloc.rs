@@ -0,0 +1,671 @@
+use crate::{
+    Buffer, BufferCopy, Commands, Device, Error, Image, ImageCreateInfo, ImageTiling, Memory,
+    MemoryAllocateInfo, MemoryBarrier, MemoryProperties, MemoryRequirements, Pod,
+    ACCESS_MEMORY_READ, ACCESS_MEMORY_WRITE, ACCESS_TRANSFER_WRITE, BUFFER_USAGE_TRANSFER_DST,
+    PIPELINE_STAGE_ALL_COMMANDS, PIPELINE_STAGE_TRANSFER,
+};
+
+use std::cell::{Cell, RefCell};
+use std::cmp::Reverse;
+use std::mem;
+use std::rc::Rc;
+use std::slice;
+
+//max_memory_allocation_count can be as low as 4096, so resources share large blocks
+pub const DEFAULT_BLOCK_SIZE: u64 = 64 * 1024 * 1024;
+
+pub struct AllocatorCreateInfo {
+    pub memory_properties: MemoryProperties,
+    //requests larger than this get a block of their own size
+    pub block_size: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct BlockKey {
+    memory_type_index: usize,
+    mapped: bool,
+    //buffers and optimal tiling images never share a block, which keeps them
+    //buffer_image_granularity apart without tracking neighbours
+    linear: bool,
+    priority: Option<u32>,
+    //MemoryAllocateInfo::flags, e.g. device address memory only shares blocks with its own
+    flags: u32,
+}
+
+//The free ranges of a block, kept apart from its memory
+struct FreeList {
+    //(offset, size) sorted by offset, adjacent ranges are merged
+    ranges: Vec<(u64, u64)>,
+}
+
+impl FreeList {
+    fn new(size: u64) -> Self {
+        Self {
+            ranges: vec![(0, size)],
+        }
+    }
+
+    //first fit, the alignment padding in front stays free
+    fn allocate(&mut self, size: u64, alignment: u64) -> Option<u64> {
+        let free = &mut self.ranges;
+
+        let (i, offset) = free.iter().enumerate().find_map(|(i, &(offset, range))| {
+            let aligned = offset.div_ceil(alignment) * alignment;
+
+            (aligned + size <= offset + range).then_some((i, aligned))
+        })?;
+
+        let (start, range) = free[i];
+
+        let front = offset - start;
+        let back = start + range - offset - size;
+
+        match (front > 0, back > 0) {
+            (false, false) => {
+                free.remove(i);
+            }
+            (true, false) => free[i] = (start, front),
+            (false, true) => free[i] = (offset + size, back),
+            (true, true) => {
+                free[i] = (start, front);
+                free.insert(i + 1, (offset + size, back));
+            }
+        }
+
+        Some(offset)
+    }
+
+    fn free(&mut self, offset: u64, size: u64) {
+        let free = &mut self.ranges;
+
+        let i = free.partition_point(|&(start, _)| start < offset);
+
+        free.insert(i, (offset, size));
+
+        if i + 1 < free.len() && free[i].0 + free[i].1 == free[i + 1].0 {
+            free[i].1 += free[i + 1].1;
+            free.remove(i + 1);
+        }
+
+        if i > 0 && free[i - 1].0 + free[i - 1].1 == free[i].0 {
+            free[i - 1].1 += free[i].1;
+            free.remove(i);
+        }
+    }
+}
+
+struct Block {
+    key: BlockKey,
+    memory: Memory,
+    free: RefCell<FreeList>,
+    used: Cell<u64>,
+    allocations: Cell<usize>,
+}
+
+impl Block {
+    fn allocate(&self, size: u64, alignment: u64) -> Option<u64> {
+        let offset = self.free.borrow_mut().allocate(size, alignment)?;
+
+        self.used.set(self.used.get() + size);
+        self.allocations.set(self.allocations.get() + 1);
+
+        Some(offset)
+    }
+
+    fn free(&self, offset: u64, size: u64) {
+        self.free.borrow_mut().free(offset, size);
+
+        self.used.set(self.used.get() - size);
+        self.allocations.set(self.allocations.get() - 1);
+    }
+}
+
+//A range of a shared block. The range returns to the block's free list on drop, so
+//whatever is bound to it must be destroyed first.
+pub struct Allocation {
+    block: Rc<Block>,
+    offset: u64,
+    size: u64,
+    //of the resource it was made for, kept by moves
+    alignment: u64,
+}
+
+impl Allocation {
+    pub fn memory(&self) -> &Memory {
+        &self.block.memory
+    }
+
+    //where the allocation starts in memory(), aligned for the resource it was made for
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn is_mapped(&self) -> bool {
+        self.block.memory.is_mapped()
+    }
+
+    //Like Memory::write, with offset relative to the allocation and the slice ending at its end
+    pub fn write<T: Pod>(
+        &self,
+        offset: usize,
+        mut script: impl FnMut(&mut [T]),
+    ) -> Result<(), Error> {
+        let mem = self.mapped(offset, mem::align_of::<T>())?;
+
+        let data = unsafe {
+            slice::from_raw_parts_mut(
+                mem as _,
+                (self.size as usize - offset) / mem::size_of::<T>(),
+            )
+        };
+
+        script(data);
+
+        Ok(())
+    }
+
+    pub fn read<T: Pod>(&self, offset: usize, mut script: impl FnMut(&[T])) -> Result<(), Error> {
+        let mem = self.mapped(offset, mem::align_of::<T>())?;
+
+        let data = unsafe {
+            slice::from_raw_parts(
+                mem as _,
+                (self.size as usize - offset) / mem::size_of::<T>(),
+            )
+        };
+
+        script(data);
+
+        Ok(())
+    }
+
+    fn mapped(&self, offset: usize, alignment: usize) -> Result<*mut u8, Error> {
+        if offset > self.size as _ {
+            Err(Error::MemoryOutOfRange {
+                offset,
+                size: self.size,
+            })?
+        }
+
+        let mem = self.block.memory.mem.ok_or(Error::MemoryMapFailed)?;
+
+        let mem = unsafe { mem.add(self.offset as usize + offset) };
+
+        if mem as usize % alignment != 0 {
+            Err(Error::MemoryMisaligned { offset, alignment })?
+        }
+
+        Ok(mem)
+    }
+}
+
+impl Drop for Allocation {
+    fn drop(&mut self) {
+        self.block.free(self.offset, self.size);
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct AllocatorStats {
+    pub blocks: usize,
+    pub allocations: usize,
+    //bytes of device memory held by blocks
+    pub reserved: u64,
+    //bytes handed out, alignment padding counts as free
+    pub used: u64,
+    pub free_ranges: usize,
+    pub largest_free_range: u64,
+}
+
+impl AllocatorStats {
+    pub fn free(&self) -> u64 {
+        self.reserved - self.used
+    }
+
+    //0.0 while the free memory is one contiguous range, towards 1.0 as it splinters
+    //into ranges too small for larger requests
+    pub fn fragmentation(&self) -> f32 {
+        if self.free() == 0 {
+            return 0.0;
+        }
+
+        1.0 - self.largest_free_range as f32 / self.free() as f32
+    }
+}
+
+//A new place for the allocation at index of those passed to Allocator::propose_moves. The
+//resource has to be recreated there and its contents copied over before the old one is
+//dropped.
+pub struct Move {
+    pub index: usize,
+    pub allocation: Allocation,
+}
+
+pub struct DefragmentBuffer<'a> {
+    //created with BUFFER_USAGE_TRANSFER_SRC, and not written by pending submissions
+    pub buffer: &'a Buffer,
+    pub allocation: &'a Allocation,
+    //what buffer was created with, the moved buffer gets the same. Size is the creation
+    //size, the allocation may be larger
+    pub size: u64,
+    pub usage: u32,
+    pub flags: u32,
+}
+
+//A buffer moved by Allocator::defragment_buffers, replacing the one at index
+pub struct BufferMove {
+    pub index: usize,
+    pub buffer: Buffer,
+    pub allocation: Allocation,
+}
+
+//Allocates device memory in blocks per memory type and sub-allocates buffers and
+//images out of them, instead of one vkAllocateMemory per resource
+pub struct Allocator {
+    device: Rc<Device>,
+    memory_properties: MemoryProperties,
+    block_size: u64,
+    blocks: Vec<Rc<Block>>,
+}
+
+impl Allocator {
+    pub fn new(device: Rc<Device>, create_info: AllocatorCreateInfo) -> Self {
+        Self {
+            device,
+            memory_properties: create_info.memory_properties,
+            block_size: create_info.block_size,
+            blocks: vec![],
+        }
+    }
+
+    //linear is true for buffers and linear tiling images
+    pub fn allocate(
+        &mut self,
+        allocate_info: MemoryAllocateInfo,
+        requirements: MemoryRequirements,
+        mapped: bool,
+        linear: bool,
+    ) -> Result<Allocation, Error> {
+        let key = BlockKey {
+            memory_type_index: self
+                .memory_properties
+                .memory_type_index(&requirements, allocate_info.property_flags)?,
+            mapped,
+            linear,
+            priority: allocate_info.priority.map(f32::to_bits),
+            flags: allocate_info.flags,
+        };
+
+        let size = requirements.size;
+        let alignment = requirements.alignment.max(1);
+
+        for block in &self.blocks {
+            if block.key != key {
+                continue;
+            }
+
+            if let Some(offset) = block.allocate(size, alignment) {
+                return Ok(Allocation {
+                    block: block.clone(),
+                    offset,
+                    size,
+                    alignment,
+                });
+            }
+        }
+
+        let block_size = self.block_size.max(size);
+
+        let memory = Memory::allocate(
+            self.device.clone(),
+            allocate_info,
+            MemoryRequirements {
+                size: block_size,
+                alignment,
+                memory_type: 1 << key.memory_type_index,
+            },
+            self.memory_properties.clone(),
+            mapped,
+        )?;
+
+        let block = Rc::new(Block {
+            key,
+            memory,
+            free: RefCell::new(FreeList::new(block_size)),
+            used: Cell::new(0),
+            allocations: Cell::new(0),
+        });
+
+        let offset = block
+            .allocate(size, alignment)
+            .expect("new block is too small");
+
+        self.blocks.push(block.clone());
+
+        Ok(Allocation {
+            block,
+            offset,
+            size,
+            alignment,
+        })
+    }
+
+    //Proposes new places for allocations, up to max_bytes of them, to compact the blocks
+    //they are in. Allocations move out of the emptiest blocks first, into fuller blocks of
+    //the same kind or else further to the front of their own. Both the old and the new
+    //allocation stay reserved until dropped, trim afterwards to release emptied blocks.
+    pub fn propose_moves(&mut self, allocations: &[&Allocation], max_bytes: u64) -> Vec<Move> {
+        let mut order = (0..allocations.len()).collect::<Vec<_>>();
+
+        order.sort_by_key(|&index| {
+            let allocation = allocations[index];
+
+            (allocation.block.used.get(), allocation.offset)
+        });
+
+        let mut moves = vec![];
+        let mut moved = 0;
+
+        for index in order {
+            let allocation = allocations[index];
+
+            if moved + allocation.size > max_bytes {
+                continue;
+            }
+
+            let source = &allocation.block;
+
+            let mut destinations = self
+                .blocks
+                .iter()
+                .filter(|block| {
+                    !Rc::ptr_eq(block, source)
+                        && block.key == source.key
+                        && block.used.get() > source.used.get()
+                })
+                .collect::<Vec<_>>();
+
+            destinations.sort_by_key(|block| Reverse(block.used.get()));
+
+            let destination = destinations
+                .into_iter()
+                .find_map(|block| {
+                    let offset = block.allocate(allocation.size, allocation.alignment)?;
+
+                    Some((block.clone(), offset))
+                })
+                .or_else(|| {
+                    let offset = source.allocate(allocation.size, allocation.alignment)?;
+
+                    if offset >= allocation.offset {
+                        source.free(offset, allocation.size);
+
+                        return None;
+                    }
+
+                    Some((source.clone(), offset))
+                });
+
+            let Some((block, offset)) = destination else {
+                continue;
+            };
+
+            moved += allocation.size;
+
+            moves.push(Move {
+                index,
+                allocation: Allocation {
+                    block,
+                    offset,
+                    size: allocation.size,
+                    alignment: allocation.alignment,
+                },
+            });
+        }
+
+        moves
+    }
+
+    //Moves buffers along propose_moves. Each move gets a new buffer bound at its new
+    //allocation, with the copy from the old one recorded into commands. Once the submission
+    //of commands finished, swap the moved buffers in for those at their index, drop the old
+    //buffers and allocations, then trim.
+    pub fn defragment_buffers(
+        &mut self,
+        commands: &mut Commands<'_>,
+        buffers: &[DefragmentBuffer<'_>],
+        max_bytes: u64,
+    ) -> Result<Vec<BufferMove>, Error> {
+        let allocations = buffers
+            .iter()
+            .map(|buffer| buffer.allocation)
+            .collect::<Vec<_>>();
+
+        let mut buffer_moves = vec![];
+
+        for Move { index, allocation } in self.propose_moves(&allocations, max_bytes) {
+            let mut buffer = Buffer::new(
+                self.device.clone(),
+                buffers[index].size,
+                buffers[index].usage | BUFFER_USAGE_TRANSFER_DST,
+                buffers[index].flags,
+            )?;
+
+            let requirements = buffer.memory_requirements();
+
+            //the transfer usage asked for more, the buffer stays where it is
+            if requirements.size > allocation.size
+                || allocation.offset % requirements.alignment.max(1) != 0
+            {
+                continue;
+            }
+
+            buffer.bind_memory_at(allocation.memory(), allocation.offset())?;
+
+            commands.copy_buffer(
+                buffers[index].buffer,
+                &mut buffer,
+                &[BufferCopy {
+                    src_offset: 0,
+                    dst_offset: 0,
+                    size: buffers[index].size,
+                }],
+            );
+
+            buffer_moves.push(BufferMove {
+                index,
+                buffer,
+                allocation,
+            });
+        }
+
+        //so the moved buffers can be used by whatever is submitted after
+        if !buffer_moves.is_empty() {
+            commands.pipeline_barrier(
+                PIPELINE_STAGE_TRANSFER,
+                PIPELINE_STAGE_ALL_COMMANDS,
+                0,
+                &[MemoryBarrier {
+                    src_access_mask: ACCESS_TRANSFER_WRITE,
+                    dst_access_mask: ACCESS_MEMORY_READ | ACCESS_MEMORY_WRITE,
+                }],
+                &[],
+                &[],
+            );
+        }
+
+        Ok(buffer_moves)
+    }
+
+    //Frees blocks with no allocations left in them
+    pub fn trim(&mut self) {
+        self.blocks.retain(|block| block.allocations.get() > 0);
+    }
+
+    pub fn stats(&self) -> AllocatorStats {
+        let mut stats = AllocatorStats {
+            blocks: self.blocks.len(),
+            ..Default::default()
+        };
+
+        for block in &self.blocks {
+            let free = &block.free.borrow().ranges;
+
+            stats.allocations += block.allocations.get();
+            stats.reserved += block.memory.size();
+            stats.used += block.used.get();
+            stats.free_ranges += free.len();
+            stats.largest_free_range = free
+                .iter()
+                .map(|&(_, size)| size)
+                .fold(stats.largest_free_range, u64::max);
+        }
+
+        stats
+    }
+}
+
+impl Buffer {
+    //The buffer must be dropped before the allocation
+    pub fn new_with_allocator(
+        device: Rc<Device>,
+        size: u64,
+        usage: u32,
+        flags: u32,
+        allocator: &mut Allocator,
+        allocate_info: MemoryAllocateInfo,
+        mapped: bool,
+    ) -> Result<(Self, Allocation), Error> {
+        let mut buffer = Self::new(device, size, usage, flags)?;
+
+        let allocation =
+            allocator.allocate(allocate_info, buffer.memory_requirements(), mapped, true)?;
+
+        buffer.bind_memory_at(allocation.memory(), allocation.offset())?;
+
+        Ok((buffer, allocation))
+    }
+}
+
+impl Image {
+    //The image must be dropped before the allocation
+    pub fn new_with_allocator(
+        device: Rc<Device>,
+        create_info: ImageCreateInfo,
+        allocator: &mut Allocator,
+        allocate_info: MemoryAllocateInfo,
+    ) -> Result<(Self, Allocation), Error> {
+        let linear = matches!(create_info.tiling, ImageTiling::Linear);
+
+        let mut image = Self::new(device, create_info)?;
+
+        let allocation =
+            allocator.allocate(allocate_info, image.memory_requirements(), false, linear)?;
+
+        image.bind_memory_at(allocation.memory(), allocation.offset())?;
+
+        Ok((image, allocation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocate_first_fit() {
+        let mut free = FreeList::new(256);
+
+        assert_eq!(free.allocate(64, 1), Some(0));
+        assert_eq!(free.allocate(64, 1), Some(64));
+        assert_eq!(free.ranges, vec![(128, 128)]);
+
+        assert_eq!(free.allocate(128, 1), Some(128));
+        assert!(free.ranges.is_empty());
+        assert_eq!(free.allocate(1, 1), None);
+    }
+
+    #[test]
+    fn allocate_aligned() {
+        let mut free = FreeList::new(256);
+
+        assert_eq!(free.allocate(10, 1), Some(0));
+        //the padding in front of the aligned offset stays free
+        assert_eq!(free.allocate(16, 64), Some(64));
+        assert_eq!(free.ranges, vec![(10, 54), (80, 176)]);
+
+        assert_eq!(free.allocate(8, 8), Some(16));
+        assert_eq!(free.ranges, vec![(10, 6), (24, 40), (80, 176)]);
+    }
+
+    #[test]
+    fn allocate_too_large_for_aligned_range() {
+        let mut free = FreeList::new(100);
+
+        assert_eq!(free.allocate(1, 1), Some(0));
+        //99 bytes are free but only 36 of them past the alignment
+        assert_eq!(free.allocate(40, 64), None);
+        assert_eq!(free.allocate(36, 64), Some(64));
+    }
+
+    #[test]
+    fn free_coalesces() {
+        let mut free = FreeList::new(192);
+
+        let a = free.allocate(64, 1).unwrap();
+        let b = free.allocate(64, 1).unwrap();
+        let c = free.allocate(64, 1).unwrap();
+
+        free.free(a, 64);
+        free.free(c, 64);
+        assert_eq!(free.ranges, vec![(0, 64), (128, 64)]);
+
+        //merges with both neighbours
+        free.free(b, 64);
+        assert_eq!(free.ranges, vec![(0, 192)]);
+    }
+
+    #[test]
+    fn free_coalesces_one_side() {
+        let mut free = FreeList::new(192);
+
+        let a = free.allocate(64, 1).unwrap();
+        let b = free.allocate(64, 1).unwrap();
+        let _c = free.allocate(64, 1).unwrap();
+
+        free.free(a, 64);
+        free.free(b, 64);
+        assert_eq!(free.ranges, vec![(0, 128)]);
+    }
+
+    #[test]
+    fn fragmentation() {
+        let stats = AllocatorStats {
+            reserved: 256,
+            used: 128,
+            largest_free_range: 128,
+            ..Default::default()
+        };
+
+        assert_eq!(stats.fragmentation(), 0.0);
+
+        let stats = AllocatorStats {
+            largest_free_range: 32,
+            ..stats
+        };
+
+        assert_eq!(stats.fragmentation(), 0.75);
+
+        let stats = AllocatorStats {
+            reserved: 256,
+            used: 256,
+            ..Default::default()
+        };
+
+        assert_eq!(stats.fragmentation(), 0.0);
+    }
+}
@@ -0,0 +1,201 @@
+use std::cell::{Cell, RefCell};
+use std::mem;
+use std::ptr;
+use std::slice;
+
+//words of the first chunk, each chunk after it doubles
+const FIRST_CHUNK_WORDS: usize = 8 * 1024;
+
+//Bump allocator for the ffi structs a call translates its arguments into. Slices live
+//until reset, which keeps the chunks, so once a frame's worth of scratch has been seen
+//recording no longer goes through the global allocator. Values are never dropped.
+pub struct Arena {
+    //boxed [u64] chunks, which keeps every allocation 8 byte aligned. Raw so the
+    //slices already handed out are never reborrowed through the chunk list.
+    chunks: RefCell<Vec<(*mut u64, usize)>>,
+    //chunk being bumped and the next free word in it
+    chunk: Cell<usize>,
+    offset: Cell<usize>,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Arena {
+    pub fn new() -> Self {
+        Self {
+            chunks: RefCell::new(vec![]),
+            chunk: Cell::new(0),
+            offset: Cell::new(0),
+        }
+    }
+
+    pub fn alloc_from_iter<T: Copy, I>(&self, iter: I) -> &[T]
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        let len = iter.len();
+
+        if len == 0 {
+            return &[];
+        }
+
+        if mem::size_of::<T>() == 0 || mem::align_of::<T>() > mem::align_of::<u64>() {
+            panic!("arena values must be sized and at most 8 byte aligned");
+        }
+
+        //reserved before iterating, so the iterator may allocate from the arena itself
+        let data = self.reserve(len * mem::size_of::<T>()) as *mut T;
+
+        let mut count = 0;
+
+        for value in iter.take(len) {
+            unsafe { data.add(count).write(value) };
+            count += 1;
+        }
+
+        if count != len {
+            panic!("iterator yielded fewer values than its length");
+        }
+
+        unsafe { slice::from_raw_parts(data, len) }
+    }
+
+    //Frees every allocation at once, the chunks are kept for reuse
+    pub fn reset(&mut self) {
+        self.chunk.set(0);
+        self.offset.set(0);
+    }
+
+    //Bytes of all chunks, the most scratch a frame has needed so far
+    pub fn capacity(&self) -> usize {
+        self.chunks
+            .borrow()
+            .iter()
+            .map(|&(_, words)| words * mem::size_of::<u64>())
+            .sum()
+    }
+
+    fn reserve(&self, size: usize) -> *mut u8 {
+        let words = size.div_ceil(mem::size_of::<u64>());
+
+        let mut chunks = self.chunks.borrow_mut();
+
+        loop {
+            let chunk = self.chunk.get();
+            let offset = self.offset.get();
+
+            let Some(&(data, len)) = chunks.get(chunk) else {
+                let last = chunks.last().map_or(0, |&(_, len)| len);
+                let len = (last * 2).max(FIRST_CHUNK_WORDS).max(words);
+
+                let data = Box::into_raw(vec![0u64; len].into_boxed_slice()) as *mut u64;
+
+                chunks.push((data, len));
+
+                continue;
+            };
+
+            if offset + words <= len {
+                self.offset.set(offset + words);
+
+                return unsafe { data.add(offset) } as *mut u8;
+            }
+
+            //the rest of this chunk is wasted until reset
+            self.chunk.set(chunk + 1);
+            self.offset.set(0);
+        }
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        for &(data, len) in self.chunks.get_mut().iter() {
+            drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)) });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocations_are_aligned() {
+        let arena = Arena::new();
+
+        let bytes = arena.alloc_from_iter([1u8, 2, 3]);
+        let words = arena.alloc_from_iter([4u64, 5]);
+        let halves = arena.alloc_from_iter([6u16]);
+        let floats = arena.alloc_from_iter([7.0f32, 8.0]);
+
+        assert_eq!(bytes, [1, 2, 3]);
+        assert_eq!(words, [4, 5]);
+        assert_eq!(halves, [6]);
+        assert_eq!(floats, [7.0, 8.0]);
+
+        assert_eq!(words.as_ptr() as usize % mem::align_of::<u64>(), 0);
+        assert_eq!(floats.as_ptr() as usize % mem::align_of::<f32>(), 0);
+    }
+
+    #[test]
+    fn empty_allocation_takes_no_chunk() {
+        let arena = Arena::new();
+
+        assert!(arena.alloc_from_iter(std::iter::empty::<u32>()).is_empty());
+        assert_eq!(arena.capacity(), 0);
+    }
+
+    #[test]
+    fn growth_keeps_earlier_slices() {
+        let arena = Arena::new();
+
+        let first = arena.alloc_from_iter((0..FIRST_CHUNK_WORDS).map(|i| i as u64));
+
+        assert_eq!(arena.capacity(), FIRST_CHUNK_WORDS * 8);
+
+        //does not fit the first chunk, the next one doubles
+        let second = arena.alloc_from_iter([u64::MAX]);
+
+        assert_eq!(arena.capacity(), FIRST_CHUNK_WORDS * 8 * 3);
+        assert!(first
+            .iter()
+            .copied()
+            .eq((0..FIRST_CHUNK_WORDS).map(|i| i as u64)));
+        assert_eq!(second, [u64::MAX]);
+    }
+
+    #[test]
+    fn oversized_allocation_gets_its_own_chunk() {
+        let arena = Arena::new();
+
+        let words = FIRST_CHUNK_WORDS * 4 + 1;
+        let large = arena.alloc_from_iter((0..words).map(|i| i as u64));
+
+        assert_eq!(large.len(), words);
+        assert_eq!(arena.capacity(), words * 8);
+    }
+
+    #[test]
+    fn reset_reuses_chunks() {
+        let mut arena = Arena::new();
+
+        let first = arena.alloc_from_iter([1u32, 2]).as_ptr() as usize;
+        arena.alloc_from_iter((0..FIRST_CHUNK_WORDS).map(|i| i as u64));
+
+        let capacity = arena.capacity();
+
+        arena.reset();
+
+        let again = arena.alloc_from_iter([3u32, 4]).as_ptr() as usize;
+
+        assert_eq!(first, again);
+        assert_eq!(arena.capacity(), capacity);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{ffi, Buffer, Image};
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Key {
+    Buffer(ffi::Buffer),
+    Image(ffi::Image),
+}
+
+impl Key {
+    fn kind(self) -> ResourceKind {
+        match self {
+            Self::Buffer(_) => ResourceKind::Buffer,
+            Self::Image(_) => ResourceKind::Image,
+        }
+    }
+
+    fn handle(self) -> String {
+        match self {
+            Self::Buffer(handle) => format!("{:?}", handle),
+            Self::Image(handle) => format!("{:?}", handle),
+        }
+    }
+}
+
+impl From<&Buffer> for Key {
+    fn from(buffer: &Buffer) -> Self {
+        Self::Buffer(buffer.handle)
+    }
+}
+
+impl From<&Image> for Key {
+    fn from(image: &Image) -> Self {
+        Self::Image(image.handle)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResourceKind {
+    Buffer,
+    Image,
+}
+
+#[derive(Clone, Debug)]
+pub struct Access {
+    //the Commands method, e.g. "copy_buffer"
+    pub command: &'static str,
+    //innermost begin_label scope the command was recorded in
+    pub label: Option<String>,
+}
+
+//A read of a resource written earlier in the same recording with no barrier
+//covering it in between
+#[derive(Clone, Debug)]
+pub struct Hazard {
+    pub kind: ResourceKind,
+    //the name given to Commands::name_buffer or name_image, otherwise the handle
+    pub name: String,
+    pub write: Access,
+    pub read: Access,
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.command)?;
+
+        if let Some(label) = &self.label {
+            write!(f, " in \"{}\"", label)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Hazard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            ResourceKind::Buffer => "buffer",
+            ResourceKind::Image => "image",
+        };
+
+        write!(
+            f,
+            "{} {} written by {} is read by {} without a barrier in between",
+            kind, self.name, self.write, self.read
+        )
+    }
+}
+
+//Only sees resources passed to Commands directly, so transfers, vertex, index and
+//indirect buffers. Accesses through descriptor sets are not tracked.
+#[derive(Default)]
+pub(crate) struct Tracker {
+    //writes no barrier has covered yet
+    writes: HashMap<Key, Access>,
+    names: HashMap<Key, String>,
+    labels: Vec<String>,
+    hazards: Vec<Hazard>,
+}
+
+impl Tracker {
+    pub(crate) fn reset(&mut self) {
+        self.writes.clear();
+        self.names.clear();
+        self.labels.clear();
+        self.hazards.clear();
+    }
+
+    pub(crate) fn name(&mut self, resource: impl Into<Key>, name: &str) {
+        self.names.insert(resource.into(), name.to_owned());
+    }
+
+    pub(crate) fn push_label(&mut self, label: &str) {
+        self.labels.push(label.to_owned());
+    }
+
+    pub(crate) fn pop_label(&mut self) {
+        self.labels.pop();
+    }
+
+    pub(crate) fn write(&mut self, resource: impl Into<Key>, command: &'static str) {
+        let access = self.access(command);
+
+        self.writes.insert(resource.into(), access);
+    }
+
+    pub(crate) fn read(&mut self, resource: impl Into<Key>, command: &'static str) {
+        let key = resource.into();
+
+        //reported once, further reads of the same write would only repeat it
+        let Some(write) = self.writes.remove(&key) else {
+            return;
+        };
+
+        let name = match self.names.get(&key) {
+            Some(name) => format!("\"{}\" ({})", name, key.handle()),
+            None => key.handle(),
+        };
+
+        self.hazards.push(Hazard {
+            kind: key.kind(),
+            name,
+            write,
+            read: self.access(command),
+        });
+    }
+
+    pub(crate) fn barrier(&mut self, resource: impl Into<Key>) {
+        self.writes.remove(&resource.into());
+    }
+
+    //a global memory barrier covers every resource
+    pub(crate) fn barrier_all(&mut self) {
+        self.writes.clear();
+    }
+
+    pub(crate) fn hazards(&self) -> &[Hazard] {
+        &self.hazards
+    }
+
+    fn access(&self, command: &'static str) -> Access {
+        Access {
+            command,
+            label: self.labels.last().cloned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::mem;
+
+    //handles are repr(transparent) over the raw u64
+    fn buffer(handle: u64) -> Key {
+        Key::Buffer(unsafe { mem::transmute::<u64, ffi::Buffer>(handle) })
+    }
+
+    fn image(handle: u64) -> Key {
+        Key::Image(unsafe { mem::transmute::<u64, ffi::Image>(handle) })
+    }
+
+    #[test]
+    fn read_after_write_is_flagged() {
+        let mut tracker = Tracker::default();
+
+        tracker.push_label("upload");
+        tracker.write(buffer(1), "copy_buffer");
+        tracker.pop_label();
+        tracker.read(buffer(1), "bind_vertex_buffers");
+
+        let [hazard] = tracker.hazards() else {
+            panic!("expected one hazard, got {}", tracker.hazards().len());
+        };
+
+        assert_eq!(hazard.kind, ResourceKind::Buffer);
+        assert_eq!(hazard.write.command, "copy_buffer");
+        assert_eq!(hazard.write.label.as_deref(), Some("upload"));
+        assert_eq!(hazard.read.command, "bind_vertex_buffers");
+        assert_eq!(hazard.read.label, None);
+    }
+
+    #[test]
+    fn barrier_clears_write() {
+        let mut tracker = Tracker::default();
+
+        tracker.write(image(1), "copy_buffer_to_image");
+        tracker.barrier(image(1));
+        tracker.read(image(1), "blit_image");
+
+        assert!(tracker.hazards().is_empty());
+    }
+
+    #[test]
+    fn barrier_of_other_resource_keeps_write() {
+        let mut tracker = Tracker::default();
+
+        tracker.write(buffer(1), "copy_buffer");
+        tracker.barrier(buffer(2));
+        tracker.barrier(image(1));
+        tracker.read(buffer(1), "copy_buffer");
+
+        assert_eq!(tracker.hazards().len(), 1);
+    }
+
+    #[test]
+    fn global_barrier_clears_every_write() {
+        let mut tracker = Tracker::default();
+
+        tracker.write(buffer(1), "copy_buffer");
+        tracker.write(image(1), "copy_buffer_to_image");
+        tracker.barrier_all();
+        tracker.read(buffer(1), "copy_buffer");
+        tracker.read(image(1), "blit_image");
+
+        assert!(tracker.hazards().is_empty());
+    }
+
+    #[test]
+    fn hazard_is_reported_once_per_write() {
+        let mut tracker = Tracker::default();
+
+        tracker.write(buffer(1), "fill_buffer");
+        tracker.read(buffer(1), "copy_buffer");
+        tracker.read(buffer(1), "copy_buffer");
+
+        assert_eq!(tracker.hazards().len(), 1);
+    }
+
+    #[test]
+    fn hazard_uses_given_name() {
+        let mut tracker = Tracker::default();
+
+        tracker.name(buffer(0x10), "vertices");
+        tracker.write(buffer(0x10), "copy_buffer");
+        tracker.read(buffer(0x10), "bind_vertex_buffers");
+
+        assert_eq!(tracker.hazards()[0].name, "\"vertices\" (0x10)");
+    }
+
+    #[test]
+    fn reset_forgets_writes_and_hazards() {
+        let mut tracker = Tracker::default();
+
+        tracker.write(buffer(1), "copy_buffer");
+        tracker.read(buffer(1), "copy_buffer");
+        tracker.write(buffer(1), "copy_buffer");
+        tracker.reset();
+        tracker.read(buffer(1), "copy_buffer");
+
+        assert!(tracker.hazards().is_empty());
+    }
+}
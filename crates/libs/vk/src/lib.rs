@@ -1,13 +1,26 @@
 //TODO implement From for ffi types
 
+use std::cell::{Ref, RefCell};
+use std::collections::BTreeMap;
 use std::ffi::{CStr, CString};
+use std::hash::{Hash, Hasher};
 use std::mem::{self, MaybeUninit};
+use std::ops::Deref;
 use std::ptr;
 use std::rc::Rc;
 use std::slice;
+use std::thread;
 
+use arena::Arena;
+
+#[cfg(feature = "window")]
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 
+pub mod alloc;
+pub mod arena;
+#[cfg(feature = "barrier-validation")]
+pub mod hazard;
+
 mod ffi {
     use std::ffi::CStr;
     use std::fmt;
@@ -41,6 +54,26 @@ mod ffi {
         }
     };}
 
+    macro_rules! impl_from_features {
+    ($ name : ident, $($cases : ident),*) => {
+        impl_from_struct!($name, $($cases),*);
+
+        impl From<$name> for super::$name {
+            fn from(x: $name) -> Self {
+                Self {
+                    $($cases: x.$cases != 0),*
+                }
+            }
+        }
+
+        impl super::$name {
+            //true if every feature set in other is also set here
+            pub fn contains(&self, other: &Self) -> bool {
+                true $(&& (self.$cases || !other.$cases))*
+            }
+        }
+    };}
+
     macro_rules! handle {
         ($ name : ident) => {
             #[repr(transparent)]
@@ -129,13 +162,18 @@ mod ffi {
     handle_nondispatchable!(CommandPool);
     handle_nondispatchable!(Fence);
     handle_nondispatchable!(Semaphore);
+    handle_nondispatchable!(Event);
     handle_nondispatchable!(Buffer);
     handle_nondispatchable!(DeviceMemory);
     handle_nondispatchable!(DescriptorPool);
     handle_nondispatchable!(DescriptorSet);
+    handle_nondispatchable!(DescriptorUpdateTemplate);
     handle_nondispatchable!(Sampler);
+    handle_nondispatchable!(SamplerYcbcrConversion);
+    handle_nondispatchable!(QueryPool);
 
     pub type DeviceSize = u64;
+    pub type DeviceAddress = u64;
     pub type Flags = u32;
 
     #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -183,12 +221,16 @@ mod ffi {
         DeviceCreateInfo = 3,
         SubmitInfo = 4,
         MemoryAllocateInfo = 5,
+        MappedMemoryRange = 6,
         FenceCreateInfo = 8,
         SemaphoreCreateInfo = 9,
+        EventCreateInfo = 10,
+        QueryPoolCreateInfo = 11,
         BufferCreateInfo = 12,
         ImageCreateInfo = 14,
         ImageViewCreateInfo = 15,
         ShaderModuleCreateInfo = 16,
+        PipelineCacheCreateInfo = 17,
         PipelineShaderStageCreateInfo = 18,
         PipelineVertexInputStateCreateInfo = 19,
         PipelineInputAssemblyStateCreateInfo = 20,
@@ -204,6 +246,7 @@ mod ffi {
         PipelineLayoutCreateInfo = 30,
         SamplerCreateInfo = 31,
         DescriptorSetLayoutCreateInfo = 32,
+        DescriptorUpdateTemplateCreateInfo = 1000085000,
         DescriptorPoolCreateInfo = 33,
         DescriptorSetAllocateInfo = 34,
         WriteDescriptorSet = 35,
@@ -212,14 +255,85 @@ mod ffi {
         RenderPassCreateInfo = 38,
         CommandPoolCreateInfo = 39,
         CommandBufferAllocateInfo = 40,
+        CommandBufferInheritanceInfo = 41,
         CommandBufferBeginInfo = 42,
         RenderPassBeginInfo = 43,
         BufferMemoryBarrier = 44,
         ImageMemoryBarrier = 45,
         MemoryBarrier = 46,
+        PhysicalDeviceFeatures2 = 1000059000,
+        PhysicalDeviceProperties2 = 1000059001,
+        FormatProperties2 = 1000059002,
+        ExternalMemoryImageCreateInfo = 1000072001,
+        ImportMemoryFdInfo = 1000074000,
+        MemoryFdProperties = 1000074001,
+        MemoryDedicatedAllocateInfo = 1000127001,
+        ProtectedSubmitInfo = 1000145000,
+        PhysicalDeviceProtectedMemoryFeatures = 1000145001,
+        DeviceQueueInfo2 = 1000145003,
+        PipelineVertexInputDivisorStateCreateInfo = 1000190001,
+        PhysicalDeviceVertexAttributeDivisorFeatures = 1000190002,
+        PhysicalDeviceLineRasterizationFeatures = 1000259000,
+        PipelineRasterizationLineStateCreateInfo = 1000259001,
+        PhysicalDeviceShaderAtomicFloatFeatures = 1000260000,
+        PhysicalDeviceDepthClipEnableFeatures = 1000102000,
+        PipelineRasterizationDepthClipStateCreateInfo = 1000102001,
+        PhysicalDeviceFragmentShaderInterlockFeatures = 1000251000,
+        DescriptorSetLayoutBindingFlagsCreateInfo = 1000161000,
+        PhysicalDeviceDescriptorIndexingFeatures = 1000161001,
+        DescriptorSetVariableDescriptorCountAllocateInfo = 1000161003,
+        PhysicalDevicePerformanceQueryFeatures = 1000116000,
+        QueryPoolPerformanceCreateInfo = 1000116002,
+        AcquireProfilingLockInfo = 1000116004,
+        PerformanceCounter = 1000116005,
+        PerformanceCounterDescription = 1000116006,
+        PhysicalDeviceMemoryPriorityFeatures = 1000238000,
+        MemoryPriorityAllocateInfo = 1000238001,
+        PhysicalDevicePageableDeviceLocalMemoryFeatures = 1000412000,
+        PhysicalDevicePipelineExecutablePropertiesFeatures = 1000269000,
+        PipelineInfo = 1000269001,
+        PipelineExecutableProperties = 1000269002,
+        PipelineExecutableInfo = 1000269003,
+        PipelineExecutableStatistic = 1000269004,
+        PipelineExecutableInternalRepresentation = 1000269005,
+        MemoryBarrier2 = 1000314000,
+        BufferMemoryBarrier2 = 1000314001,
+        ImageMemoryBarrier2 = 1000314002,
+        DependencyInfo = 1000314003,
+        SubmitInfo2 = 1000314004,
+        SemaphoreSubmitInfo = 1000314005,
+        CommandBufferSubmitInfo = 1000314006,
+        PhysicalDeviceSynchronization2Features = 1000314007,
+        PhysicalDeviceSurfaceInfo2 = 1000119000,
+        SurfaceCapabilities2 = 1000119001,
+        SurfacePresentMode = 1000274000,
+        SurfacePresentScalingCapabilities = 1000274001,
+        PhysicalDeviceSwapchainMaintenance1Features = 1000275000,
+        SwapchainPresentFenceInfo = 1000275001,
+        MemoryAllocateFlagsInfo = 1000060000,
+        BufferDeviceAddressInfo = 1000244001,
+        PhysicalDeviceBufferDeviceAddressFeatures = 1000257000,
+        RenderingInfo = 1000044000,
+        RenderingAttachmentInfo = 1000044001,
+        PipelineRenderingCreateInfo = 1000044002,
+        PhysicalDeviceDynamicRenderingFeatures = 1000044003,
+        PhysicalDeviceInheritedViewportScissorFeatures = 1000278000,
+        CommandBufferInheritanceViewportScissorInfo = 1000278001,
+        SamplerYcbcrConversionCreateInfo = 1000156000,
+        SamplerYcbcrConversionInfo = 1000156001,
+        PhysicalDeviceSamplerYcbcrConversionFeatures = 1000156004,
+        DrmFormatModifierPropertiesList = 1000158000,
+        ImageDrmFormatModifierExplicitCreateInfo = 1000158004,
         SwapchainCreateInfo = 1000001000,
+        HdrMetadata = 1000105000,
         PresentInfo = 1000001001,
         XlibSurfaceCreateInfo = 1000004000,
+        XcbSurfaceCreateInfo = 1000005000,
+        WaylandSurfaceCreateInfo = 1000006000,
+        MetalSurfaceCreateInfo = 1000217000,
+        DebugUtilsLabel = 1000128002,
+        ValidationFeatures = 1000247000,
+        PhysicalDeviceRayTracingPipelineProperties = 1000347001,
         Win32SurfaceCreateInfo = 1000009000,
         DebugUtilsMessengerCreateInfo = 1000128004,
     }
@@ -258,32 +372,423 @@ mod ffi {
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub enum Format {
+        Rg4UnormPack8 = 1,
+        Rgba4UnormPack16 = 2,
+        Bgra4UnormPack16 = 3,
+        R5G6B5UnormPack16 = 4,
+        B5G6R5UnormPack16 = 5,
+        R5G5B5A1UnormPack16 = 6,
+        B5G5R5A1UnormPack16 = 7,
+        A1R5G5B5UnormPack16 = 8,
+        R8Unorm = 9,
+        R8Snorm = 10,
+        R8Uscaled = 11,
+        R8Sscaled = 12,
+        R8Uint = 13,
+        R8Sint = 14,
+        R8Srgb = 15,
+        Rg8Unorm = 16,
+        Rg8Snorm = 17,
+        Rg8Uscaled = 18,
+        Rg8Sscaled = 19,
+        Rg8Uint = 20,
+        Rg8Sint = 21,
+        Rg8Srgb = 22,
+        Rgb8Unorm = 23,
+        Rgb8Snorm = 24,
+        Rgb8Uscaled = 25,
+        Rgb8Sscaled = 26,
+        Rgb8Uint = 27,
+        Rgb8Sint = 28,
+        Rgb8Srgb = 29,
+        Bgr8Unorm = 30,
+        Bgr8Snorm = 31,
+        Bgr8Uscaled = 32,
+        Bgr8Sscaled = 33,
+        Bgr8Uint = 34,
+        Bgr8Sint = 35,
+        Bgr8Srgb = 36,
+        Rgba8Unorm = 37,
+        Rgba8Snorm = 38,
+        Rgba8Uscaled = 39,
+        Rgba8Sscaled = 40,
+        Rgba8Uint = 41,
+        Rgba8Sint = 42,
         Rgba8Srgb = 43,
+        Bgra8Unorm = 44,
+        Bgra8Snorm = 45,
+        Bgra8Uscaled = 46,
+        Bgra8Sscaled = 47,
+        Bgra8Uint = 48,
+        Bgra8Sint = 49,
         Bgra8Srgb = 50,
+        Abgr8UnormPack32 = 51,
+        Abgr8SnormPack32 = 52,
+        Abgr8UscaledPack32 = 53,
+        Abgr8SscaledPack32 = 54,
+        Abgr8UintPack32 = 55,
+        Abgr8SintPack32 = 56,
+        Abgr8SrgbPack32 = 57,
+        A2R10G10B10UnormPack32 = 58,
+        A2R10G10B10SnormPack32 = 59,
+        A2R10G10B10UscaledPack32 = 60,
+        A2R10G10B10SscaledPack32 = 61,
+        A2R10G10B10UintPack32 = 62,
+        A2R10G10B10SintPack32 = 63,
+        A2B10G10R10UnormPack32 = 64,
+        A2B10G10R10SnormPack32 = 65,
+        A2B10G10R10UscaledPack32 = 66,
+        A2B10G10R10SscaledPack32 = 67,
+        A2B10G10R10UintPack32 = 68,
+        A2B10G10R10SintPack32 = 69,
+        R16Unorm = 70,
+        R16Snorm = 71,
+        R16Uscaled = 72,
+        R16Sscaled = 73,
         R16Uint = 74,
+        R16Sint = 75,
+        R16Sfloat = 76,
+        Rg16Unorm = 77,
+        Rg16Snorm = 78,
+        Rg16Uscaled = 79,
+        Rg16Sscaled = 80,
+        Rg16Uint = 81,
+        Rg16Sint = 82,
+        Rg16Sfloat = 83,
+        Rgb16Unorm = 84,
+        Rgb16Snorm = 85,
+        Rgb16Uscaled = 86,
+        Rgb16Sscaled = 87,
+        Rgb16Uint = 88,
+        Rgb16Sint = 89,
+        Rgb16Sfloat = 90,
+        Rgba16Unorm = 91,
+        Rgba16Snorm = 92,
+        Rgba16Uscaled = 93,
+        Rgba16Sscaled = 94,
+        Rgba16Uint = 95,
+        Rgba16Sint = 96,
+        Rgba16Sfloat = 97,
         R32Uint = 98,
+        R32Sint = 99,
         R32Sfloat = 100,
+        Rg32Uint = 101,
+        Rg32Sint = 102,
         Rg32Sfloat = 103,
         Rgb32Uint = 104,
+        Rgb32Sint = 105,
         Rgb32Sfloat = 106,
+        Rgba32Uint = 107,
+        Rgba32Sint = 108,
         Rgba32Sfloat = 109,
+        R64Uint = 110,
+        R64Sint = 111,
+        R64Sfloat = 112,
+        Rg64Uint = 113,
+        Rg64Sint = 114,
+        Rg64Sfloat = 115,
+        Rgb64Uint = 116,
+        Rgb64Sint = 117,
+        Rgb64Sfloat = 118,
+        Rgba64Uint = 119,
+        Rgba64Sint = 120,
+        Rgba64Sfloat = 121,
+        B10G11R11UfloatPack32 = 122,
+        E5B9G9R9UfloatPack32 = 123,
+        D16Unorm = 124,
+        X8D24UnormPack32 = 125,
         D32Sfloat = 126,
+        S8Uint = 127,
+        D16UnormS8Uint = 128,
+        D24UnormS8Uint = 129,
+        D32SfloatS8Uint = 130,
+        Bc1RgbUnormBlock = 131,
+        Bc1RgbSrgbBlock = 132,
+        Bc1RgbaUnormBlock = 133,
+        Bc1RgbaSrgbBlock = 134,
+        Bc2UnormBlock = 135,
+        Bc2SrgbBlock = 136,
+        Bc3UnormBlock = 137,
+        Bc3SrgbBlock = 138,
+        Bc4UnormBlock = 139,
+        Bc4SnormBlock = 140,
+        Bc5UnormBlock = 141,
+        Bc5SnormBlock = 142,
+        Bc6hUfloatBlock = 143,
+        Bc6hSfloatBlock = 144,
+        Bc7UnormBlock = 145,
+        Bc7SrgbBlock = 146,
+        Etc2Rgb8UnormBlock = 147,
+        Etc2Rgb8SrgbBlock = 148,
+        Etc2R8G8B8A1UnormBlock = 149,
+        Etc2R8G8B8A1SrgbBlock = 150,
+        Etc2Rgba8UnormBlock = 151,
+        Etc2Rgba8SrgbBlock = 152,
+        EacR11UnormBlock = 153,
+        EacR11SnormBlock = 154,
+        EacRg11UnormBlock = 155,
+        EacRg11SnormBlock = 156,
+        Astc4x4UnormBlock = 157,
+        Astc4x4SrgbBlock = 158,
+        Astc5x4UnormBlock = 159,
+        Astc5x4SrgbBlock = 160,
+        Astc5x5UnormBlock = 161,
+        Astc5x5SrgbBlock = 162,
+        Astc6x5UnormBlock = 163,
+        Astc6x5SrgbBlock = 164,
+        Astc6x6UnormBlock = 165,
+        Astc6x6SrgbBlock = 166,
+        Astc8x5UnormBlock = 167,
+        Astc8x5SrgbBlock = 168,
+        Astc8x6UnormBlock = 169,
+        Astc8x6SrgbBlock = 170,
+        Astc8x8UnormBlock = 171,
+        Astc8x8SrgbBlock = 172,
+        Astc10x5UnormBlock = 173,
+        Astc10x5SrgbBlock = 174,
+        Astc10x6UnormBlock = 175,
+        Astc10x6SrgbBlock = 176,
+        Astc10x8UnormBlock = 177,
+        Astc10x8SrgbBlock = 178,
+        Astc10x10UnormBlock = 179,
+        Astc10x10SrgbBlock = 180,
+        Astc12x10UnormBlock = 181,
+        Astc12x10SrgbBlock = 182,
+        Astc12x12UnormBlock = 183,
+        Astc12x12SrgbBlock = 184,
+        G8B8R83Plane420Unorm = 1000156002,
+        G8B8R82Plane420Unorm = 1000156003,
+        G10X6B10X6R10X62Plane420Unorm3Pack16 = 1000156013,
     }
 
     impl_from_enum!(
         Format,
+        Rg4UnormPack8,
+        Rgba4UnormPack16,
+        Bgra4UnormPack16,
+        R5G6B5UnormPack16,
+        B5G6R5UnormPack16,
+        R5G5B5A1UnormPack16,
+        B5G5R5A1UnormPack16,
+        A1R5G5B5UnormPack16,
+        R8Unorm,
+        R8Snorm,
+        R8Uscaled,
+        R8Sscaled,
+        R8Uint,
+        R8Sint,
+        R8Srgb,
+        Rg8Unorm,
+        Rg8Snorm,
+        Rg8Uscaled,
+        Rg8Sscaled,
+        Rg8Uint,
+        Rg8Sint,
+        Rg8Srgb,
+        Rgb8Unorm,
+        Rgb8Snorm,
+        Rgb8Uscaled,
+        Rgb8Sscaled,
+        Rgb8Uint,
+        Rgb8Sint,
+        Rgb8Srgb,
+        Bgr8Unorm,
+        Bgr8Snorm,
+        Bgr8Uscaled,
+        Bgr8Sscaled,
+        Bgr8Uint,
+        Bgr8Sint,
+        Bgr8Srgb,
+        Rgba8Unorm,
+        Rgba8Snorm,
+        Rgba8Uscaled,
+        Rgba8Sscaled,
+        Rgba8Uint,
+        Rgba8Sint,
         Rgba8Srgb,
+        Bgra8Unorm,
+        Bgra8Snorm,
+        Bgra8Uscaled,
+        Bgra8Sscaled,
+        Bgra8Uint,
+        Bgra8Sint,
         Bgra8Srgb,
+        Abgr8UnormPack32,
+        Abgr8SnormPack32,
+        Abgr8UscaledPack32,
+        Abgr8SscaledPack32,
+        Abgr8UintPack32,
+        Abgr8SintPack32,
+        Abgr8SrgbPack32,
+        A2R10G10B10UnormPack32,
+        A2R10G10B10SnormPack32,
+        A2R10G10B10UscaledPack32,
+        A2R10G10B10SscaledPack32,
+        A2R10G10B10UintPack32,
+        A2R10G10B10SintPack32,
+        A2B10G10R10UnormPack32,
+        A2B10G10R10SnormPack32,
+        A2B10G10R10UscaledPack32,
+        A2B10G10R10SscaledPack32,
+        A2B10G10R10UintPack32,
+        A2B10G10R10SintPack32,
+        R16Unorm,
+        R16Snorm,
+        R16Uscaled,
+        R16Sscaled,
         R16Uint,
+        R16Sint,
+        R16Sfloat,
+        Rg16Unorm,
+        Rg16Snorm,
+        Rg16Uscaled,
+        Rg16Sscaled,
+        Rg16Uint,
+        Rg16Sint,
+        Rg16Sfloat,
+        Rgb16Unorm,
+        Rgb16Snorm,
+        Rgb16Uscaled,
+        Rgb16Sscaled,
+        Rgb16Uint,
+        Rgb16Sint,
+        Rgb16Sfloat,
+        Rgba16Unorm,
+        Rgba16Snorm,
+        Rgba16Uscaled,
+        Rgba16Sscaled,
+        Rgba16Uint,
+        Rgba16Sint,
+        Rgba16Sfloat,
         R32Uint,
+        R32Sint,
         R32Sfloat,
+        Rg32Uint,
+        Rg32Sint,
         Rg32Sfloat,
         Rgb32Uint,
+        Rgb32Sint,
         Rgb32Sfloat,
+        Rgba32Uint,
+        Rgba32Sint,
         Rgba32Sfloat,
-        D32Sfloat
+        R64Uint,
+        R64Sint,
+        R64Sfloat,
+        Rg64Uint,
+        Rg64Sint,
+        Rg64Sfloat,
+        Rgb64Uint,
+        Rgb64Sint,
+        Rgb64Sfloat,
+        Rgba64Uint,
+        Rgba64Sint,
+        Rgba64Sfloat,
+        B10G11R11UfloatPack32,
+        E5B9G9R9UfloatPack32,
+        D16Unorm,
+        X8D24UnormPack32,
+        D32Sfloat,
+        S8Uint,
+        D16UnormS8Uint,
+        D24UnormS8Uint,
+        D32SfloatS8Uint,
+        Bc1RgbUnormBlock,
+        Bc1RgbSrgbBlock,
+        Bc1RgbaUnormBlock,
+        Bc1RgbaSrgbBlock,
+        Bc2UnormBlock,
+        Bc2SrgbBlock,
+        Bc3UnormBlock,
+        Bc3SrgbBlock,
+        Bc4UnormBlock,
+        Bc4SnormBlock,
+        Bc5UnormBlock,
+        Bc5SnormBlock,
+        Bc6hUfloatBlock,
+        Bc6hSfloatBlock,
+        Bc7UnormBlock,
+        Bc7SrgbBlock,
+        Etc2Rgb8UnormBlock,
+        Etc2Rgb8SrgbBlock,
+        Etc2R8G8B8A1UnormBlock,
+        Etc2R8G8B8A1SrgbBlock,
+        Etc2Rgba8UnormBlock,
+        Etc2Rgba8SrgbBlock,
+        EacR11UnormBlock,
+        EacR11SnormBlock,
+        EacRg11UnormBlock,
+        EacRg11SnormBlock,
+        Astc4x4UnormBlock,
+        Astc4x4SrgbBlock,
+        Astc5x4UnormBlock,
+        Astc5x4SrgbBlock,
+        Astc5x5UnormBlock,
+        Astc5x5SrgbBlock,
+        Astc6x5UnormBlock,
+        Astc6x5SrgbBlock,
+        Astc6x6UnormBlock,
+        Astc6x6SrgbBlock,
+        Astc8x5UnormBlock,
+        Astc8x5SrgbBlock,
+        Astc8x6UnormBlock,
+        Astc8x6SrgbBlock,
+        Astc8x8UnormBlock,
+        Astc8x8SrgbBlock,
+        Astc10x5UnormBlock,
+        Astc10x5SrgbBlock,
+        Astc10x6UnormBlock,
+        Astc10x6SrgbBlock,
+        Astc10x8UnormBlock,
+        Astc10x8SrgbBlock,
+        Astc10x10UnormBlock,
+        Astc10x10SrgbBlock,
+        Astc12x10UnormBlock,
+        Astc12x10SrgbBlock,
+        Astc12x12UnormBlock,
+        Astc12x12SrgbBlock,
+        G8B8R83Plane420Unorm,
+        G8B8R82Plane420Unorm,
+        G10X6B10X6R10X62Plane420Unorm3Pack16
+    );
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum SamplerYcbcrModelConversion {
+        RgbIdentity = 0,
+        YcbcrIdentity = 1,
+        Ycbcr709 = 2,
+        Ycbcr601 = 3,
+        Ycbcr2020 = 4,
+    }
+
+    impl_from_enum!(
+        SamplerYcbcrModelConversion,
+        RgbIdentity,
+        YcbcrIdentity,
+        Ycbcr709,
+        Ycbcr601,
+        Ycbcr2020
     );
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum SamplerYcbcrRange {
+        ItuFull = 0,
+        ItuNarrow = 1,
+    }
+
+    impl_from_enum!(SamplerYcbcrRange, ItuFull, ItuNarrow);
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum ChromaLocation {
+        CositedEven = 0,
+        Midpoint = 1,
+    }
+
+    impl_from_enum!(ChromaLocation, CositedEven, Midpoint);
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub enum IndexType {
@@ -338,11 +843,12 @@ mod ffi {
         pub supported_usage_flags: u32,
     }
 
+    //raw, since drivers report formats and color spaces without a variant here
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct SurfaceFormat {
-        pub format: Format,
-        pub color_space: ColorSpace,
+        pub format: u32,
+        pub color_space: u32,
     }
 
     #[derive(Clone, Copy)]
@@ -392,6 +898,24 @@ mod ffi {
     }
     #[derive(Clone, Copy)]
     #[repr(C)]
+    pub enum ValidationFeatureEnable {
+        GpuAssisted = 0,
+        BestPractices = 2,
+        DebugPrintf = 3,
+        SynchronizationValidation = 4,
+    }
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ValidationFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub enabled_validation_feature_count: u32,
+        pub enabled_validation_features: *const ValidationFeatureEnable,
+        pub disabled_validation_feature_count: u32,
+        pub disabled_validation_features: *const i32,
+    }
+    #[derive(Clone, Copy)]
+    #[repr(C)]
     pub struct DebugUtilsObjectNameInfo {
         pub structure_type: StructureType,
         pub p_next: *const (),
@@ -460,10 +984,28 @@ mod ffi {
             .to_string_lossy()
             .into_owned();
 
+        let message_id_name = if callback_data.message_id_name.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(callback_data.message_id_name)
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        let labels = (0..callback_data.cmd_buf_label_count as usize)
+            .map(|i| {
+                CStr::from_ptr((*callback_data.cmd_buf_labels.add(i)).label_name)
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect::<Vec<_>>();
+
         let exposed_callback_data = super::DebugUtilsMessengerCallbackData {
             message_severity,
             message_type,
             message: &message,
+            message_id_name: &message_id_name,
+            labels: &labels,
         };
 
         f(&exposed_callback_data) as _
@@ -599,6 +1141,37 @@ mod ffi {
         pub residency_non_resident_strict: Bool,
     }
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct FormatProperties {
+        pub linear_tiling_features: Flags,
+        pub optimal_tiling_features: Flags,
+        pub buffer_features: Flags,
+    }
+
+    #[repr(C)]
+    pub struct FormatProperties2 {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub format_properties: FormatProperties,
+    }
+
+    #[repr(C)]
+    pub struct DrmFormatModifierPropertiesList {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub drm_format_modifier_count: u32,
+        pub drm_format_modifier_properties: *mut DrmFormatModifierProperties,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DrmFormatModifierProperties {
+        pub drm_format_modifier: u64,
+        pub drm_format_modifier_plane_count: u32,
+        pub drm_format_modifier_tiling_features: Flags,
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct PhysicalDeviceProperties {
@@ -613,6 +1186,27 @@ mod ffi {
         pub sparse_properties: PhysicalDeviceSparseProperties,
     }
 
+    #[repr(C)]
+    pub struct PhysicalDeviceProperties2 {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub properties: PhysicalDeviceProperties,
+    }
+
+    #[repr(C)]
+    pub struct PhysicalDeviceRayTracingPipelineProperties {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub shader_group_handle_size: u32,
+        pub max_ray_recursion_depth: u32,
+        pub max_shader_group_stride: u32,
+        pub shader_group_base_alignment: u32,
+        pub shader_group_handle_capture_replay_size: u32,
+        pub max_ray_dispatch_invocation_count: u32,
+        pub shader_group_handle_alignment: u32,
+        pub max_ray_hit_attribute_size: u32,
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct PhysicalDeviceFeatures {
@@ -673,7 +1267,7 @@ mod ffi {
         pub inherited_queries: Bool,
     }
 
-    impl_from_struct!(
+    impl_from_features!(
         PhysicalDeviceFeatures,
         robust_buffer_access,
         full_draw_index_uint_32,
@@ -732,6 +1326,13 @@ mod ffi {
         inherited_queries
     );
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ExtensionProperties {
+        pub extension_name: [i8; 256],
+        pub spec_version: u32,
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct QueueFamilyProperties {
@@ -752,6 +1353,118 @@ mod ffi {
         pub queue_priorities: *const f32,
     }
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DeviceQueueInfo2 {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: u32,
+        pub queue_family_index: u32,
+        pub queue_index: u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceFeatures2 {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub features: PhysicalDeviceFeatures,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceProtectedMemoryFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub protected_memory: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceVertexAttributeDivisorFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub vertex_attribute_instance_rate_divisor: Bool,
+        pub vertex_attribute_instance_rate_zero_divisor: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceLineRasterizationFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub rectangular_lines: Bool,
+        pub bresenham_lines: Bool,
+        pub smooth_lines: Bool,
+        pub stippled_rectangular_lines: Bool,
+        pub stippled_bresenham_lines: Bool,
+        pub stippled_smooth_lines: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceShaderAtomicFloatFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub shader_buffer_float_32_atomics: Bool,
+        pub shader_buffer_float_32_atomic_add: Bool,
+        pub shader_buffer_float_64_atomics: Bool,
+        pub shader_buffer_float_64_atomic_add: Bool,
+        pub shader_shared_float_32_atomics: Bool,
+        pub shader_shared_float_32_atomic_add: Bool,
+        pub shader_shared_float_64_atomics: Bool,
+        pub shader_shared_float_64_atomic_add: Bool,
+        pub shader_image_float_32_atomics: Bool,
+        pub shader_image_float_32_atomic_add: Bool,
+        pub sparse_image_float_32_atomics: Bool,
+        pub sparse_image_float_32_atomic_add: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceDepthClipEnableFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub depth_clip_enable: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceFragmentShaderInterlockFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub fragment_shader_sample_interlock: Bool,
+        pub fragment_shader_pixel_interlock: Bool,
+        pub fragment_shader_shading_rate_interlock: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceDescriptorIndexingFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub shader_input_attachment_array_dynamic_indexing: Bool,
+        pub shader_uniform_texel_buffer_array_dynamic_indexing: Bool,
+        pub shader_storage_texel_buffer_array_dynamic_indexing: Bool,
+        pub shader_uniform_buffer_array_non_uniform_indexing: Bool,
+        pub shader_sampled_image_array_non_uniform_indexing: Bool,
+        pub shader_storage_buffer_array_non_uniform_indexing: Bool,
+        pub shader_storage_image_array_non_uniform_indexing: Bool,
+        pub shader_input_attachment_array_non_uniform_indexing: Bool,
+        pub shader_uniform_texel_buffer_array_non_uniform_indexing: Bool,
+        pub shader_storage_texel_buffer_array_non_uniform_indexing: Bool,
+        pub descriptor_binding_uniform_buffer_update_after_bind: Bool,
+        pub descriptor_binding_sampled_image_update_after_bind: Bool,
+        pub descriptor_binding_storage_image_update_after_bind: Bool,
+        pub descriptor_binding_storage_buffer_update_after_bind: Bool,
+        pub descriptor_binding_uniform_texel_buffer_update_after_bind: Bool,
+        pub descriptor_binding_storage_texel_buffer_update_after_bind: Bool,
+        pub descriptor_binding_update_unused_while_pending: Bool,
+        pub descriptor_binding_partially_bound: Bool,
+        pub descriptor_binding_variable_descriptor_count: Bool,
+        pub runtime_descriptor_array: Bool,
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct DeviceCreateInfo {
@@ -778,6 +1491,39 @@ mod ffi {
         pub window: u64,
     }
 
+    #[cfg(target_os = "linux")]
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct XcbSurfaceCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: u32,
+        pub connection: *mut (),
+        pub window: u32,
+    }
+
+    #[cfg(target_os = "linux")]
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct WaylandSurfaceCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: u32,
+        pub display: *mut (),
+        pub surface: *mut (),
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct MetalSurfaceCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: u32,
+        //CAMetalLayer
+        pub layer: *const (),
+    }
+
     #[cfg(target_os = "windows")]
     #[derive(Clone, Copy)]
     #[repr(C)]
@@ -868,6 +1614,16 @@ mod ffi {
         pub subresource_range: ImageSubresourceRange,
     }
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PipelineCacheCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: u32,
+        pub initial_data_size: usize,
+        pub initial_data: *const (),
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct ShaderModuleCreateInfo {
@@ -995,7 +1751,7 @@ mod ffi {
             Self {
                 flags: 0,
                 format: attachment_description.format.into(),
-                samples: attachment_description.samples as _,
+                samples: attachment_description.samples.to_vk(),
                 load_op: attachment_description.load_op.into(),
                 store_op: attachment_description.store_op.into(),
                 stencil_load_op: attachment_description.stencil_load_op.into(),
@@ -1112,6 +1868,22 @@ mod ffi {
         pub offset: u32,
     }
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct VertexInputBindingDivisorDescription {
+        pub binding: u32,
+        pub divisor: u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PipelineVertexInputDivisorStateCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub vertex_binding_divisor_count: u32,
+        pub vertex_binding_divisors: *const VertexInputBindingDivisorDescription,
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct PipelineVertexInputStateCreateInfo {
@@ -1132,6 +1904,8 @@ mod ffi {
         LineStrip = 2,
         TriangleList = 3,
         TriangleStrip = 4,
+        LineListWithAdjacency = 6,
+        LineStripWithAdjacency = 7,
     }
 
     impl From<super::PrimitiveTopology> for PrimitiveTopology {
@@ -1142,6 +1916,8 @@ mod ffi {
                 super::PrimitiveTopology::LineStrip => Self::LineStrip,
                 super::PrimitiveTopology::TriangleList => Self::TriangleList,
                 super::PrimitiveTopology::TriangleStrip => Self::TriangleStrip,
+                super::PrimitiveTopology::LineListWithAdjacency => Self::LineListWithAdjacency,
+                super::PrimitiveTopology::LineStripWithAdjacency => Self::LineStripWithAdjacency,
             }
         }
     }
@@ -1247,6 +2023,46 @@ mod ffi {
         pub line_width: f32,
     }
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum LineRasterizationMode {
+        Default = 0,
+        Rectangular = 1,
+        Bresenham = 2,
+        RectangularSmooth = 3,
+    }
+
+    impl From<super::LineRasterizationMode> for LineRasterizationMode {
+        fn from(mode: super::LineRasterizationMode) -> Self {
+            match mode {
+                super::LineRasterizationMode::Default => Self::Default,
+                super::LineRasterizationMode::Rectangular => Self::Rectangular,
+                super::LineRasterizationMode::Bresenham => Self::Bresenham,
+                super::LineRasterizationMode::RectangularSmooth => Self::RectangularSmooth,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PipelineRasterizationLineStateCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub line_rasterization_mode: LineRasterizationMode,
+        pub stippled_line_enable: Bool,
+        pub line_stipple_factor: u32,
+        pub line_stipple_pattern: u16,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PipelineRasterizationDepthClipStateCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: u32,
+        pub depth_clip_enable: Bool,
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct PipelineMultisampleStateCreateInfo {
@@ -1323,6 +2139,20 @@ mod ffi {
         pub reference: u32,
     }
 
+    impl From<super::StencilOpState> for StencilOpState {
+        fn from(state: super::StencilOpState) -> Self {
+            Self {
+                fail_op: state.fail_op.into(),
+                pass_op: state.pass_op.into(),
+                depth_fail_op: state.depth_fail_op.into(),
+                compare_op: state.compare_op.into(),
+                compare_mask: state.compare_mask,
+                write_mask: state.write_mask,
+                reference: state.reference,
+            }
+        }
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct PipelineDepthStencilStateCreateInfo {
@@ -1418,12 +2248,16 @@ mod ffi {
     #[repr(C)]
     pub enum DynamicState {
         Viewport = 0,
+        Scissor = 1,
+        BlendConstants = 4,
     }
 
     impl From<super::DynamicState> for DynamicState {
         fn from(dynamic_state: super::DynamicState) -> Self {
             match dynamic_state {
                 super::DynamicState::Viewport => Self::Viewport,
+                super::DynamicState::Scissor => Self::Scissor,
+                super::DynamicState::BlendConstants => Self::BlendConstants,
             }
         }
     }
@@ -1533,7 +2367,92 @@ mod ffi {
         pub structure_type: StructureType,
         pub p_next: *const (),
         pub flags: u32,
-        pub inheritence_info: *const (),
+        pub inheritence_info: *const CommandBufferInheritanceInfo,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct CommandBufferInheritanceInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub render_pass: RenderPass,
+        pub subpass: u32,
+        pub framebuffer: Framebuffer,
+        pub occlusion_query_enable: Bool,
+        pub query_flags: u32,
+        pub pipeline_statistics: u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct CommandBufferInheritanceViewportScissorInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub viewport_scissor_2d: Bool,
+        pub viewport_depth_count: u32,
+        pub viewport_depths: *const Viewport,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceDynamicRenderingFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub dynamic_rendering: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct RenderingAttachmentInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub image_view: ImageView,
+        pub image_layout: ImageLayout,
+        pub resolve_mode: u32,
+        pub resolve_image_view: ImageView,
+        pub resolve_image_layout: ImageLayout,
+        pub load_op: AttachmentLoadOp,
+        pub store_op: AttachmentStoreOp,
+        pub clear_value: ClearValue,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct RenderingInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: u32,
+        pub render_area: Rect2d,
+        pub layer_count: u32,
+        pub view_mask: u32,
+        pub color_attachment_count: u32,
+        pub color_attachments: *const RenderingAttachmentInfo,
+        pub depth_attachment: *const RenderingAttachmentInfo,
+        pub stencil_attachment: *const RenderingAttachmentInfo,
+    }
+
+    //formats are u32 since there is no Format for VK_FORMAT_UNDEFINED
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PipelineRenderingCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub view_mask: u32,
+        pub color_attachment_count: u32,
+        pub color_attachment_formats: *const u32,
+        pub depth_attachment_format: u32,
+        pub stencil_attachment_format: u32,
+    }
+
+    pub type CmdBeginRendering = unsafe extern "system" fn(CommandBuffer, *const RenderingInfo);
+    pub type CmdEndRendering = unsafe extern "system" fn(CommandBuffer);
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceInheritedViewportScissorFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub inherited_viewport_scissor_2d: Bool,
     }
 
     #[derive(Clone, Copy)]
@@ -1594,6 +2513,14 @@ mod ffi {
         pub signal_semaphores: *const Semaphore,
     }
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ProtectedSubmitInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub protected_submit: Bool,
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct PresentInfo {
@@ -1631,453 +2558,1165 @@ mod ffi {
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct MemoryRequirements {
+    pub struct MappedMemoryRange {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub memory: DeviceMemory,
+        pub offset: DeviceSize,
         pub size: DeviceSize,
-        pub alignment: DeviceSize,
-        pub memory_type: u32,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct MemoryType {
-        pub property_flags: Flags,
-        pub heap_index: u32,
+    pub struct MemoryPriorityAllocateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub priority: f32,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct MemoryHeap {
-        pub size: DeviceSize,
-        pub flags: Flags,
+    pub struct PhysicalDeviceMemoryPriorityFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub memory_priority: Bool,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct PhysicalDeviceMemoryProperties {
-        pub memory_type_count: u32,
-        pub memory_types: [MemoryType; 32],
-        pub memory_heap_count: u32,
-        pub memory_heaps: [MemoryHeap; 32],
+    pub struct PhysicalDevicePageableDeviceLocalMemoryFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub pageable_device_local_memory: Bool,
     }
 
-    #[derive(Clone, Copy)]
-    #[repr(C)]
-    pub enum DescriptorType {
-        CombinedImageSampler = 1,
-        StorageImage = 3,
-        UniformBuffer = 6,
-        StorageBuffer = 7,
-    }
+    pub type SetDeviceMemoryPriority = unsafe extern "system" fn(Device, DeviceMemory, f32);
 
-    impl_from_enum!(
-        DescriptorType,
-        CombinedImageSampler,
-        StorageImage,
-        UniformBuffer,
-        StorageBuffer
+    pub type CmdBeginDebugUtilsLabel = unsafe extern "system" fn(CommandBuffer, *const DebugUtilsLabel);
+
+    pub type CmdEndDebugUtilsLabel = unsafe extern "system" fn(CommandBuffer);
+
+    pub type CmdDrawIndexedIndirectCount = unsafe extern "system" fn(
+        CommandBuffer,
+        Buffer,
+        DeviceSize,
+        Buffer,
+        DeviceSize,
+        u32,
+        u32,
     );
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct DescriptorSetLayoutBinding {
-        pub binding: u32,
-        pub descriptor_type: DescriptorType,
-        pub descriptor_count: u32,
-        pub stage: u32,
-        pub immutable_samplers: *const (),
+    pub struct PhysicalDevicePipelineExecutablePropertiesFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub pipeline_executable_info: Bool,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct DescriptorSetLayoutCreateInfo {
+    pub struct PipelineInfo {
         pub structure_type: StructureType,
         pub p_next: *const (),
-        pub flags: Flags,
-        pub binding_count: u32,
-        pub bindings: *const DescriptorSetLayoutBinding,
+        pub pipeline: Pipeline,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct DescriptorBufferInfo {
-        pub buffer: Buffer,
-        pub offset: DeviceSize,
-        pub range: DeviceSize,
+    pub struct PipelineExecutableProperties {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub stages: u32,
+        pub name: [i8; 256],
+        pub description: [i8; 256],
+        pub subgroup_size: u32,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct DescriptorImageInfo {
-        pub sampler: Sampler,
-        pub image_view: ImageView,
-        pub image_layout: ImageLayout,
+    pub struct PipelineExecutableInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub pipeline: Pipeline,
+        pub executable_index: u32,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct WriteDescriptorSet {
+    pub struct PipelineExecutableStatistic {
         pub structure_type: StructureType,
-        pub p_next: *const (),
-        pub dst_set: DescriptorSet,
-        pub dst_binding: u32,
-        pub dst_array_element: u32,
-        pub descriptor_count: u32,
-        pub descriptor_type: DescriptorType,
-        pub image_infos: *const DescriptorImageInfo,
-        pub buffer_infos: *const DescriptorBufferInfo,
-        pub texel_buffer_view: *const (),
+        pub p_next: *mut (),
+        pub name: [i8; 256],
+        pub description: [i8; 256],
+        pub format: u32,
+        //union of bool32, i64, u64 and f64
+        pub value: [u8; 8],
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct CopyDescriptorSet {
+    pub struct PipelineExecutableInternalRepresentation {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub name: [i8; 256],
+        pub description: [i8; 256],
+        pub is_text: Bool,
+        pub data_size: usize,
+        pub data: *mut (),
+    }
+
+    pub type GetPipelineExecutableProperties = unsafe extern "system" fn(
+        Device,
+        *const PipelineInfo,
+        *mut u32,
+        *mut PipelineExecutableProperties,
+    ) -> Result;
+    pub type GetPipelineExecutableStatistics = unsafe extern "system" fn(
+        Device,
+        *const PipelineExecutableInfo,
+        *mut u32,
+        *mut PipelineExecutableStatistic,
+    ) -> Result;
+    pub type GetPipelineExecutableInternalRepresentations = unsafe extern "system" fn(
+        Device,
+        *const PipelineExecutableInfo,
+        *mut u32,
+        *mut PipelineExecutableInternalRepresentation,
+    ) -> Result;
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct EventCreateInfo {
         pub structure_type: StructureType,
         pub p_next: *const (),
-        pub src_set: DescriptorSet,
-        pub src_binding: u32,
-        pub src_array_element: u32,
-        pub dst_set: DescriptorSet,
-        pub dst_binding: u32,
-        pub dst_array_element: u32,
-        pub descriptor_count: u32,
+        pub flags: Flags,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct DescriptorPoolSize {
-        pub descriptor_type: DescriptorType,
-        pub descriptor_count: u32,
+    pub struct PhysicalDeviceSynchronization2Features {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub synchronization2: Bool,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct DescriptorSetAllocateInfo {
+    pub struct MemoryBarrier2 {
         pub structure_type: StructureType,
         pub p_next: *const (),
-        pub descriptor_pool: DescriptorPool,
-        pub descriptor_set_count: u32,
-        pub set_layouts: *const DescriptorSetLayout,
+        pub src_stage_mask: u64,
+        pub src_access_mask: u64,
+        pub dst_stage_mask: u64,
+        pub dst_access_mask: u64,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct DescriptorPoolCreateInfo {
+    pub struct BufferMemoryBarrier2 {
         pub structure_type: StructureType,
         pub p_next: *const (),
-        pub flags: Flags,
-        pub max_sets: u32,
-        pub pool_size_count: u32,
-        pub pool_sizes: *const DescriptorPoolSize,
+        pub src_stage_mask: u64,
+        pub src_access_mask: u64,
+        pub dst_stage_mask: u64,
+        pub dst_access_mask: u64,
+        pub src_queue_family_index: u32,
+        pub dst_queue_family_index: u32,
+        pub buffer: Buffer,
+        pub offset: DeviceSize,
+        pub size: DeviceSize,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub enum ImageType {
-        OneDim = 0,
-        TwoDim = 1,
-        ThreeDim = 2,
-    }
-
-    impl From<super::ImageType> for ImageType {
-        fn from(image_type: super::ImageType) -> Self {
-            match image_type {
-                super::ImageType::OneDim => Self::OneDim,
-                super::ImageType::TwoDim => Self::TwoDim,
-                super::ImageType::ThreeDim => Self::ThreeDim,
-            }
-        }
+    pub struct ImageMemoryBarrier2 {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub src_stage_mask: u64,
+        pub src_access_mask: u64,
+        pub dst_stage_mask: u64,
+        pub dst_access_mask: u64,
+        pub old_layout: ImageLayout,
+        pub new_layout: ImageLayout,
+        pub src_queue_family_index: u32,
+        pub dst_queue_family_index: u32,
+        pub image: Image,
+        pub subresource_range: ImageSubresourceRange,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub enum ImageTiling {
-        Optimal = 0,
-        Linear = 1,
+    pub struct DependencyInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub dependency_flags: Flags,
+        pub memory_barrier_count: u32,
+        pub memory_barriers: *const MemoryBarrier2,
+        pub buffer_memory_barrier_count: u32,
+        pub buffer_memory_barriers: *const BufferMemoryBarrier2,
+        pub image_memory_barrier_count: u32,
+        pub image_memory_barriers: *const ImageMemoryBarrier2,
     }
 
-    impl From<super::ImageTiling> for ImageTiling {
-        fn from(image_tiling: super::ImageTiling) -> Self {
-            match image_tiling {
-                super::ImageTiling::Optimal => Self::Optimal,
-                super::ImageTiling::Linear => Self::Linear,
-            }
-        }
-    }
+    pub type CmdSetEvent2 =
+        unsafe extern "system" fn(CommandBuffer, Event, *const DependencyInfo);
+    pub type CmdWaitEvents2 =
+        unsafe extern "system" fn(CommandBuffer, u32, *const Event, *const DependencyInfo);
+    pub type CmdPipelineBarrier2 = unsafe extern "system" fn(CommandBuffer, *const DependencyInfo);
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct ImageCreateInfo {
+    pub struct SemaphoreSubmitInfo {
         pub structure_type: StructureType,
         pub p_next: *const (),
-        pub flags: Flags,
-        pub image_type: ImageType,
-        pub format: Format,
-        pub extent: Extent3d,
-        pub mip_levels: u32,
-        pub array_layers: u32,
-        pub samples: Flags,
-        pub tiling: ImageTiling,
-        pub image_usage: u32,
-        pub sharing_mode: SharingMode,
-        pub queue_family_index_count: u32,
-        pub queue_family_indices: *const u32,
-        pub initial_layout: ImageLayout,
+        pub semaphore: Semaphore,
+        pub value: u64,
+        pub stage_mask: u64,
+        pub device_index: u32,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct BufferCopy {
-        pub src_offset: DeviceSize,
-        pub dst_offset: DeviceSize,
-        pub size: DeviceSize,
+    pub struct CommandBufferSubmitInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub command_buffer: CommandBuffer,
+        pub device_mask: u32,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct ImageSubresourceLayers {
-        pub aspect_mask: Flags,
-        pub mip_level: u32,
-        pub base_array_layer: u32,
-        pub layer_count: u32,
+    pub struct SubmitInfo2 {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: Flags,
+        pub wait_semaphore_info_count: u32,
+        pub wait_semaphore_infos: *const SemaphoreSubmitInfo,
+        pub command_buffer_info_count: u32,
+        pub command_buffer_infos: *const CommandBufferSubmitInfo,
+        pub signal_semaphore_info_count: u32,
+        pub signal_semaphore_infos: *const SemaphoreSubmitInfo,
     }
 
+    pub type QueueSubmit2 = unsafe extern "system" fn(Queue, u32, *const SubmitInfo2, Fence) -> Result;
+
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct BufferImageCopy {
-        pub buffer_offset: DeviceSize,
-        pub buffer_row_length: u32,
-        pub buffer_image_height: u32,
-        pub image_subresource: ImageSubresourceLayers,
-        pub image_offset: Offset3d,
-        pub image_extent: Extent3d,
+    pub struct PhysicalDeviceSwapchainMaintenance1Features {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub swapchain_maintenance1: Bool,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub enum Filter {
-        Nearest = 0,
-        Linear = 1,
-    }
-
-    impl From<super::Filter> for Filter {
-        fn from(filter: super::Filter) -> Self {
-            match filter {
-                super::Filter::Nearest => Self::Nearest,
-                super::Filter::Linear => Self::Linear,
-            }
-        }
+    pub struct SwapchainPresentFenceInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub swapchain_count: u32,
+        pub fences: *const Fence,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub enum SamplerMipmapMode {
-        Nearest = 0,
-        Linear = 1,
-    }
-
-    impl From<super::SamplerMipmapMode> for SamplerMipmapMode {
-        fn from(mipmap_mode: super::SamplerMipmapMode) -> Self {
-            match mipmap_mode {
-                super::SamplerMipmapMode::Nearest => Self::Nearest,
-                super::SamplerMipmapMode::Linear => Self::Linear,
-            }
-        }
+    pub struct PhysicalDeviceSurfaceInfo2 {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub surface: Surface,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub enum SamplerAddressMode {
-        Repeat = 0,
-        MirroredRepeat = 1,
-        ClampToEdge = 2,
-        ClampToBorder = 3,
+    pub struct SurfacePresentMode {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub present_mode: PresentMode,
     }
 
-    impl From<super::SamplerAddressMode> for SamplerAddressMode {
-        fn from(address_mode: super::SamplerAddressMode) -> Self {
-            match address_mode {
-                super::SamplerAddressMode::Repeat => Self::Repeat,
-                super::SamplerAddressMode::MirroredRepeat => Self::MirroredRepeat,
-                super::SamplerAddressMode::ClampToEdge => Self::ClampToEdge,
-                super::SamplerAddressMode::ClampToBorder => Self::ClampToBorder,
-            }
-        }
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct SurfaceCapabilities2 {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub surface_capabilities: SurfaceCapabilities,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub enum BorderColor {
-        FloatTransparentBlack = 0,
-        IntTransparentBlack = 1,
-        FloatOpaqueBlack = 2,
-        IntOpaqueBlack = 3,
-        FloatOpaqueWhite = 4,
-        IntOpaqueWhite = 5,
+    pub struct SurfacePresentScalingCapabilities {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub supported_present_scaling: Flags,
+        pub supported_present_gravity_x: Flags,
+        pub supported_present_gravity_y: Flags,
+        pub min_scaled_image_extent: Extent2d,
+        pub max_scaled_image_extent: Extent2d,
     }
 
-    impl From<super::BorderColor> for BorderColor {
-        fn from(border_color: super::BorderColor) -> Self {
-            match border_color {
-                super::BorderColor::FloatTransparentBlack => Self::FloatTransparentBlack,
-                super::BorderColor::IntTransparentBlack => Self::IntTransparentBlack,
-                super::BorderColor::FloatOpaqueBlack => Self::FloatOpaqueBlack,
-                super::BorderColor::IntOpaqueBlack => Self::IntOpaqueBlack,
-                super::BorderColor::FloatOpaqueWhite => Self::FloatOpaqueWhite,
-                super::BorderColor::IntOpaqueWhite => Self::IntOpaqueWhite,
-            }
-        }
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceBufferDeviceAddressFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub buffer_device_address: Bool,
+        pub buffer_device_address_capture_replay: Bool,
+        pub buffer_device_address_multi_device: Bool,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct SamplerCreateInfo {
+    pub struct MemoryAllocateFlagsInfo {
         pub structure_type: StructureType,
         pub p_next: *const (),
         pub flags: Flags,
-        pub mag_filter: Filter,
-        pub min_filter: Filter,
-        pub mipmap_mode: SamplerMipmapMode,
-        pub address_mode_u: SamplerAddressMode,
-        pub address_mode_v: SamplerAddressMode,
-        pub address_mode_w: SamplerAddressMode,
-        pub mip_lod_bias: f32,
-        pub anisotropy_enable: Bool,
-        pub max_anisotropy: f32,
-        pub compare_enable: Bool,
-        pub compare_op: CompareOp,
-        pub min_lod: f32,
-        pub max_lod: f32,
-        pub border_color: BorderColor,
-        pub unnormalized_coordinates: Bool,
+        pub device_mask: u32,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct ImageMemoryBarrier {
+    pub struct BufferDeviceAddressInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub buffer: Buffer,
+    }
+
+    pub type GetBufferDeviceAddress =
+        unsafe extern "system" fn(Device, *const BufferDeviceAddressInfo) -> DeviceAddress;
+
+    pub type GetPhysicalDeviceFeatures2 =
+        unsafe extern "system" fn(PhysicalDevice, *mut PhysicalDeviceFeatures2);
+
+    pub type GetPhysicalDeviceSurfaceCapabilities2 = unsafe extern "system" fn(
+        PhysicalDevice,
+        *const PhysicalDeviceSurfaceInfo2,
+        *mut SurfaceCapabilities2,
+    ) -> Result;
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ImportMemoryFdInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub handle_type: u32,
+        pub fd: i32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct MemoryDedicatedAllocateInfo {
         pub structure_type: StructureType,
         pub p_next: *const (),
-        pub src_access_mask: u32,
-        pub dst_access_mask: u32,
-        pub old_layout: ImageLayout,
-        pub new_layout: ImageLayout,
-        pub src_queue_family_index: u32,
-        pub dst_queue_family_index: u32,
         pub image: Image,
-        pub subresource_range: ImageSubresourceRange,
+        pub buffer: Buffer,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct MemoryBarrier {
+    pub struct MemoryFdProperties {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub memory_type_bits: u32,
+    }
+
+    pub type GetMemoryFdProperties =
+        unsafe extern "system" fn(Device, u32, i32, *mut MemoryFdProperties) -> Result;
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum QueryType {
+        Occlusion = 0,
+        Timestamp = 2,
+        PerformanceQuery = 1000116000,
+    }
+
+    impl_from_enum!(QueryType, Occlusion, Timestamp, PerformanceQuery);
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct QueryPoolCreateInfo {
         pub structure_type: StructureType,
         pub p_next: *const (),
-        pub src_access_mask: u32,
-        pub dst_access_mask: u32,
+        pub flags: u32,
+        pub query_type: QueryType,
+        pub query_count: u32,
+        pub pipeline_statistics: u32,
     }
 
     #[derive(Clone, Copy)]
     #[repr(C)]
-    pub struct BufferMemoryBarrier {
+    pub struct PhysicalDevicePerformanceQueryFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub performance_counter_query_pools: Bool,
+        pub performance_counter_multiple_query_pools: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct QueryPoolPerformanceCreateInfo {
         pub structure_type: StructureType,
         pub p_next: *const (),
-        pub src_access_mask: u32,
-        pub dst_access_mask: u32,
-        pub src_queue_family_index: u32,
-        pub dst_queue_family_index: u32,
+        pub queue_family_index: u32,
+        pub counter_index_count: u32,
+        pub counter_indices: *const u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct AcquireProfilingLockInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: u32,
+        pub timeout: u64,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PerformanceCounter {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub unit: u32,
+        pub scope: u32,
+        pub storage: u32,
+        pub uuid: [u8; 16],
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PerformanceCounterDescription {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub flags: u32,
+        pub name: [i8; 256],
+        pub category: [i8; 256],
+        pub description: [i8; 256],
+    }
+
+    pub type EnumeratePhysicalDeviceQueueFamilyPerformanceQueryCounters = unsafe extern "system" fn(
+        PhysicalDevice,
+        u32,
+        *mut u32,
+        *mut PerformanceCounter,
+        *mut PerformanceCounterDescription,
+    ) -> Result;
+    pub type GetPhysicalDeviceQueueFamilyPerformanceQueryPasses =
+        unsafe extern "system" fn(PhysicalDevice, *const QueryPoolPerformanceCreateInfo, *mut u32);
+    pub type AcquireProfilingLock =
+        unsafe extern "system" fn(Device, *const AcquireProfilingLockInfo) -> Result;
+    pub type ReleaseProfilingLock = unsafe extern "system" fn(Device);
+    pub type EnumerateInstanceVersion = unsafe extern "system" fn(*mut u32) -> Result;
+
+    pub type SetHdrMetadata =
+        unsafe extern "system" fn(Device, u32, *const Swapchain, *const HdrMetadata);
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct XyColor {
+        pub x: f32,
+        pub y: f32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct HdrMetadata {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub display_primary_red: XyColor,
+        pub display_primary_green: XyColor,
+        pub display_primary_blue: XyColor,
+        pub white_point: XyColor,
+        pub max_luminance: f32,
+        pub min_luminance: f32,
+        pub max_content_light_level: f32,
+        pub max_frame_average_light_level: f32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct MemoryRequirements {
+        pub size: DeviceSize,
+        pub alignment: DeviceSize,
+        pub memory_type: u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct MemoryType {
+        pub property_flags: Flags,
+        pub heap_index: u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct MemoryHeap {
+        pub size: DeviceSize,
+        pub flags: Flags,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceMemoryProperties {
+        pub memory_type_count: u32,
+        pub memory_types: [MemoryType; 32],
+        pub memory_heap_count: u32,
+        pub memory_heaps: [MemoryHeap; 32],
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum DescriptorType {
+        CombinedImageSampler = 1,
+        StorageImage = 3,
+        UniformBuffer = 6,
+        StorageBuffer = 7,
+        UniformBufferDynamic = 8,
+    }
+
+    impl_from_enum!(
+        DescriptorType,
+        CombinedImageSampler,
+        StorageImage,
+        UniformBuffer,
+        StorageBuffer,
+        UniformBufferDynamic
+    );
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DescriptorSetLayoutBinding {
+        pub binding: u32,
+        pub descriptor_type: DescriptorType,
+        pub descriptor_count: u32,
+        pub stage: u32,
+        pub immutable_samplers: *const Sampler,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DescriptorSetLayoutCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: Flags,
+        pub binding_count: u32,
+        pub bindings: *const DescriptorSetLayoutBinding,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DescriptorSetLayoutBindingFlagsCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub binding_count: u32,
+        pub binding_flags: *const Flags,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DescriptorSetVariableDescriptorCountAllocateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub descriptor_set_count: u32,
+        pub descriptor_counts: *const u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DescriptorBufferInfo {
         pub buffer: Buffer,
-        pub offset: u64,
-        pub size: u64,
+        pub offset: DeviceSize,
+        pub range: DeviceSize,
     }
 
-    #[link(name = "vulkan-1")]
-    #[allow(non_snake_case)]
-    extern "C" {
-        //TODO implement VkAllocationCallbacks
-        pub fn vkCreateInstance(
-            create_info: *const InstanceCreateInfo,
-            allocator: *const (),
-            instance: *mut Instance,
-        ) -> Result;
-        pub fn vkDestroyInstance(instance: Instance, allocator: *const ());
-        pub fn vkGetInstanceProcAddr(instance: Instance, name: *const i8) -> *const ();
-        pub fn vkEnumeratePhysicalDevices(
-            instance: Instance,
-            physical_device_count: *mut u32,
-            physical_devices: *mut PhysicalDevice,
-        ) -> Result;
-        pub fn vkGetPhysicalDeviceProperties(
-            physical_device: PhysicalDevice,
-            properties: *mut PhysicalDeviceProperties,
-        );
-        pub fn vkGetPhysicalDeviceQueueFamilyProperties(
-            physical_device: PhysicalDevice,
-            queue_family_property_count: *mut u32,
-            queue_family_properties: *mut QueueFamilyProperties,
-        );
-        pub fn vkGetPhysicalDeviceSurfaceCapabilitiesKHR(
-            physical_device: PhysicalDevice,
-            surface: Surface,
-            surface_capabilities: *mut SurfaceCapabilities,
-        );
-        pub fn vkGetPhysicalDeviceSurfaceSupportKHR(
-            physical_device: PhysicalDevice,
-            queue_family_index: u32,
-            surface: Surface,
-            supported: *mut Bool,
-        ) -> Result;
-        pub fn vkGetPhysicalDeviceMemoryProperties(
-            physical_device: PhysicalDevice,
-            memory_properties: *mut PhysicalDeviceMemoryProperties,
-        );
-        pub fn vkCreateDevice(
-            physical_device: PhysicalDevice,
-            create_info: *const DeviceCreateInfo,
-            allocator: *const (),
-            device: *mut Device,
-        ) -> Result;
-        pub fn vkDestroyDevice(device: Device, allocator: *const ());
-        pub fn vkDeviceWaitIdle(device: Device) -> Result;
-        pub fn vkQueueWaitIdle(queue: Queue) -> Result;
-        pub fn vkGetDeviceQueue(
-            device: Device,
-            queue_family_index: u32,
-            queue_index: u32,
-            queue: *mut Queue,
-        );
-        pub fn vkGetBufferMemoryRequirements(
-            device: Device,
-            buffer: Buffer,
-            memory_requirements: *mut MemoryRequirements,
-        );
-        #[cfg(target_os = "linux")]
-        pub fn vkCreateXlibSurfaceKHR(
-            instance: Instance,
-            create_info: *const XlibSurfaceCreateInfo,
-            allocator: *const (),
-            surface: *mut Surface,
-        );
-        #[cfg(target_os = "windows")]
-        pub fn vkCreateWin32SurfaceKHR(
-            instance: Instance,
-            create_info: *const Win32SurfaceCreateInfo,
-            allocator: *const (),
-            surface: *mut Surface,
-        );
-        pub fn vkDestroySurfaceKHR(instance: Instance, surface: Surface, allocator: *const ());
-        pub fn vkCreateSwapchainKHR(
-            device: Device,
-            create_info: *const SwapchainCreateInfo,
-            allocator: *const (),
-            swapchain: *mut Swapchain,
-        ) -> Result;
-        pub fn vkDestroySwapchainKHR(
-            device: Device,
-            swapchain: Swapchain,
-            allocator: *const (),
-        );
-        pub fn vkGetSwapchainImagesKHR(
-            device: Device,
-            swapchain: Swapchain,
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DescriptorImageInfo {
+        pub sampler: Sampler,
+        pub image_view: ImageView,
+        pub image_layout: ImageLayout,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct WriteDescriptorSet {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub dst_set: DescriptorSet,
+        pub dst_binding: u32,
+        pub dst_array_element: u32,
+        pub descriptor_count: u32,
+        pub descriptor_type: DescriptorType,
+        pub image_infos: *const DescriptorImageInfo,
+        pub buffer_infos: *const DescriptorBufferInfo,
+        pub texel_buffer_view: *const (),
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum DescriptorUpdateTemplateType {
+        DescriptorSet = 0,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DescriptorUpdateTemplateEntry {
+        pub dst_binding: u32,
+        pub dst_array_element: u32,
+        pub descriptor_count: u32,
+        pub descriptor_type: DescriptorType,
+        pub offset: usize,
+        pub stride: usize,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DescriptorUpdateTemplateCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: Flags,
+        pub descriptor_update_entry_count: u32,
+        pub descriptor_update_entries: *const DescriptorUpdateTemplateEntry,
+        pub template_type: DescriptorUpdateTemplateType,
+        pub descriptor_set_layout: DescriptorSetLayout,
+        pub pipeline_bind_point: PipelineBindPoint,
+        pub pipeline_layout: PipelineLayout,
+        pub set: u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct CopyDescriptorSet {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub src_set: DescriptorSet,
+        pub src_binding: u32,
+        pub src_array_element: u32,
+        pub dst_set: DescriptorSet,
+        pub dst_binding: u32,
+        pub dst_array_element: u32,
+        pub descriptor_count: u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DescriptorPoolSize {
+        pub descriptor_type: DescriptorType,
+        pub descriptor_count: u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DescriptorSetAllocateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub descriptor_pool: DescriptorPool,
+        pub descriptor_set_count: u32,
+        pub set_layouts: *const DescriptorSetLayout,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DescriptorPoolCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: Flags,
+        pub max_sets: u32,
+        pub pool_size_count: u32,
+        pub pool_sizes: *const DescriptorPoolSize,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum ImageType {
+        OneDim = 0,
+        TwoDim = 1,
+        ThreeDim = 2,
+    }
+
+    impl From<super::ImageType> for ImageType {
+        fn from(image_type: super::ImageType) -> Self {
+            match image_type {
+                super::ImageType::OneDim => Self::OneDim,
+                super::ImageType::TwoDim => Self::TwoDim,
+                super::ImageType::ThreeDim => Self::ThreeDim,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum ImageTiling {
+        Optimal = 0,
+        Linear = 1,
+        DrmFormatModifier = 1000158000,
+    }
+
+    impl From<super::ImageTiling> for ImageTiling {
+        fn from(image_tiling: super::ImageTiling) -> Self {
+            match image_tiling {
+                super::ImageTiling::Optimal => Self::Optimal,
+                super::ImageTiling::Linear => Self::Linear,
+                super::ImageTiling::DrmFormatModifier => Self::DrmFormatModifier,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ImageCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: Flags,
+        pub image_type: ImageType,
+        pub format: Format,
+        pub extent: Extent3d,
+        pub mip_levels: u32,
+        pub array_layers: u32,
+        pub samples: Flags,
+        pub tiling: ImageTiling,
+        pub image_usage: u32,
+        pub sharing_mode: SharingMode,
+        pub queue_family_index_count: u32,
+        pub queue_family_indices: *const u32,
+        pub initial_layout: ImageLayout,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ExternalMemoryImageCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub handle_types: u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct SubresourceLayout {
+        pub offset: DeviceSize,
+        pub size: DeviceSize,
+        pub row_pitch: DeviceSize,
+        pub array_pitch: DeviceSize,
+        pub depth_pitch: DeviceSize,
+    }
+
+    impl_from_struct!(
+        SubresourceLayout,
+        offset,
+        size,
+        row_pitch,
+        array_pitch,
+        depth_pitch
+    );
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ImageDrmFormatModifierExplicitCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub drm_format_modifier: u64,
+        pub drm_format_modifier_plane_count: u32,
+        pub plane_layouts: *const SubresourceLayout,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct BufferCopy {
+        pub src_offset: DeviceSize,
+        pub dst_offset: DeviceSize,
+        pub size: DeviceSize,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ImageSubresourceLayers {
+        pub aspect_mask: Flags,
+        pub mip_level: u32,
+        pub base_array_layer: u32,
+        pub layer_count: u32,
+    }
+
+    impl From<super::ImageSubresourceLayers> for ImageSubresourceLayers {
+        fn from(layers: super::ImageSubresourceLayers) -> Self {
+            Self {
+                aspect_mask: layers.aspect_mask as _,
+                mip_level: layers.mip_level,
+                base_array_layer: layers.base_array_layer,
+                layer_count: layers.layer_count,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct BufferImageCopy {
+        pub buffer_offset: DeviceSize,
+        pub buffer_row_length: u32,
+        pub buffer_image_height: u32,
+        pub image_subresource: ImageSubresourceLayers,
+        pub image_offset: Offset3d,
+        pub image_extent: Extent3d,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ImageCopy {
+        pub src_subresource: ImageSubresourceLayers,
+        pub src_offset: Offset3d,
+        pub dst_subresource: ImageSubresourceLayers,
+        pub dst_offset: Offset3d,
+        pub extent: Extent3d,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ImageBlit {
+        pub src_subresource: ImageSubresourceLayers,
+        pub src_offsets: [Offset3d; 2],
+        pub dst_subresource: ImageSubresourceLayers,
+        pub dst_offsets: [Offset3d; 2],
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ImageResolve {
+        pub src_subresource: ImageSubresourceLayers,
+        pub src_offset: Offset3d,
+        pub dst_subresource: ImageSubresourceLayers,
+        pub dst_offset: Offset3d,
+        pub extent: Extent3d,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum Filter {
+        Nearest = 0,
+        Linear = 1,
+    }
+
+    impl From<super::Filter> for Filter {
+        fn from(filter: super::Filter) -> Self {
+            match filter {
+                super::Filter::Nearest => Self::Nearest,
+                super::Filter::Linear => Self::Linear,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum SamplerMipmapMode {
+        Nearest = 0,
+        Linear = 1,
+    }
+
+    impl From<super::SamplerMipmapMode> for SamplerMipmapMode {
+        fn from(mipmap_mode: super::SamplerMipmapMode) -> Self {
+            match mipmap_mode {
+                super::SamplerMipmapMode::Nearest => Self::Nearest,
+                super::SamplerMipmapMode::Linear => Self::Linear,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum SamplerAddressMode {
+        Repeat = 0,
+        MirroredRepeat = 1,
+        ClampToEdge = 2,
+        ClampToBorder = 3,
+    }
+
+    impl From<super::SamplerAddressMode> for SamplerAddressMode {
+        fn from(address_mode: super::SamplerAddressMode) -> Self {
+            match address_mode {
+                super::SamplerAddressMode::Repeat => Self::Repeat,
+                super::SamplerAddressMode::MirroredRepeat => Self::MirroredRepeat,
+                super::SamplerAddressMode::ClampToEdge => Self::ClampToEdge,
+                super::SamplerAddressMode::ClampToBorder => Self::ClampToBorder,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum BorderColor {
+        FloatTransparentBlack = 0,
+        IntTransparentBlack = 1,
+        FloatOpaqueBlack = 2,
+        IntOpaqueBlack = 3,
+        FloatOpaqueWhite = 4,
+        IntOpaqueWhite = 5,
+    }
+
+    impl From<super::BorderColor> for BorderColor {
+        fn from(border_color: super::BorderColor) -> Self {
+            match border_color {
+                super::BorderColor::FloatTransparentBlack => Self::FloatTransparentBlack,
+                super::BorderColor::IntTransparentBlack => Self::IntTransparentBlack,
+                super::BorderColor::FloatOpaqueBlack => Self::FloatOpaqueBlack,
+                super::BorderColor::IntOpaqueBlack => Self::IntOpaqueBlack,
+                super::BorderColor::FloatOpaqueWhite => Self::FloatOpaqueWhite,
+                super::BorderColor::IntOpaqueWhite => Self::IntOpaqueWhite,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct SamplerYcbcrConversionCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub format: Format,
+        pub ycbcr_model: SamplerYcbcrModelConversion,
+        pub ycbcr_range: SamplerYcbcrRange,
+        pub components: ComponentMapping,
+        pub x_chroma_offset: ChromaLocation,
+        pub y_chroma_offset: ChromaLocation,
+        pub chroma_filter: Filter,
+        pub force_explicit_reconstruction: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct SamplerYcbcrConversionInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub conversion: SamplerYcbcrConversion,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceSamplerYcbcrConversionFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub sampler_ycbcr_conversion: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct SamplerCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: Flags,
+        pub mag_filter: Filter,
+        pub min_filter: Filter,
+        pub mipmap_mode: SamplerMipmapMode,
+        pub address_mode_u: SamplerAddressMode,
+        pub address_mode_v: SamplerAddressMode,
+        pub address_mode_w: SamplerAddressMode,
+        pub mip_lod_bias: f32,
+        pub anisotropy_enable: Bool,
+        pub max_anisotropy: f32,
+        pub compare_enable: Bool,
+        pub compare_op: CompareOp,
+        pub min_lod: f32,
+        pub max_lod: f32,
+        pub border_color: BorderColor,
+        pub unnormalized_coordinates: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ImageMemoryBarrier {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub src_access_mask: u32,
+        pub dst_access_mask: u32,
+        pub old_layout: ImageLayout,
+        pub new_layout: ImageLayout,
+        pub src_queue_family_index: u32,
+        pub dst_queue_family_index: u32,
+        pub image: Image,
+        pub subresource_range: ImageSubresourceRange,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct MemoryBarrier {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub src_access_mask: u32,
+        pub dst_access_mask: u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct BufferMemoryBarrier {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub src_access_mask: u32,
+        pub dst_access_mask: u32,
+        pub src_queue_family_index: u32,
+        pub dst_queue_family_index: u32,
+        pub buffer: Buffer,
+        pub offset: u64,
+        pub size: u64,
+    }
+
+    //MoltenVK through the loader of the Vulkan SDK on apple platforms
+    #[cfg_attr(any(target_os = "macos", target_os = "ios"), link(name = "vulkan"))]
+    #[cfg_attr(not(any(target_os = "macos", target_os = "ios")), link(name = "vulkan-1"))]
+    #[allow(non_snake_case)]
+    extern "C" {
+        //TODO implement VkAllocationCallbacks
+        pub fn vkCreateInstance(
+            create_info: *const InstanceCreateInfo,
+            allocator: *const (),
+            instance: *mut Instance,
+        ) -> Result;
+        pub fn vkDestroyInstance(instance: Instance, allocator: *const ());
+        pub fn vkGetInstanceProcAddr(instance: Instance, name: *const i8) -> *const ();
+        pub fn vkEnumeratePhysicalDevices(
+            instance: Instance,
+            physical_device_count: *mut u32,
+            physical_devices: *mut PhysicalDevice,
+        ) -> Result;
+        pub fn vkGetPhysicalDeviceProperties(
+            physical_device: PhysicalDevice,
+            properties: *mut PhysicalDeviceProperties,
+        );
+        pub fn vkGetPhysicalDeviceFormatProperties(
+            physical_device: PhysicalDevice,
+            format: Format,
+            format_properties: *mut FormatProperties,
+        );
+        pub fn vkGetPhysicalDeviceFormatProperties2(
+            physical_device: PhysicalDevice,
+            format: Format,
+            format_properties: *mut FormatProperties2,
+        );
+        pub fn vkEnumerateInstanceExtensionProperties(
+            layer_name: *const i8,
+            property_count: *mut u32,
+            properties: *mut ExtensionProperties,
+        ) -> Result;
+        pub fn vkEnumerateDeviceExtensionProperties(
+            physical_device: PhysicalDevice,
+            layer_name: *const i8,
+            property_count: *mut u32,
+            properties: *mut ExtensionProperties,
+        ) -> Result;
+        pub fn vkGetPhysicalDeviceFeatures(
+            physical_device: PhysicalDevice,
+            features: *mut PhysicalDeviceFeatures,
+        );
+        pub fn vkGetPhysicalDeviceFeatures2(
+            physical_device: PhysicalDevice,
+            features: *mut PhysicalDeviceFeatures2,
+        );
+        pub fn vkGetPhysicalDeviceProperties2(
+            physical_device: PhysicalDevice,
+            properties: *mut PhysicalDeviceProperties2,
+        );
+        pub fn vkGetPhysicalDeviceQueueFamilyProperties(
+            physical_device: PhysicalDevice,
+            queue_family_property_count: *mut u32,
+            queue_family_properties: *mut QueueFamilyProperties,
+        );
+        pub fn vkGetPhysicalDeviceSurfaceCapabilitiesKHR(
+            physical_device: PhysicalDevice,
+            surface: Surface,
+            surface_capabilities: *mut SurfaceCapabilities,
+        );
+        pub fn vkGetPhysicalDeviceSurfaceSupportKHR(
+            physical_device: PhysicalDevice,
+            queue_family_index: u32,
+            surface: Surface,
+            supported: *mut Bool,
+        ) -> Result;
+        pub fn vkGetPhysicalDeviceSurfaceFormatsKHR(
+            physical_device: PhysicalDevice,
+            surface: Surface,
+            surface_format_count: *mut u32,
+            surface_formats: *mut SurfaceFormat,
+        ) -> Result;
+        pub fn vkGetPhysicalDeviceSurfacePresentModesKHR(
+            physical_device: PhysicalDevice,
+            surface: Surface,
+            present_mode_count: *mut u32,
+            present_modes: *mut u32,
+        ) -> Result;
+        pub fn vkGetPhysicalDeviceMemoryProperties(
+            physical_device: PhysicalDevice,
+            memory_properties: *mut PhysicalDeviceMemoryProperties,
+        );
+        pub fn vkCreateDevice(
+            physical_device: PhysicalDevice,
+            create_info: *const DeviceCreateInfo,
+            allocator: *const (),
+            device: *mut Device,
+        ) -> Result;
+        pub fn vkDestroyDevice(device: Device, allocator: *const ());
+        pub fn vkGetDeviceProcAddr(device: Device, name: *const i8) -> *const ();
+        pub fn vkDeviceWaitIdle(device: Device) -> Result;
+        pub fn vkQueueWaitIdle(queue: Queue) -> Result;
+        pub fn vkGetDeviceQueue(
+            device: Device,
+            queue_family_index: u32,
+            queue_index: u32,
+            queue: *mut Queue,
+        );
+        pub fn vkGetDeviceQueue2(
+            device: Device,
+            queue_info: *const DeviceQueueInfo2,
+            queue: *mut Queue,
+        );
+        pub fn vkGetBufferMemoryRequirements(
+            device: Device,
+            buffer: Buffer,
+            memory_requirements: *mut MemoryRequirements,
+        );
+        #[cfg(target_os = "linux")]
+        pub fn vkCreateXlibSurfaceKHR(
+            instance: Instance,
+            create_info: *const XlibSurfaceCreateInfo,
+            allocator: *const (),
+            surface: *mut Surface,
+        ) -> Result;
+        #[cfg(target_os = "linux")]
+        pub fn vkCreateXcbSurfaceKHR(
+            instance: Instance,
+            create_info: *const XcbSurfaceCreateInfo,
+            allocator: *const (),
+            surface: *mut Surface,
+        ) -> Result;
+        #[cfg(target_os = "linux")]
+        pub fn vkCreateWaylandSurfaceKHR(
+            instance: Instance,
+            create_info: *const WaylandSurfaceCreateInfo,
+            allocator: *const (),
+            surface: *mut Surface,
+        ) -> Result;
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        pub fn vkCreateMetalSurfaceEXT(
+            instance: Instance,
+            create_info: *const MetalSurfaceCreateInfo,
+            allocator: *const (),
+            surface: *mut Surface,
+        ) -> Result;
+        #[cfg(target_os = "windows")]
+        pub fn vkCreateWin32SurfaceKHR(
+            instance: Instance,
+            create_info: *const Win32SurfaceCreateInfo,
+            allocator: *const (),
+            surface: *mut Surface,
+        );
+        pub fn vkDestroySurfaceKHR(instance: Instance, surface: Surface, allocator: *const ());
+        pub fn vkCreateSwapchainKHR(
+            device: Device,
+            create_info: *const SwapchainCreateInfo,
+            allocator: *const (),
+            swapchain: *mut Swapchain,
+        ) -> Result;
+        pub fn vkDestroySwapchainKHR(
+            device: Device,
+            swapchain: Swapchain,
+            allocator: *const (),
+        );
+        pub fn vkGetSwapchainImagesKHR(
+            device: Device,
+            swapchain: Swapchain,
             swapchain_image_count: *mut u32,
             swapchain_images: *mut Image,
         );
@@ -2138,6 +3777,29 @@ mod ffi {
             pipelines: *mut Pipeline,
         ) -> Result;
         pub fn vkDestroyPipeline(device: Device, pipeline: Pipeline, allocator: *const ());
+        pub fn vkCreatePipelineCache(
+            device: Device,
+            create_info: *const PipelineCacheCreateInfo,
+            allocator: *const (),
+            pipeline_cache: *mut PipelineCache,
+        ) -> Result;
+        pub fn vkDestroyPipelineCache(
+            device: Device,
+            pipeline_cache: PipelineCache,
+            allocator: *const (),
+        );
+        pub fn vkGetPipelineCacheData(
+            device: Device,
+            pipeline_cache: PipelineCache,
+            data_size: *mut usize,
+            data: *mut (),
+        ) -> Result;
+        pub fn vkMergePipelineCaches(
+            device: Device,
+            dst_cache: PipelineCache,
+            src_cache_count: u32,
+            src_caches: *const PipelineCache,
+        ) -> Result;
         pub fn vkCreateFramebuffer(
             device: Device,
             create_info: *const FramebufferCreateInfo,
@@ -2174,6 +3836,11 @@ mod ffi {
             memory: *mut DeviceMemory,
         ) -> Result;
         pub fn vkFreeMemory(device: Device, memory: DeviceMemory, allocator: *const ());
+        pub fn vkGetDeviceMemoryCommitment(
+            device: Device,
+            memory: DeviceMemory,
+            committed_memory_in_bytes: *mut DeviceSize,
+        );
         pub fn vkBindBufferMemory(
             device: Device,
             buffer: Buffer,
@@ -2197,11 +3864,23 @@ mod ffi {
         );
         pub fn vkCmdEndRenderPass(command_buffer: CommandBuffer);
         pub fn vkCmdNextSubpass(command_buffer: CommandBuffer, contents: SubpassContents);
+        pub fn vkCmdExecuteCommands(
+            command_buffer: CommandBuffer,
+            command_buffer_count: u32,
+            command_buffers: *const CommandBuffer,
+        );
         pub fn vkCmdBindPipeline(
             command_buffer: CommandBuffer,
             bind_point: PipelineBindPoint,
             pipeline: Pipeline,
         );
+        pub fn vkCmdSetBlendConstants(command_buffer: CommandBuffer, blend_constants: *const f32);
+        pub fn vkCmdSetScissor(
+            command_buffer: CommandBuffer,
+            first_scissor: u32,
+            scissor_count: u32,
+            scissors: *const Rect2d,
+        );
         pub fn vkCmdDraw(
             command_buffer: CommandBuffer,
             vertex_count: u32,
@@ -2217,6 +3896,20 @@ mod ffi {
             vertex_offset: i32,
             first_instance: u32,
         );
+        pub fn vkCmdDrawIndexedIndirect(
+            command_buffer: CommandBuffer,
+            buffer: Buffer,
+            offset: DeviceSize,
+            draw_count: u32,
+            stride: u32,
+        );
+        pub fn vkCmdFillBuffer(
+            command_buffer: CommandBuffer,
+            dst_buffer: Buffer,
+            dst_offset: DeviceSize,
+            size: DeviceSize,
+            data: u32,
+        );
         pub fn vkCmdDispatch(
             command_buffer: CommandBuffer,
             group_count_x: u32,
@@ -2251,6 +3944,42 @@ mod ffi {
             region_count: u32,
             regions: *const BufferImageCopy,
         );
+        pub fn vkCmdCopyImageToBuffer(
+            command_buffer: CommandBuffer,
+            src_image: Image,
+            src_image_layout: ImageLayout,
+            dst_buffer: Buffer,
+            region_count: u32,
+            regions: *const BufferImageCopy,
+        );
+        pub fn vkCmdCopyImage(
+            command_buffer: CommandBuffer,
+            src_image: Image,
+            src_image_layout: ImageLayout,
+            dst_image: Image,
+            dst_image_layout: ImageLayout,
+            region_count: u32,
+            regions: *const ImageCopy,
+        );
+        pub fn vkCmdBlitImage(
+            command_buffer: CommandBuffer,
+            src_image: Image,
+            src_image_layout: ImageLayout,
+            dst_image: Image,
+            dst_image_layout: ImageLayout,
+            region_count: u32,
+            regions: *const ImageBlit,
+            filter: Filter,
+        );
+        pub fn vkCmdResolveImage(
+            command_buffer: CommandBuffer,
+            src_image: Image,
+            src_image_layout: ImageLayout,
+            dst_image: Image,
+            dst_image_layout: ImageLayout,
+            region_count: u32,
+            regions: *const ImageResolve,
+        );
         pub fn vkCmdPipelineBarrier(
             command_buffer: CommandBuffer,
             src_stage_mask: Flags,
@@ -2277,6 +4006,31 @@ mod ffi {
             semaphore: *mut Semaphore,
         ) -> Result;
         pub fn vkDestroySemaphore(device: Device, semaphore: Semaphore, allocator: *const ());
+        pub fn vkCreateEvent(
+            device: Device,
+            create_info: *const EventCreateInfo,
+            allocator: *const (),
+            event: *mut Event,
+        ) -> Result;
+        pub fn vkDestroyEvent(device: Device, event: Event, allocator: *const ());
+        pub fn vkGetEventStatus(device: Device, event: Event) -> Result;
+        pub fn vkSetEvent(device: Device, event: Event) -> Result;
+        pub fn vkResetEvent(device: Device, event: Event) -> Result;
+        pub fn vkCmdSetEvent(command_buffer: CommandBuffer, event: Event, stage_mask: Flags);
+        pub fn vkCmdResetEvent(command_buffer: CommandBuffer, event: Event, stage_mask: Flags);
+        pub fn vkCmdWaitEvents(
+            command_buffer: CommandBuffer,
+            event_count: u32,
+            events: *const Event,
+            src_stage_mask: Flags,
+            dst_stage_mask: Flags,
+            memory_barrier_count: u32,
+            memory_barriers: *const MemoryBarrier,
+            buffer_memory_barrier_count: u32,
+            buffer_memory_barriers: *const BufferMemoryBarrier,
+            image_memory_barrier_count: u32,
+            image_memory_barriers: *const ImageMemoryBarrier,
+        );
         pub fn vkWaitForFences(
             device: Device,
             fence_count: u32,
@@ -2285,6 +4039,7 @@ mod ffi {
             timeout: u64,
         ) -> Result;
         pub fn vkResetFences(device: Device, fence_count: u32, fence: *const Fence) -> Result;
+        pub fn vkGetFenceStatus(device: Device, fence: Fence) -> Result;
         pub fn vkAcquireNextImageKHR(
             device: Device,
             swapchain: Swapchain,
@@ -2310,6 +4065,11 @@ mod ffi {
             data: *mut *mut (),
         ) -> Result;
         pub fn vkUnmapMemory(device: Device, memory: DeviceMemory);
+        pub fn vkInvalidateMappedMemoryRanges(
+            device: Device,
+            memory_range_count: u32,
+            memory_ranges: *const MappedMemoryRange,
+        ) -> Result;
         pub fn vkCreateDescriptorSetLayout(
             device: Device,
             create_info: *const DescriptorSetLayoutCreateInfo,
@@ -2321,337 +4081,3062 @@ mod ffi {
             set_layout: DescriptorSetLayout,
             allocator: *const (),
         );
+        pub fn vkCreateDescriptorUpdateTemplate(
+            device: Device,
+            create_info: *const DescriptorUpdateTemplateCreateInfo,
+            allocator: *const (),
+            descriptor_update_template: *mut DescriptorUpdateTemplate,
+        ) -> Result;
+        pub fn vkDestroyDescriptorUpdateTemplate(
+            device: Device,
+            descriptor_update_template: DescriptorUpdateTemplate,
+            allocator: *const (),
+        );
+        pub fn vkUpdateDescriptorSetWithTemplate(
+            device: Device,
+            descriptor_set: DescriptorSet,
+            descriptor_update_template: DescriptorUpdateTemplate,
+            data: *const (),
+        );
         pub fn vkCreateDescriptorPool(
             device: Device,
-            create_info: *const DescriptorPoolCreateInfo,
+            create_info: *const DescriptorPoolCreateInfo,
+            allocator: *const (),
+            descriptor_pool: *mut DescriptorPool,
+        ) -> Result;
+        pub fn vkDestroyDescriptorPool(
+            device: Device,
+            descriptor_pool: DescriptorPool,
+            allocator: *const (),
+        );
+        pub fn vkUpdateDescriptorSets(
+            device: Device,
+            write_count: u32,
+            writes: *const WriteDescriptorSet,
+            copy_count: u32,
+            copies: *const CopyDescriptorSet,
+        );
+        pub fn vkAllocateDescriptorSets(
+            device: Device,
+            allocate_info: *const DescriptorSetAllocateInfo,
+            descriptor_sets: *mut DescriptorSet,
+        ) -> Result;
+        pub fn vkCmdPushConstants(
+            command_buffer: CommandBuffer,
+            layout: PipelineLayout,
+            stage_flags: u32,
+            offset: u32,
+            size: u32,
+            values: *const (),
+        );
+        pub fn vkCreateQueryPool(
+            device: Device,
+            create_info: *const QueryPoolCreateInfo,
+            allocator: *const (),
+            query_pool: *mut QueryPool,
+        ) -> Result;
+        pub fn vkDestroyQueryPool(device: Device, query_pool: QueryPool, allocator: *const ());
+        pub fn vkGetQueryPoolResults(
+            device: Device,
+            query_pool: QueryPool,
+            first_query: u32,
+            query_count: u32,
+            data_size: usize,
+            data: *mut (),
+            stride: DeviceSize,
+            flags: u32,
+        ) -> Result;
+        pub fn vkCmdResetQueryPool(
+            command_buffer: CommandBuffer,
+            query_pool: QueryPool,
+            first_query: u32,
+            query_count: u32,
+        );
+        pub fn vkCmdBeginQuery(
+            command_buffer: CommandBuffer,
+            query_pool: QueryPool,
+            query: u32,
+            flags: u32,
+        );
+        pub fn vkCmdEndQuery(command_buffer: CommandBuffer, query_pool: QueryPool, query: u32);
+        pub fn vkCmdWriteTimestamp(
+            command_buffer: CommandBuffer,
+            pipeline_stage: u32,
+            query_pool: QueryPool,
+            query: u32,
+        );
+        pub fn vkCmdBindDescriptorSets(
+            command_buffer: CommandBuffer,
+            bind_point: PipelineBindPoint,
+            layout: PipelineLayout,
+            first_set: u32,
+            descriptor_set_count: u32,
+            descriptor_sets: *const DescriptorSet,
+            dynamic_offset_count: u32,
+            dynamic_offsets: *const u32,
+        );
+        pub fn vkCreateImage(
+            device: Device,
+            create_info: *const ImageCreateInfo,
+            allocator: *const (),
+            image: *mut Image,
+        ) -> Result;
+        pub fn vkDestroyImage(device: Device, image: Image, allocator: *const ());
+        pub fn vkGetImageMemoryRequirements(
+            device: Device,
+            image: Image,
+            memory_requirements: *mut MemoryRequirements,
+        );
+        pub fn vkBindImageMemory(
+            device: Device,
+            image: Image,
+            memory: DeviceMemory,
+            memory_offset: DeviceSize,
+        ) -> Result;
+        pub fn vkCreateSampler(
+            device: Device,
+            create_info: *const SamplerCreateInfo,
+            allocator: *const (),
+            sampler: *mut Sampler,
+        ) -> Result;
+        pub fn vkDestroySampler(device: Device, sampler: Sampler, allocator: *const ());
+        pub fn vkCreateSamplerYcbcrConversion(
+            device: Device,
+            create_info: *const SamplerYcbcrConversionCreateInfo,
             allocator: *const (),
-            descriptor_pool: *mut DescriptorPool,
+            conversion: *mut SamplerYcbcrConversion,
         ) -> Result;
-        pub fn vkDestroyDescriptorPool(
+        pub fn vkDestroySamplerYcbcrConversion(
             device: Device,
-            descriptor_pool: DescriptorPool,
+            conversion: SamplerYcbcrConversion,
             allocator: *const (),
         );
-        pub fn vkUpdateDescriptorSets(
-            device: Device,
-            write_count: u32,
-            writes: *const WriteDescriptorSet,
-            copy_count: u32,
-            copies: *const CopyDescriptorSet,
+    }
+}
+
+//Features2 queries on 1.0 instances, see Instance::api_version
+pub const KHR_GET_PHYSICAL_DEVICE_PROPERTIES_2: &str = "VK_KHR_get_physical_device_properties2";
+pub const KHR_SURFACE: &str = "VK_KHR_surface";
+pub const KHR_XLIB_SURFACE: &str = "VK_KHR_xlib_surface";
+pub const KHR_XCB_SURFACE: &str = "VK_KHR_xcb_surface";
+pub const KHR_WAYLAND_SURFACE: &str = "VK_KHR_wayland_surface";
+pub const KHR_WIN32_SURFACE: &str = "VK_KHR_win32_surface";
+pub const EXT_METAL_SURFACE: &str = "VK_EXT_metal_surface";
+pub const KHR_PORTABILITY_ENUMERATION: &str = "VK_KHR_portability_enumeration";
+pub const KHR_PORTABILITY_SUBSET: &str = "VK_KHR_portability_subset";
+pub const KHR_SWAPCHAIN: &str = "VK_KHR_swapchain";
+pub const KHR_GET_SURFACE_CAPABILITIES_2: &str = "VK_KHR_get_surface_capabilities2";
+//requires KHR_GET_SURFACE_CAPABILITIES_2
+pub const EXT_SURFACE_MAINTENANCE_1: &str = "VK_EXT_surface_maintenance1";
+//requires EXT_SURFACE_MAINTENANCE_1 on the instance
+pub const EXT_SWAPCHAIN_MAINTENANCE_1: &str = "VK_EXT_swapchain_maintenance1";
+pub const EXT_HDR_METADATA: &str = "VK_EXT_hdr_metadata";
+//core in 1.1, dependencies of the dma-buf import extensions on devices still reporting 1.0
+pub const KHR_EXTERNAL_MEMORY: &str = "VK_KHR_external_memory";
+pub const KHR_GET_MEMORY_REQUIREMENTS_2: &str = "VK_KHR_get_memory_requirements2";
+pub const KHR_DEDICATED_ALLOCATION: &str = "VK_KHR_dedicated_allocation";
+pub const KHR_BIND_MEMORY_2: &str = "VK_KHR_bind_memory2";
+pub const KHR_MAINTENANCE_1: &str = "VK_KHR_maintenance1";
+pub const KHR_SAMPLER_YCBCR_CONVERSION: &str = "VK_KHR_sampler_ycbcr_conversion";
+//core in 1.2
+pub const KHR_IMAGE_FORMAT_LIST: &str = "VK_KHR_image_format_list";
+pub const KHR_EXTERNAL_MEMORY_FD: &str = "VK_KHR_external_memory_fd";
+pub const EXT_EXTERNAL_MEMORY_DMA_BUF: &str = "VK_EXT_external_memory_dma_buf";
+pub const EXT_IMAGE_DRM_FORMAT_MODIFIER: &str = "VK_EXT_image_drm_format_modifier";
+pub const EXT_VERTEX_ATTRIBUTE_DIVISOR: &str = "VK_EXT_vertex_attribute_divisor";
+pub const EXT_LINE_RASTERIZATION: &str = "VK_EXT_line_rasterization";
+pub const EXT_SHADER_ATOMIC_FLOAT: &str = "VK_EXT_shader_atomic_float";
+pub const EXT_DEPTH_CLIP_ENABLE: &str = "VK_EXT_depth_clip_enable";
+pub const EXT_DEPTH_RANGE_UNRESTRICTED: &str = "VK_EXT_depth_range_unrestricted";
+pub const EXT_SHADER_VIEWPORT_INDEX_LAYER: &str = "VK_EXT_shader_viewport_index_layer";
+pub const EXT_FRAGMENT_SHADER_INTERLOCK: &str = "VK_EXT_fragment_shader_interlock";
+pub const EXT_DESCRIPTOR_INDEXING: &str = "VK_EXT_descriptor_indexing";
+pub const KHR_MAINTENANCE_3: &str = "VK_KHR_maintenance3";
+pub const KHR_PERFORMANCE_QUERY: &str = "VK_KHR_performance_query";
+pub const EXT_MEMORY_PRIORITY: &str = "VK_EXT_memory_priority";
+pub const EXT_PAGEABLE_DEVICE_LOCAL_MEMORY: &str = "VK_EXT_pageable_device_local_memory";
+pub const KHR_DRAW_INDIRECT_COUNT: &str = "VK_KHR_draw_indirect_count";
+pub const KHR_PIPELINE_EXECUTABLE_PROPERTIES: &str = "VK_KHR_pipeline_executable_properties";
+pub const KHR_SYNCHRONIZATION_2: &str = "VK_KHR_synchronization2";
+pub const KHR_BUFFER_DEVICE_ADDRESS: &str = "VK_KHR_buffer_device_address";
+pub const KHR_DYNAMIC_RENDERING: &str = "VK_KHR_dynamic_rendering";
+//what KHR_DYNAMIC_RENDERING depends on before Vulkan 1.2
+pub const KHR_MULTIVIEW: &str = "VK_KHR_multiview";
+pub const KHR_MAINTENANCE_2: &str = "VK_KHR_maintenance2";
+pub const KHR_CREATE_RENDERPASS_2: &str = "VK_KHR_create_renderpass2";
+pub const KHR_DEPTH_STENCIL_RESOLVE: &str = "VK_KHR_depth_stencil_resolve";
+pub const KHR_RAY_TRACING_PIPELINE: &str = "VK_KHR_ray_tracing_pipeline";
+pub const NV_INHERITED_VIEWPORT_SCISSOR: &str = "VK_NV_inherited_viewport_scissor";
+
+pub const EXT_DEBUG_REPORT: &str = "VK_EXT_debug_report";
+pub const EXT_DEBUG_UTILS: &str = "VK_EXT_debug_utils";
+pub const EXT_VALIDATION_FEATURES: &str = "VK_EXT_validation_features";
+pub const KHR_SHADER_NON_SEMANTIC_INFO: &str = "VK_KHR_shader_non_semantic_info";
+
+pub const LAYER_KHRONOS_VALIDATION: &str = "VK_LAYER_KHRONOS_validation";
+pub const LAYER_LUNARG_STANDARD_VALIDATION: &str = "VK_LAYER_LUNARG_standard_validation";
+
+pub const DEBUG_UTILS_MESSAGE_SEVERITY_VERBOSE: u32 = 0x00000001;
+pub const DEBUG_UTILS_MESSAGE_SEVERITY_INFO: u32 = 0x00000010;
+pub const DEBUG_UTILS_MESSAGE_SEVERITY_WARNING: u32 = 0x00000100;
+pub const DEBUG_UTILS_MESSAGE_SEVERITY_ERROR: u32 = 0x00001000;
+
+pub const DEBUG_UTILS_MESSAGE_TYPE_GENERAL: u32 = 0x00000001;
+pub const DEBUG_UTILS_MESSAGE_TYPE_VALIDATION: u32 = 0x00000002;
+pub const DEBUG_UTILS_MESSAGE_TYPE_PERFORMANCE: u32 = 0x00000004;
+
+pub const QUEUE_GRAPHICS: u32 = 0x00000001;
+pub const QUEUE_COMPUTE: u32 = 0x00000002;
+pub const QUEUE_TRANSFER: u32 = 0x00000004;
+
+pub const QUEUE_FAMILY_IGNORED: u32 = u32::MAX;
+
+pub const INSTANCE_CREATE_ENUMERATE_PORTABILITY: u32 = 0x00000001;
+
+pub const DEVICE_QUEUE_CREATE_PROTECTED: u32 = 0x00000001;
+
+pub const COMMAND_POOL_CREATE_TRANSIENT: u32 = 0x00000001;
+pub const COMMAND_POOL_CREATE_RESET_COMMAND_BUFFER: u32 = 0x00000002;
+pub const COMMAND_POOL_CREATE_PROTECTED: u32 = 0x00000004;
+
+pub const BUFFER_CREATE_PROTECTED: u32 = 0x00000008;
+
+pub const IMAGE_CREATE_PROTECTED: u32 = 0x00000800;
+
+pub const COMMAND_BUFFER_USAGE_ONE_TIME_SUBMIT: u32 = 0x00000001;
+pub const COMMAND_BUFFER_USAGE_RENDER_PASS_CONTINUE: u32 = 0x00000002;
+pub const COMMAND_BUFFER_USAGE_SIMULTANEOUS_USE: u32 = 0x00000004;
+
+pub const SWAPCHAIN_CREATE_PROTECTED: u32 = 0x00000002;
+
+pub const DESCRIPTOR_POOL_CREATE_FREE_DESCRIPTOR_SET: u32 = 0x00000001;
+pub const DESCRIPTOR_POOL_CREATE_UPDATE_AFTER_BIND: u32 = 0x00000002;
+
+pub const DESCRIPTOR_SET_LAYOUT_CREATE_UPDATE_AFTER_BIND_POOL: u32 = 0x00000002;
+
+pub const DESCRIPTOR_BINDING_UPDATE_AFTER_BIND: u32 = 0x00000001;
+pub const DESCRIPTOR_BINDING_UPDATE_UNUSED_WHILE_PENDING: u32 = 0x00000002;
+pub const DESCRIPTOR_BINDING_PARTIALLY_BOUND: u32 = 0x00000004;
+pub const DESCRIPTOR_BINDING_VARIABLE_DESCRIPTOR_COUNT: u32 = 0x00000008;
+
+pub const QUERY_RESULT_64: u32 = 0x00000001;
+pub const QUERY_RESULT_WAIT: u32 = 0x00000002;
+pub const QUERY_RESULT_WITH_AVAILABILITY: u32 = 0x00000004;
+pub const QUERY_RESULT_PARTIAL: u32 = 0x00000008;
+
+pub const QUERY_CONTROL_PRECISE: u32 = 0x00000001;
+
+pub const IMAGE_ASPECT_COLOR: u32 = 0x00000001;
+pub const IMAGE_ASPECT_DEPTH: u32 = 0x00000002;
+pub const IMAGE_ASPECT_STENCIL: u32 = 0x00000004;
+pub const IMAGE_ASPECT_PLANE_0: u32 = 0x00000010;
+pub const IMAGE_ASPECT_PLANE_1: u32 = 0x00000020;
+pub const IMAGE_ASPECT_PLANE_2: u32 = 0x00000040;
+
+pub const CULL_MODE_NONE: u32 = 0;
+pub const CULL_MODE_FRONT: u32 = 0x00000001;
+pub const CULL_MODE_BACK: u32 = 0x00000002;
+pub const CULL_MODE_FRONT_AND_BACK: u32 = 0x00000003;
+
+pub const COLOR_COMPONENT_R: u32 = 0x00000001;
+pub const COLOR_COMPONENT_G: u32 = 0x00000002;
+pub const COLOR_COMPONENT_B: u32 = 0x00000004;
+pub const COLOR_COMPONENT_A: u32 = 0x00000008;
+
+pub const SAMPLE_COUNT_1: u32 = 0x00000001;
+pub const SAMPLE_COUNT_2: u32 = 0x00000002;
+pub const SAMPLE_COUNT_4: u32 = 0x00000004;
+pub const SAMPLE_COUNT_8: u32 = 0x00000008;
+pub const SAMPLE_COUNT_16: u32 = 0x00000010;
+pub const SAMPLE_COUNT_32: u32 = 0x00000020;
+pub const SAMPLE_COUNT_64: u32 = 0x00000040;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub enum SampleCount {
+    #[default]
+    One,
+    Two,
+    Four,
+    Eight,
+    Sixteen,
+    ThirtyTwo,
+    SixtyFour,
+}
+
+impl SampleCount {
+    pub const ALL: [Self; 7] = [
+        Self::One,
+        Self::Two,
+        Self::Four,
+        Self::Eight,
+        Self::Sixteen,
+        Self::ThirtyTwo,
+        Self::SixtyFour,
+    ];
+
+    pub fn count(self) -> u32 {
+        self.to_vk()
+    }
+
+    //The SAMPLE_COUNT_ bit, the flag value happens to equal the count
+    pub fn to_vk(self) -> u32 {
+        match self {
+            Self::One => SAMPLE_COUNT_1,
+            Self::Two => SAMPLE_COUNT_2,
+            Self::Four => SAMPLE_COUNT_4,
+            Self::Eight => SAMPLE_COUNT_8,
+            Self::Sixteen => SAMPLE_COUNT_16,
+            Self::ThirtyTwo => SAMPLE_COUNT_32,
+            Self::SixtyFour => SAMPLE_COUNT_64,
+        }
+    }
+
+    //The highest count up to this one that is set in sample count flags such as
+    //PhysicalDeviceLimits::framebuffer_color_sample_counts, One is always supported
+    pub fn clamp_to(self, supported: u32) -> Self {
+        Self::ALL
+            .into_iter()
+            .filter(|&samples| samples <= self && supported & samples.to_vk() != 0)
+            .max()
+            .unwrap_or(Self::One)
+    }
+}
+
+pub const SUBPASS_EXTERNAL: u32 = u32::MAX;
+
+pub const PIPELINE_STAGE_TOP_OF_PIPE: u32 = 0x00000001;
+pub const PIPELINE_STAGE_DRAW_INDIRECT: u32 = 0x00000002;
+pub const PIPELINE_STAGE_VERTEX_INPUT: u32 = 0x00000004;
+pub const PIPELINE_STAGE_FRAGMENT_SHADER: u32 = 0x00000080;
+pub const PIPELINE_STAGE_EARLY_FRAGMENT_TESTS: u32 = 0x00000100;
+pub const PIPELINE_STAGE_LATE_FRAGMENT_TESTS: u32 = 0x00000200;
+pub const PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT: u32 = 0x00000400;
+pub const PIPELINE_STAGE_COMPUTE_SHADER: u32 = 0x00000800;
+pub const PIPELINE_STAGE_TRANSFER: u32 = 0x00001000;
+pub const PIPELINE_STAGE_BOTTOM_OF_PIPE: u32 = 0x00002000;
+pub const PIPELINE_STAGE_HOST: u32 = 0x00004000;
+pub const PIPELINE_STAGE_ALL_COMMANDS: u32 = 0x00010000;
+
+pub const ACCESS_INDIRECT_COMMAND_READ: u32 = 0x00000001;
+pub const ACCESS_INDEX_READ: u32 = 0x00000002;
+pub const ACCESS_VERTEX_ATTRIBUTE_READ: u32 = 0x00000004;
+pub const ACCESS_SHADER_READ: u32 = 0x00000020;
+pub const ACCESS_SHADER_WRITE: u32 = 0x00000040;
+pub const ACCESS_COLOR_ATTACHMENT_READ: u32 = 0x00000080;
+pub const ACCESS_COLOR_ATTACHMENT_WRITE: u32 = 0x00000100;
+pub const ACCESS_DEPTH_STENCIL_ATTACHMENT_READ: u32 = 0x00000200;
+pub const ACCESS_DEPTH_STENCIL_ATTACHMENT_WRITE: u32 = 0x00000400;
+pub const ACCESS_TRANSFER_READ: u32 = 0x00000800;
+pub const ACCESS_TRANSFER_WRITE: u32 = 0x00001000;
+pub const ACCESS_HOST_READ: u32 = 0x00002000;
+pub const ACCESS_MEMORY_READ: u32 = 0x00008000;
+pub const ACCESS_MEMORY_WRITE: u32 = 0x00010000;
+
+//synchronization2 masks, the low 32 bits match the legacy flags
+pub type PipelineStageFlags2 = u64;
+pub type AccessFlags2 = u64;
+
+pub const PIPELINE_STAGE_2_NONE: PipelineStageFlags2 = 0;
+pub const PIPELINE_STAGE_2_TOP_OF_PIPE: PipelineStageFlags2 = 0x00000001;
+pub const PIPELINE_STAGE_2_DRAW_INDIRECT: PipelineStageFlags2 = 0x00000002;
+pub const PIPELINE_STAGE_2_VERTEX_INPUT: PipelineStageFlags2 = 0x00000004;
+pub const PIPELINE_STAGE_2_VERTEX_SHADER: PipelineStageFlags2 = 0x00000008;
+pub const PIPELINE_STAGE_2_TESSELLATION_CONTROL_SHADER: PipelineStageFlags2 = 0x00000010;
+pub const PIPELINE_STAGE_2_TESSELLATION_EVALUATION_SHADER: PipelineStageFlags2 = 0x00000020;
+pub const PIPELINE_STAGE_2_GEOMETRY_SHADER: PipelineStageFlags2 = 0x00000040;
+pub const PIPELINE_STAGE_2_FRAGMENT_SHADER: PipelineStageFlags2 = 0x00000080;
+pub const PIPELINE_STAGE_2_EARLY_FRAGMENT_TESTS: PipelineStageFlags2 = 0x00000100;
+pub const PIPELINE_STAGE_2_LATE_FRAGMENT_TESTS: PipelineStageFlags2 = 0x00000200;
+pub const PIPELINE_STAGE_2_COLOR_ATTACHMENT_OUTPUT: PipelineStageFlags2 = 0x00000400;
+pub const PIPELINE_STAGE_2_COMPUTE_SHADER: PipelineStageFlags2 = 0x00000800;
+pub const PIPELINE_STAGE_2_ALL_TRANSFER: PipelineStageFlags2 = 0x00001000;
+pub const PIPELINE_STAGE_2_BOTTOM_OF_PIPE: PipelineStageFlags2 = 0x00002000;
+pub const PIPELINE_STAGE_2_HOST: PipelineStageFlags2 = 0x00004000;
+pub const PIPELINE_STAGE_2_ALL_GRAPHICS: PipelineStageFlags2 = 0x00008000;
+pub const PIPELINE_STAGE_2_ALL_COMMANDS: PipelineStageFlags2 = 0x00010000;
+pub const PIPELINE_STAGE_2_COPY: PipelineStageFlags2 = 0x100000000;
+pub const PIPELINE_STAGE_2_RESOLVE: PipelineStageFlags2 = 0x200000000;
+pub const PIPELINE_STAGE_2_BLIT: PipelineStageFlags2 = 0x400000000;
+pub const PIPELINE_STAGE_2_CLEAR: PipelineStageFlags2 = 0x800000000;
+pub const PIPELINE_STAGE_2_INDEX_INPUT: PipelineStageFlags2 = 0x1000000000;
+pub const PIPELINE_STAGE_2_VERTEX_ATTRIBUTE_INPUT: PipelineStageFlags2 = 0x2000000000;
+pub const PIPELINE_STAGE_2_PRE_RASTERIZATION_SHADERS: PipelineStageFlags2 = 0x4000000000;
+
+pub const ACCESS_2_NONE: AccessFlags2 = 0;
+pub const ACCESS_2_INDIRECT_COMMAND_READ: AccessFlags2 = 0x00000001;
+pub const ACCESS_2_INDEX_READ: AccessFlags2 = 0x00000002;
+pub const ACCESS_2_VERTEX_ATTRIBUTE_READ: AccessFlags2 = 0x00000004;
+pub const ACCESS_2_UNIFORM_READ: AccessFlags2 = 0x00000008;
+pub const ACCESS_2_INPUT_ATTACHMENT_READ: AccessFlags2 = 0x00000010;
+pub const ACCESS_2_SHADER_READ: AccessFlags2 = 0x00000020;
+pub const ACCESS_2_SHADER_WRITE: AccessFlags2 = 0x00000040;
+pub const ACCESS_2_COLOR_ATTACHMENT_READ: AccessFlags2 = 0x00000080;
+pub const ACCESS_2_COLOR_ATTACHMENT_WRITE: AccessFlags2 = 0x00000100;
+pub const ACCESS_2_DEPTH_STENCIL_ATTACHMENT_READ: AccessFlags2 = 0x00000200;
+pub const ACCESS_2_DEPTH_STENCIL_ATTACHMENT_WRITE: AccessFlags2 = 0x00000400;
+pub const ACCESS_2_TRANSFER_READ: AccessFlags2 = 0x00000800;
+pub const ACCESS_2_TRANSFER_WRITE: AccessFlags2 = 0x00001000;
+pub const ACCESS_2_HOST_READ: AccessFlags2 = 0x00002000;
+pub const ACCESS_2_HOST_WRITE: AccessFlags2 = 0x00004000;
+pub const ACCESS_2_MEMORY_READ: AccessFlags2 = 0x00008000;
+pub const ACCESS_2_MEMORY_WRITE: AccessFlags2 = 0x00010000;
+pub const ACCESS_2_SHADER_SAMPLED_READ: AccessFlags2 = 0x100000000;
+pub const ACCESS_2_SHADER_STORAGE_READ: AccessFlags2 = 0x200000000;
+pub const ACCESS_2_SHADER_STORAGE_WRITE: AccessFlags2 = 0x400000000;
+
+pub const SUBMIT_PROTECTED: u32 = 0x00000001;
+
+pub const PRESENT_SCALING_ONE_TO_ONE: u32 = 0x00000001;
+pub const PRESENT_SCALING_ASPECT_RATIO_STRETCH: u32 = 0x00000002;
+pub const PRESENT_SCALING_STRETCH: u32 = 0x00000004;
+
+pub const PRESENT_GRAVITY_MIN: u32 = 0x00000001;
+pub const PRESENT_GRAVITY_MAX: u32 = 0x00000002;
+pub const PRESENT_GRAVITY_CENTERED: u32 = 0x00000004;
+
+pub const BUFFER_USAGE_TRANSFER_SRC: u32 = 0x00000001;
+pub const BUFFER_USAGE_TRANSFER_DST: u32 = 0x00000002;
+pub const BUFFER_USAGE_UNIFORM: u32 = 0x00000010;
+pub const BUFFER_USAGE_STORAGE: u32 = 0x00000020;
+pub const BUFFER_USAGE_INDEX: u32 = 0x00000040;
+pub const BUFFER_USAGE_VERTEX: u32 = 0x00000080;
+pub const BUFFER_USAGE_INDIRECT: u32 = 0x00000100;
+pub const BUFFER_USAGE_SHADER_BINDING_TABLE: u32 = 0x00000400;
+//Buffer::device_address, the memory needs MEMORY_ALLOCATE_DEVICE_ADDRESS
+pub const BUFFER_USAGE_SHADER_DEVICE_ADDRESS: u32 = 0x00020000;
+
+pub const MEMORY_ALLOCATE_DEVICE_ADDRESS: u32 = 0x00000002;
+
+pub const PIPELINE_CREATE_CAPTURE_STATISTICS: u32 = 0x00000040;
+pub const PIPELINE_CREATE_CAPTURE_INTERNAL_REPRESENTATIONS: u32 = 0x00000080;
+
+pub const IMAGE_USAGE_TRANSFER_SRC: u32 = 0x00000001;
+pub const IMAGE_USAGE_TRANSFER_DST: u32 = 0x00000002;
+pub const IMAGE_USAGE_SAMPLED: u32 = 0x00000004;
+pub const IMAGE_USAGE_STORAGE: u32 = 0x00000008;
+pub const IMAGE_USAGE_COLOR_ATTACHMENT: u32 = 0x00000010;
+pub const IMAGE_USAGE_DEPTH_STENCIL_ATTACHMENT: u32 = 0x00000020;
+pub const IMAGE_USAGE_TRANSIENT_ATTACHMENT: u32 = 0x00000040;
+pub const IMAGE_USAGE_INPUT_ATTACHMENT: u32 = 0x00000080;
+
+pub const FORMAT_FEATURE_SAMPLED_IMAGE: u32 = 0x00000001;
+pub const FORMAT_FEATURE_STORAGE_IMAGE: u32 = 0x00000002;
+pub const FORMAT_FEATURE_STORAGE_IMAGE_ATOMIC: u32 = 0x00000004;
+pub const FORMAT_FEATURE_UNIFORM_TEXEL_BUFFER: u32 = 0x00000008;
+pub const FORMAT_FEATURE_STORAGE_TEXEL_BUFFER: u32 = 0x00000010;
+pub const FORMAT_FEATURE_STORAGE_TEXEL_BUFFER_ATOMIC: u32 = 0x00000020;
+pub const FORMAT_FEATURE_VERTEX_BUFFER: u32 = 0x00000040;
+pub const FORMAT_FEATURE_COLOR_ATTACHMENT: u32 = 0x00000080;
+pub const FORMAT_FEATURE_COLOR_ATTACHMENT_BLEND: u32 = 0x00000100;
+pub const FORMAT_FEATURE_DEPTH_STENCIL_ATTACHMENT: u32 = 0x00000200;
+pub const FORMAT_FEATURE_BLIT_SRC: u32 = 0x00000400;
+pub const FORMAT_FEATURE_BLIT_DST: u32 = 0x00000800;
+pub const FORMAT_FEATURE_SAMPLED_IMAGE_FILTER_LINEAR: u32 = 0x00001000;
+pub const FORMAT_FEATURE_TRANSFER_SRC: u32 = 0x00004000;
+pub const FORMAT_FEATURE_TRANSFER_DST: u32 = 0x00008000;
+
+pub const MEMORY_PROPERTY_DEVICE_LOCAL: u32 = 0x00000001;
+pub const MEMORY_PROPERTY_HOST_VISIBLE: u32 = 0x00000002;
+pub const MEMORY_PROPERTY_HOST_COHERENT: u32 = 0x00000004;
+pub const MEMORY_PROPERTY_HOST_CACHED: u32 = 0x00000008;
+pub const MEMORY_PROPERTY_LAZILY_ALLOCATED: u32 = 0x00000010;
+pub const MEMORY_PROPERTY_PROTECTED: u32 = 0x00000020;
+
+pub const EXTERNAL_MEMORY_HANDLE_TYPE_DMA_BUF: u32 = 0x00000200;
+
+pub const SHADER_STAGE_VERTEX: u32 = 0x00000001;
+pub const SHADER_STAGE_FRAGMENT: u32 = 0x00000010;
+pub const SHADER_STAGE_COMPUTE: u32 = 0x00000020;
+
+pub type DebugUtilsMessengerCallback = fn(&DebugUtilsMessengerCallbackData) -> bool;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Error {
+    OutOfHostMemory,
+    OutOfDeviceMemory,
+    InitializationFailed,
+    DeviceLost,
+    MemoryMapFailed,
+    LayerNotPresent,
+    ExtensionNotPresent,
+    FeatureNotPresent,
+    IncompatibleDriver,
+    TooManyObjects,
+    FormatNotSupported,
+    FragmentedPool,
+    Unknown,
+    OutOfPoolMemory,
+    Fragmentation,
+    InvalidExternalHandle,
+    SurfaceLost,
+    NativeWindowInUse,
+    Suboptimal,
+    OutOfDate,
+    InvalidShader,
+    FullScreenExclusiveModeLost,
+    InvalidOpaqueCaptureAddress,
+    CompressionExhausted,
+    PushConstantRangeMisaligned { offset: u32, size: u32 },
+    PushConstantRangeExceedsLimit { offset: u32, size: u32, max: u32 },
+    PushConstantStagesOverlap { stage_flags: u32 },
+    //a push constant range for no stage at all
+    PushConstantRangeWithoutStages { offset: u32, size: u32 },
+    PushConstantsOutOfRange { stage_flags: u32, offset: u32, size: u32 },
+    FramebufferLayersExceedAttachment { layers: u32, attachment: u32, layer_count: u32 },
+    VertexInputBindingsExceedLimit { count: u32, max: u32 },
+    VertexInputAttributesExceedLimit { count: u32, max: u32 },
+    VertexInputBindingExceedsLimit { binding: u32, max: u32 },
+    VertexInputBindingStrideExceedsLimit { binding: u32, stride: usize, max: u32 },
+    VertexInputLocationExceedsLimit { location: u32, max: u32 },
+    VertexInputAttributeOffsetExceedsLimit { location: u32, offset: u32, max: u32 },
+    VertexInputAttributeBindingMissing { location: u32, binding: u32 },
+    NoSuitableQueueFamily { queue_flags: u32 },
+    Timeout,
+    MemoryMisaligned { offset: usize, alignment: usize },
+    //the offset is past the end of the mapped memory or allocation
+    MemoryOutOfRange { offset: usize, size: u64 },
+    //the window handle is of a kind no surface can be created for on this platform
+    UnsupportedWindowSystem,
+    //no memory type allowed by memory_type_bits has any of the requested properties
+    NoSuitableMemoryType { memory_type_bits: u32, property_flags: u32 },
+    //objects of different devices passed to the same call
+    MixedDevices,
+    //swapchain color space or composite alpha this crate does not handle yet
+    UnsupportedSwapchainConfiguration,
+    //memory is bound to swapchain images by the presentation engine
+    ImageNotBindable,
+    PipelineCacheSelfMerge,
+    EventDependencyCountMismatch { events: usize, dependency_infos: usize },
+    //the data passed to a descriptor update template ends before its last entry
+    DescriptorTemplateDataTooSmall { required: usize, provided: usize },
+    //the loader only supports instances up to supported, see ApiVersionPolicy
+    ApiVersionNotSupported { requested: Version, supported: Version },
+    //a dma-buf import describes planes other than the modifier has, see
+    //PhysicalDevice::drm_format_modifier_plane_count
+    DrmFormatModifierPlaneCountMismatch { planes: u32, expected: u32 },
+}
+
+//A result the spec does not allow for the call, a driver or loader bug. Asserts in debug
+//builds, release builds carry on with Unknown.
+fn unexpected_result(result: ffi::Result) -> Error {
+    debug_assert!(false, "unexpected result: {:?}", result);
+
+    Error::Unknown
+}
+
+//For surface creation, which only fails for lack of memory
+#[cfg(feature = "window")]
+fn surface_result(result: ffi::Result) -> Result<(), Error> {
+    match result {
+        ffi::Result::Success => Ok(()),
+        ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+        ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+        _ => Err(unexpected_result(result)),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    Rg4UnormPack8,
+    Rgba4UnormPack16,
+    Bgra4UnormPack16,
+    R5G6B5UnormPack16,
+    B5G6R5UnormPack16,
+    R5G5B5A1UnormPack16,
+    B5G5R5A1UnormPack16,
+    A1R5G5B5UnormPack16,
+    R8Unorm,
+    R8Snorm,
+    R8Uscaled,
+    R8Sscaled,
+    R8Uint,
+    R8Sint,
+    R8Srgb,
+    Rg8Unorm,
+    Rg8Snorm,
+    Rg8Uscaled,
+    Rg8Sscaled,
+    Rg8Uint,
+    Rg8Sint,
+    Rg8Srgb,
+    Rgb8Unorm,
+    Rgb8Snorm,
+    Rgb8Uscaled,
+    Rgb8Sscaled,
+    Rgb8Uint,
+    Rgb8Sint,
+    Rgb8Srgb,
+    Bgr8Unorm,
+    Bgr8Snorm,
+    Bgr8Uscaled,
+    Bgr8Sscaled,
+    Bgr8Uint,
+    Bgr8Sint,
+    Bgr8Srgb,
+    Rgba8Unorm,
+    Rgba8Snorm,
+    Rgba8Uscaled,
+    Rgba8Sscaled,
+    Rgba8Uint,
+    Rgba8Sint,
+    Rgba8Srgb,
+    Bgra8Unorm,
+    Bgra8Snorm,
+    Bgra8Uscaled,
+    Bgra8Sscaled,
+    Bgra8Uint,
+    Bgra8Sint,
+    Bgra8Srgb,
+    Abgr8UnormPack32,
+    Abgr8SnormPack32,
+    Abgr8UscaledPack32,
+    Abgr8SscaledPack32,
+    Abgr8UintPack32,
+    Abgr8SintPack32,
+    Abgr8SrgbPack32,
+    A2R10G10B10UnormPack32,
+    A2R10G10B10SnormPack32,
+    A2R10G10B10UscaledPack32,
+    A2R10G10B10SscaledPack32,
+    A2R10G10B10UintPack32,
+    A2R10G10B10SintPack32,
+    A2B10G10R10UnormPack32,
+    A2B10G10R10SnormPack32,
+    A2B10G10R10UscaledPack32,
+    A2B10G10R10SscaledPack32,
+    A2B10G10R10UintPack32,
+    A2B10G10R10SintPack32,
+    R16Unorm,
+    R16Snorm,
+    R16Uscaled,
+    R16Sscaled,
+    R16Uint,
+    R16Sint,
+    R16Sfloat,
+    Rg16Unorm,
+    Rg16Snorm,
+    Rg16Uscaled,
+    Rg16Sscaled,
+    Rg16Uint,
+    Rg16Sint,
+    Rg16Sfloat,
+    Rgb16Unorm,
+    Rgb16Snorm,
+    Rgb16Uscaled,
+    Rgb16Sscaled,
+    Rgb16Uint,
+    Rgb16Sint,
+    Rgb16Sfloat,
+    Rgba16Unorm,
+    Rgba16Snorm,
+    Rgba16Uscaled,
+    Rgba16Sscaled,
+    Rgba16Uint,
+    Rgba16Sint,
+    Rgba16Sfloat,
+    R32Uint,
+    R32Sint,
+    R32Sfloat,
+    Rg32Uint,
+    Rg32Sint,
+    Rg32Sfloat,
+    Rgb32Uint,
+    Rgb32Sint,
+    Rgb32Sfloat,
+    Rgba32Uint,
+    Rgba32Sint,
+    Rgba32Sfloat,
+    R64Uint,
+    R64Sint,
+    R64Sfloat,
+    Rg64Uint,
+    Rg64Sint,
+    Rg64Sfloat,
+    Rgb64Uint,
+    Rgb64Sint,
+    Rgb64Sfloat,
+    Rgba64Uint,
+    Rgba64Sint,
+    Rgba64Sfloat,
+    B10G11R11UfloatPack32,
+    E5B9G9R9UfloatPack32,
+    D16Unorm,
+    X8D24UnormPack32,
+    D32Sfloat,
+    S8Uint,
+    D16UnormS8Uint,
+    D24UnormS8Uint,
+    D32SfloatS8Uint,
+    Bc1RgbUnormBlock,
+    Bc1RgbSrgbBlock,
+    Bc1RgbaUnormBlock,
+    Bc1RgbaSrgbBlock,
+    Bc2UnormBlock,
+    Bc2SrgbBlock,
+    Bc3UnormBlock,
+    Bc3SrgbBlock,
+    Bc4UnormBlock,
+    Bc4SnormBlock,
+    Bc5UnormBlock,
+    Bc5SnormBlock,
+    Bc6hUfloatBlock,
+    Bc6hSfloatBlock,
+    Bc7UnormBlock,
+    Bc7SrgbBlock,
+    Etc2Rgb8UnormBlock,
+    Etc2Rgb8SrgbBlock,
+    Etc2R8G8B8A1UnormBlock,
+    Etc2R8G8B8A1SrgbBlock,
+    Etc2Rgba8UnormBlock,
+    Etc2Rgba8SrgbBlock,
+    EacR11UnormBlock,
+    EacR11SnormBlock,
+    EacRg11UnormBlock,
+    EacRg11SnormBlock,
+    Astc4x4UnormBlock,
+    Astc4x4SrgbBlock,
+    Astc5x4UnormBlock,
+    Astc5x4SrgbBlock,
+    Astc5x5UnormBlock,
+    Astc5x5SrgbBlock,
+    Astc6x5UnormBlock,
+    Astc6x5SrgbBlock,
+    Astc6x6UnormBlock,
+    Astc6x6SrgbBlock,
+    Astc8x5UnormBlock,
+    Astc8x5SrgbBlock,
+    Astc8x6UnormBlock,
+    Astc8x6SrgbBlock,
+    Astc8x8UnormBlock,
+    Astc8x8SrgbBlock,
+    Astc10x5UnormBlock,
+    Astc10x5SrgbBlock,
+    Astc10x6UnormBlock,
+    Astc10x6SrgbBlock,
+    Astc10x8UnormBlock,
+    Astc10x8SrgbBlock,
+    Astc10x10UnormBlock,
+    Astc10x10SrgbBlock,
+    Astc12x10UnormBlock,
+    Astc12x10SrgbBlock,
+    Astc12x12UnormBlock,
+    Astc12x12SrgbBlock,
+    //multi-planar, sampled through a SamplerYcbcrConversion
+    G8B8R83Plane420Unorm,
+    G8B8R82Plane420Unorm,
+    G10X6B10X6R10X62Plane420Unorm3Pack16,
+}
+
+impl Format {
+    //Views of multi-planar formats cover every plane with the color aspect
+    pub fn aspect_mask(self) -> u32 {
+        match self {
+            Self::D16Unorm | Self::X8D24UnormPack32 | Self::D32Sfloat => IMAGE_ASPECT_DEPTH,
+            Self::S8Uint => IMAGE_ASPECT_STENCIL,
+            Self::D16UnormS8Uint | Self::D24UnormS8Uint | Self::D32SfloatS8Uint => {
+                IMAGE_ASPECT_DEPTH | IMAGE_ASPECT_STENCIL
+            }
+            _ => IMAGE_ASPECT_COLOR,
+        }
+    }
+
+    pub fn plane_count(self) -> u32 {
+        match self {
+            Self::G8B8R83Plane420Unorm => 3,
+            Self::G8B8R82Plane420Unorm | Self::G10X6B10X6R10X62Plane420Unorm3Pack16 => 2,
+            _ => 1,
+        }
+    }
+
+    //Aspect to copy into or barrier a single plane, None past the third
+    pub fn plane_aspect(plane: u32) -> Option<u32> {
+        match plane {
+            0 => Some(IMAGE_ASPECT_PLANE_0),
+            1 => Some(IMAGE_ASPECT_PLANE_1),
+            2 => Some(IMAGE_ASPECT_PLANE_2),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum IndexType {
+    Uint16,
+    Uint32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorSpace {
+    SrgbNonlinear,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PresentMode {
+    Immediate,
+    Mailbox,
+    Fifo,
+    FifoRelaxed,
+}
+
+pub type Extent2d = (u32, u32);
+pub type Extent3d = (u32, u32, u32);
+
+pub type Offset2d = (i32, i32);
+pub type Offset3d = (i32, i32, i32);
+
+#[derive(Clone, Copy)]
+pub struct SurfaceCapabilities {
+    pub min_image_count: u32,
+    pub max_image_count: u32,
+    pub current_extent: Extent2d,
+    pub min_image_extent: Extent2d,
+    pub max_image_extent: Extent2d,
+    pub max_image_array_layers: u32,
+    pub supported_transforms: u32,
+    pub current_transform: u32,
+    pub supported_composite_alpha: u32,
+    pub supported_usage_flags: u32,
+}
+
+//How a surface may scale swapchain images that do not match its size, all flags empty
+//when it cannot. PRESENT_SCALING_* and PRESENT_GRAVITY_* bits.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PresentScalingCapabilities {
+    pub supported_scaling: u32,
+    pub supported_gravity_x: u32,
+    pub supported_gravity_y: u32,
+    pub min_scaled_image_extent: Extent2d,
+    pub max_scaled_image_extent: Extent2d,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SurfaceFormat {
+    pub format: Format,
+    pub color_space: ColorSpace,
+}
+
+#[derive(Clone, Copy)]
+pub enum SharingMode {
+    Exclusive,
+    //between the queue families in queue_family_indices, without ownership transfers
+    Concurrent,
+}
+
+#[derive(Clone, Copy)]
+pub enum CompositeAlpha {
+    Opaque,
+}
+
+//Orders by major, then minor, then patch
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Version {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl Version {
+    pub fn major(self) -> u32 {
+        self.major
+    }
+
+    pub fn minor(self) -> u32 {
+        self.minor
+    }
+
+    pub fn patch(self) -> u32 {
+        self.patch
+    }
+}
+
+impl From<u32> for Version {
+    fn from(version: u32) -> Self {
+        Version {
+            major: (version >> 22) & 0x7f,
+            minor: (version >> 12) & 0x3ff,
+            patch: version & 0xfff,
+        }
+    }
+}
+
+impl From<(u32, u32, u32)> for Version {
+    fn from(tuple: (u32, u32, u32)) -> Self {
+        Version {
+            major: tuple.0,
+            minor: tuple.1,
+            patch: tuple.2,
+        }
+    }
+}
+
+impl From<Version> for u32 {
+    fn from(version: Version) -> Self {
+        (version.major << 22 | version.minor << 12 | version.patch) as u32
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ApplicationInfo<'a> {
+    pub application_name: &'a str,
+    pub application_version: Version,
+    pub engine_name: &'a str,
+    pub engine_version: Version,
+    pub api_version: Version,
+}
+
+//What Instance::new does when the loader supports less than ApplicationInfo::api_version
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ApiVersionPolicy {
+    //fails with ApiVersionNotSupported, only major and minor are compared as
+    //implementations ignore the patch of an api version
+    RequireExact,
+    //creates the instance with the highest version the loader supports
+    ClampToSupported,
+}
+
+impl ApiVersionPolicy {
+    fn apply(self, requested: Version, supported: Version) -> Result<Version, Error> {
+        match self {
+            Self::RequireExact
+                if (requested.major, requested.minor) > (supported.major, supported.minor) =>
+            {
+                Err(Error::ApiVersionNotSupported {
+                    requested,
+                    supported,
+                })
+            }
+            Self::RequireExact => Ok(requested),
+            Self::ClampToSupported => Ok(requested.min(supported)),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct InstanceCreateInfo<'a> {
+    //INSTANCE_CREATE_ENUMERATE_PORTABILITY lists portability drivers such as MoltenVK,
+    //requires KHR_PORTABILITY_ENUMERATION
+    pub flags: u32,
+    pub application_info: &'a ApplicationInfo<'a>,
+    pub api_version_policy: ApiVersionPolicy,
+    pub extensions: &'a [&'a str],
+    pub layers: &'a [&'a str],
+    pub debug_utils: &'a Option<DebugUtilsMessengerCreateInfo>,
+    pub validation_features: &'a [ValidationFeature],
+}
+
+#[derive(Clone, Copy)]
+pub struct DebugUtilsMessengerCreateInfo {
+    pub message_severity: u32,
+    pub message_type: u32,
+    pub user_callback: DebugUtilsMessengerCallback,
+}
+
+#[derive(Clone, Copy)]
+pub struct DebugUtilsMessengerCallbackData<'a> {
+    pub message_severity: u32,
+    pub message_type: u32,
+    pub message: &'a str,
+    //e.g. WARNING-DEBUG-PRINTF for debugPrintfEXT output
+    pub message_id_name: &'a str,
+    //command buffer labels open at the command that triggered the message, innermost last
+    pub labels: &'a [String],
+}
+
+//Opt in validation layer features, requires EXT_VALIDATION_FEATURES
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ValidationFeature {
+    GpuAssisted,
+    BestPractices,
+    //debugPrintfEXT output arrives through the debug messenger as INFO messages,
+    //shaders also need KHR_SHADER_NON_SEMANTIC_INFO on the device
+    DebugPrintf,
+    Synchronization,
+}
+
+impl From<ValidationFeature> for ffi::ValidationFeatureEnable {
+    fn from(feature: ValidationFeature) -> Self {
+        match feature {
+            ValidationFeature::GpuAssisted => Self::GpuAssisted,
+            ValidationFeature::BestPractices => Self::BestPractices,
+            ValidationFeature::DebugPrintf => Self::DebugPrintf,
+            ValidationFeature::Synchronization => Self::SynchronizationValidation,
+        }
+    }
+}
+
+pub struct Instance {
+    handle: ffi::Instance,
+    api_version: Version,
+    extensions: Vec<String>,
+}
+
+impl Instance {
+    //Instance extensions the loader and drivers provide, to check before Instance::new
+    pub fn available_extensions() -> Vec<String> {
+        let mut property_count: u32 = 0;
+
+        unsafe {
+            ffi::vkEnumerateInstanceExtensionProperties(
+                ptr::null(),
+                &mut property_count,
+                ptr::null_mut(),
+            )
+        };
+
+        let mut properties = Vec::<ffi::ExtensionProperties>::with_capacity(property_count as _);
+
+        unsafe {
+            ffi::vkEnumerateInstanceExtensionProperties(
+                ptr::null(),
+                &mut property_count,
+                properties.as_mut_ptr(),
+            )
+        };
+
+        unsafe { properties.set_len(property_count as _) };
+
+        let extensions = properties
+            .into_iter()
+            .map(|properties| {
+                unsafe { CStr::from_ptr(properties.extension_name.as_ptr()) }
+                    .to_str()
+                    .unwrap()
+                    .to_owned()
+            })
+            .collect::<Vec<_>>();
+
+        extensions
+    }
+
+    //The highest instance version the loader supports, 1.0 loaders lack
+    //vkEnumerateInstanceVersion altogether
+    pub fn supported_version() -> Result<Version, Error> {
+        let f_name = CStr::from_bytes_with_nul(b"vkEnumerateInstanceVersion\0").unwrap();
+
+        let f = unsafe { ffi::vkGetInstanceProcAddr(ffi::Instance::null(), f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Ok((1, 0, 0).into());
+        }
+
+        let f = unsafe { mem::transmute::<_, ffi::EnumerateInstanceVersion>(f) };
+
+        let mut version = 0;
+
+        let result = unsafe { f(&mut version) };
+
+        match result {
+            ffi::Result::Success => Ok(version.into()),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    //What the instance was created with after ApiVersionPolicy, device features and
+    //extensions promoted past it still have to be enabled through their extension
+    pub fn api_version(&self) -> Version {
+        self.api_version
+    }
+
+    pub fn is_extension_enabled(&self, extension: &str) -> bool {
+        self.extensions.iter().any(|enabled| enabled == extension)
+    }
+
+    pub fn new(create_info: InstanceCreateInfo<'_>) -> Result<Rc<Instance>, Error> {
+        let application_name = CString::new(create_info.application_info.application_name).unwrap();
+
+        let application_version: u32 = create_info.application_info.application_version.into();
+
+        let engine_name = CString::new(create_info.application_info.application_name).unwrap();
+
+        let engine_version: u32 = create_info.application_info.engine_version.into();
+
+        let requested = create_info.application_info.api_version;
+
+        let supported = Self::supported_version()?;
+
+        let api_version = create_info.api_version_policy.apply(requested, supported)?;
+
+        let application_info = ffi::ApplicationInfo {
+            structure_type: ffi::StructureType::ApplicationInfo,
+            p_next: ptr::null(),
+            application_name: application_name.as_ptr(),
+            application_version,
+            engine_name: engine_name.as_ptr(),
+            engine_version,
+            api_version: api_version.into(),
+        };
+
+        let layer_names = create_info
+            .layers
+            .iter()
+            .map(|layer_name| CString::new(*layer_name).unwrap())
+            .collect::<Vec<_>>();
+
+        let enabled_layer_names = layer_names
+            .iter()
+            .map(|string| string.as_ptr())
+            .collect::<Vec<_>>();
+
+        let enabled_layer_names_ptr = if layer_names.len() > 0 {
+            enabled_layer_names.as_ptr()
+        } else {
+            ptr::null()
+        };
+
+        let extension_names = create_info
+            .extensions
+            .iter()
+            .map(|extension_name| CString::new(*extension_name).unwrap())
+            .collect::<Vec<_>>();
+
+        let enabled_extension_names = extension_names
+            .iter()
+            .map(|string| string.as_ptr())
+            .collect::<Vec<_>>();
+
+        let enabled_extension_names_ptr = if extension_names.len() > 0 {
+            enabled_extension_names.as_ptr()
+        } else {
+            ptr::null()
+        };
+
+        let debug_utils = if let Some(create_info) = create_info.debug_utils {
+            let g = unsafe { mem::transmute(create_info.user_callback) };
+
+            let create_info = ffi::DebugUtilsMessengerCreateInfo {
+                structure_type: ffi::StructureType::DebugUtilsMessengerCreateInfo,
+                p_next: ptr::null(),
+                flags: 0,
+                message_severity: create_info.message_severity as _,
+                message_type: create_info.message_type as _,
+                user_callback: ffi::debug_utils_messenger_callback,
+                user_data: g,
+            };
+
+            Some(create_info)
+        } else {
+            None
+        };
+
+        let p_next = if let Some(create_info) = &debug_utils {
+            unsafe { mem::transmute::<_, _>(create_info) }
+        } else {
+            ptr::null()
+        };
+
+        let enabled_validation_features = create_info
+            .validation_features
+            .iter()
+            .map(|&feature| feature.into())
+            .collect::<Vec<ffi::ValidationFeatureEnable>>();
+
+        let validation_features = ffi::ValidationFeatures {
+            structure_type: ffi::StructureType::ValidationFeatures,
+            p_next,
+            enabled_validation_feature_count: enabled_validation_features.len() as _,
+            enabled_validation_features: enabled_validation_features.as_ptr(),
+            disabled_validation_feature_count: 0,
+            disabled_validation_features: ptr::null(),
+        };
+
+        let p_next = if enabled_validation_features.len() > 0 {
+            &validation_features as *const _ as _
+        } else {
+            p_next
+        };
+
+        let extensions = create_info
+            .extensions
+            .iter()
+            .map(|extension| extension.to_string())
+            .collect::<Vec<_>>();
+
+        let create_info = ffi::InstanceCreateInfo {
+            structure_type: ffi::StructureType::InstanceCreateInfo,
+            p_next,
+            flags: create_info.flags,
+            application_info: &application_info,
+            enabled_layer_count: create_info.layers.len() as _,
+            enabled_layer_names: enabled_layer_names_ptr,
+            enabled_extension_count: create_info.extensions.len() as _,
+            enabled_extension_names: enabled_extension_names_ptr,
+        };
+
+        let mut handle = MaybeUninit::<ffi::Instance>::uninit();
+
+        let result =
+            unsafe { ffi::vkCreateInstance(&create_info, ptr::null(), handle.as_mut_ptr()) };
+
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                let instance = Self {
+                    handle,
+                    api_version,
+                    extensions,
+                };
+
+                let instance = Rc::new(instance);
+
+                Ok(instance)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            ffi::Result::InitializationFailed => Err(Error::InitializationFailed),
+            ffi::Result::LayerNotPresent => Err(Error::LayerNotPresent),
+            ffi::Result::ExtensionNotPresent => Err(Error::ExtensionNotPresent),
+            ffi::Result::IncompatibleDriver => Err(Error::IncompatibleDriver),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+}
+
+impl Drop for Instance {
+    fn drop(&mut self) {
+        unsafe { ffi::vkDestroyInstance(self.handle, ptr::null()) };
+    }
+}
+
+pub struct DebugUtilsMessenger {
+    instance: Rc<Instance>,
+    handle: ffi::DebugUtilsMessenger,
+}
+
+impl DebugUtilsMessenger {
+    pub fn new(
+        instance: Rc<Instance>,
+        create_info: DebugUtilsMessengerCreateInfo,
+    ) -> Result<Self, Error> {
+        let f_name = CStr::from_bytes_with_nul(b"vkCreateDebugUtilsMessengerEXT\0").unwrap();
+
+        let f = unsafe { ffi::vkGetInstanceProcAddr(instance.handle, f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f = unsafe { mem::transmute::<_, ffi::CreateDebugUtilsMessenger>(f) };
+
+        let g = unsafe { mem::transmute(create_info.user_callback) };
+
+        let create_info = ffi::DebugUtilsMessengerCreateInfo {
+            structure_type: ffi::StructureType::DebugUtilsMessengerCreateInfo,
+            p_next: ptr::null(),
+            flags: 0,
+            message_severity: create_info.message_severity as _,
+            message_type: create_info.message_type as _,
+            user_callback: ffi::debug_utils_messenger_callback,
+            user_data: g,
+        };
+
+        let mut handle = MaybeUninit::<ffi::DebugUtilsMessenger>::uninit();
+
+        let result = unsafe {
+            f(
+                instance.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                let debug_utils_messenger = Self { instance, handle };
+
+                Ok(debug_utils_messenger)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+}
+
+impl Drop for DebugUtilsMessenger {
+    fn drop(&mut self) {
+        let f_name = CStr::from_bytes_with_nul(b"vkDestroyDebugUtilsMessengerEXT\0").unwrap();
+
+        let f = unsafe { ffi::vkGetInstanceProcAddr(self.instance.handle, f_name.as_ptr()) };
+
+        //cannot be missing once the messenger was created
+        if f == ptr::null() {
+            debug_assert!(false, "extension not present, but handle already created");
+            return;
+        }
+
+        let f = unsafe { mem::transmute::<_, ffi::DestroyDebugUtilsMessenger>(f) };
+
+        unsafe { f(self.instance.handle, self.handle, ptr::null()) };
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum PhysicalDeviceType {
+    Other,
+    Integrated,
+    Discrete,
+    Virtual,
+    Cpu,
+}
+
+pub struct PhysicalDeviceLimits {
+    pub max_image_dimension_2d: u32,
+    pub max_push_constants_size: u32,
+    pub min_uniform_buffer_offset_alignment: u64,
+    pub max_storage_buffer_range: u32,
+    pub line_width_range: [f32; 2],
+    pub line_width_granularity: f32,
+    pub max_vertex_input_attributes: u32,
+    pub max_vertex_input_bindings: u32,
+    pub max_vertex_input_attribute_offset: u32,
+    pub max_vertex_input_binding_stride: u32,
+    pub max_sampler_anisotropy: f32,
+    //SAMPLE_COUNT_ flags of the sample counts attachments may have
+    pub framebuffer_color_sample_counts: u32,
+    pub framebuffer_depth_sample_counts: u32,
+    //nanoseconds per timestamp query tick
+    pub timestamp_period: f32,
+}
+
+//Limits that shader binding tables are laid out by, all sizes in bytes
+#[derive(Clone, Copy, Debug)]
+pub struct RayTracingPipelineProperties {
+    pub shader_group_handle_size: u32,
+    pub max_ray_recursion_depth: u32,
+    pub max_shader_group_stride: u32,
+    //each table region starts at a multiple of this
+    pub shader_group_base_alignment: u32,
+    pub shader_group_handle_capture_replay_size: u32,
+    pub max_ray_dispatch_invocation_count: u32,
+    //records within a region are strided by a multiple of this
+    pub shader_group_handle_alignment: u32,
+    pub max_ray_hit_attribute_size: u32,
+}
+
+//A region of a shader binding table as trace_rays takes it. Unused regions are all zero.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct StridedDeviceAddressRegion {
+    pub device_address: u64,
+    pub stride: u64,
+    pub size: u64,
+}
+
+//TODO add more info
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FormatProperties {
+    pub linear_tiling_features: u32,
+    pub optimal_tiling_features: u32,
+    pub buffer_features: u32,
+}
+
+impl FormatProperties {
+    //Whether images of the tiling support every FORMAT_FEATURE_* in features, drm format
+    //modifier tiling reports its features per modifier so never matches here
+    pub fn supports(&self, tiling: ImageTiling, features: u32) -> bool {
+        let supported = match tiling {
+            ImageTiling::Optimal => self.optimal_tiling_features,
+            ImageTiling::Linear => self.linear_tiling_features,
+            ImageTiling::DrmFormatModifier => return false,
+        };
+
+        supported & features == features
+    }
+}
+
+pub struct PhysicalDeviceProperties {
+    pub device_type: PhysicalDeviceType,
+    pub device_name: String,
+    pub limits: PhysicalDeviceLimits,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct PhysicalDeviceFeatures {
+    pub robust_buffer_access: bool,
+    pub full_draw_index_uint_32: bool,
+    pub image_cube_array: bool,
+    pub independent_blend: bool,
+    pub geometry_shader: bool,
+    pub tessellation_shader: bool,
+    pub sample_rate_shading: bool,
+    pub dual_src_blend: bool,
+    pub logic_op: bool,
+    pub multi_draw_indirect: bool,
+    pub draw_indirect_first_instance: bool,
+    pub depth_clamp: bool,
+    pub depth_bias_clamp: bool,
+    pub fill_mode_non_solid: bool,
+    pub depth_bounds: bool,
+    pub wide_lines: bool,
+    pub large_points: bool,
+    pub alpha_to_one: bool,
+    pub multi_viewport: bool,
+    pub sampler_anisotropy: bool,
+    pub texture_compression_etc_2: bool,
+    pub texture_compression_astc_ldr: bool,
+    pub texture_compression_bc: bool,
+    pub occlusion_query_precise: bool,
+    pub pipeline_statistics_query: bool,
+    pub vertex_pipeline_stores_and_atomics: bool,
+    pub fragment_stores_and_atomics: bool,
+    pub shader_tessellation_and_geometry_point_size: bool,
+    pub shader_image_gather_extended: bool,
+    pub shader_storage_image_extended_formats: bool,
+    pub shader_storage_image_multisample: bool,
+    pub shader_storage_image_read_without_format: bool,
+    pub shader_storage_image_write_without_format: bool,
+    pub shader_uniform_buffer_array_dynamic_indexing: bool,
+    pub shader_sampled_image_array_dynamic_indexing: bool,
+    pub shader_storage_buffer_array_dynamic_indexing: bool,
+    pub shader_storage_image_array_dynamic_indexing: bool,
+    pub shader_clip_distance: bool,
+    pub shader_cull_distance: bool,
+    pub shader_float_64: bool,
+    pub shader_int_64: bool,
+    pub shader_int_16: bool,
+    pub shader_resource_residency: bool,
+    pub shader_resource_min_lod: bool,
+    pub sparse_binding: bool,
+    pub sparse_residency_buffer: bool,
+    pub sparse_residency_image_2_d: bool,
+    pub sparse_residency_image_3_d: bool,
+    pub sparse_residency_2_samples: bool,
+    pub sparse_residency_4_samples: bool,
+    pub sparse_residency_8_samples: bool,
+    pub sparse_residency_16_samples: bool,
+    pub sparse_residency_aliased: bool,
+    pub variable_multisample_rate: bool,
+    pub inherited_queries: bool,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct LineRasterizationFeatures {
+    pub rectangular_lines: bool,
+    pub bresenham_lines: bool,
+    pub smooth_lines: bool,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct ShaderAtomicFloatFeatures {
+    pub shader_buffer_float_32_atomics: bool,
+    pub shader_buffer_float_32_atomic_add: bool,
+    pub shader_image_float_32_atomics: bool,
+    pub shader_image_float_32_atomic_add: bool,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct DescriptorIndexingFeatures {
+    pub shader_sampled_image_array_non_uniform_indexing: bool,
+    pub descriptor_binding_sampled_image_update_after_bind: bool,
+    pub descriptor_binding_update_unused_while_pending: bool,
+    pub descriptor_binding_partially_bound: bool,
+    pub descriptor_binding_variable_descriptor_count: bool,
+    pub runtime_descriptor_array: bool,
+}
+
+impl DescriptorIndexingFeatures {
+    pub fn any(&self) -> bool {
+        self.shader_sampled_image_array_non_uniform_indexing
+            || self.descriptor_binding_sampled_image_update_after_bind
+            || self.descriptor_binding_update_unused_while_pending
+            || self.descriptor_binding_partially_bound
+            || self.descriptor_binding_variable_descriptor_count
+            || self.runtime_descriptor_array
+    }
+}
+
+impl ShaderAtomicFloatFeatures {
+    pub fn any(&self) -> bool {
+        self.shader_buffer_float_32_atomics
+            || self.shader_buffer_float_32_atomic_add
+            || self.shader_image_float_32_atomics
+            || self.shader_image_float_32_atomic_add
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PerformanceCounterUnit {
+    Generic,
+    Percentage,
+    Nanoseconds,
+    Bytes,
+    BytesPerSecond,
+    Kelvin,
+    Watts,
+    Volts,
+    Amps,
+    Hertz,
+    Cycles,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PerformanceCounterScope {
+    CommandBuffer,
+    RenderPass,
+    Command,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PerformanceCounterStorage {
+    Int32,
+    Int64,
+    Uint32,
+    Uint64,
+    Float32,
+    Float64,
+}
+
+#[derive(Clone, Debug)]
+pub struct PerformanceCounter {
+    pub unit: PerformanceCounterUnit,
+    pub scope: PerformanceCounterScope,
+    pub storage: PerformanceCounterStorage,
+    pub uuid: [u8; 16],
+    pub name: String,
+    pub category: String,
+    pub description: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PerformanceCounterValue {
+    Int32(i32),
+    Int64(i64),
+    Uint32(u32),
+    Uint64(u64),
+    Float32(f32),
+    Float64(f64),
+}
+
+impl PerformanceCounter {
+    fn from_ffi(
+        counter: &ffi::PerformanceCounter,
+        description: &ffi::PerformanceCounterDescription,
+    ) -> Self {
+        use PerformanceCounterUnit::*;
+
+        let unit = match counter.unit {
+            1 => Percentage,
+            2 => Nanoseconds,
+            3 => Bytes,
+            4 => BytesPerSecond,
+            5 => Kelvin,
+            6 => Watts,
+            7 => Volts,
+            8 => Amps,
+            9 => Hertz,
+            10 => Cycles,
+            _ => Generic,
+        };
+
+        let scope = match counter.scope {
+            0 => PerformanceCounterScope::CommandBuffer,
+            1 => PerformanceCounterScope::RenderPass,
+            _ => PerformanceCounterScope::Command,
+        };
+
+        let storage = match counter.storage {
+            0 => PerformanceCounterStorage::Int32,
+            1 => PerformanceCounterStorage::Int64,
+            2 => PerformanceCounterStorage::Uint32,
+            3 => PerformanceCounterStorage::Uint64,
+            4 => PerformanceCounterStorage::Float32,
+            _ => PerformanceCounterStorage::Float64,
+        };
+
+        let string = |chars: &[i8; 256]| {
+            unsafe { CStr::from_ptr(chars.as_ptr()) }
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        Self {
+            unit,
+            scope,
+            storage,
+            uuid: counter.uuid,
+            name: string(&description.name),
+            category: string(&description.category),
+            description: string(&description.description),
+        }
+    }
+}
+
+impl PerformanceCounterValue {
+    //Results are a union of 8 bytes, narrower values occupy the first bytes
+    fn from_raw(storage: PerformanceCounterStorage, raw: [u8; 8]) -> Self {
+        let low = [raw[0], raw[1], raw[2], raw[3]];
+
+        match storage {
+            PerformanceCounterStorage::Int32 => Self::Int32(i32::from_ne_bytes(low)),
+            PerformanceCounterStorage::Int64 => Self::Int64(i64::from_ne_bytes(raw)),
+            PerformanceCounterStorage::Uint32 => Self::Uint32(u32::from_ne_bytes(low)),
+            PerformanceCounterStorage::Uint64 => Self::Uint64(u64::from_ne_bytes(raw)),
+            PerformanceCounterStorage::Float32 => Self::Float32(f32::from_ne_bytes(low)),
+            PerformanceCounterStorage::Float64 => Self::Float64(f64::from_ne_bytes(raw)),
+        }
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Self::Int32(x) => x as _,
+            Self::Int64(x) => x as _,
+            Self::Uint32(x) => x as _,
+            Self::Uint64(x) => x as _,
+            Self::Float32(x) => x as _,
+            Self::Float64(x) => x,
+        }
+    }
+}
+
+pub struct PhysicalDevice {
+    instance: Rc<Instance>,
+    handle: ffi::PhysicalDevice,
+}
+
+impl PhysicalDevice {
+    pub fn enumerate(instance: Rc<Instance>) -> Vec<Rc<Self>> {
+        let mut handle_count: u32 = 0;
+
+        unsafe {
+            ffi::vkEnumeratePhysicalDevices(instance.handle, &mut handle_count, ptr::null_mut())
+        };
+
+        let mut handles = Vec::<ffi::PhysicalDevice>::with_capacity(handle_count as _);
+
+        unsafe {
+            ffi::vkEnumeratePhysicalDevices(
+                instance.handle,
+                &mut handle_count,
+                handles.as_mut_ptr(),
+            )
+        };
+
+        unsafe { handles.set_len(handle_count as _) };
+
+        let physical_devices = handles
+            .into_iter()
+            .map(|handle| Self {
+                instance: instance.clone(),
+                handle,
+            })
+            .map(|physical_device| Rc::new(physical_device))
+            .collect::<Vec<_>>();
+
+        physical_devices
+    }
+
+    pub fn properties(&self) -> PhysicalDeviceProperties {
+        let mut properties = MaybeUninit::<ffi::PhysicalDeviceProperties>::uninit();
+
+        unsafe { ffi::vkGetPhysicalDeviceProperties(self.handle, properties.as_mut_ptr()) };
+
+        let properties = unsafe { properties.assume_init() };
+
+        let device_type = match properties.device_type {
+            ffi::PhysicalDeviceType::Other => PhysicalDeviceType::Other,
+            ffi::PhysicalDeviceType::Integrated => PhysicalDeviceType::Integrated,
+            ffi::PhysicalDeviceType::Discrete => PhysicalDeviceType::Discrete,
+            ffi::PhysicalDeviceType::Virtual => PhysicalDeviceType::Virtual,
+            ffi::PhysicalDeviceType::Cpu => PhysicalDeviceType::Cpu,
+        };
+
+        let device_name = unsafe { CStr::from_ptr(properties.device_name.as_ptr()) }
+            .to_str()
+            .unwrap()
+            .to_owned();
+
+        let limits = PhysicalDeviceLimits {
+            max_image_dimension_2d: properties.limits.max_image_dimension_2d,
+            max_push_constants_size: properties.limits.max_push_constants_size,
+            min_uniform_buffer_offset_alignment: properties
+                .limits
+                .min_uniform_buffer_offset_alignment,
+            max_storage_buffer_range: properties.limits.max_storage_buffer_range,
+            line_width_range: properties.limits.line_width_range,
+            line_width_granularity: properties.limits.line_width_granularity,
+            max_vertex_input_attributes: properties.limits.max_vertex_input_attributes,
+            max_vertex_input_bindings: properties.limits.max_vertex_input_bindings,
+            max_vertex_input_attribute_offset: properties.limits.max_vertex_input_attribute_offset,
+            max_vertex_input_binding_stride: properties.limits.max_vertex_input_binding_stride,
+            max_sampler_anisotropy: properties.limits.max_sampler_anisotropy,
+            framebuffer_color_sample_counts: properties.limits.framebuffer_color_sample_counts,
+            framebuffer_depth_sample_counts: properties.limits.framebuffer_depth_sample_counts,
+            timestamp_period: properties.limits.timestamp_period,
+        };
+
+        PhysicalDeviceProperties {
+            device_type,
+            device_name,
+            limits,
+        }
+    }
+
+    pub fn format_properties(&self, format: Format) -> FormatProperties {
+        let mut format_properties = MaybeUninit::<ffi::FormatProperties>::uninit();
+
+        unsafe {
+            ffi::vkGetPhysicalDeviceFormatProperties(
+                self.handle,
+                format.into(),
+                format_properties.as_mut_ptr(),
+            )
+        };
+
+        let format_properties = unsafe { format_properties.assume_init() };
+
+        FormatProperties {
+            linear_tiling_features: format_properties.linear_tiling_features,
+            optimal_tiling_features: format_properties.optimal_tiling_features,
+            buffer_features: format_properties.buffer_features,
+        }
+    }
+
+    //Memory planes of format laid out with modifier, None when the format does not support
+    //the modifier or the instance is older than 1.1
+    pub fn drm_format_modifier_plane_count(&self, format: Format, modifier: u64) -> Option<u32> {
+        if self.instance.api_version() < (1, 1, 0).into() {
+            return None;
+        }
+
+        let mut modifier_properties_list = ffi::DrmFormatModifierPropertiesList {
+            structure_type: ffi::StructureType::DrmFormatModifierPropertiesList,
+            p_next: ptr::null_mut(),
+            drm_format_modifier_count: 0,
+            drm_format_modifier_properties: ptr::null_mut(),
+        };
+
+        let mut format_properties = MaybeUninit::<ffi::FormatProperties2>::uninit();
+
+        unsafe {
+            let format_properties = format_properties.as_mut_ptr();
+
+            (*format_properties).structure_type = ffi::StructureType::FormatProperties2;
+            (*format_properties).p_next = &mut modifier_properties_list as *mut _ as _;
+
+            ffi::vkGetPhysicalDeviceFormatProperties2(self.handle, format.into(), format_properties)
+        };
+
+        let mut modifier_properties = Vec::<ffi::DrmFormatModifierProperties>::with_capacity(
+            modifier_properties_list.drm_format_modifier_count as _,
+        );
+
+        modifier_properties_list.drm_format_modifier_properties = modifier_properties.as_mut_ptr();
+
+        unsafe {
+            let format_properties = format_properties.as_mut_ptr();
+
+            (*format_properties).structure_type = ffi::StructureType::FormatProperties2;
+            (*format_properties).p_next = &mut modifier_properties_list as *mut _ as _;
+
+            ffi::vkGetPhysicalDeviceFormatProperties2(self.handle, format.into(), format_properties)
+        };
+
+        unsafe {
+            modifier_properties.set_len(modifier_properties_list.drm_format_modifier_count as _)
+        };
+
+        modifier_properties
+            .iter()
+            .find(|properties| properties.drm_format_modifier == modifier)
+            .map(|properties| properties.drm_format_modifier_plane_count)
+    }
+
+    //The first of candidates, in order of preference, supporting features with tiling
+    pub fn find_supported_format(
+        &self,
+        candidates: &[Format],
+        tiling: ImageTiling,
+        features: u32,
+    ) -> Option<Format> {
+        candidates
+            .iter()
+            .copied()
+            .find(|format| self.format_properties(*format).supports(tiling, features))
+    }
+
+    pub fn features(&self) -> PhysicalDeviceFeatures {
+        let mut features = MaybeUninit::<ffi::PhysicalDeviceFeatures>::uninit();
+
+        unsafe { ffi::vkGetPhysicalDeviceFeatures(self.handle, features.as_mut_ptr()) };
+
+        let features = unsafe { features.assume_init() };
+
+        features.into()
+    }
+
+    //Whether Device::new can enable every feature set in features
+    pub fn supports(&self, features: &PhysicalDeviceFeatures) -> bool {
+        self.features().contains(features)
+    }
+
+    pub fn extensions(&self) -> Vec<String> {
+        let mut property_count: u32 = 0;
+
+        unsafe {
+            ffi::vkEnumerateDeviceExtensionProperties(
+                self.handle,
+                ptr::null(),
+                &mut property_count,
+                ptr::null_mut(),
+            )
+        };
+
+        let mut properties = Vec::<ffi::ExtensionProperties>::with_capacity(property_count as _);
+
+        unsafe {
+            ffi::vkEnumerateDeviceExtensionProperties(
+                self.handle,
+                ptr::null(),
+                &mut property_count,
+                properties.as_mut_ptr(),
+            )
+        };
+
+        unsafe { properties.set_len(property_count as _) };
+
+        let extensions = properties
+            .into_iter()
+            .map(|properties| {
+                unsafe { CStr::from_ptr(properties.extension_name.as_ptr()) }
+                    .to_str()
+                    .unwrap()
+                    .to_owned()
+            })
+            .collect::<Vec<_>>();
+
+        extensions
+    }
+
+    //Fills in features, a zeroed feature struct chained behind PhysicalDeviceFeatures2. Left
+    //zeroed, so unsupported, when the instance is 1.0 without KHR_get_physical_device_properties2.
+    fn features2<T>(&self, features: &mut T) {
+        //the extension's entry point on 1.0 instances, the core one otherwise
+        let f = if self.instance.api_version() >= (1, 1, 0).into() {
+            None
+        } else if self
+            .instance
+            .is_extension_enabled(KHR_GET_PHYSICAL_DEVICE_PROPERTIES_2)
+        {
+            let f_name = CStr::from_bytes_with_nul(b"vkGetPhysicalDeviceFeatures2KHR\0").unwrap();
+
+            let f = unsafe { ffi::vkGetInstanceProcAddr(self.instance.handle, f_name.as_ptr()) };
+
+            if f == ptr::null() {
+                return;
+            }
+
+            Some(unsafe { mem::transmute::<_, ffi::GetPhysicalDeviceFeatures2>(f) })
+        } else {
+            return;
+        };
+
+        let mut features2 = MaybeUninit::<ffi::PhysicalDeviceFeatures2>::uninit();
+
+        unsafe {
+            let features2 = features2.as_mut_ptr();
+
+            (*features2).structure_type = ffi::StructureType::PhysicalDeviceFeatures2;
+            (*features2).p_next = features as *mut T as _;
+
+            match f {
+                Some(f) => f(self.handle, features2),
+                None => ffi::vkGetPhysicalDeviceFeatures2(self.handle, features2),
+            }
+        };
+    }
+
+    //Protected memory has no extension, protected_queue and protected submits are Vulkan 1.1
+    pub fn protected_memory_supported(&self) -> bool {
+        if self.instance.api_version() < (1, 1, 0).into() {
+            return false;
+        }
+
+        let mut protected_memory_features = ffi::PhysicalDeviceProtectedMemoryFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceProtectedMemoryFeatures,
+            p_next: ptr::null_mut(),
+            protected_memory: 0,
+        };
+
+        self.features2(&mut protected_memory_features);
+
+        protected_memory_features.protected_memory != 0
+    }
+
+    pub fn vertex_attribute_divisor_supported(&self) -> bool {
+        if !self.extensions().iter().any(|s| s == EXT_VERTEX_ATTRIBUTE_DIVISOR) {
+            return false;
+        }
+
+        let mut vertex_attribute_divisor_features = ffi::PhysicalDeviceVertexAttributeDivisorFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceVertexAttributeDivisorFeatures,
+            p_next: ptr::null_mut(),
+            vertex_attribute_instance_rate_divisor: 0,
+            vertex_attribute_instance_rate_zero_divisor: 0,
+        };
+
+        self.features2(&mut vertex_attribute_divisor_features);
+
+        vertex_attribute_divisor_features.vertex_attribute_instance_rate_divisor != 0
+    }
+
+    pub fn line_rasterization_features(&self) -> LineRasterizationFeatures {
+        if !self.extensions().iter().any(|s| s == EXT_LINE_RASTERIZATION) {
+            return LineRasterizationFeatures::default();
+        }
+
+        let mut line_rasterization_features = ffi::PhysicalDeviceLineRasterizationFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceLineRasterizationFeatures,
+            p_next: ptr::null_mut(),
+            rectangular_lines: 0,
+            bresenham_lines: 0,
+            smooth_lines: 0,
+            stippled_rectangular_lines: 0,
+            stippled_bresenham_lines: 0,
+            stippled_smooth_lines: 0,
+        };
+
+        self.features2(&mut line_rasterization_features);
+
+        LineRasterizationFeatures {
+            rectangular_lines: line_rasterization_features.rectangular_lines != 0,
+            bresenham_lines: line_rasterization_features.bresenham_lines != 0,
+            smooth_lines: line_rasterization_features.smooth_lines != 0,
+        }
+    }
+
+    pub fn depth_clip_enable_supported(&self) -> bool {
+        if !self.extensions().iter().any(|s| s == EXT_DEPTH_CLIP_ENABLE) {
+            return false;
+        }
+
+        let mut depth_clip_enable_features = ffi::PhysicalDeviceDepthClipEnableFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceDepthClipEnableFeatures,
+            p_next: ptr::null_mut(),
+            depth_clip_enable: 0,
+        };
+
+        self.features2(&mut depth_clip_enable_features);
+
+        depth_clip_enable_features.depth_clip_enable != 0
+    }
+
+    pub fn fragment_shader_interlock_supported(&self) -> bool {
+        if !self
+            .extensions()
+            .iter()
+            .any(|s| s == EXT_FRAGMENT_SHADER_INTERLOCK)
+        {
+            return false;
+        }
+
+        let mut fragment_shader_interlock_features =
+            ffi::PhysicalDeviceFragmentShaderInterlockFeatures {
+                structure_type: ffi::StructureType::PhysicalDeviceFragmentShaderInterlockFeatures,
+                p_next: ptr::null_mut(),
+                fragment_shader_sample_interlock: 0,
+                fragment_shader_pixel_interlock: 0,
+                fragment_shader_shading_rate_interlock: 0,
+            };
+
+        self.features2(&mut fragment_shader_interlock_features);
+
+        fragment_shader_interlock_features.fragment_shader_pixel_interlock != 0
+    }
+
+    pub fn descriptor_indexing_features(&self) -> DescriptorIndexingFeatures {
+        if !self
+            .extensions()
+            .iter()
+            .any(|s| s == EXT_DESCRIPTOR_INDEXING)
+        {
+            return DescriptorIndexingFeatures::default();
+        }
+
+        let mut descriptor_indexing_features = ffi::PhysicalDeviceDescriptorIndexingFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceDescriptorIndexingFeatures,
+            p_next: ptr::null_mut(),
+            shader_input_attachment_array_dynamic_indexing: 0,
+            shader_uniform_texel_buffer_array_dynamic_indexing: 0,
+            shader_storage_texel_buffer_array_dynamic_indexing: 0,
+            shader_uniform_buffer_array_non_uniform_indexing: 0,
+            shader_sampled_image_array_non_uniform_indexing: 0,
+            shader_storage_buffer_array_non_uniform_indexing: 0,
+            shader_storage_image_array_non_uniform_indexing: 0,
+            shader_input_attachment_array_non_uniform_indexing: 0,
+            shader_uniform_texel_buffer_array_non_uniform_indexing: 0,
+            shader_storage_texel_buffer_array_non_uniform_indexing: 0,
+            descriptor_binding_uniform_buffer_update_after_bind: 0,
+            descriptor_binding_sampled_image_update_after_bind: 0,
+            descriptor_binding_storage_image_update_after_bind: 0,
+            descriptor_binding_storage_buffer_update_after_bind: 0,
+            descriptor_binding_uniform_texel_buffer_update_after_bind: 0,
+            descriptor_binding_storage_texel_buffer_update_after_bind: 0,
+            descriptor_binding_update_unused_while_pending: 0,
+            descriptor_binding_partially_bound: 0,
+            descriptor_binding_variable_descriptor_count: 0,
+            runtime_descriptor_array: 0,
+        };
+
+        self.features2(&mut descriptor_indexing_features);
+
+        DescriptorIndexingFeatures {
+            shader_sampled_image_array_non_uniform_indexing: descriptor_indexing_features.shader_sampled_image_array_non_uniform_indexing != 0,
+            descriptor_binding_sampled_image_update_after_bind: descriptor_indexing_features.descriptor_binding_sampled_image_update_after_bind != 0,
+            descriptor_binding_update_unused_while_pending: descriptor_indexing_features.descriptor_binding_update_unused_while_pending != 0,
+            descriptor_binding_partially_bound: descriptor_indexing_features.descriptor_binding_partially_bound != 0,
+            descriptor_binding_variable_descriptor_count: descriptor_indexing_features.descriptor_binding_variable_descriptor_count != 0,
+            runtime_descriptor_array: descriptor_indexing_features.runtime_descriptor_array != 0,
+        }
+    }
+
+    //None without KHR_RAY_TRACING_PIPELINE
+    pub fn ray_tracing_pipeline_properties(&self) -> Option<RayTracingPipelineProperties> {
+        if !self
+            .extensions()
+            .iter()
+            .any(|s| s == KHR_RAY_TRACING_PIPELINE)
+        {
+            return None;
+        }
+
+        let mut ray_tracing_properties = ffi::PhysicalDeviceRayTracingPipelineProperties {
+            structure_type: ffi::StructureType::PhysicalDeviceRayTracingPipelineProperties,
+            p_next: ptr::null_mut(),
+            shader_group_handle_size: 0,
+            max_ray_recursion_depth: 0,
+            max_shader_group_stride: 0,
+            shader_group_base_alignment: 0,
+            shader_group_handle_capture_replay_size: 0,
+            max_ray_dispatch_invocation_count: 0,
+            shader_group_handle_alignment: 0,
+            max_ray_hit_attribute_size: 0,
+        };
+
+        let mut properties = MaybeUninit::<ffi::PhysicalDeviceProperties2>::uninit();
+
+        unsafe {
+            let properties = properties.as_mut_ptr();
+
+            (*properties).structure_type = ffi::StructureType::PhysicalDeviceProperties2;
+            (*properties).p_next = &mut ray_tracing_properties as *mut _ as _;
+
+            ffi::vkGetPhysicalDeviceProperties2(self.handle, properties)
+        };
+
+        Some(RayTracingPipelineProperties {
+            shader_group_handle_size: ray_tracing_properties.shader_group_handle_size,
+            max_ray_recursion_depth: ray_tracing_properties.max_ray_recursion_depth,
+            max_shader_group_stride: ray_tracing_properties.max_shader_group_stride,
+            shader_group_base_alignment: ray_tracing_properties.shader_group_base_alignment,
+            shader_group_handle_capture_replay_size: ray_tracing_properties
+                .shader_group_handle_capture_replay_size,
+            max_ray_dispatch_invocation_count: ray_tracing_properties
+                .max_ray_dispatch_invocation_count,
+            shader_group_handle_alignment: ray_tracing_properties.shader_group_handle_alignment,
+            max_ray_hit_attribute_size: ray_tracing_properties.max_ray_hit_attribute_size,
+        })
+    }
+
+    pub fn performance_query_supported(&self) -> bool {
+        if !self
+            .extensions()
+            .iter()
+            .any(|s| s == KHR_PERFORMANCE_QUERY)
+        {
+            return false;
+        }
+
+        let mut performance_query_features = ffi::PhysicalDevicePerformanceQueryFeatures {
+            structure_type: ffi::StructureType::PhysicalDevicePerformanceQueryFeatures,
+            p_next: ptr::null_mut(),
+            performance_counter_query_pools: 0,
+            performance_counter_multiple_query_pools: 0,
+        };
+
+        self.features2(&mut performance_query_features);
+
+        performance_query_features.performance_counter_query_pools != 0
+    }
+
+    pub fn memory_priority_supported(&self) -> bool {
+        if !self.extensions().iter().any(|s| s == EXT_MEMORY_PRIORITY) {
+            return false;
+        }
+
+        let mut memory_priority_features = ffi::PhysicalDeviceMemoryPriorityFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceMemoryPriorityFeatures,
+            p_next: ptr::null_mut(),
+            memory_priority: 0,
+        };
+
+        self.features2(&mut memory_priority_features);
+
+        memory_priority_features.memory_priority != 0
+    }
+
+    pub fn pipeline_executable_info_supported(&self) -> bool {
+        if !self
+            .extensions()
+            .iter()
+            .any(|s| s == KHR_PIPELINE_EXECUTABLE_PROPERTIES)
+        {
+            return false;
+        }
+
+        let mut pipeline_executable_properties_features =
+            ffi::PhysicalDevicePipelineExecutablePropertiesFeatures {
+                structure_type:
+                    ffi::StructureType::PhysicalDevicePipelineExecutablePropertiesFeatures,
+                p_next: ptr::null_mut(),
+                pipeline_executable_info: 0,
+            };
+
+        self.features2(&mut pipeline_executable_properties_features);
+
+        pipeline_executable_properties_features.pipeline_executable_info != 0
+    }
+
+    pub fn synchronization2_supported(&self) -> bool {
+        if !self
+            .extensions()
+            .iter()
+            .any(|s| s == KHR_SYNCHRONIZATION_2)
+        {
+            return false;
+        }
+
+        let mut synchronization2_features = ffi::PhysicalDeviceSynchronization2Features {
+            structure_type: ffi::StructureType::PhysicalDeviceSynchronization2Features,
+            p_next: ptr::null_mut(),
+            synchronization2: 0,
+        };
+
+        self.features2(&mut synchronization2_features);
+
+        synchronization2_features.synchronization2 != 0
+    }
+
+    //Present fences, also needs EXT_SURFACE_MAINTENANCE_1 enabled on the instance
+    pub fn swapchain_maintenance1_supported(&self) -> bool {
+        if !self
+            .extensions()
+            .iter()
+            .any(|s| s == EXT_SWAPCHAIN_MAINTENANCE_1)
+        {
+            return false;
+        }
+
+        let mut swapchain_maintenance1_features =
+            ffi::PhysicalDeviceSwapchainMaintenance1Features {
+                structure_type: ffi::StructureType::PhysicalDeviceSwapchainMaintenance1Features,
+                p_next: ptr::null_mut(),
+                swapchain_maintenance1: 0,
+            };
+
+        self.features2(&mut swapchain_maintenance1_features);
+
+        swapchain_maintenance1_features.swapchain_maintenance1 != 0
+    }
+
+    pub fn buffer_device_address_supported(&self) -> bool {
+        if !self
+            .extensions()
+            .iter()
+            .any(|s| s == KHR_BUFFER_DEVICE_ADDRESS)
+        {
+            return false;
+        }
+
+        let mut buffer_device_address_features = ffi::PhysicalDeviceBufferDeviceAddressFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceBufferDeviceAddressFeatures,
+            p_next: ptr::null_mut(),
+            buffer_device_address: 0,
+            buffer_device_address_capture_replay: 0,
+            buffer_device_address_multi_device: 0,
+        };
+
+        self.features2(&mut buffer_device_address_features);
+
+        buffer_device_address_features.buffer_device_address != 0
+    }
+
+    pub fn dynamic_rendering_supported(&self) -> bool {
+        if !self
+            .extensions()
+            .iter()
+            .any(|s| s == KHR_DYNAMIC_RENDERING)
+        {
+            return false;
+        }
+
+        let mut dynamic_rendering_features = ffi::PhysicalDeviceDynamicRenderingFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceDynamicRenderingFeatures,
+            p_next: ptr::null_mut(),
+            dynamic_rendering: 0,
+        };
+
+        self.features2(&mut dynamic_rendering_features);
+
+        dynamic_rendering_features.dynamic_rendering != 0
+    }
+
+    pub fn inherited_viewport_scissor_supported(&self) -> bool {
+        if !self
+            .extensions()
+            .iter()
+            .any(|s| s == NV_INHERITED_VIEWPORT_SCISSOR)
+        {
+            return false;
+        }
+
+        let mut inherited_viewport_scissor_features =
+            ffi::PhysicalDeviceInheritedViewportScissorFeatures {
+                structure_type: ffi::StructureType::PhysicalDeviceInheritedViewportScissorFeatures,
+                p_next: ptr::null_mut(),
+                inherited_viewport_scissor_2d: 0,
+            };
+
+        self.features2(&mut inherited_viewport_scissor_features);
+
+        inherited_viewport_scissor_features.inherited_viewport_scissor_2d != 0
+    }
+
+    //Core since Vulkan 1.1, but still an optional feature
+    pub fn sampler_ycbcr_conversion_supported(&self) -> bool {
+        let mut sampler_ycbcr_conversion_features =
+            ffi::PhysicalDeviceSamplerYcbcrConversionFeatures {
+                structure_type: ffi::StructureType::PhysicalDeviceSamplerYcbcrConversionFeatures,
+                p_next: ptr::null_mut(),
+                sampler_ycbcr_conversion: 0,
+            };
+
+        self.features2(&mut sampler_ycbcr_conversion_features);
+
+        sampler_ycbcr_conversion_features.sampler_ycbcr_conversion != 0
+    }
+
+    //Also requires memory_priority_supported
+    pub fn pageable_device_local_memory_supported(&self) -> bool {
+        if !self
+            .extensions()
+            .iter()
+            .any(|s| s == EXT_PAGEABLE_DEVICE_LOCAL_MEMORY)
+        {
+            return false;
+        }
+
+        let mut pageable_device_local_memory_features =
+            ffi::PhysicalDevicePageableDeviceLocalMemoryFeatures {
+                structure_type: ffi::StructureType::PhysicalDevicePageableDeviceLocalMemoryFeatures,
+                p_next: ptr::null_mut(),
+                pageable_device_local_memory: 0,
+            };
+
+        self.features2(&mut pageable_device_local_memory_features);
+
+        pageable_device_local_memory_features.pageable_device_local_memory != 0
+    }
+
+    //Counters are identified by their index in the returned list
+    pub fn performance_counters(
+        &self,
+        queue_family_index: u32,
+    ) -> Result<Vec<PerformanceCounter>, Error> {
+        let f_name = CStr::from_bytes_with_nul(
+            b"vkEnumeratePhysicalDeviceQueueFamilyPerformanceQueryCountersKHR\0",
+        )
+        .unwrap();
+
+        let f = unsafe { ffi::vkGetInstanceProcAddr(self.instance.handle, f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f = unsafe {
+            mem::transmute::<_, ffi::EnumeratePhysicalDeviceQueueFamilyPerformanceQueryCounters>(f)
+        };
+
+        let mut counter_count: u32 = 0;
+
+        let result = unsafe {
+            f(
+                self.handle,
+                queue_family_index,
+                &mut counter_count,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {}
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+            ffi::Result::InitializationFailed => Err(Error::InitializationFailed)?,
+            _ => Err(unexpected_result(result))?,
+        }
+
+        let mut counters = vec![
+            ffi::PerformanceCounter {
+                structure_type: ffi::StructureType::PerformanceCounter,
+                p_next: ptr::null_mut(),
+                unit: 0,
+                scope: 0,
+                storage: 0,
+                uuid: [0; 16],
+            };
+            counter_count as _
+        ];
+
+        let mut descriptions = vec![
+            ffi::PerformanceCounterDescription {
+                structure_type: ffi::StructureType::PerformanceCounterDescription,
+                p_next: ptr::null_mut(),
+                flags: 0,
+                name: [0; 256],
+                category: [0; 256],
+                description: [0; 256],
+            };
+            counter_count as _
+        ];
+
+        let result = unsafe {
+            f(
+                self.handle,
+                queue_family_index,
+                &mut counter_count,
+                counters.as_mut_ptr(),
+                descriptions.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success | ffi::Result::Incomplete => {}
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+            ffi::Result::InitializationFailed => Err(Error::InitializationFailed)?,
+            _ => Err(unexpected_result(result))?,
+        }
+
+        let counters = counters
+            .iter()
+            .zip(descriptions.iter())
+            .take(counter_count as _)
+            .map(|(counter, description)| PerformanceCounter::from_ffi(counter, description))
+            .collect();
+
+        Ok(counters)
+    }
+
+    //Number of times a command buffer must be submitted to collect all of the counters
+    pub fn performance_query_passes(
+        &self,
+        queue_family_index: u32,
+        counter_indices: &'_ [u32],
+    ) -> Result<u32, Error> {
+        let f_name = CStr::from_bytes_with_nul(
+            b"vkGetPhysicalDeviceQueueFamilyPerformanceQueryPassesKHR\0",
+        )
+        .unwrap();
+
+        let f = unsafe { ffi::vkGetInstanceProcAddr(self.instance.handle, f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f = unsafe {
+            mem::transmute::<_, ffi::GetPhysicalDeviceQueueFamilyPerformanceQueryPasses>(f)
+        };
+
+        let create_info = ffi::QueryPoolPerformanceCreateInfo {
+            structure_type: ffi::StructureType::QueryPoolPerformanceCreateInfo,
+            p_next: ptr::null(),
+            queue_family_index,
+            counter_index_count: counter_indices.len() as _,
+            counter_indices: counter_indices.as_ptr(),
+        };
+
+        let mut passes = 0;
+
+        unsafe { f(self.handle, &create_info, &mut passes) };
+
+        Ok(passes)
+    }
+
+    pub fn shader_atomic_float_features(&self) -> ShaderAtomicFloatFeatures {
+        if !self.extensions().iter().any(|s| s == EXT_SHADER_ATOMIC_FLOAT) {
+            return ShaderAtomicFloatFeatures::default();
+        }
+
+        let mut shader_atomic_float_features = ffi::PhysicalDeviceShaderAtomicFloatFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceShaderAtomicFloatFeatures,
+            p_next: ptr::null_mut(),
+            shader_buffer_float_32_atomics: 0,
+            shader_buffer_float_32_atomic_add: 0,
+            shader_buffer_float_64_atomics: 0,
+            shader_buffer_float_64_atomic_add: 0,
+            shader_shared_float_32_atomics: 0,
+            shader_shared_float_32_atomic_add: 0,
+            shader_shared_float_64_atomics: 0,
+            shader_shared_float_64_atomic_add: 0,
+            shader_image_float_32_atomics: 0,
+            shader_image_float_32_atomic_add: 0,
+            sparse_image_float_32_atomics: 0,
+            sparse_image_float_32_atomic_add: 0,
+        };
+
+        self.features2(&mut shader_atomic_float_features);
+
+        ShaderAtomicFloatFeatures {
+            shader_buffer_float_32_atomics: shader_atomic_float_features
+                .shader_buffer_float_32_atomics
+                != 0,
+            shader_buffer_float_32_atomic_add: shader_atomic_float_features
+                .shader_buffer_float_32_atomic_add
+                != 0,
+            shader_image_float_32_atomics: shader_atomic_float_features
+                .shader_image_float_32_atomics
+                != 0,
+            shader_image_float_32_atomic_add: shader_atomic_float_features
+                .shader_image_float_32_atomic_add
+                != 0,
+        }
+    }
+
+    pub fn queue_families(&self) -> Vec<QueueFamilyProperties> {
+        let mut queue_family_count: u32 = 0;
+
+        unsafe {
+            ffi::vkGetPhysicalDeviceQueueFamilyProperties(
+                self.handle,
+                &mut queue_family_count,
+                ptr::null_mut(),
+            )
+        };
+
+        let mut queue_families =
+            Vec::<ffi::QueueFamilyProperties>::with_capacity(queue_family_count as _);
+
+        unsafe {
+            ffi::vkGetPhysicalDeviceQueueFamilyProperties(
+                self.handle,
+                &mut queue_family_count,
+                queue_families.as_mut_ptr(),
+            )
+        };
+
+        unsafe { queue_families.set_len(queue_family_count as _) };
+
+        let queue_families = queue_families
+            .into_iter()
+            .map(|queue_family| QueueFamilyProperties {
+                queue_flags: queue_family.queue_flags,
+                queue_count: queue_family.queue_count,
+                timestamp_valid_bits: queue_family.timestamp_valid_bits,
+                min_image_transfer_granularity: queue_family.min_image_transfer_granularity,
+            })
+            .collect::<Vec<_>>();
+
+        queue_families
+    }
+
+    //Picks a graphics family, preferring one that can also present to the surface, then
+    //the most specialized compute and transfer families
+    pub fn select_queue_families(
+        &self,
+        surface: Option<&Surface>,
+    ) -> Result<QueueFamilies, Error> {
+        let queue_families = self.queue_families();
+
+        let find = |required: u32, excluded: u32| {
+            queue_families
+                .iter()
+                .position(|family| {
+                    family.queue_count > 0
+                        && family.queue_flags & required == required
+                        && family.queue_flags & excluded == 0
+                })
+                .map(|i| i as u32)
+        };
+
+        let mut graphics = find(QUEUE_GRAPHICS, 0).ok_or(Error::NoSuitableQueueFamily {
+            queue_flags: QUEUE_GRAPHICS,
+        })?;
+
+        let mut present = None;
+
+        if let Some(surface) = surface {
+            for i in 0..queue_families.len() as u32 {
+                if !self.surface_supported(surface, i)? {
+                    continue;
+                }
+
+                let is_graphics = queue_families[i as usize].queue_flags & QUEUE_GRAPHICS != 0;
+
+                if present.is_none() || is_graphics {
+                    present = Some(i);
+                }
+
+                if is_graphics {
+                    graphics = i;
+                    break;
+                }
+            }
+
+            if present.is_none() {
+                Err(Error::NoSuitableQueueFamily { queue_flags: 0 })?;
+            }
+        }
+
+        let compute = find(QUEUE_COMPUTE, QUEUE_GRAPHICS)
+            .or_else(|| find(QUEUE_COMPUTE, 0))
+            .ok_or(Error::NoSuitableQueueFamily {
+                queue_flags: QUEUE_COMPUTE,
+            })?;
+
+        //graphics and compute families support transfers whether or not they report it
+        let transfer = find(QUEUE_TRANSFER, QUEUE_GRAPHICS | QUEUE_COMPUTE)
+            .or_else(|| find(QUEUE_COMPUTE, QUEUE_GRAPHICS))
+            .unwrap_or(graphics);
+
+        Ok(QueueFamilies {
+            graphics,
+            compute,
+            transfer,
+            present,
+        })
+    }
+
+    //Prefers an sRGB format, panics if the surface supports none of the formats here
+    //FormatNotSupported when the surface reports no formats at all
+    pub fn surface_format(&self, surface: &Surface) -> Result<SurfaceFormat, Error> {
+        let surface_formats = self.surface_formats(surface)?;
+
+        [Format::Bgra8Srgb, Format::Rgba8Srgb]
+            .into_iter()
+            .find_map(|format| {
+                surface_formats.iter().copied().find(|surface_format| {
+                    surface_format.format == format
+                        && surface_format.color_space == ColorSpace::SrgbNonlinear
+                })
+            })
+            .or_else(|| surface_formats.first().copied())
+            .ok_or(Error::FormatNotSupported)
+    }
+
+    //Requires EXT_SURFACE_MAINTENANCE_1 on the instance
+    pub fn surface_present_scaling(
+        &self,
+        surface: &Surface,
+        present_mode: PresentMode,
+    ) -> Result<PresentScalingCapabilities, Error> {
+        if !self.instance.is_extension_enabled(EXT_SURFACE_MAINTENANCE_1) {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f_name =
+            CStr::from_bytes_with_nul(b"vkGetPhysicalDeviceSurfaceCapabilities2KHR\0").unwrap();
+
+        let f = unsafe { ffi::vkGetInstanceProcAddr(self.instance.handle, f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f = unsafe { mem::transmute::<_, ffi::GetPhysicalDeviceSurfaceCapabilities2>(f) };
+
+        let mut present_mode = ffi::SurfacePresentMode {
+            structure_type: ffi::StructureType::SurfacePresentMode,
+            p_next: ptr::null_mut(),
+            present_mode: match present_mode {
+                PresentMode::Immediate => ffi::PresentMode::Immediate,
+                PresentMode::Mailbox => ffi::PresentMode::Mailbox,
+                PresentMode::Fifo => ffi::PresentMode::Fifo,
+                PresentMode::FifoRelaxed => ffi::PresentMode::FifoRelaxed,
+            },
+        };
+
+        let surface_info = ffi::PhysicalDeviceSurfaceInfo2 {
+            structure_type: ffi::StructureType::PhysicalDeviceSurfaceInfo2,
+            p_next: &mut present_mode as *mut _ as _,
+            surface: surface.handle,
+        };
+
+        let mut scaling = ffi::SurfacePresentScalingCapabilities {
+            structure_type: ffi::StructureType::SurfacePresentScalingCapabilities,
+            p_next: ptr::null_mut(),
+            supported_present_scaling: 0,
+            supported_present_gravity_x: 0,
+            supported_present_gravity_y: 0,
+            min_scaled_image_extent: [0; 2],
+            max_scaled_image_extent: [0; 2],
+        };
+
+        let mut capabilities = MaybeUninit::<ffi::SurfaceCapabilities2>::uninit();
+
+        let result = unsafe {
+            let capabilities = capabilities.as_mut_ptr();
+
+            (*capabilities).structure_type = ffi::StructureType::SurfaceCapabilities2;
+            (*capabilities).p_next = &mut scaling as *mut _ as _;
+
+            f(self.handle, &surface_info, capabilities)
+        };
+
+        match result {
+            ffi::Result::Success => Ok(PresentScalingCapabilities {
+                supported_scaling: scaling.supported_present_scaling,
+                supported_gravity_x: scaling.supported_present_gravity_x,
+                supported_gravity_y: scaling.supported_present_gravity_y,
+                min_scaled_image_extent: (
+                    scaling.min_scaled_image_extent[0],
+                    scaling.min_scaled_image_extent[1],
+                ),
+                max_scaled_image_extent: (
+                    scaling.max_scaled_image_extent[0],
+                    scaling.max_scaled_image_extent[1],
+                ),
+            }),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            ffi::Result::SurfaceLost => Err(Error::SurfaceLost),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    //TODO
+    pub fn surface_capabilities(&self, surface: &Surface) -> SurfaceCapabilities {
+        let mut surface_capabilities = MaybeUninit::<ffi::SurfaceCapabilities>::uninit();
+
+        unsafe {
+            ffi::vkGetPhysicalDeviceSurfaceCapabilitiesKHR(
+                self.handle,
+                surface.handle,
+                surface_capabilities.as_mut_ptr(),
+            )
+        };
+
+        let surface_capabilities = unsafe { surface_capabilities.assume_init() };
+
+        let current_extent = (
+            surface_capabilities.current_extent[0],
+            surface_capabilities.current_extent[1],
         );
-        pub fn vkAllocateDescriptorSets(
-            device: Device,
-            allocate_info: *const DescriptorSetAllocateInfo,
-            descriptor_sets: *mut DescriptorSet,
-        ) -> Result;
-        pub fn vkCmdBindDescriptorSets(
-            command_buffer: CommandBuffer,
-            bind_point: PipelineBindPoint,
-            layout: PipelineLayout,
-            first_set: u32,
-            descriptor_set_count: u32,
-            descriptor_sets: *const DescriptorSet,
-            dynamic_offset_count: u32,
-            dynamic_offsets: *const u32,
+
+        let min_image_extent = (
+            surface_capabilities.min_image_extent[0],
+            surface_capabilities.min_image_extent[1],
         );
-        pub fn vkCreateImage(
-            device: Device,
-            create_info: *const ImageCreateInfo,
-            allocator: *const (),
-            image: *mut Image,
-        ) -> Result;
-        pub fn vkDestroyImage(device: Device, image: Image, allocator: *const ());
-        pub fn vkGetImageMemoryRequirements(
-            device: Device,
-            image: Image,
-            memory_requirements: *mut MemoryRequirements,
+
+        let max_image_extent = (
+            surface_capabilities.max_image_extent[0],
+            surface_capabilities.max_image_extent[1],
         );
-        pub fn vkBindImageMemory(
-            device: Device,
-            image: Image,
-            memory: DeviceMemory,
-            memory_offset: DeviceSize,
-        ) -> Result;
-        pub fn vkCreateSampler(
-            device: Device,
-            create_info: *const SamplerCreateInfo,
-            allocator: *const (),
-            sampler: *mut Sampler,
-        ) -> Result;
-        pub fn vkDestroySampler(device: Device, sampler: Sampler, allocator: *const ());
+
+        SurfaceCapabilities {
+            min_image_count: surface_capabilities.min_image_count,
+            max_image_count: surface_capabilities.max_image_count,
+            current_extent,
+            min_image_extent,
+            max_image_extent,
+            max_image_array_layers: surface_capabilities.max_image_array_layers,
+            supported_transforms: surface_capabilities.supported_transforms,
+            current_transform: surface_capabilities.current_transform,
+            supported_composite_alpha: surface_capabilities.supported_composite_alpha,
+            supported_usage_flags: surface_capabilities.supported_usage_flags,
+        }
     }
-}
 
-pub const KHR_SURFACE: &str = "VK_KHR_surface";
-pub const KHR_XLIB_SURFACE: &str = "VK_KHR_xlib_surface";
-pub const KHR_WIN32_SURFACE: &str = "VK_KHR_win32_surface";
-pub const KHR_SWAPCHAIN: &str = "VK_KHR_swapchain";
+    pub fn surface_supported(
+        &self,
+        surface: &Surface,
+        queue_family_index: u32,
+    ) -> Result<bool, Error> {
+        let mut supported = MaybeUninit::<ffi::Bool>::uninit();
 
-pub const EXT_DEBUG_REPORT: &str = "VK_EXT_debug_report";
-pub const EXT_DEBUG_UTILS: &str = "VK_EXT_debug_utils";
+        let result = unsafe {
+            ffi::vkGetPhysicalDeviceSurfaceSupportKHR(
+                self.handle,
+                queue_family_index,
+                surface.handle,
+                supported.as_mut_ptr(),
+            )
+        };
 
-pub const LAYER_KHRONOS_VALIDATION: &str = "VK_LAYER_KHRONOS_validation";
-pub const LAYER_LUNARG_STANDARD_VALIDATION: &str = "VK_LAYER_LUNARG_standard_validation";
+        match result {
+            ffi::Result::Success => {
+                let supported = unsafe { supported.assume_init() };
 
-pub const DEBUG_UTILS_MESSAGE_SEVERITY_VERBOSE: u32 = 0x00000001;
-pub const DEBUG_UTILS_MESSAGE_SEVERITY_INFO: u32 = 0x00000010;
-pub const DEBUG_UTILS_MESSAGE_SEVERITY_WARNING: u32 = 0x00000100;
-pub const DEBUG_UTILS_MESSAGE_SEVERITY_ERROR: u32 = 0x00001000;
+                let supported = supported != 0;
 
-pub const DEBUG_UTILS_MESSAGE_TYPE_GENERAL: u32 = 0x00000001;
-pub const DEBUG_UTILS_MESSAGE_TYPE_VALIDATION: u32 = 0x00000002;
-pub const DEBUG_UTILS_MESSAGE_TYPE_PERFORMANCE: u32 = 0x00000004;
+                Ok(supported)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            ffi::Result::SurfaceLost => Err(Error::SurfaceLost),
+            _ => Err(unexpected_result(result)),
+        }
+    }
 
-pub const QUEUE_GRAPHICS: u32 = 0x00000001;
-pub const QUEUE_COMPUTE: u32 = 0x00000002;
+    pub fn memory_properties(&self) -> MemoryProperties {
+        let mut memory_properties = MaybeUninit::<ffi::PhysicalDeviceMemoryProperties>::uninit();
 
-pub const QUEUE_FAMILY_IGNORED: u32 = u32::MAX;
+        unsafe {
+            ffi::vkGetPhysicalDeviceMemoryProperties(self.handle, memory_properties.as_mut_ptr())
+        };
 
-pub const IMAGE_ASPECT_COLOR: u32 = 0x00000001;
-pub const IMAGE_ASPECT_DEPTH: u32 = 0x00000002;
+        let memory_properties = unsafe { memory_properties.assume_init() };
 
-pub const CULL_MODE_NONE: u32 = 0;
-pub const CULL_MODE_FRONT: u32 = 0x00000001;
-pub const CULL_MODE_BACK: u32 = 0x00000002;
-pub const CULL_MODE_FRONT_AND_BACK: u32 = 0x00000003;
+        let memory_types = (0..memory_properties.memory_type_count)
+            .into_iter()
+            .map(|i| memory_properties.memory_types[i as usize])
+            .map(|t| MemoryType {
+                property_flags: t.property_flags,
+                heap_index: t.heap_index,
+            })
+            .collect::<Vec<_>>();
 
-pub const COLOR_COMPONENT_R: u32 = 0x00000001;
-pub const COLOR_COMPONENT_G: u32 = 0x00000002;
-pub const COLOR_COMPONENT_B: u32 = 0x00000004;
-pub const COLOR_COMPONENT_A: u32 = 0x00000008;
+        let memory_heaps = (0..memory_properties.memory_heap_count)
+            .into_iter()
+            .map(|i| memory_properties.memory_heaps[i as usize])
+            .map(|h| MemoryHeap {
+                size: h.size,
+                flags: h.flags,
+            })
+            .collect::<Vec<_>>();
+
+        MemoryProperties {
+            memory_types,
+            memory_heaps,
+        }
+    }
 
-pub const SAMPLE_COUNT_1: u32 = 0x00000001;
+    //Formats and color spaces without a variant here are left out
+    pub fn surface_formats(&self, surface: &Surface) -> Result<Vec<SurfaceFormat>, Error> {
+        let mut surface_format_count: u32 = 0;
 
-pub const SUBPASS_EXTERNAL: u32 = u32::MAX;
+        let result = unsafe {
+            ffi::vkGetPhysicalDeviceSurfaceFormatsKHR(
+                self.handle,
+                surface.handle,
+                &mut surface_format_count,
+                ptr::null_mut(),
+            )
+        };
 
-pub const PIPELINE_STAGE_TOP_OF_PIPE: u32 = 0x00000001;
-pub const PIPELINE_STAGE_FRAGMENT_SHADER: u32 = 0x00000080;
-pub const PIPELINE_STAGE_EARLY_FRAGMENT_TESTS: u32 = 0x00000100;
-pub const PIPELINE_STAGE_LATE_FRAGMENT_TESTS: u32 = 0x00000200;
-pub const PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT: u32 = 0x00000400;
-pub const PIPELINE_STAGE_COMPUTE_SHADER: u32 = 0x00000800;
-pub const PIPELINE_STAGE_TRANSFER: u32 = 0x00001000;
-pub const PIPELINE_STAGE_BOTTOM_OF_PIPE: u32 = 0x00002000;
+        if result != ffi::Result::Success {
+            return Err(Self::surface_query_error(result));
+        }
 
-pub const ACCESS_SHADER_READ: u32 = 0x00000020;
-pub const ACCESS_COLOR_ATTACHMENT_READ: u32 = 0x00000080;
-pub const ACCESS_COLOR_ATTACHMENT_WRITE: u32 = 0x00000100;
-pub const ACCESS_DEPTH_STENCIL_ATTACHMENT_READ: u32 = 0x00000200;
-pub const ACCESS_DEPTH_STENCIL_ATTACHMENT_WRITE: u32 = 0x00000400;
+        let mut surface_formats =
+            Vec::<ffi::SurfaceFormat>::with_capacity(surface_format_count as _);
 
-pub const BUFFER_USAGE_TRANSFER_SRC: u32 = 0x00000001;
-pub const BUFFER_USAGE_TRANSFER_DST: u32 = 0x00000002;
-pub const BUFFER_USAGE_UNIFORM: u32 = 0x00000010;
-pub const BUFFER_USAGE_STORAGE: u32 = 0x00000020;
-pub const BUFFER_USAGE_INDEX: u32 = 0x00000040;
-pub const BUFFER_USAGE_VERTEX: u32 = 0x00000080;
+        let result = unsafe {
+            ffi::vkGetPhysicalDeviceSurfaceFormatsKHR(
+                self.handle,
+                surface.handle,
+                &mut surface_format_count,
+                surface_formats.as_mut_ptr(),
+            )
+        };
 
-pub const IMAGE_USAGE_TRANSFER_SRC: u32 = 0x00000001;
-pub const IMAGE_USAGE_TRANSFER_DST: u32 = 0x00000002;
-pub const IMAGE_USAGE_SAMPLED: u32 = 0x00000004;
-pub const IMAGE_USAGE_STORAGE: u32 = 0x00000008;
-pub const IMAGE_USAGE_COLOR_ATTACHMENT: u32 = 0x00000010;
-pub const IMAGE_USAGE_DEPTH_STENCIL_ATTACHMENT: u32 = 0x00000020;
-pub const IMAGE_USAGE_TRANSIENT_ATTACHMENT: u32 = 0x00000040;
-pub const IMAGE_USAGE_INPUT_ATTACHMENT: u32 = 0x00000080;
+        if result != ffi::Result::Success && result != ffi::Result::Incomplete {
+            return Err(Self::surface_query_error(result));
+        }
 
-pub const MEMORY_PROPERTY_DEVICE_LOCAL: u32 = 0x00000001;
-pub const MEMORY_PROPERTY_HOST_VISIBLE: u32 = 0x00000002;
-pub const MEMORY_PROPERTY_HOST_COHERENT: u32 = 0x00000004;
-pub const MEMORY_PROPERTY_HOST_CACHED: u32 = 0x00000008;
-pub const MEMORY_PROPERTY_LAZILY_ALLOCATED: u32 = 0x00000010;
+        unsafe { surface_formats.set_len(surface_format_count as _) };
 
-pub const SHADER_STAGE_VERTEX: u32 = 0x00000001;
-pub const SHADER_STAGE_FRAGMENT: u32 = 0x00000010;
-pub const SHADER_STAGE_COMPUTE: u32 = 0x00000020;
+        //the formats a swapchain can be created with
+        let formats = [
+            Format::Bgra8Srgb,
+            Format::Rgba8Srgb,
+            Format::Rgba8Unorm,
+            Format::Rgba32Sfloat,
+        ];
 
-pub type DebugUtilsMessengerCallback = fn(&DebugUtilsMessengerCallbackData) -> bool;
+        let surface_formats = surface_formats
+            .into_iter()
+            .filter_map(|surface_format| {
+                let format = formats
+                    .into_iter()
+                    .find(|format| ffi::Format::from(*format) as u32 == surface_format.format)?;
 
-#[derive(Clone, Copy, Debug)]
-pub enum Error {
-    OutOfHostMemory,
-    OutOfDeviceMemory,
-    InitializationFailed,
-    DeviceLost,
-    MemoryMapFailed,
-    LayerNotPresent,
-    ExtensionNotPresent,
-    FeatureNotPresent,
-    IncompatibleDriver,
-    TooManyObjects,
-    FormatNotSupported,
-    FragmentedPool,
-    Unknown,
-    OutOfPoolMemory,
-    Fragmentation,
-    InvalidExternalHandle,
-    SurfaceLost,
-    NativeWindowInUse,
-    Suboptimal,
-    OutOfDate,
-    InvalidShader,
-    FullScreenExclusiveModeLost,
-    InvalidOpaqueCaptureAddress,
-    CompressionExhausted,
-}
+                let color_space = match surface_format.color_space {
+                    0 => ColorSpace::SrgbNonlinear,
+                    _ => None?,
+                };
 
-#[derive(Clone, Copy, Debug)]
-pub enum Format {
-    Rgba8Srgb,
-    Bgra8Srgb,
-    R16Uint,
-    R32Uint,
-    R32Sfloat,
-    Rg32Sfloat,
-    Rgb32Uint,
-    Rgb32Sfloat,
-    Rgba32Sfloat,
-    D32Sfloat,
-}
+                Some(SurfaceFormat {
+                    format,
+                    color_space,
+                })
+            })
+            .collect::<Vec<_>>();
 
-impl Format {
-    pub fn aspect_mask(self) -> u32 {
-        match self {
-            Self::D32Sfloat => IMAGE_ASPECT_DEPTH,
-            _ => IMAGE_ASPECT_COLOR,
-        }
+        Ok(surface_formats)
     }
-}
 
-#[derive(Clone, Copy)]
-pub enum IndexType {
-    Uint16,
-    Uint32,
-}
+    //Fifo is always supported, modes without a variant here are left out
+    pub fn surface_present_modes(&self, surface: &Surface) -> Result<Vec<PresentMode>, Error> {
+        let mut present_mode_count: u32 = 0;
 
-#[derive(Clone, Copy)]
-pub enum ColorSpace {
-    SrgbNonlinear,
-}
+        let result = unsafe {
+            ffi::vkGetPhysicalDeviceSurfacePresentModesKHR(
+                self.handle,
+                surface.handle,
+                &mut present_mode_count,
+                ptr::null_mut(),
+            )
+        };
 
-#[derive(Clone, Copy)]
-pub enum PresentMode {
-    Immediate,
-    Mailbox,
-    Fifo,
-    FifoRelaxed,
-}
+        if result != ffi::Result::Success {
+            return Err(Self::surface_query_error(result));
+        }
 
-pub type Extent2d = (u32, u32);
-pub type Extent3d = (u32, u32, u32);
+        let mut present_modes = Vec::<u32>::with_capacity(present_mode_count as _);
 
-pub type Offset2d = (i32, i32);
-pub type Offset3d = (i32, i32, i32);
+        let result = unsafe {
+            ffi::vkGetPhysicalDeviceSurfacePresentModesKHR(
+                self.handle,
+                surface.handle,
+                &mut present_mode_count,
+                present_modes.as_mut_ptr(),
+            )
+        };
 
-#[derive(Clone, Copy)]
-pub struct SurfaceCapabilities {
-    pub min_image_count: u32,
-    pub max_image_count: u32,
-    pub current_extent: Extent2d,
-    pub min_image_extent: Extent2d,
-    pub max_image_extent: Extent2d,
-    pub max_image_array_layers: u32,
-    pub supported_transforms: u32,
-    pub current_transform: u32,
-    pub supported_composite_alpha: u32,
-    pub supported_usage_flags: u32,
-}
+        if result != ffi::Result::Success && result != ffi::Result::Incomplete {
+            return Err(Self::surface_query_error(result));
+        }
 
-#[derive(Clone, Copy)]
-pub struct SurfaceFormat {
-    pub format: Format,
-    pub color_space: ColorSpace,
-}
+        unsafe { present_modes.set_len(present_mode_count as _) };
 
-#[derive(Clone, Copy)]
-pub enum SharingMode {
-    Exclusive,
-}
+        let present_modes = present_modes
+            .into_iter()
+            .filter_map(|present_mode| match present_mode {
+                0 => Some(PresentMode::Immediate),
+                1 => Some(PresentMode::Mailbox),
+                2 => Some(PresentMode::Fifo),
+                3 => Some(PresentMode::FifoRelaxed),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
 
-#[derive(Clone, Copy)]
-pub enum CompositeAlpha {
-    Opaque,
-}
+        Ok(present_modes)
+    }
 
-#[derive(Clone, Copy)]
-pub struct Version {
-    major: u32,
-    minor: u32,
-    patch: u32,
+    fn surface_query_error(result: ffi::Result) -> Error {
+        match result {
+            ffi::Result::OutOfHostMemory => Error::OutOfHostMemory,
+            ffi::Result::OutOfDeviceMemory => Error::OutOfDeviceMemory,
+            ffi::Result::SurfaceLost => Error::SurfaceLost,
+            _ => unexpected_result(result),
+        }
+    }
 }
 
-impl From<(u32, u32, u32)> for Version {
-    fn from(tuple: (u32, u32, u32)) -> Self {
-        Version {
-            major: tuple.0,
-            minor: tuple.1,
-            patch: tuple.2,
+pub struct QueueFamilyProperties {
+    pub queue_flags: u32,
+    pub queue_count: u32,
+    //0 when the family does not support timestamp queries
+    pub timestamp_valid_bits: u32,
+    //image copies on this family must be multiples of this, (1, 1, 1) on graphics and compute
+    pub min_image_transfer_granularity: [u32; 3],
+}
+
+//Queue family per role, the same index may fill several roles
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueueFamilies {
+    pub graphics: u32,
+    //a compute only family when there is one, for async compute
+    pub compute: u32,
+    //a transfer only family when there is one, for uploads alongside rendering
+    pub transfer: u32,
+    //None when no surface was given
+    pub present: Option<u32>,
+}
+
+impl QueueFamilies {
+    //Each family once, in role order, for DeviceCreateInfo::queues
+    pub fn unique(&self) -> Vec<u32> {
+        let mut families = vec![];
+
+        for family in [Some(self.graphics), Some(self.compute), Some(self.transfer), self.present]
+            .into_iter()
+            .flatten()
+        {
+            if !families.contains(&family) {
+                families.push(family);
+            }
         }
+
+        families
     }
-}
 
-impl From<Version> for u32 {
-    fn from(version: Version) -> Self {
-        (version.major << 22 | version.minor << 12 | version.patch) as u32
+    pub fn queue_create_infos<'a>(
+        &self,
+        queue_priorities: &'a [f32],
+    ) -> Vec<DeviceQueueCreateInfo<'a>> {
+        self.unique()
+            .into_iter()
+            .map(|queue_family_index| DeviceQueueCreateInfo {
+                flags: 0,
+                queue_family_index,
+                queue_priorities,
+            })
+            .collect()
     }
-}
 
-#[derive(Clone, Copy)]
-pub struct ApplicationInfo<'a> {
-    pub application_name: &'a str,
-    pub application_version: Version,
-    pub engine_name: &'a str,
-    pub engine_version: Version,
-    pub api_version: Version,
+    pub fn has_dedicated_transfer(&self) -> bool {
+        self.transfer != self.graphics && self.transfer != self.compute
+    }
 }
 
-#[derive(Clone, Copy)]
-pub struct InstanceCreateInfo<'a> {
-    pub application_info: &'a ApplicationInfo<'a>,
-    pub extensions: &'a [&'a str],
-    pub layers: &'a [&'a str],
-    pub debug_utils: &'a Option<DebugUtilsMessengerCreateInfo>,
+//One queue per role from Device::queues, roles sharing a family share the queue
+pub struct Queues {
+    pub graphics: Queue,
+    pub compute: Queue,
+    pub transfer: Queue,
+    pub present: Option<Queue>,
 }
 
-#[derive(Clone, Copy)]
-pub struct DebugUtilsMessengerCreateInfo {
-    pub message_severity: u32,
-    pub message_type: u32,
-    pub user_callback: DebugUtilsMessengerCallback,
+pub struct DeviceQueueCreateInfo<'a> {
+    pub flags: u32,
+    pub queue_family_index: u32,
+    pub queue_priorities: &'a [f32],
 }
 
-#[derive(Clone, Copy)]
-pub struct DebugUtilsMessengerCallbackData<'a> {
-    pub message_severity: u32,
-    pub message_type: u32,
-    pub message: &'a str,
+pub struct DeviceCreateInfo<'a> {
+    pub queues: &'a [DeviceQueueCreateInfo<'a>],
+    pub enabled_features: &'a PhysicalDeviceFeatures,
+    pub protected_memory: bool,
+    //requires EXT_VERTEX_ATTRIBUTE_DIVISOR
+    pub vertex_attribute_divisor: bool,
+    //requires EXT_LINE_RASTERIZATION
+    pub line_rasterization: LineRasterizationFeatures,
+    //requires EXT_SHADER_ATOMIC_FLOAT
+    pub shader_atomic_float: ShaderAtomicFloatFeatures,
+    //requires EXT_DEPTH_CLIP_ENABLE
+    pub depth_clip_enable: bool,
+    //pixel interlock, requires EXT_FRAGMENT_SHADER_INTERLOCK
+    pub fragment_shader_interlock: bool,
+    //requires EXT_DESCRIPTOR_INDEXING
+    pub descriptor_indexing: DescriptorIndexingFeatures,
+    //counter query pools, requires KHR_PERFORMANCE_QUERY
+    pub performance_query: bool,
+    //requires EXT_MEMORY_PRIORITY
+    pub memory_priority: bool,
+    //requires memory_priority and EXT_PAGEABLE_DEVICE_LOCAL_MEMORY
+    pub pageable_device_local_memory: bool,
+    //pipelines capture statistics and internal representations,
+    //requires KHR_PIPELINE_EXECUTABLE_PROPERTIES
+    pub pipeline_executable_info: bool,
+    //set_event2, wait_events2, pipeline_barrier2 and Queue::submit2, requires
+    //KHR_SYNCHRONIZATION_2
+    pub synchronization2: bool,
+    //begin_rendering and pipelines without a render pass, requires KHR_DYNAMIC_RENDERING
+    pub dynamic_rendering: bool,
+    //secondaries may inherit viewport and scissor, requires NV_INHERITED_VIEWPORT_SCISSOR
+    pub inherited_viewport_scissor: bool,
+    //SamplerYcbcrConversion for sampling multi-planar formats
+    pub sampler_ycbcr_conversion: bool,
+    //PresentInfo::fences, requires EXT_SWAPCHAIN_MAINTENANCE_1
+    pub swapchain_maintenance1: bool,
+    //Buffer::device_address, requires KHR_BUFFER_DEVICE_ADDRESS
+    pub buffer_device_address: bool,
+    pub extensions: &'a [&'a str],
+    pub layers: &'a [&'a str],
 }
 
-pub struct Instance {
-    handle: ffi::Instance,
+//The device's scratch for the ffi structs one call translates its arguments into. The
+//arena is reset as the last of these is dropped, so it only grows to what the largest call
+//needed whether or not anything ever waits for a frame.
+struct Scratch<'a> {
+    scratch: &'a RefCell<Arena>,
+    arena: Option<Ref<'a, Arena>>,
 }
 
-impl Instance {
-    pub fn new(create_info: InstanceCreateInfo<'_>) -> Result<Rc<Instance>, Error> {
-        let application_name = CString::new(create_info.application_info.application_name).unwrap();
+impl Deref for Scratch<'_> {
+    type Target = Arena;
 
-        let application_version: u32 = create_info.application_info.application_version.into();
+    fn deref(&self) -> &Arena {
+        self.arena.as_ref().unwrap()
+    }
+}
 
-        let engine_name = CString::new(create_info.application_info.application_name).unwrap();
+impl Drop for Scratch<'_> {
+    fn drop(&mut self) {
+        self.arena = None;
 
-        let engine_version: u32 = create_info.application_info.engine_version.into();
+        //still borrowed by an enclosing call
+        if let Ok(mut arena) = self.scratch.try_borrow_mut() {
+            arena.reset();
+        }
+    }
+}
 
-        let api_version: u32 = create_info.application_info.api_version.into();
+pub struct Device {
+    handle: ffi::Device,
+    limits: PhysicalDeviceLimits,
+    features: PhysicalDeviceFeatures,
+    line_rasterization: LineRasterizationFeatures,
+    shader_atomic_float: ShaderAtomicFloatFeatures,
+    descriptor_indexing: DescriptorIndexingFeatures,
+    memory_priority: bool,
+    pageable_device_local_memory: bool,
+    pipeline_executable_info: bool,
+    synchronization2: bool,
+    dynamic_rendering: bool,
+    inherited_viewport_scissor: bool,
+    sampler_ycbcr_conversion: bool,
+    swapchain_maintenance1: bool,
+    buffer_device_address: bool,
+    extensions: Vec<String>,
+    children: RefCell<BTreeMap<&'static str, usize>>,
+    //ffi structs of barriers and descriptor writes, see Scratch
+    scratch: RefCell<Arena>,
+    #[cfg(feature = "fault-injection")]
+    allocation_faults: RefCell<Option<AllocationFaults>>,
+}
+
+//An allocation Memory::allocate is about to make, see Device::inject_allocation_faults
+#[cfg(feature = "fault-injection")]
+#[derive(Clone, Copy, Debug)]
+pub struct AllocationAttempt {
+    //counts from 0 since the faults were injected
+    pub index: u64,
+    pub size: u64,
+    pub memory_type_index: u32,
+    pub property_flags: u32,
+}
 
-        let application_info = ffi::ApplicationInfo {
-            structure_type: ffi::StructureType::ApplicationInfo,
-            p_next: ptr::null(),
-            application_name: application_name.as_ptr(),
-            application_version,
-            engine_name: engine_name.as_ptr(),
-            engine_version,
-            api_version,
-        };
+#[cfg(feature = "fault-injection")]
+struct AllocationFaults {
+    attempts: u64,
+    fail: Box<dyn FnMut(&AllocationAttempt) -> bool>,
+}
+
+impl Device {
+    pub fn new(
+        physical_device: &PhysicalDevice,
+        create_info: DeviceCreateInfo<'_>,
+    ) -> Result<Rc<Device>, Error> {
+        let queue_create_infos = create_info
+            .queues
+            .iter()
+            .map(|create_info| ffi::DeviceQueueCreateInfo {
+                structure_type: ffi::StructureType::DeviceQueueCreateInfo,
+                p_next: ptr::null(),
+                flags: create_info.flags,
+                queue_family_index: create_info.queue_family_index,
+                queue_count: create_info.queue_priorities.len() as _,
+                queue_priorities: create_info.queue_priorities.as_ptr(),
+            })
+            .collect::<Vec<_>>();
 
         let layer_names = create_info
             .layers
@@ -2664,12 +7149,6 @@ impl Instance {
             .map(|string| string.as_ptr())
             .collect::<Vec<_>>();
 
-        let enabled_layer_names_ptr = if layer_names.len() > 0 {
-            enabled_layer_names.as_ptr()
-        } else {
-            ptr::null()
-        };
-
         let extension_names = create_info
             .extensions
             .iter()
@@ -2681,578 +7160,705 @@ impl Instance {
             .map(|string| string.as_ptr())
             .collect::<Vec<_>>();
 
-        let enabled_extension_names_ptr = if extension_names.len() > 0 {
-            enabled_extension_names.as_ptr()
-        } else {
-            ptr::null()
-        };
+        let enabled_features = create_info.enabled_features.clone().into();
 
-        let debug_utils = if let Some(create_info) = create_info.debug_utils {
-            let g = unsafe { mem::transmute(create_info.user_callback) };
+        //each enabled feature struct is pushed onto the front of the chain
+        let mut p_next: *const () = ptr::null();
 
-            let create_info = ffi::DebugUtilsMessengerCreateInfo {
-                structure_type: ffi::StructureType::DebugUtilsMessengerCreateInfo,
-                p_next: ptr::null(),
-                flags: 0,
-                message_severity: create_info.message_severity as _,
-                message_type: create_info.message_type as _,
-                user_callback: ffi::debug_utils_messenger_callback,
-                user_data: g,
-            };
+        let mut protected_memory_features = ffi::PhysicalDeviceProtectedMemoryFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceProtectedMemoryFeatures,
+            p_next: ptr::null_mut(),
+            protected_memory: create_info.protected_memory as _,
+        };
 
-            Some(create_info)
-        } else {
-            None
+        if create_info.protected_memory {
+            protected_memory_features.p_next = p_next as _;
+            p_next = &protected_memory_features as *const _ as _;
+        }
+
+        let mut vertex_attribute_divisor_features = ffi::PhysicalDeviceVertexAttributeDivisorFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceVertexAttributeDivisorFeatures,
+            p_next: ptr::null_mut(),
+            vertex_attribute_instance_rate_divisor: create_info.vertex_attribute_divisor as _,
+            vertex_attribute_instance_rate_zero_divisor: false as _,
         };
 
-        let p_next = if let Some(create_info) = &debug_utils {
-            unsafe { mem::transmute::<_, _>(create_info) }
-        } else {
-            ptr::null()
+        if create_info.vertex_attribute_divisor {
+            vertex_attribute_divisor_features.p_next = p_next as _;
+            p_next = &vertex_attribute_divisor_features as *const _ as _;
+        }
+
+        let line_rasterization = create_info.line_rasterization;
+
+        let mut line_rasterization_features = ffi::PhysicalDeviceLineRasterizationFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceLineRasterizationFeatures,
+            p_next: ptr::null_mut(),
+            rectangular_lines: line_rasterization.rectangular_lines as _,
+            bresenham_lines: line_rasterization.bresenham_lines as _,
+            smooth_lines: line_rasterization.smooth_lines as _,
+            stippled_rectangular_lines: false as _,
+            stippled_bresenham_lines: false as _,
+            stippled_smooth_lines: false as _,
         };
 
-        let create_info = ffi::InstanceCreateInfo {
-            structure_type: ffi::StructureType::InstanceCreateInfo,
-            p_next,
-            flags: 0,
-            application_info: &application_info,
-            enabled_layer_count: create_info.layers.len() as _,
-            enabled_layer_names: enabled_layer_names_ptr,
-            enabled_extension_count: create_info.extensions.len() as _,
-            enabled_extension_names: enabled_extension_names_ptr,
+        if line_rasterization.rectangular_lines
+            || line_rasterization.bresenham_lines
+            || line_rasterization.smooth_lines
+        {
+            line_rasterization_features.p_next = p_next as _;
+            p_next = &line_rasterization_features as *const _ as _;
+        }
+
+        let shader_atomic_float = create_info.shader_atomic_float;
+
+        let mut shader_atomic_float_features = ffi::PhysicalDeviceShaderAtomicFloatFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceShaderAtomicFloatFeatures,
+            p_next: ptr::null_mut(),
+            shader_buffer_float_32_atomics: shader_atomic_float.shader_buffer_float_32_atomics as _,
+            shader_buffer_float_32_atomic_add: shader_atomic_float
+                .shader_buffer_float_32_atomic_add as _,
+            shader_buffer_float_64_atomics: false as _,
+            shader_buffer_float_64_atomic_add: false as _,
+            shader_shared_float_32_atomics: false as _,
+            shader_shared_float_32_atomic_add: false as _,
+            shader_shared_float_64_atomics: false as _,
+            shader_shared_float_64_atomic_add: false as _,
+            shader_image_float_32_atomics: shader_atomic_float.shader_image_float_32_atomics as _,
+            shader_image_float_32_atomic_add: shader_atomic_float.shader_image_float_32_atomic_add
+                as _,
+            sparse_image_float_32_atomics: false as _,
+            sparse_image_float_32_atomic_add: false as _,
         };
 
-        let mut handle = MaybeUninit::<ffi::Instance>::uninit();
+        if shader_atomic_float.any() {
+            shader_atomic_float_features.p_next = p_next as _;
+            p_next = &shader_atomic_float_features as *const _ as _;
+        }
 
-        let result =
-            unsafe { ffi::vkCreateInstance(&create_info, ptr::null(), handle.as_mut_ptr()) };
+        let mut depth_clip_enable_features = ffi::PhysicalDeviceDepthClipEnableFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceDepthClipEnableFeatures,
+            p_next: ptr::null_mut(),
+            depth_clip_enable: create_info.depth_clip_enable as _,
+        };
 
-        match result {
-            ffi::Result::Success => {
-                let handle = unsafe { handle.assume_init() };
+        if create_info.depth_clip_enable {
+            depth_clip_enable_features.p_next = p_next as _;
+            p_next = &depth_clip_enable_features as *const _ as _;
+        }
 
-                let instance = Self { handle };
+        let mut fragment_shader_interlock_features =
+            ffi::PhysicalDeviceFragmentShaderInterlockFeatures {
+                structure_type: ffi::StructureType::PhysicalDeviceFragmentShaderInterlockFeatures,
+                p_next: ptr::null_mut(),
+                fragment_shader_sample_interlock: false as _,
+                fragment_shader_pixel_interlock: create_info.fragment_shader_interlock as _,
+                fragment_shader_shading_rate_interlock: false as _,
+            };
 
-                let instance = Rc::new(instance);
+        if create_info.fragment_shader_interlock {
+            fragment_shader_interlock_features.p_next = p_next as _;
+            p_next = &fragment_shader_interlock_features as *const _ as _;
+        }
 
-                Ok(instance)
-            }
-            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
-            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            ffi::Result::InitializationFailed => Err(Error::InitializationFailed),
-            ffi::Result::LayerNotPresent => Err(Error::LayerNotPresent),
-            ffi::Result::ExtensionNotPresent => Err(Error::ExtensionNotPresent),
-            ffi::Result::IncompatibleDriver => Err(Error::IncompatibleDriver),
-            _ => panic!("unexpected result: {:?}", result),
+        let descriptor_indexing = create_info.descriptor_indexing;
+
+        let mut descriptor_indexing_features = ffi::PhysicalDeviceDescriptorIndexingFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceDescriptorIndexingFeatures,
+            p_next: ptr::null_mut(),
+            shader_input_attachment_array_dynamic_indexing: false as _,
+            shader_uniform_texel_buffer_array_dynamic_indexing: false as _,
+            shader_storage_texel_buffer_array_dynamic_indexing: false as _,
+            shader_uniform_buffer_array_non_uniform_indexing: false as _,
+            shader_sampled_image_array_non_uniform_indexing: descriptor_indexing.shader_sampled_image_array_non_uniform_indexing as _,
+            shader_storage_buffer_array_non_uniform_indexing: false as _,
+            shader_storage_image_array_non_uniform_indexing: false as _,
+            shader_input_attachment_array_non_uniform_indexing: false as _,
+            shader_uniform_texel_buffer_array_non_uniform_indexing: false as _,
+            shader_storage_texel_buffer_array_non_uniform_indexing: false as _,
+            descriptor_binding_uniform_buffer_update_after_bind: false as _,
+            descriptor_binding_sampled_image_update_after_bind: descriptor_indexing.descriptor_binding_sampled_image_update_after_bind as _,
+            descriptor_binding_storage_image_update_after_bind: false as _,
+            descriptor_binding_storage_buffer_update_after_bind: false as _,
+            descriptor_binding_uniform_texel_buffer_update_after_bind: false as _,
+            descriptor_binding_storage_texel_buffer_update_after_bind: false as _,
+            descriptor_binding_update_unused_while_pending: descriptor_indexing.descriptor_binding_update_unused_while_pending as _,
+            descriptor_binding_partially_bound: descriptor_indexing.descriptor_binding_partially_bound as _,
+            descriptor_binding_variable_descriptor_count: descriptor_indexing.descriptor_binding_variable_descriptor_count as _,
+            runtime_descriptor_array: descriptor_indexing.runtime_descriptor_array as _,
+        };
+
+        if descriptor_indexing.any() {
+            descriptor_indexing_features.p_next = p_next as _;
+            p_next = &descriptor_indexing_features as *const _ as _;
         }
-    }
-}
 
-impl Drop for Instance {
-    fn drop(&mut self) {
-        unsafe { ffi::vkDestroyInstance(self.handle, ptr::null()) };
-    }
-}
+        let mut performance_query_features = ffi::PhysicalDevicePerformanceQueryFeatures {
+            structure_type: ffi::StructureType::PhysicalDevicePerformanceQueryFeatures,
+            p_next: ptr::null_mut(),
+            performance_counter_query_pools: create_info.performance_query as _,
+            performance_counter_multiple_query_pools: false as _,
+        };
 
-pub struct DebugUtilsMessenger {
-    instance: Rc<Instance>,
-    handle: ffi::DebugUtilsMessenger,
-}
+        if create_info.performance_query {
+            performance_query_features.p_next = p_next as _;
+            p_next = &performance_query_features as *const _ as _;
+        }
 
-impl DebugUtilsMessenger {
-    pub fn new(
-        instance: Rc<Instance>,
-        create_info: DebugUtilsMessengerCreateInfo,
-    ) -> Result<Self, Error> {
-        let f_name = CStr::from_bytes_with_nul(b"vkCreateDebugUtilsMessengerEXT\0").unwrap();
+        let mut memory_priority_features = ffi::PhysicalDeviceMemoryPriorityFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceMemoryPriorityFeatures,
+            p_next: ptr::null_mut(),
+            memory_priority: create_info.memory_priority as _,
+        };
 
-        let f = unsafe { ffi::vkGetInstanceProcAddr(instance.handle, f_name.as_ptr()) };
+        if create_info.memory_priority {
+            memory_priority_features.p_next = p_next as _;
+            p_next = &memory_priority_features as *const _ as _;
+        }
 
-        if f == ptr::null() {
-            return Err(Error::ExtensionNotPresent);
+        let mut pageable_device_local_memory_features =
+            ffi::PhysicalDevicePageableDeviceLocalMemoryFeatures {
+                structure_type: ffi::StructureType::PhysicalDevicePageableDeviceLocalMemoryFeatures,
+                p_next: ptr::null_mut(),
+                pageable_device_local_memory: create_info.pageable_device_local_memory as _,
+            };
+
+        if create_info.pageable_device_local_memory {
+            pageable_device_local_memory_features.p_next = p_next as _;
+            p_next = &pageable_device_local_memory_features as *const _ as _;
         }
 
-        let f = unsafe { mem::transmute::<_, ffi::CreateDebugUtilsMessenger>(f) };
+        let mut pipeline_executable_properties_features =
+            ffi::PhysicalDevicePipelineExecutablePropertiesFeatures {
+                structure_type:
+                    ffi::StructureType::PhysicalDevicePipelineExecutablePropertiesFeatures,
+                p_next: ptr::null_mut(),
+                pipeline_executable_info: create_info.pipeline_executable_info as _,
+            };
 
-        let g = unsafe { mem::transmute(create_info.user_callback) };
+        if create_info.pipeline_executable_info {
+            pipeline_executable_properties_features.p_next = p_next as _;
+            p_next = &pipeline_executable_properties_features as *const _ as _;
+        }
 
-        let create_info = ffi::DebugUtilsMessengerCreateInfo {
-            structure_type: ffi::StructureType::DebugUtilsMessengerCreateInfo,
-            p_next: ptr::null(),
-            flags: 0,
-            message_severity: create_info.message_severity as _,
-            message_type: create_info.message_type as _,
-            user_callback: ffi::debug_utils_messenger_callback,
-            user_data: g,
+        let mut synchronization2_features = ffi::PhysicalDeviceSynchronization2Features {
+            structure_type: ffi::StructureType::PhysicalDeviceSynchronization2Features,
+            p_next: ptr::null_mut(),
+            synchronization2: create_info.synchronization2 as _,
         };
 
-        let mut handle = MaybeUninit::<ffi::DebugUtilsMessenger>::uninit();
+        if create_info.synchronization2 {
+            synchronization2_features.p_next = p_next as _;
+            p_next = &synchronization2_features as *const _ as _;
+        }
 
-        let result = unsafe {
-            f(
-                instance.handle,
-                &create_info,
-                ptr::null(),
-                handle.as_mut_ptr(),
-            )
+        let mut dynamic_rendering_features = ffi::PhysicalDeviceDynamicRenderingFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceDynamicRenderingFeatures,
+            p_next: ptr::null_mut(),
+            dynamic_rendering: create_info.dynamic_rendering as _,
         };
 
-        match result {
-            ffi::Result::Success => {
-                let handle = unsafe { handle.assume_init() };
+        if create_info.dynamic_rendering {
+            dynamic_rendering_features.p_next = p_next as _;
+            p_next = &dynamic_rendering_features as *const _ as _;
+        }
 
-                let debug_utils_messenger = Self { instance, handle };
+        let mut swapchain_maintenance1_features =
+            ffi::PhysicalDeviceSwapchainMaintenance1Features {
+                structure_type: ffi::StructureType::PhysicalDeviceSwapchainMaintenance1Features,
+                p_next: ptr::null_mut(),
+                swapchain_maintenance1: create_info.swapchain_maintenance1 as _,
+            };
 
-                Ok(debug_utils_messenger)
-            }
-            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
-            _ => panic!("unexpected result: {:?}", result),
+        if create_info.swapchain_maintenance1 {
+            swapchain_maintenance1_features.p_next = p_next as _;
+            p_next = &swapchain_maintenance1_features as *const _ as _;
         }
-    }
-}
 
-impl Drop for DebugUtilsMessenger {
-    fn drop(&mut self) {
-        let f_name = CStr::from_bytes_with_nul(b"vkDestroyDebugUtilsMessengerEXT\0").unwrap();
+        let mut buffer_device_address_features = ffi::PhysicalDeviceBufferDeviceAddressFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceBufferDeviceAddressFeatures,
+            p_next: ptr::null_mut(),
+            buffer_device_address: create_info.buffer_device_address as _,
+            buffer_device_address_capture_replay: 0,
+            buffer_device_address_multi_device: 0,
+        };
 
-        let f = unsafe { ffi::vkGetInstanceProcAddr(self.instance.handle, f_name.as_ptr()) };
+        if create_info.buffer_device_address {
+            buffer_device_address_features.p_next = p_next as _;
+            p_next = &buffer_device_address_features as *const _ as _;
+        }
 
-        if f == ptr::null() {
-            panic!("extension not present, but handle already created");
+        let mut inherited_viewport_scissor_features =
+            ffi::PhysicalDeviceInheritedViewportScissorFeatures {
+                structure_type: ffi::StructureType::PhysicalDeviceInheritedViewportScissorFeatures,
+                p_next: ptr::null_mut(),
+                inherited_viewport_scissor_2d: create_info.inherited_viewport_scissor as _,
+            };
+
+        if create_info.inherited_viewport_scissor {
+            inherited_viewport_scissor_features.p_next = p_next as _;
+            p_next = &inherited_viewport_scissor_features as *const _ as _;
         }
 
-        let f = unsafe { mem::transmute::<_, ffi::DestroyDebugUtilsMessenger>(f) };
+        let mut sampler_ycbcr_conversion_features =
+            ffi::PhysicalDeviceSamplerYcbcrConversionFeatures {
+                structure_type: ffi::StructureType::PhysicalDeviceSamplerYcbcrConversionFeatures,
+                p_next: ptr::null_mut(),
+                sampler_ycbcr_conversion: create_info.sampler_ycbcr_conversion as _,
+            };
 
-        unsafe { f(self.instance.handle, self.handle, ptr::null()) };
-    }
-}
+        if create_info.sampler_ycbcr_conversion {
+            sampler_ycbcr_conversion_features.p_next = p_next as _;
+            p_next = &sampler_ycbcr_conversion_features as *const _ as _;
+        }
 
-#[derive(PartialEq, Eq, Debug)]
-pub enum PhysicalDeviceType {
-    Other,
-    Integrated,
-    Discrete,
-    Virtual,
-    Cpu,
-}
+        let memory_priority = create_info.memory_priority;
+        let pageable_device_local_memory = create_info.pageable_device_local_memory;
+        let pipeline_executable_info = create_info.pipeline_executable_info;
+        let synchronization2 = create_info.synchronization2;
+        let dynamic_rendering = create_info.dynamic_rendering;
+        let inherited_viewport_scissor = create_info.inherited_viewport_scissor;
+        let sampler_ycbcr_conversion = create_info.sampler_ycbcr_conversion;
+        let swapchain_maintenance1 = create_info.swapchain_maintenance1;
+        let buffer_device_address = create_info.buffer_device_address;
 
-pub struct PhysicalDeviceLimits {
-    pub max_image_dimension_2d: u32,
-}
+        let features = *create_info.enabled_features;
 
-//TODO add more info
-pub struct PhysicalDeviceProperties {
-    pub device_type: PhysicalDeviceType,
-    pub device_name: String,
-    pub limits: PhysicalDeviceLimits,
-}
+        let extensions = create_info
+            .extensions
+            .iter()
+            .map(|extension| extension.to_string())
+            .collect::<Vec<_>>();
 
-#[derive(Clone, Copy, Default)]
-pub struct PhysicalDeviceFeatures {
-    pub robust_buffer_access: bool,
-    pub full_draw_index_uint_32: bool,
-    pub image_cube_array: bool,
-    pub independent_blend: bool,
-    pub geometry_shader: bool,
-    pub tessellation_shader: bool,
-    pub sample_rate_shading: bool,
-    pub dual_src_blend: bool,
-    pub logic_op: bool,
-    pub multi_draw_indirect: bool,
-    pub draw_indirect_first_instance: bool,
-    pub depth_clamp: bool,
-    pub depth_bias_clamp: bool,
-    pub fill_mode_non_solid: bool,
-    pub depth_bounds: bool,
-    pub wide_lines: bool,
-    pub large_points: bool,
-    pub alpha_to_one: bool,
-    pub multi_viewport: bool,
-    pub sampler_anisotropy: bool,
-    pub texture_compression_etc_2: bool,
-    pub texture_compression_astc_ldr: bool,
-    pub texture_compression_bc: bool,
-    pub occlusion_query_precise: bool,
-    pub pipeline_statistics_query: bool,
-    pub vertex_pipeline_stores_and_atomics: bool,
-    pub fragment_stores_and_atomics: bool,
-    pub shader_tessellation_and_geometry_point_size: bool,
-    pub shader_image_gather_extended: bool,
-    pub shader_storage_image_extended_formats: bool,
-    pub shader_storage_image_multisample: bool,
-    pub shader_storage_image_read_without_format: bool,
-    pub shader_storage_image_write_without_format: bool,
-    pub shader_uniform_buffer_array_dynamic_indexing: bool,
-    pub shader_sampled_image_array_dynamic_indexing: bool,
-    pub shader_storage_buffer_array_dynamic_indexing: bool,
-    pub shader_storage_image_array_dynamic_indexing: bool,
-    pub shader_clip_distance: bool,
-    pub shader_cull_distance: bool,
-    pub shader_float_64: bool,
-    pub shader_int_64: bool,
-    pub shader_int_16: bool,
-    pub shader_resource_residency: bool,
-    pub shader_resource_min_lod: bool,
-    pub sparse_binding: bool,
-    pub sparse_residency_buffer: bool,
-    pub sparse_residency_image_2_d: bool,
-    pub sparse_residency_image_3_d: bool,
-    pub sparse_residency_2_samples: bool,
-    pub sparse_residency_4_samples: bool,
-    pub sparse_residency_8_samples: bool,
-    pub sparse_residency_16_samples: bool,
-    pub sparse_residency_aliased: bool,
-    pub variable_multisample_rate: bool,
-    pub inherited_queries: bool,
-}
+        let create_info = ffi::DeviceCreateInfo {
+            structure_type: ffi::StructureType::DeviceCreateInfo,
+            p_next,
+            flags: 0,
+            queue_create_info_count: queue_create_infos.len() as _,
+            queue_create_infos: queue_create_infos.as_ptr(),
+            enabled_layer_count: create_info.layers.len() as _,
+            enabled_layer_names: enabled_layer_names.as_ptr(),
+            enabled_extension_count: create_info.extensions.len() as _,
+            enabled_extension_names: enabled_extension_names.as_ptr(),
+            enabled_features: &enabled_features,
+        };
+
+        let mut handle = MaybeUninit::<ffi::Device>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreateDevice(
+                physical_device.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
 
-pub struct PhysicalDevice {
-    handle: ffi::PhysicalDevice,
-}
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
 
-impl PhysicalDevice {
-    pub fn enumerate(instance: Rc<Instance>) -> Vec<Rc<Self>> {
-        let mut handle_count: u32 = 0;
+                let limits = physical_device.properties().limits;
 
-        unsafe {
-            ffi::vkEnumeratePhysicalDevices(instance.handle, &mut handle_count, ptr::null_mut())
-        };
+                let device = Self {
+                    handle,
+                    limits,
+                    features,
+                    line_rasterization,
+                    shader_atomic_float,
+                    descriptor_indexing,
+                    memory_priority,
+                    pageable_device_local_memory,
+                    pipeline_executable_info,
+                    synchronization2,
+                    dynamic_rendering,
+                    inherited_viewport_scissor,
+                    sampler_ycbcr_conversion,
+                    swapchain_maintenance1,
+                    buffer_device_address,
+                    extensions,
+                    children: RefCell::new(BTreeMap::new()),
+                    scratch: RefCell::new(Arena::new()),
+                    #[cfg(feature = "fault-injection")]
+                    allocation_faults: RefCell::new(None),
+                };
 
-        let mut handles = Vec::<ffi::PhysicalDevice>::with_capacity(handle_count as _);
+                let device = Rc::new(device);
+
+                Ok(device)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            ffi::Result::InitializationFailed => Err(Error::InitializationFailed),
+            ffi::Result::ExtensionNotPresent => Err(Error::ExtensionNotPresent),
+            ffi::Result::FeatureNotPresent => Err(Error::FeatureNotPresent),
+            ffi::Result::TooManyObjects => Err(Error::TooManyObjects),
+            ffi::Result::DeviceLost => Err(Error::DeviceLost),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    pub fn queue(&self, queue_family_index: u32) -> Queue {
+        let mut handle = MaybeUninit::<ffi::Queue>::uninit();
 
         unsafe {
-            ffi::vkEnumeratePhysicalDevices(
-                instance.handle,
-                &mut handle_count,
-                handles.as_mut_ptr(),
-            )
+            ffi::vkGetDeviceQueue(self.handle, queue_family_index as _, 0, handle.as_mut_ptr())
         };
 
-        unsafe { handles.set_len(handle_count as _) };
-
-        let physical_devices = handles
-            .into_iter()
-            .map(|handle| Self { handle })
-            .map(|physical_device| Rc::new(physical_device))
-            .collect::<Vec<_>>();
+        let handle = unsafe { handle.assume_init() };
 
-        physical_devices
+        Queue {
+            handle,
+            scratch: Arena::new(),
+            submit2: self.queue_submit2_fn(),
+        }
     }
 
-    pub fn properties(&self) -> PhysicalDeviceProperties {
-        let mut properties = MaybeUninit::<ffi::PhysicalDeviceProperties>::uninit();
+    fn queue_submit2_fn(&self) -> Option<ffi::QueueSubmit2> {
+        if !self.synchronization2 {
+            return None;
+        }
 
-        unsafe { ffi::vkGetPhysicalDeviceProperties(self.handle, properties.as_mut_ptr()) };
+        let f_name = CStr::from_bytes_with_nul(b"vkQueueSubmit2KHR\0").unwrap();
 
-        let properties = unsafe { properties.assume_init() };
+        let f = unsafe { ffi::vkGetDeviceProcAddr(self.handle, f_name.as_ptr()) };
 
-        let device_type = match properties.device_type {
-            ffi::PhysicalDeviceType::Other => PhysicalDeviceType::Other,
-            ffi::PhysicalDeviceType::Integrated => PhysicalDeviceType::Integrated,
-            ffi::PhysicalDeviceType::Discrete => PhysicalDeviceType::Discrete,
-            ffi::PhysicalDeviceType::Virtual => PhysicalDeviceType::Virtual,
-            ffi::PhysicalDeviceType::Cpu => PhysicalDeviceType::Cpu,
-        };
+        if f == ptr::null() {
+            return None;
+        }
 
-        let device_name = unsafe { CStr::from_ptr(properties.device_name.as_ptr()) }
-            .to_str()
-            .unwrap()
-            .to_owned();
+        Some(unsafe { mem::transmute_copy::<_, ffi::QueueSubmit2>(&f) })
+    }
 
-        let limits = PhysicalDeviceLimits {
-            max_image_dimension_2d: properties.limits.max_image_dimension_2d,
+    //The first queue of each family in families, which must have been created with the device
+    pub fn queues(&self, families: &QueueFamilies) -> Queues {
+        Queues {
+            graphics: self.queue(families.graphics),
+            compute: self.queue(families.compute),
+            transfer: self.queue(families.transfer),
+            present: families.present.map(|family| self.queue(family)),
+        }
+    }
+
+    pub fn protected_queue(&self, queue_family_index: u32) -> Queue {
+        let queue_info = ffi::DeviceQueueInfo2 {
+            structure_type: ffi::StructureType::DeviceQueueInfo2,
+            p_next: ptr::null(),
+            flags: DEVICE_QUEUE_CREATE_PROTECTED,
+            queue_family_index,
+            queue_index: 0,
         };
 
-        PhysicalDeviceProperties {
-            device_type,
-            device_name,
-            limits,
+        let mut handle = MaybeUninit::<ffi::Queue>::uninit();
+
+        unsafe { ffi::vkGetDeviceQueue2(self.handle, &queue_info, handle.as_mut_ptr()) };
+
+        let handle = unsafe { handle.assume_init() };
+
+        Queue {
+            handle,
+            scratch: Arena::new(),
+            submit2: self.queue_submit2_fn(),
         }
     }
 
-    //TODO
-    pub fn features(&self) -> PhysicalDeviceFeatures {
-        unimplemented!();
+    //Every following Memory::allocate asks fail first and returns OutOfDeviceMemory without
+    //calling the driver where it returns true, so out of memory handling can be tested
+    #[cfg(feature = "fault-injection")]
+    pub fn inject_allocation_faults(
+        &self,
+        fail: impl FnMut(&AllocationAttempt) -> bool + 'static,
+    ) {
+        *self.allocation_faults.borrow_mut() = Some(AllocationFaults {
+            attempts: 0,
+            fail: Box::new(fail),
+        });
     }
 
-    pub fn queue_families(&self) -> Vec<QueueFamilyProperties> {
-        let mut queue_family_count: u32 = 0;
+    #[cfg(feature = "fault-injection")]
+    pub fn clear_allocation_faults(&self) {
+        *self.allocation_faults.borrow_mut() = None;
+    }
 
-        unsafe {
-            ffi::vkGetPhysicalDeviceQueueFamilyProperties(
-                self.handle,
-                &mut queue_family_count,
-                ptr::null_mut(),
-            )
-        };
+    #[cfg(feature = "fault-injection")]
+    fn allocation_fault(&self, size: u64, memory_type_index: u32, property_flags: u32) -> bool {
+        let mut allocation_faults = self.allocation_faults.borrow_mut();
 
-        let mut queue_families =
-            Vec::<ffi::QueueFamilyProperties>::with_capacity(queue_family_count as _);
+        let Some(AllocationFaults { attempts, fail }) = allocation_faults.as_mut() else {
+            return false;
+        };
 
-        unsafe {
-            ffi::vkGetPhysicalDeviceQueueFamilyProperties(
-                self.handle,
-                &mut queue_family_count,
-                queue_families.as_mut_ptr(),
-            )
+        let attempt = AllocationAttempt {
+            index: *attempts,
+            size,
+            memory_type_index,
+            property_flags,
         };
 
-        unsafe { queue_families.set_len(queue_family_count as _) };
+        *attempts += 1;
 
-        let queue_families = queue_families
-            .into_iter()
-            .map(|queue_family| QueueFamilyProperties {
-                queue_flags: queue_family.queue_flags,
-                queue_count: queue_family.queue_count,
-            })
-            .collect::<Vec<_>>();
+        fail(&attempt)
+    }
 
-        queue_families
+    pub fn is_extension_enabled(&self, extension: &str) -> bool {
+        self.extensions.iter().any(|enabled| enabled == extension)
     }
 
-    pub fn surface_format(&self, surface: &Surface) -> SurfaceFormat {
-        SurfaceFormat {
-            format: Format::Bgra8Srgb,
-            color_space: ColorSpace::SrgbNonlinear,
-        }
+    pub fn limits(&self) -> &PhysicalDeviceLimits {
+        &self.limits
     }
 
-    //TODO
-    pub fn surface_capabilities(&self, surface: &Surface) -> SurfaceCapabilities {
-        let mut surface_capabilities = MaybeUninit::<ffi::SurfaceCapabilities>::uninit();
+    pub fn enabled_features(&self) -> &PhysicalDeviceFeatures {
+        &self.features
+    }
 
-        unsafe {
-            ffi::vkGetPhysicalDeviceSurfaceCapabilitiesKHR(
-                self.handle,
-                surface.handle,
-                surface_capabilities.as_mut_ptr(),
-            )
-        };
+    pub fn line_rasterization_features(&self) -> &LineRasterizationFeatures {
+        &self.line_rasterization
+    }
 
-        let surface_capabilities = unsafe { surface_capabilities.assume_init() };
+    pub fn shader_atomic_float_features(&self) -> &ShaderAtomicFloatFeatures {
+        &self.shader_atomic_float
+    }
 
-        let current_extent = (
-            surface_capabilities.current_extent[0],
-            surface_capabilities.current_extent[1],
-        );
+    pub fn descriptor_indexing_features(&self) -> &DescriptorIndexingFeatures {
+        &self.descriptor_indexing
+    }
 
-        let min_image_extent = (
-            surface_capabilities.min_image_extent[0],
-            surface_capabilities.min_image_extent[1],
-        );
+    pub fn memory_priority_enabled(&self) -> bool {
+        self.memory_priority
+    }
 
-        let max_image_extent = (
-            surface_capabilities.max_image_extent[0],
-            surface_capabilities.max_image_extent[1],
-        );
+    pub fn pipeline_executable_info_enabled(&self) -> bool {
+        self.pipeline_executable_info
+    }
 
-        SurfaceCapabilities {
-            min_image_count: surface_capabilities.min_image_count,
-            max_image_count: surface_capabilities.max_image_count,
-            current_extent,
-            min_image_extent,
-            max_image_extent,
-            max_image_array_layers: surface_capabilities.max_image_array_layers,
-            supported_transforms: surface_capabilities.supported_transforms,
-            current_transform: surface_capabilities.current_transform,
-            supported_composite_alpha: surface_capabilities.supported_composite_alpha,
-            supported_usage_flags: surface_capabilities.supported_usage_flags,
-        }
+    pub fn synchronization2_enabled(&self) -> bool {
+        self.synchronization2
     }
 
-    pub fn surface_supported(
-        &self,
-        surface: &Surface,
-        queue_family_index: u32,
-    ) -> Result<bool, Error> {
-        let mut supported = MaybeUninit::<ffi::Bool>::uninit();
+    pub fn dynamic_rendering_enabled(&self) -> bool {
+        self.dynamic_rendering
+    }
 
-        let result = unsafe {
-            ffi::vkGetPhysicalDeviceSurfaceSupportKHR(
-                self.handle,
-                queue_family_index,
-                surface.handle,
-                supported.as_mut_ptr(),
-            )
-        };
+    pub fn swapchain_maintenance1_enabled(&self) -> bool {
+        self.swapchain_maintenance1
+    }
 
-        match result {
-            ffi::Result::Success => {
-                let supported = unsafe { supported.assume_init() };
+    pub fn buffer_device_address_enabled(&self) -> bool {
+        self.buffer_device_address
+    }
 
-                let supported = supported != 0;
+    pub fn inherited_viewport_scissor_enabled(&self) -> bool {
+        self.inherited_viewport_scissor
+    }
 
-                Ok(supported)
-            }
-            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
-            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            ffi::Result::SurfaceLost => Err(Error::SurfaceLost),
-            _ => panic!("unexpected result: {:?}", result),
+    pub fn sampler_ycbcr_conversion_enabled(&self) -> bool {
+        self.sampler_ycbcr_conversion
+    }
+
+    //Creation flags that make pipelines queryable through the executable properties
+    fn pipeline_create_flags(&self) -> u32 {
+        if self.pipeline_executable_info {
+            PIPELINE_CREATE_CAPTURE_STATISTICS | PIPELINE_CREATE_CAPTURE_INTERNAL_REPRESENTATIONS
+        } else {
+            0
         }
     }
 
-    pub fn memory_properties(&self) -> MemoryProperties {
-        let mut memory_properties = MaybeUninit::<ffi::PhysicalDeviceMemoryProperties>::uninit();
+    pub fn pageable_device_local_memory_enabled(&self) -> bool {
+        self.pageable_device_local_memory
+    }
 
-        unsafe {
-            ffi::vkGetPhysicalDeviceMemoryProperties(self.handle, memory_properties.as_mut_ptr())
-        };
+    //Must be held while recording and submitting command buffers with performance queries
+    pub fn acquire_profiling_lock(&self, timeout: u64) -> Result<(), Error> {
+        let f_name = CStr::from_bytes_with_nul(b"vkAcquireProfilingLockKHR\0").unwrap();
 
-        let memory_properties = unsafe { memory_properties.assume_init() };
+        let f = unsafe { ffi::vkGetDeviceProcAddr(self.handle, f_name.as_ptr()) };
 
-        let memory_types = (0..memory_properties.memory_type_count)
-            .into_iter()
-            .map(|i| memory_properties.memory_types[i as usize])
-            .map(|t| MemoryType {
-                property_flags: t.property_flags,
-                heap_index: t.heap_index,
-            })
-            .collect::<Vec<_>>();
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
+        }
 
-        let memory_heaps = (0..memory_properties.memory_heap_count)
-            .into_iter()
-            .map(|i| memory_properties.memory_heaps[i as usize])
-            .map(|h| MemoryHeap {
-                size: h.size,
-                flags: h.flags,
-            })
-            .collect::<Vec<_>>();
+        let f = unsafe { mem::transmute::<_, ffi::AcquireProfilingLock>(f) };
 
-        MemoryProperties {
-            memory_types,
-            memory_heaps,
+        let acquire_info = ffi::AcquireProfilingLockInfo {
+            structure_type: ffi::StructureType::AcquireProfilingLockInfo,
+            p_next: ptr::null(),
+            flags: 0,
+            timeout,
+        };
+
+        let result = unsafe { f(self.handle, &acquire_info) };
+
+        match result {
+            ffi::Result::Success => Ok(()),
+            ffi::Result::Timeout => Err(Error::Timeout),
+            _ => Err(unexpected_result(result)),
         }
     }
 
-    //TODO
-    pub fn surface_formats(&self, surface: &Surface) -> Vec<SurfaceFormat> {
-        unimplemented!();
-    }
+    pub fn release_profiling_lock(&self) {
+        let f_name = CStr::from_bytes_with_nul(b"vkReleaseProfilingLockKHR\0").unwrap();
 
-    //TODO
-    pub fn surface_present_modes(&self, surface: &Surface) -> Vec<PresentMode> {
-        unimplemented!();
-    }
-}
+        let f = unsafe { ffi::vkGetDeviceProcAddr(self.handle, f_name.as_ptr()) };
 
-pub struct QueueFamilyProperties {
-    pub queue_flags: u32,
-    pub queue_count: u32,
-}
+        if f == ptr::null() {
+            return;
+        }
 
-pub struct DeviceQueueCreateInfo<'a> {
-    pub queue_family_index: u32,
-    pub queue_priorities: &'a [f32],
-}
+        let f = unsafe { mem::transmute::<_, ffi::ReleaseProfilingLock>(f) };
 
-pub struct DeviceCreateInfo<'a> {
-    pub queues: &'a [DeviceQueueCreateInfo<'a>],
-    pub enabled_features: &'a PhysicalDeviceFeatures,
-    pub extensions: &'a [&'a str],
-    pub layers: &'a [&'a str],
-}
+        unsafe { f(self.handle) };
+    }
 
-pub struct Device {
-    handle: ffi::Device,
-}
+    //Descriptor sets are externally synchronized, the sets written or copied here must not be
+    //updated or bound for recording on another thread at the same time.
+    pub fn update_descriptor_sets(
+        &self,
+        writes: &'_ [WriteDescriptorSet],
+        copies: &'_ [CopyDescriptorSet],
+    ) -> Result<(), Error> {
+        if writes.len() == 0 && copies.len() == 0 {
+            return Ok(());
+        }
 
-impl Device {
-    pub fn new(
-        physical_device: &PhysicalDevice,
-        create_info: DeviceCreateInfo<'_>,
-    ) -> Result<Rc<Device>, Error> {
-        let queue_create_infos = create_info
-            .queues
+        let same_device_writes = writes
             .iter()
-            .map(|create_info| ffi::DeviceQueueCreateInfo {
-                structure_type: ffi::StructureType::DeviceQueueCreateInfo,
-                p_next: ptr::null(),
-                flags: 0,
-                queue_family_index: create_info.queue_family_index,
-                queue_count: create_info.queue_priorities.len() as _,
-                queue_priorities: create_info.queue_priorities.as_ptr(),
-            })
-            .collect::<Vec<_>>();
+            .all(|write| write.dst_set.device.handle == self.handle);
 
-        let layer_names = create_info
-            .layers
-            .iter()
-            .map(|layer_name| CString::new(*layer_name).unwrap())
-            .collect::<Vec<_>>();
+        let same_device_copies = copies.iter().all(|copy| {
+            copy.src_set.device.handle == self.handle && copy.dst_set.device.handle == self.handle
+        });
 
-        let enabled_layer_names = layer_names
-            .iter()
-            .map(|string| string.as_ptr())
-            .collect::<Vec<_>>();
+        if !same_device_writes || !same_device_copies {
+            return Err(Error::MixedDevices);
+        }
 
-        let extension_names = create_info
-            .extensions
-            .iter()
-            .map(|extension_name| CString::new(*extension_name).unwrap())
-            .collect::<Vec<_>>();
+        let scratch = self.scratch();
 
-        let enabled_extension_names = extension_names
-            .iter()
-            .map(|string| string.as_ptr())
-            .collect::<Vec<_>>();
+        let writes = scratch.alloc_from_iter(writes.iter().map(|write| {
+            let buffer_infos =
+                scratch.alloc_from_iter(write.buffer_infos.iter().map(|buffer_info| {
+                    ffi::DescriptorBufferInfo {
+                        buffer: buffer_info.buffer.handle,
+                        offset: buffer_info.offset as _,
+                        range: buffer_info.range as _,
+                    }
+                }));
 
-        let enabled_features = create_info.enabled_features.clone().into();
+            let image_infos = scratch.alloc_from_iter(write.image_infos.iter().map(|image_info| {
+                ffi::DescriptorImageInfo {
+                    sampler: image_info.sampler.handle,
+                    image_view: image_info.image_view.handle,
+                    image_layout: image_info.image_layout.into(),
+                }
+            }));
 
-        let create_info = ffi::DeviceCreateInfo {
-            structure_type: ffi::StructureType::DeviceCreateInfo,
+            ffi::WriteDescriptorSet {
+                structure_type: ffi::StructureType::WriteDescriptorSet,
+                p_next: ptr::null(),
+                dst_set: write.dst_set.handle,
+                dst_binding: write.dst_binding,
+                dst_array_element: write.dst_array_element,
+                descriptor_count: write.descriptor_count,
+                descriptor_type: write.descriptor_type.into(),
+                image_infos: slice_ptr(image_infos),
+                buffer_infos: slice_ptr(buffer_infos),
+                texel_buffer_view: ptr::null(),
+            }
+        }));
+
+        let copies = scratch.alloc_from_iter(copies.iter().map(|copy| ffi::CopyDescriptorSet {
+            structure_type: ffi::StructureType::CopyDescriptorSet,
             p_next: ptr::null(),
-            flags: 0,
-            queue_create_info_count: queue_create_infos.len() as _,
-            queue_create_infos: queue_create_infos.as_ptr(),
-            enabled_layer_count: create_info.layers.len() as _,
-            enabled_layer_names: enabled_layer_names.as_ptr(),
-            enabled_extension_count: create_info.extensions.len() as _,
-            enabled_extension_names: enabled_extension_names.as_ptr(),
-            enabled_features: &enabled_features,
+            src_set: copy.src_set.handle,
+            src_binding: copy.src_binding,
+            src_array_element: copy.src_array_element,
+            dst_set: copy.dst_set.handle,
+            dst_binding: copy.dst_binding,
+            dst_array_element: copy.dst_array_element,
+            descriptor_count: copy.descriptor_count,
+        }));
+
+        unsafe {
+            ffi::vkUpdateDescriptorSets(
+                self.handle,
+                writes.len() as _,
+                slice_ptr(writes),
+                copies.len() as _,
+                slice_ptr(copies),
+            )
         };
 
-        let mut handle = MaybeUninit::<ffi::Device>::uninit();
+        Ok(())
+    }
 
-        let result = unsafe {
-            ffi::vkCreateDevice(
-                physical_device.handle,
-                &create_info,
-                ptr::null(),
-                handle.as_mut_ptr(),
+    //Writes every entry of the template into the set at once, reading the descriptors from
+    //data at the entries' offsets
+    pub fn update_descriptor_set_with_template<T: DescriptorTemplateData>(
+        &self,
+        set: &DescriptorSet,
+        template: &DescriptorUpdateTemplate,
+        data: &T,
+    ) -> Result<(), Error> {
+        if set.device.handle != self.handle || template.device.handle != self.handle {
+            return Err(Error::MixedDevices);
+        }
+
+        if mem::size_of::<T>() < template.data_size {
+            return Err(Error::DescriptorTemplateDataTooSmall {
+                required: template.data_size,
+                provided: mem::size_of::<T>(),
+            });
+        }
+
+        unsafe {
+            ffi::vkUpdateDescriptorSetWithTemplate(
+                self.handle,
+                set.handle,
+                template.handle,
+                data as *const T as _,
             )
         };
 
-        match result {
-            ffi::Result::Success => {
-                let handle = unsafe { handle.assume_init() };
+        Ok(())
+    }
+
+    fn scratch(&self) -> Scratch<'_> {
+        Scratch {
+            scratch: &self.scratch,
+            arena: Some(self.scratch.borrow()),
+        }
+    }
 
-                let device = Self { handle };
+    fn track(device: Rc<Device>, ty: &'static str) -> Rc<Device> {
+        *device.children.borrow_mut().entry(ty).or_insert(0) += 1;
+        device
+    }
 
-                let device = Rc::new(device);
+    fn untrack(&self, ty: &'static str) {
+        let mut children = self.children.borrow_mut();
 
-                Ok(device)
+        if let Some(count) = children.get_mut(ty) {
+            *count -= 1;
+
+            if *count == 0 {
+                children.remove(ty);
             }
-            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
-            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            ffi::Result::InitializationFailed => Err(Error::InitializationFailed),
-            ffi::Result::ExtensionNotPresent => Err(Error::ExtensionNotPresent),
-            ffi::Result::FeatureNotPresent => Err(Error::FeatureNotPresent),
-            ffi::Result::TooManyObjects => Err(Error::TooManyObjects),
-            ffi::Result::DeviceLost => Err(Error::DeviceLost),
-            _ => panic!("unexpected result: {:?}", result),
         }
     }
 
-    pub fn queue(&self, queue_family_index: u32) -> Queue {
-        let mut handle = MaybeUninit::<ffi::Queue>::uninit();
-
-        unsafe {
-            ffi::vkGetDeviceQueue(self.handle, queue_family_index as _, 0, handle.as_mut_ptr())
-        };
-
-        let handle = unsafe { handle.assume_init() };
+    pub fn live_children(&self) -> Vec<(&'static str, usize)> {
+        self.children
+            .borrow()
+            .iter()
+            .map(|(&ty, &count)| (ty, count))
+            .collect()
+    }
 
-        Queue { handle }
+    pub fn destroy_guard(self: &Rc<Self>) -> DestroyGuard {
+        DestroyGuard {
+            device: self.clone(),
+        }
     }
 
     pub fn wait_idle(&self) -> Result<(), Error> {
@@ -3263,7 +7869,7 @@ impl Device {
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             ffi::Result::DeviceLost => Err(Error::DeviceLost),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
@@ -3274,8 +7880,46 @@ impl Drop for Device {
     }
 }
 
+//Hold this in place of the owning Rc<Device>. Dropping it while child objects
+//still reference the device (e.g. through an Rc cycle) asserts in debug builds.
+pub struct DestroyGuard {
+    device: Rc<Device>,
+}
+
+impl Deref for DestroyGuard {
+    type Target = Rc<Device>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.device
+    }
+}
+
+impl Drop for DestroyGuard {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            return;
+        }
+
+        let children = self.device.live_children();
+
+        debug_assert!(
+            children.is_empty(),
+            "device dropped while children are still alive: {}",
+            children
+                .iter()
+                .map(|(ty, count)| format!("{} x{}", ty, count))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
+
 pub struct Queue {
     handle: ffi::Queue,
+    //submit infos, reset at the start of every submit
+    scratch: Arena,
+    //Some when the device was created with synchronization2
+    submit2: Option<ffi::QueueSubmit2>,
 }
 
 impl Queue {
@@ -3284,54 +7928,56 @@ impl Queue {
         submit_infos: &'_ [SubmitInfo],
         fence: Option<&'_ mut Fence>,
     ) -> Result<(), Error> {
-        let wait_semaphores = submit_infos
-            .iter()
-            .map(|submit_info| {
+        self.scratch.reset();
+
+        let scratch = &self.scratch;
+
+        let submit_infos = scratch.alloc_from_iter(submit_infos.iter().map(|submit_info| {
+            let wait_semaphores = scratch.alloc_from_iter(
                 submit_info
                     .wait_semaphores
                     .iter()
-                    .map(|semaphore| semaphore.handle)
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
+                    .map(|semaphore| semaphore.handle),
+            );
 
-        let command_buffers = submit_infos
-            .iter()
-            .map(|submit_info| {
+            let command_buffers = scratch.alloc_from_iter(
                 submit_info
                     .command_buffers
                     .iter()
-                    .map(|command_buffer| command_buffer.handle)
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
+                    .map(|command_buffer| command_buffer.handle),
+            );
 
-        let signal_semaphores = submit_infos
-            .iter()
-            .map(|submit_info| {
+            let signal_semaphores = scratch.alloc_from_iter(
                 submit_info
                     .signal_semaphores
                     .iter()
-                    .map(|semaphore| semaphore.handle)
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
+                    .map(|semaphore| semaphore.handle),
+            );
 
-        let submit_infos = submit_infos
-            .iter()
-            .enumerate()
-            .map(|(i, submit_info)| ffi::SubmitInfo {
+            let p_next = if submit_info.protected {
+                let protected_submit_info = scratch.alloc_from_iter([ffi::ProtectedSubmitInfo {
+                    structure_type: ffi::StructureType::ProtectedSubmitInfo,
+                    p_next: ptr::null(),
+                    protected_submit: true as _,
+                }]);
+
+                protected_submit_info.as_ptr() as _
+            } else {
+                ptr::null()
+            };
+
+            ffi::SubmitInfo {
                 structure_type: ffi::StructureType::SubmitInfo,
-                p_next: ptr::null(),
-                wait_semaphore_count: wait_semaphores[i].len() as _,
-                wait_semaphores: wait_semaphores[i].as_ptr(),
+                p_next,
+                wait_semaphore_count: wait_semaphores.len() as _,
+                wait_semaphores: wait_semaphores.as_ptr(),
                 wait_dst_stage_mask: submit_info.wait_stages.as_ptr() as _,
-                command_buffer_count: command_buffers[i].len() as _,
-                command_buffers: command_buffers[i].as_ptr(),
-                signal_semaphore_count: signal_semaphores[i].len() as _,
-                signal_semaphores: signal_semaphores[i].as_ptr(),
-            })
-            .collect::<Vec<_>>();
+                command_buffer_count: command_buffers.len() as _,
+                command_buffers: command_buffers.as_ptr(),
+                signal_semaphore_count: signal_semaphores.len() as _,
+                signal_semaphores: signal_semaphores.as_ptr(),
+            }
+        }));
 
         let fence = fence.map_or(ffi::Fence::null(), |fence| fence.handle);
 
@@ -3349,7 +7995,80 @@ impl Queue {
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             ffi::Result::DeviceLost => Err(Error::DeviceLost),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    //Stages are given per semaphore, requires synchronization2
+    pub fn submit2(
+        &mut self,
+        submit_infos: &'_ [SubmitInfo2],
+        fence: Option<&'_ mut Fence>,
+    ) -> Result<(), Error> {
+        let f = self.submit2.ok_or(Error::ExtensionNotPresent)?;
+
+        self.scratch.reset();
+
+        let scratch = &self.scratch;
+
+        let semaphore_infos = |infos: &[SemaphoreSubmitInfo]| {
+            scratch.alloc_from_iter(infos.iter().map(|info| ffi::SemaphoreSubmitInfo {
+                structure_type: ffi::StructureType::SemaphoreSubmitInfo,
+                p_next: ptr::null(),
+                semaphore: info.semaphore.handle,
+                value: info.value,
+                stage_mask: info.stage_mask,
+                device_index: 0,
+            }))
+        };
+
+        let submit_infos = scratch.alloc_from_iter(submit_infos.iter().map(|submit_info| {
+            let wait_semaphore_infos = semaphore_infos(submit_info.wait_semaphore_infos);
+
+            let command_buffer_infos = scratch.alloc_from_iter(
+                submit_info
+                    .command_buffers
+                    .iter()
+                    .map(|command_buffer| ffi::CommandBufferSubmitInfo {
+                        structure_type: ffi::StructureType::CommandBufferSubmitInfo,
+                        p_next: ptr::null(),
+                        command_buffer: command_buffer.handle,
+                        device_mask: 0,
+                    }),
+            );
+
+            let signal_semaphore_infos = semaphore_infos(submit_info.signal_semaphore_infos);
+
+            ffi::SubmitInfo2 {
+                structure_type: ffi::StructureType::SubmitInfo2,
+                p_next: ptr::null(),
+                flags: if submit_info.protected { SUBMIT_PROTECTED } else { 0 },
+                wait_semaphore_info_count: wait_semaphore_infos.len() as _,
+                wait_semaphore_infos: wait_semaphore_infos.as_ptr(),
+                command_buffer_info_count: command_buffer_infos.len() as _,
+                command_buffer_infos: command_buffer_infos.as_ptr(),
+                signal_semaphore_info_count: signal_semaphore_infos.len() as _,
+                signal_semaphore_infos: signal_semaphore_infos.as_ptr(),
+            }
+        }));
+
+        let fence = fence.map_or(ffi::Fence::null(), |fence| fence.handle);
+
+        let result = unsafe {
+            f(
+                self.handle,
+                submit_infos.len() as _,
+                submit_infos.as_ptr(),
+                fence,
+            )
+        };
+
+        match result {
+            ffi::Result::Success => Ok(()),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            ffi::Result::DeviceLost => Err(Error::DeviceLost),
+            _ => Err(unexpected_result(result)),
         }
     }
 
@@ -3366,9 +8085,28 @@ impl Queue {
             .map(|swapchain| swapchain.handle)
             .collect::<Vec<_>>();
 
+        let fences = present_info
+            .fences
+            .iter()
+            .map(|fence| fence.handle)
+            .collect::<Vec<_>>();
+
+        let present_fence_info = ffi::SwapchainPresentFenceInfo {
+            structure_type: ffi::StructureType::SwapchainPresentFenceInfo,
+            p_next: ptr::null(),
+            swapchain_count: fences.len() as _,
+            fences: fences.as_ptr(),
+        };
+
+        let p_next = if fences.is_empty() {
+            ptr::null()
+        } else {
+            &present_fence_info as *const _ as _
+        };
+
         let present_info = ffi::PresentInfo {
             structure_type: ffi::StructureType::PresentInfo,
-            p_next: ptr::null(),
+            p_next,
             wait_semaphore_count: wait_semaphores.len() as _,
             wait_semaphores: wait_semaphores.as_ptr(),
             swapchain_count: swapchains.len() as _,
@@ -3389,8 +8127,90 @@ impl Queue {
             ffi::Result::OutOfDate => Err(Error::OutOfDate),
             ffi::Result::SurfaceLost => Err(Error::SurfaceLost),
             ffi::Result::FullScreenExclusiveModeLost => Err(Error::FullScreenExclusiveModeLost),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    pub fn present_across(
+        producer: &mut Queue,
+        present: &mut Queue,
+        present_info: CrossQueuePresentInfo<'_>,
+    ) -> Result<(), Error> {
+        let CrossQueuePresentInfo {
+            producer_queue_family_index,
+            present_queue_family_index,
+            wait_semaphores,
+            wait_stages,
+            command_buffers,
+            acquire_command_buffer,
+            handoff_semaphore,
+            present_semaphore,
+            fence,
+            swapchain,
+            image,
+            image_index,
+            old_layout,
+        } = present_info;
+
+        if producer_queue_family_index == present_queue_family_index {
+            producer.submit(
+                &[SubmitInfo {
+                    wait_semaphores,
+                    wait_stages,
+                    command_buffers,
+                    signal_semaphores: &[present_semaphore],
+                    protected: false,
+                }],
+                fence,
+            )?;
+        } else {
+            producer.submit(
+                &[SubmitInfo {
+                    wait_semaphores,
+                    wait_stages,
+                    command_buffers,
+                    signal_semaphores: &[handoff_semaphore],
+                    protected: false,
+                }],
+                None,
+            )?;
+
+            acquire_command_buffer.record(|mut commands| {
+                let barrier = present_ownership_barrier(
+                    image,
+                    old_layout,
+                    producer_queue_family_index,
+                    present_queue_family_index,
+                );
+
+                commands.pipeline_barrier(
+                    PIPELINE_STAGE_ALL_COMMANDS,
+                    PIPELINE_STAGE_BOTTOM_OF_PIPE,
+                    0,
+                    &[],
+                    &[],
+                    &[barrier],
+                );
+            })?;
+
+            present.submit(
+                &[SubmitInfo {
+                    wait_semaphores: &[handoff_semaphore],
+                    wait_stages: &[PIPELINE_STAGE_ALL_COMMANDS],
+                    command_buffers: &[acquire_command_buffer],
+                    signal_semaphores: &[present_semaphore],
+                    protected: false,
+                }],
+                fence,
+            )?;
         }
+
+        present.present(PresentInfo {
+            wait_semaphores: &[present_semaphore],
+            swapchains: &[swapchain],
+            image_indices: &[image_index],
+            fences: &[],
+        })
     }
 
     pub fn wait_idle(&self) -> Result<(), Error> {
@@ -3401,7 +8221,7 @@ impl Queue {
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             ffi::Result::DeviceLost => Err(Error::DeviceLost),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
@@ -3411,9 +8231,12 @@ pub struct Surface {
     handle: ffi::Surface,
 }
 
-#[cfg(target_os = "windows")]
+#[cfg(all(feature = "window", target_os = "windows"))]
 impl Surface {
-    pub fn new(instance: Rc<Instance>, window: &impl HasRawWindowHandle) -> Rc<Self> {
+    pub fn new(
+        instance: Rc<Instance>,
+        window: &impl HasRawWindowHandle,
+    ) -> Result<Rc<Self>, Error> {
         match window.raw_window_handle() {
             RawWindowHandle::Win32(win32_handle) => {
                 let create_info = ffi::Win32SurfaceCreateInfo {
@@ -3426,7 +8249,7 @@ impl Surface {
 
                 let mut handle = MaybeUninit::<ffi::Surface>::uninit();
 
-                unsafe {
+                let result = unsafe {
                     ffi::vkCreateWin32SurfaceKHR(
                         instance.handle,
                         &create_info,
@@ -3435,34 +8258,91 @@ impl Surface {
                     )
                 };
 
+                surface_result(result)?;
+
                 let handle = unsafe { handle.assume_init() };
 
-                Rc::new(Self { instance, handle })
+                Ok(Rc::new(Self { instance, handle }))
             }
-            RawWindowHandle::Xcb(_) => unimplemented!("xcb unimplemented"),
-            RawWindowHandle::Wayland(_) => unimplemented!("wayland unimplemented"),
-            _ => panic!("unsupported window handle"),
+            _ => Err(Error::UnsupportedWindowSystem),
         }
     }
 }
 
-#[cfg(target_os = "linux")]
+#[cfg(all(feature = "window", target_os = "linux"))]
 impl Surface {
-    pub fn new(instance: Rc<Instance>, window: &impl HasRawWindowHandle) -> Rc<Self> {
+    pub fn new(
+        instance: Rc<Instance>,
+        window: &impl HasRawWindowHandle,
+    ) -> Result<Rc<Self>, Error> {
         match window.raw_window_handle() {
             RawWindowHandle::Xlib(xlib_handle) => {
                 let create_info = ffi::XlibSurfaceCreateInfo {
                     structure_type: ffi::StructureType::XlibSurfaceCreateInfo,
                     p_next: ptr::null(),
                     flags: 0,
-                    display: xlib_handle.display,
-                    window: xlib_handle.window,
+                    display: xlib_handle.display,
+                    window: xlib_handle.window,
+                };
+
+                let mut handle = MaybeUninit::<ffi::Surface>::uninit();
+
+                let result = unsafe {
+                    ffi::vkCreateXlibSurfaceKHR(
+                        instance.handle,
+                        &create_info,
+                        ptr::null(),
+                        handle.as_mut_ptr(),
+                    )
+                };
+
+                surface_result(result)?;
+
+                let handle = unsafe { handle.assume_init() };
+
+                Ok(Rc::new(Self { instance, handle }))
+            }
+            //requires KHR_XCB_SURFACE
+            RawWindowHandle::Xcb(xcb_handle) => {
+                let create_info = ffi::XcbSurfaceCreateInfo {
+                    structure_type: ffi::StructureType::XcbSurfaceCreateInfo,
+                    p_next: ptr::null(),
+                    flags: 0,
+                    connection: xcb_handle.connection as _,
+                    window: xcb_handle.window,
+                };
+
+                let mut handle = MaybeUninit::<ffi::Surface>::uninit();
+
+                let result = unsafe {
+                    ffi::vkCreateXcbSurfaceKHR(
+                        instance.handle,
+                        &create_info,
+                        ptr::null(),
+                        handle.as_mut_ptr(),
+                    )
+                };
+
+                surface_result(result)?;
+
+                let handle = unsafe { handle.assume_init() };
+
+                Ok(Rc::new(Self { instance, handle }))
+            }
+            //requires KHR_WAYLAND_SURFACE
+            RawWindowHandle::Wayland(wayland_handle) => {
+                let create_info = ffi::WaylandSurfaceCreateInfo {
+                    structure_type: ffi::StructureType::WaylandSurfaceCreateInfo,
+                    p_next: ptr::null(),
+                    flags: 0,
+                    display: wayland_handle.display as _,
+                    surface: wayland_handle.surface as _,
                 };
 
                 let mut handle = MaybeUninit::<ffi::Surface>::uninit();
 
-                unsafe {
-                    ffi::vkCreateXlibSurfaceKHR(
+                let result = unsafe {
+                    ffi::vkCreateWaylandSurfaceKHR(
                         instance.handle,
                         &create_info,
                         ptr::null(),
@@ -3470,14 +8350,149 @@ impl Surface {
                     )
                 };
 
+                surface_result(result)?;
+
                 let handle = unsafe { handle.assume_init() };
 
-                Rc::new(Self { instance, handle })
+                Ok(Rc::new(Self { instance, handle }))
             }
-            RawWindowHandle::Xcb(_) => unimplemented!("xcb unimplemented"),
-            RawWindowHandle::Wayland(_) => unimplemented!("wayland unimplemented"),
-            _ => panic!("unsupported window handle"),
+            _ => Err(Error::UnsupportedWindowSystem),
+        }
+    }
+}
+
+#[cfg(all(feature = "window", any(target_os = "macos", target_os = "ios")))]
+impl Surface {
+    pub fn new(
+        instance: Rc<Instance>,
+        window: &impl HasRawWindowHandle,
+    ) -> Result<Rc<Self>, Error> {
+        let layer = match window.raw_window_handle() {
+            #[cfg(target_os = "macos")]
+            RawWindowHandle::AppKit(appkit_handle) => unsafe {
+                objc::appkit_metal_layer(appkit_handle.ns_view as _)
+            },
+            #[cfg(target_os = "ios")]
+            RawWindowHandle::UiKit(uikit_handle) => unsafe {
+                objc::uikit_metal_layer(uikit_handle.ui_view as _)
+            },
+            _ => return Err(Error::UnsupportedWindowSystem),
+        };
+
+        if layer.is_null() {
+            return Err(Error::UnsupportedWindowSystem);
+        }
+
+        //requires EXT_METAL_SURFACE
+        let create_info = ffi::MetalSurfaceCreateInfo {
+            structure_type: ffi::StructureType::MetalSurfaceCreateInfo,
+            p_next: ptr::null(),
+            flags: 0,
+            layer,
+        };
+
+        let mut handle = MaybeUninit::<ffi::Surface>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreateMetalSurfaceEXT(
+                instance.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
+
+        surface_result(result)?;
+
+        let handle = unsafe { handle.assume_init() };
+
+        Ok(Rc::new(Self { instance, handle }))
+    }
+}
+
+//Just enough of the objective-c runtime to find or install the CAMetalLayer
+//that VK_EXT_metal_surface presents to
+#[cfg(all(feature = "window", any(target_os = "macos", target_os = "ios")))]
+mod objc {
+    use std::mem;
+
+    type Id = *mut ();
+    type Sel = *const ();
+
+    #[link(name = "objc")]
+    extern "C" {
+        fn objc_getClass(name: *const i8) -> Id;
+        fn sel_registerName(name: *const i8) -> Sel;
+        fn objc_msgSend();
+    }
+
+    #[link(name = "QuartzCore", kind = "framework")]
+    extern "C" {}
+
+    unsafe fn selector(name: &[u8]) -> Sel {
+        sel_registerName(name.as_ptr() as _)
+    }
+
+    //objc_msgSend has to be called through the exact signature of the method
+    unsafe fn send<A, R>(receiver: Id, name: &[u8], argument: A) -> R {
+        let msg_send: unsafe extern "C" fn(Id, Sel, A) -> R =
+            mem::transmute(objc_msgSend as unsafe extern "C" fn());
+
+        msg_send(receiver, selector(name), argument)
+    }
+
+    unsafe fn get<R>(receiver: Id, name: &[u8]) -> R {
+        let msg_send: unsafe extern "C" fn(Id, Sel) -> R =
+            mem::transmute(objc_msgSend as unsafe extern "C" fn());
+
+        msg_send(receiver, selector(name))
+    }
+
+    unsafe fn metal_layer_class() -> Id {
+        objc_getClass(b"CAMetalLayer\0".as_ptr() as _)
+    }
+
+    unsafe fn is_metal_layer(layer: Id) -> bool {
+        !layer.is_null() && send::<Id, i8>(layer, b"isKindOfClass:\0", metal_layer_class()) != 0
+    }
+
+    //Makes the NSView layer hosting with a new CAMetalLayer unless it already has one
+    #[cfg(target_os = "macos")]
+    pub(super) unsafe fn appkit_metal_layer(view: Id) -> *const () {
+        let layer = get::<Id>(view, b"layer\0");
+
+        if is_metal_layer(layer) {
+            return layer;
+        }
+
+        let layer = get::<Id>(metal_layer_class(), b"layer\0");
+
+        send::<Id, ()>(view, b"setLayer:\0", layer);
+        send::<i8, ()>(view, b"setWantsLayer:\0", 1);
+
+        //render at the backing resolution of retina displays
+        let window = get::<Id>(view, b"window\0");
+
+        if !window.is_null() {
+            let scale = get::<f64>(window, b"backingScaleFactor\0");
+
+            send::<f64, ()>(layer, b"setContentsScale:\0", scale);
+        }
+
+        layer
+    }
+
+    //A UIView's layer cannot be replaced, its class must return CAMetalLayer from layerClass.
+    //Null when it does not.
+    #[cfg(target_os = "ios")]
+    pub(super) unsafe fn uikit_metal_layer(view: Id) -> *const () {
+        let layer = get::<Id>(view, b"layer\0");
+
+        if !is_metal_layer(layer) {
+            return std::ptr::null();
         }
+
+        layer
     }
 }
 
@@ -3488,6 +8503,7 @@ impl Drop for Surface {
 }
 
 pub struct SwapchainCreateInfo<'a> {
+    pub flags: u32,
     pub surface: &'a Surface,
     pub min_image_count: u32,
     pub image_format: Format,
@@ -3501,7 +8517,8 @@ pub struct SwapchainCreateInfo<'a> {
     pub composite_alpha: CompositeAlpha,
     pub present_mode: PresentMode,
     pub clipped: bool,
-    pub old_swapchain: Option<Swapchain>,
+    //may be dropped as soon as this returns, once its images are no longer in use
+    pub old_swapchain: Option<&'a Swapchain>,
 }
 
 pub struct Swapchain {
@@ -3509,16 +8526,34 @@ pub struct Swapchain {
     handle: ffi::Swapchain,
 }
 
+//CIE 1931 chromaticity coordinates
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct XyColor {
+    pub x: f32,
+    pub y: f32,
+}
+
+//The mastering display and content light levels of what is presented, luminances are
+//in nits
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct HdrMetadata {
+    pub display_primary_red: XyColor,
+    pub display_primary_green: XyColor,
+    pub display_primary_blue: XyColor,
+    pub white_point: XyColor,
+    pub max_luminance: f32,
+    pub min_luminance: f32,
+    pub max_content_light_level: f32,
+    pub max_frame_average_light_level: f32,
+}
+
 impl Swapchain {
     pub fn new(device: Rc<Device>, create_info: SwapchainCreateInfo<'_>) -> Result<Self, Error> {
-        let image_format = match create_info.image_format {
-            Format::Bgra8Srgb => ffi::Format::Bgra8Srgb,
-            _ => unimplemented!(),
-        };
+        let image_format = create_info.image_format.into();
 
         let image_color_space = match create_info.image_color_space {
             ColorSpace::SrgbNonlinear => ffi::ColorSpace::SrgbNonlinear,
-            _ => unimplemented!(),
+            _ => return Err(Error::UnsupportedSwapchainConfiguration),
         };
 
         let image_extent = [
@@ -3530,14 +8565,14 @@ impl Swapchain {
 
         let image_sharing_mode = match create_info.image_sharing_mode {
             SharingMode::Exclusive => ffi::SharingMode::Exclusive,
-            _ => unimplemented!(),
+            SharingMode::Concurrent => ffi::SharingMode::Concurrent,
         };
 
-        let queue_family_indices = unsafe { mem::transmute(&create_info.queue_family_indices) };
+        let queue_family_indices = create_info.queue_family_indices.as_ptr();
 
         let composite_alpha = match create_info.composite_alpha {
             CompositeAlpha::Opaque => ffi::CompositeAlpha::Opaque,
-            _ => unimplemented!(),
+            _ => return Err(Error::UnsupportedSwapchainConfiguration),
         };
 
         let present_mode = match create_info.present_mode {
@@ -3547,18 +8582,14 @@ impl Swapchain {
             PresentMode::FifoRelaxed => ffi::PresentMode::FifoRelaxed,
         };
 
-        //if this is not extracted, and handle is not declared separately,
-        //then swapchain will be dropped too early.
-        let old_swapchain = create_info.old_swapchain;
-
-        let old_swapchain_handle = old_swapchain
-            .as_ref()
+        let old_swapchain_handle = create_info
+            .old_swapchain
             .map_or(ffi::Swapchain::null(), |swapchain| swapchain.handle);
 
         let create_info = ffi::SwapchainCreateInfo {
             structure_type: ffi::StructureType::SwapchainCreateInfo,
             p_next: ptr::null(),
-            flags: 0,
+            flags: create_info.flags,
             surface: create_info.surface.handle,
             min_image_count: create_info.min_image_count,
             image_format,
@@ -3591,7 +8622,10 @@ impl Swapchain {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let swapchain = Self { device, handle };
+                let swapchain = Self {
+                    device: Device::track(device, "Swapchain"),
+                    handle,
+                };
 
                 Ok(swapchain)
             }
@@ -3602,8 +8636,47 @@ impl Swapchain {
             ffi::Result::NativeWindowInUse => Err(Error::NativeWindowInUse),
             ffi::Result::InitializationFailed => Err(Error::InitializationFailed),
             ffi::Result::CompressionExhausted => Err(Error::CompressionExhausted),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    //Applies from the next present on, requires EXT_HDR_METADATA
+    pub fn set_hdr_metadata(&self, metadata: &HdrMetadata) -> Result<(), Error> {
+        if !self.device.is_extension_enabled(EXT_HDR_METADATA) {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f_name = CStr::from_bytes_with_nul(b"vkSetHdrMetadataEXT\0").unwrap();
+
+        let f = unsafe { ffi::vkGetDeviceProcAddr(self.device.handle, f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
         }
+
+        let f = unsafe { mem::transmute::<_, ffi::SetHdrMetadata>(f) };
+
+        let xy = |color: XyColor| ffi::XyColor {
+            x: color.x,
+            y: color.y,
+        };
+
+        let metadata = ffi::HdrMetadata {
+            structure_type: ffi::StructureType::HdrMetadata,
+            p_next: ptr::null(),
+            display_primary_red: xy(metadata.display_primary_red),
+            display_primary_green: xy(metadata.display_primary_green),
+            display_primary_blue: xy(metadata.display_primary_blue),
+            white_point: xy(metadata.white_point),
+            max_luminance: metadata.max_luminance,
+            min_luminance: metadata.min_luminance,
+            max_content_light_level: metadata.max_content_light_level,
+            max_frame_average_light_level: metadata.max_frame_average_light_level,
+        };
+
+        unsafe { f(self.device.handle, 1, &self.handle, &metadata) };
+
+        Ok(())
     }
 
     pub fn images(&self) -> Vec<Image> {
@@ -3634,7 +8707,7 @@ impl Swapchain {
         let swapchain_images = swapchain_images
             .into_iter()
             .map(|handle| Image {
-                device: self.device.clone(),
+                device: Device::track(self.device.clone(), "Image"),
                 handle,
                 user: false,
             })
@@ -3643,12 +8716,14 @@ impl Swapchain {
         swapchain_images
     }
 
+    //The image index and whether the swapchain is suboptimal, in which case the image is
+    //acquired all the same and semaphore and fence are still signaled
     pub fn acquire_next_image(
         &mut self,
         timeout: u64,
         semaphore: Option<&'_ mut Semaphore>,
         fence: Option<&'_ mut Fence>,
-    ) -> Result<u32, Error> {
+    ) -> Result<(u32, bool), Error> {
         let mut image_index = 0;
 
         let semaphore = semaphore.map_or(ffi::Semaphore::null(), |semaphore| semaphore.handle);
@@ -3667,21 +8742,25 @@ impl Swapchain {
         };
         //TODO this might be wrong
         match result {
-            ffi::Result::Success | ffi::Result::Timeout | ffi::Result::NotReady => Ok(image_index),
-            ffi::Result::Suboptimal => Err(Error::Suboptimal),
+            ffi::Result::Success | ffi::Result::Timeout | ffi::Result::NotReady => {
+                Ok((image_index, false))
+            }
+            ffi::Result::Suboptimal => Ok((image_index, true)),
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             ffi::Result::DeviceLost => Err(Error::DeviceLost),
             ffi::Result::OutOfDate => Err(Error::OutOfDate),
             ffi::Result::SurfaceLost => Err(Error::SurfaceLost),
             ffi::Result::FullScreenExclusiveModeLost => Err(Error::FullScreenExclusiveModeLost),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
 
 impl Drop for Swapchain {
     fn drop(&mut self) {
+        self.device.untrack("Swapchain");
+
         unsafe { ffi::vkDestroySwapchainKHR(self.device.handle, self.handle, ptr::null()) };
     }
 }
@@ -3692,23 +8771,43 @@ pub enum ImageType {
     ThreeDim,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ImageTiling {
     Optimal,
     Linear,
+    DrmFormatModifier,
 }
 
 pub struct ImageCreateInfo {
+    //IMAGE_CREATE_* bits, IMAGE_CREATE_PROTECTED to bind MEMORY_PROPERTY_PROTECTED memory
+    pub flags: u32,
     pub image_type: ImageType,
     pub format: Format,
     pub extent: Extent3d,
     pub mip_levels: u32,
     pub array_layers: u32,
-    pub samples: u32,
+    pub samples: SampleCount,
     pub tiling: ImageTiling,
     pub image_usage: u32,
     pub initial_layout: ImageLayout,
 }
 
+pub struct SubresourceLayout {
+    pub offset: u64,
+    pub size: u64,
+    pub row_pitch: u64,
+    pub array_pitch: u64,
+    pub depth_pitch: u64,
+}
+
+pub struct DmaBufImportInfo<'a> {
+    //owned by the memory once the import succeeded, dup it to keep using it. Still the
+    //caller's to close when the import fails.
+    pub fd: i32,
+    pub drm_format_modifier: u64,
+    pub plane_layouts: &'a [SubresourceLayout],
+}
+
 pub struct Image {
     device: Rc<Device>,
     handle: ffi::Image,
@@ -3720,7 +8819,7 @@ impl Image {
         let create_info = ffi::ImageCreateInfo {
             structure_type: ffi::StructureType::ImageCreateInfo,
             p_next: ptr::null(),
-            flags: 0,
+            flags: create_info.flags,
             image_type: create_info.image_type.into(),
             extent: [
                 create_info.extent.0,
@@ -3733,82 +8832,279 @@ impl Image {
             tiling: create_info.tiling.into(),
             initial_layout: create_info.initial_layout.into(),
             image_usage: create_info.image_usage as _,
-            samples: create_info.samples,
+            samples: create_info.samples.to_vk(),
+            sharing_mode: ffi::SharingMode::Exclusive,
+            queue_family_index_count: 0,
+            queue_family_indices: ptr::null(),
+        };
+
+        let mut handle = MaybeUninit::<ffi::Image>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreateImage(
+                device.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                let image = Self {
+                    device: Device::track(device, "Image"),
+                    handle,
+                    user: true,
+                };
+
+                Ok(image)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+            ffi::Result::CompressionExhausted => Err(Error::CompressionExhausted)?,
+            _ => Err(unexpected_result(result))?,
+        }
+    }
+
+    pub fn memory_requirements(&self) -> MemoryRequirements {
+        let mut memory_requirements = MaybeUninit::<ffi::MemoryRequirements>::uninit();
+
+        unsafe {
+            ffi::vkGetImageMemoryRequirements(
+                self.device.handle,
+                self.handle,
+                memory_requirements.as_mut_ptr(),
+            )
+        };
+
+        let memory_requirements = unsafe { memory_requirements.assume_init() };
+
+        MemoryRequirements {
+            size: memory_requirements.size,
+            alignment: memory_requirements.alignment,
+            memory_type: memory_requirements.memory_type,
+        }
+    }
+
+    pub fn bind_memory(&mut self, memory: &Memory) -> Result<(), Error> {
+        self.bind_memory_at(memory, 0)
+    }
+
+    //offset must respect the alignment from memory_requirements
+    pub fn bind_memory_at(&mut self, memory: &Memory, offset: u64) -> Result<(), Error> {
+        if !self.user {
+            return Err(Error::ImageNotBindable);
+        }
+
+        let result = unsafe {
+            ffi::vkBindImageMemory(self.device.handle, self.handle, memory.handle, offset)
+        };
+
+        match result {
+            ffi::Result::Success => Ok(()),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            ffi::Result::InvalidOpaqueCaptureAddress => Err(Error::InvalidOpaqueCaptureAddress),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    //physical_device must be the one device was created from, one plane layout is needed
+    //for each of the modifier's memory planes
+    pub fn import_dma_buf(
+        device: Rc<Device>,
+        physical_device: &PhysicalDevice,
+        create_info: ImageCreateInfo,
+        import_info: DmaBufImportInfo<'_>,
+    ) -> Result<(Self, Memory), Error> {
+        let expected = physical_device
+            .drm_format_modifier_plane_count(create_info.format, import_info.drm_format_modifier)
+            .ok_or(Error::FormatNotSupported)?;
+
+        let planes = import_info.plane_layouts.len() as u32;
+
+        if planes != expected {
+            return Err(Error::DrmFormatModifierPlaneCountMismatch { planes, expected });
+        }
+
+        let plane_layouts = import_info
+            .plane_layouts
+            .iter()
+            .map(|plane_layout| ffi::SubresourceLayout {
+                offset: plane_layout.offset,
+                size: plane_layout.size,
+                row_pitch: plane_layout.row_pitch,
+                array_pitch: plane_layout.array_pitch,
+                depth_pitch: plane_layout.depth_pitch,
+            })
+            .collect::<Vec<_>>();
+
+        let drm_format_modifier_create_info = ffi::ImageDrmFormatModifierExplicitCreateInfo {
+            structure_type: ffi::StructureType::ImageDrmFormatModifierExplicitCreateInfo,
+            p_next: ptr::null(),
+            drm_format_modifier: import_info.drm_format_modifier,
+            drm_format_modifier_plane_count: plane_layouts.len() as _,
+            plane_layouts: plane_layouts.as_ptr(),
+        };
+
+        let external_memory_create_info = ffi::ExternalMemoryImageCreateInfo {
+            structure_type: ffi::StructureType::ExternalMemoryImageCreateInfo,
+            p_next: &drm_format_modifier_create_info as *const _ as _,
+            handle_types: EXTERNAL_MEMORY_HANDLE_TYPE_DMA_BUF,
+        };
+
+        let create_info = ffi::ImageCreateInfo {
+            structure_type: ffi::StructureType::ImageCreateInfo,
+            p_next: &external_memory_create_info as *const _ as _,
+            flags: create_info.flags,
+            image_type: create_info.image_type.into(),
+            extent: [
+                create_info.extent.0,
+                create_info.extent.1,
+                create_info.extent.2,
+            ],
+            mip_levels: create_info.mip_levels,
+            array_layers: create_info.array_layers,
+            format: create_info.format.into(),
+            tiling: ffi::ImageTiling::DrmFormatModifier,
+            initial_layout: create_info.initial_layout.into(),
+            image_usage: create_info.image_usage as _,
+            samples: create_info.samples.to_vk(),
             sharing_mode: ffi::SharingMode::Exclusive,
             queue_family_index_count: 0,
             queue_family_indices: ptr::null(),
         };
 
-        let mut handle = MaybeUninit::<ffi::Image>::uninit();
+        let mut handle = MaybeUninit::<ffi::Image>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreateImage(
+                device.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
+
+        let mut image = match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                Self {
+                    device: Device::track(device.clone(), "Image"),
+                    handle,
+                    user: true,
+                }
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+            ffi::Result::CompressionExhausted => Err(Error::CompressionExhausted)?,
+            _ => Err(unexpected_result(result))?,
+        };
+
+        let f_name = CStr::from_bytes_with_nul(b"vkGetMemoryFdPropertiesKHR\0").unwrap();
+
+        let f = unsafe { ffi::vkGetDeviceProcAddr(device.handle, f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f = unsafe { mem::transmute::<_, ffi::GetMemoryFdProperties>(f) };
+
+        let mut memory_fd_properties = ffi::MemoryFdProperties {
+            structure_type: ffi::StructureType::MemoryFdProperties,
+            p_next: ptr::null_mut(),
+            memory_type_bits: 0,
+        };
+
+        let result = unsafe {
+            f(
+                device.handle,
+                EXTERNAL_MEMORY_HANDLE_TYPE_DMA_BUF,
+                import_info.fd,
+                &mut memory_fd_properties,
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {}
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+            ffi::Result::InvalidExternalHandle => Err(Error::InvalidExternalHandle)?,
+            _ => Err(unexpected_result(result))?,
+        }
+
+        let requirements = image.memory_requirements();
+
+        let memory_type_bits = requirements.memory_type & memory_fd_properties.memory_type_bits;
+
+        if memory_type_bits == 0 {
+            return Err(Error::InvalidExternalHandle);
+        }
+
+        let dedicated_allocate_info = ffi::MemoryDedicatedAllocateInfo {
+            structure_type: ffi::StructureType::MemoryDedicatedAllocateInfo,
+            p_next: ptr::null(),
+            image: image.handle,
+            buffer: ffi::Buffer::null(),
+        };
+
+        let import_memory_fd_info = ffi::ImportMemoryFdInfo {
+            structure_type: ffi::StructureType::ImportMemoryFdInfo,
+            p_next: &dedicated_allocate_info as *const _ as _,
+            handle_type: EXTERNAL_MEMORY_HANDLE_TYPE_DMA_BUF,
+            fd: import_info.fd,
+        };
+
+        let allocate_info = ffi::MemoryAllocateInfo {
+            structure_type: ffi::StructureType::MemoryAllocateInfo,
+            p_next: &import_memory_fd_info as *const _ as _,
+            size: requirements.size,
+            memory_type_index: memory_type_bits.trailing_zeros(),
+        };
+
+        let mut handle = MaybeUninit::<ffi::DeviceMemory>::uninit();
 
         let result = unsafe {
-            ffi::vkCreateImage(
+            ffi::vkAllocateMemory(
                 device.handle,
-                &create_info,
+                &allocate_info,
                 ptr::null(),
                 handle.as_mut_ptr(),
             )
         };
 
-        match result {
+        let memory = match result {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let image = Self {
-                    device,
+                Memory {
+                    device: Device::track(device, "Memory"),
                     handle,
-                    user: true,
-                };
-
-                Ok(image)
+                    size: requirements.size,
+                    mem: None,
+                    lazily_allocated: false,
+                    host_coherent: false,
+                }
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
-            ffi::Result::CompressionExhausted => Err(Error::CompressionExhausted)?,
-            _ => panic!("unexpected result: {:?}", result),
-        }
-    }
-
-    pub fn memory_requirements(&self) -> MemoryRequirements {
-        let mut memory_requirements = MaybeUninit::<ffi::MemoryRequirements>::uninit();
-
-        unsafe {
-            ffi::vkGetImageMemoryRequirements(
-                self.device.handle,
-                self.handle,
-                memory_requirements.as_mut_ptr(),
-            )
+            ffi::Result::InvalidExternalHandle => Err(Error::InvalidExternalHandle)?,
+            _ => Err(unexpected_result(result))?,
         };
 
-        let memory_requirements = unsafe { memory_requirements.assume_init() };
-
-        MemoryRequirements {
-            size: memory_requirements.size,
-            alignment: memory_requirements.alignment,
-            memory_type: memory_requirements.memory_type,
-        }
-    }
-
-    pub fn bind_memory(&mut self, memory: &Memory) -> Result<(), Error> {
-        if !self.user {
-            panic!("cannot bind memory to non-user image");
-        }
-
-        let result =
-            unsafe { ffi::vkBindImageMemory(self.device.handle, self.handle, memory.handle, 0) };
+        image.bind_memory(&memory)?;
 
-        match result {
-            ffi::Result::Success => Ok(()),
-            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
-            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            ffi::Result::InvalidOpaqueCaptureAddress => Err(Error::InvalidOpaqueCaptureAddress),
-            _ => panic!("unexpected result: {:?}", result),
-        }
+        Ok((image, memory))
     }
 }
 
 impl Drop for Image {
     fn drop(&mut self) {
+        self.device.untrack("Image");
+
         if self.user {
             unsafe { ffi::vkDestroyImage(self.device.handle, self.handle, ptr::null()) };
         }
@@ -3860,10 +9156,34 @@ pub struct ImageViewCreateInfo<'a> {
 pub struct ImageView {
     device: Rc<Device>,
     handle: ffi::ImageView,
+    layer_count: u32,
 }
 
 impl ImageView {
     pub fn new(device: Rc<Device>, create_info: ImageViewCreateInfo) -> Result<Self, Error> {
+        Self::new_chained(device, create_info, ptr::null())
+    }
+
+    //Views of multi-planar formats that are sampled must use the same conversion as the sampler
+    pub fn new_with_conversion(
+        device: Rc<Device>,
+        create_info: ImageViewCreateInfo,
+        conversion: &SamplerYcbcrConversion,
+    ) -> Result<Self, Error> {
+        let conversion_info = ffi::SamplerYcbcrConversionInfo {
+            structure_type: ffi::StructureType::SamplerYcbcrConversionInfo,
+            p_next: ptr::null(),
+            conversion: conversion.handle,
+        };
+
+        Self::new_chained(device, create_info, &conversion_info as *const _ as _)
+    }
+
+    fn new_chained(
+        device: Rc<Device>,
+        create_info: ImageViewCreateInfo,
+        p_next: *const (),
+    ) -> Result<Self, Error> {
         //TODO change these to From impl
         let view_type = match create_info.view_type {
             ImageViewType::OneDim => ffi::ImageViewType::OneDim,
@@ -3877,23 +9197,9 @@ impl ImageView {
 
         let format = create_info.format.into();
 
-        //TODO convert to From<non-ffi> for ffi
-        let swizzle_f = |component| match component {
-            ComponentSwizzle::Identity => ffi::ComponentSwizzle::Identity,
-            ComponentSwizzle::Zero => ffi::ComponentSwizzle::Zero,
-            ComponentSwizzle::One => ffi::ComponentSwizzle::One,
-            ComponentSwizzle::R => ffi::ComponentSwizzle::R,
-            ComponentSwizzle::G => ffi::ComponentSwizzle::G,
-            ComponentSwizzle::B => ffi::ComponentSwizzle::B,
-            ComponentSwizzle::A => ffi::ComponentSwizzle::A,
-        };
+        let components = ffi_component_mapping(&create_info.components);
 
-        let components = ffi::ComponentMapping {
-            r: swizzle_f(create_info.components.r),
-            g: swizzle_f(create_info.components.g),
-            b: swizzle_f(create_info.components.b),
-            a: swizzle_f(create_info.components.a),
-        };
+        let layer_count = create_info.subresource_range.layer_count;
 
         let subresource_range = ffi::ImageSubresourceRange {
             aspect_mask: create_info.subresource_range.aspect_mask,
@@ -3905,7 +9211,7 @@ impl ImageView {
 
         let create_info = ffi::ImageViewCreateInfo {
             structure_type: ffi::StructureType::ImageViewCreateInfo,
-            p_next: ptr::null(),
+            p_next,
             flags: 0,
             image: create_info.image.handle,
             view_type,
@@ -3929,23 +9235,49 @@ impl ImageView {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let image_view = Self { device, handle };
+                let image_view = Self {
+                    device: Device::track(device, "ImageView"),
+                    handle,
+                    layer_count,
+                };
 
                 Ok(image_view)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
 
 impl Drop for ImageView {
     fn drop(&mut self) {
+        self.device.untrack("ImageView");
+
         unsafe { ffi::vkDestroyImageView(self.device.handle, self.handle, ptr::null()) };
     }
 }
 
+//TODO convert to From<non-ffi> for ffi
+fn ffi_component_mapping(components: &ComponentMapping) -> ffi::ComponentMapping {
+    let swizzle_f = |component: &ComponentSwizzle| match component {
+        ComponentSwizzle::Identity => ffi::ComponentSwizzle::Identity,
+        ComponentSwizzle::Zero => ffi::ComponentSwizzle::Zero,
+        ComponentSwizzle::One => ffi::ComponentSwizzle::One,
+        ComponentSwizzle::R => ffi::ComponentSwizzle::R,
+        ComponentSwizzle::G => ffi::ComponentSwizzle::G,
+        ComponentSwizzle::B => ffi::ComponentSwizzle::B,
+        ComponentSwizzle::A => ffi::ComponentSwizzle::A,
+    };
+
+    ffi::ComponentMapping {
+        r: swizzle_f(&components.r),
+        g: swizzle_f(&components.g),
+        b: swizzle_f(&components.b),
+        a: swizzle_f(&components.a),
+    }
+}
+
 pub struct ShaderModuleCreateInfo<'a> {
     pub code: &'a [u32],
 }
@@ -3980,20 +9312,32 @@ impl ShaderModule {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let shader_module = Self { device, handle };
+                let shader_module = Self {
+                    device: Device::track(device, "ShaderModule"),
+                    handle,
+                };
 
                 Ok(shader_module)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             ffi::Result::InvalidShader => Err(Error::InvalidShader),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
 
+//Hashes the handle, identifying the object for as long as it is alive
+impl Hash for ShaderModule {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.handle.hash(state);
+    }
+}
+
 impl Drop for ShaderModule {
     fn drop(&mut self) {
+        self.device.untrack("ShaderModule");
+
         unsafe { ffi::vkDestroyShaderModule(self.device.handle, self.handle, ptr::null()) };
     }
 }
@@ -4025,9 +9369,83 @@ pub struct VertexInputAttributeDescription {
     pub offset: u32,
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct VertexInputBindingDivisorDescription {
+    pub binding: u32,
+    pub divisor: u32,
+}
+
 pub struct PipelineVertexInputStateCreateInfo<'a> {
     pub bindings: &'a [VertexInputBindingDescription],
     pub attributes: &'a [VertexInputAttributeDescription],
+    //requires EXT_VERTEX_ATTRIBUTE_DIVISOR
+    pub binding_divisors: &'a [VertexInputBindingDivisorDescription],
+}
+
+impl PipelineVertexInputStateCreateInfo<'_> {
+    //Drivers differ in how they fail past these limits, so catch it here
+    fn validate(&self, limits: &PhysicalDeviceLimits) -> Result<(), Error> {
+        if self.bindings.len() > limits.max_vertex_input_bindings as usize {
+            Err(Error::VertexInputBindingsExceedLimit {
+                count: self.bindings.len() as _,
+                max: limits.max_vertex_input_bindings,
+            })?;
+        }
+
+        if self.attributes.len() > limits.max_vertex_input_attributes as usize {
+            Err(Error::VertexInputAttributesExceedLimit {
+                count: self.attributes.len() as _,
+                max: limits.max_vertex_input_attributes,
+            })?;
+        }
+
+        for binding in self.bindings {
+            if binding.binding >= limits.max_vertex_input_bindings {
+                Err(Error::VertexInputBindingExceedsLimit {
+                    binding: binding.binding,
+                    max: limits.max_vertex_input_bindings,
+                })?;
+            }
+
+            if binding.stride > limits.max_vertex_input_binding_stride as usize {
+                Err(Error::VertexInputBindingStrideExceedsLimit {
+                    binding: binding.binding,
+                    stride: binding.stride,
+                    max: limits.max_vertex_input_binding_stride,
+                })?;
+            }
+        }
+
+        for attribute in self.attributes {
+            if attribute.location >= limits.max_vertex_input_attributes {
+                Err(Error::VertexInputLocationExceedsLimit {
+                    location: attribute.location,
+                    max: limits.max_vertex_input_attributes,
+                })?;
+            }
+
+            if attribute.offset > limits.max_vertex_input_attribute_offset {
+                Err(Error::VertexInputAttributeOffsetExceedsLimit {
+                    location: attribute.location,
+                    offset: attribute.offset,
+                    max: limits.max_vertex_input_attribute_offset,
+                })?;
+            }
+
+            if !self
+                .bindings
+                .iter()
+                .any(|binding| binding.binding == attribute.binding)
+            {
+                Err(Error::VertexInputAttributeBindingMissing {
+                    location: attribute.location,
+                    binding: attribute.binding,
+                })?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -4037,6 +9455,8 @@ pub enum PrimitiveTopology {
     LineStrip,
     TriangleList,
     TriangleStrip,
+    LineListWithAdjacency,
+    LineStripWithAdjacency,
 }
 
 pub struct PipelineInputAssemblyStateCreateInfo {
@@ -4081,6 +9501,8 @@ pub enum FrontFace {
 
 pub struct PipelineRasterizationStateCreateInfo {
     pub depth_clamp_enable: bool,
+    //overrides clipping independently of depth_clamp_enable, requires EXT_DEPTH_CLIP_ENABLE
+    pub depth_clip_enable: Option<bool>,
     pub rasterizer_discard_enable: bool,
     pub polygon_mode: PolygonMode,
     pub cull_mode: u32,
@@ -4090,15 +9512,50 @@ pub struct PipelineRasterizationStateCreateInfo {
     pub depth_bias_clamp: f32,
     pub depth_bias_slope_factor: f32,
     pub line_width: f32,
+    //anything but Default requires EXT_LINE_RASTERIZATION
+    pub line_rasterization_mode: LineRasterizationMode,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LineRasterizationMode {
+    Default,
+    Rectangular,
+    Bresenham,
+    RectangularSmooth,
+}
+
+//Every attachment of the subpass or rendering must have rasterization_samples samples
+pub struct PipelineMultisampleStateCreateInfo {
+    pub rasterization_samples: SampleCount,
+    //shades at least min_sample_shading of the samples individually, requires the
+    //sample_rate_shading feature
+    pub sample_shading_enable: bool,
+    pub min_sample_shading: f32,
+    pub alpha_to_coverage_enable: bool,
+    pub alpha_to_one_enable: bool,
+}
+
+impl Default for PipelineMultisampleStateCreateInfo {
+    fn default() -> Self {
+        Self {
+            rasterization_samples: SampleCount::One,
+            sample_shading_enable: false,
+            min_sample_shading: 1.0,
+            alpha_to_coverage_enable: false,
+            alpha_to_one_enable: false,
+        }
+    }
 }
 
-pub struct PipelineMultisampleStateCreateInfo {}
-
 pub struct PipelineDepthStencilStateCreateInfo {
     pub depth_test_enable: bool,
     pub depth_write_enable: bool,
     pub depth_compare_op: CompareOp,
     pub depth_bounds_test_enable: bool,
+    pub stencil_test_enable: bool,
+    //for front and back facing polygons, lines and points count as front facing
+    pub front: StencilOpState,
+    pub back: StencilOpState,
     pub min_depth_bounds: f32,
     pub max_depth_bounds: f32,
 }
@@ -4142,37 +9599,154 @@ pub struct PipelineColorBlendStateCreateInfo<'a> {
 #[derive(Clone, Copy)]
 pub enum DynamicState {
     Viewport,
+    Scissor,
+    BlendConstants,
 }
 
 pub struct PipelineDynamicStateCreateInfo<'a> {
     pub dynamic_states: &'a [DynamicState],
 }
 
+#[derive(Clone, Copy)]
+pub struct PushConstantRange {
+    pub stage_flags: u32,
+    pub offset: u32,
+    pub size: u32,
+}
+
 pub struct PipelineLayoutCreateInfo<'a> {
     pub set_layouts: &'a [&'a DescriptorSetLayout],
+    pub push_constant_ranges: &'a [PushConstantRange],
 }
 
 pub struct PipelineLayout {
     device: Rc<Device>,
     handle: ffi::PipelineLayout,
+    push_constant_ranges: Vec<PushConstantRange>,
+}
+
+//max is maxPushConstantsSize
+fn validate_push_constant_ranges(ranges: &[PushConstantRange], max: u32) -> Result<(), Error> {
+    let mut stages = 0;
+
+    for range in ranges {
+        if range.stage_flags == 0 {
+            Err(Error::PushConstantRangeWithoutStages {
+                offset: range.offset,
+                size: range.size,
+            })?;
+        }
+
+        if range.offset % 4 != 0 || range.size == 0 || range.size % 4 != 0 {
+            Err(Error::PushConstantRangeMisaligned {
+                offset: range.offset,
+                size: range.size,
+            })?;
+        }
+
+        if range.offset >= max || range.size > max - range.offset {
+            Err(Error::PushConstantRangeExceedsLimit {
+                offset: range.offset,
+                size: range.size,
+                max,
+            })?;
+        }
+
+        if range.stage_flags & stages != 0 {
+            Err(Error::PushConstantStagesOverlap {
+                stage_flags: range.stage_flags & stages,
+            })?;
+        }
+
+        stages |= range.stage_flags;
+    }
+
+    Ok(())
+}
+
+//The size of data once every byte of it is in ranges for exactly stage_flags
+fn validate_push_constants(
+    ranges: &[PushConstantRange],
+    stage_flags: u32,
+    offset: u32,
+    data: &[u8],
+) -> Result<u32, Error> {
+    let size = u32::try_from(data.len()).unwrap_or(u32::MAX);
+
+    let out_of_range = Error::PushConstantsOutOfRange {
+        stage_flags,
+        offset,
+        size,
+    };
+
+    if size as usize != data.len()
+        || stage_flags == 0
+        || size == 0
+        || offset % 4 != 0
+        || size % 4 != 0
+    {
+        Err(out_of_range)?;
+    }
+
+    let Some(end) = offset.checked_add(size) else {
+        return Err(out_of_range);
+    };
+
+    for byte in offset..end {
+        let mut covered = 0;
+
+        for range in ranges {
+            if byte < range.offset || byte - range.offset >= range.size {
+                continue;
+            }
+
+            if range.stage_flags & !stage_flags != 0 {
+                Err(out_of_range)?;
+            }
+
+            covered |= range.stage_flags;
+        }
+
+        if covered & stage_flags != stage_flags {
+            Err(out_of_range)?;
+        }
+    }
+
+    Ok(size)
 }
 
 impl PipelineLayout {
     pub fn new(device: Rc<Device>, create_info: PipelineLayoutCreateInfo) -> Result<Self, Error> {
+        validate_push_constant_ranges(
+            create_info.push_constant_ranges,
+            device.limits.max_push_constants_size,
+        )?;
+
         let set_layouts = create_info
             .set_layouts
             .iter()
             .map(|set_layout| set_layout.handle)
             .collect::<Vec<_>>();
 
+        let push_constant_ranges = create_info.push_constant_ranges.to_vec();
+
+        let ranges = push_constant_ranges
+            .iter()
+            .map(|range| ffi::PushConstantRange {
+                stage_flags: range.stage_flags,
+                offset: range.offset,
+                size: range.size,
+            })
+            .collect::<Vec<_>>();
+
         let create_info = ffi::PipelineLayoutCreateInfo {
             structure_type: ffi::StructureType::PipelineLayoutCreateInfo,
             p_next: ptr::null(),
             flags: 0,
             set_layout_count: create_info.set_layouts.len() as _,
             set_layouts: set_layouts.as_ptr(),
-            push_constant_range_count: 0,
-            push_constant_ranges: ptr::null(),
+            push_constant_range_count: ranges.len() as _,
+            push_constant_ranges: ranges.as_ptr(),
         };
 
         let mut handle = MaybeUninit::<ffi::PipelineLayout>::uninit();
@@ -4190,19 +9764,25 @@ impl PipelineLayout {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let pipeline_layout = Self { device, handle };
+                let pipeline_layout = Self {
+                    device: Device::track(device, "PipelineLayout"),
+                    handle,
+                    push_constant_ranges,
+                };
 
                 Ok(pipeline_layout)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
 
 impl Drop for PipelineLayout {
     fn drop(&mut self) {
+        self.device.untrack("PipelineLayout");
+
         unsafe { ffi::vkDestroyPipelineLayout(self.device.handle, self.handle, ptr::null()) };
     }
 }
@@ -4237,7 +9817,7 @@ pub enum ImageLayout {
 #[derive(Clone, Copy)]
 pub struct AttachmentDescription {
     pub format: Format,
-    pub samples: u32,
+    pub samples: SampleCount,
     pub load_op: AttachmentLoadOp,
     pub store_op: AttachmentStoreOp,
     pub stencil_load_op: AttachmentLoadOp,
@@ -4454,50 +10034,227 @@ impl RenderPass {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let render_pass = Self { device, handle };
+                let render_pass = Self {
+                    device: Device::track(device, "RenderPass"),
+                    handle,
+                };
 
                 Ok(render_pass)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
 
+//Hashes the handle, identifying the object for as long as it is alive
+impl Hash for RenderPass {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.handle.hash(state);
+    }
+}
+
 impl Drop for RenderPass {
     fn drop(&mut self) {
+        self.device.untrack("RenderPass");
+
         unsafe { ffi::vkDestroyRenderPass(self.device.handle, self.handle, ptr::null()) };
     }
 }
 
-pub struct ComputePipelineCreateInfo<'a> {
-    pub stage: PipelineShaderStageCreateInfo<'a>,
-    pub layout: &'a PipelineLayout,
-    pub base_pipeline: Option<Pipeline>,
-    pub base_pipeline_index: i32,
-}
-
-pub struct GraphicsPipelineCreateInfo<'a> {
-    pub stages: &'a [PipelineShaderStageCreateInfo<'a>],
-    pub vertex_input_state: &'a PipelineVertexInputStateCreateInfo<'a>,
-    pub input_assembly_state: &'a PipelineInputAssemblyStateCreateInfo,
-    pub tessellation_state: &'a PipelineTessellationStateCreateInfo,
-    pub viewport_state: &'a PipelineViewportStateCreateInfo<'a>,
-    pub rasterization_state: &'a PipelineRasterizationStateCreateInfo,
-    pub multisample_state: &'a PipelineMultisampleStateCreateInfo,
-    pub depth_stencil_state: &'a PipelineDepthStencilStateCreateInfo,
-    pub color_blend_state: &'a PipelineColorBlendStateCreateInfo<'a>,
-    pub dynamic_state: &'a PipelineDynamicStateCreateInfo<'a>,
-    pub layout: &'a PipelineLayout,
-    pub render_pass: &'a RenderPass,
-    pub subpass: u32,
-    pub base_pipeline: Option<Pipeline>,
-    pub base_pipeline_index: i32,
-}
+#[derive(Clone, Debug)]
+pub struct PipelineExecutableProperties {
+    //shader stage flags compiled into this executable
+    pub stages: u32,
+    pub name: String,
+    pub description: String,
+    pub subgroup_size: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PipelineExecutableStatisticValue {
+    Bool(bool),
+    Int64(i64),
+    Uint64(u64),
+    Float64(f64),
+}
+
+#[derive(Clone, Debug)]
+pub struct PipelineExecutableStatistic {
+    pub name: String,
+    pub description: String,
+    pub value: PipelineExecutableStatisticValue,
+}
+
+#[derive(Clone, Debug)]
+pub struct PipelineExecutableInternalRepresentation {
+    pub name: String,
+    pub description: String,
+    //utf8 text when true, opaque binary otherwise
+    pub is_text: bool,
+    pub data: Vec<u8>,
+}
+
+fn fixed_string(chars: &[i8; 256]) -> String {
+    unsafe { CStr::from_ptr(chars.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+pub struct ComputePipelineCreateInfo<'a> {
+    pub stage: PipelineShaderStageCreateInfo<'a>,
+    pub layout: &'a PipelineLayout,
+    pub base_pipeline: Option<Pipeline>,
+    pub base_pipeline_index: i32,
+}
+
+pub struct GraphicsPipelineCreateInfo<'a> {
+    pub stages: &'a [PipelineShaderStageCreateInfo<'a>],
+    pub vertex_input_state: &'a PipelineVertexInputStateCreateInfo<'a>,
+    pub input_assembly_state: &'a PipelineInputAssemblyStateCreateInfo,
+    pub tessellation_state: &'a PipelineTessellationStateCreateInfo,
+    pub viewport_state: &'a PipelineViewportStateCreateInfo<'a>,
+    pub rasterization_state: &'a PipelineRasterizationStateCreateInfo,
+    pub multisample_state: &'a PipelineMultisampleStateCreateInfo,
+    pub depth_stencil_state: &'a PipelineDepthStencilStateCreateInfo,
+    pub color_blend_state: &'a PipelineColorBlendStateCreateInfo<'a>,
+    pub dynamic_state: &'a PipelineDynamicStateCreateInfo<'a>,
+    pub layout: &'a PipelineLayout,
+    //None for pipelines used between begin_rendering and end_rendering
+    pub render_pass: Option<&'a RenderPass>,
+    pub subpass: u32,
+    //the attachment formats when there is no render pass, requires dynamic_rendering
+    pub rendering: Option<PipelineRenderingCreateInfo<'a>>,
+    pub base_pipeline: Option<Pipeline>,
+    pub base_pipeline_index: i32,
+}
+
+pub struct PipelineCache {
+    device: Rc<Device>,
+    handle: ffi::PipelineCache,
+}
+
+//Compiled pipeline state the driver can reuse across pipeline creations and, through
+//data and new, across runs. Data from another driver or device is ignored, not an error.
+impl PipelineCache {
+    pub fn new(device: Rc<Device>, initial_data: &'_ [u8]) -> Result<Self, Error> {
+        let create_info = ffi::PipelineCacheCreateInfo {
+            structure_type: ffi::StructureType::PipelineCacheCreateInfo,
+            p_next: ptr::null(),
+            flags: 0,
+            initial_data_size: initial_data.len(),
+            initial_data: initial_data.as_ptr() as _,
+        };
+
+        let mut handle = MaybeUninit::<ffi::PipelineCache>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreatePipelineCache(
+                device.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                Ok(Self {
+                    device: Device::track(device, "PipelineCache"),
+                    handle,
+                })
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    //The cache contents to pass to new in a later run
+    pub fn get_data(&self) -> Result<Vec<u8>, Error> {
+        loop {
+            let mut size = 0;
+
+            let result = unsafe {
+                ffi::vkGetPipelineCacheData(
+                    self.device.handle,
+                    self.handle,
+                    &mut size,
+                    ptr::null_mut(),
+                )
+            };
+
+            match result {
+                ffi::Result::Success => {}
+                ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+                ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+                _ => Err(unexpected_result(result))?,
+            }
+
+            let mut data = vec![0u8; size];
+
+            let result = unsafe {
+                ffi::vkGetPipelineCacheData(
+                    self.device.handle,
+                    self.handle,
+                    &mut size,
+                    data.as_mut_ptr() as _,
+                )
+            };
+
+            match result {
+                ffi::Result::Success => {
+                    data.truncate(size);
+
+                    return Ok(data);
+                }
+                //pipelines created on another thread grew the cache in between
+                ffi::Result::Incomplete => continue,
+                ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+                ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+                _ => Err(unexpected_result(result))?,
+            }
+        }
+    }
+
+    //Adds the contents of sources, e.g. caches filled by pipeline creation on worker threads
+    pub fn merge(&mut self, sources: &'_ [&'_ PipelineCache]) -> Result<(), Error> {
+        let sources = sources
+            .iter()
+            .map(|source| source.handle)
+            .collect::<Vec<_>>();
+
+        if sources.contains(&self.handle) {
+            return Err(Error::PipelineCacheSelfMerge);
+        }
+
+        let result = unsafe {
+            ffi::vkMergePipelineCaches(
+                self.device.handle,
+                self.handle,
+                sources.len() as _,
+                sources.as_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => Ok(()),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+}
+
+impl Drop for PipelineCache {
+    fn drop(&mut self) {
+        self.device.untrack("PipelineCache");
 
-pub struct PipelineCache {
-    handle: ffi::PipelineCache,
+        unsafe { ffi::vkDestroyPipelineCache(self.device.handle, self.handle, ptr::null()) };
+    }
 }
 
 pub struct Pipeline {
@@ -4508,9 +10265,13 @@ pub struct Pipeline {
 impl Pipeline {
     pub fn new_graphics_pipelines(
         device: Rc<Device>,
-        cache: Option<PipelineCache>,
+        cache: Option<&'_ PipelineCache>,
         create_infos: &'_ [GraphicsPipelineCreateInfo],
     ) -> Result<Vec<Self>, Error> {
+        for create_info in create_infos {
+            create_info.vertex_input_state.validate(&device.limits)?;
+        }
+
         let entry_points = create_infos
             .iter()
             .map(|create_info| {
@@ -4576,12 +10337,41 @@ impl Pipeline {
             })
             .collect::<Vec<_>>();
 
+        let vertex_binding_divisors = create_infos
+            .iter()
+            .map(|create_info| {
+                create_info
+                    .vertex_input_state
+                    .binding_divisors
+                    .iter()
+                    .map(|divisor| ffi::VertexInputBindingDivisorDescription {
+                        binding: divisor.binding,
+                        divisor: divisor.divisor,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let vertex_input_divisor_states = vertex_binding_divisors
+            .iter()
+            .map(|divisors| ffi::PipelineVertexInputDivisorStateCreateInfo {
+                structure_type: ffi::StructureType::PipelineVertexInputDivisorStateCreateInfo,
+                p_next: ptr::null(),
+                vertex_binding_divisor_count: divisors.len() as _,
+                vertex_binding_divisors: divisors.as_ptr(),
+            })
+            .collect::<Vec<_>>();
+
         let vertex_input_states = create_infos
             .iter()
             .enumerate()
             .map(|(i, _)| ffi::PipelineVertexInputStateCreateInfo {
                 structure_type: ffi::StructureType::PipelineVertexInputStateCreateInfo,
-                p_next: ptr::null(),
+                p_next: if vertex_binding_divisors[i].len() > 0 {
+                    &vertex_input_divisor_states[i] as *const _ as _
+                } else {
+                    ptr::null()
+                },
                 flags: 0,
                 vertex_binding_description_count: vertex_binding_descriptions[i].len() as _,
                 vertex_binding_descriptions: vertex_binding_descriptions[i].as_ptr(),
@@ -4671,11 +10461,59 @@ impl Pipeline {
             })
             .collect::<Vec<_>>();
 
+        let rasterization_depth_clip_states = create_infos
+            .iter()
+            .map(|create_info| ffi::PipelineRasterizationDepthClipStateCreateInfo {
+                structure_type: ffi::StructureType::PipelineRasterizationDepthClipStateCreateInfo,
+                p_next: ptr::null(),
+                flags: 0,
+                depth_clip_enable: create_info
+                    .rasterization_state
+                    .depth_clip_enable
+                    .unwrap_or(false) as _,
+            })
+            .collect::<Vec<_>>();
+
+        let rasterization_depth_clip_next = create_infos
+            .iter()
+            .enumerate()
+            .map(|(i, create_info)| {
+                if create_info.rasterization_state.depth_clip_enable.is_some() {
+                    &rasterization_depth_clip_states[i] as *const _ as *const ()
+                } else {
+                    ptr::null()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let rasterization_line_states = create_infos
+            .iter()
+            .enumerate()
+            .map(|(i, create_info)| ffi::PipelineRasterizationLineStateCreateInfo {
+                structure_type: ffi::StructureType::PipelineRasterizationLineStateCreateInfo,
+                p_next: rasterization_depth_clip_next[i],
+                line_rasterization_mode: create_info
+                    .rasterization_state
+                    .line_rasterization_mode
+                    .into(),
+                stippled_line_enable: false as _,
+                line_stipple_factor: 1,
+                line_stipple_pattern: 0xFFFF,
+            })
+            .collect::<Vec<_>>();
+
         let rasterization_states = create_infos
             .iter()
-            .map(|create_info| ffi::PipelineRasterizationStateCreateInfo {
+            .enumerate()
+            .map(|(i, create_info)| ffi::PipelineRasterizationStateCreateInfo {
                 structure_type: ffi::StructureType::PipelineRasterizationStateCreateInfo,
-                p_next: ptr::null(),
+                p_next: if create_info.rasterization_state.line_rasterization_mode
+                    != LineRasterizationMode::Default
+                {
+                    &rasterization_line_states[i] as *const _ as _
+                } else {
+                    rasterization_depth_clip_next[i]
+                },
                 flags: 0,
                 depth_clamp_enable: create_info.rasterization_state.depth_clamp_enable as _,
                 rasterizer_discard_enable: create_info.rasterization_state.rasterizer_discard_enable
@@ -4699,13 +10537,13 @@ impl Pipeline {
                 structure_type: ffi::StructureType::PipelineMultisampleStateCreateInfo,
                 p_next: ptr::null(),
                 flags: 0,
-                //Disable
-                rasterization_samples: 0x00000001,
-                sample_shading_enable: false as _,
-                min_sample_shading: 1.0,
+                rasterization_samples: create_info.multisample_state.rasterization_samples.to_vk(),
+                sample_shading_enable: create_info.multisample_state.sample_shading_enable as _,
+                min_sample_shading: create_info.multisample_state.min_sample_shading,
                 sample_mask: ptr::null(),
-                alpha_to_coverage_enable: false as _,
-                alpha_to_one_enable: false as _,
+                alpha_to_coverage_enable: create_info.multisample_state.alpha_to_coverage_enable
+                    as _,
+                alpha_to_one_enable: create_info.multisample_state.alpha_to_one_enable as _,
             })
             .collect::<Vec<_>>();
 
@@ -4721,26 +10559,9 @@ impl Pipeline {
                 depth_compare_op: create_info.depth_stencil_state.depth_compare_op.into(),
                 depth_bounds_test_enable: create_info.depth_stencil_state.depth_bounds_test_enable
                     as _,
-                //TODO stencil
-                stencil_test_enable: false as _,
-                front: ffi::StencilOpState {
-                    fail_op: ffi::StencilOp::Keep,
-                    pass_op: ffi::StencilOp::Keep,
-                    depth_fail_op: ffi::StencilOp::Keep,
-                    compare_op: ffi::CompareOp::Never,
-                    compare_mask: 0,
-                    write_mask: 0,
-                    reference: 0,
-                },
-                back: ffi::StencilOpState {
-                    fail_op: ffi::StencilOp::Keep,
-                    pass_op: ffi::StencilOp::Keep,
-                    depth_fail_op: ffi::StencilOp::Keep,
-                    compare_op: ffi::CompareOp::Never,
-                    compare_mask: 0,
-                    write_mask: 0,
-                    reference: 0,
-                },
+                stencil_test_enable: create_info.depth_stencil_state.stencil_test_enable as _,
+                front: create_info.depth_stencil_state.front.into(),
+                back: create_info.depth_stencil_state.back.into(),
                 min_depth_bounds: create_info.depth_stencil_state.min_depth_bounds as _,
                 max_depth_bounds: create_info.depth_stencil_state.max_depth_bounds as _,
             })
@@ -4831,13 +10652,51 @@ impl Pipeline {
             })
             .collect::<Vec<_>>();
 
+        let rendering_format_data = create_infos
+            .iter()
+            .map(|create_info| {
+                create_info.rendering.as_ref().map_or(vec![], |rendering| {
+                    rendering
+                        .color_attachment_formats
+                        .iter()
+                        .map(|&format| ffi::Format::from(format) as u32)
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let rendering_create_infos = create_infos
+            .iter()
+            .enumerate()
+            .map(|(i, create_info)| {
+                let rendering = create_info.rendering.as_ref()?;
+
+                //VK_FORMAT_UNDEFINED when there is no such attachment
+                let format = |format: Option<Format>| {
+                    format.map_or(0, |format| ffi::Format::from(format) as u32)
+                };
+
+                Some(ffi::PipelineRenderingCreateInfo {
+                    structure_type: ffi::StructureType::PipelineRenderingCreateInfo,
+                    p_next: ptr::null(),
+                    view_mask: 0,
+                    color_attachment_count: rendering_format_data[i].len() as _,
+                    color_attachment_formats: slice_ptr(&rendering_format_data[i]),
+                    depth_attachment_format: format(rendering.depth_attachment_format),
+                    stencil_attachment_format: format(rendering.stencil_attachment_format),
+                })
+            })
+            .collect::<Vec<_>>();
+
         let create_infos = create_infos
             .iter()
             .enumerate()
             .map(|(i, create_info)| ffi::GraphicsPipelineCreateInfo {
                 structure_type: ffi::StructureType::GraphicsPipelineCreateInfo,
-                p_next: ptr::null(),
-                flags: 0,
+                p_next: rendering_create_infos[i]
+                    .as_ref()
+                    .map_or(ptr::null(), |rendering| rendering as *const _ as _),
+                flags: device.pipeline_create_flags(),
                 stage_count: stages[i].len() as _,
                 stages: stages[i].as_ptr(),
                 vertex_input_state: &vertex_input_states[i],
@@ -4850,7 +10709,9 @@ impl Pipeline {
                 color_blend_state: &color_blend_states[i],
                 dynamic_state: &dynamic_states[i],
                 layout: create_info.layout.handle,
-                render_pass: create_info.render_pass.handle,
+                render_pass: create_info
+                    .render_pass
+                    .map_or(ffi::RenderPass::null(), |render_pass| render_pass.handle),
                 subpass: create_info.subpass as _,
                 base_pipeline_handle: create_info
                     .base_pipeline
@@ -4865,7 +10726,7 @@ impl Pipeline {
         let result = unsafe {
             ffi::vkCreateGraphicsPipelines(
                 device.handle,
-                ffi::PipelineCache::null(),
+                cache.map_or(ffi::PipelineCache::null(), |cache| cache.handle),
                 create_infos.len() as _,
                 create_infos.as_ptr(),
                 ptr::null(),
@@ -4880,7 +10741,7 @@ impl Pipeline {
                 let pipelines = handles
                     .into_iter()
                     .map(|handle| Pipeline {
-                        device: device.clone(),
+                        device: Device::track(device.clone(), "Pipeline"),
                         handle,
                     })
                     .collect::<Vec<_>>();
@@ -4890,13 +10751,13 @@ impl Pipeline {
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             ffi::Result::InvalidShader => Err(Error::InvalidShader),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 
     pub fn new_compute_pipelines(
         device: Rc<Device>,
-        cache: Option<PipelineCache>,
+        cache: Option<&'_ PipelineCache>,
         create_infos: &'_ [ComputePipelineCreateInfo],
     ) -> Result<Vec<Self>, Error> {
         let entry_points = create_infos
@@ -4904,74 +10765,345 @@ impl Pipeline {
             .map(|create_info| CString::new(create_info.stage.entry_point).unwrap())
             .collect::<Vec<_>>();
 
-        let stages = create_infos
-            .iter()
-            .enumerate()
-            .map(|(i, create_info)| ffi::PipelineShaderStageCreateInfo {
-                structure_type: ffi::StructureType::PipelineShaderStageCreateInfo,
-                p_next: ptr::null(),
-                flags: 0,
-                stage: create_info.stage.stage as _,
-                module: create_info.stage.module.handle,
-                entry_point: entry_points[i].as_ptr(),
-                specialization_info: ptr::null(),
-            })
-            .collect::<Vec<_>>();
+        let stages = create_infos
+            .iter()
+            .enumerate()
+            .map(|(i, create_info)| ffi::PipelineShaderStageCreateInfo {
+                structure_type: ffi::StructureType::PipelineShaderStageCreateInfo,
+                p_next: ptr::null(),
+                flags: 0,
+                stage: create_info.stage.stage as _,
+                module: create_info.stage.module.handle,
+                entry_point: entry_points[i].as_ptr(),
+                specialization_info: ptr::null(),
+            })
+            .collect::<Vec<_>>();
+
+        let create_infos = create_infos
+            .iter()
+            .enumerate()
+            .map(|(i, create_info)| ffi::ComputePipelineCreateInfo {
+                structure_type: ffi::StructureType::ComputePipelineCreateInfo,
+                p_next: ptr::null(),
+                flags: device.pipeline_create_flags(),
+                stage: stages[i],
+                layout: create_info.layout.handle,
+                base_pipeline_handle: create_info
+                    .base_pipeline
+                    .as_ref()
+                    .map_or(ffi::Pipeline::null(), |pipeline| pipeline.handle),
+                base_pipeline_index: create_info.base_pipeline_index,
+            })
+            .collect::<Vec<_>>();
+
+        let mut handles = Vec::with_capacity(create_infos.len());
+
+        let result = unsafe {
+            ffi::vkCreateComputePipelines(
+                device.handle,
+                cache.map_or(ffi::PipelineCache::null(), |cache| cache.handle),
+                create_infos.len() as _,
+                create_infos.as_ptr(),
+                ptr::null(),
+                handles.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {
+                unsafe { handles.set_len(create_infos.len()) };
+
+                let pipelines = handles
+                    .into_iter()
+                    .map(|handle| Pipeline {
+                        device: Device::track(device.clone(), "Pipeline"),
+                        handle,
+                    })
+                    .collect::<Vec<_>>();
+
+                Ok(pipelines)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            ffi::Result::InvalidShader => Err(Error::InvalidShader),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+    fn executable_fn<F>(&self, name: &[u8]) -> Result<F, Error> {
+        if !self.device.pipeline_executable_info {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f_name = CStr::from_bytes_with_nul(name).unwrap();
+
+        let f = unsafe { ffi::vkGetDeviceProcAddr(self.device.handle, f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        Ok(unsafe { mem::transmute_copy::<_, F>(&f) })
+    }
+
+    //One executable per compiled stage or group of stages, requires the device
+    //to have enabled pipeline_executable_info before the pipeline was created
+    pub fn executable_properties(&self) -> Result<Vec<PipelineExecutableProperties>, Error> {
+        let f = self.executable_fn::<ffi::GetPipelineExecutableProperties>(
+            b"vkGetPipelineExecutablePropertiesKHR\0",
+        )?;
+
+        let pipeline_info = ffi::PipelineInfo {
+            structure_type: ffi::StructureType::PipelineInfo,
+            p_next: ptr::null(),
+            pipeline: self.handle,
+        };
+
+        let mut count: u32 = 0;
+
+        let result = unsafe { f(self.device.handle, &pipeline_info, &mut count, ptr::null_mut()) };
+
+        match result {
+            ffi::Result::Success => {}
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+            _ => Err(unexpected_result(result))?,
+        }
+
+        let mut properties = vec![
+            ffi::PipelineExecutableProperties {
+                structure_type: ffi::StructureType::PipelineExecutableProperties,
+                p_next: ptr::null_mut(),
+                stages: 0,
+                name: [0; 256],
+                description: [0; 256],
+                subgroup_size: 0,
+            };
+            count as _
+        ];
+
+        let result = unsafe {
+            f(
+                self.device.handle,
+                &pipeline_info,
+                &mut count,
+                properties.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success | ffi::Result::Incomplete => {}
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+            _ => Err(unexpected_result(result))?,
+        }
+
+        let properties = properties
+            .iter()
+            .take(count as _)
+            .map(|properties| PipelineExecutableProperties {
+                stages: properties.stages,
+                name: fixed_string(&properties.name),
+                description: fixed_string(&properties.description),
+                subgroup_size: properties.subgroup_size,
+            })
+            .collect();
+
+        Ok(properties)
+    }
+
+    //Driver defined statistics such as register counts and spilled bytes
+    pub fn executable_statistics(
+        &self,
+        executable_index: u32,
+    ) -> Result<Vec<PipelineExecutableStatistic>, Error> {
+        let f = self.executable_fn::<ffi::GetPipelineExecutableStatistics>(
+            b"vkGetPipelineExecutableStatisticsKHR\0",
+        )?;
+
+        let executable_info = ffi::PipelineExecutableInfo {
+            structure_type: ffi::StructureType::PipelineExecutableInfo,
+            p_next: ptr::null(),
+            pipeline: self.handle,
+            executable_index,
+        };
+
+        let mut count: u32 = 0;
+
+        let result =
+            unsafe { f(self.device.handle, &executable_info, &mut count, ptr::null_mut()) };
+
+        match result {
+            ffi::Result::Success => {}
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+            _ => Err(unexpected_result(result))?,
+        }
+
+        let mut statistics = vec![
+            ffi::PipelineExecutableStatistic {
+                structure_type: ffi::StructureType::PipelineExecutableStatistic,
+                p_next: ptr::null_mut(),
+                name: [0; 256],
+                description: [0; 256],
+                format: 0,
+                value: [0; 8],
+            };
+            count as _
+        ];
+
+        let result = unsafe {
+            f(
+                self.device.handle,
+                &executable_info,
+                &mut count,
+                statistics.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success | ffi::Result::Incomplete => {}
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+            _ => Err(unexpected_result(result))?,
+        }
+
+        let statistics = statistics
+            .iter()
+            .take(count as _)
+            .map(|statistic| {
+                let raw = statistic.value;
+                let low = [raw[0], raw[1], raw[2], raw[3]];
+
+                let value = match statistic.format {
+                    0 => PipelineExecutableStatisticValue::Bool(u32::from_ne_bytes(low) != 0),
+                    1 => PipelineExecutableStatisticValue::Int64(i64::from_ne_bytes(raw)),
+                    2 => PipelineExecutableStatisticValue::Uint64(u64::from_ne_bytes(raw)),
+                    _ => PipelineExecutableStatisticValue::Float64(f64::from_ne_bytes(raw)),
+                };
+
+                PipelineExecutableStatistic {
+                    name: fixed_string(&statistic.name),
+                    description: fixed_string(&statistic.description),
+                    value,
+                }
+            })
+            .collect();
+
+        Ok(statistics)
+    }
+
+    //Compiler intermediate forms such as ir or isa disassembly, when the driver exposes them
+    pub fn executable_internal_representations(
+        &self,
+        executable_index: u32,
+    ) -> Result<Vec<PipelineExecutableInternalRepresentation>, Error> {
+        let f = self.executable_fn::<ffi::GetPipelineExecutableInternalRepresentations>(
+            b"vkGetPipelineExecutableInternalRepresentationsKHR\0",
+        )?;
+
+        let executable_info = ffi::PipelineExecutableInfo {
+            structure_type: ffi::StructureType::PipelineExecutableInfo,
+            p_next: ptr::null(),
+            pipeline: self.handle,
+            executable_index,
+        };
+
+        let mut count: u32 = 0;
+
+        let result =
+            unsafe { f(self.device.handle, &executable_info, &mut count, ptr::null_mut()) };
+
+        match result {
+            ffi::Result::Success => {}
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+            _ => Err(unexpected_result(result))?,
+        }
+
+        let mut representations = vec![
+            ffi::PipelineExecutableInternalRepresentation {
+                structure_type: ffi::StructureType::PipelineExecutableInternalRepresentation,
+                p_next: ptr::null_mut(),
+                name: [0; 256],
+                description: [0; 256],
+                is_text: 0,
+                data_size: 0,
+                data: ptr::null_mut(),
+            };
+            count as _
+        ];
+
+        //the first call with null data only fills in the sizes
+        let result = unsafe {
+            f(
+                self.device.handle,
+                &executable_info,
+                &mut count,
+                representations.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success | ffi::Result::Incomplete => {}
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+            _ => Err(unexpected_result(result))?,
+        }
 
-        let create_infos = create_infos
+        let mut data = representations
             .iter()
-            .enumerate()
-            .map(|(i, create_info)| ffi::ComputePipelineCreateInfo {
-                structure_type: ffi::StructureType::ComputePipelineCreateInfo,
-                p_next: ptr::null(),
-                flags: 0,
-                stage: stages[i],
-                layout: create_info.layout.handle,
-                base_pipeline_handle: create_info
-                    .base_pipeline
-                    .as_ref()
-                    .map_or(ffi::Pipeline::null(), |pipeline| pipeline.handle),
-                base_pipeline_index: create_info.base_pipeline_index,
-            })
+            .take(count as _)
+            .map(|representation| vec![0u8; representation.data_size])
             .collect::<Vec<_>>();
 
-        let mut handles = Vec::with_capacity(create_infos.len());
+        for (representation, data) in representations.iter_mut().zip(data.iter_mut()) {
+            representation.data = data.as_mut_ptr() as _;
+        }
 
         let result = unsafe {
-            ffi::vkCreateComputePipelines(
-                device.handle,
-                ffi::PipelineCache::null(),
-                create_infos.len() as _,
-                create_infos.as_ptr(),
-                ptr::null(),
-                handles.as_mut_ptr(),
+            f(
+                self.device.handle,
+                &executable_info,
+                &mut count,
+                representations.as_mut_ptr(),
             )
         };
 
         match result {
-            ffi::Result::Success => {
-                unsafe { handles.set_len(create_infos.len()) };
+            ffi::Result::Success | ffi::Result::Incomplete => {}
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+            _ => Err(unexpected_result(result))?,
+        }
 
-                let pipelines = handles
-                    .into_iter()
-                    .map(|handle| Pipeline {
-                        device: device.clone(),
-                        handle,
-                    })
-                    .collect::<Vec<_>>();
+        let representations = representations
+            .iter()
+            .zip(data)
+            .map(|(representation, mut data)| {
+                data.truncate(representation.data_size);
 
-                Ok(pipelines)
-            }
-            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
-            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            ffi::Result::InvalidShader => Err(Error::InvalidShader),
-            _ => panic!("unexpected result: {:?}", result),
-        }
+                //text representations are null terminated
+                if representation.is_text != 0 && data.last() == Some(&0) {
+                    data.pop();
+                }
+
+                PipelineExecutableInternalRepresentation {
+                    name: fixed_string(&representation.name),
+                    description: fixed_string(&representation.description),
+                    is_text: representation.is_text != 0,
+                    data,
+                }
+            })
+            .collect();
+
+        Ok(representations)
     }
 }
 
 impl Drop for Pipeline {
     fn drop(&mut self) {
+        self.device.untrack("Pipeline");
+
         unsafe { ffi::vkDestroyPipeline(self.device.handle, self.handle, ptr::null()) };
     }
 }
@@ -4991,6 +11123,16 @@ pub struct Framebuffer {
 
 impl Framebuffer {
     pub fn new(device: Rc<Device>, create_info: FramebufferCreateInfo) -> Result<Self, Error> {
+        for (i, image_view) in create_info.attachments.iter().enumerate() {
+            if create_info.layers == 0 || image_view.layer_count < create_info.layers {
+                Err(Error::FramebufferLayersExceedAttachment {
+                    layers: create_info.layers,
+                    attachment: i as _,
+                    layer_count: image_view.layer_count,
+                })?;
+            }
+        }
+
         let attachments = create_info
             .attachments
             .iter()
@@ -5024,24 +11166,30 @@ impl Framebuffer {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let framebuffer = Self { device, handle };
+                let framebuffer = Self {
+                    device: Device::track(device, "Framebuffer"),
+                    handle,
+                };
 
                 Ok(framebuffer)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
 
 impl Drop for Framebuffer {
     fn drop(&mut self) {
+        self.device.untrack("Framebuffer");
+
         unsafe { ffi::vkDestroyFramebuffer(self.device.handle, self.handle, ptr::null()) };
     }
 }
 
 pub struct CommandPoolCreateInfo {
+    pub flags: u32,
     pub queue_family_index: u32,
 }
 
@@ -5055,7 +11203,7 @@ impl CommandPool {
         let create_info = ffi::CommandPoolCreateInfo {
             structure_type: ffi::StructureType::CommandPoolCreateInfo,
             p_next: ptr::null(),
-            flags: 0x00000002,
+            flags: create_info.flags,
             queue_family_index: create_info.queue_family_index,
         };
 
@@ -5074,19 +11222,24 @@ impl CommandPool {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let command_pool = Self { device, handle };
+                let command_pool = Self {
+                    device: Device::track(device, "CommandPool"),
+                    handle,
+                };
 
                 Ok(command_pool)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
 
 impl Drop for CommandPool {
     fn drop(&mut self) {
+        self.device.untrack("CommandPool");
+
         unsafe { ffi::vkDestroyCommandPool(self.device.handle, self.handle, ptr::null()) };
     }
 }
@@ -5103,9 +11256,23 @@ pub struct CommandBufferAllocateInfo<'a> {
     pub count: u32,
 }
 
+//Render pass state a secondary command buffer continues when flags has
+//COMMAND_BUFFER_USAGE_RENDER_PASS_CONTINUE
+pub struct CommandBufferInheritanceInfo<'a> {
+    pub render_pass: &'a RenderPass,
+    pub subpass: u32,
+    //optional, knowing it up front may let the driver optimize
+    pub framebuffer: Option<&'a Framebuffer>,
+    //inherit the viewport and scissor the primary has set, instead of setting them again.
+    //One depth range per inherited viewport, requires inherited_viewport_scissor_enabled
+    pub viewport_depths: Option<&'a [Viewport]>,
+}
+
 pub struct CommandBuffer {
     device: Rc<Device>,
     handle: ffi::CommandBuffer,
+    #[cfg(feature = "barrier-validation")]
+    tracker: hazard::Tracker,
 }
 
 impl CommandBuffer {
@@ -5134,8 +11301,10 @@ impl CommandBuffer {
                 let command_pools = handles
                     .into_iter()
                     .map(|handle| Self {
-                        device: device.clone(),
+                        device: Device::track(device.clone(), "CommandBuffer"),
                         handle,
+                        #[cfg(feature = "barrier-validation")]
+                        tracker: Default::default(),
                     })
                     .collect::<Vec<_>>();
 
@@ -5143,16 +11312,77 @@ impl CommandBuffer {
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 
-    pub fn record<'a>(&'a mut self, mut script: impl FnMut(Commands<'_>)) -> Result<(), Error> {
+    pub fn record<'a>(&'a mut self, script: impl FnMut(Commands<'_>)) -> Result<(), Error> {
+        self.record_with(0, ptr::null(), script)
+    }
+
+    pub fn record_secondary<'a>(
+        &'a mut self,
+        flags: u32,
+        inheritance_info: CommandBufferInheritanceInfo<'_>,
+        script: impl FnMut(Commands<'_>),
+    ) -> Result<(), Error> {
+        let viewport_depths = inheritance_info.viewport_depths.map(|viewports| {
+            viewports
+                .iter()
+                .map(|viewport| ffi::Viewport {
+                    x: viewport.x,
+                    y: viewport.y,
+                    width: viewport.width,
+                    height: viewport.height,
+                    min_depth: viewport.min_depth,
+                    max_depth: viewport.max_depth,
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let viewport_scissor_info = viewport_depths.as_ref().map(|viewport_depths| {
+            ffi::CommandBufferInheritanceViewportScissorInfo {
+                structure_type: ffi::StructureType::CommandBufferInheritanceViewportScissorInfo,
+                p_next: ptr::null(),
+                viewport_scissor_2d: true as _,
+                viewport_depth_count: viewport_depths.len() as _,
+                viewport_depths: viewport_depths.as_ptr(),
+            }
+        });
+
+        if viewport_scissor_info.is_some() && !self.device.inherited_viewport_scissor {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let ffi_inheritance_info = ffi::CommandBufferInheritanceInfo {
+            structure_type: ffi::StructureType::CommandBufferInheritanceInfo,
+            p_next: viewport_scissor_info
+                .as_ref()
+                .map_or(ptr::null(), |info| info as *const _ as _),
+            render_pass: inheritance_info.render_pass.handle,
+            subpass: inheritance_info.subpass,
+            framebuffer: inheritance_info
+                .framebuffer
+                .map_or(ffi::Framebuffer::null(), |framebuffer| framebuffer.handle),
+            occlusion_query_enable: false as _,
+            query_flags: 0,
+            pipeline_statistics: 0,
+        };
+
+        self.record_with(flags, &ffi_inheritance_info, script)
+    }
+
+    fn record_with<'a>(
+        &'a mut self,
+        flags: u32,
+        inheritance_info: *const ffi::CommandBufferInheritanceInfo,
+        mut script: impl FnMut(Commands<'_>),
+    ) -> Result<(), Error> {
         let begin_info = ffi::CommandBufferBeginInfo {
             structure_type: ffi::StructureType::CommandBufferBeginInfo,
             p_next: ptr::null(),
-            flags: 0,
-            inheritence_info: ptr::null(),
+            flags,
+            inheritence_info: inheritance_info,
         };
 
         let result = unsafe { ffi::vkBeginCommandBuffer(self.handle, &begin_info) };
@@ -5161,9 +11391,12 @@ impl CommandBuffer {
             ffi::Result::Success => {}
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result))?,
         }
 
+        #[cfg(feature = "barrier-validation")]
+        self.tracker.reset();
+
         let commands = Commands {
             command_buffer: self,
         };
@@ -5176,27 +11409,53 @@ impl CommandBuffer {
             ffi::Result::Success => Ok(()),
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 
+    //Reads with no barrier after an earlier write to the same resource, found in the
+    //last recording
+    #[cfg(feature = "barrier-validation")]
+    pub fn hazards(&self) -> &[hazard::Hazard] {
+        self.tracker.hazards()
+    }
+
     pub fn reset(&mut self) -> Result<(), Error> {
         let result = unsafe { ffi::vkResetCommandBuffer(self.handle, 0) };
 
         match result {
             ffi::Result::Success => Ok(()),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
 
+impl Drop for CommandBuffer {
+    fn drop(&mut self) {
+        self.device.untrack("CommandBuffer");
+    }
+}
+
 pub struct Commands<'a> {
     command_buffer: &'a mut CommandBuffer,
 }
 
 impl Commands<'_> {
     pub fn begin_render_pass(&mut self, begin_info: RenderPassBeginInfo<'_>) {
+        self.begin_render_pass_with(begin_info, ffi::SubpassContents::Inline);
+    }
+
+    //The first subpass is recorded in secondary command buffers, see execute_commands
+    pub fn begin_render_pass_secondary(&mut self, begin_info: RenderPassBeginInfo<'_>) {
+        self.begin_render_pass_with(begin_info, ffi::SubpassContents::Secondary);
+    }
+
+    fn begin_render_pass_with(
+        &mut self,
+        begin_info: RenderPassBeginInfo<'_>,
+        contents: ffi::SubpassContents,
+    ) {
         let mut clear_values = vec![];
 
         clear_values.extend(
@@ -5239,11 +11498,7 @@ impl Commands<'_> {
         };
 
         unsafe {
-            ffi::vkCmdBeginRenderPass(
-                self.command_buffer.handle,
-                &begin_info,
-                ffi::SubpassContents::Inline,
-            )
+            ffi::vkCmdBeginRenderPass(self.command_buffer.handle, &begin_info, contents)
         };
     }
 
@@ -5251,10 +11506,94 @@ impl Commands<'_> {
         unsafe { ffi::vkCmdEndRenderPass(self.command_buffer.handle) };
     }
 
+    //Renders straight to image views, for pipelines created with rendering instead of
+    //a render pass
+    pub fn begin_rendering(&mut self, rendering_info: RenderingInfo<'_>) -> Result<(), Error> {
+        let f = self.dynamic_rendering_fn::<ffi::CmdBeginRendering>(b"vkCmdBeginRenderingKHR\0")?;
+
+        let scratch = self.command_buffer.device.scratch();
+
+        let color_attachments = scratch.alloc_from_iter(
+            rendering_info
+                .color_attachments
+                .iter()
+                .map(ffi_rendering_attachment_info),
+        );
+
+        let depth_attachment = scratch.alloc_from_iter(
+            rendering_info
+                .depth_attachment
+                .as_ref()
+                .map(ffi_rendering_attachment_info),
+        );
+
+        let stencil_attachment = scratch.alloc_from_iter(
+            rendering_info
+                .stencil_attachment
+                .as_ref()
+                .map(ffi_rendering_attachment_info),
+        );
+
+        let rendering_info = ffi::RenderingInfo {
+            structure_type: ffi::StructureType::RenderingInfo,
+            p_next: ptr::null(),
+            flags: 0,
+            render_area: ffi::Rect2d {
+                offset: [
+                    rendering_info.render_area.offset.0,
+                    rendering_info.render_area.offset.1,
+                ],
+                extent: [
+                    rendering_info.render_area.extent.0,
+                    rendering_info.render_area.extent.1,
+                ],
+            },
+            layer_count: rendering_info.layer_count,
+            view_mask: 0,
+            color_attachment_count: color_attachments.len() as _,
+            color_attachments: slice_ptr(color_attachments),
+            depth_attachment: slice_ptr(depth_attachment),
+            stencil_attachment: slice_ptr(stencil_attachment),
+        };
+
+        unsafe { f(self.command_buffer.handle, &rendering_info) };
+
+        Ok(())
+    }
+
+    pub fn end_rendering(&mut self) -> Result<(), Error> {
+        let f = self.dynamic_rendering_fn::<ffi::CmdEndRendering>(b"vkCmdEndRenderingKHR\0")?;
+
+        unsafe { f(self.command_buffer.handle) };
+
+        Ok(())
+    }
+
     pub fn next_subpass(&mut self) {
         unsafe { ffi::vkCmdNextSubpass(self.command_buffer.handle, ffi::SubpassContents::Inline) };
     }
 
+    pub fn next_subpass_secondary(&mut self) {
+        unsafe {
+            ffi::vkCmdNextSubpass(self.command_buffer.handle, ffi::SubpassContents::Secondary)
+        };
+    }
+
+    pub fn execute_commands(&mut self, command_buffers: &[&CommandBuffer]) {
+        let command_buffers = command_buffers
+            .iter()
+            .map(|command_buffer| command_buffer.handle)
+            .collect::<Vec<_>>();
+
+        unsafe {
+            ffi::vkCmdExecuteCommands(
+                self.command_buffer.handle,
+                command_buffers.len() as _,
+                command_buffers.as_ptr(),
+            )
+        };
+    }
+
     pub fn bind_pipeline(&mut self, bind_point: PipelineBindPoint, pipeline: &Pipeline) {
         unsafe {
             ffi::vkCmdBindPipeline(
@@ -5292,12 +11631,78 @@ impl Commands<'_> {
         };
     }
 
+    pub fn push_constants(
+        &mut self,
+        layout: &'_ PipelineLayout,
+        stage_flags: u32,
+        offset: u32,
+        data: &'_ [u8],
+    ) -> Result<(), Error> {
+        let size =
+            validate_push_constants(&layout.push_constant_ranges, stage_flags, offset, data)?;
+
+        unsafe {
+            ffi::vkCmdPushConstants(
+                self.command_buffer.handle,
+                layout.handle,
+                stage_flags,
+                offset,
+                size,
+                data.as_ptr() as _,
+            )
+        };
+
+        Ok(())
+    }
+
+    pub fn reset_query_pool(&mut self, query_pool: &'_ QueryPool, first_query: u32, query_count: u32) {
+        unsafe {
+            ffi::vkCmdResetQueryPool(
+                self.command_buffer.handle,
+                query_pool.handle,
+                first_query,
+                query_count,
+            )
+        };
+    }
+
+    pub fn begin_query(&mut self, query_pool: &'_ QueryPool, query: u32) {
+        self.begin_query_with(query_pool, query, 0);
+    }
+
+    //flags is QUERY_CONTROL_PRECISE for exact occlusion sample counts, which may be slower
+    //than the default of only telling zero from non zero
+    pub fn begin_query_with(&mut self, query_pool: &'_ QueryPool, query: u32, flags: u32) {
+        unsafe {
+            ffi::vkCmdBeginQuery(self.command_buffer.handle, query_pool.handle, query, flags)
+        };
+    }
+
+    pub fn end_query(&mut self, query_pool: &'_ QueryPool, query: u32) {
+        unsafe { ffi::vkCmdEndQuery(self.command_buffer.handle, query_pool.handle, query) };
+    }
+
+    //Writes the time once all previous commands finished stage, the query must have been
+    //reset. Requires a nonzero timestamp_valid_bits on the queue family.
+    pub fn write_timestamp(&mut self, stage: u32, query_pool: &'_ QueryPool, query: u32) {
+        unsafe {
+            ffi::vkCmdWriteTimestamp(self.command_buffer.handle, stage, query_pool.handle, query)
+        };
+    }
+
     pub fn bind_vertex_buffers(
         &mut self,
         first_binding: u32,
         buffers: &'_ [&'_ Buffer],
         offsets: &'_ [usize],
     ) {
+        #[cfg(feature = "barrier-validation")]
+        buffers.iter().for_each(|&buffer| {
+            self.command_buffer
+                .tracker
+                .read(buffer, "bind_vertex_buffers")
+        });
+
         let buffers = buffers
             .iter()
             .map(|buffer| buffer.handle)
@@ -5321,6 +11726,11 @@ impl Commands<'_> {
     }
 
     pub fn bind_index_buffer(&mut self, buffer: &'_ Buffer, offset: usize, index_type: IndexType) {
+        #[cfg(feature = "barrier-validation")]
+        self.command_buffer
+            .tracker
+            .read(buffer, "bind_index_buffer");
+
         unsafe {
             ffi::vkCmdBindIndexBuffer(
                 self.command_buffer.handle,
@@ -5331,6 +11741,31 @@ impl Commands<'_> {
         };
     }
 
+    pub fn set_blend_constants(&mut self, blend_constants: [f32; 4]) {
+        unsafe {
+            ffi::vkCmdSetBlendConstants(self.command_buffer.handle, blend_constants.as_ptr())
+        };
+    }
+
+    pub fn set_scissor(&mut self, first_scissor: u32, scissors: &'_ [Rect2d]) {
+        let scissors = scissors
+            .iter()
+            .map(|scissor| ffi::Rect2d {
+                offset: [scissor.offset.0, scissor.offset.1],
+                extent: [scissor.extent.0, scissor.extent.1],
+            })
+            .collect::<Vec<_>>();
+
+        unsafe {
+            ffi::vkCmdSetScissor(
+                self.command_buffer.handle,
+                first_scissor,
+                scissors.len() as _,
+                scissors.as_ptr(),
+            )
+        };
+    }
+
     pub fn draw(
         &mut self,
         vertex_count: u32,
@@ -5339,34 +11774,181 @@ impl Commands<'_> {
         first_instance: u32,
     ) {
         unsafe {
-            ffi::vkCmdDraw(
+            ffi::vkCmdDraw(
+                self.command_buffer.handle,
+                vertex_count,
+                instance_count,
+                first_vertex,
+                first_instance,
+            )
+        };
+    }
+
+    pub fn draw_indexed(
+        &mut self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    ) {
+        unsafe {
+            ffi::vkCmdDrawIndexed(
+                self.command_buffer.handle,
+                index_count,
+                instance_count,
+                first_index,
+                vertex_offset,
+                first_instance,
+            )
+        };
+    }
+
+    pub fn draw_indexed_indirect(
+        &mut self,
+        buffer: &'_ Buffer,
+        offset: u64,
+        draw_count: u32,
+        stride: u32,
+    ) {
+        #[cfg(feature = "barrier-validation")]
+        self.command_buffer
+            .tracker
+            .read(buffer, "draw_indexed_indirect");
+
+        unsafe {
+            ffi::vkCmdDrawIndexedIndirect(
+                self.command_buffer.handle,
+                buffer.handle,
+                offset,
+                draw_count,
+                stride,
+            )
+        };
+    }
+
+    //Requires KHR_DRAW_INDIRECT_COUNT, draws min(count, max_draw_count) commands
+    pub fn draw_indexed_indirect_count(
+        &mut self,
+        buffer: &'_ Buffer,
+        offset: u64,
+        count_buffer: &'_ Buffer,
+        count_offset: u64,
+        max_draw_count: u32,
+        stride: u32,
+    ) -> Result<(), Error> {
+        let device = &self.command_buffer.device;
+
+        if !device.is_extension_enabled(KHR_DRAW_INDIRECT_COUNT) {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f_name = CStr::from_bytes_with_nul(b"vkCmdDrawIndexedIndirectCountKHR\0").unwrap();
+
+        let f = unsafe { ffi::vkGetDeviceProcAddr(device.handle, f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f = unsafe { mem::transmute::<_, ffi::CmdDrawIndexedIndirectCount>(f) };
+
+        #[cfg(feature = "barrier-validation")]
+        {
+            let tracker = &mut self.command_buffer.tracker;
+
+            tracker.read(buffer, "draw_indexed_indirect_count");
+            tracker.read(count_buffer, "draw_indexed_indirect_count");
+        }
+
+        unsafe {
+            f(
                 self.command_buffer.handle,
-                vertex_count,
-                instance_count,
-                first_vertex,
-                first_instance,
+                buffer.handle,
+                offset,
+                count_buffer.handle,
+                count_offset,
+                max_draw_count,
+                stride,
             )
         };
+
+        Ok(())
     }
 
-    pub fn draw_indexed(
-        &mut self,
-        index_count: u32,
-        instance_count: u32,
-        first_index: u32,
-        vertex_offset: i32,
-        first_instance: u32,
-    ) {
+    pub fn fill_buffer(&mut self, buffer: &'_ Buffer, offset: u64, size: u64, data: u32) {
+        #[cfg(feature = "barrier-validation")]
+        self.command_buffer.tracker.write(buffer, "fill_buffer");
+
         unsafe {
-            ffi::vkCmdDrawIndexed(
-                self.command_buffer.handle,
-                index_count,
-                instance_count,
-                first_index,
-                vertex_offset,
-                first_instance,
-            )
+            ffi::vkCmdFillBuffer(self.command_buffer.handle, buffer.handle, offset, size, data)
+        };
+    }
+
+    //Requires EXT_DEBUG_UTILS on the instance. Labels show up in captures and in the
+    //labels of debug messages raised by commands between begin and end.
+    pub fn begin_label(&mut self, label: &str, color: [f32; 4]) -> Result<(), Error> {
+        let device = &self.command_buffer.device;
+
+        let f_name = CStr::from_bytes_with_nul(b"vkCmdBeginDebugUtilsLabelEXT\0").unwrap();
+
+        let f = unsafe { ffi::vkGetDeviceProcAddr(device.handle, f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f = unsafe { mem::transmute::<_, ffi::CmdBeginDebugUtilsLabel>(f) };
+
+        let label_name = CString::new(label).unwrap();
+
+        let label = ffi::DebugUtilsLabel {
+            structure_type: ffi::StructureType::DebugUtilsLabel,
+            p_next: ptr::null(),
+            label_name: label_name.as_ptr(),
+            color,
         };
+
+        unsafe { f(self.command_buffer.handle, &label) };
+
+        #[cfg(feature = "barrier-validation")]
+        self.command_buffer
+            .tracker
+            .push_label(&label_name.to_string_lossy());
+
+        Ok(())
+    }
+
+    pub fn end_label(&mut self) -> Result<(), Error> {
+        let device = &self.command_buffer.device;
+
+        let f_name = CStr::from_bytes_with_nul(b"vkCmdEndDebugUtilsLabelEXT\0").unwrap();
+
+        let f = unsafe { ffi::vkGetDeviceProcAddr(device.handle, f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f = unsafe { mem::transmute::<_, ffi::CmdEndDebugUtilsLabel>(f) };
+
+        unsafe { f(self.command_buffer.handle) };
+
+        #[cfg(feature = "barrier-validation")]
+        self.command_buffer.tracker.pop_label();
+
+        Ok(())
+    }
+
+    //Names buffer in hazards found for the rest of the recording
+    #[cfg(feature = "barrier-validation")]
+    pub fn name_buffer(&mut self, buffer: &'_ Buffer, name: &str) {
+        self.command_buffer.tracker.name(buffer, name);
+    }
+
+    #[cfg(feature = "barrier-validation")]
+    pub fn name_image(&mut self, image: &'_ Image, name: &str) {
+        self.command_buffer.tracker.name(image, name);
     }
 
     pub fn dispatch(&mut self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
@@ -5386,6 +11968,14 @@ impl Commands<'_> {
         dst_buffer: &mut Buffer,
         regions: &'_ [BufferCopy],
     ) {
+        #[cfg(feature = "barrier-validation")]
+        {
+            let tracker = &mut self.command_buffer.tracker;
+
+            tracker.read(src_buffer, "copy_buffer");
+            tracker.write(&*dst_buffer, "copy_buffer");
+        }
+
         let regions = regions
             .iter()
             .map(|copy| ffi::BufferCopy {
@@ -5413,6 +12003,66 @@ impl Commands<'_> {
         dst_image_layout: ImageLayout,
         regions: &'_ [BufferImageCopy],
     ) {
+        #[cfg(feature = "barrier-validation")]
+        {
+            let tracker = &mut self.command_buffer.tracker;
+
+            tracker.read(src_buffer, "copy_buffer_to_image");
+            tracker.write(&*dst_image, "copy_buffer_to_image");
+        }
+
+        let regions = regions
+            .iter()
+            .map(|copy| ffi::BufferImageCopy {
+                buffer_offset: copy.buffer_offset as _,
+                buffer_row_length: copy.buffer_row_length as _,
+                buffer_image_height: copy.buffer_image_height as _,
+                image_subresource: ffi::ImageSubresourceLayers {
+                    aspect_mask: copy.image_subresource.aspect_mask as _,
+                    mip_level: copy.image_subresource.mip_level as _,
+                    base_array_layer: copy.image_subresource.base_array_layer as _,
+                    layer_count: copy.image_subresource.layer_count as _,
+                },
+                image_offset: [
+                    copy.image_offset.0 as _,
+                    copy.image_offset.1 as _,
+                    copy.image_offset.2 as _,
+                ],
+                image_extent: [
+                    copy.image_extent.0 as _,
+                    copy.image_extent.1 as _,
+                    copy.image_extent.2 as _,
+                ],
+            })
+            .collect::<Vec<_>>();
+
+        unsafe {
+            ffi::vkCmdCopyBufferToImage(
+                self.command_buffer.handle,
+                src_buffer.handle,
+                dst_image.handle,
+                dst_image_layout.into(),
+                regions.len() as _,
+                regions.as_ptr(),
+            )
+        };
+    }
+
+    pub fn copy_image_to_buffer(
+        &mut self,
+        src_image: &Image,
+        src_image_layout: ImageLayout,
+        dst_buffer: &mut Buffer,
+        regions: &'_ [BufferImageCopy],
+    ) {
+        #[cfg(feature = "barrier-validation")]
+        {
+            let tracker = &mut self.command_buffer.tracker;
+
+            tracker.read(src_image, "copy_image_to_buffer");
+            tracker.write(&*dst_buffer, "copy_image_to_buffer");
+        }
+
         let regions = regions
             .iter()
             .map(|copy| ffi::BufferImageCopy {
@@ -5439,105 +12089,512 @@ impl Commands<'_> {
             .collect::<Vec<_>>();
 
         unsafe {
-            ffi::vkCmdCopyBufferToImage(
+            ffi::vkCmdCopyImageToBuffer(
+                self.command_buffer.handle,
+                src_image.handle,
+                src_image_layout.into(),
+                dst_buffer.handle,
+                regions.len() as _,
+                regions.as_ptr(),
+            )
+        };
+    }
+
+    pub fn copy_image(
+        &mut self,
+        src_image: &Image,
+        src_image_layout: ImageLayout,
+        dst_image: &mut Image,
+        dst_image_layout: ImageLayout,
+        regions: &'_ [ImageCopy],
+    ) {
+        #[cfg(feature = "barrier-validation")]
+        {
+            let tracker = &mut self.command_buffer.tracker;
+
+            tracker.read(src_image, "copy_image");
+            tracker.write(&*dst_image, "copy_image");
+        }
+
+        let regions = regions
+            .iter()
+            .map(|copy| ffi::ImageCopy {
+                src_subresource: copy.src_subresource.into(),
+                src_offset: [copy.src_offset.0, copy.src_offset.1, copy.src_offset.2],
+                dst_subresource: copy.dst_subresource.into(),
+                dst_offset: [copy.dst_offset.0, copy.dst_offset.1, copy.dst_offset.2],
+                extent: [copy.extent.0, copy.extent.1, copy.extent.2],
+            })
+            .collect::<Vec<_>>();
+
+        unsafe {
+            ffi::vkCmdCopyImage(
+                self.command_buffer.handle,
+                src_image.handle,
+                src_image_layout.into(),
+                dst_image.handle,
+                dst_image_layout.into(),
+                regions.len() as _,
+                regions.as_ptr(),
+            )
+        };
+    }
+
+    //Both formats need blit support in their optimal tiling features, Linear also
+    //needs them to be filterable
+    pub fn blit_image(
+        &mut self,
+        src_image: &Image,
+        src_image_layout: ImageLayout,
+        dst_image: &mut Image,
+        dst_image_layout: ImageLayout,
+        regions: &'_ [ImageBlit],
+        filter: Filter,
+    ) {
+        #[cfg(feature = "barrier-validation")]
+        {
+            let tracker = &mut self.command_buffer.tracker;
+
+            tracker.read(src_image, "blit_image");
+            tracker.write(&*dst_image, "blit_image");
+        }
+
+        let regions = regions
+            .iter()
+            .map(|blit| ffi::ImageBlit {
+                src_subresource: blit.src_subresource.into(),
+                src_offsets: blit.src_offsets.map(|offset| [offset.0, offset.1, offset.2]),
+                dst_subresource: blit.dst_subresource.into(),
+                dst_offsets: blit.dst_offsets.map(|offset| [offset.0, offset.1, offset.2]),
+            })
+            .collect::<Vec<_>>();
+
+        unsafe {
+            ffi::vkCmdBlitImage(
+                self.command_buffer.handle,
+                src_image.handle,
+                src_image_layout.into(),
+                dst_image.handle,
+                dst_image_layout.into(),
+                regions.len() as _,
+                regions.as_ptr(),
+                filter.into(),
+            )
+        };
+    }
+
+    //The source is multisampled and the destination single sampled, of the same format
+    pub fn resolve_image(
+        &mut self,
+        src_image: &Image,
+        src_image_layout: ImageLayout,
+        dst_image: &mut Image,
+        dst_image_layout: ImageLayout,
+        regions: &'_ [ImageResolve],
+    ) {
+        #[cfg(feature = "barrier-validation")]
+        {
+            let tracker = &mut self.command_buffer.tracker;
+
+            tracker.read(src_image, "resolve_image");
+            tracker.write(&*dst_image, "resolve_image");
+        }
+
+        let regions = regions
+            .iter()
+            .map(|resolve| ffi::ImageResolve {
+                src_subresource: resolve.src_subresource.into(),
+                src_offset: [resolve.src_offset.0, resolve.src_offset.1, resolve.src_offset.2],
+                dst_subresource: resolve.dst_subresource.into(),
+                dst_offset: [resolve.dst_offset.0, resolve.dst_offset.1, resolve.dst_offset.2],
+                extent: [resolve.extent.0, resolve.extent.1, resolve.extent.2],
+            })
+            .collect::<Vec<_>>();
+
+        unsafe {
+            ffi::vkCmdResolveImage(
+                self.command_buffer.handle,
+                src_image.handle,
+                src_image_layout.into(),
+                dst_image.handle,
+                dst_image_layout.into(),
+                regions.len() as _,
+                regions.as_ptr(),
+            )
+        };
+    }
+
+    pub fn pipeline_barrier(
+        &mut self,
+        src_stage_mask: u32,
+        dst_stage_mask: u32,
+        dependency_flags: u32,
+        memory_barriers: &'_ [MemoryBarrier],
+        buffer_memory_barriers: &'_ [BufferMemoryBarrier],
+        image_memory_barriers: &'_ [ImageMemoryBarrier],
+    ) {
+        #[cfg(feature = "barrier-validation")]
+        self.track_barrier(
+            !memory_barriers.is_empty(),
+            buffer_memory_barriers.iter().map(|barrier| barrier.buffer),
+            image_memory_barriers.iter().map(|barrier| barrier.image),
+        );
+
+        let scratch = self.command_buffer.device.scratch();
+
+        let memory_barriers = ffi_memory_barriers(&scratch, memory_barriers);
+        let buffer_memory_barriers = ffi_buffer_memory_barriers(&scratch, buffer_memory_barriers);
+        let image_memory_barriers = ffi_image_memory_barriers(&scratch, image_memory_barriers);
+
+        unsafe {
+            ffi::vkCmdPipelineBarrier(
+                self.command_buffer.handle,
+                src_stage_mask as _,
+                dst_stage_mask as _,
+                dependency_flags as _,
+                memory_barriers.len() as _,
+                slice_ptr(&memory_barriers),
+                buffer_memory_barriers.len() as _,
+                slice_ptr(&buffer_memory_barriers),
+                image_memory_barriers.len() as _,
+                slice_ptr(&image_memory_barriers),
+            )
+        };
+    }
+
+    pub fn set_event(&mut self, event: &'_ Event, stage_mask: u32) {
+        unsafe { ffi::vkCmdSetEvent(self.command_buffer.handle, event.handle, stage_mask) };
+    }
+
+    pub fn reset_event(&mut self, event: &'_ Event, stage_mask: u32) {
+        unsafe { ffi::vkCmdResetEvent(self.command_buffer.handle, event.handle, stage_mask) };
+    }
+
+    pub fn wait_events(
+        &mut self,
+        events: &'_ [&'_ Event],
+        src_stage_mask: u32,
+        dst_stage_mask: u32,
+        memory_barriers: &'_ [MemoryBarrier],
+        buffer_memory_barriers: &'_ [BufferMemoryBarrier],
+        image_memory_barriers: &'_ [ImageMemoryBarrier],
+    ) {
+        #[cfg(feature = "barrier-validation")]
+        self.track_barrier(
+            !memory_barriers.is_empty(),
+            buffer_memory_barriers.iter().map(|barrier| barrier.buffer),
+            image_memory_barriers.iter().map(|barrier| barrier.image),
+        );
+
+        let scratch = self.command_buffer.device.scratch();
+
+        let events = scratch.alloc_from_iter(events.iter().map(|event| event.handle));
+
+        let memory_barriers = ffi_memory_barriers(&scratch, memory_barriers);
+        let buffer_memory_barriers = ffi_buffer_memory_barriers(&scratch, buffer_memory_barriers);
+        let image_memory_barriers = ffi_image_memory_barriers(&scratch, image_memory_barriers);
+
+        unsafe {
+            ffi::vkCmdWaitEvents(
+                self.command_buffer.handle,
+                events.len() as _,
+                events.as_ptr(),
+                src_stage_mask,
+                dst_stage_mask,
+                memory_barriers.len() as _,
+                slice_ptr(&memory_barriers),
+                buffer_memory_barriers.len() as _,
+                slice_ptr(&buffer_memory_barriers),
+                image_memory_barriers.len() as _,
+                slice_ptr(&image_memory_barriers),
+            )
+        };
+    }
+
+    //The dependency must match the one later passed to wait_events2 for this event
+    pub fn set_event2(
+        &mut self,
+        event: &'_ Event,
+        dependency_info: &'_ DependencyInfo,
+    ) -> Result<(), Error> {
+        let f = self.synchronization2_fn::<ffi::CmdSetEvent2>(b"vkCmdSetEvent2KHR\0")?;
+
+        let scratch = self.command_buffer.device.scratch();
+
+        let dependency_info = ffi_dependency_info(&scratch, dependency_info);
+
+        unsafe { f(self.command_buffer.handle, event.handle, &dependency_info) };
+
+        Ok(())
+    }
+
+    //One dependency per event
+    pub fn wait_events2(
+        &mut self,
+        events: &'_ [&'_ Event],
+        dependency_infos: &'_ [DependencyInfo],
+    ) -> Result<(), Error> {
+        if events.len() != dependency_infos.len() {
+            return Err(Error::EventDependencyCountMismatch {
+                events: events.len(),
+                dependency_infos: dependency_infos.len(),
+            });
+        }
+
+        let f = self.synchronization2_fn::<ffi::CmdWaitEvents2>(b"vkCmdWaitEvents2KHR\0")?;
+
+        #[cfg(feature = "barrier-validation")]
+        dependency_infos
+            .iter()
+            .for_each(|dependency_info| self.track_dependency(dependency_info));
+
+        let scratch = self.command_buffer.device.scratch();
+
+        let events = scratch.alloc_from_iter(events.iter().map(|event| event.handle));
+
+        let infos = scratch.alloc_from_iter(
+            dependency_infos
+                .iter()
+                .map(|dependency_info| ffi_dependency_info(&scratch, dependency_info)),
+        );
+
+        unsafe {
+            f(
                 self.command_buffer.handle,
-                src_buffer.handle,
-                dst_image.handle,
-                dst_image_layout.into(),
-                regions.len() as _,
-                regions.as_ptr(),
+                events.len() as _,
+                events.as_ptr(),
+                infos.as_ptr(),
             )
         };
+
+        Ok(())
     }
 
-    pub fn pipeline_barrier(
+    pub fn pipeline_barrier2(&mut self, dependency_info: &'_ DependencyInfo) -> Result<(), Error> {
+        let f =
+            self.synchronization2_fn::<ffi::CmdPipelineBarrier2>(b"vkCmdPipelineBarrier2KHR\0")?;
+
+        #[cfg(feature = "barrier-validation")]
+        self.track_dependency(dependency_info);
+
+        let scratch = self.command_buffer.device.scratch();
+
+        let dependency_info = ffi_dependency_info(&scratch, dependency_info);
+
+        unsafe { f(self.command_buffer.handle, &dependency_info) };
+
+        Ok(())
+    }
+
+    #[cfg(feature = "barrier-validation")]
+    fn track_dependency(&mut self, dependency_info: &DependencyInfo<'_>) {
+        self.track_barrier(
+            !dependency_info.memory_barriers.is_empty(),
+            dependency_info
+                .buffer_memory_barriers
+                .iter()
+                .map(|barrier| barrier.buffer),
+            dependency_info
+                .image_memory_barriers
+                .iter()
+                .map(|barrier| barrier.image),
+        );
+    }
+
+    #[cfg(feature = "barrier-validation")]
+    fn track_barrier<'b>(
         &mut self,
-        src_stage_mask: u32,
-        dst_stage_mask: u32,
-        dependency_flags: u32,
-        memory_barriers: &'_ [MemoryBarrier],
-        buffer_memory_barriers: &'_ [BufferMemoryBarrier],
-        image_memory_barriers: &'_ [ImageMemoryBarrier],
+        global: bool,
+        buffers: impl Iterator<Item = &'b Buffer>,
+        images: impl Iterator<Item = &'b Image>,
     ) {
-        let memory_barriers = memory_barriers
-            .iter()
-            .map(|barrier| ffi::MemoryBarrier {
-                structure_type: ffi::StructureType::MemoryBarrier,
-                p_next: ptr::null(),
-                src_access_mask: barrier.src_access_mask as _,
-                dst_access_mask: barrier.dst_access_mask as _,
-            })
-            .collect::<Vec<_>>();
+        let tracker = &mut self.command_buffer.tracker;
 
-        let memory_barriers_ptr = if memory_barriers.len() > 0 {
-            memory_barriers.as_ptr()
-        } else {
-            ptr::null()
-        };
+        if global {
+            tracker.barrier_all();
+        }
 
-        let buffer_memory_barriers = buffer_memory_barriers
-            .iter()
-            .map(|barrier| ffi::BufferMemoryBarrier {
-                structure_type: ffi::StructureType::BufferMemoryBarrier,
+        buffers.for_each(|buffer| tracker.barrier(buffer));
+        images.for_each(|image| tracker.barrier(image));
+    }
+
+    fn synchronization2_fn<F>(&self, name: &[u8]) -> Result<F, Error> {
+        self.extension_fn(self.command_buffer.device.synchronization2, name)
+    }
+
+    fn dynamic_rendering_fn<F>(&self, name: &[u8]) -> Result<F, Error> {
+        self.extension_fn(self.command_buffer.device.dynamic_rendering, name)
+    }
+
+    fn extension_fn<F>(&self, enabled: bool, name: &[u8]) -> Result<F, Error> {
+        let device = &self.command_buffer.device;
+
+        if !enabled {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f_name = CStr::from_bytes_with_nul(name).unwrap();
+
+        let f = unsafe { ffi::vkGetDeviceProcAddr(device.handle, f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        Ok(unsafe { mem::transmute_copy::<_, F>(&f) })
+    }
+}
+
+fn slice_ptr<T>(slice: &[T]) -> *const T {
+    if slice.len() > 0 {
+        slice.as_ptr()
+    } else {
+        ptr::null()
+    }
+}
+
+fn ffi_subresource_range(range: &ImageSubresourceRange) -> ffi::ImageSubresourceRange {
+    ffi::ImageSubresourceRange {
+        aspect_mask: range.aspect_mask,
+        base_mip_level: range.base_mip_level,
+        level_count: range.level_count,
+        base_array_layer: range.base_array_layer,
+        layer_count: range.layer_count,
+    }
+}
+
+fn ffi_memory_barriers<'a>(
+    scratch: &'a Arena,
+    barriers: &[MemoryBarrier],
+) -> &'a [ffi::MemoryBarrier] {
+    scratch.alloc_from_iter(barriers.iter().map(|barrier| ffi::MemoryBarrier {
+        structure_type: ffi::StructureType::MemoryBarrier,
+        p_next: ptr::null(),
+        src_access_mask: barrier.src_access_mask as _,
+        dst_access_mask: barrier.dst_access_mask as _,
+    }))
+}
+
+fn ffi_buffer_memory_barriers<'a>(
+    scratch: &'a Arena,
+    barriers: &[BufferMemoryBarrier],
+) -> &'a [ffi::BufferMemoryBarrier] {
+    scratch.alloc_from_iter(barriers.iter().map(|barrier| ffi::BufferMemoryBarrier {
+        structure_type: ffi::StructureType::BufferMemoryBarrier,
+        p_next: ptr::null(),
+        src_access_mask: barrier.src_access_mask as _,
+        dst_access_mask: barrier.dst_access_mask as _,
+        src_queue_family_index: barrier.src_queue_family_index as _,
+        dst_queue_family_index: barrier.dst_queue_family_index as _,
+        buffer: barrier.buffer.handle,
+        offset: barrier.offset as _,
+        size: barrier.size as _,
+    }))
+}
+
+fn ffi_image_memory_barriers<'a>(
+    scratch: &'a Arena,
+    barriers: &[ImageMemoryBarrier],
+) -> &'a [ffi::ImageMemoryBarrier] {
+    scratch.alloc_from_iter(barriers.iter().map(|barrier| ffi::ImageMemoryBarrier {
+        structure_type: ffi::StructureType::ImageMemoryBarrier,
+        p_next: ptr::null(),
+        src_access_mask: barrier.src_access_mask as _,
+        dst_access_mask: barrier.dst_access_mask as _,
+        old_layout: barrier.old_layout.into(),
+        new_layout: barrier.new_layout.into(),
+        src_queue_family_index: barrier.src_queue_family_index as _,
+        dst_queue_family_index: barrier.dst_queue_family_index as _,
+        image: barrier.image.handle,
+        subresource_range: ffi_subresource_range(&barrier.subresource_range),
+    }))
+}
+
+fn ffi_rendering_attachment_info(
+    attachment: &RenderingAttachmentInfo<'_>,
+) -> ffi::RenderingAttachmentInfo {
+    let clear_value = match attachment.clear_value {
+        ClearValue::Color(rgba) => ffi::ClearValue {
+            color: ffi::ClearColorValue { rgba },
+        },
+        ClearValue::DepthStencil(depth, stencil) => ffi::ClearValue {
+            depth_stencil: ffi::ClearDepthStencilValue { depth, stencil },
+        },
+    };
+
+    ffi::RenderingAttachmentInfo {
+        structure_type: ffi::StructureType::RenderingAttachmentInfo,
+        p_next: ptr::null(),
+        image_view: attachment.image_view.handle,
+        image_layout: attachment.image_layout.into(),
+        resolve_mode: 0,
+        resolve_image_view: ffi::ImageView::null(),
+        resolve_image_layout: ffi::ImageLayout::Undefined,
+        load_op: attachment.load_op.into(),
+        store_op: attachment.store_op.into(),
+        clear_value,
+    }
+}
+
+//Points into scratch, which has to outlive the call it is passed to
+fn ffi_dependency_info(scratch: &Arena, info: &DependencyInfo) -> ffi::DependencyInfo {
+    let memory_barriers =
+        scratch.alloc_from_iter(info.memory_barriers.iter().map(|barrier| {
+            ffi::MemoryBarrier2 {
+                structure_type: ffi::StructureType::MemoryBarrier2,
                 p_next: ptr::null(),
-                src_access_mask: barrier.src_access_mask as _,
-                dst_access_mask: barrier.dst_access_mask as _,
-                src_queue_family_index: barrier.src_queue_family_index as _,
-                dst_queue_family_index: barrier.dst_queue_family_index as _,
-                buffer: barrier.buffer.handle,
-                offset: barrier.offset as _,
-                size: barrier.size as _,
-            })
-            .collect::<Vec<_>>();
+                src_stage_mask: barrier.src_stage_mask,
+                src_access_mask: barrier.src_access_mask,
+                dst_stage_mask: barrier.dst_stage_mask,
+                dst_access_mask: barrier.dst_access_mask,
+            }
+        }));
 
-        let buffer_memory_barriers_ptr = if buffer_memory_barriers.len() > 0 {
-            buffer_memory_barriers.as_ptr()
-        } else {
-            ptr::null()
-        };
+    let buffer_memory_barriers =
+        scratch.alloc_from_iter(info.buffer_memory_barriers.iter().map(|barrier| {
+            ffi::BufferMemoryBarrier2 {
+                structure_type: ffi::StructureType::BufferMemoryBarrier2,
+                p_next: ptr::null(),
+                src_stage_mask: barrier.src_stage_mask,
+                src_access_mask: barrier.src_access_mask,
+                dst_stage_mask: barrier.dst_stage_mask,
+                dst_access_mask: barrier.dst_access_mask,
+                src_queue_family_index: barrier.src_queue_family_index,
+                dst_queue_family_index: barrier.dst_queue_family_index,
+                buffer: barrier.buffer.handle,
+                offset: barrier.offset,
+                size: barrier.size,
+            }
+        }));
 
-        let image_memory_barriers = image_memory_barriers
-            .iter()
-            .map(|barrier| ffi::ImageMemoryBarrier {
-                structure_type: ffi::StructureType::ImageMemoryBarrier,
+    let image_memory_barriers =
+        scratch.alloc_from_iter(info.image_memory_barriers.iter().map(|barrier| {
+            ffi::ImageMemoryBarrier2 {
+                structure_type: ffi::StructureType::ImageMemoryBarrier2,
                 p_next: ptr::null(),
-                src_access_mask: barrier.src_access_mask as _,
-                dst_access_mask: barrier.dst_access_mask as _,
+                src_stage_mask: barrier.src_stage_mask,
+                src_access_mask: barrier.src_access_mask,
+                dst_stage_mask: barrier.dst_stage_mask,
+                dst_access_mask: barrier.dst_access_mask,
                 old_layout: barrier.old_layout.into(),
                 new_layout: barrier.new_layout.into(),
-                src_queue_family_index: barrier.src_queue_family_index as _,
-                dst_queue_family_index: barrier.dst_queue_family_index as _,
+                src_queue_family_index: barrier.src_queue_family_index,
+                dst_queue_family_index: barrier.dst_queue_family_index,
                 image: barrier.image.handle,
-                subresource_range: ffi::ImageSubresourceRange {
-                    aspect_mask: barrier.subresource_range.aspect_mask,
-                    base_mip_level: barrier.subresource_range.base_mip_level,
-                    level_count: barrier.subresource_range.level_count,
-                    base_array_layer: barrier.subresource_range.base_array_layer,
-                    layer_count: barrier.subresource_range.layer_count,
-                },
-            })
-            .collect::<Vec<_>>();
-
-        let image_memory_barriers_ptr = if image_memory_barriers.len() > 0 {
-            image_memory_barriers.as_ptr()
-        } else {
-            ptr::null()
-        };
+                subresource_range: ffi_subresource_range(&barrier.subresource_range),
+            }
+        }));
 
-        unsafe {
-            ffi::vkCmdPipelineBarrier(
-                self.command_buffer.handle,
-                src_stage_mask as _,
-                dst_stage_mask as _,
-                dependency_flags as _,
-                memory_barriers.len() as _,
-                memory_barriers_ptr,
-                buffer_memory_barriers.len() as _,
-                buffer_memory_barriers_ptr,
-                image_memory_barriers.len() as _,
-                image_memory_barriers_ptr,
-            )
-        };
+    ffi::DependencyInfo {
+        structure_type: ffi::StructureType::DependencyInfo,
+        p_next: ptr::null(),
+        dependency_flags: info.dependency_flags,
+        memory_barrier_count: memory_barriers.len() as _,
+        memory_barriers: slice_ptr(memory_barriers),
+        buffer_memory_barrier_count: buffer_memory_barriers.len() as _,
+        buffer_memory_barriers: slice_ptr(buffer_memory_barriers),
+        image_memory_barrier_count: image_memory_barriers.len() as _,
+        image_memory_barriers: slice_ptr(image_memory_barriers),
     }
 }
 
@@ -5549,6 +12606,37 @@ pub struct RenderPassBeginInfo<'a> {
     pub depth_stencil_clear_value: Option<(f32, u32)>,
 }
 
+#[derive(Clone, Copy)]
+pub enum ClearValue {
+    Color([f32; 4]),
+    DepthStencil(f32, u32),
+}
+
+pub struct RenderingAttachmentInfo<'a> {
+    pub image_view: &'a ImageView,
+    //the layout the image is in while rendering, no transition happens
+    pub image_layout: ImageLayout,
+    pub load_op: AttachmentLoadOp,
+    pub store_op: AttachmentStoreOp,
+    //used when load_op is Clear
+    pub clear_value: ClearValue,
+}
+
+pub struct RenderingInfo<'a> {
+    pub render_area: Rect2d,
+    pub layer_count: u32,
+    pub color_attachments: &'a [RenderingAttachmentInfo<'a>],
+    pub depth_attachment: Option<RenderingAttachmentInfo<'a>>,
+    pub stencil_attachment: Option<RenderingAttachmentInfo<'a>>,
+}
+
+//Attachment formats a pipeline renders to when it is created without a render pass
+pub struct PipelineRenderingCreateInfo<'a> {
+    pub color_attachment_formats: &'a [Format],
+    pub depth_attachment_format: Option<Format>,
+    pub stencil_attachment_format: Option<Format>,
+}
+
 pub struct SemaphoreCreateInfo {}
 
 pub struct Semaphore {
@@ -5579,23 +12667,119 @@ impl Semaphore {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let semaphore = Self { device, handle };
+                let semaphore = Self {
+                    device: Device::track(device, "Semaphore"),
+                    handle,
+                };
 
                 Ok(semaphore)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
 
 impl Drop for Semaphore {
     fn drop(&mut self) {
+        self.device.untrack("Semaphore");
+
         unsafe { ffi::vkDestroySemaphore(self.device.handle, self.handle, ptr::null()) };
     }
 }
 
+pub struct EventCreateInfo {}
+
+pub struct Event {
+    device: Rc<Device>,
+    handle: ffi::Event,
+}
+
+impl Event {
+    pub fn new(device: Rc<Device>, create_info: EventCreateInfo) -> Result<Self, Error> {
+        let create_info = ffi::EventCreateInfo {
+            structure_type: ffi::StructureType::EventCreateInfo,
+            p_next: ptr::null(),
+            flags: 0,
+        };
+
+        let mut handle = MaybeUninit::<ffi::Event>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreateEvent(
+                device.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                let event = Self {
+                    device: Device::track(device, "Event"),
+                    handle,
+                };
+
+                Ok(event)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    //Whether the event is signaled, by set or by a set_event command that executed
+    pub fn status(&self) -> Result<bool, Error> {
+        let result = unsafe { ffi::vkGetEventStatus(self.device.handle, self.handle) };
+
+        match result {
+            ffi::Result::EventSet => Ok(true),
+            ffi::Result::EventReset => Ok(false),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            ffi::Result::DeviceLost => Err(Error::DeviceLost),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    //Signals from the host, e.g. to release a wait_events recorded ahead of the data it
+    //waits on. Not for events a pending command buffer sets or resets.
+    pub fn set(&self) -> Result<(), Error> {
+        let result = unsafe { ffi::vkSetEvent(self.device.handle, self.handle) };
+
+        match result {
+            ffi::Result::Success => Ok(()),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    //Not for events a pending wait_events waits on
+    pub fn reset(&self) -> Result<(), Error> {
+        let result = unsafe { ffi::vkResetEvent(self.device.handle, self.handle) };
+
+        match result {
+            ffi::Result::Success => Ok(()),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+}
+
+impl Drop for Event {
+    fn drop(&mut self) {
+        self.device.untrack("Event");
+
+        unsafe { ffi::vkDestroyEvent(self.device.handle, self.handle, ptr::null()) };
+    }
+}
+
 pub struct FenceCreateInfo {}
 
 pub struct Fence {
@@ -5626,13 +12810,16 @@ impl Fence {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let fence = Self { device, handle };
+                let fence = Self {
+                    device: Device::track(device, "Fence"),
+                    handle,
+                };
 
                 Ok(fence)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 
@@ -5646,7 +12833,7 @@ impl Fence {
             .all(|fence| fence.device.handle == fences[0].device.handle);
 
         if !same_device {
-            panic!("fences must be for same device");
+            return Err(Error::MixedDevices);
         }
 
         let device_handle = fences[0].device.handle;
@@ -5664,11 +12851,26 @@ impl Fence {
         };
 
         match result {
-            ffi::Result::Success | ffi::Result::Timeout => Ok(()),
+            ffi::Result::Success => Ok(()),
+            ffi::Result::Timeout => Err(Error::Timeout),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            ffi::Result::DeviceLost => Err(Error::DeviceLost),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    //Signaled, without waiting
+    pub fn status(&self) -> Result<bool, Error> {
+        let result = unsafe { ffi::vkGetFenceStatus(self.device.handle, self.handle) };
+
+        match result {
+            ffi::Result::Success => Ok(true),
+            ffi::Result::NotReady => Ok(false),
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             ffi::Result::DeviceLost => Err(Error::DeviceLost),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 
@@ -5682,7 +12884,7 @@ impl Fence {
             .all(|fence| fence.device.handle == fences[0].device.handle);
 
         if !same_device {
-            panic!("fences must be for same device");
+            return Err(Error::MixedDevices);
         }
 
         let device_handle = fences[0].device.handle;
@@ -5695,14 +12897,192 @@ impl Fence {
         match result {
             ffi::Result::Success => Ok(()),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+}
+
+impl Drop for Fence {
+    fn drop(&mut self) {
+        self.device.untrack("Fence");
+
+        unsafe { ffi::vkDestroyFence(self.device.handle, self.handle, ptr::null()) };
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum QueryType {
+    Occlusion,
+    Timestamp,
+    PerformanceQuery,
+}
+
+pub struct QueryPoolPerformanceCreateInfo<'a> {
+    pub queue_family_index: u32,
+    pub counter_indices: &'a [u32],
+}
+
+pub struct QueryPoolCreateInfo<'a> {
+    pub query_type: QueryType,
+    pub query_count: u32,
+    //required for QueryType::PerformanceQuery
+    pub performance: Option<QueryPoolPerformanceCreateInfo<'a>>,
+}
+
+pub struct QueryPool {
+    device: Rc<Device>,
+    handle: ffi::QueryPool,
+}
+
+//Integer widths QueryPool::get_results can return
+pub trait QueryResult: Pod {
+    const FLAGS: u32;
+}
+
+impl QueryResult for u32 {
+    const FLAGS: u32 = 0;
+}
+
+impl QueryResult for u64 {
+    const FLAGS: u32 = QUERY_RESULT_64;
+}
+
+impl QueryPool {
+    pub fn new(device: Rc<Device>, create_info: QueryPoolCreateInfo<'_>) -> Result<Self, Error> {
+        let performance_create_info =
+            create_info
+                .performance
+                .as_ref()
+                .map(|performance| ffi::QueryPoolPerformanceCreateInfo {
+                    structure_type: ffi::StructureType::QueryPoolPerformanceCreateInfo,
+                    p_next: ptr::null(),
+                    queue_family_index: performance.queue_family_index,
+                    counter_index_count: performance.counter_indices.len() as _,
+                    counter_indices: performance.counter_indices.as_ptr(),
+                });
+
+        let create_info = ffi::QueryPoolCreateInfo {
+            structure_type: ffi::StructureType::QueryPoolCreateInfo,
+            p_next: performance_create_info
+                .as_ref()
+                .map_or(ptr::null(), |info| info as *const _ as _),
+            flags: 0,
+            query_type: create_info.query_type.into(),
+            query_count: create_info.query_count,
+            pipeline_statistics: 0,
+        };
+
+        let mut handle = MaybeUninit::<ffi::QueryPool>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreateQueryPool(
+                device.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                Ok(Self {
+                    device: Device::track(device, "QueryPool"),
+                    handle,
+                })
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    //Returns false when a result is not available yet, which only happens without QUERY_RESULT_WAIT
+    pub fn results(
+        &self,
+        first_query: u32,
+        query_count: u32,
+        data: &'_ mut [u8],
+        stride: u64,
+        flags: u32,
+    ) -> Result<bool, Error> {
+        let result = unsafe {
+            ffi::vkGetQueryPoolResults(
+                self.device.handle,
+                self.handle,
+                first_query,
+                query_count,
+                data.len(),
+                data.as_mut_ptr() as _,
+                stride,
+                flags,
+            )
+        };
+
+        match result {
+            ffi::Result::Success => Ok(true),
+            ffi::Result::NotReady => Ok(false),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            ffi::Result::DeviceLost => Err(Error::DeviceLost),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    //One result per query, None when a result is not available yet and flags lacks
+    //QUERY_RESULT_WAIT. QUERY_RESULT_64 is set for u64.
+    pub fn get_results<T: QueryResult>(
+        &self,
+        first_query: u32,
+        query_count: u32,
+        flags: u32,
+    ) -> Result<Option<Vec<T>>, Error> {
+        let stride = mem::size_of::<T>();
+
+        let mut data = vec![0u8; stride * query_count as usize];
+
+        if !self.results(first_query, query_count, &mut data, stride as _, flags | T::FLAGS)? {
+            return Ok(None);
         }
+
+        let values = data
+            .chunks_exact(stride)
+            .map(|raw| unsafe { ptr::read_unaligned(raw.as_ptr() as *const T) })
+            .collect();
+
+        Ok(Some(values))
+    }
+
+    //Waits for the query and decodes one value per counter the pool was created with
+    pub fn performance_counter_values(
+        &self,
+        query: u32,
+        storages: &'_ [PerformanceCounterStorage],
+    ) -> Result<Vec<PerformanceCounterValue>, Error> {
+        let stride = storages.len() * 8;
+
+        let mut data = vec![0u8; stride];
+
+        self.results(query, 1, &mut data, stride as _, QUERY_RESULT_WAIT)?;
+
+        let values = storages
+            .iter()
+            .zip(data.chunks_exact(8))
+            .map(|(&storage, raw)| {
+                PerformanceCounterValue::from_raw(storage, raw.try_into().unwrap())
+            })
+            .collect();
+
+        Ok(values)
     }
 }
 
-impl Drop for Fence {
+impl Drop for QueryPool {
     fn drop(&mut self) {
-        unsafe { ffi::vkDestroyFence(self.device.handle, self.handle, ptr::null()) };
+        self.device.untrack("QueryPool");
+
+        unsafe { ffi::vkDestroyQueryPool(self.device.handle, self.handle, ptr::null()) };
     }
 }
 
@@ -5711,12 +13091,58 @@ pub struct SubmitInfo<'a> {
     pub wait_stages: &'a [u32],
     pub signal_semaphores: &'a [&'a mut Semaphore],
     pub command_buffers: &'a [&'a CommandBuffer],
+    pub protected: bool,
 }
 
 pub struct PresentInfo<'a> {
     pub wait_semaphores: &'a [&'a Semaphore],
     pub swapchains: &'a [&'a Swapchain],
     pub image_indices: &'a [u32],
+    //Empty or one per swapchain, signaled once the present completed and its wait
+    //semaphores may be reused. Requires swapchain_maintenance1.
+    pub fences: &'a [&'a mut Fence],
+}
+
+pub struct CrossQueuePresentInfo<'a> {
+    pub producer_queue_family_index: u32,
+    pub present_queue_family_index: u32,
+    pub wait_semaphores: &'a [&'a Semaphore],
+    pub wait_stages: &'a [u32],
+    pub command_buffers: &'a [&'a CommandBuffer],
+    pub acquire_command_buffer: &'a mut CommandBuffer,
+    pub handoff_semaphore: &'a mut Semaphore,
+    pub present_semaphore: &'a mut Semaphore,
+    pub fence: Option<&'a mut Fence>,
+    pub swapchain: &'a Swapchain,
+    pub image: &'a Image,
+    pub image_index: u32,
+    pub old_layout: ImageLayout,
+}
+
+//Record this at the end of the producing command buffers to release the
+//swapchain image; Queue::present_across records the matching acquire.
+pub fn present_ownership_barrier<'a>(
+    image: &'a Image,
+    old_layout: ImageLayout,
+    src_queue_family_index: u32,
+    dst_queue_family_index: u32,
+) -> ImageMemoryBarrier<'a> {
+    ImageMemoryBarrier {
+        src_access_mask: 0,
+        dst_access_mask: 0,
+        old_layout,
+        new_layout: ImageLayout::PresentSrc,
+        src_queue_family_index,
+        dst_queue_family_index,
+        image,
+        subresource_range: ImageSubresourceRange {
+            aspect_mask: IMAGE_ASPECT_COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        },
+    }
 }
 
 pub struct Buffer {
@@ -5725,11 +13151,13 @@ pub struct Buffer {
 }
 
 impl Buffer {
-    pub fn new(device: Rc<Device>, size: u64, usage: u32) -> Result<Self, Error> {
+    //flags are BUFFER_CREATE_* bits, BUFFER_CREATE_PROTECTED to bind MEMORY_PROPERTY_PROTECTED
+    //memory
+    pub fn new(device: Rc<Device>, size: u64, usage: u32, flags: u32) -> Result<Self, Error> {
         let create_info = ffi::BufferCreateInfo {
             structure_type: ffi::StructureType::BufferCreateInfo,
             p_next: ptr::null(),
-            flags: 0,
+            flags,
             size: size as _,
             usage: usage as _,
             sharing_mode: ffi::SharingMode::Exclusive,
@@ -5752,17 +13180,46 @@ impl Buffer {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let buffer = Self { device, handle };
+                let buffer = Self {
+                    device: Device::track(device, "Buffer"),
+                    handle,
+                };
 
                 Ok(buffer)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             ffi::Result::InvalidOpaqueCaptureAddress => Err(Error::InvalidOpaqueCaptureAddress),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 
+    //Requires buffer_device_address on the device, BUFFER_USAGE_SHADER_DEVICE_ADDRESS and
+    //bound memory allocated with MEMORY_ALLOCATE_DEVICE_ADDRESS
+    pub fn device_address(&self) -> Result<u64, Error> {
+        if !self.device.buffer_device_address {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f_name = CStr::from_bytes_with_nul(b"vkGetBufferDeviceAddressKHR\0").unwrap();
+
+        let f = unsafe { ffi::vkGetDeviceProcAddr(self.device.handle, f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f = unsafe { mem::transmute::<_, ffi::GetBufferDeviceAddress>(f) };
+
+        let info = ffi::BufferDeviceAddressInfo {
+            structure_type: ffi::StructureType::BufferDeviceAddressInfo,
+            p_next: ptr::null(),
+            buffer: self.handle,
+        };
+
+        Ok(unsafe { f(self.device.handle, &info) })
+    }
+
     pub fn memory_requirements(&self) -> MemoryRequirements {
         let mut memory_requirements = MaybeUninit::<ffi::MemoryRequirements>::uninit();
 
@@ -5784,21 +13241,29 @@ impl Buffer {
     }
 
     pub fn bind_memory(&mut self, memory: &Memory) -> Result<(), Error> {
-        let result =
-            unsafe { ffi::vkBindBufferMemory(self.device.handle, self.handle, memory.handle, 0) };
+        self.bind_memory_at(memory, 0)
+    }
+
+    //offset must respect the alignment from memory_requirements
+    pub fn bind_memory_at(&mut self, memory: &Memory, offset: u64) -> Result<(), Error> {
+        let result = unsafe {
+            ffi::vkBindBufferMemory(self.device.handle, self.handle, memory.handle, offset)
+        };
 
         match result {
             ffi::Result::Success => Ok(()),
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             ffi::Result::InvalidOpaqueCaptureAddress => Err(Error::InvalidOpaqueCaptureAddress),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
 
 impl Drop for Buffer {
     fn drop(&mut self) {
+        self.device.untrack("Buffer");
+
         unsafe { ffi::vkDestroyBuffer(self.device.handle, self.handle, ptr::null()) };
     }
 }
@@ -5809,6 +13274,7 @@ pub enum DescriptorType {
     StorageImage,
     UniformBuffer,
     StorageBuffer,
+    UniformBufferDynamic,
 }
 
 pub struct DescriptorSetLayoutBinding {
@@ -5819,7 +13285,13 @@ pub struct DescriptorSetLayoutBinding {
 }
 
 pub struct DescriptorSetLayoutCreateInfo<'a> {
+    pub flags: u32,
     pub bindings: &'a [DescriptorSetLayoutBinding],
+    //one entry per binding or empty, requires EXT_DESCRIPTOR_INDEXING
+    pub binding_flags: &'a [u32],
+    //samplers baked into the layout, one per descriptor of the binding.
+    //Required for samplers with a SamplerYcbcrConversion.
+    pub immutable_samplers: &'a [(u32, &'a Sampler)],
 }
 
 pub struct DescriptorSetLayout {
@@ -5832,22 +13304,51 @@ impl DescriptorSetLayout {
         device: Rc<Device>,
         create_info: DescriptorSetLayoutCreateInfo<'_>,
     ) -> Result<Self, Error> {
+        let immutable_samplers = create_info
+            .bindings
+            .iter()
+            .map(|binding| {
+                create_info
+                    .immutable_samplers
+                    .iter()
+                    .filter(|(index, _)| *index == binding.binding)
+                    .map(|(_, sampler)| sampler.handle)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
         let bindings = create_info
             .bindings
             .iter()
-            .map(|binding| ffi::DescriptorSetLayoutBinding {
+            .zip(&immutable_samplers)
+            .map(|(binding, immutable_samplers)| ffi::DescriptorSetLayoutBinding {
                 binding: binding.binding as _,
                 descriptor_type: binding.descriptor_type.into(),
                 descriptor_count: binding.descriptor_count as _,
                 stage: binding.stage as _,
-                immutable_samplers: ptr::null(),
+                immutable_samplers: if immutable_samplers.len() > 0 {
+                    immutable_samplers.as_ptr()
+                } else {
+                    ptr::null()
+                },
             })
             .collect::<Vec<_>>();
 
+        let binding_flags = ffi::DescriptorSetLayoutBindingFlagsCreateInfo {
+            structure_type: ffi::StructureType::DescriptorSetLayoutBindingFlagsCreateInfo,
+            p_next: ptr::null(),
+            binding_count: create_info.binding_flags.len() as _,
+            binding_flags: create_info.binding_flags.as_ptr(),
+        };
+
         let create_info = ffi::DescriptorSetLayoutCreateInfo {
             structure_type: ffi::StructureType::DescriptorSetLayoutCreateInfo,
-            p_next: ptr::null(),
-            flags: 0,
+            p_next: if create_info.binding_flags.len() > 0 {
+                &binding_flags as *const _ as _
+            } else {
+                ptr::null()
+            },
+            flags: create_info.flags,
             binding_count: create_info.bindings.len() as _,
             bindings: bindings.as_ptr(),
         };
@@ -5867,19 +13368,31 @@ impl DescriptorSetLayout {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let descriptor_set_layout = Self { device, handle };
+                let descriptor_set_layout = Self {
+                    device: Device::track(device, "DescriptorSetLayout"),
+                    handle,
+                };
 
                 Ok(descriptor_set_layout)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
 
+//Hashes the handle, identifying the object for as long as it is alive
+impl Hash for DescriptorSetLayout {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.handle.hash(state);
+    }
+}
+
 impl Drop for DescriptorSetLayout {
     fn drop(&mut self) {
+        self.device.untrack("DescriptorSetLayout");
+
         unsafe { ffi::vkDestroyDescriptorSetLayout(self.device.handle, self.handle, ptr::null()) };
     }
 }
@@ -5887,6 +13400,8 @@ impl Drop for DescriptorSetLayout {
 pub struct DescriptorSetAllocateInfo<'a> {
     pub descriptor_pool: &'a DescriptorPool,
     pub set_layouts: &'a [&'a DescriptorSetLayout],
+    //one entry per set layout or empty, requires EXT_DESCRIPTOR_INDEXING
+    pub variable_descriptor_counts: &'a [u32],
 }
 
 pub struct DescriptorSet {
@@ -5905,9 +13420,20 @@ impl DescriptorSet {
             .map(|set_layout| set_layout.handle)
             .collect::<Vec<_>>();
 
+        let variable_descriptor_counts = ffi::DescriptorSetVariableDescriptorCountAllocateInfo {
+            structure_type: ffi::StructureType::DescriptorSetVariableDescriptorCountAllocateInfo,
+            p_next: ptr::null(),
+            descriptor_set_count: allocate_info.variable_descriptor_counts.len() as _,
+            descriptor_counts: allocate_info.variable_descriptor_counts.as_ptr(),
+        };
+
         let allocate_info = ffi::DescriptorSetAllocateInfo {
             structure_type: ffi::StructureType::DescriptorSetAllocateInfo,
-            p_next: ptr::null(),
+            p_next: if allocate_info.variable_descriptor_counts.len() > 0 {
+                &variable_descriptor_counts as *const _ as _
+            } else {
+                ptr::null()
+            },
             descriptor_pool: allocate_info.descriptor_pool.handle,
             descriptor_set_count: set_layouts.len() as _,
             set_layouts: set_layouts.as_ptr(),
@@ -5927,7 +13453,7 @@ impl DescriptorSet {
                 let descriptor_sets = handles
                     .into_iter()
                     .map(|handle| Self {
-                        device: device.clone(),
+                        device: Device::track(device.clone(), "DescriptorSet"),
                         handle,
                     })
                     .collect::<Vec<_>>();
@@ -5938,151 +13464,216 @@ impl DescriptorSet {
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             ffi::Result::FragmentedPool => Err(Error::FragmentedPool),
             ffi::Result::OutOfPoolMemory => Err(Error::OutOfPoolMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
+}
 
-    pub fn update(writes: &'_ [WriteDescriptorSet], copies: &'_ [CopyDescriptorSet]) {
-        if writes.len() == 0 && copies.len() == 0 {
-            return;
-        }
+impl Drop for DescriptorSet {
+    fn drop(&mut self) {
+        self.device.untrack("DescriptorSet");
+    }
+}
 
-        let same_device_writes = writes
-            .iter()
-            .all(|write| write.dst_set.device.handle == writes[0].dst_set.device.handle);
+pub struct DescriptorBufferInfo<'a> {
+    pub buffer: &'a Buffer,
+    pub offset: usize,
+    pub range: usize,
+}
 
-        let same_device_copies = copies
-            .iter()
-            .all(|copy| copy.dst_set.device.handle == copies[0].dst_set.device.handle);
+pub struct DescriptorImageInfo<'a> {
+    pub sampler: &'a Sampler,
+    pub image_view: &'a ImageView,
+    pub image_layout: ImageLayout,
+}
 
-        if !same_device_writes || !same_device_copies {
-            panic!("descriptor set write or copy must be for same device");
+pub struct WriteDescriptorSet<'a> {
+    pub dst_set: &'a DescriptorSet,
+    pub dst_binding: u32,
+    pub dst_array_element: u32,
+    pub descriptor_count: u32,
+    pub descriptor_type: DescriptorType,
+    pub buffer_infos: &'a [DescriptorBufferInfo<'a>],
+    pub image_infos: &'a [DescriptorImageInfo<'a>],
+}
+
+//One descriptor as a descriptor update template reads it, either a buffer or an image
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union DescriptorData {
+    buffer: ffi::DescriptorBufferInfo,
+    image: ffi::DescriptorImageInfo,
+}
+
+impl DescriptorData {
+    pub fn buffer(buffer_info: DescriptorBufferInfo<'_>) -> Self {
+        Self {
+            buffer: ffi::DescriptorBufferInfo {
+                buffer: buffer_info.buffer.handle,
+                offset: buffer_info.offset as _,
+                range: buffer_info.range as _,
+            },
         }
+    }
 
-        let device = if writes.len() > 0 {
-            writes[0].dst_set.device.clone()
-        } else {
-            copies[0].dst_set.device.clone()
-        };
+    pub fn image(image_info: DescriptorImageInfo<'_>) -> Self {
+        Self {
+            image: ffi::DescriptorImageInfo {
+                sampler: image_info.sampler.handle,
+                image_view: image_info.image_view.handle,
+                image_layout: image_info.image_layout.into(),
+            },
+        }
+    }
+}
 
-        let write_buffer_infos = writes
-            .iter()
-            .map(|write| {
-                write
-                    .buffer_infos
-                    .iter()
-                    .map(|buffer_info| ffi::DescriptorBufferInfo {
-                        buffer: buffer_info.buffer.handle,
-                        offset: buffer_info.offset as _,
-                        range: buffer_info.range as _,
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
+//Data for Device::update_descriptor_set_with_template. Implementors must be repr(C) structs
+//or arrays of DescriptorData, laid out like the entries of the templates they are used with.
+pub unsafe trait DescriptorTemplateData: Copy + 'static {}
 
-        let write_buffer_info_ptrs = writes
-            .iter()
-            .enumerate()
-            .map(|(i, write)| {
-                if write.buffer_infos.len() > 0 {
-                    write_buffer_infos[i].as_ptr()
-                } else {
-                    ptr::null()
-                }
-            })
-            .collect::<Vec<_>>();
+unsafe impl DescriptorTemplateData for DescriptorData {}
 
-        let write_image_infos = writes
-            .iter()
-            .map(|write| {
-                write
-                    .image_infos
-                    .iter()
-                    .map(|image_info| ffi::DescriptorImageInfo {
-                        sampler: image_info.sampler.handle,
-                        image_view: image_info.image_view.handle,
-                        image_layout: image_info.image_layout.into(),
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
+unsafe impl<const N: usize> DescriptorTemplateData for [DescriptorData; N] {}
 
-        let write_image_info_ptrs = writes
-            .iter()
-            .enumerate()
-            .map(|(i, write)| {
-                if write.image_infos.len() > 0 {
-                    write_image_infos[i].as_ptr()
-                } else {
-                    ptr::null()
-                }
+//offset and stride are in bytes from the start of the data
+#[derive(Clone, Copy)]
+pub struct DescriptorUpdateTemplateEntry {
+    pub dst_binding: u32,
+    pub dst_array_element: u32,
+    pub descriptor_count: u32,
+    pub descriptor_type: DescriptorType,
+    pub offset: usize,
+    pub stride: usize,
+}
+
+impl DescriptorUpdateTemplateEntry {
+    //Maps the bindings onto consecutive DescriptorData in binding order, every descriptor
+    //of an array binding taking one, the layout of a repr(C) struct of DescriptorData fields
+    pub fn sequential(bindings: &'_ [DescriptorSetLayoutBinding]) -> Vec<Self> {
+        let mut bindings = bindings.iter().collect::<Vec<_>>();
+
+        bindings.sort_by_key(|binding| binding.binding);
+
+        let mut offset = 0;
+
+        bindings
+            .into_iter()
+            .map(|binding| {
+                let entry = Self {
+                    dst_binding: binding.binding,
+                    dst_array_element: 0,
+                    descriptor_count: binding.descriptor_count,
+                    descriptor_type: binding.descriptor_type,
+                    offset,
+                    stride: mem::size_of::<DescriptorData>(),
+                };
+
+                offset += binding.descriptor_count as usize * mem::size_of::<DescriptorData>();
+
+                entry
             })
-            .collect::<Vec<_>>();
+            .collect()
+    }
+}
+
+pub struct DescriptorUpdateTemplateCreateInfo<'a> {
+    pub descriptor_set_layout: &'a DescriptorSetLayout,
+    pub entries: &'a [DescriptorUpdateTemplateEntry],
+}
+
+pub struct DescriptorUpdateTemplate {
+    device: Rc<Device>,
+    handle: ffi::DescriptorUpdateTemplate,
+    //bytes the data must span to reach the last descriptor of every entry
+    data_size: usize,
+}
 
-        let writes = writes
+impl DescriptorUpdateTemplate {
+    pub fn new(
+        device: Rc<Device>,
+        create_info: DescriptorUpdateTemplateCreateInfo<'_>,
+    ) -> Result<Self, Error> {
+        let data_size = create_info
+            .entries
             .iter()
-            .enumerate()
-            .map(|(i, write)| ffi::WriteDescriptorSet {
-                structure_type: ffi::StructureType::WriteDescriptorSet,
-                p_next: ptr::null(),
-                dst_set: write.dst_set.handle,
-                dst_binding: write.dst_binding,
-                dst_array_element: write.dst_array_element,
-                descriptor_count: write.descriptor_count,
-                descriptor_type: write.descriptor_type.into(),
-                image_infos: write_image_info_ptrs[i],
-                buffer_infos: write_buffer_info_ptrs[i],
-                texel_buffer_view: ptr::null(),
+            .filter(|entry| entry.descriptor_count > 0)
+            .map(|entry| {
+                entry.offset
+                    + (entry.descriptor_count as usize - 1) * entry.stride
+                    + mem::size_of::<DescriptorData>()
             })
-            .collect::<Vec<_>>();
+            .max()
+            .unwrap_or(0);
 
-        let copies = copies
+        let entries = create_info
+            .entries
             .iter()
-            .map(|copy| ffi::CopyDescriptorSet {
-                structure_type: ffi::StructureType::CopyDescriptorSet,
-                p_next: ptr::null(),
-                src_set: copy.src_set.handle,
-                src_binding: copy.src_binding,
-                src_array_element: copy.src_array_element,
-                dst_set: copy.dst_set.handle,
-                dst_binding: copy.dst_binding,
-                dst_array_element: copy.dst_array_element,
-                descriptor_count: copy.descriptor_count,
+            .map(|entry| ffi::DescriptorUpdateTemplateEntry {
+                dst_binding: entry.dst_binding,
+                dst_array_element: entry.dst_array_element,
+                descriptor_count: entry.descriptor_count,
+                descriptor_type: entry.descriptor_type.into(),
+                offset: entry.offset,
+                stride: entry.stride,
             })
             .collect::<Vec<_>>();
 
-        unsafe {
-            ffi::vkUpdateDescriptorSets(
+        let create_info = ffi::DescriptorUpdateTemplateCreateInfo {
+            structure_type: ffi::StructureType::DescriptorUpdateTemplateCreateInfo,
+            p_next: ptr::null(),
+            flags: 0,
+            descriptor_update_entry_count: entries.len() as _,
+            descriptor_update_entries: entries.as_ptr(),
+            template_type: ffi::DescriptorUpdateTemplateType::DescriptorSet,
+            descriptor_set_layout: create_info.descriptor_set_layout.handle,
+            //only read by push descriptor templates
+            pipeline_bind_point: ffi::PipelineBindPoint::Graphics,
+            pipeline_layout: ffi::PipelineLayout::null(),
+            set: 0,
+        };
+
+        let mut handle = MaybeUninit::<ffi::DescriptorUpdateTemplate>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreateDescriptorUpdateTemplate(
                 device.handle,
-                writes.len() as _,
-                writes.as_ptr(),
-                copies.len() as _,
-                copies.as_ptr(),
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
             )
         };
+
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                let descriptor_update_template = Self {
+                    device: Device::track(device, "DescriptorUpdateTemplate"),
+                    handle,
+                    data_size,
+                };
+
+                Ok(descriptor_update_template)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => Err(unexpected_result(result)),
+        }
     }
-}
 
-pub struct DescriptorBufferInfo<'a> {
-    pub buffer: &'a Buffer,
-    pub offset: usize,
-    pub range: usize,
+    pub fn data_size(&self) -> usize {
+        self.data_size
+    }
 }
 
-pub struct DescriptorImageInfo<'a> {
-    pub sampler: &'a Sampler,
-    pub image_view: &'a ImageView,
-    pub image_layout: ImageLayout,
-}
+impl Drop for DescriptorUpdateTemplate {
+    fn drop(&mut self) {
+        self.device.untrack("DescriptorUpdateTemplate");
 
-pub struct WriteDescriptorSet<'a> {
-    pub dst_set: &'a DescriptorSet,
-    pub dst_binding: u32,
-    pub dst_array_element: u32,
-    pub descriptor_count: u32,
-    pub descriptor_type: DescriptorType,
-    pub buffer_infos: &'a [DescriptorBufferInfo<'a>],
-    pub image_infos: &'a [DescriptorImageInfo<'a>],
+        unsafe {
+            ffi::vkDestroyDescriptorUpdateTemplate(self.device.handle, self.handle, ptr::null())
+        };
+    }
 }
 
 pub struct CopyDescriptorSet<'a> {
@@ -6101,6 +13692,7 @@ pub struct DescriptorPoolSize {
 }
 
 pub struct DescriptorPoolCreateInfo<'a> {
+    pub flags: u32,
     pub max_sets: u32,
     pub pool_sizes: &'a [DescriptorPoolSize],
 }
@@ -6127,7 +13719,7 @@ impl DescriptorPool {
         let create_info = ffi::DescriptorPoolCreateInfo {
             structure_type: ffi::StructureType::DescriptorPoolCreateInfo,
             p_next: ptr::null(),
-            flags: 0,
+            flags: create_info.flags,
             max_sets: create_info.max_sets,
             pool_size_count: create_info.pool_sizes.len() as _,
             pool_sizes: pool_sizes.as_ptr(),
@@ -6148,47 +13740,103 @@ impl DescriptorPool {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let descriptor_pool = Self { device, handle };
+                let descriptor_pool = Self {
+                    device: Device::track(device, "DescriptorPool"),
+                    handle,
+                };
 
                 Ok(descriptor_pool)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             ffi::Result::Fragmentation => Err(Error::Fragmentation),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
 
 impl Drop for DescriptorPool {
     fn drop(&mut self) {
+        self.device.untrack("DescriptorPool");
+
         unsafe { ffi::vkDestroyDescriptorPool(self.device.handle, self.handle, ptr::null()) };
     }
 }
 
+//Plain old data that may be copied to and from mapped memory byte for byte.
+//Implementors must be Copy, contain no padding and be valid for any bit pattern,
+//which in practice means repr(C) structs made of Pod fields without gaps.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+    ($($ty:ty),*) => {
+        $(unsafe impl Pod for $ty {})*
+    };
+}
+
+impl_pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+#[derive(Clone)]
 pub struct MemoryType {
     property_flags: u32,
     heap_index: u32,
 }
 
+#[derive(Clone)]
 pub struct MemoryHeap {
     size: u64,
     flags: u32,
 }
 
+#[derive(Clone)]
 pub struct MemoryProperties {
     memory_types: Vec<MemoryType>,
     memory_heaps: Vec<MemoryHeap>,
 }
 
+impl MemoryProperties {
+    //prefer a type with every requested property, e.g. device local and lazily allocated
+    //on tilers, before settling for any overlap
+    pub(crate) fn memory_type_index(
+        &self,
+        requirements: &MemoryRequirements,
+        property_flags: u32,
+    ) -> Result<usize, Error> {
+        let allowed = |i: usize| requirements.memory_type & (1 << i) != 0;
+
+        (0..self.memory_types.len())
+            .find(|&i| {
+                allowed(i) && self.memory_types[i].property_flags & property_flags == property_flags
+            })
+            .or_else(|| {
+                (0..self.memory_types.len())
+                    .find(|&i| allowed(i) && self.memory_types[i].property_flags & property_flags != 0)
+            })
+            .ok_or(Error::NoSuitableMemoryType {
+                memory_type_bits: requirements.memory_type,
+                property_flags,
+            })
+    }
+}
+
+#[derive(Clone, Copy)]
 pub struct MemoryRequirements {
-    size: u64,
-    alignment: u64,
-    memory_type: u32,
+    pub size: u64,
+    pub alignment: u64,
+    //bitmask of allowed memory type indices
+    pub memory_type: u32,
 }
 
 pub struct MemoryAllocateInfo {
     pub property_flags: u32,
+    //MEMORY_ALLOCATE_* bits, MEMORY_ALLOCATE_DEVICE_ADDRESS for buffers created with
+    //BUFFER_USAGE_SHADER_DEVICE_ADDRESS
+    pub flags: u32,
+    //0.0 to 1.0, which allocations stay resident under memory pressure. Ignored unless
+    //the device enabled memory_priority
+    pub priority: Option<f32>,
 }
 
 pub struct Memory {
@@ -6196,6 +13844,8 @@ pub struct Memory {
     handle: ffi::DeviceMemory,
     size: u64,
     mem: Option<*mut u8>,
+    lazily_allocated: bool,
+    host_coherent: bool,
 }
 
 impl Memory {
@@ -6208,24 +13858,54 @@ impl Memory {
     ) -> Result<Self, Error> {
         let size = requirements.size;
 
-        let mut memory_type_index = None;
+        let memory_type_index =
+            properties.memory_type_index(&requirements, allocate_info.property_flags)?;
+
+        let lazily_allocated = properties.memory_types[memory_type_index].property_flags
+            & MEMORY_PROPERTY_LAZILY_ALLOCATED
+            != 0;
+
+        let host_coherent = properties.memory_types[memory_type_index].property_flags
+            & MEMORY_PROPERTY_HOST_COHERENT
+            != 0;
+
+        let memory_type_index = memory_type_index as _;
+
+        #[cfg(feature = "fault-injection")]
+        if device.allocation_fault(
+            size,
+            memory_type_index,
+            properties.memory_types[memory_type_index as usize].property_flags,
+        ) {
+            return Err(Error::OutOfDeviceMemory);
+        }
+
+        let priority_allocate_info = ffi::MemoryPriorityAllocateInfo {
+            structure_type: ffi::StructureType::MemoryPriorityAllocateInfo,
+            p_next: ptr::null(),
+            priority: allocate_info.priority.unwrap_or(0.5).clamp(0.0, 1.0),
+        };
+
+        let mut p_next = if allocate_info.priority.is_some() && device.memory_priority {
+            &priority_allocate_info as *const _ as _
+        } else {
+            ptr::null()
+        };
+
+        let flags_allocate_info = ffi::MemoryAllocateFlagsInfo {
+            structure_type: ffi::StructureType::MemoryAllocateFlagsInfo,
+            p_next,
+            flags: allocate_info.flags,
+            device_mask: 0,
+        };
 
-        for i in 0..properties.memory_types.len() {
-            if requirements.memory_type & (1 << i) != 0
-                && properties.memory_types[i as usize].property_flags & allocate_info.property_flags
-                    != 0
-            {
-                memory_type_index = Some(i);
-                break;
-            }
+        if allocate_info.flags != 0 {
+            p_next = &flags_allocate_info as *const _ as _;
         }
 
-        let memory_type_index =
-            memory_type_index.unwrap_or_else(|| panic!("couldnt find valid memory type")) as _;
-
         let allocate_info = ffi::MemoryAllocateInfo {
             structure_type: ffi::StructureType::MemoryAllocateInfo,
-            p_next: ptr::null(),
+            p_next,
             size,
             memory_type_index,
         };
@@ -6257,7 +13937,7 @@ impl Memory {
                         ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
                         ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
                         ffi::Result::MemoryMapFailed => Err(Error::MemoryMapFailed)?,
-                        _ => panic!("unexpected result: {:?}", result),
+                        _ => Err(unexpected_result(result))?,
                     }
 
                     Some(mem)
@@ -6266,10 +13946,12 @@ impl Memory {
                 };
 
                 let memory = Self {
-                    device,
+                    device: Device::track(device, "Memory"),
                     handle,
                     size,
                     mem,
+                    lazily_allocated,
+                    host_coherent,
                 };
 
                 Ok(memory)
@@ -6278,21 +13960,30 @@ impl Memory {
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             ffi::Result::InvalidExternalHandle => Err(Error::InvalidExternalHandle),
             ffi::Result::InvalidOpaqueCaptureAddress => Err(Error::InvalidOpaqueCaptureAddress),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 
-    pub fn write<'a, T: 'a>(
+    pub fn write<T: Pod>(
         &self,
         offset: usize,
-        mut script: impl FnMut(&'a mut [T]),
+        mut script: impl FnMut(&mut [T]),
     ) -> Result<(), Error> {
         if offset > self.size as _ {
-            panic!("attempt to overflow buffer");
+            Err(Error::MemoryOutOfRange {
+                offset,
+                size: self.size,
+            })?
         }
 
         let mem = self.mem.ok_or(Error::MemoryMapFailed)?;
 
+        let alignment = mem::align_of::<T>();
+
+        if (mem as usize + offset) % alignment != 0 {
+            Err(Error::MemoryMisaligned { offset, alignment })?
+        }
+
         let data = unsafe {
             slice::from_raw_parts_mut(
                 mem.add(offset) as _,
@@ -6304,10 +13995,112 @@ impl Memory {
 
         Ok(())
     }
+
+    pub fn read<T: Pod>(&self, offset: usize, mut script: impl FnMut(&[T])) -> Result<(), Error> {
+        if offset > self.size as _ {
+            Err(Error::MemoryOutOfRange {
+                offset,
+                size: self.size,
+            })?
+        }
+
+        let mem = self.mem.ok_or(Error::MemoryMapFailed)?;
+
+        let alignment = mem::align_of::<T>();
+
+        if (mem as usize + offset) % alignment != 0 {
+            Err(Error::MemoryMisaligned { offset, alignment })?
+        }
+
+        let data = unsafe {
+            slice::from_raw_parts(
+                mem.add(offset) as _,
+                (self.size as usize - offset) / mem::size_of::<T>(),
+            )
+        };
+
+        script(data);
+
+        Ok(())
+    }
+
+    pub fn is_mapped(&self) -> bool {
+        self.mem.is_some()
+    }
+
+    //Makes device writes visible to the mapping, a no-op for unmapped or host coherent memory
+    pub fn invalidate(&self) -> Result<(), Error> {
+        if self.mem.is_none() || self.host_coherent {
+            return Ok(());
+        }
+
+        let range = ffi::MappedMemoryRange {
+            structure_type: ffi::StructureType::MappedMemoryRange,
+            p_next: ptr::null(),
+            memory: self.handle,
+            offset: 0,
+            size: !0,
+        };
+
+        let result = unsafe { ffi::vkInvalidateMappedMemoryRanges(self.device.handle, 1, &range) };
+
+        match result {
+            ffi::Result::Success => Ok(()),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+
+    //Changes the priority of a live allocation, requires pageable_device_local_memory
+    pub fn set_priority(&self, priority: f32) -> Result<(), Error> {
+        if !self.device.pageable_device_local_memory {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f_name = CStr::from_bytes_with_nul(b"vkSetDeviceMemoryPriorityEXT\0").unwrap();
+
+        let f = unsafe { ffi::vkGetDeviceProcAddr(self.device.handle, f_name.as_ptr()) };
+
+        if f == ptr::null() {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let f = unsafe { mem::transmute::<_, ffi::SetDeviceMemoryPriority>(f) };
+
+        unsafe { f(self.device.handle, self.handle, priority.clamp(0.0, 1.0)) };
+
+        Ok(())
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    //Backed by a lazily allocated type, so only transient attachments may be bound to it
+    pub fn is_lazily_allocated(&self) -> bool {
+        self.lazily_allocated
+    }
+
+    //Bytes the implementation has committed so far. Lazily allocated memory grows as tiles
+    //spill, every other allocation is committed in full.
+    pub fn commitment(&self) -> u64 {
+        if !self.lazily_allocated {
+            return self.size;
+        }
+
+        let mut committed = 0;
+
+        unsafe { ffi::vkGetDeviceMemoryCommitment(self.device.handle, self.handle, &mut committed) };
+
+        committed
+    }
 }
 
 impl Drop for Memory {
     fn drop(&mut self) {
+        self.device.untrack("Memory");
+
         if let Some(_) = self.mem {
             unsafe { ffi::vkUnmapMemory(self.device.handle, self.handle) };
         }
@@ -6321,6 +14114,7 @@ pub struct BufferCopy {
     pub size: u64,
 }
 
+#[derive(Clone, Copy)]
 pub struct ImageSubresourceLayers {
     pub aspect_mask: u32,
     pub mip_level: u32,
@@ -6337,6 +14131,30 @@ pub struct BufferImageCopy {
     pub image_extent: Extent3d,
 }
 
+pub struct ImageCopy {
+    pub src_subresource: ImageSubresourceLayers,
+    pub src_offset: Offset3d,
+    pub dst_subresource: ImageSubresourceLayers,
+    pub dst_offset: Offset3d,
+    pub extent: Extent3d,
+}
+
+//The offsets are opposite corners of each region, the source is scaled to fit
+pub struct ImageBlit {
+    pub src_subresource: ImageSubresourceLayers,
+    pub src_offsets: [Offset3d; 2],
+    pub dst_subresource: ImageSubresourceLayers,
+    pub dst_offsets: [Offset3d; 2],
+}
+
+pub struct ImageResolve {
+    pub src_subresource: ImageSubresourceLayers,
+    pub src_offset: Offset3d,
+    pub dst_subresource: ImageSubresourceLayers,
+    pub dst_offset: Offset3d,
+    pub extent: Extent3d,
+}
+
 #[derive(Clone, Copy)]
 pub enum Filter {
     Nearest,
@@ -6392,9 +14210,32 @@ pub struct Sampler {
 
 impl Sampler {
     pub fn new(device: Rc<Device>, create_info: SamplerCreateInfo) -> Result<Self, Error> {
+        Self::new_chained(device, create_info, ptr::null())
+    }
+
+    //Only usable as an immutable sampler, see DescriptorSetLayoutCreateInfo
+    pub fn new_with_conversion(
+        device: Rc<Device>,
+        create_info: SamplerCreateInfo,
+        conversion: &SamplerYcbcrConversion,
+    ) -> Result<Self, Error> {
+        let conversion_info = ffi::SamplerYcbcrConversionInfo {
+            structure_type: ffi::StructureType::SamplerYcbcrConversionInfo,
+            p_next: ptr::null(),
+            conversion: conversion.handle,
+        };
+
+        Self::new_chained(device, create_info, &conversion_info as *const _ as _)
+    }
+
+    fn new_chained(
+        device: Rc<Device>,
+        create_info: SamplerCreateInfo,
+        p_next: *const (),
+    ) -> Result<Self, Error> {
         let create_info = ffi::SamplerCreateInfo {
             structure_type: ffi::StructureType::SamplerCreateInfo,
-            p_next: ptr::null(),
+            p_next,
             flags: 0,
             mag_filter: create_info.mag_filter.into(),
             min_filter: create_info.min_filter.into(),
@@ -6428,23 +14269,127 @@ impl Sampler {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let sampler = Self { device, handle };
+                let sampler = Self {
+                    device: Device::track(device, "Sampler"),
+                    handle,
+                };
 
                 Ok(sampler)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
+            _ => Err(unexpected_result(result)),
         }
     }
 }
 
 impl Drop for Sampler {
     fn drop(&mut self) {
+        self.device.untrack("Sampler");
+
         unsafe { ffi::vkDestroySampler(self.device.handle, self.handle, ptr::null()) };
     }
 }
 
+#[derive(Clone, Copy)]
+pub enum SamplerYcbcrModelConversion {
+    RgbIdentity,
+    YcbcrIdentity,
+    Ycbcr709,
+    Ycbcr601,
+    Ycbcr2020,
+}
+
+#[derive(Clone, Copy)]
+pub enum SamplerYcbcrRange {
+    ItuFull,
+    ItuNarrow,
+}
+
+#[derive(Clone, Copy)]
+pub enum ChromaLocation {
+    CositedEven,
+    Midpoint,
+}
+
+pub struct SamplerYcbcrConversionCreateInfo {
+    pub format: Format,
+    pub ycbcr_model: SamplerYcbcrModelConversion,
+    pub ycbcr_range: SamplerYcbcrRange,
+    pub components: ComponentMapping,
+    pub x_chroma_offset: ChromaLocation,
+    pub y_chroma_offset: ChromaLocation,
+    pub chroma_filter: Filter,
+    pub force_explicit_reconstruction: bool,
+}
+
+//Converts multi-planar YCbCr to RGB while sampling, requires sampler_ycbcr_conversion_enabled
+pub struct SamplerYcbcrConversion {
+    device: Rc<Device>,
+    handle: ffi::SamplerYcbcrConversion,
+}
+
+impl SamplerYcbcrConversion {
+    pub fn new(
+        device: Rc<Device>,
+        create_info: SamplerYcbcrConversionCreateInfo,
+    ) -> Result<Self, Error> {
+        if !device.sampler_ycbcr_conversion {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let create_info = ffi::SamplerYcbcrConversionCreateInfo {
+            structure_type: ffi::StructureType::SamplerYcbcrConversionCreateInfo,
+            p_next: ptr::null(),
+            format: create_info.format.into(),
+            ycbcr_model: create_info.ycbcr_model.into(),
+            ycbcr_range: create_info.ycbcr_range.into(),
+            components: ffi_component_mapping(&create_info.components),
+            x_chroma_offset: create_info.x_chroma_offset.into(),
+            y_chroma_offset: create_info.y_chroma_offset.into(),
+            chroma_filter: create_info.chroma_filter.into(),
+            force_explicit_reconstruction: create_info.force_explicit_reconstruction as _,
+        };
+
+        let mut handle = MaybeUninit::<ffi::SamplerYcbcrConversion>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreateSamplerYcbcrConversion(
+                device.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                let conversion = Self {
+                    device: Device::track(device, "SamplerYcbcrConversion"),
+                    handle,
+                };
+
+                Ok(conversion)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => Err(unexpected_result(result)),
+        }
+    }
+}
+
+impl Drop for SamplerYcbcrConversion {
+    fn drop(&mut self) {
+        self.device.untrack("SamplerYcbcrConversion");
+
+        unsafe {
+            ffi::vkDestroySamplerYcbcrConversion(self.device.handle, self.handle, ptr::null())
+        };
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum CompareOp {
     Never,
@@ -6469,6 +14414,32 @@ pub enum StencilOp {
     DecrementAndWrap,
 }
 
+#[derive(Clone, Copy)]
+pub struct StencilOpState {
+    pub fail_op: StencilOp,
+    pub pass_op: StencilOp,
+    pub depth_fail_op: StencilOp,
+    pub compare_op: CompareOp,
+    pub compare_mask: u32,
+    pub write_mask: u32,
+    pub reference: u32,
+}
+
+//Passes everything and keeps the stencil untouched
+impl Default for StencilOpState {
+    fn default() -> Self {
+        Self {
+            fail_op: StencilOp::Keep,
+            pass_op: StencilOp::Keep,
+            depth_fail_op: StencilOp::Keep,
+            compare_op: CompareOp::Always,
+            compare_mask: 0xff,
+            write_mask: 0xff,
+            reference: 0,
+        }
+    }
+}
+
 pub struct ImageMemoryBarrier<'a> {
     pub src_access_mask: u32,
     pub dst_access_mask: u32,
@@ -6494,3 +14465,296 @@ pub struct BufferMemoryBarrier<'a> {
     pub offset: u64,
     pub size: u64,
 }
+
+//Stage and access masks of synchronization2 are 64 bit, the legacy flags keep their values
+pub struct MemoryBarrier2 {
+    pub src_stage_mask: PipelineStageFlags2,
+    pub src_access_mask: AccessFlags2,
+    pub dst_stage_mask: PipelineStageFlags2,
+    pub dst_access_mask: AccessFlags2,
+}
+
+pub struct BufferMemoryBarrier2<'a> {
+    pub src_stage_mask: PipelineStageFlags2,
+    pub src_access_mask: AccessFlags2,
+    pub dst_stage_mask: PipelineStageFlags2,
+    pub dst_access_mask: AccessFlags2,
+    pub src_queue_family_index: u32,
+    pub dst_queue_family_index: u32,
+    pub buffer: &'a Buffer,
+    pub offset: u64,
+    pub size: u64,
+}
+
+pub struct ImageMemoryBarrier2<'a> {
+    pub src_stage_mask: PipelineStageFlags2,
+    pub src_access_mask: AccessFlags2,
+    pub dst_stage_mask: PipelineStageFlags2,
+    pub dst_access_mask: AccessFlags2,
+    pub old_layout: ImageLayout,
+    pub new_layout: ImageLayout,
+    pub src_queue_family_index: u32,
+    pub dst_queue_family_index: u32,
+    pub image: &'a Image,
+    pub subresource_range: ImageSubresourceRange,
+}
+
+pub struct DependencyInfo<'a> {
+    pub dependency_flags: u32,
+    pub memory_barriers: &'a [MemoryBarrier2],
+    pub buffer_memory_barriers: &'a [BufferMemoryBarrier2<'a>],
+    pub image_memory_barriers: &'a [ImageMemoryBarrier2<'a>],
+}
+
+//value is only read for timeline semaphores
+pub struct SemaphoreSubmitInfo<'a> {
+    pub semaphore: &'a Semaphore,
+    pub value: u64,
+    pub stage_mask: PipelineStageFlags2,
+}
+
+pub struct SubmitInfo2<'a> {
+    pub wait_semaphore_infos: &'a [SemaphoreSubmitInfo<'a>],
+    pub command_buffers: &'a [&'a CommandBuffer],
+    pub signal_semaphore_infos: &'a [SemaphoreSubmitInfo<'a>],
+    pub protected: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX: u32 = 128;
+
+    fn range(stage_flags: u32, offset: u32, size: u32) -> PushConstantRange {
+        PushConstantRange {
+            stage_flags,
+            offset,
+            size,
+        }
+    }
+
+    #[test]
+    fn push_constant_ranges_accept_disjoint_stages() {
+        let ranges = [
+            range(SHADER_STAGE_VERTEX, 0, 64),
+            range(SHADER_STAGE_FRAGMENT, 64, 64),
+        ];
+
+        assert!(validate_push_constant_ranges(&ranges, MAX).is_ok());
+    }
+
+    #[test]
+    fn push_constant_ranges_reject_overlapping_stages() {
+        let ranges = [
+            range(SHADER_STAGE_VERTEX | SHADER_STAGE_FRAGMENT, 0, 16),
+            range(SHADER_STAGE_FRAGMENT, 16, 16),
+        ];
+
+        assert!(matches!(
+            validate_push_constant_ranges(&ranges, MAX),
+            Err(Error::PushConstantStagesOverlap {
+                stage_flags: SHADER_STAGE_FRAGMENT
+            })
+        ));
+    }
+
+    #[test]
+    fn push_constant_ranges_reject_overflow() {
+        for range in [
+            range(SHADER_STAGE_VERTEX, 0, MAX + 4),
+            range(SHADER_STAGE_VERTEX, MAX, 4),
+            range(SHADER_STAGE_VERTEX, 4, u32::MAX - 3),
+        ] {
+            assert!(matches!(
+                validate_push_constant_ranges(&[range], MAX),
+                Err(Error::PushConstantRangeExceedsLimit { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn push_constant_ranges_reject_misalignment() {
+        for range in [
+            range(SHADER_STAGE_VERTEX, 2, 16),
+            range(SHADER_STAGE_VERTEX, 0, 6),
+            range(SHADER_STAGE_VERTEX, 0, 0),
+        ] {
+            assert!(matches!(
+                validate_push_constant_ranges(&[range], MAX),
+                Err(Error::PushConstantRangeMisaligned { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn push_constant_ranges_reject_empty_stage_flags() {
+        assert!(matches!(
+            validate_push_constant_ranges(&[range(0, 0, 16)], MAX),
+            Err(Error::PushConstantRangeWithoutStages {
+                offset: 0,
+                size: 16
+            })
+        ));
+    }
+
+    #[test]
+    fn push_constants_accept_covered_bytes() {
+        let ranges = [
+            range(SHADER_STAGE_VERTEX, 0, 16),
+            range(SHADER_STAGE_FRAGMENT, 16, 16),
+        ];
+
+        assert!(matches!(
+            validate_push_constants(&ranges, SHADER_STAGE_FRAGMENT, 16, &[0; 16]),
+            Ok(16)
+        ));
+    }
+
+    #[test]
+    fn push_constants_reject_overflow() {
+        let ranges = [range(SHADER_STAGE_VERTEX, 0, 16)];
+
+        assert!(matches!(
+            validate_push_constants(&ranges, SHADER_STAGE_VERTEX, u32::MAX - 3, &[0; 8]),
+            Err(Error::PushConstantsOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn push_constants_reject_uncovered_bytes() {
+        let ranges = [range(SHADER_STAGE_VERTEX, 0, 16)];
+
+        assert!(matches!(
+            validate_push_constants(&ranges, SHADER_STAGE_VERTEX, 8, &[0; 16]),
+            Err(Error::PushConstantsOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn push_constants_reject_stages_missing_from_range() {
+        let ranges = [range(SHADER_STAGE_VERTEX | SHADER_STAGE_FRAGMENT, 0, 16)];
+
+        assert!(matches!(
+            validate_push_constants(&ranges, SHADER_STAGE_VERTEX, 0, &[0; 16]),
+            Err(Error::PushConstantsOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn push_constants_reject_misalignment() {
+        let ranges = [range(SHADER_STAGE_VERTEX, 0, 16)];
+
+        for (offset, data) in [(2, &[0; 4][..]), (0, &[0; 6][..]), (0, &[][..])] {
+            assert!(matches!(
+                validate_push_constants(&ranges, SHADER_STAGE_VERTEX, offset, data),
+                Err(Error::PushConstantsOutOfRange { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn push_constants_reject_empty_stage_flags() {
+        let ranges = [range(SHADER_STAGE_VERTEX, 0, 16)];
+
+        assert!(matches!(
+            validate_push_constants(&ranges, 0, 0, &[0; 16]),
+            Err(Error::PushConstantsOutOfRange { .. })
+        ));
+    }
+
+    fn scratch(arena: &RefCell<Arena>) -> Scratch<'_> {
+        Scratch {
+            scratch: arena,
+            arena: Some(arena.borrow()),
+        }
+    }
+
+    #[test]
+    fn scratch_resets_arena_on_drop() {
+        let arena = RefCell::new(Arena::new());
+
+        let first = scratch(&arena).alloc_from_iter([1u32, 2]).as_ptr() as usize;
+        let capacity = arena.borrow().capacity();
+
+        let again = scratch(&arena).alloc_from_iter([3u32, 4]).as_ptr() as usize;
+
+        assert_eq!(first, again);
+        assert_eq!(arena.borrow().capacity(), capacity);
+    }
+
+    #[test]
+    fn nested_scratch_keeps_outer_allocations() {
+        let arena = RefCell::new(Arena::new());
+
+        let outer = scratch(&arena);
+        let values = outer.alloc_from_iter([1u32, 2]);
+
+        //dropped while the outer scratch still borrows the arena
+        drop(scratch(&arena));
+
+        let after = outer.alloc_from_iter([3u32, 4]);
+
+        assert_eq!(values, [1, 2]);
+        assert_eq!(after, [3, 4]);
+        assert_ne!(values.as_ptr(), after.as_ptr());
+
+        let first = values.as_ptr() as usize;
+
+        drop(outer);
+
+        let again = scratch(&arena).alloc_from_iter([5u32]).as_ptr() as usize;
+
+        assert_eq!(first, again);
+    }
+
+    #[test]
+    fn require_exact_ignores_patch() {
+        let requested = Version::from((1, 3, 250));
+        let supported = Version::from((1, 3, 200));
+
+        assert_eq!(
+            ApiVersionPolicy::RequireExact
+                .apply(requested, supported)
+                .unwrap(),
+            requested
+        );
+    }
+
+    #[test]
+    fn require_exact_rejects_newer_minor() {
+        let requested = Version::from((1, 3, 0));
+        let supported = Version::from((1, 2, 250));
+
+        assert!(matches!(
+            ApiVersionPolicy::RequireExact.apply(requested, supported),
+            Err(Error::ApiVersionNotSupported { .. })
+        ));
+    }
+
+    #[test]
+    fn require_exact_accepts_older_version() {
+        let requested = Version::from((1, 1, 0));
+        let supported = Version::from((1, 3, 250));
+
+        assert_eq!(
+            ApiVersionPolicy::RequireExact
+                .apply(requested, supported)
+                .unwrap(),
+            requested
+        );
+    }
+
+    #[test]
+    fn clamp_to_supported_uses_lower_version() {
+        let requested = Version::from((1, 3, 0));
+        let supported = Version::from((1, 2, 250));
+
+        assert_eq!(
+            ApiVersionPolicy::ClampToSupported
+                .apply(requested, supported)
+                .unwrap(),
+            supported
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//Lighting shader helper for cascaded shadow maps.
+//Paste into the lighting shader and bind the Cascades buffer
+//and the shadow depth array at the bindings below.
+
+#define MAX_CASCADES 4
+#define CASCADE_BIAS 5e-4
+
+layout(binding = CASCADE_BINDING) uniform Cascades {
+	mat4 matrices[MAX_CASCADES];
+	vec4 splits;
+} cascades;
+
+layout(binding = CASCADE_BINDING + 1) uniform sampler2DArray shadow_map;
+
+//view_depth is the positive distance along the camera forward axis
+uint cascade_index(float view_depth) {
+	for (uint i = 0; i < MAX_CASCADES - 1; i++) {
+		if (view_depth < cascades.splits[i]) {
+			return i;
+		}
+	}
+	return MAX_CASCADES - 1;
+}
+
+//returns 1.0 when lit and 0.0 when fully shadowed
+float cascade_shadow(vec3 world_position, float view_depth) {
+	uint index = cascade_index(view_depth);
+
+	vec4 light_clip = cascades.matrices[index] * vec4(world_position, 1.0);
+	vec3 light_ndc = light_clip.xyz / light_clip.w;
+	vec2 uv = light_ndc.xy * 0.5 + 0.5;
+
+	if (any(lessThan(uv, vec2(0))) || any(greaterThan(uv, vec2(1))) || light_ndc.z > 1.0) {
+		return 1.0;
+	}
+
+	vec2 texel = 1.0 / vec2(textureSize(shadow_map, 0).xy);
+
+	float lit = 0.0;
+	for (int x = -1; x <= 1; x++) {
+		for (int y = -1; y <= 1; y++) {
+			float depth = texture(shadow_map, vec3(uv + vec2(x, y) * texel, float(index))).r;
+			lit += light_ndc.z - CASCADE_BIAS > depth ? 0.0 : 1.0;
+		}
+	}
+
+	return lit / 9.0;
+}
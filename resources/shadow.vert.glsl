@@ -0,0 +1,35 @@
+#version 450
+
+#extension GL_ARB_shader_viewport_layer_array : require
+
+#define CHUNK_SIZE 8
+#define MAX_CASCADES 4
+
+layout(binding = 0) uniform Cascades {
+	mat4 matrices[MAX_CASCADES];
+	vec4 splits;
+} cascades;
+
+layout(binding = 1) uniform Object {
+	mat4 model;
+} object;
+
+layout(push_constant) uniform Cascade {
+	uint index;
+} cascade;
+
+layout(location = 0) in vec3 in_position;
+layout(location = 1) in vec3 in_normal;
+layout(location = 2) in vec3 in_uvw;
+layout(location = 3) in uvec3 in_chunk_position;
+
+void main() {
+	mat4 true_model = object.model;
+
+	true_model[3].xyz += vec3(in_chunk_position) * CHUNK_SIZE;
+
+	vec3 position = in_position * CHUNK_SIZE / 2;
+
+	gl_Position = cascades.matrices[cascade.index] * true_model * vec4(position, 1.0);
+	gl_Layer = int(cascade.index);
+}